@@ -0,0 +1,71 @@
+//! Time from place_order to the first order_status of the order, against MockTws
+//!
+//! Run with cargo bench --bench place_order.  The time measured is the first_status latency
+//! EClient records for each order, so it covers encoding, the socket round trip to MockTws and
+//! decoding the status.
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use twsapi::core::client::EClient;
+use twsapi::core::contract::Contract;
+use twsapi::core::messages::OutgoingMessageIds;
+use twsapi::core::order::Action;
+use twsapi::core::order_templates;
+use twsapi::core::server_versions::MAX_CLIENT_VER;
+use twsapi::core::testing::MockTws;
+use twsapi::examples::defaults::DefaultWrapper;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn bench_first_status(c: &mut Criterion) {
+    let mock = MockTws::start(MAX_CLIENT_VER).expect("MockTws failed to start");
+    // every order is submitted at once
+    mock.respond_with(
+        OutgoingMessageIds::PlaceOrder,
+        Box::new(|request| {
+            let fields = ["3", &request[1], "Submitted", "0", "1", "0", "0", "0"];
+            vec![fields
+                .iter()
+                .chain(["0", "0", "", "0"].iter())
+                .map(|field| field.to_string())
+                .collect()]
+        }),
+    );
+    let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+    client
+        .connect("127.0.0.1", mock.port(), 0)
+        .expect("connect to MockTws failed");
+
+    let contract = Contract::stock("AAPL");
+    let order = order_templates::limit(Action::Buy, 1.0, 150.0).expect("invalid limit order");
+    let mut order_id = 0;
+    c.bench_function("place_order_first_status", |b| {
+        b.iter_custom(|iterations| {
+            let mut total = Duration::ZERO;
+            for _ in 0..iterations {
+                order_id += 1;
+                client
+                    .place_order(order_id, &contract, &order)
+                    .expect("place_order failed");
+                let deadline = Instant::now() + TIMEOUT;
+                let first_status = loop {
+                    let latency = client.order_latency(order_id);
+                    match latency.and_then(|latency| latency.first_status) {
+                        Some(first_status) => break first_status,
+                        None if Instant::now() < deadline => thread::yield_now(),
+                        None => panic!("no order_status for order {}", order_id),
+                    }
+                };
+                total += first_status;
+            }
+            total
+        })
+    });
+    client.disconnect().expect("disconnect failed");
+}
+
+criterion_group!(benches, bench_first_status);
+criterion_main!(benches);
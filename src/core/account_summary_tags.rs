@@ -1,9 +1,14 @@
 //! Account summary tags
+//!
+//! AccountSummaryTagSet builds the tags argument of EClient::req_account_summary, and
+//! Wrapper::account_summary reports the tag of every value as an AccountSummaryTag.
+use std::borrow::Cow;
 use std::fmt::{Display, Error, Formatter};
 
-use crate::core::account_summary_tags::AccountSummaryTags::*;
+use serde::{Deserialize, Serialize};
 
 //==================================================================================================
+/// A tag of the account summary
 /// AccountType — Identifies the IB account structure
 /// NetLiquidation — The basis for determining the price of the assets in your account. Total cash value + stock value + options value + bond value
 /// TotalCashValue — Total cash balance recognized at the time of trade + futures PNL
@@ -33,11 +38,14 @@ use crate::core::account_summary_tags::AccountSummaryTags::*;
 /// HighestSeverity — A measure of how close the account is to liquidation
 /// DayTradesRemaining — The Number of Open/Close trades a user could put on before Pattern Day Trading is detected. A value of "-1" means that the user can put on unlimited day trades.
 /// Leverage — GrossPositionValue / NetLiquidation
-/// $LEDGER — Single flag to relay all cash balance tags*, only in base currency.
-/// $LEDGER:CURRENCY — Single flag to relay all cash balance tags*, only in the specified currency.
-/// $LEDGER:ALL — Single flag to relay all cash balance tags* in all currencies.
-
-pub enum AccountSummaryTags {
+/// Ledger — $LEDGER: Single flag to relay all cash balance tags*, only in base currency.
+/// LedgerCurrency — $LEDGER:CURRENCY: Single flag to relay all cash balance tags*, only in the specified currency.
+/// LedgerAll — $LEDGER:ALL: Single flag to relay all cash balance tags* in all currencies.
+///
+/// *The cash balance tags, from Currency to IssuerOptionValue, are only reported for the
+/// $LEDGER flags.  Other holds the tags this list doesn't know.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AccountSummaryTag {
     AccountType,
     NetLiquidation,
     TotalCashValue,
@@ -47,8 +55,8 @@ pub enum AccountSummaryTags {
     EquityWithLoanValue,
     PreviousEquityWithLoanValue,
     GrossPositionValue,
-    ReqTEquity,
-    ReqTMargin,
+    RegTEquity,
+    RegTMargin,
     SMA,
     InitMarginReq,
     MaintMarginReq,
@@ -68,86 +76,246 @@ pub enum AccountSummaryTags {
     DayTradesRemaining,
     Leverage,
     Ledger,
-    LedgerCurrency,
+    LedgerCurrency(String),
     LedgerAll,
-    AllTags,
+    Currency,
+    RealCurrency,
+    CashBalance,
+    TotalCashBalance,
+    NetLiquidationByCurrency,
+    StockMarketValue,
+    OptionMarketValue,
+    FutureOptionValue,
+    FuturesPnl,
+    UnrealizedPnl,
+    RealizedPnl,
+    ExchangeRate,
+    NetDividend,
+    FundValue,
+    MutualFundValue,
+    MoneyMarketFundValue,
+    CorporateBondValue,
+    TBondValue,
+    TBillValue,
+    WarrantValue,
+    IssuerOptionValue,
+    Other(String),
 }
 
-impl AccountSummaryTags {
-    fn display(&self) -> &str {
-        match self {
-            AccountType => "AccountType",
-            NetLiquidation => "NetLiquidation",
-            TotalCashValue => "TotalCashValue",
-            SettledCash => "SettledCash",
-            AccruedCash => "AccruedCash",
-            BuyingPower => "BuyingPower",
-            EquityWithLoanValue => "EquityWithLoanValue",
-            PreviousEquityWithLoanValue => "PreviousEquityWithLoanValue",
-            GrossPositionValue => "GrossPositionValue",
-            ReqTEquity => "ReqTEquity",
-            ReqTMargin => "ReqTMargin",
-            SMA => "SMA",
-            InitMarginReq => "InitMarginReq",
-            MaintMarginReq => "MaintMarginReq",
-            AvailableFunds => "AvailableFunds",
-            ExcessLiquidity => "ExcessLiquidity",
-            Cushion => "Cushion",
-            FullInitMarginReq => "FullInitMarginReq",
-            FullMaintMarginReq => "FullMaintMarginReq",
-            FullAvailableFunds => "FullAvailableFunds",
-            FullExcessLiquidity => "FullExcessLiquidity",
-            LookAheadNextChange => "LookAheadNextChange",
-            LookAheadInitMarginReq => "LookAheadInitMarginReq",
-            LookAheadMaintMarginReq => "LookAheadMaintMarginReq",
-            LookAheadAvailableFunds => "LookAheadAvailableFunds",
-            LookAheadExcessLiquidity => "LookAheadExcessLiquidity",
-            HighestSeverity => "HighestSeverity",
-            DayTradesRemaining => "DayTradesRemaining",
-            Leverage => "Leverage",
-            Ledger => "$LEDGER",
-            LedgerCurrency => "$LEDGER:CURRENCY",
-            LedgerAll => "$LEDGER:ALL",
-            AllTags => {
-                "AccountType,
-            NetLiquidation,
-            TotalCashValue,
-            SettledCash,
-            AccruedCash,
-            BuyingPower,
-            EquityWithLoanValue,
-            PreviousEquityWithLoanValue,
-            GrossPositionValue,
-            ReqTEquity,
-            ReqTMargin,
-            SMA,
-            InitMarginReq,
-            MaintMarginReq,
-            AvailableFunds,
-            ExcessLiquidity,
-            Cushion,Cushion,
-            FullInitMarginReq,
-            FullMaintMarginReq,
-            FullAvailableFunds,
-            FullExcessLiquidity,
-            LookAheadNextChange,
-            LookAheadInitMarginReq,
-            LookAheadMaintMarginReq,
-            LookAheadAvailableFunds,
-            LookAheadExcessLiquidity,
-            HighestSeverity,
-            DayTradesRemaining,
-            Leverage,
-            $LEDGER,
-            $LEDGER:CURRENCY,
-            $LEDGER:ALL"
+impl AccountSummaryTag {
+    /// The tags req_account_summary can request, apart from the $LEDGER flags
+    pub const ALL: [AccountSummaryTag; 29] = [
+        AccountSummaryTag::AccountType,
+        AccountSummaryTag::NetLiquidation,
+        AccountSummaryTag::TotalCashValue,
+        AccountSummaryTag::SettledCash,
+        AccountSummaryTag::AccruedCash,
+        AccountSummaryTag::BuyingPower,
+        AccountSummaryTag::EquityWithLoanValue,
+        AccountSummaryTag::PreviousEquityWithLoanValue,
+        AccountSummaryTag::GrossPositionValue,
+        AccountSummaryTag::RegTEquity,
+        AccountSummaryTag::RegTMargin,
+        AccountSummaryTag::SMA,
+        AccountSummaryTag::InitMarginReq,
+        AccountSummaryTag::MaintMarginReq,
+        AccountSummaryTag::AvailableFunds,
+        AccountSummaryTag::ExcessLiquidity,
+        AccountSummaryTag::Cushion,
+        AccountSummaryTag::FullInitMarginReq,
+        AccountSummaryTag::FullMaintMarginReq,
+        AccountSummaryTag::FullAvailableFunds,
+        AccountSummaryTag::FullExcessLiquidity,
+        AccountSummaryTag::LookAheadNextChange,
+        AccountSummaryTag::LookAheadInitMarginReq,
+        AccountSummaryTag::LookAheadMaintMarginReq,
+        AccountSummaryTag::LookAheadAvailableFunds,
+        AccountSummaryTag::LookAheadExcessLiquidity,
+        AccountSummaryTag::HighestSeverity,
+        AccountSummaryTag::DayTradesRemaining,
+        AccountSummaryTag::Leverage,
+    ];
+
+    //----------------------------------------------------------------------------------------------
+    /// The tag of a Wrapper::account_summary value
+    pub fn from_tag(tag: &str) -> Self {
+        match tag {
+            "AccountType" => AccountSummaryTag::AccountType,
+            "NetLiquidation" => AccountSummaryTag::NetLiquidation,
+            "TotalCashValue" => AccountSummaryTag::TotalCashValue,
+            "SettledCash" => AccountSummaryTag::SettledCash,
+            "AccruedCash" => AccountSummaryTag::AccruedCash,
+            "BuyingPower" => AccountSummaryTag::BuyingPower,
+            "EquityWithLoanValue" => AccountSummaryTag::EquityWithLoanValue,
+            "PreviousEquityWithLoanValue" => AccountSummaryTag::PreviousEquityWithLoanValue,
+            "GrossPositionValue" => AccountSummaryTag::GrossPositionValue,
+            "RegTEquity" => AccountSummaryTag::RegTEquity,
+            "RegTMargin" => AccountSummaryTag::RegTMargin,
+            "SMA" => AccountSummaryTag::SMA,
+            "InitMarginReq" => AccountSummaryTag::InitMarginReq,
+            "MaintMarginReq" => AccountSummaryTag::MaintMarginReq,
+            "AvailableFunds" => AccountSummaryTag::AvailableFunds,
+            "ExcessLiquidity" => AccountSummaryTag::ExcessLiquidity,
+            "Cushion" => AccountSummaryTag::Cushion,
+            "FullInitMarginReq" => AccountSummaryTag::FullInitMarginReq,
+            "FullMaintMarginReq" => AccountSummaryTag::FullMaintMarginReq,
+            "FullAvailableFunds" => AccountSummaryTag::FullAvailableFunds,
+            "FullExcessLiquidity" => AccountSummaryTag::FullExcessLiquidity,
+            "LookAheadNextChange" => AccountSummaryTag::LookAheadNextChange,
+            "LookAheadInitMarginReq" => AccountSummaryTag::LookAheadInitMarginReq,
+            "LookAheadMaintMarginReq" => AccountSummaryTag::LookAheadMaintMarginReq,
+            "LookAheadAvailableFunds" => AccountSummaryTag::LookAheadAvailableFunds,
+            "LookAheadExcessLiquidity" => AccountSummaryTag::LookAheadExcessLiquidity,
+            "HighestSeverity" => AccountSummaryTag::HighestSeverity,
+            "DayTradesRemaining" => AccountSummaryTag::DayTradesRemaining,
+            "Leverage" => AccountSummaryTag::Leverage,
+            "$LEDGER" => AccountSummaryTag::Ledger,
+            "$LEDGER:ALL" => AccountSummaryTag::LedgerAll,
+            "Currency" => AccountSummaryTag::Currency,
+            "RealCurrency" => AccountSummaryTag::RealCurrency,
+            "CashBalance" => AccountSummaryTag::CashBalance,
+            "TotalCashBalance" => AccountSummaryTag::TotalCashBalance,
+            "NetLiquidationByCurrency" => AccountSummaryTag::NetLiquidationByCurrency,
+            "StockMarketValue" => AccountSummaryTag::StockMarketValue,
+            "OptionMarketValue" => AccountSummaryTag::OptionMarketValue,
+            "FutureOptionValue" => AccountSummaryTag::FutureOptionValue,
+            "FuturesPNL" => AccountSummaryTag::FuturesPnl,
+            "UnrealizedPnL" => AccountSummaryTag::UnrealizedPnl,
+            "RealizedPnL" => AccountSummaryTag::RealizedPnl,
+            "ExchangeRate" => AccountSummaryTag::ExchangeRate,
+            "NetDividend" => AccountSummaryTag::NetDividend,
+            "FundValue" => AccountSummaryTag::FundValue,
+            "MutualFundValue" => AccountSummaryTag::MutualFundValue,
+            "MoneyMarketFundValue" => AccountSummaryTag::MoneyMarketFundValue,
+            "CorporateBondValue" => AccountSummaryTag::CorporateBondValue,
+            "TBondValue" => AccountSummaryTag::TBondValue,
+            "TBillValue" => AccountSummaryTag::TBillValue,
+            "WarrantValue" => AccountSummaryTag::WarrantValue,
+            "IssuerOptionValue" => AccountSummaryTag::IssuerOptionValue,
+            _ => match tag.strip_prefix("$LEDGER:") {
+                Some(currency) => AccountSummaryTag::LedgerCurrency(currency.to_string()),
+                None => AccountSummaryTag::Other(tag.to_string()),
+            },
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The tag as TWS spells it, e.g. "$LEDGER:USD" for LedgerCurrency("USD")
+    pub fn as_str(&self) -> Cow<'_, str> {
+        let tag = match self {
+            AccountSummaryTag::AccountType => "AccountType",
+            AccountSummaryTag::NetLiquidation => "NetLiquidation",
+            AccountSummaryTag::TotalCashValue => "TotalCashValue",
+            AccountSummaryTag::SettledCash => "SettledCash",
+            AccountSummaryTag::AccruedCash => "AccruedCash",
+            AccountSummaryTag::BuyingPower => "BuyingPower",
+            AccountSummaryTag::EquityWithLoanValue => "EquityWithLoanValue",
+            AccountSummaryTag::PreviousEquityWithLoanValue => "PreviousEquityWithLoanValue",
+            AccountSummaryTag::GrossPositionValue => "GrossPositionValue",
+            AccountSummaryTag::RegTEquity => "RegTEquity",
+            AccountSummaryTag::RegTMargin => "RegTMargin",
+            AccountSummaryTag::SMA => "SMA",
+            AccountSummaryTag::InitMarginReq => "InitMarginReq",
+            AccountSummaryTag::MaintMarginReq => "MaintMarginReq",
+            AccountSummaryTag::AvailableFunds => "AvailableFunds",
+            AccountSummaryTag::ExcessLiquidity => "ExcessLiquidity",
+            AccountSummaryTag::Cushion => "Cushion",
+            AccountSummaryTag::FullInitMarginReq => "FullInitMarginReq",
+            AccountSummaryTag::FullMaintMarginReq => "FullMaintMarginReq",
+            AccountSummaryTag::FullAvailableFunds => "FullAvailableFunds",
+            AccountSummaryTag::FullExcessLiquidity => "FullExcessLiquidity",
+            AccountSummaryTag::LookAheadNextChange => "LookAheadNextChange",
+            AccountSummaryTag::LookAheadInitMarginReq => "LookAheadInitMarginReq",
+            AccountSummaryTag::LookAheadMaintMarginReq => "LookAheadMaintMarginReq",
+            AccountSummaryTag::LookAheadAvailableFunds => "LookAheadAvailableFunds",
+            AccountSummaryTag::LookAheadExcessLiquidity => "LookAheadExcessLiquidity",
+            AccountSummaryTag::HighestSeverity => "HighestSeverity",
+            AccountSummaryTag::DayTradesRemaining => "DayTradesRemaining",
+            AccountSummaryTag::Leverage => "Leverage",
+            AccountSummaryTag::Ledger => "$LEDGER",
+            AccountSummaryTag::LedgerCurrency(currency) => {
+                return Cow::Owned(format!("$LEDGER:{}", currency))
             }
+            AccountSummaryTag::LedgerAll => "$LEDGER:ALL",
+            AccountSummaryTag::Currency => "Currency",
+            AccountSummaryTag::RealCurrency => "RealCurrency",
+            AccountSummaryTag::CashBalance => "CashBalance",
+            AccountSummaryTag::TotalCashBalance => "TotalCashBalance",
+            AccountSummaryTag::NetLiquidationByCurrency => "NetLiquidationByCurrency",
+            AccountSummaryTag::StockMarketValue => "StockMarketValue",
+            AccountSummaryTag::OptionMarketValue => "OptionMarketValue",
+            AccountSummaryTag::FutureOptionValue => "FutureOptionValue",
+            AccountSummaryTag::FuturesPnl => "FuturesPNL",
+            AccountSummaryTag::UnrealizedPnl => "UnrealizedPnL",
+            AccountSummaryTag::RealizedPnl => "RealizedPnL",
+            AccountSummaryTag::ExchangeRate => "ExchangeRate",
+            AccountSummaryTag::NetDividend => "NetDividend",
+            AccountSummaryTag::FundValue => "FundValue",
+            AccountSummaryTag::MutualFundValue => "MutualFundValue",
+            AccountSummaryTag::MoneyMarketFundValue => "MoneyMarketFundValue",
+            AccountSummaryTag::CorporateBondValue => "CorporateBondValue",
+            AccountSummaryTag::TBondValue => "TBondValue",
+            AccountSummaryTag::TBillValue => "TBillValue",
+            AccountSummaryTag::WarrantValue => "WarrantValue",
+            AccountSummaryTag::IssuerOptionValue => "IssuerOptionValue",
+            AccountSummaryTag::Other(tag) => tag,
+        };
+        Cow::Borrowed(tag)
+    }
+}
+
+impl Display for AccountSummaryTag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+//==================================================================================================
+/// The tags of a req_account_summary request, each once, in the order added
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct AccountSummaryTagSet {
+    tags: Vec<AccountSummaryTag>,
+}
+
+impl AccountSummaryTagSet {
+    pub fn new() -> Self {
+        AccountSummaryTagSet::default()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Every tag of AccountSummaryTag::ALL
+    pub fn all() -> Self {
+        AccountSummaryTagSet::new().with_all(AccountSummaryTag::ALL.iter().cloned())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn with(mut self, tag: AccountSummaryTag) -> Self {
+        if !self.tags.contains(&tag) {
+            self.tags.push(tag);
         }
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn with_all<I: IntoIterator<Item = AccountSummaryTag>>(self, tags: I) -> Self {
+        tags.into_iter().fold(self, |set, tag| set.with(tag))
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn tags(&self) -> &[AccountSummaryTag] {
+        &self.tags
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
     }
 }
 
-impl Display for AccountSummaryTags {
+impl Display for AccountSummaryTagSet {
+    /// The comma separated list req_account_summary sends
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
-        write!(f, "{}", self.display())
+        let tags: Vec<String> = self.tags.iter().map(|tag| tag.to_string()).collect();
+        write!(f, "{}", tags.join(","))
     }
 }
@@ -0,0 +1,160 @@
+//! Filters the account value stream down to changes that matter to the consumer
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::accounts::Accounts;
+use crate::core::currency::Currency;
+
+//==================================================================================================
+/// How much a numeric account value must move before a change is reported.
+/// A change is reported when it exceeds both limits; a limit of 0.0 does not hold back any
+/// change, so set one of them to 0.0 to report on the other alone.
+/// absolute - minimum absolute difference from the last reported value
+/// relative - minimum difference relative to the last reported value (0.01 = 1%).  Any change
+///   from a last reported value of 0 exceeds it, leaving the absolute limit to decide.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+pub struct ChangeThreshold {
+    pub absolute: f64,
+    pub relative: f64,
+}
+
+impl ChangeThreshold {
+    pub fn new(absolute: f64, relative: f64) -> Self {
+        ChangeThreshold { absolute, relative }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn is_exceeded(&self, previous: f64, current: f64) -> bool {
+        let diff = (current - previous).abs();
+        if diff == 0.0 {
+            return false;
+        }
+        let relative_diff = if previous == 0.0 {
+            f64::INFINITY
+        } else {
+            diff / previous.abs()
+        };
+        diff > self.absolute && relative_diff > self.relative
+    }
+}
+
+//==================================================================================================
+/// A reported change of an account value.
+/// previous is None the first time a value is seen.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AccountValueChange {
+    pub account_name: String,
+    pub key: String,
+    pub currency: Currency,
+    pub previous: Option<String>,
+    pub value: String,
+}
+
+impl AccountValueChange {
+    /// The change as Display shows it, with the display name of the account, e.g. its alias
+    pub fn display_with(&self, accounts: &Accounts) -> String {
+        self.describe(&accounts.display_name(&self.account_name))
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn describe(&self, account_name: &str) -> String {
+        format!(
+            "account_name: {}, key: {}, currency: {}, previous: {}, value: {}",
+            account_name,
+            self.key,
+            self.currency,
+            self.previous.as_deref().unwrap_or(""),
+            self.value
+        )
+    }
+}
+
+impl fmt::Display for AccountValueChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.describe(&self.account_name))
+    }
+}
+
+//==================================================================================================
+/// Feed this with the values received in Wrapper::update_account_value (or
+/// Wrapper::account_update_multi) and it returns only the changes exceeding the configured
+/// thresholds.  Values are compared with the last *reported* value, so slow drifts are
+/// reported once they add up.  Non-numeric values are reported whenever they change.
+#[derive(Clone, Debug, Default)]
+pub struct AccountValueDeltas {
+    default_threshold: ChangeThreshold,
+    thresholds: HashMap<String, ChangeThreshold>,
+    last_reported: HashMap<(String, String, String), String>,
+}
+
+impl AccountValueDeltas {
+    pub fn new(default_threshold: ChangeThreshold) -> Self {
+        AccountValueDeltas {
+            default_threshold,
+            thresholds: HashMap::new(),
+            last_reported: HashMap::new(),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Overrides the default threshold for one key, e.g. "UnrealizedPnL"
+    pub fn set_threshold(&mut self, key: &str, threshold: ChangeThreshold) {
+        self.thresholds.insert(key.to_string(), threshold);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Records a received account value and returns it as a change if it should be reported
+    ///
+    /// # Arguments
+    /// * key - the account value key, e.g. "NetLiquidation"
+    /// * val - the value as sent by TWS
+    /// * currency - the currency of the value
+    /// * account_name - the account the value belongs to
+    pub fn update(
+        &mut self,
+        key: &str,
+        val: &str,
+        currency: &str,
+        account_name: &str,
+    ) -> Option<AccountValueChange> {
+        let entry_key = (
+            account_name.to_string(),
+            key.to_string(),
+            currency.to_string(),
+        );
+        let previous = self.last_reported.get(&entry_key).cloned();
+
+        let report = match previous.as_ref() {
+            None => true,
+            Some(previous) => match (previous.parse::<f64>(), val.parse::<f64>()) {
+                (Ok(previous), Ok(current)) => self
+                    .thresholds
+                    .get(key)
+                    .unwrap_or(&self.default_threshold)
+                    .is_exceeded(previous, current),
+                _ => previous != val,
+            },
+        };
+
+        if !report {
+            return None;
+        }
+
+        self.last_reported.insert(entry_key, val.to_string());
+        Some(AccountValueChange {
+            account_name: account_name.to_string(),
+            key: key.to_string(),
+            currency: Currency::from(currency),
+            previous,
+            value: val.to_string(),
+        })
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Forgets all reported values, so the next update of every value is reported
+    pub fn reset(&mut self) {
+        self.last_reported.clear();
+    }
+}
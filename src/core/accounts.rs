@@ -0,0 +1,164 @@
+//! Managed accounts and their display names
+//!
+//! TWS identifies accounts by codes like U1234567.  Accounts keeps the accounts of the
+//! ManagedAccts message with the aliases of the FA alias configuration, so reports and logs can
+//! show the names operators gave the accounts.  EClient::accounts returns the accounts the
+//! Decoder recorded so far; aliases are recorded whenever TWS sends the alias configuration,
+//! e.g. in answer to EClient::request_fa(FaDataType::ALIASES).  EClient::managed_accounts
+//! returns the AccountIds of the ManagedAccts message of the current connection from them.
+//!
+//! The decoder and order logs name accounts by display_name, and Position::display_with and
+//! AccountValueChange::display_with show reports with them.
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::fa::FaAliases;
+
+//==================================================================================================
+/// The code of an account, e.g. U1234567, or DU1234567 for a paper trading account
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AccountId(pub String);
+
+impl AccountId {
+    pub fn new(code: &str) -> Self {
+        AccountId(code.trim().to_string())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Parses the comma-separated list of Wrapper::managed_accounts.  Empty codes are skipped.
+    pub fn parse_list(accounts_list: &str) -> Vec<AccountId> {
+        accounts_list
+            .split(',')
+            .filter(|account| !account.trim().is_empty())
+            .map(AccountId::new)
+            .collect()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for AccountId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<&str> for AccountId {
+    fn from(code: &str) -> Self {
+        AccountId::new(code)
+    }
+}
+
+impl PartialEq<str> for AccountId {
+    fn eq(&self, code: &str) -> bool {
+        self.0 == code
+    }
+}
+
+impl PartialEq<&str> for AccountId {
+    fn eq(&self, code: &&str) -> bool {
+        self.0 == *code
+    }
+}
+
+//==================================================================================================
+/// The managed accounts, and the alias of each account that has one
+/// accounts - the managed accounts of the current connection, None until TWS sent them
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Accounts {
+    accounts: Option<Vec<AccountId>>,
+    aliases: HashMap<String, String>,
+}
+
+impl Accounts {
+    pub fn new() -> Self {
+        Accounts::default()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Replaces the managed accounts with the comma-separated list of Wrapper::managed_accounts
+    pub fn set_managed_accounts(&mut self, accounts_list: &str) {
+        self.accounts = Some(AccountId::parse_list(accounts_list));
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Forgets the managed accounts, e.g. on connecting again.  The aliases are kept.
+    pub fn clear_managed_accounts(&mut self) {
+        self.accounts = None;
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The managed accounts, None until TWS sent them
+    pub fn managed_accounts(&self) -> Option<&[AccountId]> {
+        self.accounts.as_deref()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The managed accounts, empty until TWS sent them
+    pub fn accounts(&self) -> &[AccountId] {
+        self.managed_accounts().unwrap_or_default()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Replaces the aliases with those of an FA alias configuration.  Empty aliases are skipped.
+    pub fn set_aliases(&mut self, aliases: &FaAliases) {
+        self.aliases.clear();
+        for alias in aliases.aliases.iter() {
+            self.set_alias(&alias.account, &alias.alias);
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Sets the alias of an account, or removes it if alias is empty
+    pub fn set_alias(&mut self, account: &str, alias: &str) {
+        if alias.trim().is_empty() {
+            self.aliases.remove(account);
+        } else {
+            self.aliases
+                .insert(account.to_string(), alias.trim().to_string());
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn alias(&self, account: &str) -> Option<&str> {
+        self.aliases.get(account).map(|alias| alias.as_str())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The name to show for an account: its alias, or the account code without one
+    pub fn display_name(&self, account: &str) -> String {
+        self.alias(account).unwrap_or(account).to_string()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The account code for a code or an alias, e.g. one an operator typed
+    pub fn resolve<'a>(&'a self, name: &'a str) -> Option<&'a str> {
+        if self.accounts().iter().any(|account| account == name) || self.aliases.contains_key(name)
+        {
+            return Some(name);
+        }
+        self.aliases
+            .iter()
+            .find(|(_, alias)| alias.as_str() == name)
+            .map(|(account, _)| account.as_str())
+    }
+}
+
+impl fmt::Display for Accounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<String> = self
+            .accounts()
+            .iter()
+            .map(|account| match self.alias(account.as_str()) {
+                Some(alias) => format!("{} ({})", alias, account),
+                None => account.to_string(),
+            })
+            .collect();
+        write!(f, "accounts: {}", names.join(", "))
+    }
+}
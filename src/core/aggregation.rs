@@ -0,0 +1,284 @@
+//! Builds bars from streaming market data
+use bigdecimal::BigDecimal;
+use num_traits::{Signed, ToPrimitive};
+
+use crate::core::common::{BarData, RealTimeBar};
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+use crate::core::timestamps::timestamp_from_seconds;
+
+/// Length of the bars sent by req_real_time_bars, in seconds
+pub const REAL_TIME_BAR_SECONDS: i64 = 5;
+
+//==================================================================================================
+/// Bar being built: start time in epoch seconds plus running totals, and the times of the
+/// trades the open and close came from
+#[derive(Clone, Debug)]
+struct OpenBar {
+    start: i64,
+    bar: BarData,
+    turnover: f64,
+    first: i64,
+    last: i64,
+}
+
+impl OpenBar {
+    fn new(start: i64, open: f64) -> Self {
+        OpenBar {
+            start,
+            bar: BarData::new(
+                timestamp_from_seconds(start),
+                open,
+                open,
+                open,
+                open,
+                BigDecimal::default(),
+                0,
+                open,
+            ),
+            turnover: 0.0,
+            first: start,
+            last: start,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Adds a trade.  Trades arriving out of order only move the open if they are older than
+    /// the first trade, and the close if they are not older than the last.
+    fn add_trade(&mut self, time: i64, price: f64, size: BigDecimal, trades: i32) {
+        let first_trade = self.bar.bar_count == 0;
+        let bar = &mut self.bar;
+        if first_trade || time < self.first {
+            bar.open = price;
+            self.first = time;
+        }
+        if first_trade || time >= self.last {
+            bar.close = price;
+            self.last = time;
+        }
+        bar.high = bar.high.max(price);
+        bar.low = bar.low.min(price);
+        self.turnover += price * size.to_f64().unwrap_or(0.0);
+        bar.volume += size;
+        bar.bar_count += trades;
+        if bar.volume.is_positive() {
+            bar.average = self.turnover / bar.volume.to_f64().unwrap_or(0.0);
+        }
+    }
+}
+
+//==================================================================================================
+/// Aggregates trades from Wrapper::tick_by_tick_all_last (Last or AllLast) into OHLCV bars of a
+/// fixed interval aligned to multiples of the interval since the epoch.
+///
+/// The bars are BarData with date set to the bar's start time in epoch seconds, bar_count set
+/// to the number of trades and average set to the volume weighted average price.
+///
+/// The open and close are the prices of the earliest and latest trade of the bar, also when
+/// trades of the bar arrive out of order.  Trades older than the bar being built belong to bars
+/// already emitted; they are dropped and counted in late_ticks().  Intervals without trades
+/// produce no bar unless fill_gaps is set, in which case a flat bar at the previous close with
+/// zero volume is emitted.
+#[derive(Clone, Debug)]
+pub struct TickBarAggregator {
+    interval: i64,
+    fill_gaps: bool,
+    current: Option<OpenBar>,
+    late_ticks: u64,
+}
+
+impl TickBarAggregator {
+    /// # Arguments
+    /// * interval - bar length in seconds, e.g. 1, 5 or 60.  Must be positive.
+    /// * fill_gaps - emit flat bars for intervals without trades
+    pub fn new(interval: i64, fill_gaps: bool) -> Self {
+        assert!(interval > 0, "bar interval must be positive");
+        TickBarAggregator {
+            interval,
+            fill_gaps,
+            current: None,
+            late_ticks: 0,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Adds a trade and returns the bars it completed, oldest first
+    ///
+    /// # Arguments
+    /// * time - trade time in epoch seconds, as passed to tick_by_tick_all_last
+    /// * price - trade price
+    /// * size - trade size
+    pub fn on_trade(&mut self, time: i64, price: f64, size: BigDecimal) -> Vec<BarData> {
+        let start = time - time.rem_euclid(self.interval);
+        let mut completed = Vec::new();
+
+        match self.current.as_ref().map(|open_bar| open_bar.start) {
+            Some(current_start) if start < current_start => {
+                self.late_ticks += 1;
+                return completed;
+            }
+            Some(current_start) if start > current_start => {
+                let finished = self.current.take().unwrap().bar;
+                if self.fill_gaps {
+                    let mut gap_start = current_start + self.interval;
+                    while gap_start < start {
+                        completed.push(OpenBar::new(gap_start, finished.close).bar);
+                        gap_start += self.interval;
+                    }
+                }
+                completed.insert(0, finished);
+            }
+            _ => (),
+        }
+
+        self.current
+            .get_or_insert_with(|| OpenBar::new(start, price))
+            .add_trade(time, price, size, 1);
+        completed
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The bar being built, if any
+    pub fn current(&self) -> Option<&BarData> {
+        self.current.as_ref().map(|open_bar| &open_bar.bar)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Emits the bar being built, e.g. when its interval ended without a following trade
+    pub fn flush(&mut self) -> Option<BarData> {
+        self.current.take().map(|open_bar| open_bar.bar)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Number of trades dropped because they arrived after their bar was emitted
+    pub fn late_ticks(&self) -> u64 {
+        self.late_ticks
+    }
+}
+
+//==================================================================================================
+/// Consolidates the 5 second bars of Wrapper::realtime_bar into bars of a larger interval,
+/// aligned to multiples of the interval since the epoch (so 1 minute bars start on the minute).
+///
+/// The bars are RealTimeBar with date_time set to the bar's start time in epoch seconds, wap
+/// set to the volume weighted wap of the 5 second bars and count to the sum of their counts.
+/// A bar is emitted as soon as the last 5 second bar of its interval arrives, or when a bar of
+/// a later interval arrives first.  Each emitted bar moves a watermark to its end: 5 second
+/// bars before it, or older than the bar being built, belong to bars already emitted, so they
+/// are dropped and counted in late_bars() rather than emitted again.
+#[derive(Clone, Debug)]
+pub struct RealTimeBarResampler {
+    interval: i64,
+    start: i64,
+    current: Option<RealTimeBar>,
+    turnover: f64,
+    /// the end of the last bar emitted
+    watermark: Option<i64>,
+    late_bars: u64,
+}
+
+impl RealTimeBarResampler {
+    /// # Arguments
+    /// * interval - bar length in seconds, e.g. 15, 60 or 300.  Must be a positive multiple
+    ///   of 5.
+    pub fn new(interval: i64) -> Self {
+        assert!(
+            interval > 0 && interval % REAL_TIME_BAR_SECONDS == 0,
+            "bar interval must be a positive multiple of 5 seconds"
+        );
+        RealTimeBarResampler {
+            interval,
+            start: 0,
+            current: None,
+            turnover: 0.0,
+            watermark: None,
+            late_bars: 0,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Adds a 5 second bar and returns the bars it completed, oldest first
+    ///
+    /// # Arguments
+    /// * bar - the bar passed to realtime_bar
+    pub fn on_bar(&mut self, bar: &RealTimeBar) -> Result<Vec<RealTimeBar>, IBKRApiLibError> {
+        let time = bar.date_time.parse::<i64>().map_err(|_| {
+            IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                -1,
+                TwsError::BadMessage.code().to_string(),
+                format!(
+                    "{} Real time bar time: {}",
+                    TwsError::BadMessage.message(),
+                    bar.date_time
+                ),
+            ))
+        })?;
+        let start = time - time.rem_euclid(self.interval);
+        let mut completed = Vec::new();
+
+        let late = matches!(self.watermark, Some(watermark) if start < watermark)
+            || (self.current.is_some() && start < self.start);
+        if late {
+            self.late_bars += 1;
+            return Ok(completed);
+        }
+        if self.current.is_some() && start > self.start {
+            completed.extend(self.flush());
+        }
+
+        let turnover = bar.wap * bar.volume.to_f64().unwrap_or(0.0);
+        match self.current.as_mut() {
+            Some(current) => {
+                current.high = current.high.max(bar.high);
+                current.low = current.low.min(bar.low);
+                current.close = bar.close;
+                current.volume += &bar.volume;
+                current.count += bar.count;
+                self.turnover += turnover;
+                if current.volume.is_positive() {
+                    current.wap = self.turnover / current.volume.to_f64().unwrap_or(0.0);
+                }
+            }
+            None => {
+                self.start = start;
+                self.turnover = turnover;
+                self.current = Some(RealTimeBar::new(
+                    start.to_string(),
+                    bar.open,
+                    bar.high,
+                    bar.low,
+                    bar.close,
+                    bar.volume.clone(),
+                    bar.wap,
+                    bar.count,
+                ));
+            }
+        }
+
+        if time + REAL_TIME_BAR_SECONDS >= start + self.interval {
+            completed.extend(self.flush());
+        }
+        Ok(completed)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The bar being built, if any
+    pub fn current(&self) -> Option<&RealTimeBar> {
+        self.current.as_ref()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Emits the bar being built, e.g. when the subscription is cancelled mid-interval
+    pub fn flush(&mut self) -> Option<RealTimeBar> {
+        let bar = self.current.take();
+        if bar.is_some() {
+            self.watermark = Some(self.start + self.interval);
+        }
+        bar
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The number of 5 second bars dropped because their bar was already emitted
+    pub fn late_bars(&self) -> u64 {
+        self.late_bars
+    }
+}
@@ -0,0 +1,456 @@
+//! Capture of the raw message frames exchanged with TWS, and their replay
+//!
+//! With EClient::set_capture, every frame sent or received while connected is written to a
+//! capture file with the time it passed the socket.  EClient::connect_replay feeds the
+//! received frames of a capture back through the Decoder, so a session can be reproduced
+//! without TWS.
+//!
+//! A capture file starts with CAPTURE_MAGIC, followed by one record per frame: the direction
+//! byte ('<' received, '>' sent), the time in microseconds since the epoch as a big-endian i64,
+//! the payload length as a big-endian u32 and the payload, i.e. the frame without its length
+//! prefix.  The "API\0" prefix of the handshake is not a frame and is not recorded.
+//!
+//! A raw capture, created with WireCapture::create_raw, records the bytes of every socket read
+//! and write as they were, length prefixes and handshake included.  It starts with
+//! RAW_CAPTURE_MAGIC, followed by one record per read or write: the direction byte, the time in
+//! nanoseconds since the epoch as a big-endian i64, the length as a big-endian u32 and the
+//! bytes.  read_capture splits a raw capture back into frames; decode_capture passes the frames
+//! of either kind of capture to a wrapper offline.
+use std::convert::TryInto;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::net::{Shutdown, SocketAddr};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::client::ConnStatus;
+use crate::core::decoder::Decoder;
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+use crate::core::message_queue::{message_queue, QueueConfig};
+use crate::core::messages::read_fields;
+use crate::core::streamer::Streamer;
+use crate::core::wrapper::Wrapper;
+
+/// First bytes of a capture file
+pub const CAPTURE_MAGIC: &[u8] = b"TWSCAP1\n";
+/// First bytes of a raw capture file
+pub const RAW_CAPTURE_MAGIC: &[u8] = b"TWSRAW1\n";
+
+const API_PREFIX: &[u8] = b"API\0";
+const CAPTURE_POISONED_MUTEX: &str = "Capture mutex was poisoned";
+
+//==================================================================================================
+/// Whether a frame was received from TWS or sent to it
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+impl Direction {
+    fn marker(&self) -> u8 {
+        match self {
+            Direction::Inbound => b'<',
+            Direction::Outbound => b'>',
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn from_marker(marker: u8) -> Option<Self> {
+        match marker {
+            b'<' => Some(Direction::Inbound),
+            b'>' => Some(Direction::Outbound),
+            _ => None,
+        }
+    }
+}
+
+//==================================================================================================
+/// One recorded frame
+/// time_micros - when the frame passed the socket, in microseconds since the epoch
+/// payload - the message fields, each terminated by a NUL byte
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CapturedFrame {
+    pub direction: Direction,
+    pub time_micros: i64,
+    pub payload: Vec<u8>,
+}
+
+impl CapturedFrame {
+    pub fn new(direction: Direction, time_micros: i64, payload: Vec<u8>) -> Self {
+        CapturedFrame {
+            direction,
+            time_micros,
+            payload,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The payload as text, as passed to the Decoder
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.payload).to_string()
+    }
+}
+
+impl fmt::Display for CapturedFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "direction: {:?}, time_micros: {}, payload: {:?}",
+            self.direction,
+            self.time_micros,
+            self.text()
+        )
+    }
+}
+
+//==================================================================================================
+/// The bytes of one socket read or write in a raw capture
+/// time_nanos - when they passed the socket, in nanoseconds since the epoch
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RawChunk {
+    pub direction: Direction,
+    pub time_nanos: i64,
+    pub bytes: Vec<u8>,
+}
+
+impl RawChunk {
+    pub fn new(direction: Direction, time_nanos: i64, bytes: Vec<u8>) -> Self {
+        RawChunk {
+            direction,
+            time_nanos,
+            bytes,
+        }
+    }
+}
+
+impl fmt::Display for RawChunk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "direction: {:?}, time_nanos: {}, bytes: {:?}",
+            self.direction,
+            self.time_nanos,
+            String::from_utf8_lossy(&self.bytes)
+        )
+    }
+}
+
+//==================================================================================================
+/// Speed of a replay
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReplaySpeed {
+    /// Waits between frames as long as the recording did
+    Original,
+    /// Waits between frames the recorded time divided by the factor
+    Accelerated(f64),
+    /// Does not wait between frames
+    Unpaced,
+}
+
+impl ReplaySpeed {
+    /// How long to wait before a frame recorded elapsed_micros after the previous one
+    pub fn delay(&self, elapsed_micros: i64) -> Duration {
+        let elapsed = Duration::from_micros(elapsed_micros.max(0) as u64);
+        match *self {
+            ReplaySpeed::Original => elapsed,
+            ReplaySpeed::Accelerated(factor) if factor > 0.0 => elapsed.div_f64(factor),
+            _ => Duration::from_secs(0),
+        }
+    }
+}
+
+//==================================================================================================
+/// Capture file being written.  Clones write to the same file.
+#[derive(Clone)]
+pub struct WireCapture {
+    writer: Arc<Mutex<BufWriter<fs::File>>>,
+    raw: bool,
+}
+
+impl WireCapture {
+    /// Creates the capture file, replacing an existing one
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, IBKRApiLibError> {
+        WireCapture::create_with(path, CAPTURE_MAGIC, false)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Creates a raw capture file, replacing an existing one
+    pub fn create_raw<P: AsRef<Path>>(path: P) -> Result<Self, IBKRApiLibError> {
+        WireCapture::create_with(path, RAW_CAPTURE_MAGIC, true)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn create_with<P: AsRef<Path>>(
+        path: P,
+        magic: &[u8],
+        raw: bool,
+    ) -> Result<Self, IBKRApiLibError> {
+        let mut writer = BufWriter::new(fs::File::create(path)?);
+        writer.write_all(magic)?;
+        writer.flush()?;
+        Ok(WireCapture {
+            writer: Arc::new(Mutex::new(writer)),
+            raw,
+        })
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether the capture records the socket reads and writes rather than frames
+    pub fn is_raw(&self) -> bool {
+        self.raw
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Appends a frame, or in a raw capture the bytes of a read or write, stamped with the
+    /// current time.  Each record is flushed, so a capture of a session that crashed is
+    /// complete up to the crash.
+    pub fn record(&self, direction: Direction, payload: &[u8]) -> io::Result<()> {
+        let elapsed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let time = if self.raw {
+            elapsed.as_nanos() as i64
+        } else {
+            elapsed.as_micros() as i64
+        };
+        let mut writer = self.writer.lock().expect(CAPTURE_POISONED_MUTEX);
+        writer.write_all(&[direction.marker()])?;
+        writer.write_all(&time.to_be_bytes())?;
+        writer.write_all(&(payload.len() as u32).to_be_bytes())?;
+        writer.write_all(payload)?;
+        writer.flush()
+    }
+}
+
+//==================================================================================================
+/// Reads every frame of a capture file, in the order recorded.  The frames of a raw capture
+/// have the time of the read or write that completed them.
+pub fn read_capture<P: AsRef<Path>>(path: P) -> Result<Vec<CapturedFrame>, IBKRApiLibError> {
+    let path = path.as_ref();
+    let bytes = read_file(path)?;
+    if bytes.starts_with(RAW_CAPTURE_MAGIC) {
+        let mut inbound = FrameSplitter::default();
+        let mut outbound = FrameSplitter::default();
+        let mut frames = Vec::new();
+        for chunk in read_records(path, &bytes[RAW_CAPTURE_MAGIC.len()..])? {
+            let splitter = match chunk.direction {
+                Direction::Inbound => &mut inbound,
+                Direction::Outbound => &mut outbound,
+            };
+            for payload in splitter.push(&chunk.bytes) {
+                frames.push(CapturedFrame::new(
+                    chunk.direction,
+                    chunk.time_nanos / 1000,
+                    payload,
+                ));
+            }
+        }
+        return Ok(frames);
+    }
+    if !bytes.starts_with(CAPTURE_MAGIC) {
+        return Err(bad_capture_file(path));
+    }
+    Ok(read_records(path, &bytes[CAPTURE_MAGIC.len()..])?
+        .into_iter()
+        .map(|record| CapturedFrame::new(record.direction, record.time_nanos, record.bytes))
+        .collect())
+}
+
+//==================================================================================================
+/// Reads every read and write of a raw capture file, in the order recorded
+pub fn read_raw_capture<P: AsRef<Path>>(path: P) -> Result<Vec<RawChunk>, IBKRApiLibError> {
+    let path = path.as_ref();
+    let bytes = read_file(path)?;
+    if !bytes.starts_with(RAW_CAPTURE_MAGIC) {
+        return Err(bad_capture_file(path));
+    }
+    read_records(path, &bytes[RAW_CAPTURE_MAGIC.len()..])
+}
+
+//==================================================================================================
+/// Passes the inbound frames of a capture file to a wrapper, as a Decoder does while
+/// connected, to examine a session offline.  The server version is the one of the recorded
+/// handshake.
+///
+/// # Returns
+/// The number of messages decoded
+pub fn decode_capture<P, T>(path: P, wrapper: Arc<Mutex<T>>) -> Result<usize, IBKRApiLibError>
+where
+    P: AsRef<Path>,
+    T: Wrapper + Sync,
+{
+    let mut inbound = read_capture(path)?
+        .into_iter()
+        .filter(|frame| frame.direction == Direction::Inbound);
+    let handshake = inbound
+        .next()
+        .map(|frame| read_fields(&frame.text()))
+        .filter(|fields| fields.len() == 2)
+        .ok_or_else(|| {
+            IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                -1,
+                TwsError::BadMessage.code().to_string(),
+                format!(
+                    "{} The capture does not start with the handshake.",
+                    TwsError::BadMessage.message()
+                ),
+            ))
+        })?;
+
+    let (_, rx) = message_queue(QueueConfig::default());
+    let mut decoder = Decoder::new(
+        wrapper,
+        rx,
+        handshake[0].parse()?,
+        Arc::new(Mutex::new(ConnStatus::CONNECTED)),
+    );
+    let mut decoded = 0;
+    for frame in inbound {
+        decoder.interpret(&read_fields(&frame.text()))?;
+        decoded += 1;
+    }
+    Ok(decoded)
+}
+
+//==================================================================================================
+fn read_file(path: &Path) -> Result<Vec<u8>, IBKRApiLibError> {
+    let mut bytes = Vec::new();
+    BufReader::new(fs::File::open(path)?).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+//==================================================================================================
+/// Reads the records following the magic bytes.  Their time is micros in a frame capture.
+fn read_records(path: &Path, mut rest: &[u8]) -> Result<Vec<RawChunk>, IBKRApiLibError> {
+    let mut records = Vec::new();
+    while !rest.is_empty() {
+        if rest.len() < 13 {
+            return Err(bad_capture_file(path));
+        }
+        let direction = Direction::from_marker(rest[0]).ok_or_else(|| bad_capture_file(path))?;
+        let time = i64::from_be_bytes(rest[1..9].try_into().unwrap());
+        let len = u32::from_be_bytes(rest[9..13].try_into().unwrap()) as usize;
+        if rest.len() < 13 + len {
+            return Err(bad_capture_file(path));
+        }
+        records.push(RawChunk::new(direction, time, rest[13..13 + len].to_vec()));
+        rest = &rest[13 + len..];
+    }
+    Ok(records)
+}
+
+//==================================================================================================
+fn bad_capture_file(path: &Path) -> IBKRApiLibError {
+    IBKRApiLibError::ApiError(TwsApiReportableError::new(
+        -1,
+        TwsError::BadMessage.code().to_string(),
+        format!(
+            "{} {} is not a complete capture file.",
+            TwsError::BadMessage.message(),
+            path.display()
+        ),
+    ))
+}
+
+//==================================================================================================
+/// Splits the bytes of one direction into frames
+#[derive(Default)]
+struct FrameSplitter {
+    bytes: Vec<u8>,
+    started: bool,
+}
+
+impl FrameSplitter {
+    /// Adds the bytes and takes the payloads of the frames they complete
+    fn push(&mut self, bytes: &[u8]) -> Vec<Vec<u8>> {
+        let mut payloads = Vec::new();
+        self.bytes.extend_from_slice(bytes);
+        if !self.started && self.bytes.len() >= API_PREFIX.len() {
+            self.started = true;
+            if self.bytes.starts_with(API_PREFIX) {
+                self.bytes.drain(..API_PREFIX.len());
+            }
+        }
+        while self.started && self.bytes.len() >= 4 {
+            let len = u32::from_be_bytes(self.bytes[0..4].try_into().unwrap()) as usize;
+            if self.bytes.len() < 4 + len {
+                break;
+            }
+            payloads.push(self.bytes[4..4 + len].to_vec());
+            self.bytes.drain(..4 + len);
+        }
+        payloads
+    }
+}
+
+//==================================================================================================
+/// Streamer recording the frames read and written through another streamer, or the reads and
+/// writes themselves for a raw capture
+pub struct CapturingStreamer<S: Streamer> {
+    inner: S,
+    capture: WireCapture,
+    inbound: FrameSplitter,
+    outbound: FrameSplitter,
+}
+
+impl<S: Streamer> CapturingStreamer<S> {
+    pub fn new(inner: S, capture: WireCapture) -> Self {
+        CapturingStreamer {
+            inner,
+            capture,
+            inbound: FrameSplitter::default(),
+            outbound: FrameSplitter::default(),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn record(&mut self, direction: Direction, bytes: &[u8]) {
+        // a capture that can't be written must not break the connection
+        if self.capture.is_raw() {
+            let _ = self.capture.record(direction, bytes);
+            return;
+        }
+        let splitter = match direction {
+            Direction::Inbound => &mut self.inbound,
+            Direction::Outbound => &mut self.outbound,
+        };
+        for payload in splitter.push(bytes) {
+            let _ = self.capture.record(direction, &payload);
+        }
+    }
+}
+
+impl<S: Streamer> Streamer for CapturingStreamer<S> {
+    fn shutdown(&mut self, how: Shutdown) -> io::Result<()> {
+        self.inner.shutdown(how)
+    }
+
+    fn connect(&mut self, addr: &SocketAddr) {
+        self.inner.connect(addr)
+    }
+}
+
+impl<S: Streamer> Read for CapturingStreamer<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let bytes_read = self.inner.read(buf)?;
+        self.record(Direction::Inbound, &buf[..bytes_read]);
+        Ok(bytes_read)
+    }
+}
+
+impl<S: Streamer> Write for CapturingStreamer<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let bytes_written = self.inner.write(buf)?;
+        self.record(Direction::Outbound, &buf[..bytes_written]);
+        Ok(bytes_written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
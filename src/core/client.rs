@@ -1,12 +1,15 @@
 //! EClient and supporting structs.  Responsible for connecting to Trader Workstation or IB Gatway and sending requests
+use std::collections::HashMap;
 use std::io::Write;
 use std::marker::Sync;
 use std::net::Shutdown;
-use std::net::TcpStream;
+use std::net::{TcpStream, ToSocketAddrs};
 use std::ops::Deref;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::channel;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
 use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 use std::{thread, result::Result};
 use std::fmt::{Debug, Display};
 use std::fmt;
@@ -14,22 +17,67 @@ use std::fmt;
 use from_ascii::FromAscii;
 use log::*;
 
+use chrono::{DateTime, TimeZone, Utc};
 use num_derive::FromPrimitive;
 
-use super::streamer::{Streamer, TcpStreamer};
+use super::streamer::{Streamer, TcpStreamer, TestStreamer};
+use crate::core::account_summary_tags::AccountSummaryTagSet;
+use crate::core::accounts::{AccountId, Accounts};
+use crate::core::capture::{CapturedFrame, CapturingStreamer, Direction, ReplaySpeed, WireCapture};
+use crate::core::common;
 use crate::core::common::*;
-use crate::core::contract::Contract;
+use crate::core::connection::{
+    ConnectionConfig, MessagePacer, SessionReady, CLIENT_ID_CONFIRMATION_TIMEOUT, CLIENT_ID_IN_USE,
+};
+use crate::core::contract::{
+    Contract, ContractDescription, ContractDetails, DeltaNeutralContract, CONTRACT_DETAILS_PACING,
+    SYMBOL_SEARCH_PACING,
+};
+use crate::core::coverage::FieldCoverage;
 use crate::core::decoder::Decoder;
-use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+use crate::core::display_groups::{DisplayGroupEvent, DisplayGroupId};
+use crate::core::errors::{
+    ErrorCategory, IBKRApiLibError, IbError, TwsApiReportableError, TwsError, TwsMessage,
+};
 use crate::core::execution::ExecutionFilter;
+use crate::core::exercise::{ExerciseStatus, OptionExercise};
+use crate::core::fa::FaConfig;
+use crate::core::history::{
+    self, BarUpdate, BarUpdateTracker, HISTORICAL_DATA_PACING, NO_HISTORICAL_DATA_CODE,
+};
+use crate::core::history_cache::{completed_end, HistoryCache, HistoryCacheKey};
+use crate::core::intent_log::{OrderIntent, OrderIntentLog};
+use crate::core::latency::{OrderLatency, OrderLatencyStats};
+use crate::core::log_targets::{CONNECTION, MARKET_DATA, ORDERS, PACING};
+use crate::core::market_rules::{MarketRules, RoundDirection};
+use crate::core::message_queue::{message_queue, QueueConfig, QueueMonitor, QueueStats};
 use crate::core::messages::make_field;
-use crate::core::messages::{make_field_handle_empty, read_msg};
+use crate::core::messages::make_field_handle_empty;
 use crate::core::messages::{make_message, read_fields, OutgoingMessageIds};
-use crate::core::order::Order;
+use crate::core::news_bulletins::{Bulletin, NewsBulletins};
+use crate::core::option_chains::{OptionChain, OptionChainCache};
+use crate::core::order::{
+    check_adjusted_order, check_cash_quantity, check_crypto_order, check_delta_neutral,
+    check_hedge_order, check_mifid2, check_order_attributes, check_pegged_to_benchmark,
+    check_scale_order, check_time_in_force, Order, OrderType, SoftDollarTier,
+};
 use crate::core::order_condition::Condition;
-use crate::core::reader::Reader;
+use crate::core::pending::{
+    ContractDetailsEvent, HistoricalBarKind, PendingBarTimeZone, PendingBarUpdates,
+    PendingDisplayGroup, PendingOptionChains, PendingRequests, PendingSnapshot,
+};
+use crate::core::precautions::{Marketability, MarketabilityCheck, PrecautionarySettings};
+use crate::core::quote::Quote;
+use crate::core::reader::{DispatchMode, Reader};
+use crate::core::request_context::RequestContext;
 use crate::core::scanner::ScannerSubscription;
 use crate::core::server_versions::*;
+use crate::core::smart_components::{SmartComponentMap, SmartComponents};
+use crate::core::subscriptions::{MetadataStore, RequestMetadata, Subscription};
+use crate::core::tick_by_tick::TickByTickEvent;
+use crate::core::time_zones::ExchangeTz;
+use crate::core::timestamps::timestamp_to_wire;
+use crate::core::what_if::{MarginPreview, WhatIfContribution, WhatIfScenario};
 use crate::core::wrapper::Wrapper;
 
 pub(crate) static POISONED_MUTEX: &str = "Mutex was poisoned";
@@ -81,6 +129,20 @@ where
     pub conn_state: Arc<Mutex<ConnStatus>>,
     opt_capab: String,
     disconnect_requested: Arc<AtomicBool>,
+    pending: Arc<PendingRequests>,
+    precautions: Option<PrecautionarySettings>,
+    marketability: Option<MarketabilityCheck>,
+    capture: Option<WireCapture>,
+    intent_log: Option<OrderIntentLog>,
+    dispatch_mode: DispatchMode,
+    order_wrapper: Option<Arc<Mutex<T>>>,
+    queue_config: QueueConfig,
+    queue: Option<QueueMonitor>,
+    connection: Option<ConnectionConfig>,
+    pacer: MessagePacer,
+    last_symbol_search: Option<Instant>,
+    subscriptions: Vec<Subscription>,
+    threads: Vec<JoinHandle<()>>,
 }
 
 impl<T> EClient<T>
@@ -100,14 +162,51 @@ where
             conn_state: Arc::new(Mutex::new(ConnStatus::DISCONNECTED)),
             opt_capab: "".to_string(),
             disconnect_requested: Arc::new(AtomicBool::new(false)),
+            pending: Arc::new(PendingRequests::default()),
+            precautions: None,
+            marketability: None,
+            capture: None,
+            intent_log: None,
+            dispatch_mode: DispatchMode::Single,
+            order_wrapper: None,
+            queue_config: QueueConfig::default(),
+            queue: None,
+            connection: None,
+            pacer: MessagePacer::default(),
+            last_symbol_search: None,
+            subscriptions: Vec::new(),
+            threads: Vec::new(),
         }
     }
     fn send_request(&mut self, request: &str) -> Result<(), IBKRApiLibError> {
         let bytes = make_message(request)?;
+        self.pace_request();
         self.send_bytes(bytes.as_slice())?;
         Ok(())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Waits until a request may be sent at the max_message_rate of the connection
+    fn pace_request(&mut self) {
+        let rate = match self
+            .connection
+            .as_ref()
+            .and_then(|connection| connection.max_message_rate)
+        {
+            Some(rate) => rate,
+            None => return,
+        };
+        let wait = self.pacer.wait(rate, Instant::now());
+        if wait > Duration::from_secs(0) {
+            debug!(
+                target: PACING,
+                "Waiting {:?} to stay within {} messages per second", wait, rate
+            );
+            thread::sleep(wait);
+        }
+        self.pacer.record(Instant::now());
+    }
+
     fn send_bytes(&mut self, bytes: &[u8]) -> Result<usize, IBKRApiLibError> {
         let return_val = self.stream.as_mut().unwrap().write(bytes)?;
         Ok(return_val)
@@ -124,26 +223,283 @@ where
         port: u32,
         client_id: i32,
     ) -> Result<(), IBKRApiLibError> {
+        self.connect_with(&ConnectionConfig::new(host, port, client_id))
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Establishes a connection to TWS or IB Gateway as configured, attempting it as often as
+    /// the reconnect policy of the config allows.  With client_id_retries set, a client id
+    /// TWS reports in use is replaced by the next one; client_id tells the id connected with.
+    pub fn connect_with(&mut self, config: &ConnectionConfig) -> Result<(), IBKRApiLibError> {
         if self.is_connected() {
-            info!("Already connected...");
+            info!(target: CONNECTION, "Already connected...");
             return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
                 -1,
                 TwsError::AlreadyConnected.code().to_string(),
                 TwsError::AlreadyConnected.message().to_string(),
             )));
         }
-        self.host = host.to_string();
-        self.port = port;
+        self.connection = Some(config.clone());
+        let messages = if config.client_id_retries > 0 {
+            Some(self.subscribe_messages())
+        } else {
+            None
+        };
+        let attempts = config.reconnect.attempts.max(1);
+        let mut attempt = 1;
+        let mut client_id = config.client_id;
+        loop {
+            match self.try_connect(config, client_id) {
+                Ok(()) => {
+                    let in_use = match messages.as_ref() {
+                        Some(messages) => self.is_client_id_in_use(messages, config),
+                        None => false,
+                    };
+                    if !in_use {
+                        return Ok(());
+                    }
+                    // TWS closes the connection, so the socket may be shut down already
+                    if let Err(err) = self.disconnect() {
+                        debug!(target: CONNECTION, "Closing the rejected connection: {}", err);
+                        *self.conn_state.lock().expect(POISONED_MUTEX) = ConnStatus::DISCONNECTED;
+                    }
+                    self.join_threads();
+                    if client_id - config.client_id >= config.client_id_retries as i32 {
+                        error!(
+                            target: CONNECTION,
+                            "Client ids {} to {} are all in use", config.client_id, client_id
+                        );
+                        return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                            NO_VALID_ID,
+                            CLIENT_ID_IN_USE.to_string(),
+                            format!(
+                                "Client ids {} to {} are already in use.",
+                                config.client_id, client_id
+                            ),
+                        )));
+                    }
+                    warn!(
+                        target: CONNECTION,
+                        "Client id {} is already in use, retrying with {}",
+                        client_id,
+                        client_id + 1
+                    );
+                    client_id += 1;
+                }
+                Err(err) => {
+                    *self.conn_state.lock().expect(POISONED_MUTEX) = ConnStatus::DISCONNECTED;
+                    if attempt >= attempts {
+                        error!(
+                            target: CONNECTION,
+                            "Could not connect to {}:{}: {}", config.host, config.port, err
+                        );
+                        return Err(err);
+                    }
+                    let delay = config.reconnect.delay_before(attempt);
+                    warn!(
+                        target: CONNECTION,
+                        "Connection attempt {} of {} failed: {}.  Retrying in {:?}",
+                        attempt,
+                        attempts,
+                        err,
+                        delay
+                    );
+                    thread::sleep(delay);
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Waits for TWS to either accept the client id with the next valid order id, or report it
+    /// in use and close the connection
+    fn is_client_id_in_use(
+        &self,
+        messages: &Receiver<TwsMessage>,
+        config: &ConnectionConfig,
+    ) -> bool {
+        let timeout = config
+            .read_timeout
+            .unwrap_or(CLIENT_ID_CONFIRMATION_TIMEOUT);
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self
+                .pending
+                .next_valid_id
+                .lock()
+                .expect(POISONED_MUTEX)
+                .is_some()
+            {
+                return false;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                warn!(
+                    target: CONNECTION,
+                    "TWS did not confirm client id {} within {:?}", self.client_id, timeout
+                );
+                return false;
+            }
+            let wait = (deadline - now).min(Duration::from_millis(10));
+            match messages.recv_timeout(wait) {
+                Ok(message) if message.code == CLIENT_ID_IN_USE => return true,
+                Ok(_) | Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => return false,
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The last next valid order id TWS sent on this connection, None until it sent one
+    pub fn next_valid_id(&self) -> Option<i32> {
+        *self.pending.next_valid_id.lock().expect(POISONED_MUTEX)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The managed accounts TWS sent on this connection, None until it sent them
+    pub fn managed_accounts(&self) -> Option<Vec<AccountId>> {
+        self.pending
+            .accounts
+            .lock()
+            .expect(POISONED_MUTEX)
+            .managed_accounts()
+            .map(|accounts| accounts.to_vec())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Waits until TWS sent both the next valid order id and the managed accounts on this
+    /// connection, which it does after every connect; requests sent before may be ignored.
+    /// Returns at once if they arrived already.
+    ///
+    /// Returns an error with code TwsError::NotConnected if the connection is closed, or a
+    /// RecvTimeoutError if they did not arrive within timeout.
+    pub fn wait_until_ready(&self, timeout: Duration) -> Result<SessionReady, IBKRApiLibError> {
+        let deadline = Instant::now() + timeout;
+        // held while checking, so a change signalled in between is not missed
+        let mut session = self.pending.session.lock().expect(POISONED_MUTEX);
+        loop {
+            self.check_connected(NO_VALID_ID)?;
+            let next_valid_id = self.next_valid_id();
+            if let (Some(next_valid_id), Some(accounts)) = (next_valid_id, self.managed_accounts())
+            {
+                return Ok(SessionReady {
+                    next_valid_id,
+                    accounts,
+                });
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(IBKRApiLibError::RecvTimeoutError(RecvTimeoutError::Timeout));
+            }
+            session = self
+                .pending
+                .session_changed
+                .wait_timeout(session, deadline - now)
+                .expect(POISONED_MUTEX)
+                .0;
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The client id of the connection, which connect_with may have chosen
+    pub fn client_id(&self) -> i32 {
+        self.client_id
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Waits for the reader and decoder threads of the connections to finish
+    fn join_threads(&mut self) {
+        for handle in self.threads.drain(..) {
+            if handle.join().is_err() {
+                warn!(target: CONNECTION, "A connection thread panicked");
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Disconnects if connected and connects again with the config of the last connection
+    pub fn reconnect(&mut self) -> Result<(), IBKRApiLibError> {
+        let config = match self.connection.clone() {
+            Some(config) => config,
+            None => {
+                return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                    NO_VALID_ID,
+                    TwsError::NotConnected.code().to_string(),
+                    format!(
+                        "{} there is no connection to reconnect",
+                        TwsError::NotConnected.message()
+                    ),
+                )))
+            }
+        };
+        self.disconnect()?;
+        self.connect_with(&config)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The config of the last connection, None before the first
+    pub fn connection_config(&self) -> Option<&ConnectionConfig> {
+        self.connection.as_ref()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn open_stream(config: &ConnectionConfig) -> Result<TcpStream, IBKRApiLibError> {
+        let address = format!("{}:{}", config.host, config.port);
+        let connect_timeout = match config.connect_timeout {
+            Some(connect_timeout) => connect_timeout,
+            None => return Ok(TcpStream::connect(address)?),
+        };
+        let mut last_err = None;
+        for addr in address.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, connect_timeout) {
+                Ok(tcp_stream) => return Ok(tcp_stream),
+                Err(err) => last_err = Some(err),
+            }
+        }
+        Err(last_err
+            .unwrap_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("{} did not resolve", address),
+                )
+            })
+            .into())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn try_connect(
+        &mut self,
+        config: &ConnectionConfig,
+        client_id: i32,
+    ) -> Result<(), IBKRApiLibError> {
+        self.host = config.host.clone();
+        self.port = config.port;
         self.client_id = client_id;
-        info!("Connecting");
+        info!(target: CONNECTION, "Connecting");
         self.disconnect_requested.store(false, Ordering::Release);
         *self.conn_state.lock().expect(POISONED_MUTEX) = ConnStatus::CONNECTING;
-        let tcp_stream = TcpStream::connect(format!("{}:{}", self.host, port))?;
+        let tcp_stream = Self::open_stream(config)?;
+        // only the handshake is bounded by the read timeout, the reader waits for messages
+        tcp_stream.set_read_timeout(config.read_timeout)?;
         let streamer = TcpStreamer::new(tcp_stream);
-        self.set_streamer(Option::from(Box::new(streamer.clone()) as Box<dyn Streamer>));
-        let (tx, rx) = channel::<String>();
+        let reader_streamer: Box<dyn Streamer> = match self.capture.clone() {
+            Some(capture) => {
+                self.set_streamer(Option::from(Box::new(CapturingStreamer::new(
+                    streamer.clone(),
+                    capture.clone(),
+                )) as Box<dyn Streamer>));
+                Box::new(CapturingStreamer::new(streamer.clone(), capture))
+            }
+            None => {
+                self.set_streamer(Option::from(Box::new(streamer.clone()) as Box<dyn Streamer>));
+                Box::new(streamer.clone())
+            }
+        };
+        let (tx, rx) = message_queue(self.queue_config);
+        self.queue = Some(tx.monitor());
         let mut reader = Reader::new(
-            Box::new(streamer.clone()),
+            reader_streamer,
             tx.clone(),
             self.disconnect_requested.clone(),
         );
@@ -151,7 +507,11 @@ where
         let mut fields: Vec<String> = Vec::new();
 
         let v_100_prefix = "API\0";
-        let v_100_version = format!("v{}..{}", MIN_CLIENT_VER, MAX_CLIENT_VER);
+        let mut v_100_version = format!("v{}..{}", MIN_CLIENT_VER, MAX_CLIENT_VER);
+        if let Some(connect_options) = config.connect_options.as_ref() {
+            v_100_version.push(' ');
+            v_100_version.push_str(connect_options);
+        }
 
         let msg = make_message(v_100_version.as_str())?;
 
@@ -167,6 +527,7 @@ where
             self.server_version,
             self.conn_state.clone(),
         );
+        decoder.pending = self.pending.clone();
 
         //An Interactive Broker's developer's note: "sometimes I get news before the server version, thus the loop"
         while fields.len() != 2 {
@@ -174,40 +535,208 @@ where
                 decoder.interpret(fields.as_slice())?;
             }
 
-            let buf = reader.recv_packet()?;
-
-            if buf.len() > 0 {
-                let (_size, msg, _remaining_messages) = read_msg(buf.as_slice())?;
-
-                fields.clear();
-                fields.extend_from_slice(read_fields(msg.as_ref()).as_slice());
-            } else {
-                fields.clear();
-            }
+            let msg = reader.read_frame()?;
+            fields.clear();
+            fields.extend_from_slice(read_fields(msg.as_ref()).as_slice());
         }
 
         self.server_version = i32::from_ascii(fields.get(0).unwrap().as_bytes()).unwrap();
 
-        info!("Server version: {}", self.server_version);
+        info!(target: CONNECTION, "Server version: {}", self.server_version);
 
         self.conn_time = fields.get(1).unwrap().to_string();
         decoder.server_version = self.server_version;
+        streamer.stream.set_read_timeout(None)?;
+
+        if self.dispatch_mode == DispatchMode::PriorityOrders {
+            let (priority_tx, priority_rx) = message_queue(self.queue_config);
+            reader.set_priority_messages(Some(priority_tx));
+            let order_wrapper = self.order_wrapper.as_ref().unwrap_or(&self.wrapper);
+            let mut priority_decoder = Decoder::new(
+                order_wrapper.clone(),
+                priority_rx,
+                self.server_version,
+                self.conn_state.clone(),
+            );
+            priority_decoder.pending = self.pending.clone();
+            priority_decoder.reports_connection_closed = false;
+            self.threads.push(thread::spawn(move || {
+                if priority_decoder.run().is_err() {
+                    panic!("priority decoder.run() failed!!");
+                }
+            }));
+        }
 
-        thread::spawn(move || {
+        self.threads.push(thread::spawn(move || {
             reader.run();
-        });
-
-        thread::spawn(move || {
-            if decoder.run().is_err() {
+        }));
+
+        let pending = self.pending.clone();
+        self.threads.push(thread::spawn(move || {
+            let result = decoder.run();
+            // the connection closed
+            pending.notify_session_changed();
+            if result.is_err() {
                 panic!("decoder.run() failed!!");
             }
-        });
+        }));
+        self.subscriptions.clear();
+        self.pending
+            .request_contexts
+            .lock()
+            .expect(POISONED_MUTEX)
+            .clear();
+        self.pending
+            .order_types
+            .lock()
+            .expect(POISONED_MUTEX)
+            .clear();
+        *self.pending.next_valid_id.lock().expect(POISONED_MUTEX) = None;
+        self.pending
+            .accounts
+            .lock()
+            .expect(POISONED_MUTEX)
+            .clear_managed_accounts();
         *self.conn_state.lock().expect(POISONED_MUTEX) = ConnStatus::CONNECTED;
-        info!("Connected");
+        info!(target: CONNECTION, "Connected");
         self.start_api()?;
         Ok(())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Records the frames of the next connections to a capture file, or stops recording with
+    /// None.  Takes effect on connect.  See the capture module.
+    pub fn set_capture(&mut self, capture: Option<WireCapture>) {
+        self.capture = capture;
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Sets how the messages of the next connections are dispatched to the wrapper.  Takes
+    /// effect on connect.  See reader::DispatchMode.
+    pub fn set_dispatch_mode(&mut self, dispatch_mode: DispatchMode) {
+        self.dispatch_mode = dispatch_mode;
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Sets the wrapper DispatchMode::PriorityOrders passes the order and execution messages
+    /// to, with a lock of its own, or passes them to the main wrapper with None.  Takes effect
+    /// on connect.
+    pub fn set_order_wrapper(&mut self, order_wrapper: Option<Arc<Mutex<T>>>) {
+        self.order_wrapper = order_wrapper;
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Sets the capacity and policy of the queue between the reader and the Decoder for the
+    /// next connections.  Takes effect on connect.  See message_queue::QueuePolicy.
+    pub fn set_queue_config(&mut self, queue_config: QueueConfig) {
+        self.queue_config = queue_config;
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The statistics of the message queue of the last connection, None before the first.
+    /// With DispatchMode::PriorityOrders they are those of the queue of the other messages.
+    pub fn queue_stats(&self) -> Option<QueueStats> {
+        self.queue.as_ref().map(|queue| queue.stats())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Sets the log place_order, cancel_order and req_global_cancel append their intent to
+    /// before sending.  A request whose intent can't be logged is not sent.  What-if orders
+    /// are not logged.
+    pub fn set_intent_log(&mut self, intent_log: Option<OrderIntentLog>) {
+        self.intent_log = intent_log;
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn record_intent(&self, intent: OrderIntent) -> Result<(), IBKRApiLibError> {
+        match self.intent_log.as_ref() {
+            Some(intent_log) => intent_log.record(&intent),
+            None => Ok(()),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Connects to a recorded session instead of TWS.  The frames TWS sent are fed through the
+    /// Decoder to the wrapper, paced by speed, and the connection closes after the last one.
+    /// Requests are accepted and discarded.
+    ///
+    /// # Arguments
+    /// * frames - a capture, see capture::read_capture.  Must start with the handshake.
+    /// * speed - how fast to replay the frames
+    pub fn connect_replay(
+        &mut self,
+        frames: Vec<CapturedFrame>,
+        speed: ReplaySpeed,
+    ) -> Result<(), IBKRApiLibError> {
+        if self.is_connected() {
+            info!(target: CONNECTION, "Already connected...");
+            return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                -1,
+                TwsError::AlreadyConnected.code().to_string(),
+                TwsError::AlreadyConnected.message().to_string(),
+            )));
+        }
+        let mut inbound = frames
+            .into_iter()
+            .filter(|frame| frame.direction == Direction::Inbound);
+        let handshake = inbound
+            .next()
+            .map(|frame| read_fields(&frame.text()))
+            .filter(|fields| fields.len() == 2)
+            .ok_or_else(|| {
+                IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                    -1,
+                    TwsError::BadMessage.code().to_string(),
+                    format!(
+                        "{} The capture does not start with the handshake.",
+                        TwsError::BadMessage.message()
+                    ),
+                ))
+            })?;
+        self.server_version = handshake[0].parse()?;
+        self.conn_time = handshake[1].clone();
+        info!(target: CONNECTION, "Replaying session with server version {}", self.server_version);
+
+        self.disconnect_requested.store(false, Ordering::Release);
+        self.set_streamer(Option::from(
+            Box::new(TestStreamer::new()) as Box<dyn Streamer>
+        ));
+        let (tx, rx) = message_queue(QueueConfig::default());
+        let mut decoder = Decoder::new(
+            self.wrapper.clone(),
+            rx,
+            self.server_version,
+            self.conn_state.clone(),
+        );
+        decoder.pending = self.pending.clone();
+        *self.conn_state.lock().expect(POISONED_MUTEX) = ConnStatus::CONNECTED;
+
+        let frames: Vec<CapturedFrame> = inbound.collect();
+        let disconnect_requested = self.disconnect_requested.clone();
+        self.threads.push(thread::spawn(move || {
+            let mut previous: Option<i64> = None;
+            for frame in frames.iter() {
+                if disconnect_requested.load(Ordering::Acquire) {
+                    break;
+                }
+                if let Some(previous) = previous {
+                    thread::sleep(speed.delay(frame.time_micros - previous));
+                }
+                previous = Some(frame.time_micros);
+                if tx.send(Ok(frame.text())).is_err() {
+                    break;
+                }
+            }
+            // dropping tx ends the decoder loop, which closes the connection
+        }));
+        self.threads.push(thread::spawn(move || {
+            if decoder.run().is_err() {
+                panic!("decoder.run() failed!!");
+            }
+        }));
+        Ok(())
+    }
+
     //----------------------------------------------------------------------------------------------
     /// Checks connection status
     pub fn is_connected(&self) -> bool {
@@ -222,6 +751,157 @@ where
         connected
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Sets the limits place_order checks every order against before sending it, or None to
+    /// send orders unchecked.  See PrecautionarySettings.
+    pub fn set_precautionary_settings(&mut self, settings: Option<PrecautionarySettings>) {
+        self.precautions = settings;
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Sets the check place_order_against_quote applies to limit prices, or None to not
+    /// classify them.  See MarketabilityCheck.
+    pub fn set_marketability_check(&mut self, check: Option<MarketabilityCheck>) {
+        self.marketability = check;
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Turns lenient decoding on or off.  In lenient mode a message that fails to decode is
+    /// reported to the wrapper's error as a bad message and skipped instead of stopping the
+    /// message loop, and the fields each message type sent beyond those the decoder parses are
+    /// recorded in field_coverage().
+    pub fn set_lenient_decoding(&mut self, lenient: bool) {
+        self.pending
+            .lenient_decoding
+            .store(lenient, Ordering::Relaxed);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The managed accounts TWS reported and the aliases of the FA alias configuration
+    /// received so far
+    pub fn accounts(&self) -> Accounts {
+        self.pending.accounts.lock().expect(POISONED_MUTEX).clone()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The alias of an account, or the account code without one.  See Accounts::display_name.
+    pub fn display_name(&self, account: &str) -> String {
+        self.pending
+            .accounts
+            .lock()
+            .expect(POISONED_MUTEX)
+            .display_name(account)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Field coverage recorded in lenient mode since the client was created, e.g. to report at
+    /// the end of a session which messages carried fields this crate does not map yet
+    pub fn field_coverage(&self) -> FieldCoverage {
+        self.pending
+            .field_coverage
+            .lock()
+            .expect(POISONED_MUTEX)
+            .clone()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// When the last message from TWS was decoded, None before the first one
+    pub fn last_inbound(&self) -> Option<Instant> {
+        *self.pending.last_inbound.lock().expect(POISONED_MUTEX)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Returns a receiver of the bars of a req_historical_data request made with
+    /// keep_up_to_date, telling the bar in progress from the final bars.  The bars are still
+    /// passed to Wrapper::historical_data_update as well.  cancel_historical_data ends the
+    /// receiver without a Final for the bar in progress.  See history::BarUpdate.
+    pub fn subscribe_bar_updates(&mut self, req_id: i32) -> Receiver<BarUpdate> {
+        let (tx, rx) = channel::<BarUpdate>();
+        self.pending
+            .bar_updates
+            .lock()
+            .expect(POISONED_MUTEX)
+            .insert(
+                req_id,
+                PendingBarUpdates {
+                    tracker: BarUpdateTracker::new(),
+                    sender: tx,
+                },
+            );
+        rx
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Reads the bar dates of a req_historical_data request in the time zone of the exchange,
+    /// the time_zone_id of the contract details.  Needed for format_date 1, whose dates are in
+    /// exchange time; dates followed by a time zone id are read in that zone either way.  Call
+    /// before the request.  The zone is used until the end of the historical data, or until
+    /// cancel_historical_data for requests with keep_up_to_date.
+    ///
+    /// Returns the zone, or an error with code TwsError::InvalidRequest if the time zone
+    /// database does not know the time_zone_id.
+    ///
+    /// # Arguments
+    /// * req_id - the id of the req_historical_data request
+    /// * details - the details of the contract of the request
+    pub fn set_bar_time_zone(
+        &mut self,
+        req_id: i32,
+        details: &ContractDetails,
+    ) -> Result<ExchangeTz, IBKRApiLibError> {
+        let zone = ExchangeTz::of_details(details).ok_or_else(|| {
+            IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                req_id,
+                TwsError::InvalidRequest.code().to_string(),
+                format!(
+                    "{} Unknown time zone id: {}",
+                    TwsError::InvalidRequest.message(),
+                    details.time_zone_id
+                ),
+            ))
+        })?;
+        self.pending
+            .bar_time_zones
+            .lock()
+            .expect(POISONED_MUTEX)
+            .insert(
+                req_id,
+                PendingBarTimeZone {
+                    zone,
+                    last: None,
+                    keep_up_to_date: false,
+                },
+            );
+        Ok(zone)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Returns a receiver of the ticks of a req_tick_by_tick_data request, typed by tick type.
+    /// The ticks are still passed to the tick_by_tick_ callbacks of the wrapper as well.
+    /// cancel_tick_by_tick_data ends the receiver.  See tick_by_tick::TickByTickEvent.
+    pub fn subscribe_tick_by_tick(&mut self, req_id: i32) -> Receiver<TickByTickEvent> {
+        let (tx, rx) = channel::<TickByTickEvent>();
+        self.pending
+            .tick_by_tick
+            .lock()
+            .expect(POISONED_MUTEX)
+            .insert(req_id, tx);
+        rx
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Returns a receiver of every error and warning message TWS sends, in the order received.
+    /// The messages are still passed to Wrapper::error and Wrapper::warning as well.
+    pub fn subscribe_messages(&mut self) -> Receiver<TwsMessage> {
+        let (tx, rx) = channel::<TwsMessage>();
+        self.pending
+            .message_listeners
+            .lock()
+            .expect(POISONED_MUTEX)
+            .push(tx);
+        rx
+    }
+
     //----------------------------------------------------------------------------------------------
     /// Get the server version (important for checking feature flags for different versions)
     pub fn server_version(&self) -> i32 {
@@ -233,7 +913,7 @@ where
     pub fn set_server_log_level(&mut self, log_evel: LogLevel) -> Result<(), IBKRApiLibError> {
         //The pub default detail level is ERROR. For more details, see API
         //        Logging.
-        debug!("set_server_log_level -- log_evel: {}", log_evel);
+        debug!(target: CONNECTION, "set_server_log_level -- log_evel: {}", log_evel);
 
         self.check_connected(NO_VALID_ID)?;
 
@@ -270,7 +950,7 @@ where
         msg.push_str(&make_field(&message_id)?);
         msg.push_str(&make_field(&version)?);
 
-        debug!("Requesting current time: {}", msg.as_str());
+        debug!(target: CONNECTION, "Requesting current time: {}", msg.as_str());
         self.send_request(msg.as_str())
     }
 
@@ -278,16 +958,123 @@ where
     /// Disconnect from TWS
     pub fn disconnect(&mut self) -> Result<(), IBKRApiLibError> {
         if !self.is_connected() {
-            info!("Already disconnected...");
+            info!(target: CONNECTION, "Already disconnected...");
             return Ok(());
         }
-        info!("Disconnect requested.  Shutting down stream...");
+        info!(target: CONNECTION, "Disconnect requested.  Shutting down stream...");
         self.disconnect_requested.store(true, Ordering::Release);
         self.stream.as_mut().unwrap().shutdown(Shutdown::Both)?;
         *self.conn_state.lock().expect(POISONED_MUTEX) = ConnStatus::DISCONNECTED;
+        self.pending.notify_session_changed();
+        // the order types may change by the next connection
+        self.pending
+            .order_types
+            .lock()
+            .expect(POISONED_MUTEX)
+            .clear();
         Ok(())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Ends the session deterministically: cancels the active subscriptions, flushes what was
+    /// written to the socket, disconnects and waits for the reader and decoder threads of the
+    /// connection to finish.  Messages already read are passed to the wrapper before it returns.
+    /// Safe to call when not connected.
+    pub fn shutdown(&mut self) -> Result<(), IBKRApiLibError> {
+        if self.is_connected() {
+            info!(target: CONNECTION, "Shutting down...");
+            for subscription in std::mem::take(&mut self.subscriptions) {
+                // keep going, the connection is closed anyway
+                if let Err(err) = self.cancel_subscription(&subscription) {
+                    warn!(target: CONNECTION, "Could not cancel {}: {}", subscription, err);
+                }
+            }
+            if let Some(stream) = self.stream.as_mut() {
+                stream.flush()?;
+            }
+            self.disconnect()?;
+        }
+        self.join_threads();
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The streaming requests made on this connection and not cancelled yet
+    pub fn active_subscriptions(&self) -> Vec<Subscription> {
+        self.subscriptions.clone()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn add_subscription(&mut self, subscription: Subscription) {
+        if !self.subscriptions.contains(&subscription) {
+            self.subscriptions.push(subscription);
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Drops the metadata of requests, keyed by their req_id, when they end: subscriptions when
+    /// they are cancelled, other requests with the message ending them or an error for them
+    pub fn track_metadata<M: Send + 'static>(&mut self, metadata: &RequestMetadata<M>) {
+        let store: Box<dyn MetadataStore> = Box::new(metadata.clone());
+        self.pending
+            .metadata
+            .lock()
+            .expect(POISONED_MUTEX)
+            .push(store);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn remove_subscription(&mut self, subscription: &Subscription) {
+        self.subscriptions.retain(|active| active != subscription);
+        if let Some(req_id) = subscription.req_id() {
+            self.pending.forget_request(req_id);
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Records the context the Decoder makes current while passing the messages of a request
+    /// to the wrapper
+    fn record_request_context(&self, req_id: i32, request: &str, contract: Option<&Contract>) {
+        self.pending
+            .request_contexts
+            .lock()
+            .expect(POISONED_MUTEX)
+            .record(RequestContext::new(req_id, request, contract));
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The context recorded for a request sent by this client
+    pub fn request_context(&self, req_id: i32) -> Option<Arc<RequestContext>> {
+        self.pending
+            .request_contexts
+            .lock()
+            .expect(POISONED_MUTEX)
+            .get(req_id)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn cancel_subscription(&mut self, subscription: &Subscription) -> Result<(), IBKRApiLibError> {
+        match subscription {
+            Subscription::MktData(req_id) => self.cancel_mkt_data(*req_id),
+            Subscription::TickByTick(req_id) => self.cancel_tick_by_tick_data(*req_id),
+            Subscription::MktDepth {
+                req_id,
+                is_smart_depth,
+            } => self.cancel_mkt_depth(*req_id, *is_smart_depth),
+            Subscription::RealTimeBars(req_id) => self.cancel_real_time_bars(*req_id),
+            Subscription::HistoricalData(req_id) => self.cancel_historical_data(*req_id),
+            Subscription::ScannerSubscription(req_id) => self.cancel_scanner_subscription(*req_id),
+            Subscription::AccountSummary(req_id) => self.cancel_account_summary(*req_id),
+            Subscription::PositionsMulti(req_id) => self.cancel_positions_multi(*req_id),
+            Subscription::AccountUpdatesMulti(req_id) => self.cancel_account_updates_multi(*req_id),
+            Subscription::Pnl(req_id) => self.cancel_pnl(*req_id),
+            Subscription::PnlSingle(req_id) => self.cancel_pnl_single(*req_id),
+            Subscription::Positions => self.cancel_positions(),
+            Subscription::NewsBulletins => self.cancel_news_bulletins(),
+            Subscription::AccountUpdates(account) => self.req_account_updates(false, account),
+        }
+    }
+
     //----------------------------------------------------------------------------------------------
     /// Initiates the message exchange between the client application and the TWS/IB Gateway
     fn start_api(&mut self) -> Result<(), IBKRApiLibError> {
@@ -344,6 +1131,7 @@ where
         mkt_data_options: Vec<TagValue>,
     ) -> Result<(), IBKRApiLibError> {
         self.check_connected(req_id)?;
+        self.check_contract_codes(req_id, contract)?;
 
         if self.server_version() < MIN_SERVER_VER_DELTA_NEUTRAL {
             if let Some(_value) = &contract.delta_neutral_contract {
@@ -415,7 +1203,7 @@ where
             msg.push_str(&make_field(&contract.trading_class)?);
         }
         // Send combo legs for BAG requests(srv v8 and above)
-        if contract.sec_type == "BAG" {
+        if contract.sec_type == SecType::BAG {
             let combo_legs_count = contract.combo_legs.len();
             msg.push_str(&make_field(&combo_legs_count)?);
             for combo_leg in &contract.combo_legs {
@@ -470,10 +1258,49 @@ where
             msg.push_str(&make_field(&mkt_data_options_str)?);
         }
 
+        self.record_request_context(req_id, "req_mkt_data", Some(contract));
         self.send_request(msg.as_str())?;
+        if !snapshot && !regulatory_snapshot {
+            self.add_subscription(Subscription::MktData(req_id));
+        }
         Ok(())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Call this function to request a one-shot snapshot quote.  The ticks are still passed to
+    /// the tick_price and tick_size wrapper events, and the returned receiver yields the
+    /// consolidated Quote once tick_snapshot_end arrives.
+    ///
+    /// If TWS answers with an error instead, the receiver ends without yielding.
+    ///
+    /// # Arguments
+    /// * req_id - The request id. Must be a unique value.
+    /// * contract - The contract to quote.
+    pub fn snapshot(
+        &mut self,
+        req_id: i32,
+        contract: &Contract,
+    ) -> Result<Receiver<Quote>, IBKRApiLibError> {
+        let (tx, rx) = channel::<Quote>();
+        self.pending.snapshots.lock().expect(POISONED_MUTEX).insert(
+            req_id,
+            PendingSnapshot {
+                quote: Quote::new(),
+                sender: tx,
+            },
+        );
+
+        if let Err(err) = self.req_mkt_data(req_id, contract, "", true, false, vec![]) {
+            self.pending
+                .snapshots
+                .lock()
+                .expect(POISONED_MUTEX)
+                .remove(&req_id);
+            return Err(err);
+        }
+        Ok(rx)
+    }
+
     //----------------------------------------------------------------------------------------------
     /// After calling this function, market data for the specified id will stop flowing.
     ///
@@ -491,6 +1318,7 @@ where
         msg.push_str(&make_field(&req_id)?);
 
         self.send_request(msg.as_str())?;
+        self.remove_subscription(&Subscription::MktData(req_id));
         Ok(())
     }
 
@@ -572,10 +1400,59 @@ where
         msg.push_str(&make_field(&req_id)?);
         msg.push_str(&make_field(&String::from(bbo_exchange))?);
 
-        self.send_request(msg.as_str())?;
+        self.pending
+            .smart_component_requests
+            .lock()
+            .expect(POISONED_MUTEX)
+            .insert(req_id, bbo_exchange.to_string());
+        if let Err(err) = self.send_request(msg.as_str()) {
+            self.pending
+                .smart_component_requests
+                .lock()
+                .expect(POISONED_MUTEX)
+                .remove(&req_id);
+            return Err(err);
+        }
         Ok(())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// The bbo_exchanges of the market data requests and the smart component maps received so
+    /// far.  The map of a bbo_exchange is recorded once req_smart_components returned it.
+    pub fn smart_components(&self) -> SmartComponents {
+        self.pending
+            .smart_components
+            .lock()
+            .expect(POISONED_MUTEX)
+            .clone()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The smart component map of a bbo_exchange, None until req_smart_components returned it
+    pub fn smart_component_map(&self, bbo_exchange: &str) -> Option<SmartComponentMap> {
+        self.pending
+            .smart_components
+            .lock()
+            .expect(POISONED_MUTEX)
+            .map(bbo_exchange)
+            .cloned()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Translates the exchange letters of a market data request into exchange names, None
+    /// until the map of its bbo_exchange was received.  See smart_components::exchange_names.
+    ///
+    /// # Arguments
+    /// * ticker_id - the req_id of the market data request
+    /// * letters - exchange letters of its ticks, e.g. the value of a BID_EXCH tick
+    pub fn exchange_names(&self, ticker_id: i32, letters: &str) -> Option<Vec<String>> {
+        self.pending
+            .smart_components
+            .lock()
+            .expect(POISONED_MUTEX)
+            .exchange_names(ticker_id, letters)
+    }
+
     //----------------------------------------------------------------------------------------------
     /// Requests details about a given market rule
     /// The market rule for an instrument on a particular exchange provides details about how the
@@ -613,6 +1490,55 @@ where
         Ok(())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Requests the market rules of a contract not received yet, returning their ids
+    pub fn req_missing_market_rules(
+        &mut self,
+        details: &ContractDetails,
+    ) -> Result<Vec<i32>, IBKRApiLibError> {
+        let missing = self
+            .pending
+            .market_rules
+            .lock()
+            .expect(POISONED_MUTEX)
+            .missing(details);
+        for market_rule_id in &missing {
+            self.req_market_rule(*market_rule_id)?;
+        }
+        Ok(missing)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The price increments of the market rules received so far
+    pub fn market_rules(&self) -> MarketRules {
+        self.pending
+            .market_rules
+            .lock()
+            .expect(POISONED_MUTEX)
+            .clone()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// A price rounded to a valid tick of a contract on the exchange of its contract, by the
+    /// market rule received for it or else by its min_tick.  See MarketRules::round_to_tick.
+    ///
+    /// # Arguments
+    /// * details - the details of the contract, whose market rules to request first
+    /// * price - the price to round
+    /// * direction - e.g. RoundDirection::for_limit of the action of a limit order
+    pub fn round_to_tick(
+        &self,
+        details: &ContractDetails,
+        price: f64,
+        direction: RoundDirection,
+    ) -> f64 {
+        self.pending
+            .market_rules
+            .lock()
+            .expect(POISONED_MUTEX)
+            .round_to_tick(details, price, direction)
+    }
+
     //----------------------------------------------------------------------------------------------
     /// Request tick by tick data
     ///
@@ -631,6 +1557,7 @@ where
         ignore_size: bool,
     ) -> Result<(), IBKRApiLibError> {
         self.check_connected(NO_VALID_ID)?;
+        self.check_contract_codes(req_id, contract)?;
 
         if self.server_version() < MIN_SERVER_VER_TICK_BY_TICK {
             let err = IBKRApiLibError::ApiError(TwsApiReportableError::new(
@@ -687,7 +1614,9 @@ where
             msg.push_str(&make_field(&ignore_size)?);
         }
 
+        self.record_request_context(req_id, "req_tick_by_tick_data", Some(contract));
         self.send_request(msg.as_str())?;
+        self.add_subscription(Subscription::TickByTick(req_id));
         Ok(())
     }
 
@@ -721,6 +1650,12 @@ where
         msg.push_str(&make_field(&req_id)?);
 
         self.send_request(msg.as_str())?;
+        self.pending
+            .tick_by_tick
+            .lock()
+            .expect(POISONED_MUTEX)
+            .remove(&req_id);
+        self.remove_subscription(&Subscription::TickByTick(req_id));
         Ok(())
     }
 
@@ -747,6 +1682,7 @@ where
         impl_vol_options: Vec<TagValue>,
     ) -> Result<(), IBKRApiLibError> {
         self.check_connected(req_id)?;
+        self.check_contract_codes(req_id, contract)?;
 
         if self.server_version() < MIN_SERVER_VER_REQ_CALC_IMPLIED_VOLAT {
             let err = IBKRApiLibError::ApiError(TwsApiReportableError::new(
@@ -819,8 +1755,8 @@ where
             msg.push_str(&make_field(&tag_values_count)?);
             msg.push_str(&make_field(&impl_vol_opt_str)?);
         }
-        error!("sending calculate_implied_volatility");
-        error!("{}", msg);
+        error!(target: MARKET_DATA, "sending calculate_implied_volatility");
+        error!(target: MARKET_DATA, "{}", msg);
         self.send_request(msg.as_str())?;
         Ok(())
     }
@@ -842,6 +1778,7 @@ where
         opt_prc_options: Vec<TagValue>,
     ) -> Result<(), IBKRApiLibError> {
         self.check_connected(req_id)?;
+        self.check_contract_codes(req_id, contract)?;
 
         if self.server_version() < MIN_SERVER_VER_REQ_CALC_IMPLIED_VOLAT {
             let err = IBKRApiLibError::ApiError(TwsApiReportableError::new(
@@ -997,7 +1934,8 @@ where
     }
 
     //----------------------------------------------------------------------------------------------
-    /// Call this function to excercise options
+    /// Call this function to excercise options.  See exercise for a typed request checked
+    /// against the details of the option.
     ///
     /// # Arguments
     /// * req_id - The ticker id. multipleust be a unique value.
@@ -1025,6 +1963,7 @@ where
         over_ride: i32,
     ) -> Result<(), IBKRApiLibError> {
         self.check_connected(req_id)?;
+        self.check_contract_codes(req_id, contract)?;
 
         if self.server_version() < MIN_SERVER_VER_TRADING_CLASS {
             if !contract.trading_class.is_empty() {
@@ -1079,6 +2018,77 @@ where
         Ok(())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Exercises or lapses an option.  Looks up the details of the option first, so the
+    /// request goes out for exactly one contract, then sends it with exercise_options.  Blocks
+    /// until TWS answers the lookup, so call it from a thread other than the one processing
+    /// messages.
+    ///
+    /// Take req_id from the valid order ids.  TWS reports the exercise with order statuses for
+    /// req_id, which the returned receiver yields as ExerciseStatus until one is terminal;
+    /// they are still passed on to Wrapper::order_status as usual.  A rejection arrives
+    /// through Wrapper::error and the receiver ends without yielding.
+    ///
+    /// Returns an error with code TwsError::InvalidRequest if the request fails
+    /// OptionExercise::check, or if no contract or several contracts match the option.
+    ///
+    /// # Arguments
+    /// * req_id - The id of the lookup and of the exercise, which TWS reports as an order
+    /// * exercise - The option, action and quantity
+    /// * timeout - How long to wait for the details of the option
+    pub fn exercise(
+        &mut self,
+        req_id: i32,
+        exercise: &OptionExercise,
+        timeout: Duration,
+    ) -> Result<Receiver<ExerciseStatus>, IBKRApiLibError> {
+        exercise.check()?;
+        let found = self
+            .contract_details_bulk(req_id, std::slice::from_ref(&exercise.contract), 1, timeout)?
+            .pop()
+            .unwrap_or_else(|| Ok(vec![]))?;
+        let contract = match found.as_slice() {
+            [details] => details.contract.clone(),
+            _ => {
+                return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                    req_id,
+                    TwsError::InvalidRequest.code().to_string(),
+                    format!(
+                        "{} {} contracts match the option {} to exercise, give its con_id.",
+                        TwsError::InvalidRequest.message(),
+                        found.len(),
+                        exercise.contract.symbol
+                    ),
+                )));
+            }
+        };
+
+        let (tx, rx) = channel::<ExerciseStatus>();
+        self.pending
+            .exercises
+            .lock()
+            .expect(POISONED_MUTEX)
+            .insert(req_id, tx);
+
+        let sent = self.exercise_options(
+            req_id,
+            &contract,
+            exercise.action.code(),
+            exercise.quantity,
+            &exercise.account,
+            exercise.override_natural as i32,
+        );
+        if let Err(err) = sent {
+            self.pending
+                .exercises
+                .lock()
+                .expect(POISONED_MUTEX)
+                .remove(&req_id);
+            return Err(err);
+        }
+        Ok(rx)
+    }
+
     //#########################################################################
     //################## Orders
     //########################################################################
@@ -1095,6 +2105,21 @@ where
     /// * order - This structure contains the details of the order.
     ///
     /// Note: Each client MUST connect with a unique client_id.
+    ///
+    /// If precautionary settings are set, orders breaking them are not sent and an error
+    /// with code TwsError::PrecautionViolation is returned.  What-if orders are not checked.
+    /// Orders with routing attributes the contract doesn't accept are not sent either, see
+    /// order::check_order_attributes, nor are GTD orders without a good_till_date, or orders
+    /// of a type missing from the order_types of the details received for the contract, or
+    /// with an adjusted stop missing the prices it needs, see order::check_adjusted_order, or
+    /// PEG BENCH orders without their reference, see order::check_pegged_to_benchmark, or
+    /// hedge orders without their parent or hedge_param, see order::check_hedge_order, or
+    /// scale orders breaking the constraints of IB, see order::check_scale_order, or orders
+    /// naming both a person and an algo for a MiFID II field, see order::check_mifid2, or
+    /// cash quantity orders other than MKT and LMT, see order::check_cash_quantity, or crypto
+    /// orders IB does not accept, see order::check_crypto_order, or delta neutral orders
+    /// missing what TWS needs to hedge them, see order::check_delta_neutral.  Orders on a
+    /// continuous future are rejected too, place them on its contract::front_month instead.
     pub fn place_order(
         &mut self,
         order_id: i32,
@@ -1102,6 +2127,37 @@ where
         order: &Order,
     ) -> Result<(), IBKRApiLibError> {
         self.check_connected(NO_VALID_ID)?;
+        self.check_contract_codes(order_id, contract)?;
+        let started = Instant::now();
+
+        if let Some(precautions) = self.precautions.as_ref() {
+            if !order.what_if {
+                precautions.check(order_id, contract, order, None)?;
+            }
+        }
+        check_order_attributes(order_id, contract, order)?;
+        check_time_in_force(order_id, order)?;
+        check_adjusted_order(order_id, order)?;
+        check_pegged_to_benchmark(order_id, order)?;
+        check_hedge_order(order_id, order)?;
+        check_scale_order(order_id, order)?;
+        check_mifid2(order_id, order)?;
+        check_cash_quantity(order_id, order)?;
+        check_crypto_order(order_id, contract, order)?;
+        check_delta_neutral(order_id, contract, order)?;
+        self.check_order_type(order_id, contract, order)?;
+
+        if contract.sec_type.is_continuous() {
+            return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                order_id,
+                TwsError::InvalidRequest.code().to_string(),
+                format!(
+                    "{} {} contracts can't be traded, order their front month FUT.",
+                    TwsError::InvalidRequest.message(),
+                    contract.sec_type
+                ),
+            )));
+        }
 
         if self.server_version() < MIN_SERVER_VER_DELTA_NEUTRAL {
             if contract.delta_neutral_contract.is_some() {
@@ -1135,6 +2191,38 @@ where
             return Err(err);
         }
 
+        if self.server_version() < MIN_SERVER_VER_PEGGED_TO_BENCHMARK
+            && order.order_type == OrderType::PegBench
+        {
+            let err = IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                order_id,
+                TwsError::UpdateTws.code().to_string(),
+                format!(
+                    "{}{}",
+                    TwsError::UpdateTws.message(),
+                    " It does not support pegged to benchmark orders."
+                ),
+            ));
+
+            return Err(err);
+        }
+
+        if self.server_version() < MIN_SERVER_VER_PEGGED_TO_BENCHMARK
+            && order.adjusted_order_type != OrderType::None
+        {
+            let err = IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                order_id,
+                TwsError::UpdateTws.code().to_string(),
+                format!(
+                    "{}{}",
+                    TwsError::UpdateTws.message(),
+                    " It does not support adjusted orders."
+                ),
+            ));
+
+            return Err(err);
+        }
+
         if self.server_version() < MIN_SERVER_VER_ALGO_ORDERS && !order.algo_strategy.is_empty() {
             let err = IBKRApiLibError::ApiError(TwsApiReportableError::new(
                 order_id,
@@ -1223,7 +2311,7 @@ where
                 return Err(err);
             }
         }
-        if self.server_version() < MIN_SERVER_VER_HEDGE_ORDERS && !order.hedge_type.is_empty() {
+        if self.server_version() < MIN_SERVER_VER_HEDGE_ORDERS && order.hedge_type.is_set() {
             let err = IBKRApiLibError::ApiError(TwsApiReportableError::new(
                 order_id,
                 TwsError::UpdateTws.code().to_string(),
@@ -1317,7 +2405,7 @@ where
         }
 
         if self.server_version() < MIN_SERVER_VER_ORDER_COMBO_LEGS_PRICE
-            && contract.sec_type == "BAG"
+            && contract.sec_type == SecType::BAG
             && order.order_combo_legs.len() > 0
             && order
                 .order_combo_legs
@@ -1444,7 +2532,7 @@ where
         }
 
         if self.server_version() < MIN_SERVER_VER_SOFT_DOLLAR_TIER
-            && (!order.soft_dollar_tier.name.is_empty() || !order.soft_dollar_tier.val.is_empty())
+            && order.soft_dollar_tier.is_set()
         {
             let err = IBKRApiLibError::ApiError(TwsApiReportableError::new(
                 order_id,
@@ -1459,7 +2547,7 @@ where
             return Err(err);
         }
 
-        if self.server_version() < MIN_SERVER_VER_CASH_QTY && order.cash_qty != 0.0 {
+        if self.server_version() < MIN_SERVER_VER_CASH_QTY && order.cash_qty != UNSET_DOUBLE {
             let err = IBKRApiLibError::ApiError(TwsApiReportableError::new(
                 order_id,
                 TwsError::UpdateTws.code().to_string(),
@@ -1680,7 +2768,7 @@ where
         msg.push_str(&make_field(&order.hidden)?); // srv v7 && above
 
         // Send combo legs for BAG requests (srv v8 && above)
-        if contract.sec_type == "BAG" {
+        if contract.sec_type == SecType::BAG {
             let combo_legs_count = contract.combo_legs.len();
             msg.push_str(&make_field(&combo_legs_count)?);
             if combo_legs_count > 0 {
@@ -1701,7 +2789,7 @@ where
 
         // Send order combo legs for BAG requests
         if self.server_version() >= MIN_SERVER_VER_ORDER_COMBO_LEGS_PRICE
-            && contract.sec_type == "BAG"
+            && contract.sec_type == SecType::BAG
         {
             let order_combo_legs_count = order.order_combo_legs.len();
 
@@ -1714,7 +2802,7 @@ where
         }
 
         if self.server_version() >= MIN_SERVER_VER_SMART_COMBO_ROUTING_PARAMS
-            && contract.sec_type == "BAG"
+            && contract.sec_type == SecType::BAG
         {
             let smart_combo_routing_params_count = order.smart_combo_routing_params.len();
             msg.push_str(&make_field(&smart_combo_routing_params_count)?);
@@ -1743,8 +2831,8 @@ where
         msg.push_str(&make_field(&"")?); // srv v9 && above
 
         msg.push_str(&make_field(&order.discretionary_amt)?); // srv v10 && above
-        msg.push_str(&make_field(&order.good_after_time)?); // srv v11 && above
-        msg.push_str(&make_field(&order.good_till_date)?); // srv v12 && above
+        msg.push_str(&make_field(&timestamp_to_wire(&order.good_after_time))?); // srv v11 && above
+        msg.push_str(&make_field(&timestamp_to_wire(&order.good_till_date))?); // srv v12 && above
 
         msg.push_str(&make_field(&order.fa_group)?); // srv v13 && above
         msg.push_str(&make_field(&order.fa_method)?); // srv v13 && above
@@ -1796,7 +2884,7 @@ where
         msg.push_str(&make_field_handle_empty(&order.delta_neutral_aux_price)?); // srv v28 && above
 
         if self.server_version() >= MIN_SERVER_VER_DELTA_NEUTRAL_CONID
-            && !order.delta_neutral_order_type.is_empty()
+            && order.delta_neutral_order_type != OrderType::None
         {
             msg.push_str(&make_field(&order.delta_neutral_con_id)?);
             msg.push_str(&make_field(&order.delta_neutral_settling_firm)?);
@@ -1805,7 +2893,7 @@ where
         }
 
         if self.server_version() >= MIN_SERVER_VER_DELTA_NEUTRAL_OPEN_CLOSE
-            && order.delta_neutral_order_type != ""
+            && order.delta_neutral_order_type != OrderType::None
         {
             msg.push_str(&make_field(&order.delta_neutral_open_close)?);
             msg.push_str(&make_field(&order.delta_neutral_short_sale)?);
@@ -1859,7 +2947,7 @@ where
         if self.server_version() >= MIN_SERVER_VER_HEDGE_ORDERS {
             msg.push_str(&make_field(&order.hedge_type)?);
 
-            if !order.hedge_type.is_empty() {
+            if order.hedge_type.is_set() {
                 msg.push_str(&make_field(&order.hedge_param)?);
             }
         }
@@ -1934,7 +3022,7 @@ where
         }
 
         if self.server_version() >= MIN_SERVER_VER_PEGGED_TO_BENCHMARK {
-            if order.order_type == "PEG BENCH" {
+            if order.order_type == OrderType::PegBench {
                 msg.push_str(&make_field(&order.reference_contract_id)?);
                 msg.push_str(&make_field(&order.is_pegged_change_amount_decrease)?);
                 msg.push_str(&make_field(&order.pegged_change_amount)?);
@@ -1971,11 +3059,11 @@ where
 
         if self.server_version() >= MIN_SERVER_VER_SOFT_DOLLAR_TIER {
             msg.push_str(&make_field(&order.soft_dollar_tier.name)?);
-            msg.push_str(&make_field(&order.soft_dollar_tier.val)?);
+            msg.push_str(&make_field(&order.soft_dollar_tier.value)?);
         }
 
         if self.server_version() >= MIN_SERVER_VER_CASH_QTY {
-            msg.push_str(&make_field(&order.cash_qty)?);
+            msg.push_str(&make_field_handle_empty(&order.cash_qty)?);
         }
 
         if self.server_version() >= MIN_SERVER_VER_DECISION_MAKER {
@@ -2016,10 +3104,230 @@ where
             msg.push_str(&make_field(&order.auto_cancel_parent)?);
         }
 
-        self.send_request(msg.as_str())?;
+        let bytes = make_message(msg.as_str())?;
+        if !order.what_if {
+            self.record_intent(OrderIntent::place(order_id, contract, order))?;
+        }
+        self.pending
+            .order_latency
+            .lock()
+            .expect(POISONED_MUTEX)
+            .record_encoded(order_id, started, Instant::now());
+        self.pace_request();
+        self.send_bytes(bytes.as_slice())?;
+        self.pending
+            .order_latency
+            .lock()
+            .expect(POISONED_MUTEX)
+            .record_written(order_id, Instant::now());
+        debug!(
+            target: ORDERS,
+            "place_order: sent order {} for account {}",
+            order_id,
+            self.display_name(&order.account)
+        );
         Ok(())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Places an order after checking it against the current quote.  The precautionary
+    /// settings check the price deviation from the quote midpoint, or the last price without
+    /// one, and the marketability check set with set_marketability_check classifies the limit
+    /// price.  What-if orders are not checked.
+    ///
+    /// # Arguments
+    /// * order_id - The order id, as for place_order
+    /// * contract - The contract being traded
+    /// * order - The order
+    /// * quote - The current quote of the contract, e.g. from snapshot or a Quote kept up to
+    ///   date from tick_price and tick_size
+    pub fn place_order_against_quote(
+        &mut self,
+        order_id: i32,
+        contract: &Contract,
+        order: &Order,
+        quote: &Quote,
+    ) -> Result<Option<Marketability>, IBKRApiLibError> {
+        let mut marketability = None;
+        if !order.what_if {
+            if let Some(precautions) = self.precautions.as_ref() {
+                let market_price = quote
+                    .midpoint()
+                    .or_else(|| Some(quote.last).filter(|last| *last != UNSET_DOUBLE));
+                precautions.check(order_id, contract, order, market_price)?;
+            }
+            if let Some(check) = self.marketability.as_ref() {
+                marketability = check.check(order_id, order, quote)?;
+            }
+        }
+        self.place_order(order_id, contract, order)?;
+        Ok(marketability)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Time spent placing an order: encoding, writing to the socket and waiting for the first
+    /// order status.  Kept for the last MAX_TRACKED_ORDERS orders.
+    ///
+    /// # Arguments
+    /// * order_id - the order id passed to place_order
+    pub fn order_latency(&self, order_id: i32) -> Option<OrderLatency> {
+        self.pending
+            .order_latency
+            .lock()
+            .expect(POISONED_MUTEX)
+            .get(order_id)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Percentiles of the place order latencies of the tracked orders
+    pub fn order_latency_stats(&self) -> OrderLatencyStats {
+        self.pending
+            .order_latency
+            .lock()
+            .expect(POISONED_MUTEX)
+            .stats()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Call this function to preview the margin and commission impact of an order without
+    /// transmitting it. The order is sent with what_if set, and the returned receiver
+    /// yields a MarginPreview once TWS answers with the corresponding open order. The
+    /// open order is still passed on to Wrapper::open_order as usual.
+    ///
+    /// If TWS rejects the order, the rejection arrives through Wrapper::error and the receiver
+    /// ends without yielding.
+    ///
+    /// # Arguments
+    /// * order_id - A unique order id, as for place_order
+    /// * contract - The contract the order would trade
+    /// * order - The order to preview. It is copied and what_if is set on the copy.
+    pub fn what_if(
+        &mut self,
+        order_id: i32,
+        contract: &Contract,
+        order: &Order,
+    ) -> Result<Receiver<MarginPreview>, IBKRApiLibError> {
+        let mut what_if_order = order.clone();
+        what_if_order.what_if = true;
+
+        let (tx, rx) = channel::<MarginPreview>();
+        self.pending
+            .what_if
+            .lock()
+            .expect(POISONED_MUTEX)
+            .insert(order_id, tx);
+
+        if let Err(err) = self.place_order(order_id, contract, &what_if_order) {
+            self.pending
+                .what_if
+                .lock()
+                .expect(POISONED_MUTEX)
+                .remove(&order_id);
+            return Err(err);
+        }
+        Ok(rx)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Places an order on a contract with a delta_neutral_contract, e.g. a delta neutral combo
+    /// hedged with its underlying.  TWS confirms the order with the delta and price it locks
+    /// for the hedge, the current ones if those of the delta_neutral_contract are 0, and the
+    /// returned receiver yields them.  The confirmation is still passed on to
+    /// Wrapper::delta_neutral_validation as usual.
+    ///
+    /// Returns an error with code TwsError::InvalidRequest if the contract has no
+    /// delta_neutral_contract, or if place_order rejects the order.
+    ///
+    /// # Arguments
+    /// * order_id - A unique order id, as for place_order
+    /// * contract - The contract to trade, with its delta_neutral_contract
+    /// * order - The order to place
+    pub fn place_delta_neutral(
+        &mut self,
+        order_id: i32,
+        contract: &Contract,
+        order: &Order,
+    ) -> Result<Receiver<DeltaNeutralContract>, IBKRApiLibError> {
+        if contract.delta_neutral_contract.is_none() {
+            return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                order_id,
+                TwsError::InvalidRequest.code().to_string(),
+                format!(
+                    "{} The contract of a delta neutral order needs a delta_neutral_contract.",
+                    TwsError::InvalidRequest.message()
+                ),
+            )));
+        }
+
+        let (tx, rx) = channel::<DeltaNeutralContract>();
+        self.pending
+            .delta_neutral_validations
+            .lock()
+            .expect(POISONED_MUTEX)
+            .insert(order_id, tx);
+
+        if let Err(err) = self.place_order(order_id, contract, order) {
+            self.pending
+                .delta_neutral_validations
+                .lock()
+                .expect(POISONED_MUTEX)
+                .remove(&order_id);
+            return Err(err);
+        }
+        Ok(rx)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Previews a list of hypothetical orders one after the other and aggregates their margin
+    /// impact, e.g. to check that a rebalance is feasible before placing its orders.  Blocks
+    /// until every order was answered or timed out.  See what_if::WhatIfScenario.
+    ///
+    /// # Arguments
+    /// * orders - The contracts and orders to preview
+    /// * next_order_id - The id for the first order, incremented for each order
+    /// * timeout - How long to wait for the preview of each order
+    /// * pacing - The wait between two orders, e.g. what_if::WHAT_IF_PACING
+    pub fn what_if_scenario(
+        &mut self,
+        orders: &[(Contract, Order)],
+        next_order_id: &mut i32,
+        timeout: Duration,
+        pacing: Duration,
+    ) -> Result<WhatIfScenario, IBKRApiLibError> {
+        let mut contributions = Vec::with_capacity(orders.len());
+        for (index, (contract, order)) in orders.iter().enumerate() {
+            if index > 0 {
+                thread::sleep(pacing);
+            }
+            let order_id = *next_order_id;
+            *next_order_id += 1;
+            let previews = self.what_if(order_id, contract, order)?;
+            let preview = match previews.recv_timeout(timeout) {
+                Ok(preview) => Some(preview),
+                Err(RecvTimeoutError::Disconnected) => {
+                    warn!(target: ORDERS, "TWS rejected the what-if order {}", order_id);
+                    None
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    warn!(
+                        target: ORDERS,
+                        "No what-if preview of order {} within {:?}", order_id, timeout
+                    );
+                    self.pending
+                        .what_if
+                        .lock()
+                        .expect(POISONED_MUTEX)
+                        .remove(&order_id);
+                    None
+                }
+            };
+            contributions.push(WhatIfContribution::new(order_id, contract, order, preview));
+        }
+        let scenario = WhatIfScenario::new(contributions);
+        info!(target: ORDERS, "What-if scenario -- {}", scenario);
+        Ok(scenario)
+    }
+
     //----------------------------------------------------------------------------------------------
     /// Call this function to cancel an order.
     /// # Arguments
@@ -2037,6 +3345,7 @@ where
         msg.push_str(&make_field(&version)?);
         msg.push_str(&make_field(&order_id)?);
 
+        self.record_intent(OrderIntent::cancel(order_id))?;
         self.send_request(msg.as_str())?;
         Ok(())
     }
@@ -2136,6 +3445,7 @@ where
         msg.push_str(&make_field(&message_id)?);
         msg.push_str(&make_field(&version)?);
 
+        self.record_intent(OrderIntent::global_cancel())?;
         self.send_request(msg.as_str())?;
 
         Ok(())
@@ -2152,7 +3462,7 @@ where
     /// * num_ids - deprecated
     pub fn req_ids(&mut self, num_ids: i32) -> Result<(), IBKRApiLibError> {
         self.check_connected(NO_VALID_ID)?;
-        info!("req_ids is connected...");
+        info!(target: ORDERS, "req_ids is connected...");
         let version = 1;
 
         let mut msg = "".to_string();
@@ -2162,7 +3472,7 @@ where
         msg.push_str(&make_field(&message_id)?);
         msg.push_str(&make_field(&version)?);
         msg.push_str(&make_field(&num_ids)?);
-        info!("req_ids... sending request...");
+        info!(target: ORDERS, "req_ids... sending request...");
         self.send_request(msg.as_str())?;
         Ok(())
     }
@@ -2206,6 +3516,11 @@ where
 
         self.send_request(msg.as_str())?;
 
+        if subscribe {
+            self.add_subscription(Subscription::AccountUpdates(acct_code.to_string()));
+        } else {
+            self.remove_subscription(&Subscription::AccountUpdates(acct_code.to_string()));
+        }
         Ok(())
     }
 
@@ -2223,12 +3538,12 @@ where
     /// * group_name - Set to All to return account summary data for all
     ///                accounts, or set to a specific Advisor Account Group name that has
     ///                already been created in TWS Global Configuration.
-    /// * tags- A comma-separated list of account tags.  See the AccountSummaryTags enum for valid values
+    /// * tags - The account tags to request
     pub fn req_account_summary(
         &mut self,
         req_id: i32,
         group_name: &str,
-        tags: &str,
+        tags: &AccountSummaryTagSet,
     ) -> Result<(), IBKRApiLibError> {
         self.check_connected(NO_VALID_ID)?;
 
@@ -2241,9 +3556,11 @@ where
         msg.push_str(&make_field(&version)?);
         msg.push_str(&make_field(&req_id)?);
         msg.push_str(&make_field(&String::from(group_name))?);
-        msg.push_str(&make_field(&String::from(tags))?);
+        msg.push_str(&make_field(&tags.to_string())?);
 
+        self.record_request_context(req_id, "req_account_summary", None);
         self.send_request(msg.as_str())?;
+        self.add_subscription(Subscription::AccountSummary(req_id));
         Ok(())
     }
 
@@ -2264,6 +3581,7 @@ where
 
         self.send_request(msg.as_str())?;
 
+        self.remove_subscription(&Subscription::AccountSummary(req_id));
         Ok(())
     }
 
@@ -2295,6 +3613,7 @@ where
 
         self.send_request(msg.as_str())?;
 
+        self.add_subscription(Subscription::Positions);
         Ok(())
     }
 
@@ -2325,6 +3644,7 @@ where
         msg.push_str(&make_field(&version)?);
         self.send_request(msg.as_str())?;
 
+        self.remove_subscription(&Subscription::Positions);
         Ok(())
     }
 
@@ -2371,8 +3691,10 @@ where
         msg.push_str(&make_field(&String::from(mut_account))?);
         msg.push_str(&make_field(&String::from(mut_model_code))?);
 
+        self.record_request_context(req_id, "req_positions_multi", None);
         self.send_request(msg.as_str())?;
 
+        self.add_subscription(Subscription::PositionsMulti(req_id));
         Ok(())
     }
 
@@ -2407,6 +3729,7 @@ where
         msg.push_str(&make_field(&mut_req_id)?);
 
         self.send_request(msg.as_str())?;
+        self.remove_subscription(&Subscription::PositionsMulti(req_id));
         Ok(())
     }
 
@@ -2457,8 +3780,10 @@ where
         msg.push_str(&make_field(&String::from(mut_model_code))?);
         msg.push_str(&make_field(&mut_ledger_and_nlv)?);
 
+        self.record_request_context(req_id, "req_account_updates_multi", None);
         self.send_request(msg.as_str())?;
 
+        self.add_subscription(Subscription::AccountUpdatesMulti(req_id));
         Ok(())
     }
 
@@ -2492,7 +3817,9 @@ where
         msg.push_str(&make_field(&version)?);
         msg.push_str(&make_field(&mut_req_id)?);
 
-        self.send_request(msg.as_str())
+        self.send_request(msg.as_str())?;
+        self.remove_subscription(&Subscription::AccountUpdatesMulti(req_id));
+        Ok(())
     }
 
     //#########################################################################
@@ -2533,7 +3860,10 @@ where
         msg.push_str(&make_field(&String::from(account))?);
         msg.push_str(&make_field(&String::from(model_code))?);
 
-        self.send_request(msg.as_str())
+        self.record_request_context(req_id, "req_pnl", None);
+        self.send_request(msg.as_str())?;
+        self.add_subscription(Subscription::Pnl(req_id));
+        Ok(())
     }
 
     //----------------------------------------------------------------------------------------------
@@ -2563,7 +3893,9 @@ where
         msg.push_str(&make_field(&message_id)?);
         msg.push_str(&make_field(&req_id)?);
 
-        self.send_request(msg.as_str())
+        self.send_request(msg.as_str())?;
+        self.remove_subscription(&Subscription::Pnl(req_id));
+        Ok(())
     }
 
     //----------------------------------------------------------------------------------------------
@@ -2605,7 +3937,10 @@ where
         msg.push_str(&make_field(&String::from(model_code))?);
         msg.push_str(&make_field(&con_id)?);
 
-        self.send_request(msg.as_str())
+        self.record_request_context(req_id, "req_pnl_single", None);
+        self.send_request(msg.as_str())?;
+        self.add_subscription(Subscription::PnlSingle(req_id));
+        Ok(())
     }
 
     //----------------------------------------------------------------------------------------------
@@ -2635,7 +3970,9 @@ where
         msg.push_str(&make_field(&message_id)?);
         msg.push_str(&make_field(&req_id)?);
 
-        self.send_request(msg.as_str())
+        self.send_request(msg.as_str())?;
+        self.remove_subscription(&Subscription::PnlSingle(req_id));
+        Ok(())
     }
 
     //#########################################################################
@@ -2700,6 +4037,7 @@ where
         contract: &Contract,
     ) -> Result<(), IBKRApiLibError> {
         self.check_connected(req_id)?;
+        self.check_contract_codes(req_id, contract)?;
 
         if self.server_version() < MIN_SERVER_VER_SEC_ID_TYPE {
             if contract.sec_id_type != "" || contract.sec_id != "" {
@@ -2794,12 +4132,136 @@ where
             msg.push_str(&make_field(&contract.include_expired)?); // srv v31 and above
         }
 
-        if self.server_version() >= MIN_SERVER_VER_SEC_ID_TYPE {
-            msg.push_str(&make_field(&contract.sec_id_type)?);
-            msg.push_str(&make_field(&contract.sec_id)?);
+        if self.server_version() >= MIN_SERVER_VER_SEC_ID_TYPE {
+            msg.push_str(&make_field(&contract.sec_id_type)?);
+            msg.push_str(&make_field(&contract.sec_id)?);
+        }
+
+        self.record_request_context(req_id, "req_contract_details", Some(contract));
+        self.send_request(msg.as_str())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Requests the details of many contracts, keeping at most max_in_flight requests
+    /// outstanding and sending them CONTRACT_DETAILS_PACING apart.  Blocks until every request
+    /// has ended, so call it from a thread other than the one processing messages.  The details
+    /// are also passed to the wrapper's contract_details as they arrive.
+    ///
+    /// Returns the details found for each contract, in the order of contracts, or the error
+    /// its request failed with; an ambiguous contract has several details, an unknown one
+    /// fails with error 200.
+    ///
+    /// # Arguments
+    /// * first_req_id - the request ids are consecutive from this one, one per contract
+    /// * contracts - the contracts to look up
+    /// * max_in_flight - most requests outstanding at once
+    /// * timeout - how long each request may take from when it was sent before it fails
+    pub fn contract_details_bulk(
+        &mut self,
+        first_req_id: i32,
+        contracts: &[Contract],
+        max_in_flight: usize,
+        timeout: Duration,
+    ) -> Result<Vec<Result<Vec<ContractDetails>, IBKRApiLibError>>, IBKRApiLibError> {
+        let (sender, receiver) = channel();
+        let mut results: Vec<Option<Result<Vec<ContractDetails>, IBKRApiLibError>>> =
+            contracts.iter().map(|_| None).collect();
+        let mut found: HashMap<usize, Vec<ContractDetails>> = HashMap::new();
+        // the index of the contract of each request, and when the request was sent
+        let mut in_flight: HashMap<i32, (usize, Instant)> = HashMap::new();
+        let mut next = 0;
+        let mut last_sent: Option<Instant> = None;
+
+        while next < contracts.len() || !in_flight.is_empty() {
+            while next < contracts.len() && in_flight.len() < max_in_flight.max(1) {
+                if let Some(wait) = last_sent
+                    .map(|sent| CONTRACT_DETAILS_PACING.saturating_sub(sent.elapsed()))
+                    .filter(|wait| *wait > Duration::from_secs(0))
+                {
+                    thread::sleep(wait);
+                }
+                let req_id = first_req_id + next as i32;
+                self.pending
+                    .contract_details
+                    .lock()
+                    .expect(POISONED_MUTEX)
+                    .insert(req_id, sender.clone());
+                match self.req_contract_details(req_id, &contracts[next]) {
+                    Ok(()) => {
+                        in_flight.insert(req_id, (next, Instant::now()));
+                    }
+                    Err(err) => {
+                        self.pending
+                            .contract_details
+                            .lock()
+                            .expect(POISONED_MUTEX)
+                            .remove(&req_id);
+                        results[next] = Some(Err(err));
+                    }
+                }
+                last_sent = Some(Instant::now());
+                next += 1;
+            }
+            let oldest = match in_flight.values().map(|(_, sent)| *sent).min() {
+                Some(oldest) => oldest,
+                None => continue,
+            };
+
+            match receiver
+                .recv_timeout((oldest + timeout).saturating_duration_since(Instant::now()))
+            {
+                Ok((req_id, event)) => {
+                    let index = match in_flight.get(&req_id) {
+                        Some((index, _)) => *index,
+                        None => continue,
+                    };
+                    match event {
+                        ContractDetailsEvent::Details(details) => {
+                            found.entry(index).or_default().push(*details)
+                        }
+                        ContractDetailsEvent::End => {
+                            in_flight.remove(&req_id);
+                            results[index] = Some(Ok(found.remove(&index).unwrap_or_default()));
+                        }
+                        ContractDetailsEvent::Error(message) => {
+                            in_flight.remove(&req_id);
+                            results[index] =
+                                Some(Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                                    req_id,
+                                    message.code.to_string(),
+                                    message.message,
+                                ))));
+                        }
+                    }
+                }
+                Err(_) => {
+                    let expired: Vec<i32> = in_flight
+                        .iter()
+                        .filter(|(_, (_, sent))| sent.elapsed() >= timeout)
+                        .map(|(req_id, _)| *req_id)
+                        .collect();
+                    warn!(
+                        target: PACING,
+                        "contract_details_bulk: no response in {:?}, failing {} requests",
+                        timeout,
+                        expired.len()
+                    );
+                    let mut lookups = self.pending.contract_details.lock().expect(POISONED_MUTEX);
+                    for req_id in expired {
+                        lookups.remove(&req_id);
+                        if let Some((index, _)) = in_flight.remove(&req_id) {
+                            results[index] = Some(Err(IBKRApiLibError::RecvTimeoutError(
+                                RecvTimeoutError::Timeout,
+                            )));
+                        }
+                    }
+                }
+            }
         }
-
-        self.send_request(msg.as_str())
+        Ok(results
+            .into_iter()
+            .map(|result| result.expect("every contract has a result"))
+            .collect())
     }
 
     //#########################################################################
@@ -2860,6 +4322,7 @@ where
         mkt_depth_options: Vec<TagValue>,
     ) -> Result<(), IBKRApiLibError> {
         self.check_connected(NO_VALID_ID)?;
+        self.check_contract_codes(req_id, contract)?;
 
         if self.server_version() < MIN_SERVER_VER_TRADING_CLASS {
             if &contract.trading_class != "" || *&contract.con_id > 0 {
@@ -2962,7 +4425,13 @@ where
             let mkt_data_options_str = "";
             msg.push_str(&make_field(&mkt_data_options_str)?);
         }
-        self.send_request(msg.as_str())
+        self.record_request_context(req_id, "req_mkt_depth", Some(contract));
+        self.send_request(msg.as_str())?;
+        self.add_subscription(Subscription::MktDepth {
+            req_id,
+            is_smart_depth,
+        });
+        Ok(())
     }
 
     //----------------------------------------------------------------------------------------------
@@ -3006,7 +4475,12 @@ where
             msg.push_str(&make_field(&is_smart_depth)?);
         }
 
-        self.send_request(msg.as_str())
+        self.send_request(msg.as_str())?;
+        self.remove_subscription(&Subscription::MktDepth {
+            req_id,
+            is_smart_depth,
+        });
+        Ok(())
     }
 
     //#########################################################################
@@ -3031,6 +4505,7 @@ where
         msg.push_str(&make_field(&all_msgs)?);
 
         self.send_request(msg.as_str())?;
+        self.add_subscription(Subscription::NewsBulletins);
         Ok(())
     }
 
@@ -3046,9 +4521,42 @@ where
         msg.push_str(&make_field(&message_id)?);
         msg.push_str(&make_field(&version)?);
         self.send_request(msg.as_str())?;
+        self.remove_subscription(&Subscription::NewsBulletins);
+        self.pending
+            .news_bulletins
+            .lock()
+            .expect(POISONED_MUTEX)
+            .clear();
         Ok(())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Starts the news bulletins with req_news_bulletins and returns a handle receiving them.
+    /// The bulletins are still passed to Wrapper::update_news_bulletin as well.
+    ///
+    /// # Arguments
+    /// * all_msgs - whether to receive the bulletins of the current day before new ones
+    pub fn subscribe_news_bulletins(
+        &mut self,
+        all_msgs: bool,
+    ) -> Result<NewsBulletins, IBKRApiLibError> {
+        let (tx, rx) = channel::<Bulletin>();
+        self.pending
+            .news_bulletins
+            .lock()
+            .expect(POISONED_MUTEX)
+            .push(tx);
+        if let Err(err) = self.req_news_bulletins(all_msgs) {
+            self.pending
+                .news_bulletins
+                .lock()
+                .expect(POISONED_MUTEX)
+                .pop();
+            return Err(err);
+        }
+        Ok(NewsBulletins::new(rx))
+    }
+
     //#########################################################################
     //################## Financial Advisors
     //#########################################################################
@@ -3115,7 +4623,7 @@ where
         msg.push_str(&make_field(&message_id)?);
 
         msg.push_str(&make_field(&version)?);
-        msg.push_str(&make_field(&fa_data)?);
+        msg.push_str(&make_field(&(fa_data as i32))?);
         msg.push_str(&make_field(&String::from(cxml))?);
 
         if self.server_version() >= MIN_SERVER_VER_REPLACE_FA_END {
@@ -3125,6 +4633,16 @@ where
         self.send_request(msg.as_str())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Replaces the FA groups, profiles or aliases with a typed configuration, see replace_fa
+    pub fn replace_fa_config<C: FaConfig>(
+        &mut self,
+        req_id: i32,
+        config: &C,
+    ) -> Result<(), IBKRApiLibError> {
+        self.replace_fa(req_id, C::fa_data_type(), &config.to_xml())
+    }
+
     //#########################################################################
     //################## Historical Data
     //#########################################################################
@@ -3141,23 +4659,10 @@ where
     /// * end_date_time - Defines a query end date and time at any point during the past 6 mos.
     ///                   Valid values include any date/time within the past six months in the format:
     ///                   yyyymmdd HH:mm:ss ttt where "ttt" is the optional time zone.
-    /// * duration_str - Set the query duration up to one week, using a time unit
-    ///                  of seconds, days or weeks. Valid values include any integer followed by a space
-    ///                  and then S (seconds)); D (days) or W (week). If no unit is specified, seconds is used.
-    /// * bar_size_setting - See the BarSize enum for valid values. Specifies the size of the bars that will be returned (within IB/TWS listimits).
-    ///                      Valid values include:
-    ///     * 1 sec
-    ///     * 5 secs
-    ///     * 15 secs
-    ///     * 30 secs
-    ///     * 1 min
-    ///     * 2 mins
-    ///     * 3 mins
-    ///     * 5 mins
-    ///     * 15 mins
-    ///     * 30 mins
-    ///     * 1 hour
-    ///     * 1 day
+    /// * duration - How far back from end_date_time the query goes, e.g. Duration::Days(10).
+    ///              Sent as IB's duration string, e.g. "10 D".
+    /// * bar_size - The size of the bars that will be returned, e.g. BarSize::_1Min.  Sent as
+    ///              IB's bar size string, e.g. "1 min".
     /// * what_to_show - See the WhatToShow enum for valid values.  Determines the nature of data beinging extracted. Valid values include:
     ///
     ///     * TRADES
@@ -3167,6 +4672,14 @@ where
     ///     * BID_ASK
     ///     * HISTORICAL_VOLATILITY
     ///     * OPTION_IMPLIED_VOLATILITY
+    ///     * YIELD_BID, YIELD_ASK, YIELD_BID_ASK, YIELD_LAST (bonds)
+    ///     * ADJUSTED_LAST, FEE_RATE (stocks)
+    ///     * AGGTRADES (crypto)
+    ///     * SCHEDULE
+    ///
+    ///   WhatToShow::is_valid_for reports which of these apply to a given security type.  Types
+    ///   the server version does not serve, see WhatToShow::min_server_version, are rejected
+    ///   with an UpdateTws error.
     /// * use_rth - Determines whether to return all data available during the requested time span,
     ///             or only data that falls within regular trading hours. Valid values include:
     ///
@@ -3179,20 +4692,54 @@ where
     ///     * 1 - dates applying to bars returned in the format: yyyymmdd{space}{space}hh:mm:dd
     ///     * 2 - dates are returned as a long integer specifying the number of seconds since 1/1/1970 GMT.
     /// *chart_options: - For internal use only. Use default value XYZ.
+    ///
+    /// TWS serves CONTFUT bars up to now only, so CONTFUT requests with an end_date_time are
+    /// rejected with an InvalidRequest error, as are FUT+CONTFUT ones, a type for contract
+    /// details only.
     pub fn req_historical_data(
         &mut self,
         req_id: i32,
         contract: &Contract,
         end_date_time: &str,
-        duration_str: &str,
-        bar_size_setting: &str,
-        what_to_show: &str,
+        duration: &common::Duration,
+        bar_size: &BarSize,
+        what_to_show: &WhatToShow,
         use_rth: i32,
         format_date: i32,
         keep_up_to_date: bool,
         chart_options: Vec<TagValue>,
     ) -> Result<(), IBKRApiLibError> {
         self.check_connected(NO_VALID_ID)?;
+        self.check_contract_codes(req_id, contract)?;
+
+        let continuous_rejected = match contract.sec_type {
+            SecType::CONTFUT if !end_date_time.is_empty() => {
+                Some("CONTFUT historical data can't have an end_date_time")
+            }
+            SecType::FUTCONTFUT => Some("FUT+CONTFUT is only for contract details"),
+            _ => None,
+        };
+        if let Some(detail) = continuous_rejected {
+            return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                req_id,
+                TwsError::InvalidRequest.code().to_string(),
+                format!("{} {}.", TwsError::InvalidRequest.message(), detail),
+            )));
+        }
+
+        if let Some(min_server_version) = what_to_show.min_server_version() {
+            if self.server_version() < min_server_version {
+                return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                    req_id,
+                    TwsError::UpdateTws.code().to_string(),
+                    format!(
+                        "{} It does not support {} historical data.",
+                        TwsError::UpdateTws.message(),
+                        what_to_show
+                    ),
+                )));
+            }
+        }
 
         if self.server_version() < MIN_SERVER_VER_TRADING_CLASS {
             if &contract.trading_class != "" || contract.con_id > 0 {
@@ -3243,14 +4790,14 @@ where
         msg.push_str(&make_field(&contract.include_expired)?); // srv v31 and above
 
         msg.push_str(&make_field(&String::from(end_date_time))?); // srv v20 and above
-        msg.push_str(&make_field(&String::from(bar_size_setting))?); // srv v20 and above
-        msg.push_str(&make_field(&String::from(duration_str))?);
+        msg.push_str(&make_field(&bar_size.to_string())?); // srv v20 and above
+        msg.push_str(&make_field(&duration.to_string())?);
         msg.push_str(&make_field(&use_rth)?);
-        msg.push_str(&make_field(&String::from(what_to_show))?);
+        msg.push_str(&make_field(&what_to_show.to_string())?);
         msg.push_str(&make_field(&format_date)?); // srv v16 and above
 
         // Send combo legs for BAG requests
-        if contract.sec_type == "BAG" {
+        if contract.sec_type == SecType::BAG {
             msg.push_str(&make_field(&contract.combo_legs.len())?);
             for combo_leg in &contract.combo_legs {
                 msg.push_str(&make_field(&combo_leg.con_id)?);
@@ -3271,10 +4818,209 @@ where
             msg.push_str(&make_field(&chart_options_str)?);
         }
 
+        if let Some(bar_kind) = HistoricalBarKind::from_what_to_show(what_to_show) {
+            self.pending
+                .historical_bar_kinds
+                .lock()
+                .expect(POISONED_MUTEX)
+                .insert(req_id, bar_kind);
+        }
+
+        if let Some(pending) = self
+            .pending
+            .bar_time_zones
+            .lock()
+            .expect(POISONED_MUTEX)
+            .get_mut(&req_id)
+        {
+            pending.keep_up_to_date = keep_up_to_date;
+        }
+
+        self.record_request_context(req_id, "req_historical_data", Some(contract));
         self.send_request(msg.as_str())?;
+        if keep_up_to_date {
+            self.pending
+                .request_contexts
+                .lock()
+                .expect(POISONED_MUTEX)
+                .keep(req_id);
+            self.add_subscription(Subscription::HistoricalData(req_id));
+        }
         Ok(())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Downloads a range longer than TWS serves in one request.  The range is split into
+    /// chunks of the longest duration allowed for the bar size, requested one after another
+    /// with HISTORICAL_DATA_PACING between them, and the bars are returned in time order with
+    /// the duplicates at chunk boundaries removed.  Blocks until the download completes, so
+    /// call it from a thread other than the one processing messages.
+    ///
+    /// The bars are also passed to the wrapper's historical_data as they arrive.  Chunks for
+    /// which TWS has no data are skipped.  A pacing violation ends the download with its error.
+    ///
+    /// # Arguments
+    /// * req_id - the request id, reused for every chunk
+    /// * contract - the contract the bars are requested for
+    /// * start - start of the range
+    /// * end - end of the range
+    /// * bar_size - the bar size
+    /// * what_to_show - the type of data to retrieve.  Yield and fee rate data are not supported.
+    /// * use_rth - 1 for data within regular trading hours only, 0 for all data
+    /// * chunk_timeout - how long to wait for each chunk
+    #[allow(clippy::too_many_arguments)]
+    pub fn download_history(
+        &mut self,
+        req_id: i32,
+        contract: &Contract,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        bar_size: &BarSize,
+        what_to_show: &WhatToShow,
+        use_rth: i32,
+        chunk_timeout: Duration,
+    ) -> Result<Vec<BarData>, IBKRApiLibError> {
+        let chunks = history::plan_chunks(start, end, bar_size);
+        let mut downloaded = Vec::new();
+
+        for (i, chunk) in chunks.iter().enumerate() {
+            if i > 0 {
+                debug!(
+                    target: PACING,
+                    "download_history: waiting {:?} before chunk {} of {}",
+                    HISTORICAL_DATA_PACING,
+                    i + 1,
+                    chunks.len()
+                );
+                thread::sleep(HISTORICAL_DATA_PACING);
+            }
+            let (sender, receiver) = channel();
+            self.pending
+                .historical_downloads
+                .lock()
+                .expect(POISONED_MUTEX)
+                .insert(req_id, sender);
+
+            let result = self.req_historical_data(
+                req_id,
+                contract,
+                chunk.end_date_time.as_str(),
+                &chunk.duration,
+                bar_size,
+                what_to_show,
+                use_rth,
+                2,
+                false,
+                vec![],
+            );
+            let received = result.and_then(|_| Ok(receiver.recv_timeout(chunk_timeout)?));
+            if received.is_err() {
+                self.pending
+                    .historical_downloads
+                    .lock()
+                    .expect(POISONED_MUTEX)
+                    .remove(&req_id);
+            }
+            match received {
+                Ok(Ok(bars)) => downloaded.push(bars),
+                // a 162 reporting a pacing violation is not an empty chunk, and must not be
+                // cached as one
+                Ok(Err(message))
+                    if message.code == NO_HISTORICAL_DATA_CODE
+                        && IbError::from_code(message.code).category_of(&message.message)
+                            != ErrorCategory::Pacing => {}
+                Ok(Err(message)) => {
+                    return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                        req_id,
+                        message.code.to_string(),
+                        message.message,
+                    )))
+                }
+                Err(err) => {
+                    if let IBKRApiLibError::RecvTimeoutError(_) = err {
+                        self.cancel_historical_data(req_id)?;
+                    }
+                    return Err(err);
+                }
+            }
+        }
+        Ok(history::stitch(downloaded, start, end))
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Like download_history, but serves the range from an on-disk cache where it can and
+    /// downloads only the missing parts, which are then added to the cache.  Bars still forming,
+    /// and any part of the range after now, are downloaded again by the next call.
+    ///
+    /// # Arguments
+    /// * cache - the cache
+    /// * req_id - the request id, reused for every chunk
+    /// * contract - the contract the bars are requested for.  Must have its con_id set, as
+    ///   the cache is keyed by it.
+    /// * start - start of the range
+    /// * end - end of the range
+    /// * bar_size - the bar size
+    /// * what_to_show - the type of data to retrieve
+    /// * use_rth - 1 for data within regular trading hours only, 0 for all data
+    /// * chunk_timeout - how long to wait for each chunk
+    #[allow(clippy::too_many_arguments)]
+    pub fn download_history_cached(
+        &mut self,
+        cache: &HistoryCache,
+        req_id: i32,
+        contract: &Contract,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        bar_size: &BarSize,
+        what_to_show: &WhatToShow,
+        use_rth: i32,
+        chunk_timeout: Duration,
+    ) -> Result<Vec<BarData>, IBKRApiLibError> {
+        if contract.con_id <= 0 {
+            return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                req_id,
+                TwsError::BadMessage.code().to_string(),
+                format!(
+                    "{} The history cache needs the contract's con_id.",
+                    TwsError::BadMessage.message()
+                ),
+            )));
+        }
+        let key = HistoryCacheKey::new(contract.con_id, bar_size, what_to_show, use_rth);
+        let mut series = cache.load(&key)?;
+
+        for (missing_start, missing_end) in series.missing(start, end) {
+            let (missing_start_time, missing_end_time) = match (
+                Utc.timestamp_opt(missing_start, 0).single(),
+                Utc.timestamp_opt(missing_end, 0).single(),
+            ) {
+                (Some(from), Some(to)) => (from, to),
+                _ => continue,
+            };
+            let bars = self.download_history(
+                req_id,
+                contract,
+                missing_start_time,
+                missing_end_time,
+                bar_size,
+                what_to_show,
+                use_rth,
+                chunk_timeout,
+            )?;
+            let completed = completed_end(
+                missing_start,
+                missing_end,
+                &bars,
+                bar_size,
+                Utc::now().timestamp(),
+            );
+            series.insert(missing_start, completed, bars);
+            // save after every range so an error later keeps what was downloaded
+            cache.save(&key, &series)?;
+        }
+        Ok(series.bars(start, end))
+    }
+
     //----------------------------------------------------------------------------------------------
     /// Used if an internet disconnect has occurred or the results of a query
     /// are otherwise delayed and the client is no longer interested in receiving
@@ -3285,6 +5031,23 @@ where
     pub fn cancel_historical_data(&mut self, req_id: i32) -> Result<(), IBKRApiLibError> {
         self.check_connected(NO_VALID_ID)?;
 
+        self.pending
+            .historical_bar_kinds
+            .lock()
+            .expect(POISONED_MUTEX)
+            .remove(&req_id);
+        self.pending
+            .bar_time_zones
+            .lock()
+            .expect(POISONED_MUTEX)
+            .remove(&req_id);
+        // the bar in progress was already sent as InProgress, and never becomes final
+        self.pending
+            .bar_updates
+            .lock()
+            .expect(POISONED_MUTEX)
+            .remove(&req_id);
+
         let version = 1;
 
         let message_id: i32 = OutgoingMessageIds::CancelHistoricalData as i32;
@@ -3296,6 +5059,7 @@ where
 
         self.send_request(msg.as_str())?;
 
+        self.remove_subscription(&Subscription::HistoricalData(req_id));
         Ok(())
     }
 
@@ -3320,6 +5084,7 @@ where
         format_date: i32,
     ) -> Result<(), IBKRApiLibError> {
         self.check_connected(NO_VALID_ID)?;
+        self.check_contract_codes(req_id, contract)?;
 
         if self.server_version() < MIN_SERVER_VER_REQ_HEAD_TIMESTAMP {
             let err = IBKRApiLibError::ApiError(TwsApiReportableError::new(
@@ -3357,10 +5122,65 @@ where
         msg.push_str(&make_field(&String::from(what_to_show))?);
         msg.push_str(&make_field(&format_date)?);
 
+        self.record_request_context(req_id, "req_head_time_stamp", Some(contract));
         self.send_request(msg.as_str())?;
         Ok(())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// The time of the earliest data TWS has for a contract, blocking until it arrives.  The
+    /// request is cancelled if it does not arrive in time.  The timestamp is still passed to
+    /// Wrapper::head_timestamp as well.
+    ///
+    /// # Arguments
+    /// * req_id - the request's identifier
+    /// * contract - the contract for which the earliest data point is requested
+    /// * what_to_show - the type of data, e.g. WhatToShow::Trades
+    /// * use_rth - whether only data in regular trading hours counts
+    /// * timeout - how long to wait for the timestamp
+    pub fn earliest_data_point(
+        &mut self,
+        req_id: i32,
+        contract: &Contract,
+        what_to_show: &WhatToShow,
+        use_rth: bool,
+        timeout: Duration,
+    ) -> Result<DateTime<Utc>, IBKRApiLibError> {
+        let (tx, rx) = channel::<Result<DateTime<Utc>, TwsMessage>>();
+        self.pending
+            .head_timestamps
+            .lock()
+            .expect(POISONED_MUTEX)
+            .insert(req_id, tx);
+        // format_date 2 asks for epoch seconds, which need no time zone
+        let sent = self.req_head_time_stamp(
+            req_id,
+            contract,
+            &what_to_show.to_string(),
+            use_rth as i32,
+            2,
+        );
+        let received = sent.and_then(|_| match rx.recv_timeout(timeout)? {
+            Ok(head_timestamp) => Ok(head_timestamp),
+            Err(message) => Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                req_id,
+                message.code.to_string(),
+                message.message,
+            ))),
+        });
+        if received.is_err() {
+            self.pending
+                .head_timestamps
+                .lock()
+                .expect(POISONED_MUTEX)
+                .remove(&req_id);
+        }
+        if let Err(IBKRApiLibError::RecvTimeoutError(RecvTimeoutError::Timeout)) = received {
+            self.cancel_head_time_stamp(req_id)?;
+        }
+        received
+    }
+
     //----------------------------------------------------------------------------------------------
     /// Cancel the request
     ///
@@ -3390,6 +5210,7 @@ where
         msg.push_str(&make_field(&req_id)?);
 
         self.send_request(msg.as_str())?;
+        self.pending.forget_request(req_id);
         Ok(())
     }
 
@@ -3409,6 +5230,7 @@ where
         time_period: &str,
     ) -> Result<(), IBKRApiLibError> {
         self.check_connected(NO_VALID_ID)?;
+        self.check_contract_codes(ticker_id, contract)?;
 
         if self.server_version() < MIN_SERVER_VER_REQ_HISTOGRAM {
             let err = IBKRApiLibError::ApiError(TwsApiReportableError::new(
@@ -3445,10 +5267,47 @@ where
         msg.push_str(&make_field(&use_rth)?);
         msg.push_str(&make_field(&String::from(time_period))?);
 
+        self.record_request_context(ticker_id, "req_histogram_data", Some(contract));
         self.send_request(msg.as_str())?;
         Ok(())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Requests the histogram of a contract like req_histogram_data.  The returned receiver
+    /// yields the entries, with their fractional counts, once the histogram arrives; it is
+    /// still passed to Wrapper::histogram_data as well.
+    ///
+    /// If TWS answers with an error instead, the receiver ends without yielding.
+    ///
+    /// # Arguments
+    /// * req_id - an identifier for the request
+    /// * contract - Contract object for which histogram is being requested
+    /// * use_rth - use regular trading hours only
+    /// * time_period - period of which data is being requested, e.g. "3 days"
+    pub fn req_histogram(
+        &mut self,
+        req_id: i32,
+        contract: &Contract,
+        use_rth: bool,
+        time_period: &str,
+    ) -> Result<Receiver<Vec<HistogramEntry>>, IBKRApiLibError> {
+        let (tx, rx) = channel::<Vec<HistogramEntry>>();
+        self.pending
+            .histograms
+            .lock()
+            .expect(POISONED_MUTEX)
+            .insert(req_id, tx);
+        if let Err(err) = self.req_histogram_data(req_id, contract, use_rth, time_period) {
+            self.pending
+                .histograms
+                .lock()
+                .expect(POISONED_MUTEX)
+                .remove(&req_id);
+            return Err(err);
+        }
+        Ok(rx)
+    }
+
     //----------------------------------------------------------------------------------------------
     /// Cancel the request
     ///
@@ -3471,12 +5330,19 @@ where
             return Err(err);
         }
 
+        self.pending
+            .histograms
+            .lock()
+            .expect(POISONED_MUTEX)
+            .remove(&ticker_id);
+
         let message_id: i32 = OutgoingMessageIds::CancelHistogramData as i32;
         let mut msg = "".to_string();
         msg.push_str(&make_field(&message_id)?);
         msg.push_str(&make_field(&ticker_id)?);
 
         self.send_request(msg.as_str())?;
+        self.pending.forget_request(ticker_id);
         Ok(())
     }
 
@@ -3506,6 +5372,7 @@ where
         misc_options: Vec<TagValue>,
     ) -> Result<(), IBKRApiLibError> {
         self.check_connected(NO_VALID_ID)?;
+        self.check_contract_codes(req_id, contract)?;
 
         if self.server_version() < MIN_SERVER_VER_HISTORICAL_TICKS {
             let err = IBKRApiLibError::ApiError(TwsApiReportableError::new(
@@ -3553,6 +5420,7 @@ where
 
         msg.push_str(&make_field(&misc_options_string)?);
 
+        self.record_request_context(req_id, "req_historical_ticks", Some(contract));
         self.send_request(msg.as_str())?;
         Ok(())
     }
@@ -3593,7 +5461,7 @@ where
     ) -> Result<(), IBKRApiLibError> {
         self.check_connected(req_id)?;
 
-        error!("Server version: {}", self.server_version());
+        error!(target: MARKET_DATA, "Server version: {}", self.server_version());
         if self.server_version() < MIN_SERVER_VER_SCANNER_GENERIC_OPTS {
             let err = IBKRApiLibError::ApiError(TwsApiReportableError::new(
                 req_id,
@@ -3644,7 +5512,7 @@ where
 
         // Send scanner_subscription_filter_options parameter
         if self.server_version() >= MIN_SERVER_VER_SCANNER_GENERIC_OPTS {
-            error!("!!!!!!!! making scanner options");
+            error!(target: MARKET_DATA, "!!!!!!!! making scanner options");
             let scanner_subscription_filter = scanner_subscription_filter_options
                 .iter()
                 .map(|x| format!("{}={};", x.tag, x.value))
@@ -3660,9 +5528,11 @@ where
                 .collect::<String>();
             msg.push_str(&make_field(&scanner_subscription_options)?);
         }
-        error!("req_scanner_subscription");
-        error!("{}", msg);
+        error!(target: MARKET_DATA, "req_scanner_subscription");
+        error!(target: MARKET_DATA, "{}", msg);
+        self.record_request_context(req_id, "req_scanner_subscription", None);
         self.send_request(msg.as_str())?;
+        self.add_subscription(Subscription::ScannerSubscription(req_id));
         Ok(())
     }
 
@@ -3686,6 +5556,7 @@ where
         msg.push_str(&make_field(&req_id)?);
 
         self.send_request(msg.as_str())?;
+        self.remove_subscription(&Subscription::ScannerSubscription(req_id));
         Ok(())
     }
 
@@ -3701,10 +5572,10 @@ where
     ///            used when canceling the request.
     /// * contract - This object contains a description of the contract
     ///              for which real time bars are being requested
-    /// * bar_size - Currently only 5 second bars are supported, if any other
-    ///              value is used, an exception will be thrown.
-    /// * what_to_show - Determines the nature of the data extracted. Valid
-    ///                  values include:
+    /// * bar_size - Currently only 5 second bars are supported, BarSize::_5Secs.  Any other
+    ///              value is rejected with an InvalidRequest error.
+    /// * what_to_show - Determines the nature of the data extracted. Other values
+    ///                  are rejected with an InvalidRequest error. Valid values include:
     ///                  * TRADES
     ///                  * BID
     ///                  * ASK
@@ -3721,12 +5592,36 @@ where
         &mut self,
         req_id: i32,
         contract: &Contract,
-        bar_size: i32,
-        what_to_show: &str,
+        bar_size: &BarSize,
+        what_to_show: &WhatToShow,
         use_rth: bool,
         real_time_bars_options: Vec<TagValue>,
     ) -> Result<(), IBKRApiLibError> {
         self.check_connected(NO_VALID_ID)?;
+        self.check_contract_codes(req_id, contract)?;
+
+        if *bar_size != BarSize::_5Secs {
+            return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                req_id,
+                TwsError::InvalidRequest.code().to_string(),
+                format!(
+                    "{} Real time bars are 5 secs, not {}.",
+                    TwsError::InvalidRequest.message(),
+                    bar_size
+                ),
+            )));
+        }
+        if !what_to_show.is_valid_for_real_time_bars() {
+            return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                req_id,
+                TwsError::InvalidRequest.code().to_string(),
+                format!(
+                    "{} Real time bars are not available for {}.",
+                    TwsError::InvalidRequest.message(),
+                    what_to_show
+                ),
+            )));
+        }
 
         if self.server_version() < MIN_SERVER_VER_TRADING_CLASS {
             if !contract.trading_class.is_empty() {
@@ -3770,8 +5665,9 @@ where
         if self.server_version() >= MIN_SERVER_VER_TRADING_CLASS {
             msg.push_str(&make_field(&contract.trading_class)?);
         }
-        msg.push_str(&make_field(&bar_size)?);
-        msg.push_str(&make_field(&String::from(what_to_show))?);
+        let bar_seconds = history::bar_seconds(bar_size).unwrap_or_default() as i32;
+        msg.push_str(&make_field(&bar_seconds)?);
+        msg.push_str(&make_field(&what_to_show.to_string())?);
         msg.push_str(&make_field(&use_rth)?);
 
         // Send real_time_bars_options parameter
@@ -3784,7 +5680,9 @@ where
             msg.push_str(&make_field(&real_time_bars_options_str)?);
         }
 
+        self.record_request_context(req_id, "req_real_time_bars", Some(contract));
         self.send_request(msg.as_str())?;
+        self.add_subscription(Subscription::RealTimeBars(req_id));
         Ok(())
     }
 
@@ -3807,6 +5705,7 @@ where
         msg.push_str(&make_field(&req_id)?);
 
         self.send_request(msg.as_str())?;
+        self.remove_subscription(&Subscription::RealTimeBars(req_id));
         Ok(())
     }
 
@@ -3843,6 +5742,7 @@ where
         fundamental_data_options: Vec<TagValue>,
     ) -> Result<(), IBKRApiLibError> {
         self.check_connected(NO_VALID_ID)?;
+        self.check_contract_codes(req_id, contract)?;
 
         let version = 2;
 
@@ -3904,6 +5804,7 @@ where
             msg.push_str(&make_field(&fund_data_opt_str)?);
         }
 
+        self.record_request_context(req_id, "req_fundamental_data", Some(contract));
         self.send_request(msg.as_str())?;
         Ok(())
     }
@@ -3940,6 +5841,7 @@ where
         msg.push_str(&make_field(&req_id)?);
 
         self.send_request(msg.as_str())?;
+        self.pending.forget_request(req_id);
         Ok(())
     }
 
@@ -4246,9 +6148,79 @@ where
         msg.push_str(&make_field(&req_id)?);
 
         self.send_request(msg.as_str())?;
+        self.pending
+            .display_group_events
+            .lock()
+            .expect(POISONED_MUTEX)
+            .remove(&req_id);
         Ok(())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Requests the display groups of the TWS session with query_display_groups.  Returns a
+    /// receiver of their ids, most used first, which are still passed to
+    /// Wrapper::display_group_list as well.  See display_groups::DisplayGroups.
+    ///
+    /// # Arguments
+    /// * req_id - The unique number that will be associated with the response
+    pub fn display_groups(
+        &mut self,
+        req_id: i32,
+    ) -> Result<Receiver<Vec<DisplayGroupId>>, IBKRApiLibError> {
+        let (tx, rx) = channel::<Vec<DisplayGroupId>>();
+        self.pending
+            .display_group_lists
+            .lock()
+            .expect(POISONED_MUTEX)
+            .insert(req_id, tx);
+        if let Err(err) = self.query_display_groups(req_id) {
+            self.pending
+                .display_group_lists
+                .lock()
+                .expect(POISONED_MUTEX)
+                .remove(&req_id);
+            return Err(err);
+        }
+        Ok(rx)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Subscribes to the selection of a display group with subscribe_to_group_events.  Returns
+    /// a receiver of a DisplayGroupEvent for the current selection and for every change of it,
+    /// which are still passed to Wrapper::display_group_updated as well.
+    /// unsubscribe_from_group_events ends the receiver.  See display_groups::DisplayGroups.
+    ///
+    /// # Arguments
+    /// * req_id - The unique number that will be associated with the response
+    /// * group_id - The display group to follow
+    pub fn subscribe_display_group(
+        &mut self,
+        req_id: i32,
+        group_id: DisplayGroupId,
+    ) -> Result<Receiver<DisplayGroupEvent>, IBKRApiLibError> {
+        let (tx, rx) = channel::<DisplayGroupEvent>();
+        self.pending
+            .display_group_events
+            .lock()
+            .expect(POISONED_MUTEX)
+            .insert(
+                req_id,
+                PendingDisplayGroup {
+                    group_id,
+                    sender: tx,
+                },
+            );
+        if let Err(err) = self.subscribe_to_group_events(req_id, group_id.0) {
+            self.pending
+                .display_group_events
+                .lock()
+                .expect(POISONED_MUTEX)
+                .remove(&req_id);
+            return Err(err);
+        }
+        Ok(rx)
+    }
+
     //----------------------------------------------------------------------------------------------
     /// For IB's internal purpose. Allows to provide means of verification between the TWS and third party programs.
     pub fn verify_request(
@@ -4465,18 +6437,123 @@ where
         msg.push_str(&make_field(&String::from(underlying_sec_type))?);
         msg.push_str(&make_field(&underlying_con_id)?);
 
+        self.record_request_context(req_id, "req_sec_def_opt_params", None);
         self.send_request(msg.as_str())?;
         Ok(())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Requests the option chains of an underlying with req_sec_def_opt_params.  Returns a
+    /// receiver of the chains, one per exchange and trading class, once TWS sent them all.
+    /// They are still passed to Wrapper::security_definition_option_parameter as usual.  If
+    /// TWS rejects the request, the error arrives through Wrapper::error and the receiver ends
+    /// without yielding.
+    ///
+    /// # Arguments
+    /// * req_id - the ID chosen for the request
+    /// * underlying - the underlying, with its symbol, sec_type and con_id
+    /// * fut_fop_exchange - The exchange of the options, empty for all exchanges
+    pub fn option_chains(
+        &mut self,
+        req_id: i32,
+        underlying: &Contract,
+        fut_fop_exchange: &str,
+    ) -> Result<Receiver<Vec<OptionChain>>, IBKRApiLibError> {
+        let (tx, rx) = channel::<Vec<OptionChain>>();
+        self.pending
+            .option_chains
+            .lock()
+            .expect(POISONED_MUTEX)
+            .insert(
+                req_id,
+                PendingOptionChains {
+                    chains: vec![],
+                    sender: tx,
+                },
+            );
+        let sent = self.req_sec_def_opt_params(
+            req_id,
+            &underlying.symbol,
+            fut_fop_exchange,
+            underlying.sec_type.code(),
+            underlying.con_id,
+        );
+        if let Err(err) = sent {
+            self.pending
+                .option_chains
+                .lock()
+                .expect(POISONED_MUTEX)
+                .remove(&req_id);
+            return Err(err);
+        }
+        Ok(rx)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Like option_chains, but serves the chains of the underlying on every exchange from a
+    /// cache while they are younger than its ttl, and requests and caches them otherwise.
+    /// Blocks until TWS answers, so call it from a thread other than the one processing
+    /// messages.
+    ///
+    /// # Arguments
+    /// * cache - the cache
+    /// * req_id - the ID of the request, if one is needed
+    /// * underlying - the underlying.  Must have its con_id set, as the cache is keyed by it.
+    /// * timeout - how long to wait for the chains
+    pub fn option_chains_cached(
+        &mut self,
+        cache: &mut OptionChainCache,
+        req_id: i32,
+        underlying: &Contract,
+        timeout: Duration,
+    ) -> Result<Vec<OptionChain>, IBKRApiLibError> {
+        if underlying.con_id <= 0 {
+            return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                req_id,
+                TwsError::BadMessage.code().to_string(),
+                format!(
+                    "{} The option chain cache needs the underlying's con_id.",
+                    TwsError::BadMessage.message()
+                ),
+            )));
+        }
+        if let Some(chains) = cache.get(underlying.con_id, Instant::now()) {
+            return Ok(chains.to_vec());
+        }
+        let chains = match self
+            .option_chains(req_id, underlying, "")?
+            .recv_timeout(timeout)
+        {
+            Ok(chains) => chains,
+            Err(err) => {
+                self.pending
+                    .option_chains
+                    .lock()
+                    .expect(POISONED_MUTEX)
+                    .remove(&req_id);
+                return Err(err.into());
+            }
+        };
+        cache.insert(underlying.con_id, chains.clone(), Instant::now());
+        Ok(chains)
+    }
+
     //----------------------------------------------------------------------------------------------
     /// Requests pre-defined Soft Dollar Tiers. This is only supported for registered professional
     /// advisors and hedge and mutual funds who have configured Soft Dollar Tiers in Account Management.
     /// Refer to: <https://www.interactivebrokers.com/en/software/am/am/manageaccount/requestsoftdollars.htm?Highlight=soft%20dollar%20tier>.
     ///
+    /// Returns a receiver of the tiers, which are still passed to Wrapper::soft_dollar_tiers as
+    /// well.  Set one as the soft_dollar_tier of an order to pay its commissions by it.
+    ///
+    /// If TWS answers with an error instead, the receiver ends without yielding.
+    ///
     /// # Arguments
     /// * req_id - the identifier for this request
-    pub fn req_soft_dollar_tiers(&mut self, req_id: i32) -> Result<(), IBKRApiLibError> {
+    pub fn req_soft_dollar_tiers(
+        &mut self,
+        req_id: i32,
+    ) -> Result<Receiver<Vec<SoftDollarTier>>, IBKRApiLibError> {
         self.check_connected(NO_VALID_ID)?;
 
         let message_id: i32 = OutgoingMessageIds::ReqSoftDollarTiers as i32;
@@ -4485,8 +6562,21 @@ where
 
         msg.push_str(&make_field(&req_id)?);
 
-        self.send_request(msg.as_str())?;
-        Ok(())
+        let (tx, rx) = channel::<Vec<SoftDollarTier>>();
+        self.pending
+            .soft_dollar_tiers
+            .lock()
+            .expect(POISONED_MUTEX)
+            .insert(req_id, tx);
+        if let Err(err) = self.send_request(msg.as_str()) {
+            self.pending
+                .soft_dollar_tiers
+                .lock()
+                .expect(POISONED_MUTEX)
+                .remove(&req_id);
+            return Err(err);
+        }
+        Ok(rx)
     }
 
     //----------------------------------------------------------------------------------------------
@@ -4516,6 +6606,27 @@ where
         Ok(())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Requests the family codes of the accounts and returns a receiver of them.  They are still
+    /// passed to Wrapper::family_codes as well.
+    pub fn family_codes(&mut self) -> Result<Receiver<Vec<FamilyCode>>, IBKRApiLibError> {
+        let (tx, rx) = channel::<Vec<FamilyCode>>();
+        self.pending
+            .family_codes
+            .lock()
+            .expect(POISONED_MUTEX)
+            .push(tx);
+        if let Err(err) = self.req_family_codes() {
+            self.pending
+                .family_codes
+                .lock()
+                .expect(POISONED_MUTEX)
+                .pop();
+            return Err(err);
+        }
+        Ok(rx)
+    }
+
     //----------------------------------------------------------------------------------------------
     /// Requests matching stock symbols.
     ///
@@ -4550,10 +6661,52 @@ where
         msg.push_str(&make_field(&req_id)?);
         msg.push_str(&make_field(&String::from(pattern))?);
 
+        self.record_request_context(req_id, "req_matching_symbols", None);
         self.send_request(msg.as_str())?;
         Ok(())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Requests the stocks matching a pattern and returns a receiver of their descriptions,
+    /// which are still passed to Wrapper::symbol_samples as well.  Blocks until
+    /// SYMBOL_SEARCH_PACING has passed since the previous search, as TWS rejects faster ones.
+    ///
+    /// If TWS answers with an error instead, the receiver ends without yielding.
+    ///
+    /// # Arguments
+    /// * req_id - the identifier for this request
+    /// * pattern - either start of ticker symbol or (for larger strings) company name
+    pub fn search_symbols(
+        &mut self,
+        req_id: i32,
+        pattern: &str,
+    ) -> Result<Receiver<Vec<ContractDescription>>, IBKRApiLibError> {
+        if let Some(wait) = self
+            .last_symbol_search
+            .map(|searched| SYMBOL_SEARCH_PACING.saturating_sub(searched.elapsed()))
+            .filter(|wait| *wait > Duration::from_secs(0))
+        {
+            debug!(target: PACING, "Waiting {:?} to search symbols", wait);
+            thread::sleep(wait);
+        }
+        let (tx, rx) = channel::<Vec<ContractDescription>>();
+        self.pending
+            .symbol_searches
+            .lock()
+            .expect(POISONED_MUTEX)
+            .insert(req_id, tx);
+        if let Err(err) = self.req_matching_symbols(req_id, pattern) {
+            self.pending
+                .symbol_searches
+                .lock()
+                .expect(POISONED_MUTEX)
+                .remove(&req_id);
+            return Err(err);
+        }
+        self.last_symbol_search = Some(Instant::now());
+        Ok(rx)
+    }
+
     //----------------------------------------------------------------------------------------------
     /// Requests completed orders.
     ///
@@ -4679,9 +6832,75 @@ where
         Ok(())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Rejects an order whose order_type is missing from the order_types of the details
+    /// received for its contract on its exchange during this connection.  Orders for
+    /// contracts whose details were not received are left to TWS.
+    fn check_order_type(
+        &self,
+        order_id: i32,
+        contract: &Contract,
+        order: &Order,
+    ) -> Result<(), IBKRApiLibError> {
+        let order_types = self.pending.order_types.lock().expect(POISONED_MUTEX);
+        match order_types.get(&(contract.con_id, contract.exchange.clone())) {
+            Some(listed) if !order.order_type.is_supported_by(listed) => {
+                Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                    order_id,
+                    TwsError::InvalidRequest.code().to_string(),
+                    format!(
+                        "{} {} orders are not accepted for {} {}.",
+                        TwsError::InvalidRequest.message(),
+                        order.order_type,
+                        contract.symbol,
+                        contract.sec_type
+                    ),
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Rejects a contract whose exchange or primary_exchange looks like a misspelled known
+    /// exchange, or whose currency is not an ISO 4217 code, which TWS would answer with a
+    /// generic "no security definition" error
+    fn check_contract_codes(
+        &self,
+        req_id: i32,
+        contract: &Contract,
+    ) -> Result<(), IBKRApiLibError> {
+        if contract.currency.is_set() && !contract.currency.is_iso() {
+            return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                req_id,
+                TwsError::InvalidRequest.code().to_string(),
+                format!(
+                    "{} Unknown currency {}, expected an ISO 4217 code.",
+                    TwsError::InvalidRequest.message(),
+                    contract.currency
+                ),
+            )));
+        }
+        for exchange in &[&contract.exchange, &contract.primary_exchange] {
+            if let Some(known) = exchange.likely_misspelling_of() {
+                return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                    req_id,
+                    TwsError::InvalidRequest.code().to_string(),
+                    format!(
+                        "{} Unknown exchange {}, did you mean {}?",
+                        TwsError::InvalidRequest.message(),
+                        exchange,
+                        known
+                    ),
+                )));
+            }
+        }
+        Ok(())
+    }
+
     //------------------------------------------------------------------------------------------------
     /// check if client is connected to TWS
-    fn check_connected(&mut self, req_id: i32) -> Result<(), IBKRApiLibError> {
+    fn check_connected(&self, req_id: i32) -> Result<(), IBKRApiLibError> {
         match self.is_connected() {
             false => {
                 let err = IBKRApiLibError::ApiError(TwsApiReportableError::new(
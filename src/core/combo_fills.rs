@@ -0,0 +1,243 @@
+//! Attribution of leg executions to their combo order
+//!
+//! TWS reports a filled combo (BAG) order as one execution per leg, plus usually one for the
+//! BAG contract itself, all with the order's order_id and perm_id.  ComboFillAggregator groups
+//! them under the order and computes the net price achieved.
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::core::common::{SecType, UNSET_DOUBLE};
+use crate::core::contract::Contract;
+use crate::core::execution::Execution;
+use crate::core::order::{Action, Order};
+
+//==================================================================================================
+/// Executions of one leg
+/// action - the leg action as defined in the combo contract
+/// shares - shares filled so far
+/// avg_price - average fill price of the shares
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct LegFill {
+    pub con_id: i32,
+    pub ratio: f64,
+    pub action: Action,
+    pub shares: f64,
+    pub avg_price: f64,
+}
+
+impl LegFill {
+    fn add(&mut self, shares: f64, price: f64) {
+        let total = self.shares + shares;
+        if total > 0.0 {
+            self.avg_price = (self.avg_price * self.shares + price * shares) / total;
+        }
+        self.shares = total;
+    }
+}
+
+impl fmt::Display for LegFill {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "con_id: {}, ratio: {}, action: {}, shares: {}, avg_price: {}",
+            self.con_id, self.ratio, self.action, self.shares, self.avg_price
+        )
+    }
+}
+
+//==================================================================================================
+/// Fills of a combo order
+/// limit_price - the order's limit, UNSET_DOUBLE for orders without one
+/// reported_price - average price of the executions TWS reported for the BAG contract, if any
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ComboFill {
+    pub order_id: i32,
+    pub perm_id: i32,
+    pub action: Action,
+    pub total_quantity: f64,
+    pub limit_price: f64,
+    pub legs: Vec<LegFill>,
+    pub reported_price: Option<f64>,
+    reported_quantity: f64,
+}
+
+impl ComboFill {
+    /// Combo units filled on every leg
+    pub fn filled_quantity(&self) -> f64 {
+        self.legs
+            .iter()
+            .map(|leg| leg.shares / leg.ratio)
+            .fold(None, |min: Option<f64>, units| {
+                Some(min.map_or(units, |min| min.min(units)))
+            })
+            .unwrap_or(0.0)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether every leg filled the order quantity
+    pub fn is_complete(&self) -> bool {
+        self.total_quantity > 0.0 && self.filled_quantity() >= self.total_quantity
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Net price of one combo unit from the leg average prices: the BUY legs' prices less the
+    /// SELL legs', each times its ratio.  None until every leg has a fill.
+    pub fn net_price(&self) -> Option<f64> {
+        if self.legs.is_empty() || self.legs.iter().any(|leg| leg.shares <= 0.0) {
+            return None;
+        }
+        Some(
+            self.legs
+                .iter()
+                .map(|leg| {
+                    let sign = if leg.action.is_buy() { 1.0 } else { -1.0 };
+                    sign * leg.ratio * leg.avg_price
+                })
+                .sum(),
+        )
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// How much better than its limit the order filled, per combo unit: the limit less the net
+    /// price for a BUY order, the net price less the limit for a SELL order.  Negative when it
+    /// filled worse.  None without a limit or before every leg has a fill.
+    pub fn price_improvement(&self) -> Option<f64> {
+        if self.limit_price == UNSET_DOUBLE {
+            return None;
+        }
+        let net_price = self.net_price()?;
+        if self.action.is_buy() {
+            Some(self.limit_price - net_price)
+        } else {
+            Some(net_price - self.limit_price)
+        }
+    }
+}
+
+impl fmt::Display for ComboFill {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "order_id: {}, perm_id: {}, action: {}, filled: {}/{}, net_price: {:?}, limit_price: {}",
+            self.order_id,
+            self.perm_id,
+            self.action,
+            self.filled_quantity(),
+            self.total_quantity,
+            self.net_price(),
+            self.limit_price
+        )
+    }
+}
+
+//==================================================================================================
+/// Groups the executions of registered combo orders.  Register each combo order, then pass
+/// every execution from Wrapper::exec_details to on_execution.
+#[derive(Clone, Debug, Default)]
+pub struct ComboFillAggregator {
+    combos: HashMap<i32, ComboFill>,
+    exec_ids: HashSet<String>,
+}
+
+impl ComboFillAggregator {
+    pub fn new() -> Self {
+        ComboFillAggregator::default()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Starts attributing the executions of a combo order
+    ///
+    /// # Arguments
+    /// * order_id - the id the order was placed with
+    /// * contract - the BAG contract, with its combo legs
+    /// * order - the order.  Its perm_id may be 0 if not known yet.
+    pub fn register(&mut self, order_id: i32, contract: &Contract, order: &Order) {
+        let legs = contract
+            .combo_legs
+            .iter()
+            .map(|leg| LegFill {
+                con_id: leg.con_id,
+                ratio: leg.ratio,
+                action: leg.action.clone(),
+                ..Default::default()
+            })
+            .collect();
+        self.combos.insert(
+            order_id,
+            ComboFill {
+                order_id,
+                perm_id: order.perm_id,
+                action: order.action.clone(),
+                total_quantity: order.total_quantity,
+                limit_price: order.lmt_price,
+                legs,
+                ..Default::default()
+            },
+        );
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Attributes an execution to its combo order.  Executions are matched by perm_id, or by
+    /// order_id while the perm_id is not known, and to a leg by the contract's con_id.
+    /// Executions seen before are ignored.
+    ///
+    /// # Returns
+    /// The combo order the execution belongs to, None for executions of other orders
+    pub fn on_execution(
+        &mut self,
+        contract: &Contract,
+        execution: &Execution,
+    ) -> Option<&ComboFill> {
+        let order_id = self
+            .combos
+            .values()
+            .find(|combo| {
+                if combo.perm_id != 0 && execution.perm_id != 0 {
+                    combo.perm_id == execution.perm_id
+                } else {
+                    combo.order_id == execution.order_id
+                }
+            })?
+            .order_id;
+        if !self.exec_ids.insert(execution.exec_id.clone()) {
+            return self.combos.get(&order_id);
+        }
+
+        let combo = self.combos.get_mut(&order_id)?;
+        if combo.perm_id == 0 {
+            combo.perm_id = execution.perm_id;
+        }
+        let shares = execution.shares.to_f64().unwrap_or(0.0);
+        if contract.sec_type == SecType::BAG {
+            let total = combo.reported_quantity + shares;
+            if total > 0.0 {
+                let reported = combo.reported_price.unwrap_or(0.0);
+                combo.reported_price =
+                    Some((reported * combo.reported_quantity + execution.price * shares) / total);
+            }
+            combo.reported_quantity = total;
+        } else if let Some(leg) = combo
+            .legs
+            .iter_mut()
+            .find(|leg| leg.con_id == contract.con_id)
+        {
+            leg.add(shares, execution.price);
+        }
+        Some(combo)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Fills of a registered combo order
+    pub fn get(&self, order_id: i32) -> Option<&ComboFill> {
+        self.combos.get(&order_id)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Stops tracking a combo order and returns its fills
+    pub fn remove(&mut self, order_id: i32) -> Option<ComboFill> {
+        self.combos.remove(&order_id)
+    }
+}
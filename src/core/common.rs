@@ -1,11 +1,22 @@
 //! Common types
 use std::fmt::Display;
 use std::fmt::{self, Error, Formatter};
+use std::fs;
+use std::path::Path;
 
+use bigdecimal::BigDecimal;
+use chrono::DateTime;
 use num_derive::FromPrimitive;
 
 use serde::{Deserialize, Serialize};
 
+use crate::core::currency::Currency;
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+use crate::core::exchange::Exchange;
+use crate::core::server_versions::MIN_SERVER_VER_HISTORICAL_SCHEDULE;
+use crate::core::time_zones::ExchangeTz;
+use crate::core::timestamps::{parse_timestamp, timestamp_time_in, timestamp_to_wire, Timestamp};
+
 pub const NO_VALID_ID: i32 = -1;
 pub const MAX_MSG_LEN: i64 = 0xFFFFFF; //16Mb - 1byte
 
@@ -16,7 +27,7 @@ pub const UNSET_LONG: i64 = std::i64::MAX;
 //==================================================================================================
 /// Tick types
 #[repr(i32)]
-#[derive(Serialize, Deserialize, Clone, Debug, FromPrimitive, Copy)]
+#[derive(Serialize, Deserialize, Clone, Debug, FromPrimitive, Copy, PartialEq, Eq, Hash)]
 pub enum TickType {
     BidSize = 0,
     Bid = 1,
@@ -126,6 +137,19 @@ pub enum TickType {
     NotSet = UNSET_INTEGER,
 }
 
+impl TickType {
+    /// Converts a tick type id received from TWS.  Ids this crate does not know about
+    /// become TickType::NotSet instead of failing.
+    pub fn from_code(code: i32) -> TickType {
+        num_traits::FromPrimitive::from_i32(code).unwrap_or(TickType::NotSet)
+    }
+
+    /// The tick type id used by TWS
+    pub fn code(&self) -> i32 {
+        *self as i32
+    }
+}
+
 impl fmt::Display for TickType {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -294,24 +318,25 @@ impl fmt::Display for TickByTickType {
 /// average - average price of the bar
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct BarData {
-    pub date: String,
+    #[serde(with = "crate::core::timestamps::serde_timestamp")]
+    pub date: Timestamp,
     pub open: f64,
     pub high: f64,
     pub low: f64,
     pub close: f64,
-    pub volume: i64,
+    pub volume: BigDecimal,
     pub bar_count: i32,
     pub average: f64,
 }
 
 impl BarData {
     pub fn new(
-        date: String,
+        date: Timestamp,
         open: f64,
         high: f64,
         low: f64,
         close: f64,
-        volume: i64,
+        volume: BigDecimal,
         bar_count: i32,
         average: f64,
     ) -> Self {
@@ -326,15 +351,132 @@ impl BarData {
             average,
         }
     }
+
+    /// The start time of the bar in a time zone, e.g. that of the exchange
+    pub fn time_in(&self, zone: ExchangeTz) -> Option<DateTime<ExchangeTz>> {
+        timestamp_time_in(&self.date, zone)
+    }
 }
 
 impl fmt::Display for BarData {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "date: {}, open: {}, high: {}, low: {}, close: {}, volume: {}, average: {}, barcount: {}", self.date, self.open, self.high,
+        write!(f, "date: {}, open: {}, high: {}, low: {}, close: {}, volume: {}, average: {}, barcount: {}", timestamp_to_wire(&self.date), self.open, self.high,
                self.low, self.close, self.volume, self.average, self.bar_count)
     }
 }
 
+//==================================================================================================
+/// Historical bar returned for the YIELD_BID, YIELD_ASK, YIELD_BID_ASK and YIELD_LAST
+/// whatToShow types.  TWS reports no volume, average or bar count for these.
+/// date - the bar's date and time
+/// open_yield  - the yield at the start of the bar
+/// high_yield  - the highest yield during the bar
+/// low_yield   - the lowest yield during the bar
+/// close_yield - the yield at the end of the bar
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct YieldBar {
+    #[serde(with = "crate::core::timestamps::serde_timestamp")]
+    pub date: Timestamp,
+    pub open_yield: f64,
+    pub high_yield: f64,
+    pub low_yield: f64,
+    pub close_yield: f64,
+}
+
+impl YieldBar {
+    pub fn new(
+        date: Timestamp,
+        open_yield: f64,
+        high_yield: f64,
+        low_yield: f64,
+        close_yield: f64,
+    ) -> Self {
+        YieldBar {
+            date,
+            open_yield,
+            high_yield,
+            low_yield,
+            close_yield,
+        }
+    }
+}
+
+impl From<BarData> for YieldBar {
+    fn from(bar: BarData) -> Self {
+        YieldBar::new(bar.date, bar.open, bar.high, bar.low, bar.close)
+    }
+}
+
+impl fmt::Display for YieldBar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "date: {}, open_yield: {}, high_yield: {}, low_yield: {}, close_yield: {}",
+            timestamp_to_wire(&self.date),
+            self.open_yield,
+            self.high_yield,
+            self.low_yield,
+            self.close_yield
+        )
+    }
+}
+
+//==================================================================================================
+/// Historical bar returned for the FEE_RATE whatToShow type: the stock borrow fee rate
+/// over the bar, in percent.  TWS reports no volume, average or bar count for these.
+/// date - the bar's date and time
+/// open_rate  - the fee rate at the start of the bar
+/// high_rate  - the highest fee rate during the bar
+/// low_rate   - the lowest fee rate during the bar
+/// close_rate - the fee rate at the end of the bar
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct FeeRateBar {
+    #[serde(with = "crate::core::timestamps::serde_timestamp")]
+    pub date: Timestamp,
+    pub open_rate: f64,
+    pub high_rate: f64,
+    pub low_rate: f64,
+    pub close_rate: f64,
+}
+
+impl FeeRateBar {
+    pub fn new(
+        date: Timestamp,
+        open_rate: f64,
+        high_rate: f64,
+        low_rate: f64,
+        close_rate: f64,
+    ) -> Self {
+        FeeRateBar {
+            date,
+            open_rate,
+            high_rate,
+            low_rate,
+            close_rate,
+        }
+    }
+}
+
+impl From<BarData> for FeeRateBar {
+    fn from(bar: BarData) -> Self {
+        FeeRateBar::new(bar.date, bar.open, bar.high, bar.low, bar.close)
+    }
+}
+
+impl fmt::Display for FeeRateBar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "date: {}, open_rate: {}, high_rate: {}, low_rate: {}, close_rate: {}",
+            timestamp_to_wire(&self.date),
+            self.open_rate,
+            self.high_rate,
+            self.low_rate,
+            self.close_rate
+        )
+    }
+}
+
 //==================================================================================================
 /// date_time - the bar's date and time (either as a yyyymmss hh:mm:ssformatted
 ///        string or as system time according to the request)
@@ -354,7 +496,7 @@ pub struct RealTimeBar {
     pub high: f64,
     pub low: f64,
     pub close: f64,
-    pub volume: i64,
+    pub volume: BigDecimal,
     pub wap: f64,
     pub count: i32,
 }
@@ -366,7 +508,7 @@ impl RealTimeBar {
         high: f64,
         low: f64,
         close: f64,
-        volume: i64,
+        volume: BigDecimal,
         wap: f64,
         count: i32,
     ) -> Self {
@@ -381,6 +523,12 @@ impl RealTimeBar {
             count,
         }
     }
+
+    /// The start time of the bar in a time zone, e.g. that of the exchange.  TWS sends it in
+    /// epoch seconds.
+    pub fn time_in(&self, zone: ExchangeTz) -> Option<DateTime<ExchangeTz>> {
+        parse_timestamp(&self.date_time).map(|time| time.with_timezone(&zone))
+    }
 }
 
 impl fmt::Display for RealTimeBar {
@@ -419,19 +567,46 @@ impl fmt::Display for HistogramData {
     }
 }
 
+//==================================================================================================
+/// A price of a histogram and how often it traded, with the fractional counts TWS may send
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct HistogramEntry {
+    pub price: f64,
+    pub count: BigDecimal,
+}
+
+impl HistogramEntry {
+    pub fn new(price: f64, count: BigDecimal) -> Self {
+        HistogramEntry { price, count }
+    }
+}
+
+impl fmt::Display for HistogramEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "price: {}, count: {}", self.price, self.count)
+    }
+}
+
 //==================================================================================================
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct DepthMktDataDescription {
-    pub exchange: String,
+    pub exchange: Exchange,
     pub sec_type: String,
     pub listing_exch: String,
     pub service_data_type: String,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_integer::unset",
+            with = "crate::core::serde_unset::unset_integer"
+        )
+    )]
     pub agg_group: i32,
 }
 
 impl DepthMktDataDescription {
     pub fn new(
-        exchange: String,
+        exchange: Exchange,
         sec_type: String,
         listing_exch: String,
         service_data_type: String,
@@ -574,17 +749,19 @@ impl fmt::Display for TickAttribLast {
 }
 
 //==================================================================================================
-#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+/// The family code of an account, shared by the accounts of an FA or linked account structure
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
 pub struct FamilyCode {
     pub account_id: String,
-    pub family_code_str: String,
+    #[serde(alias = "family_code_str")]
+    pub family_code: String,
 }
 
 impl FamilyCode {
-    pub fn new(account_id: String, family_code_str: String) -> Self {
+    pub fn new(account_id: String, family_code: String) -> Self {
         FamilyCode {
             account_id,
-            family_code_str,
+            family_code,
         }
     }
 }
@@ -593,8 +770,8 @@ impl fmt::Display for FamilyCode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "account_id: {}, family_code_str: {}",
-            self.account_id, self.family_code_str
+            "account_id: {}, family_code: {}",
+            self.account_id, self.family_code
         )
     }
 }
@@ -628,13 +805,14 @@ impl fmt::Display for PriceIncrement {
 //==================================================================================================
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct HistoricalTick {
-    pub time: i32,
+    #[serde(with = "crate::core::timestamps::serde_timestamp")]
+    pub time: Timestamp,
     pub price: f64,
-    pub size: i32,
+    pub size: BigDecimal,
 }
 
 impl HistoricalTick {
-    pub fn new(time: i32, price: f64, size: i32) -> Self {
+    pub fn new(time: Timestamp, price: f64, size: BigDecimal) -> Self {
         HistoricalTick { time, price, size }
     }
 }
@@ -644,7 +822,9 @@ impl fmt::Display for HistoricalTick {
         write!(
             f,
             "time: {}, price: {}, size: {}",
-            self.time, self.price, self.size
+            timestamp_to_wire(&self.time),
+            self.price,
+            self.size
         )
     }
 }
@@ -652,22 +832,23 @@ impl fmt::Display for HistoricalTick {
 //==================================================================================================
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct HistoricalTickBidAsk {
-    pub time: i32,
+    #[serde(with = "crate::core::timestamps::serde_timestamp")]
+    pub time: Timestamp,
     pub tick_attrib_bid_ask: TickAttribBidAsk,
     pub price_bid: f64,
     pub price_ask: f64,
-    pub size_bid: i32,
-    pub size_ask: i32,
+    pub size_bid: BigDecimal,
+    pub size_ask: BigDecimal,
 }
 
 impl HistoricalTickBidAsk {
     pub fn new(
-        time: i32,
+        time: Timestamp,
         tick_attrib_bid_ask: TickAttribBidAsk,
         price_bid: f64,
         price_ask: f64,
-        size_bid: i32,
-        size_ask: i32,
+        size_bid: BigDecimal,
+        size_ask: BigDecimal,
     ) -> Self {
         HistoricalTickBidAsk {
             time,
@@ -685,7 +866,7 @@ impl fmt::Display for HistoricalTickBidAsk {
         write!(
             f,
             "time: {}, tick_attrib_bid_ask: {}, price_bid: {}, price_ask: {}, size_bid: {}, size_ask: {}",
-            self.time,
+            timestamp_to_wire(&self.time),
             self.tick_attrib_bid_ask,
             self.price_bid,
             self.price_ask,
@@ -698,20 +879,21 @@ impl fmt::Display for HistoricalTickBidAsk {
 //==================================================================================================
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct HistoricalTickLast {
-    pub time: i32,
+    #[serde(with = "crate::core::timestamps::serde_timestamp")]
+    pub time: Timestamp,
     pub tick_attrib_last: TickAttribLast,
     pub price: f64,
-    pub size: i32,
+    pub size: BigDecimal,
     pub exchange: String,
     pub special_conditions: String,
 }
 
 impl HistoricalTickLast {
     pub fn new(
-        time: i32,
+        time: Timestamp,
         tick_attrib_last: TickAttribLast,
         price: f64,
-        size: i32,
+        size: BigDecimal,
         exchange: String,
         special_conditions: String,
     ) -> Self {
@@ -729,7 +911,7 @@ impl HistoricalTickLast {
 impl fmt::Display for HistoricalTickLast {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "time: {}, tick_attrib_last: {}, price: {}, size: {}, exchange: {}, special_conditions: {}",
-               self.time,
+               timestamp_to_wire(&self.time),
                self.tick_attrib_last,
                self.price,
                self.size,
@@ -743,8 +925,22 @@ impl fmt::Display for HistoricalTickLast {
 pub struct CommissionReport {
     pub exec_id: String,
     pub commission: f64,
-    pub currency: String,
+    pub currency: Currency,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub realized_pnl: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub yield_: f64,
     pub yield_redemption_date: String, //YYYYMMDD format
 }
@@ -753,7 +949,7 @@ impl CommissionReport {
     pub fn new(
         exec_id: String,
         commission: f64,
-        currency: String,
+        currency: Currency,
         realized_pnl: f64,
         yield_: f64,
         yield_redemption_date: String,
@@ -800,6 +996,88 @@ impl fmt::Display for NewsProvider {
     }
 }
 
+//==================================================================================================
+/// article_type of a news article holding text
+pub const NEWS_ARTICLE_TEXT: i32 = 0;
+/// article_type of a news article holding a base64 encoded PDF
+pub const NEWS_ARTICLE_BINARY: i32 = 1;
+
+//==================================================================================================
+/// The body of a news article
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum NewsArticleContent {
+    Text(String),
+    Pdf(Vec<u8>),
+}
+
+impl NewsArticleContent {
+    /// Decodes the article_type and article_text of Wrapper::news_article
+    pub fn from_article(article_type: i32, article_text: &str) -> Result<Self, IBKRApiLibError> {
+        match article_type {
+            NEWS_ARTICLE_BINARY => Ok(NewsArticleContent::Pdf(decode_base64(article_text)?)),
+            _ => Ok(NewsArticleContent::Text(article_text.to_string())),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Writes the text, or the PDF bytes, to a file
+    pub fn save_to<P: AsRef<Path>>(&self, path: P) -> Result<(), IBKRApiLibError> {
+        match self {
+            NewsArticleContent::Text(text) => fs::write(path, text)?,
+            NewsArticleContent::Pdf(bytes) => fs::write(path, bytes)?,
+        }
+        Ok(())
+    }
+}
+
+//==================================================================================================
+/// Decodes standard base64, ignoring whitespace
+fn decode_base64(text: &str) -> Result<Vec<u8>, IBKRApiLibError> {
+    let invalid = |detail: &str| {
+        IBKRApiLibError::ApiError(TwsApiReportableError::new(
+            -1,
+            TwsError::BadMessage.code().to_string(),
+            format!(
+                "{} Invalid base64: {}.",
+                TwsError::BadMessage.message(),
+                detail
+            ),
+        ))
+    };
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    let mut padding = 0;
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => {
+                padding += 1;
+                continue;
+            }
+            _ => return Err(invalid(&format!("unexpected character {}", c as char))),
+        };
+        if padding > 0 {
+            return Err(invalid("data after padding"));
+        }
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    if bits >= 6 {
+        return Err(invalid("truncated data"));
+    }
+    Ok(bytes)
+}
+
 //==================================================================================================
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct TagValue {
@@ -853,25 +1131,81 @@ impl Display for ComboParam {
 }
 
 //==================================================================================================
-#[repr(i32)]
-#[derive(Serialize, Deserialize, Clone, FromPrimitive, Debug)]
+/// The hedge type of a hedge order, attached as the child of the order it hedges.  Beta hedges
+/// take "beta=X" and pair hedges "ratio=Y" as their hedge_param.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(from = "String", into = "String")]
 pub enum HedgeType {
+    /// not a hedge order
+    #[default]
     None,
     Delta,
     Beta,
+    /// hedges the currency of the parent with a CASH pair, for a quantity TWS works out
     Fx,
     Pair,
+    Other(String),
+}
+
+impl HedgeType {
+    /// The hedge type of a code, ignoring case
+    pub fn from_code(code: &str) -> HedgeType {
+        match code.trim().to_ascii_uppercase().as_str() {
+            "" => HedgeType::None,
+            "D" => HedgeType::Delta,
+            "B" => HedgeType::Beta,
+            "F" => HedgeType::Fx,
+            "P" => HedgeType::Pair,
+            _ => HedgeType::Other(code.trim().to_string()),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The code TWS knows the hedge type by, empty for None
+    pub fn code(&self) -> &str {
+        match self {
+            HedgeType::None => "",
+            HedgeType::Delta => "D",
+            HedgeType::Beta => "B",
+            HedgeType::Fx => "F",
+            HedgeType::Pair => "P",
+            HedgeType::Other(code) => code,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn is_set(&self) -> bool {
+        *self != HedgeType::None
+    }
 }
 
 impl Display for HedgeType {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
-        match *self {
-            HedgeType::None => write!(f, ""),
-            HedgeType::Delta => write!(f, "Delta"),
-            HedgeType::Beta => write!(f, "Beta"),
-            HedgeType::Fx => write!(f, "Fx"),
-            HedgeType::Pair => write!(f, "Pair"),
-        }
+        write!(f, "{}", self.code())
+    }
+}
+
+impl From<&str> for HedgeType {
+    fn from(code: &str) -> Self {
+        HedgeType::from_code(code)
+    }
+}
+
+impl From<String> for HedgeType {
+    fn from(code: String) -> Self {
+        HedgeType::from_code(&code)
+    }
+}
+
+impl From<HedgeType> for String {
+    fn from(hedge_type: HedgeType) -> Self {
+        hedge_type.code().to_string()
+    }
+}
+
+impl PartialEq<&str> for HedgeType {
+    fn eq(&self, code: &&str) -> bool {
+        self.code() == *code
     }
 }
 
@@ -1112,8 +1446,9 @@ impl Display for FundamentalType {
 }
 
 //==================================================================================================
+/// The what_to_show of a historical data or real time bars request
 #[repr(i32)]
-#[derive(Serialize, Deserialize, Clone, FromPrimitive, Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, FromPrimitive, Debug, PartialEq, Eq, Hash)]
 pub enum WhatToShow {
     Trades,
     Midpoint,
@@ -1128,6 +1463,9 @@ pub enum WhatToShow {
     YieldBidAsk,
     YieldLast,
     AdjustedLast,
+    AggTrades,
+    FeeRate,
+    Schedule,
 }
 
 impl Display for WhatToShow {
@@ -1141,17 +1479,64 @@ impl Display for WhatToShow {
             WhatToShow::HistoricalVolatility => write!(f, "HISTORICAL_VOLATILITY"),
             WhatToShow::OptionImpliedVolatility => write!(f, "OPTION_IMPLIED_VOLATILITY"),
             WhatToShow::YieldAsk => write!(f, "YIELD_ASK"),
-            WhatToShow::YieldBid => write!(f, "YIELD_ASK"),
+            WhatToShow::YieldBid => write!(f, "YIELD_BID"),
             WhatToShow::YieldBidAsk => write!(f, "YIELD_BID_ASK"),
             WhatToShow::YieldLast => write!(f, "YIELD_LAST"),
             WhatToShow::AdjustedLast => write!(f, "ADJUSTED_LAST"),
+            WhatToShow::AggTrades => write!(f, "AGGTRADES"),
+            WhatToShow::FeeRate => write!(f, "FEE_RATE"),
+            WhatToShow::Schedule => write!(f, "SCHEDULE"),
+        }
+    }
+}
+
+impl WhatToShow {
+    /// Returns true if TWS serves historical data of this type for the given security type.
+    ///
+    /// # Arguments
+    /// * sec_type - the security type of the contract the data is requested for
+    pub fn is_valid_for(&self, sec_type: &SecType) -> bool {
+        match *self {
+            WhatToShow::Trades => {
+                !matches!(*sec_type, SecType::CASH | SecType::CMDTY | SecType::CRYPTO)
+            }
+            WhatToShow::Midpoint | WhatToShow::Bid | WhatToShow::Ask | WhatToShow::BidAsk => {
+                !matches!(*sec_type, SecType::IND)
+            }
+            WhatToShow::HistoricalVolatility | WhatToShow::OptionImpliedVolatility => {
+                matches!(*sec_type, SecType::STK | SecType::IND)
+            }
+            WhatToShow::YieldAsk
+            | WhatToShow::YieldBid
+            | WhatToShow::YieldBidAsk
+            | WhatToShow::YieldLast => matches!(*sec_type, SecType::BOND | SecType::BILL),
+            WhatToShow::AdjustedLast | WhatToShow::FeeRate => matches!(*sec_type, SecType::STK),
+            WhatToShow::AggTrades => matches!(*sec_type, SecType::CRYPTO),
+            WhatToShow::Schedule => true,
+        }
+    }
+
+    /// Returns true if TWS streams real time bars of this type
+    pub fn is_valid_for_real_time_bars(&self) -> bool {
+        matches!(
+            *self,
+            WhatToShow::Trades | WhatToShow::Midpoint | WhatToShow::Bid | WhatToShow::Ask
+        )
+    }
+
+    /// The server version TWS serves this type of historical data from, None if any
+    pub fn min_server_version(&self) -> Option<i32> {
+        match *self {
+            WhatToShow::Schedule => Some(MIN_SERVER_VER_HISTORICAL_SCHEDULE),
+            _ => None,
         }
     }
 }
 
 //==================================================================================================
+/// The bar_size_setting of a historical data request, and the bar size of real time bars
 #[repr(i32)]
-#[derive(Serialize, Deserialize, Clone, FromPrimitive, Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, FromPrimitive, Debug, PartialEq, Eq, Hash)]
 pub enum BarSize {
     _1Secs,
     _5Secs,
@@ -1167,7 +1552,10 @@ pub enum BarSize {
     _20Mins,
     _30Mins,
     _1Hour,
+    _2Hours,
+    _3Hours,
     _4Hours,
+    _8Hours,
     _1Day,
     _1Week,
     _1Month,
@@ -1190,7 +1578,10 @@ impl Display for BarSize {
             BarSize::_20Mins => write!(f, "20 mins"),
             BarSize::_30Mins => write!(f, "30 mins"),
             BarSize::_1Hour => write!(f, "1 hour"),
+            BarSize::_2Hours => write!(f, "2 hours"),
+            BarSize::_3Hours => write!(f, "3 hours"),
             BarSize::_4Hours => write!(f, "4 hours"),
+            BarSize::_8Hours => write!(f, "8 hours"),
             BarSize::_1Day => write!(f, "1 day"),
             BarSize::_1Week => write!(f, "1 week"),
             BarSize::_1Month => write!(f, "1 month"),
@@ -1198,6 +1589,29 @@ impl Display for BarSize {
     }
 }
 
+//==================================================================================================
+/// The duration_str of a historical data request: how far back from end_date_time it goes
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Duration {
+    Seconds(u32),
+    Days(u32),
+    Weeks(u32),
+    Months(u32),
+    Years(u32),
+}
+
+impl Display for Duration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match *self {
+            Duration::Seconds(seconds) => write!(f, "{} S", seconds),
+            Duration::Days(days) => write!(f, "{} D", days),
+            Duration::Weeks(weeks) => write!(f, "{} W", weeks),
+            Duration::Months(months) => write!(f, "{} M", months),
+            Duration::Years(years) => write!(f, "{} Y", years),
+        }
+    }
+}
+
 //==================================================================================================
 #[repr(i32)]
 #[derive(Serialize, Deserialize, Clone, FromPrimitive, Debug)]
@@ -1307,14 +1721,19 @@ impl Display for SecIdType {
 }
 
 //==================================================================================================
-#[repr(i32)]
-#[derive(Serialize, Deserialize, Clone, FromPrimitive, Debug)]
+/// The security type of a contract.  Codes TWS sends that are not listed are kept as Other.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(from = "String", into = "String")]
 pub enum SecType {
+    #[default]
     None,
     STK,
     OPT,
     FUT,
+    /// a continuous future, for market and historical data but not orders
     CONTFUT,
+    /// the expiring futures and the continuous future of a symbol, for contract details only
+    FUTCONTFUT,
     CASH,
     BOND,
     CFD,
@@ -1333,36 +1752,118 @@ pub enum SecType {
     BSK,
     ICU,
     ICS,
+    CRYPTO,
+    Other(String),
 }
 
 impl Display for SecType {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
-        match *self {
-            SecType::None => write!(f, ""),
-            SecType::STK => write!(f, "STK"),
-            SecType::OPT => write!(f, "OPT"),
-            SecType::FUT => write!(f, "FUT"),
-            SecType::CONTFUT => write!(f, "CONTFUT"),
-            SecType::CASH => write!(f, "CASH"),
-            SecType::BOND => write!(f, "BOND"),
-            SecType::CFD => write!(f, "CFD"),
-            SecType::FOP => write!(f, "FOP"),
-            SecType::WAR => write!(f, "WAR"),
-            SecType::IOPT => write!(f, "IOPT"),
-            SecType::FWD => write!(f, "FWD"),
-            SecType::BAG => write!(f, "BAG"),
-            SecType::IND => write!(f, "IND"),
-            SecType::BILL => write!(f, "BILL"),
-            SecType::FUND => write!(f, "FUND"),
-            SecType::FIXED => write!(f, "FIXED"),
-            SecType::SLB => write!(f, "SLB"),
-            SecType::NEWS => write!(f, "NEWS"),
-            SecType::CMDTY => write!(f, "CMDTY"),
-            SecType::BSK => write!(f, "BSK"),
-            SecType::ICU => write!(f, "ICU"),
-            SecType::ICS => write!(f, "ICS"),
+        write!(f, "{}", self.code())
+    }
+}
+
+impl SecType {
+    /// The code TWS knows the security type by, e.g. "STK", empty for None
+    pub fn code(&self) -> &str {
+        match self {
+            SecType::None => "",
+            SecType::STK => "STK",
+            SecType::OPT => "OPT",
+            SecType::FUT => "FUT",
+            SecType::CONTFUT => "CONTFUT",
+            SecType::FUTCONTFUT => "FUT+CONTFUT",
+            SecType::CASH => "CASH",
+            SecType::BOND => "BOND",
+            SecType::CFD => "CFD",
+            SecType::FOP => "FOP",
+            SecType::WAR => "WAR",
+            SecType::IOPT => "IOPT",
+            SecType::FWD => "FWD",
+            SecType::BAG => "BAG",
+            SecType::IND => "IND",
+            SecType::BILL => "BILL",
+            SecType::FUND => "FUND",
+            SecType::FIXED => "FIXED",
+            SecType::SLB => "SLB",
+            SecType::NEWS => "NEWS",
+            SecType::CMDTY => "CMDTY",
+            SecType::BSK => "BSK",
+            SecType::ICU => "ICU",
+            SecType::ICS => "ICS",
+            SecType::CRYPTO => "CRYPTO",
+            SecType::Other(code) => code,
         }
     }
+
+    //----------------------------------------------------------------------------------------------
+    /// The security type of a code TWS sends, e.g. "STK", None for unknown codes
+    pub fn from_code(code: &str) -> Option<SecType> {
+        let sec_type = match code {
+            "" => SecType::None,
+            "STK" => SecType::STK,
+            "OPT" => SecType::OPT,
+            "FUT" => SecType::FUT,
+            "CONTFUT" => SecType::CONTFUT,
+            "FUT+CONTFUT" => SecType::FUTCONTFUT,
+            "CASH" => SecType::CASH,
+            "BOND" => SecType::BOND,
+            "CFD" => SecType::CFD,
+            "FOP" => SecType::FOP,
+            "WAR" => SecType::WAR,
+            "IOPT" => SecType::IOPT,
+            "FWD" => SecType::FWD,
+            "BAG" => SecType::BAG,
+            "IND" => SecType::IND,
+            "BILL" => SecType::BILL,
+            "FUND" => SecType::FUND,
+            "FIXED" => SecType::FIXED,
+            "SLB" => SecType::SLB,
+            "NEWS" => SecType::NEWS,
+            "CMDTY" => SecType::CMDTY,
+            "BSK" => SecType::BSK,
+            "ICU" => SecType::ICU,
+            "ICS" => SecType::ICS,
+            "CRYPTO" => SecType::CRYPTO,
+            _ => return None,
+        };
+        Some(sec_type)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether the type names a continuous future, CONTFUT or FUT+CONTFUT, which can't be traded
+    pub fn is_continuous(&self) -> bool {
+        matches!(self, SecType::CONTFUT | SecType::FUTCONTFUT)
+    }
+}
+
+impl From<&str> for SecType {
+    fn from(code: &str) -> Self {
+        SecType::from_code(code).unwrap_or_else(|| SecType::Other(code.to_string()))
+    }
+}
+
+impl From<String> for SecType {
+    fn from(code: String) -> Self {
+        SecType::from(code.as_str())
+    }
+}
+
+impl From<SecType> for String {
+    fn from(sec_type: SecType) -> Self {
+        sec_type.to_string()
+    }
+}
+
+impl PartialEq<&str> for SecType {
+    fn eq(&self, code: &&str) -> bool {
+        self.code() == *code
+    }
+}
+
+impl PartialEq<String> for SecType {
+    fn eq(&self, code: &String) -> bool {
+        self.code() == code
+    }
 }
 
 //==================================================================================================
@@ -1434,6 +1935,13 @@ impl UsePriceMgmtAlgo {
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct WshEventData {
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_integer::unset",
+            with = "crate::core::serde_unset::unset_integer"
+        )
+    )]
     pub con_id: i32,
     pub filter: String,
     pub fill_watchlist: bool,
@@ -1441,6 +1949,13 @@ pub struct WshEventData {
     pub fill_competitors: bool,
     pub start_date: String,
     pub end_date: String,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_integer::unset",
+            with = "crate::core::serde_unset::unset_integer"
+        )
+    )]
     pub total_limit: i32,
 }
 
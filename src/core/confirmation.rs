@@ -0,0 +1,179 @@
+//! Detects API orders stalled behind a TWS confirmation dialog
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::core::client::EClient;
+use crate::core::errors::{IBKRApiLibError, ORDER_MESSAGE_WARNING};
+use crate::core::wrapper::Wrapper;
+
+/// Error codes TWS sends for an order that is waiting on a message box ("Order message")
+pub const DEFAULT_CONFIRMATION_CODES: [i32; 1] = [ORDER_MESSAGE_WARNING];
+
+//==================================================================================================
+/// Something the monitor noticed about a watched order
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfirmationEvent {
+    /// The order got no status within the timeout, or TWS sent a confirmation warning for it.
+    /// Someone probably has to click a dialog in TWS.
+    PendingManualConfirmation { order_id: i32, waited: Duration },
+    /// The order was still stalled at the cancel deadline and should be cancelled
+    AutoCancel { order_id: i32, waited: Duration },
+}
+
+impl fmt::Display for ConfirmationEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfirmationEvent::PendingManualConfirmation { order_id, waited } => write!(
+                f,
+                "PendingManualConfirmation order_id: {}, waited: {:?}",
+                order_id, waited
+            ),
+            ConfirmationEvent::AutoCancel { order_id, waited } => {
+                write!(f, "AutoCancel order_id: {}, waited: {:?}", order_id, waited)
+            }
+        }
+    }
+}
+
+//==================================================================================================
+#[derive(Clone, Debug)]
+struct WatchedOrder {
+    submitted: Instant,
+    reported: bool,
+}
+
+//==================================================================================================
+/// Watches submitted orders for the pattern of a TWS confirmation dialog: no order status
+/// after the timeout, or one of the confirmation warning codes for the order.
+///
+/// The monitor is driven by the caller:
+/// * on_submitted after place_order
+/// * on_order_status from Wrapper::order_status and Wrapper::open_order
+/// * on_message from Wrapper::error and Wrapper::warning
+/// * poll (or cancel_stalled) periodically with the current time
+#[derive(Clone, Debug)]
+pub struct ConfirmationMonitor {
+    status_timeout: Duration,
+    cancel_after: Option<Duration>,
+    confirmation_codes: Vec<i32>,
+    orders: HashMap<i32, WatchedOrder>,
+}
+
+impl ConfirmationMonitor {
+    /// # Arguments
+    /// * status_timeout - how long a submitted order may go without a status
+    /// * cancel_after - if set, stalled orders are auto-cancelled once this long has passed
+    ///   since they were submitted
+    pub fn new(status_timeout: Duration, cancel_after: Option<Duration>) -> Self {
+        ConfirmationMonitor {
+            status_timeout,
+            cancel_after,
+            confirmation_codes: DEFAULT_CONFIRMATION_CODES.to_vec(),
+            orders: HashMap::new(),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Replaces the error codes treated as a confirmation dialog for the order
+    pub fn set_confirmation_codes(&mut self, codes: Vec<i32>) {
+        self.confirmation_codes = codes;
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Starts watching an order
+    pub fn on_submitted(&mut self, order_id: i32, now: Instant) {
+        self.orders.insert(
+            order_id,
+            WatchedOrder {
+                submitted: now,
+                reported: false,
+            },
+        );
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Stops watching an order once TWS reports a status for it
+    pub fn on_order_status(&mut self, order_id: i32) {
+        self.orders.remove(&order_id);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Checks an error or warning message.  Returns PendingManualConfirmation the first time a
+    /// confirmation code arrives for a watched order.
+    pub fn on_message(
+        &mut self,
+        req_id: i32,
+        code: i32,
+        now: Instant,
+    ) -> Option<ConfirmationEvent> {
+        if !self.confirmation_codes.contains(&code) {
+            return None;
+        }
+        let order = self.orders.get_mut(&req_id)?;
+        if order.reported {
+            return None;
+        }
+        order.reported = true;
+        Some(ConfirmationEvent::PendingManualConfirmation {
+            order_id: req_id,
+            waited: now.saturating_duration_since(order.submitted),
+        })
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Returns the orders that stalled or reached the cancel deadline since the last poll.
+    /// Orders returned as AutoCancel are no longer watched.
+    pub fn poll(&mut self, now: Instant) -> Vec<ConfirmationEvent> {
+        let mut events = Vec::new();
+        let mut cancelled = Vec::new();
+        for (order_id, order) in self.orders.iter_mut() {
+            let waited = now.saturating_duration_since(order.submitted);
+            if !order.reported && waited >= self.status_timeout {
+                order.reported = true;
+                events.push(ConfirmationEvent::PendingManualConfirmation {
+                    order_id: *order_id,
+                    waited,
+                });
+            }
+            if let Some(cancel_after) = self.cancel_after {
+                if order.reported && waited >= cancel_after {
+                    cancelled.push(*order_id);
+                    events.push(ConfirmationEvent::AutoCancel {
+                        order_id: *order_id,
+                        waited,
+                    });
+                }
+            }
+        }
+        for order_id in cancelled {
+            self.orders.remove(&order_id);
+        }
+        events
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Polls and cancels the orders returned as AutoCancel
+    pub fn cancel_stalled<T>(
+        &mut self,
+        client: &mut EClient<T>,
+        now: Instant,
+    ) -> Result<Vec<ConfirmationEvent>, IBKRApiLibError>
+    where
+        T: Wrapper + Send + Sync + 'static,
+    {
+        let events = self.poll(now);
+        for event in events.iter() {
+            if let ConfirmationEvent::AutoCancel { order_id, .. } = event {
+                client.cancel_order(*order_id)?;
+            }
+        }
+        Ok(events)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Ids of the orders still waiting for a status
+    pub fn watched_orders(&self) -> Vec<i32> {
+        self.orders.keys().copied().collect()
+    }
+}
@@ -0,0 +1,223 @@
+//! Settings of the connection to TWS or IB Gateway
+//!
+//! ConnectionConfig gathers what EClient::connect_with needs besides the wrapper: where to
+//! connect, as which client, how long to wait, the connect options of the handshake, how to
+//! retry and how fast requests may be sent.  EClient keeps the config of the last connection
+//! for EClient::reconnect.
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::accounts::AccountId;
+
+/// Messages per second TWS accepts from a client before it paces or disconnects it
+pub const TWS_MAX_MESSAGE_RATE: u32 = 50;
+
+/// The error TWS sends before closing a connection whose client id another client uses
+pub const CLIENT_ID_IN_USE: i32 = 326;
+
+/// How long connect_with waits for TWS to accept the client id, unless the read timeout is
+/// set
+pub const CLIENT_ID_CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(5);
+
+//==================================================================================================
+/// How often a connection is attempted before connect_with gives up
+/// attempts - connection attempts in all, at least 1
+/// delay - the wait before the first retry, doubled for each one after it up to max_delay
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ReconnectPolicy {
+    pub attempts: u32,
+    pub delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl ReconnectPolicy {
+    pub fn new(attempts: u32, delay: Duration, max_delay: Duration) -> Self {
+        ReconnectPolicy {
+            attempts,
+            delay,
+            max_delay,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// A single attempt
+    pub fn never() -> Self {
+        ReconnectPolicy::new(1, Duration::from_secs(0), Duration::from_secs(0))
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The wait before retry number retry, counted from 1
+    pub fn delay_before(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy::never()
+    }
+}
+
+//==================================================================================================
+/// Where and how to connect.  Build with new and the with_ methods.
+/// connect_timeout - how long to wait for the TCP connection, None for the system's timeout
+/// read_timeout - how long the handshake may take once connected, None to wait indefinitely
+/// connect_options - the connect options TWS reads with the handshake, e.g. "+PACEAPI"
+/// max_message_rate - requests sent per second at most; sending waits above it
+/// client_id_retries - how many of the next client ids connect_with tries when TWS reports
+///   the client id in use; with retries it waits for TWS to accept the id before returning
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ConnectionConfig {
+    pub host: String,
+    pub port: u32,
+    pub client_id: i32,
+    pub connect_timeout: Option<Duration>,
+    pub read_timeout: Option<Duration>,
+    pub connect_options: Option<String>,
+    pub reconnect: ReconnectPolicy,
+    pub max_message_rate: Option<u32>,
+    pub client_id_retries: u32,
+}
+
+impl ConnectionConfig {
+    pub fn new(host: &str, port: u32, client_id: i32) -> Self {
+        ConnectionConfig {
+            host: host.to_string(),
+            port,
+            client_id,
+            connect_timeout: None,
+            read_timeout: None,
+            connect_options: None,
+            reconnect: ReconnectPolicy::never(),
+            max_message_rate: None,
+            client_id_retries: 0,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn with_read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = Some(read_timeout);
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn with_connect_options(mut self, connect_options: &str) -> Self {
+        self.connect_options = if connect_options.is_empty() {
+            None
+        } else {
+            Some(connect_options.to_string())
+        };
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn with_reconnect(mut self, reconnect: ReconnectPolicy) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn with_max_message_rate(mut self, max_message_rate: u32) -> Self {
+        self.max_message_rate = Some(max_message_rate);
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn with_client_id_retries(mut self, client_id_retries: u32) -> Self {
+        self.client_id_retries = client_id_retries;
+        self
+    }
+}
+
+impl Default for ConnectionConfig {
+    fn default() -> Self {
+        ConnectionConfig::new("127.0.0.1", 7497, 0)
+    }
+}
+
+impl fmt::Display for ConnectionConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "host: {}, port: {}, client_id: {}, connect_timeout: {:?}, read_timeout: {:?}, \
+             connect_options: {:?}, attempts: {}, max_message_rate: {:?}, client_id_retries: {}",
+            self.host,
+            self.port,
+            self.client_id,
+            self.connect_timeout,
+            self.read_timeout,
+            self.connect_options,
+            self.reconnect.attempts,
+            self.max_message_rate,
+            self.client_id_retries
+        )
+    }
+}
+
+//==================================================================================================
+/// Paces messages to a maximum number per second
+#[derive(Debug, Default)]
+pub(crate) struct MessagePacer {
+    sent: VecDeque<Instant>,
+}
+
+impl MessagePacer {
+    /// How long to wait at now before sending another message at rate per second
+    pub fn wait(&mut self, rate: u32, now: Instant) -> Duration {
+        let window = Duration::from_secs(1);
+        while self
+            .sent
+            .front()
+            .is_some_and(|sent| now.saturating_duration_since(*sent) >= window)
+        {
+            self.sent.pop_front();
+        }
+        if self.sent.len() < rate.max(1) as usize {
+            return Duration::from_secs(0);
+        }
+        match self.sent.front() {
+            Some(oldest) => window - now.saturating_duration_since(*oldest),
+            None => Duration::from_secs(0),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn record(&mut self, sent: Instant) {
+        self.sent.push_back(sent);
+    }
+}
+
+//==================================================================================================
+/// The API session of a connection once it is usable: TWS sent both the next valid order id
+/// and the managed accounts.  See EClient::wait_until_ready.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SessionReady {
+    pub next_valid_id: i32,
+    pub accounts: Vec<AccountId>,
+}
+
+impl fmt::Display for SessionReady {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let accounts: Vec<&str> = self
+            .accounts
+            .iter()
+            .map(|account| account.as_str())
+            .collect();
+        write!(
+            f,
+            "next_valid_id: {}, accounts: {}",
+            self.next_valid_id,
+            accounts.join(", ")
+        )
+    }
+}
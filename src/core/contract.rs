@@ -1,14 +1,27 @@
 //! Types related to Contracts
 use std::fmt::{Display, Error, Formatter};
+use std::time::Duration;
 
+use chrono::NaiveDate;
 use num_derive::FromPrimitive;
 use serde::{Deserialize, Serialize};
 // 0.2.6 (the trait)
 
-use crate::core::common::TagValue;
+use crate::core::common::{Right, SecType, TagValue};
+use crate::core::currency::Currency;
+use crate::core::exchange::Exchange;
+use crate::core::order::{Action, OrderType};
 
 use super::common::UNSET_DOUBLE;
 
+/// Interval between the requests of EClient::contract_details_bulk, keeping them under the
+/// 50 messages per second TWS accepts
+pub const CONTRACT_DETAILS_PACING: Duration = Duration::from_millis(25);
+
+/// Interval between the requests of EClient::search_symbols; TWS accepts one matching symbols
+/// request per second
+pub const SYMBOL_SEARCH_PACING: Duration = Duration::from_secs(1);
+
 //==================================================================================================
 #[repr(i32)]
 #[derive(Serialize, Deserialize, Clone, Copy, FromPrimitive, Debug)]
@@ -44,7 +57,7 @@ impl Default for PositionType {
 pub struct ComboLeg {
     pub con_id: i32,
     pub ratio: f64,
-    pub action: String,
+    pub action: Action,
     // BUY /SELL / SSHORT
     pub exchange: String,
     pub open_close: PositionType,
@@ -58,7 +71,7 @@ impl ComboLeg {
     pub fn new(
         con_id: i32,
         ratio: f64,
-        action: String,
+        action: Action,
         exchange: String,
         open_close: PositionType,
         short_sale_slot: i32,
@@ -135,15 +148,15 @@ impl Display for DeltaNeutralContract {
 pub struct Contract {
     pub con_id: i32,
     pub symbol: String,
-    pub sec_type: String,
+    pub sec_type: SecType,
     pub last_trade_date_or_contract_month: String,
     pub strike: f64,
     pub right: String,
     pub multiplier: String,
-    pub exchange: String,
-    pub primary_exchange: String,
+    pub exchange: Exchange,
+    pub primary_exchange: Exchange,
     // pick an actual (ie non - aggregate) exchange that the contract trades on.DO NOT SET TO SMART.
-    pub currency: String,
+    pub currency: Currency,
     pub local_symbol: String,
     pub trading_class: String,
     pub include_expired: bool,
@@ -162,14 +175,14 @@ impl Contract {
     pub fn new(
         con_id: i32,
         symbol: String,
-        sec_type: String,
+        sec_type: SecType,
         last_trade_date_or_contract_month: String,
         strike: f64,
         right: String,
         multiplier: String,
-        exchange: String,
-        primary_exchange: String,
-        currency: String,
+        exchange: Exchange,
+        primary_exchange: Exchange,
+        currency: Currency,
         local_symbol: String,
         trading_class: String,
         include_expired: bool,
@@ -200,6 +213,93 @@ impl Contract {
             delta_neutral_contract,
         }
     }
+
+    //----------------------------------------------------------------------------------------------
+    /// A US stock, routed SMART in USD
+    pub fn stock(symbol: &str) -> Self {
+        Contract {
+            symbol: symbol.to_string(),
+            sec_type: SecType::STK,
+            exchange: Exchange::Smart,
+            currency: Currency::usd(),
+            ..Default::default()
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// A currency pair on IDEALPRO, e.g. "EURUSD", "EUR.USD" or "EUR/USD" to trade EUR against
+    /// USD.  A pair not made of two three letter codes is kept whole as the symbol, without a
+    /// currency.
+    pub fn forex(pair: &str) -> Self {
+        let codes: String = pair.chars().filter(|c| !matches!(c, '.' | '/')).collect();
+        let (symbol, currency) = match (codes.get(..3), codes.get(3..)) {
+            (Some(base), Some(quote)) if codes.len() == 6 => {
+                (base.to_ascii_uppercase(), quote.into())
+            }
+            _ => (pair.to_string(), Currency::default()),
+        };
+        Contract {
+            symbol,
+            sec_type: SecType::CASH,
+            exchange: Exchange::IdealPro,
+            currency,
+            ..Default::default()
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// A future in USD by its contract month, e.g. "202506", on an exchange, e.g. "CME"
+    pub fn future(symbol: &str, contract_month: &str, exchange: &str) -> Self {
+        Contract {
+            symbol: symbol.to_string(),
+            sec_type: SecType::FUT,
+            last_trade_date_or_contract_month: contract_month.to_string(),
+            exchange: exchange.into(),
+            currency: Currency::usd(),
+            ..Default::default()
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The continuous future of a symbol in USD on an exchange, e.g. "CME", for market and
+    /// historical data spanning the rolls.  See front_month for the contract to trade.
+    pub fn continuous_future(symbol: &str, exchange: &str) -> Self {
+        Contract {
+            symbol: symbol.to_string(),
+            sec_type: SecType::CONTFUT,
+            exchange: exchange.into(),
+            currency: Currency::usd(),
+            ..Default::default()
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// A US equity option of 100 shares, routed SMART in USD, by its expiry, e.g. "20250620"
+    pub fn option(symbol: &str, expiry: &str, strike: f64, right: Right) -> Self {
+        Contract {
+            symbol: symbol.to_string(),
+            sec_type: SecType::OPT,
+            last_trade_date_or_contract_month: expiry.to_string(),
+            strike,
+            right: right.to_string(),
+            multiplier: "100".to_string(),
+            exchange: Exchange::Smart,
+            currency: Currency::usd(),
+            ..Default::default()
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// A crypto currency on PAXOS in USD
+    pub fn crypto(symbol: &str) -> Self {
+        Contract {
+            symbol: symbol.to_string(),
+            sec_type: SecType::CRYPTO,
+            exchange: Exchange::Paxos,
+            currency: Currency::usd(),
+            ..Default::default()
+        }
+    }
 }
 
 impl Display for Contract {
@@ -280,8 +380,29 @@ pub struct ContractDetails {
     pub real_expiration_date: String,
     pub last_trade_time: String,
     pub stock_type: String,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub min_size: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub size_increment: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub suggested_size_increment: f64,
 
     // BOND values
@@ -394,6 +515,53 @@ impl ContractDetails {
             notes,
         }
     }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether the contract accepts orders of a type.  See OrderType::is_supported_by.
+    pub fn supports_order_type(&self, order_type: &OrderType) -> bool {
+        order_type.is_supported_by(&self.order_types)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The last trade date of the contract, from its "yyyymmdd" or "yyyymmdd hh:mm" date
+    pub fn last_trade_date(&self) -> Option<NaiveDate> {
+        self.contract
+            .last_trade_date_or_contract_month
+            .get(..8)
+            .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+    }
+}
+
+//==================================================================================================
+/// The front month future to trade for a continuous future, from the details TWS sends for
+/// a CONTFUT or FUT+CONTFUT contract.
+///
+/// The continuous future's details name the month IB rolls to, keeping its con_id, so when
+/// they are present that month is returned as a FUT contract.  Otherwise it is the FUT of
+/// the details with the earliest last trade date on or after today.  None if no future in
+/// the details is still trading.
+pub fn front_month(details: &[ContractDetails], today: NaiveDate) -> Option<Contract> {
+    let trading = |detail: &&ContractDetails| {
+        detail
+            .last_trade_date()
+            .is_some_and(|last_trade_date| last_trade_date >= today)
+    };
+    let continuous = details
+        .iter()
+        .filter(trading)
+        .find(|detail| detail.contract.sec_type == SecType::CONTFUT);
+    let detail = match continuous {
+        Some(detail) => detail,
+        None => details
+            .iter()
+            .filter(trading)
+            .filter(|detail| detail.contract.sec_type == SecType::FUT)
+            .min_by_key(|detail| detail.last_trade_date())?,
+    };
+    Some(Contract {
+        sec_type: SecType::FUT,
+        ..detail.contract.clone()
+    })
 }
 
 impl Display for ContractDetails {
@@ -559,6 +727,15 @@ impl ContractDescription {
             derivative_sec_types,
         }
     }
+
+    //----------------------------------------------------------------------------------------------
+    /// The security types of the derivatives of the contract, skipping codes SecType lacks
+    pub fn derivative_types(&self) -> Vec<SecType> {
+        self.derivative_sec_types
+            .iter()
+            .filter_map(|code| SecType::from_code(code))
+            .collect()
+    }
 }
 
 impl Display for ContractDescription {
@@ -0,0 +1,289 @@
+//! Cache of contract details that reports what changed when an entry is refreshed
+//!
+//! Exchanges change trading hours, tick size regimes and multipliers without notice.  Feed
+//! ContractCache the details of every Wrapper::contract_details callback; refreshing an entry
+//! compares the new details to the cached ones and sends a ContractChanged to every
+//! subscriber when a field strategies depend on differs.  Trading and liquid hours are compared
+//! as parsed sessions on the days both details list, so the window TWS sends moving on by a
+//! day, or the format of the hours changing, is not a change.
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDate;
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use crate::core::contract::ContractDetails;
+use crate::core::instrument::{parse_trading_hours, TradingSession};
+use crate::core::log_targets::DECODER;
+
+//==================================================================================================
+/// The contract detail fields compared on refresh
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ContractField {
+    MinTick,
+    Multiplier,
+    PriceMagnifier,
+    MarketRuleIds,
+    MinSize,
+    SizeIncrement,
+    TradingHours,
+    LiquidHours,
+    TimeZoneId,
+    OrderTypes,
+    ValidExchanges,
+    LastTradeTime,
+    RealExpirationDate,
+}
+
+impl ContractField {
+    pub const ALL: [ContractField; 13] = [
+        ContractField::MinTick,
+        ContractField::Multiplier,
+        ContractField::PriceMagnifier,
+        ContractField::MarketRuleIds,
+        ContractField::MinSize,
+        ContractField::SizeIncrement,
+        ContractField::TradingHours,
+        ContractField::LiquidHours,
+        ContractField::TimeZoneId,
+        ContractField::OrderTypes,
+        ContractField::ValidExchanges,
+        ContractField::LastTradeTime,
+        ContractField::RealExpirationDate,
+    ];
+
+    //----------------------------------------------------------------------------------------------
+    /// The value of the field, as text
+    pub fn value(&self, details: &ContractDetails) -> String {
+        match *self {
+            ContractField::MinTick => details.min_tick.to_string(),
+            ContractField::Multiplier => details.contract.multiplier.clone(),
+            ContractField::PriceMagnifier => details.price_magnifier.to_string(),
+            ContractField::MarketRuleIds => details.market_rule_ids.clone(),
+            ContractField::MinSize => details.min_size.to_string(),
+            ContractField::SizeIncrement => details.size_increment.to_string(),
+            ContractField::TradingHours => details.trading_hours.clone(),
+            ContractField::LiquidHours => details.liquid_hours.clone(),
+            ContractField::TimeZoneId => details.time_zone_id.clone(),
+            ContractField::OrderTypes => details.order_types.clone(),
+            ContractField::ValidExchanges => details.valid_exchanges.clone(),
+            ContractField::LastTradeTime => details.last_trade_time.clone(),
+            ContractField::RealExpirationDate => details.real_expiration_date.clone(),
+        }
+    }
+}
+
+//==================================================================================================
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ContractFieldChange {
+    pub field: ContractField,
+    pub old: String,
+    pub new: String,
+}
+
+impl fmt::Display for ContractFieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "field: {:?}, old: {}, new: {}",
+            self.field, self.old, self.new
+        )
+    }
+}
+
+//==================================================================================================
+/// The fields of a contract that differ between the cached and the refreshed details
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ContractChanged {
+    pub con_id: i32,
+    pub symbol: String,
+    pub changes: Vec<ContractFieldChange>,
+}
+
+impl ContractChanged {
+    /// The change of one field, if it changed
+    pub fn change(&self, field: ContractField) -> Option<&ContractFieldChange> {
+        self.changes.iter().find(|change| change.field == field)
+    }
+}
+
+impl fmt::Display for ContractChanged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "con_id: {}, symbol: {}, changes: {:?}",
+            self.con_id, self.symbol, self.changes
+        )
+    }
+}
+
+//==================================================================================================
+/// The fields in ContractField::ALL that differ between old and new
+pub fn diff_contract_details(
+    old: &ContractDetails,
+    new: &ContractDetails,
+) -> Vec<ContractFieldChange> {
+    ContractField::ALL
+        .iter()
+        .filter_map(|field| {
+            let (old, new) = (field.value(old), field.value(new));
+            let same = match field {
+                ContractField::TradingHours | ContractField::LiquidHours => {
+                    same_sessions(&old, &new)
+                }
+                _ => old == new,
+            };
+            if same {
+                None
+            } else {
+                Some(ContractFieldChange {
+                    field: *field,
+                    old,
+                    new,
+                })
+            }
+        })
+        .collect()
+}
+
+//==================================================================================================
+/// Whether two trading_hours or liquid_hours have the same sessions on the days both list,
+/// closed days included
+fn same_sessions(old: &str, new: &str) -> bool {
+    let (first, last) = match (listed_days(old), listed_days(new)) {
+        (Some(old_days), Some(new_days)) => {
+            (old_days.0.max(new_days.0), old_days.1.min(new_days.1))
+        }
+        (old_days, new_days) => return old_days.is_none() && new_days.is_none(),
+    };
+    let overlapping = |hours: &str| -> Vec<TradingSession> {
+        parse_trading_hours(hours)
+            .into_iter()
+            .filter(|session| first <= session.start.date() && session.start.date() <= last)
+            .collect()
+    };
+    overlapping(old) == overlapping(new)
+}
+
+//==================================================================================================
+/// The first and last day listed in trading_hours or liquid_hours
+fn listed_days(hours: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let days: Vec<NaiveDate> = hours
+        .split(';')
+        .filter_map(|day| day.trim().split_once(':'))
+        .filter_map(|(date, _)| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+        .collect();
+    Some((*days.iter().min()?, *days.iter().max()?))
+}
+
+//==================================================================================================
+#[derive(Clone, Debug)]
+struct CachedContract {
+    details: ContractDetails,
+    fetched: Instant,
+}
+
+//==================================================================================================
+/// Contract details keyed by con_id, with when each was last fetched
+/// max_age - how long details stay fresh before stale lists them for refreshing
+#[derive(Debug)]
+pub struct ContractCache {
+    max_age: Duration,
+    entries: HashMap<i32, CachedContract>,
+    subscribers: Vec<Sender<ContractChanged>>,
+}
+
+impl ContractCache {
+    pub fn new(max_age: Duration) -> Self {
+        ContractCache {
+            max_age,
+            entries: HashMap::new(),
+            subscribers: vec![],
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Receives a ContractChanged for every refresh that changes a contract
+    pub fn subscribe(&mut self) -> Receiver<ContractChanged> {
+        let (sender, receiver) = channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn get(&self, con_id: i32) -> Option<&ContractDetails> {
+        self.entries.get(&con_id).map(|entry| &entry.details)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether the details of a contract are cached and younger than max_age at now
+    pub fn is_fresh(&self, con_id: i32, now: Instant) -> bool {
+        self.entries
+            .get(&con_id)
+            .is_some_and(|entry| now.saturating_duration_since(entry.fetched) < self.max_age)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The con_ids of the contracts due for a refresh at now
+    pub fn stale(&self, now: Instant) -> Vec<i32> {
+        let mut stale: Vec<i32> = self
+            .entries
+            .keys()
+            .filter(|con_id| !self.is_fresh(**con_id, now))
+            .copied()
+            .collect();
+        stale.sort_unstable();
+        stale
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Stores the details fetched at now.  If the contract was cached and fields changed, sends
+    /// every subscriber the changes and returns them.
+    pub fn update(&mut self, details: ContractDetails, now: Instant) -> Option<ContractChanged> {
+        let con_id = details.contract.con_id;
+        let changed = self.entries.get(&con_id).and_then(|entry| {
+            let changes = diff_contract_details(&entry.details, &details);
+            if changes.is_empty() {
+                None
+            } else {
+                Some(ContractChanged {
+                    con_id,
+                    symbol: details.contract.symbol.clone(),
+                    changes,
+                })
+            }
+        });
+        self.entries.insert(
+            con_id,
+            CachedContract {
+                details,
+                fetched: now,
+            },
+        );
+
+        if let Some(changed) = changed.as_ref() {
+            warn!(target: DECODER, "Contract details changed -- {}", changed);
+            self.subscribers
+                .retain(|subscriber| subscriber.send(changed.clone()).is_ok());
+        }
+        changed
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn remove(&mut self, con_id: i32) -> Option<ContractDetails> {
+        self.entries.remove(&con_id).map(|entry| entry.details)
+    }
+}
@@ -0,0 +1,119 @@
+//! Report of the message fields TWS sent that the Decoder did not parse
+use std::collections::BTreeMap;
+use std::fmt;
+
+use num_traits::FromPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::core::messages::IncomingMessageIds;
+
+//==================================================================================================
+/// Unparsed trailing fields of one incoming message type
+/// message_id - the IncomingMessageIds value
+/// messages - messages of this type decoded
+/// with_unparsed - messages of this type which had trailing fields left after decoding
+/// max_unparsed - the most trailing fields seen in one message
+/// last_unparsed - the trailing fields of the last message which had some
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct MessageCoverage {
+    pub message_id: i32,
+    pub messages: u64,
+    pub with_unparsed: u64,
+    pub max_unparsed: usize,
+    pub last_unparsed: Vec<String>,
+}
+
+impl fmt::Display for MessageCoverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match IncomingMessageIds::from_i32(self.message_id) {
+            Some(id) => format!("{:?}", id),
+            None => "Unknown".to_string(),
+        };
+        write!(
+            f,
+            "message: {} ({}), messages: {}, with_unparsed: {}, max_unparsed: {}, last_unparsed: {:?}",
+            name,
+            self.message_id,
+            self.messages,
+            self.with_unparsed,
+            self.max_unparsed,
+            self.last_unparsed
+        )
+    }
+}
+
+//==================================================================================================
+/// Field coverage of the messages decoded in a session.  Recorded by the Decoder in lenient
+/// mode, see EClient::set_lenient_decoding.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct FieldCoverage {
+    messages: BTreeMap<i32, MessageCoverage>,
+}
+
+impl FieldCoverage {
+    pub fn new() -> Self {
+        FieldCoverage::default()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Records one decoded message
+    ///
+    /// # Arguments
+    /// * fields - the message's fields, starting with the message id
+    /// * unparsed - how many fields at the end of the message were not decoded
+    pub fn record(&mut self, fields: &[String], unparsed: usize) {
+        let message_id = match fields.first().and_then(|id| id.parse::<i32>().ok()) {
+            Some(message_id) => message_id,
+            None => return,
+        };
+        let coverage = self
+            .messages
+            .entry(message_id)
+            .or_insert_with(|| MessageCoverage {
+                message_id,
+                ..Default::default()
+            });
+        coverage.messages += 1;
+        if unparsed > 0 {
+            coverage.with_unparsed += 1;
+            coverage.max_unparsed = coverage.max_unparsed.max(unparsed);
+            coverage.last_unparsed = fields[fields.len().saturating_sub(unparsed)..].to_vec();
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Message types which had unparsed fields, ordered by message id
+    pub fn report(&self) -> Vec<MessageCoverage> {
+        self.messages
+            .values()
+            .filter(|coverage| coverage.with_unparsed > 0)
+            .cloned()
+            .collect()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Every message type decoded, ordered by message id
+    pub fn all(&self) -> Vec<MessageCoverage> {
+        self.messages.values().cloned().collect()
+    }
+}
+
+impl fmt::Display for FieldCoverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let report = self.report();
+        if report.is_empty() {
+            return write!(
+                f,
+                "all fields of {} message types parsed",
+                self.messages.len()
+            );
+        }
+        for (i, coverage) in report.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", coverage)?;
+        }
+        Ok(())
+    }
+}
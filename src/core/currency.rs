@@ -0,0 +1,198 @@
+//! Currency codes
+//!
+//! IB names currencies by their upper case ISO 4217 code, e.g. "USD", and by "BASE" for the
+//! base currency of an account in account values.  TWS answers a contract in a mixed case or
+//! made up currency with a generic "no security definition" error, so Currency keeps codes in
+//! upper case and EClient rejects contracts whose currency is not an ISO 4217 code before
+//! sending the request.
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::common::NO_VALID_ID;
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+
+/// The code IB uses for the base currency of an account
+pub const BASE_CURRENCY: &str = "BASE";
+
+/// The active ISO 4217 codes, plus CNH, the offshore yuan IB trades.  Sorted for binary_search.
+const ISO_CODES: &[&str] = &[
+    "AED", "AFN", "ALL", "AMD", "ANG", "AOA", "ARS", "AUD", "AWG", "AZN", "BAM", "BBD", "BDT",
+    "BGN", "BHD", "BIF", "BMD", "BND", "BOB", "BRL", "BSD", "BTN", "BWP", "BYN", "BZD", "CAD",
+    "CDF", "CHF", "CLP", "CNH", "CNY", "COP", "CRC", "CUP", "CVE", "CZK", "DJF", "DKK", "DOP",
+    "DZD", "EGP", "ERN", "ETB", "EUR", "FJD", "FKP", "GBP", "GEL", "GHS", "GIP", "GMD", "GNF",
+    "GTQ", "GYD", "HKD", "HNL", "HTG", "HUF", "IDR", "ILS", "INR", "IQD", "IRR", "ISK", "JMD",
+    "JOD", "JPY", "KES", "KGS", "KHR", "KMF", "KPW", "KRW", "KWD", "KYD", "KZT", "LAK", "LBP",
+    "LKR", "LRD", "LSL", "LYD", "MAD", "MDL", "MGA", "MKD", "MMK", "MNT", "MOP", "MRU", "MUR",
+    "MVR", "MWK", "MXN", "MYR", "MZN", "NAD", "NGN", "NIO", "NOK", "NPR", "NZD", "OMR", "PAB",
+    "PEN", "PGK", "PHP", "PKR", "PLN", "PYG", "QAR", "RON", "RSD", "RUB", "RWF", "SAR", "SBD",
+    "SCR", "SDG", "SEK", "SGD", "SHP", "SLE", "SOS", "SRD", "SSP", "STN", "SVC", "SYP", "SZL",
+    "THB", "TJS", "TMT", "TND", "TOP", "TRY", "TTD", "TWD", "TZS", "UAH", "UGX", "USD", "UYU",
+    "UZS", "VES", "VND", "VUV", "WST", "XAF", "XAG", "XAU", "XCD", "XOF", "XPF", "YER", "ZAR",
+    "ZMW", "ZWL",
+];
+
+/// The market convention for which currency of a pair is the base one, in order: of two
+/// currencies, the earlier one is the base, e.g. EUR.USD and USD.JPY.  Currencies not listed
+/// rank after these, except JPY, which is quoted against every other currency.
+const BASE_PRIORITY: &[&str] = &["EUR", "GBP", "AUD", "NZD", "USD", "CAD", "CHF"];
+
+//==================================================================================================
+/// A currency code in upper case, empty if unset.  Converting from text never fails so the
+/// currencies TWS sends are kept as they are; use Currency::parse to check a code.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(from = "String", into = "String")]
+pub struct Currency(String);
+
+impl Currency {
+    /// The currency of a code, in upper case
+    pub fn new(code: &str) -> Self {
+        Currency(code.trim().to_ascii_uppercase())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The currency of an ISO 4217 code, ignoring case.  Err with TwsError::InvalidRequest
+    /// for other codes, including BASE.
+    pub fn parse(code: &str) -> Result<Self, IBKRApiLibError> {
+        let currency = Currency::new(code);
+        match currency.is_iso() {
+            true => Ok(currency),
+            false => Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                NO_VALID_ID,
+                TwsError::InvalidRequest.code().to_string(),
+                format!(
+                    "{} {:?} is not an ISO 4217 currency code.",
+                    TwsError::InvalidRequest.message(),
+                    code
+                ),
+            ))),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The base currency of an account, as in account values
+    pub fn base() -> Self {
+        Currency(BASE_CURRENCY.to_string())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn usd() -> Self {
+        Currency("USD".to_string())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn eur() -> Self {
+        Currency("EUR".to_string())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn code(&self) -> &str {
+        &self.0
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn is_set(&self) -> bool {
+        !self.0.is_empty()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn is_base(&self) -> bool {
+        self.0 == BASE_CURRENCY
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether the code is an active ISO 4217 code or CNH
+    pub fn is_iso(&self) -> bool {
+        ISO_CODES.binary_search(&self.0.as_str()).is_ok()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The pair of a cash contract trading this currency for another, e.g. "EUR.USD"
+    pub fn pair(&self, quote: &Currency) -> String {
+        format!("{}.{}", self, quote)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The pair IDEALPRO lists for this currency and other, base currency first by market
+    /// convention, e.g. GBP.USD for USD and GBP.  Currencies the convention doesn't rank are
+    /// ordered by code.
+    pub fn market_pair(&self, other: &Currency) -> String {
+        match self.is_pair_base(other) {
+            true => self.pair(other),
+            false => other.pair(self),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether this currency is the base one of the pair it makes with other, see market_pair
+    pub fn is_pair_base(&self, other: &Currency) -> bool {
+        self.base_rank() < other.base_rank()
+            || (self.base_rank() == other.base_rank() && self.0 <= other.0)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn base_rank(&self) -> usize {
+        match BASE_PRIORITY.iter().position(|code| *code == self.0) {
+            Some(rank) => rank,
+            None if self.0 == "JPY" => BASE_PRIORITY.len() + 1,
+            None => BASE_PRIORITY.len(),
+        }
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Currency {
+    type Err = IBKRApiLibError;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        Currency::parse(code)
+    }
+}
+
+impl From<&str> for Currency {
+    fn from(code: &str) -> Self {
+        Currency::new(code)
+    }
+}
+
+impl From<String> for Currency {
+    fn from(code: String) -> Self {
+        Currency::new(&code)
+    }
+}
+
+impl From<Currency> for String {
+    fn from(currency: Currency) -> Self {
+        currency.0
+    }
+}
+
+impl AsRef<str> for Currency {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for Currency {
+    fn eq(&self, code: &str) -> bool {
+        self.0 == code
+    }
+}
+
+impl PartialEq<&str> for Currency {
+    fn eq(&self, code: &&str) -> bool {
+        self.0 == *code
+    }
+}
+
+impl PartialEq<String> for Currency {
+    fn eq(&self, code: &String) -> bool {
+        self.0 == *code
+    }
+}
@@ -6,28 +6,49 @@ use std::ops::Deref;
 use std::slice::Iter;
 use std::str::FromStr;
 use std::string::ToString;
-use std::sync::mpsc::Receiver;
+use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use bigdecimal::BigDecimal;
 use float_cmp::*;
 use log::*;
 use num_traits::float::FloatCore;
-use num_traits::FromPrimitive;
+use num_traits::{FromPrimitive, ToPrimitive};
 
+use crate::core::account_summary_tags::AccountSummaryTag;
 use crate::core::client::ConnStatus;
 use crate::core::common::{
-    BarData, CommissionReport, DepthMktDataDescription, FamilyCode, HistogramData, HistoricalTick,
-    HistoricalTickBidAsk, HistoricalTickLast, NewsProvider, PriceIncrement, RealTimeBar,
-    SmartComponent, TagValue, TickAttrib, TickAttribBidAsk, TickAttribLast, TickType, MAX_MSG_LEN,
-    NO_VALID_ID, UNSET_DOUBLE, UNSET_INTEGER,
+    BarData, CommissionReport, DepthMktDataDescription, FaDataType, FamilyCode, FeeRateBar,
+    HistogramData, HistogramEntry, HistoricalTick, HistoricalTickBidAsk, HistoricalTickLast,
+    NewsArticleContent, NewsProvider, PriceIncrement, RealTimeBar, SecType, SmartComponent,
+    TagValue, TickAttrib, TickAttribBidAsk, TickAttribLast, TickType, YieldBar, NO_VALID_ID,
+    UNSET_DOUBLE, UNSET_INTEGER,
 };
 use crate::core::contract::{Contract, ContractDescription, ContractDetails, DeltaNeutralContract};
-use crate::core::errors::{IBKRApiLibError, TwsError};
+use crate::core::currency::Currency;
+use crate::core::display_groups::{DisplayGroupEvent, DisplayGroupId, GroupSelection};
+use crate::core::errors::{
+    is_order_warning, is_warning_code, IBKRApiLibError, IbError, MessageError,
+    TwsApiReportableError, TwsError, TwsMessage,
+};
+use crate::core::exchange::Exchange;
 use crate::core::execution::Execution;
+use crate::core::exercise::ExerciseStatus;
+use crate::core::fa::{FaAliases, FaConfig};
+use crate::core::history;
+use crate::core::log_targets::DECODER;
+use crate::core::message_queue::QueueReceiver;
 use crate::core::messages::{read_fields, IncomingMessageIds};
+use crate::core::news_bulletins::{Bulletin, BulletinType};
+use crate::core::option_chains::OptionChain;
 use crate::core::order::{Order, OrderState, SoftDollarTier};
 use crate::core::order_decoder::OrderDecoder;
+use crate::core::order_tracker::OrderPhase;
+use crate::core::pending::{
+    ContractDetailsEvent, HistoricalBarKind, PendingRequests, PENDING_POISONED_MUTEX,
+};
+use crate::core::request_context;
 use crate::core::scanner::ScanData;
 use crate::core::server_versions::{
     MIN_SERVER_VER_AGG_GROUP, MIN_SERVER_VER_FRACTIONAL_POSITIONS,
@@ -40,6 +61,12 @@ use crate::core::server_versions::{
     MIN_SERVER_VER_SYNT_REALTIME_BARS, MIN_SERVER_VER_UNDERLYING_INFO,
     MIN_SERVER_VER_UNREALIZED_PNL,
 };
+use crate::core::smart_components::smart_component_map;
+use crate::core::tick_by_tick::{BidAskTick, LastTick, MidPointTick, TickByTickEvent};
+use crate::core::timestamps::{
+    timestamp_from_wire, timestamp_from_wire_in, timestamp_time, Timestamp,
+};
+use crate::core::what_if::MarginPreview;
 use crate::core::wrapper::Wrapper;
 
 use super::server_versions::{
@@ -87,6 +114,37 @@ pub fn decode_f64_show_unset(iter: &mut Iter<String>) -> Result<f64, IBKRApiLibE
     Ok(if retval == 0.0 { UNSET_DOUBLE } else { retval })
 }
 
+//==================================================================================================
+/// How IB spells an unset Decimal: Long.MAX_VALUE in the Java client, 2^127 - 1 in the Python one
+const UNSET_DECIMAL_FIELDS: [&str; 2] = [
+    "9223372036854775807",
+    "170141183460469231731687303715884105727",
+];
+
+//==================================================================================================
+/// Decodes a Decimal field.  An empty or unset field decodes as zero, as IB sends them for sizes
+/// it does not know.
+pub fn decode_decimal(iter: &mut Iter<String>) -> Result<BigDecimal, IBKRApiLibError> {
+    let next = iter.next();
+    if let Some(field) = next {
+        if field.is_empty() || UNSET_DECIMAL_FIELDS.contains(&field.as_str()) {
+            return Ok(BigDecimal::from(0));
+        }
+    }
+    next.and_then(|next| BigDecimal::from_str(next).ok())
+        .ok_or_else(|| {
+            IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                NO_VALID_ID,
+                TwsError::BadMessage.code().to_string(),
+                format!(
+                    "{} Invalid decimal {:?}.",
+                    TwsError::BadMessage.message(),
+                    next
+                ),
+            ))
+        })
+}
+
 //==================================================================================================
 pub fn decode_string(iter: &mut Iter<String>) -> Result<String, IBKRApiLibError> {
     let next = iter.next();
@@ -105,10 +163,14 @@ pub fn decode_bool(iter: &mut Iter<String>) -> Result<bool, IBKRApiLibError> {
 
 //==================================================================================================
 pub struct Decoder<T: Wrapper> {
-    msg_queue: Receiver<String>,
+    msg_queue: QueueReceiver,
     pub wrapper: Arc<Mutex<T>>,
     pub server_version: i32,
     conn_state: Arc<Mutex<ConnStatus>>,
+    pub(crate) pending: Arc<PendingRequests>,
+    /// whether this decoder tells the wrapper the connection closed, false for the priority
+    /// decoder of DispatchMode::PriorityOrders
+    pub(crate) reports_connection_closed: bool,
 }
 
 impl<T> Decoder<T>
@@ -117,7 +179,7 @@ where
 {
     pub fn new(
         the_wrapper: Arc<Mutex<T>>,
-        msg_queue: Receiver<String>,
+        msg_queue: QueueReceiver,
         server_version: i32,
         conn_state: Arc<Mutex<ConnStatus>>,
     ) -> Self {
@@ -126,6 +188,8 @@ where
             msg_queue: msg_queue,
             server_version,
             conn_state,
+            pending: Arc::new(PendingRequests::default()),
+            reports_connection_closed: true,
         }
     }
 
@@ -135,9 +199,30 @@ where
             return Ok(());
         }
 
+        *self
+            .pending
+            .last_inbound
+            .lock()
+            .expect(PENDING_POISONED_MUTEX) = Some(Instant::now());
         let msg_id = i32::from_str(fields.get(0).unwrap().as_str())?;
+        trace!(target: DECODER, "interpret: {:?}", fields);
+
+        let message: Option<IncomingMessageIds> = FromPrimitive::from_i32(msg_id);
+        let req_id = message
+            .as_ref()
+            .and_then(|message| self.request_id(message, fields));
+        // the request of the message is the current one while the wrapper handles it
+        let _context = req_id.map(|req_id| {
+            let context = self
+                .pending
+                .request_contexts
+                .lock()
+                .expect(PENDING_POISONED_MUTEX)
+                .get(req_id);
+            request_context::enter_request(req_id, context)
+        });
 
-        match FromPrimitive::from_i32(msg_id) {
+        match message {
             Some(IncomingMessageIds::TickPrice) => self.process_tick_price(fields)?,
             Some(IncomingMessageIds::AccountSummary) => self.process_account_summary(fields)?,
             Some(IncomingMessageIds::AccountSummaryEnd) => {
@@ -276,7 +361,132 @@ where
             Some(IncomingMessageIds::WshEventData) => self.process_wsh_event_data_msg(fields)?,
             _ => panic!("Received unkown message id!!  Exiting..."),
         }
-        Ok(())
+        if let (Some(message), Some(req_id)) = (message.as_ref(), req_id) {
+            self.end_request(message, req_id, fields);
+        }
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The req_id of a message, None for messages not answering a request
+    fn request_id(&self, message: &IncomingMessageIds, fields: &[String]) -> Option<i32> {
+        let position = match message {
+            IncomingMessageIds::TickPrice
+            | IncomingMessageIds::TickSize
+            | IncomingMessageIds::TickGeneric
+            | IncomingMessageIds::TickString
+            | IncomingMessageIds::TickEfp
+            | IncomingMessageIds::TickSnapshotEnd
+            | IncomingMessageIds::AccountSummary
+            | IncomingMessageIds::AccountSummaryEnd
+            | IncomingMessageIds::AccountUpdateMulti
+            | IncomingMessageIds::AccountUpdateMultiEnd
+            | IncomingMessageIds::PositionMulti
+            | IncomingMessageIds::PositionMultiEnd
+            | IncomingMessageIds::ContractDataEnd
+            | IncomingMessageIds::DeltaNeutralValidation
+            | IncomingMessageIds::DisplayGroupList
+            | IncomingMessageIds::DisplayGroupUpdated
+            | IncomingMessageIds::ErrMsg
+            | IncomingMessageIds::ExecutionDataEnd
+            | IncomingMessageIds::FundamentalData
+            | IncomingMessageIds::MarketDataType
+            | IncomingMessageIds::MarketDepth
+            | IncomingMessageIds::MarketDepthL2
+            | IncomingMessageIds::RealTimeBars
+            | IncomingMessageIds::ScannerData => 2,
+            IncomingMessageIds::HeadTimestamp
+            | IncomingMessageIds::HistogramData
+            | IncomingMessageIds::HistoricalDataUpdate
+            | IncomingMessageIds::HistoricalNews
+            | IncomingMessageIds::HistoricalNewsEnd
+            | IncomingMessageIds::HistoricalTicks
+            | IncomingMessageIds::HistoricalTicksBidAsk
+            | IncomingMessageIds::HistoricalTicksLast
+            | IncomingMessageIds::NewsArticle
+            | IncomingMessageIds::OrderBound
+            | IncomingMessageIds::Pnl
+            | IncomingMessageIds::PnlSingle
+            | IncomingMessageIds::RerouteMktDataReq
+            | IncomingMessageIds::RerouteMktDepthReq
+            | IncomingMessageIds::SecurityDefinitionOptionParameter
+            | IncomingMessageIds::SecurityDefinitionOptionParameterEnd
+            | IncomingMessageIds::SmartComponents
+            | IncomingMessageIds::SoftDollarTiers
+            | IncomingMessageIds::SymbolSamples
+            | IncomingMessageIds::TickByTick
+            | IncomingMessageIds::TickNews
+            | IncomingMessageIds::TickReqParams
+            | IncomingMessageIds::ReplaceFaEnd
+            | IncomingMessageIds::WshMetadata
+            | IncomingMessageIds::WshEventData => 1,
+            IncomingMessageIds::TickOptionComputation => {
+                if self.server_version < MIN_SERVER_VER_PRICE_BASED_VOLATILITY {
+                    2
+                } else {
+                    1
+                }
+            }
+            IncomingMessageIds::HistoricalData => {
+                if self.server_version < MIN_SERVER_VER_SYNT_REALTIME_BARS {
+                    2
+                } else {
+                    1
+                }
+            }
+            // the version field is only sent to older servers, and the req_id from version 3 on
+            IncomingMessageIds::ContractData | IncomingMessageIds::BondContractData => {
+                if self.server_version >= MIN_SERVER_VER_SIZE_RULES {
+                    1
+                } else if fields.get(1)?.parse::<i32>().ok()? >= 3 {
+                    2
+                } else {
+                    return None;
+                }
+            }
+            _ => return None,
+        };
+        fields.get(position)?.parse().ok()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Drops the context and the tracked metadata of the request a message ends: the end message
+    /// of a one-shot request, or an error failing the request
+    fn end_request(&self, message: &IncomingMessageIds, req_id: i32, fields: &[String]) {
+        if let IncomingMessageIds::ErrMsg = message {
+            let code = fields.get(3).and_then(|code| code.parse::<i32>().ok());
+            if matches!(code, Some(code) if !IbError::from_code(code).is_informational()) {
+                self.pending.forget_request(req_id);
+            }
+            return;
+        }
+        let ends = match message {
+            // the last field of the historical ticks is whether they are done
+            IncomingMessageIds::HistoricalTicks
+            | IncomingMessageIds::HistoricalTicksBidAsk
+            | IncomingMessageIds::HistoricalTicksLast => {
+                matches!(fields.last(), Some(done) if done == "1")
+            }
+            IncomingMessageIds::ContractDataEnd
+            | IncomingMessageIds::ExecutionDataEnd
+            | IncomingMessageIds::FundamentalData
+            | IncomingMessageIds::HeadTimestamp
+            | IncomingMessageIds::HistogramData
+            | IncomingMessageIds::HistoricalData
+            | IncomingMessageIds::HistoricalNewsEnd
+            | IncomingMessageIds::NewsArticle
+            | IncomingMessageIds::SecurityDefinitionOptionParameterEnd
+            | IncomingMessageIds::SmartComponents
+            | IncomingMessageIds::SoftDollarTiers
+            | IncomingMessageIds::SymbolSamples
+            | IncomingMessageIds::TickSnapshotEnd
+            | IncomingMessageIds::WshMetadata
+            | IncomingMessageIds::WshEventData => true,
+            _ => false,
+        };
+        if ends {
+            self.pending.end_request(req_id);
+        }
     }
 
     //----------------------------------------------------------------------------------------------
@@ -291,7 +501,7 @@ where
         let req_id = decode_i32(&mut fields_itr)?;
         let tick_type: i32 = decode_i32(&mut fields_itr)?;
         let price: f64 = decode_f64(&mut fields_itr)?;
-        let size = decode_i32(&mut fields_itr)?;
+        let size = decode_decimal(&mut fields_itr)?;
         let attr_mask: i32 = decode_i32(&mut fields_itr)?;
         let mut tick_arrtibute = TickAttrib::new(false, false, false);
 
@@ -304,34 +514,49 @@ where
         if self.server_version >= MIN_SERVER_VER_PRE_OPEN_BID_ASK {
             tick_arrtibute.pre_open = attr_mask & 4 != 0;
         }
+
+        let size_tick_type = match TickType::from_code(tick_type) {
+            TickType::Bid => TickType::BidSize,
+            TickType::Ask => TickType::AskSize,
+            TickType::Last => TickType::LastSize,
+            TickType::DelayedBid => TickType::DelayedBidSize,
+            TickType::DelayedAsk => TickType::DelayedAskSize,
+            TickType::DelayedLast => TickType::DelayedLastSize,
+            _ => TickType::NotSet,
+        };
+
+        if let Some(pending) = self
+            .pending
+            .snapshots
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .get_mut(&req_id)
+        {
+            pending
+                .quote
+                .apply_price(TickType::from_code(tick_type), price);
+            pending.quote.apply_size(size_tick_type, size.clone());
+        }
+
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .tick_price(
                 req_id,
-                FromPrimitive::from_i32(tick_type).unwrap(),
+                TickType::from_code(tick_type),
                 price,
                 tick_arrtibute,
             );
 
         // process ver 2 fields
 
-        let size_tick_type = match FromPrimitive::from_i32(tick_type) {
-            Some(TickType::Bid) => TickType::BidSize,
-            Some(TickType::Ask) => TickType::AskSize,
-            Some(TickType::Last) => TickType::LastSize,
-            Some(TickType::DelayedBid) => TickType::DelayedBidSize,
-            Some(TickType::DelayedAsk) => TickType::DelayedAskSize,
-            Some(TickType::DelayedLast) => TickType::DelayedLastSize,
-            _ => TickType::NotSet,
-        };
-
-        if size_tick_type as i32 != TickType::NotSet as i32 {
+        if size_tick_type != TickType::NotSet {
             self.wrapper
                 .lock()
                 .expect(WRAPPER_POISONED_MUTEX)
                 .tick_size(req_id, size_tick_type, size);
         }
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -351,11 +576,8 @@ where
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
-            .tick_string(
-                req_id,
-                FromPrimitive::from_i32(tick_type).unwrap(),
-                value.as_ref(),
-            );
+            .tick_string(req_id, TickType::from_code(tick_type), value.as_ref());
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -374,10 +596,11 @@ where
             .account_summary(
                 decode_i32(&mut fields_itr)?,
                 decode_string(&mut fields_itr)?.as_ref(),
-                decode_string(&mut fields_itr)?.as_ref(),
+                &AccountSummaryTag::from_tag(&decode_string(&mut fields_itr)?),
                 decode_string(&mut fields_itr)?.as_ref(),
                 decode_string(&mut fields_itr)?.as_ref(),
             );
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -394,6 +617,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .account_summary_end(decode_i32(&mut fields_itr)?);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -424,6 +648,7 @@ where
                 value.as_ref(),
                 currency.as_ref(),
             );
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -461,6 +686,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .account_download_end(decode_string(&mut fields_itr)?.as_ref());
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -477,6 +703,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .update_account_time(decode_string(&mut fields_itr)?.as_ref());
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -498,6 +725,7 @@ where
                 decode_string(&mut fields_itr)?.as_ref(),
                 decode_string(&mut fields_itr)?.as_ref(),
             );
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -521,7 +749,7 @@ where
         let mut contract = ContractDetails::default();
 
         contract.contract.symbol = decode_string(&mut fields_itr)?;
-        contract.contract.sec_type = decode_string(&mut fields_itr)?;
+        contract.contract.sec_type = SecType::from(decode_string(&mut fields_itr)?);
         contract.cusip = decode_string(&mut fields_itr)?;
         contract.coupon = decode_f64(&mut fields_itr)?;
         self.read_last_trade_date(&mut contract, true, fields_itr.next().unwrap())?;
@@ -533,8 +761,8 @@ where
         contract.callable = i32::from_str(fields_itr.next().unwrap().as_ref())? != 0;
         contract.putable = i32::from_str(fields_itr.next().unwrap().as_ref())? != 0;
         contract.desc_append = decode_string(&mut fields_itr)?;
-        contract.contract.exchange = decode_string(&mut fields_itr)?;
-        contract.contract.currency = decode_string(&mut fields_itr)?;
+        contract.contract.exchange = Exchange::from(decode_string(&mut fields_itr)?);
+        contract.contract.currency = Currency::from(decode_string(&mut fields_itr)?);
         contract.market_name = decode_string(&mut fields_itr)?;
         contract.contract.trading_class = decode_string(&mut fields_itr)?;
         contract.contract.con_id = decode_i32(&mut fields_itr)?;
@@ -583,10 +811,16 @@ where
             contract.suggested_size_increment = decode_f64(&mut fields_itr)?;
         }
 
+        self.record_order_types(&contract);
+        self.send_contract_details(
+            req_id,
+            ContractDetailsEvent::Details(Box::new(contract.clone())),
+        );
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .bond_contract_details(req_id, contract.clone());
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -601,7 +835,7 @@ where
         let mut commission_report = CommissionReport::default();
         commission_report.exec_id = fields_itr.next().unwrap().to_string();
         commission_report.commission = decode_f64(&mut fields_itr)?;
-        commission_report.currency = fields_itr.next().unwrap().to_string();
+        commission_report.currency = Currency::from(fields_itr.next().unwrap().as_str());
 
         commission_report.realized_pnl = decode_f64(&mut fields_itr)?;
 
@@ -613,6 +847,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .commission_report(commission_report);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -641,6 +876,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .completed_order(contract, order, order_state);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -654,6 +890,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .completed_orders_end();
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -677,12 +914,12 @@ where
         let mut contract = ContractDetails::default();
 
         contract.contract.symbol = decode_string(&mut fields_itr)?;
-        contract.contract.sec_type = decode_string(&mut fields_itr)?;
+        contract.contract.sec_type = SecType::from(decode_string(&mut fields_itr)?);
         self.read_last_trade_date(&mut contract, false, fields_itr.next().unwrap())?;
         contract.contract.strike = decode_f64(&mut fields_itr)?;
         contract.contract.right = decode_string(&mut fields_itr)?;
-        contract.contract.exchange = decode_string(&mut fields_itr)?;
-        contract.contract.currency = decode_string(&mut fields_itr)?;
+        contract.contract.exchange = Exchange::from(decode_string(&mut fields_itr)?);
+        contract.contract.currency = Currency::from(decode_string(&mut fields_itr)?);
         contract.contract.local_symbol = decode_string(&mut fields_itr)?;
         contract.market_name = decode_string(&mut fields_itr)?;
         contract.contract.trading_class = decode_string(&mut fields_itr)?;
@@ -702,7 +939,7 @@ where
         }
         if version >= 5 {
             contract.long_name = decode_string(&mut fields_itr)?;
-            contract.contract.primary_exchange = decode_string(&mut fields_itr)?;
+            contract.contract.primary_exchange = Exchange::from(decode_string(&mut fields_itr)?);
         }
 
         if version >= 6 {
@@ -763,10 +1000,16 @@ where
             contract.suggested_size_increment = decode_f64(&mut fields_itr)?;
         }
 
+        self.record_order_types(&contract);
+        self.send_contract_details(
+            req_id,
+            ContractDetailsEvent::Details(Box::new(contract.clone())),
+        );
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .contract_details(req_id, contract.clone());
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -781,13 +1024,51 @@ where
 
         let req_id = decode_i32(&mut fields_itr)?;
 
+        self.send_contract_details(req_id, ContractDetailsEvent::End);
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .contract_details_end(req_id);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Keeps the order types of a contract on its exchange, which EClient::place_order checks
+    /// orders against
+    fn record_order_types(&self, details: &ContractDetails) {
+        if details.contract.con_id != 0 {
+            let contract = &details.contract;
+            self.pending
+                .order_types
+                .lock()
+                .expect(PENDING_POISONED_MUTEX)
+                .insert(
+                    (contract.con_id, contract.exchange.clone()),
+                    details.order_types.clone(),
+                );
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Passes a response to contract_details_bulk if it made the request, forgetting the
+    /// request once it ends
+    fn send_contract_details(&self, req_id: i32, event: ContractDetailsEvent) {
+        let mut lookups = self
+            .pending
+            .contract_details
+            .lock()
+            .expect(PENDING_POISONED_MUTEX);
+        let sender = match event {
+            ContractDetailsEvent::Details(_) => lookups.get(&req_id).cloned(),
+            _ => lookups.remove(&req_id),
+        };
+        if let Some(sender) = sender {
+            // contract_details_bulk may have timed out and dropped the receiver
+            let _ = sender.send((req_id, event));
+        }
+    }
+
     //----------------------------------------------------------------------------------------------
     fn process_current_time(&mut self, fields: &[String]) -> Result<(), IBKRApiLibError> {
         let mut fields_itr = fields.iter();
@@ -801,6 +1082,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .current_time(decode_i64(&mut fields_itr)?);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -823,6 +1105,18 @@ where
         delta_neutral_contract.con_id = decode_i32(&mut fields_itr)?;
         delta_neutral_contract.delta = decode_f64(&mut fields_itr)?;
         delta_neutral_contract.price = decode_f64(&mut fields_itr)?;
+        self.record_unparsed(fields, fields_itr.len());
+
+        let pending = self
+            .pending
+            .delta_neutral_validations
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .remove(&req_id);
+        if let Some(sender) = pending {
+            // The caller may have stopped waiting, so a closed channel is not an error
+            let _ = sender.send(delta_neutral_contract.clone());
+        }
 
         self.wrapper
             .lock()
@@ -844,10 +1138,22 @@ where
 
         let groups = decode_string(&mut fields_itr)?;
 
+        let pending = self
+            .pending
+            .display_group_lists
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .remove(&req_id);
+        if let Some(sender) = pending {
+            // The caller may have stopped waiting, so a closed channel is not an error
+            let _ = sender.send(DisplayGroupId::parse_list(&groups));
+        }
+
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .display_group_list(req_id, groups.as_ref());
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -864,10 +1170,29 @@ where
 
         let contract_info = decode_string(&mut fields_itr)?;
 
+        {
+            let mut subscriptions = self
+                .pending
+                .display_group_events
+                .lock()
+                .expect(PENDING_POISONED_MUTEX);
+            if let Some(pending) = subscriptions.get(&req_id) {
+                let event = DisplayGroupEvent {
+                    req_id,
+                    group_id: pending.group_id,
+                    selection: GroupSelection::from_code(&contract_info),
+                };
+                if pending.sender.send(event).is_err() {
+                    subscriptions.remove(&req_id);
+                }
+            }
+        }
+
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .display_group_updated(req_id, contract_info.as_ref());
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
     fn process_error_message(&mut self, fields: &[String]) -> Result<(), IBKRApiLibError> {
@@ -878,11 +1203,111 @@ where
         //throw away version
         fields_itr.next();
 
-        self.wrapper.lock().expect(WRAPPER_POISONED_MUTEX).error(
-            decode_i32(&mut fields_itr)?,
-            decode_i32(&mut fields_itr)?,
-            decode_string(&mut fields_itr)?.as_ref(),
-        );
+        let req_id = decode_i32(&mut fields_itr)?;
+        let code = decode_i32(&mut fields_itr)?;
+        let message = decode_string(&mut fields_itr)?;
+
+        // Listeners whose receiver was dropped are removed
+        self.pending
+            .message_listeners
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .retain(|listener| {
+                listener
+                    .send(TwsMessage::new(req_id, code, message.clone()))
+                    .is_ok()
+            });
+
+        if !is_warning_code(code) {
+            self.pending
+                .historical_bar_kinds
+                .lock()
+                .expect(PENDING_POISONED_MUTEX)
+                .remove(&req_id);
+            let download = self
+                .pending
+                .historical_downloads
+                .lock()
+                .expect(PENDING_POISONED_MUTEX)
+                .remove(&req_id);
+            if let Some(sender) = download {
+                // download_history may have timed out and dropped the receiver
+                let _ = sender.send(Err(TwsMessage::new(req_id, code, message.clone())));
+            }
+            let lookup = self
+                .pending
+                .contract_details
+                .lock()
+                .expect(PENDING_POISONED_MUTEX)
+                .remove(&req_id);
+            if let Some(sender) = lookup {
+                let _ = sender.send((
+                    req_id,
+                    ContractDetailsEvent::Error(TwsMessage::new(req_id, code, message.clone())),
+                ));
+            }
+            let head_timestamp = self
+                .pending
+                .head_timestamps
+                .lock()
+                .expect(PENDING_POISONED_MUTEX)
+                .remove(&req_id);
+            if let Some(sender) = head_timestamp {
+                let _ = sender.send(Err(TwsMessage::new(req_id, code, message.clone())));
+            }
+            // dropping the sender ends the receiver of req_histogram
+            self.pending
+                .histograms
+                .lock()
+                .expect(PENDING_POISONED_MUTEX)
+                .remove(&req_id);
+            // and those of req_soft_dollar_tiers and search_symbols
+            self.pending
+                .soft_dollar_tiers
+                .lock()
+                .expect(PENDING_POISONED_MUTEX)
+                .remove(&req_id);
+            self.pending
+                .symbol_searches
+                .lock()
+                .expect(PENDING_POISONED_MUTEX)
+                .remove(&req_id);
+            // and those of snapshot, what_if, exercise and option_chains, unless the code only
+            // reports e.g. delayed data the request still gets
+            if !IbError::from_code(code).is_informational() {
+                self.pending
+                    .snapshots
+                    .lock()
+                    .expect(PENDING_POISONED_MUTEX)
+                    .remove(&req_id);
+                // TWS still previews an order it only warns about
+                if !is_order_warning(code) {
+                    self.pending
+                        .what_if
+                        .lock()
+                        .expect(PENDING_POISONED_MUTEX)
+                        .remove(&req_id);
+                }
+                self.pending
+                    .exercises
+                    .lock()
+                    .expect(PENDING_POISONED_MUTEX)
+                    .remove(&req_id);
+                self.pending
+                    .option_chains
+                    .lock()
+                    .expect(PENDING_POISONED_MUTEX)
+                    .remove(&req_id);
+            }
+        }
+
+        let mut wrapper = self.wrapper.lock().expect(WRAPPER_POISONED_MUTEX);
+        if is_warning_code(code) {
+            wrapper.warning(req_id, code, message.as_ref());
+        } else {
+            wrapper.error(req_id, code, message.as_ref());
+        }
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -911,15 +1336,15 @@ where
         let mut contract = Contract::default();
         contract.con_id = decode_i32(&mut fields_itr)?; // ver 5 field
         contract.symbol = decode_string(&mut fields_itr)?;
-        contract.sec_type = decode_string(&mut fields_itr)?;
+        contract.sec_type = SecType::from(decode_string(&mut fields_itr)?);
         contract.last_trade_date_or_contract_month = decode_string(&mut fields_itr)?;
         contract.strike = decode_f64(&mut fields_itr)?;
         contract.right = decode_string(&mut fields_itr)?;
         if version >= 9 {
             contract.multiplier = decode_string(&mut fields_itr)?;
         }
-        contract.exchange = decode_string(&mut fields_itr)?;
-        contract.currency = decode_string(&mut fields_itr)?;
+        contract.exchange = Exchange::from(decode_string(&mut fields_itr)?);
+        contract.currency = Currency::from(decode_string(&mut fields_itr)?);
         contract.local_symbol = decode_string(&mut fields_itr)?;
         if version >= 10 {
             contract.trading_class = decode_string(&mut fields_itr)?;
@@ -929,15 +1354,15 @@ where
         let mut execution = Execution::default();
         execution.order_id = order_id;
         execution.exec_id = decode_string(&mut fields_itr)?;
-        execution.time = decode_string(&mut fields_itr)?;
+        execution.time = timestamp_from_wire(&decode_string(&mut fields_itr)?);
         execution.acct_number = decode_string(&mut fields_itr)?;
         execution.exchange = decode_string(&mut fields_itr)?;
         execution.side = decode_string(&mut fields_itr)?;
 
         if self.server_version >= MIN_SERVER_VER_FRACTIONAL_POSITIONS {
-            execution.shares = decode_f64(&mut fields_itr)?;
+            execution.shares = decode_decimal(&mut fields_itr)?;
         } else {
-            execution.shares = decode_i32(&mut fields_itr)? as f64;
+            execution.shares = BigDecimal::from(decode_i32(&mut fields_itr)?);
         }
 
         execution.price = decode_f64(&mut fields_itr)?;
@@ -946,7 +1371,7 @@ where
         execution.liquidation = decode_i32(&mut fields_itr)?; // ver 4 field
 
         if version >= 6 {
-            execution.cum_qty = decode_f64(&mut fields_itr)?;
+            execution.cum_qty = decode_decimal(&mut fields_itr)?;
             execution.avg_price = decode_f64(&mut fields_itr)?;
         }
 
@@ -976,6 +1401,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .exec_details(req_id, contract, execution);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -994,6 +1420,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .exec_details_end(req_id);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1007,16 +1434,27 @@ where
         let family_codes_count = decode_i32(&mut fields_itr)?;
         let mut family_codes: Vec<FamilyCode> = vec![];
         for _ in 0..family_codes_count {
-            let mut fam_code = FamilyCode::default();
-            fam_code.account_id = decode_string(&mut fields_itr)?;
-            fam_code.family_code_str = decode_string(&mut fields_itr)?;
-            family_codes.push(fam_code);
+            family_codes.push(FamilyCode {
+                account_id: decode_string(&mut fields_itr)?,
+                family_code: decode_string(&mut fields_itr)?,
+            });
+        }
+
+        for sender in self
+            .pending
+            .family_codes
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .drain(..)
+        {
+            let _ = sender.send(family_codes.clone());
         }
 
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .family_codes(family_codes);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1035,6 +1473,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .fundamental_data(req_id, data.as_ref());
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1047,10 +1486,30 @@ where
 
         let req_id = decode_i32(&mut fields_itr)?;
         let timestamp = decode_string(&mut fields_itr)?;
+
+        if let Some(sender) = self
+            .pending
+            .head_timestamps
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .remove(&req_id)
+        {
+            match history::parse_head_timestamp(&timestamp) {
+                Some(head_timestamp) => {
+                    let _ = sender.send(Ok(head_timestamp));
+                }
+                None => warn!(
+                    target: DECODER,
+                    "Can't parse the head timestamp {} of request {}", timestamp, req_id
+                ),
+            }
+        }
+
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
-            .fundamental_data(req_id, timestamp.as_ref());
+            .head_timestamp(req_id, timestamp.as_ref());
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1064,18 +1523,44 @@ where
         let req_id = decode_i32(&mut fields_itr)?;
         let num_points = decode_i32(&mut fields_itr)?;
 
-        let mut histogram = vec![];
+        let mut entries = vec![];
         for _ in 0..num_points {
-            let mut data_point = HistogramData::default();
-            data_point.price = decode_f64(&mut fields_itr)?;
-            data_point.count = decode_i32(&mut fields_itr)?;
-            histogram.push(data_point);
+            let price = decode_f64(&mut fields_itr)?;
+            let count = match decode_decimal(&mut fields_itr) {
+                Ok(count) => count,
+                Err(err) => {
+                    // the receiver of req_histogram ends rather than waiting for entries
+                    self.pending
+                        .histograms
+                        .lock()
+                        .expect(PENDING_POISONED_MUTEX)
+                        .remove(&req_id);
+                    return Err(err);
+                }
+            };
+            entries.push(HistogramEntry::new(price, count));
+        }
+        // the wrapper callback takes whole counts
+        let histogram = entries
+            .iter()
+            .map(|entry| HistogramData::new(entry.price, entry.count.to_i32().unwrap_or(0)))
+            .collect();
+
+        if let Some(sender) = self
+            .pending
+            .histograms
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .remove(&req_id)
+        {
+            let _ = sender.send(entries);
         }
 
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .histogram_data(req_id, histogram);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1097,18 +1582,28 @@ where
 
         let bar_count = decode_i32(&mut fields_itr)?;
 
+        let bar_kind = self
+            .pending
+            .historical_bar_kinds
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .remove(&req_id);
+        let download = self
+            .pending
+            .historical_downloads
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .remove(&req_id);
+        let mut downloaded_bars = Vec::new();
+
         for _ in 0..bar_count {
             let mut bar = BarData::default();
-            bar.date = decode_string(&mut fields_itr)?;
+            bar.date = self.decode_bar_date(req_id, &decode_string(&mut fields_itr)?);
             bar.open = decode_f64(&mut fields_itr)?;
             bar.high = decode_f64(&mut fields_itr)?;
             bar.low = decode_f64(&mut fields_itr)?;
             bar.close = decode_f64(&mut fields_itr)?;
-            bar.volume = if self.server_version < MIN_SERVER_VER_SYNT_REALTIME_BARS {
-                decode_i32(&mut fields_itr)? as i64
-            } else {
-                decode_i64(&mut fields_itr)?
-            };
+            bar.volume = decode_decimal(&mut fields_itr)?;
             bar.average = decode_f64(&mut fields_itr)?;
 
             if self.server_version < MIN_SERVER_VER_SYNT_REALTIME_BARS {
@@ -1117,20 +1612,67 @@ where
 
             bar.bar_count = decode_i32(&mut fields_itr)?; // ver 3 field
 
-            self.wrapper
-                .lock()
-                .expect(WRAPPER_POISONED_MUTEX)
-                .historical_data(req_id, bar);
+            if download.is_some() {
+                downloaded_bars.push(bar.clone());
+            }
+
+            let mut wrapper = self.wrapper.lock().expect(WRAPPER_POISONED_MUTEX);
+            match bar_kind {
+                Some(HistoricalBarKind::Yield) => {
+                    wrapper.historical_yield_data(req_id, YieldBar::from(bar))
+                }
+                Some(HistoricalBarKind::FeeRate) => {
+                    wrapper.historical_fee_rate_data(req_id, FeeRateBar::from(bar))
+                }
+                None => wrapper.historical_data(req_id, bar),
+            }
         }
 
+        if let Some(sender) = download {
+            // download_history may have timed out and dropped the receiver
+            let _ = sender.send(Ok(downloaded_bars));
+        }
+
+        // the updates of keep_up_to_date still need the zone until cancel_historical_data
+        let mut zones = self
+            .pending
+            .bar_time_zones
+            .lock()
+            .expect(PENDING_POISONED_MUTEX);
+        if matches!(zones.get(&req_id), Some(pending) if !pending.keep_up_to_date) {
+            zones.remove(&req_id);
+        }
+        drop(zones);
+
         // send end of dataset marker
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .historical_data_end(req_id, start_date.as_ref(), end_date.as_ref());
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// The date of a bar, read in the exchange time zone registered for the request if any
+    fn decode_bar_date(&self, req_id: i32, text: &str) -> Timestamp {
+        let mut zones = self
+            .pending
+            .bar_time_zones
+            .lock()
+            .expect(PENDING_POISONED_MUTEX);
+        match zones.get_mut(&req_id) {
+            Some(pending) => {
+                let date = timestamp_from_wire_in(text, pending.zone, pending.last);
+                if let Some(time) = timestamp_time(&date) {
+                    pending.last = Some(time);
+                }
+                date
+            }
+            None => timestamp_from_wire(text),
+        }
+    }
+
     //----------------------------------------------------------------------------------------------
     fn process_historical_data_update(&mut self, fields: &[String]) -> Result<(), IBKRApiLibError> {
         let mut fields_itr = fields.iter();
@@ -1142,17 +1684,35 @@ where
 
         let mut bar = BarData::default();
         bar.bar_count = decode_i32(&mut fields_itr)?;
-        bar.date = decode_string(&mut fields_itr)?;
+        bar.date = self.decode_bar_date(req_id, &decode_string(&mut fields_itr)?);
         bar.open = decode_f64(&mut fields_itr)?;
         bar.close = decode_f64(&mut fields_itr)?;
         bar.high = decode_f64(&mut fields_itr)?;
         bar.low = decode_f64(&mut fields_itr)?;
         bar.average = decode_f64(&mut fields_itr)?;
-        bar.volume = decode_i64(&mut fields_itr)?;
+        bar.volume = decode_decimal(&mut fields_itr)?;
+        {
+            let mut bar_updates = self
+                .pending
+                .bar_updates
+                .lock()
+                .expect(PENDING_POISONED_MUTEX);
+            if let Some(pending) = bar_updates.get_mut(&req_id) {
+                let sent = pending
+                    .tracker
+                    .on_update(bar.clone())
+                    .into_iter()
+                    .all(|update| pending.sender.send(update).is_ok());
+                if !sent {
+                    bar_updates.remove(&req_id);
+                }
+            }
+        }
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .historical_data_update(req_id, bar);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1164,7 +1724,7 @@ where
         fields_itr.next();
 
         let req_id = decode_i32(&mut fields_itr)?;
-        let time = decode_string(&mut fields_itr)?;
+        let time = timestamp_from_wire(&decode_string(&mut fields_itr)?);
         let provider_code = decode_string(&mut fields_itr)?;
         let article_id = decode_string(&mut fields_itr)?;
         let headline = decode_string(&mut fields_itr)?;
@@ -1173,11 +1733,12 @@ where
             .expect(WRAPPER_POISONED_MUTEX)
             .historical_news(
                 req_id,
-                time.as_ref(),
+                &time,
                 provider_code.as_ref(),
                 article_id.as_ref(),
                 headline.as_ref(),
             );
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1195,6 +1756,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .historical_news_end(req_id, has_more);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1212,10 +1774,10 @@ where
 
         for _ in 0..tick_count {
             let mut historical_tick = HistoricalTick::default();
-            historical_tick.time = decode_i32(&mut fields_itr)?;
+            historical_tick.time = timestamp_from_wire(&decode_string(&mut fields_itr)?);
             fields_itr.next(); // for consistency
             historical_tick.price = decode_f64(&mut fields_itr)?;
-            historical_tick.size = decode_i32(&mut fields_itr)?;
+            historical_tick.size = decode_decimal(&mut fields_itr)?;
             ticks.push(historical_tick);
         }
 
@@ -1225,6 +1787,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .historical_ticks(req_id, ticks, done);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1245,7 +1808,7 @@ where
 
         for _ in 0..tick_count {
             let mut historical_tick_bid_ask = HistoricalTickBidAsk::default();
-            historical_tick_bid_ask.time = decode_i32(&mut fields_itr)?;
+            historical_tick_bid_ask.time = timestamp_from_wire(&decode_string(&mut fields_itr)?);
             let mask = decode_i32(&mut fields_itr)?;
             let mut tick_attrib_bid_ask = TickAttribBidAsk::default();
             tick_attrib_bid_ask.ask_past_high = mask & 1 != 0;
@@ -1253,8 +1816,8 @@ where
             historical_tick_bid_ask.tick_attrib_bid_ask = tick_attrib_bid_ask;
             historical_tick_bid_ask.price_bid = decode_f64(&mut fields_itr)?;
             historical_tick_bid_ask.price_ask = decode_f64(&mut fields_itr)?;
-            historical_tick_bid_ask.size_bid = decode_i32(&mut fields_itr)?;
-            historical_tick_bid_ask.size_ask = decode_i32(&mut fields_itr)?;
+            historical_tick_bid_ask.size_bid = decode_decimal(&mut fields_itr)?;
+            historical_tick_bid_ask.size_ask = decode_decimal(&mut fields_itr)?;
             ticks.push(historical_tick_bid_ask);
         }
 
@@ -1281,14 +1844,14 @@ where
 
         for _ in 0..tick_count {
             let mut historical_tick_last = HistoricalTickLast::default();
-            historical_tick_last.time = decode_i32(&mut fields_itr)?;
+            historical_tick_last.time = timestamp_from_wire(&decode_string(&mut fields_itr)?);
             let mask = decode_i32(&mut fields_itr)?;
             let mut tick_attrib_last = TickAttribLast::default();
             tick_attrib_last.past_limit = mask & 1 != 0;
             tick_attrib_last.unreported = mask & 2 != 0;
             historical_tick_last.tick_attrib_last = tick_attrib_last;
             historical_tick_last.price = decode_f64(&mut fields_itr)?;
-            historical_tick_last.size = decode_i32(&mut fields_itr)?;
+            historical_tick_last.size = decode_decimal(&mut fields_itr)?;
             historical_tick_last.exchange = decode_string(&mut fields_itr)?;
             historical_tick_last.special_conditions = decode_string(&mut fields_itr)?;
             ticks.push(historical_tick_last);
@@ -1300,6 +1863,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .historical_ticks_last(req_id, ticks, done);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1313,12 +1877,19 @@ where
         fields_itr.next();
 
         let accounts_list = decode_string(&mut fields_itr)?;
-        info!("calling managed_accounts");
+        {
+            let mut accounts = self.pending.accounts.lock().expect(PENDING_POISONED_MUTEX);
+            accounts.set_managed_accounts(&accounts_list);
+            info!(target: DECODER, "Managed {}", accounts);
+        }
+        self.pending.notify_session_changed();
+        info!(target: DECODER, "calling managed_accounts");
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .managed_accounts(accounts_list.as_ref());
-        info!("finished calling managed_accounts");
+        info!(target: DECODER, "finished calling managed_accounts");
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1335,6 +1906,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .market_data_type(req_id, market_data_type);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1352,12 +1924,13 @@ where
         let operation = decode_i32(&mut fields_itr)?;
         let side = decode_i32(&mut fields_itr)?;
         let price = decode_f64(&mut fields_itr)?;
-        let size = decode_i32(&mut fields_itr)?;
+        let size = decode_decimal(&mut fields_itr)?;
 
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .update_mkt_depth(req_id, position, operation, side, price, size);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1377,7 +1950,7 @@ where
         let operation = decode_i32(&mut fields_itr)?;
         let side = decode_i32(&mut fields_itr)?;
         let price = decode_f64(&mut fields_itr)?;
-        let size = decode_i32(&mut fields_itr)?;
+        let size = decode_decimal(&mut fields_itr)?;
         let mut is_smart_depth = false;
 
         if self.server_version >= MIN_SERVER_VER_SMART_DEPTH {
@@ -1397,6 +1970,7 @@ where
                 size,
                 is_smart_depth,
             );
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1413,16 +1987,22 @@ where
         let mut price_increments = vec![];
 
         for _ in 0..price_increments_count {
-            let mut prc_inc = PriceIncrement::default();
-            prc_inc.low_edge = decode_f64(&mut fields_itr)?;
-            prc_inc.increment = decode_f64(&mut fields_itr)?;
-            price_increments.push(prc_inc);
+            price_increments.push(PriceIncrement {
+                low_edge: decode_f64(&mut fields_itr)?,
+                increment: decode_f64(&mut fields_itr)?,
+            });
         }
 
+        self.pending
+            .market_rules
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .insert(market_rule_id, price_increments.clone());
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .market_rule(market_rule_id, price_increments);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1438,7 +2018,7 @@ where
 
         for _ in 0..depth_mkt_data_descriptions_count {
             let mut desc = DepthMktDataDescription::default();
-            desc.exchange = decode_string(&mut fields_itr)?;
+            desc.exchange = Exchange::from(decode_string(&mut fields_itr)?);
             desc.sec_type = decode_string(&mut fields_itr)?;
             if self.server_version >= MIN_SERVER_VER_SERVICE_DATA_TYPE {
                 desc.listing_exch = decode_string(&mut fields_itr)?;
@@ -1454,6 +2034,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .mkt_depth_exchanges(depth_mkt_data_descriptions);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1471,6 +2052,18 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .news_article(req_id, article_type, article_text.as_ref());
+        match NewsArticleContent::from_article(article_type, &article_text) {
+            Ok(content) => self
+                .wrapper
+                .lock()
+                .expect(WRAPPER_POISONED_MUTEX)
+                .news_article_content(req_id, &content),
+            Err(err) => error!(
+                target: DECODER,
+                "Undecodable news article -- req_id: {}, error: {:?}", req_id, err
+            ),
+        }
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1487,16 +2080,25 @@ where
         let news_msg_type = decode_i32(&mut fields_itr)?;
         let news_message = decode_string(&mut fields_itr)?;
         let originating_exch = decode_string(&mut fields_itr)?;
+        let bulletin = Bulletin::new(
+            news_msg_id,
+            BulletinType::from_code(news_msg_type),
+            &news_message,
+            &originating_exch,
+        );
+
+        // Subscribers whose handle was dropped are removed
+        self.pending
+            .news_bulletins
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .retain(|subscriber| subscriber.send(bulletin.clone()).is_ok());
 
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
-            .update_news_bulletin(
-                news_msg_id,
-                news_msg_type,
-                news_message.as_ref(),
-                originating_exch.as_ref(),
-            );
+            .update_news_bulletin(&bulletin);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1520,6 +2122,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .news_providers(news_providers);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1533,10 +2136,17 @@ where
         fields_itr.next();
 
         let order_id = decode_i32(&mut fields_itr)?;
+        *self
+            .pending
+            .next_valid_id
+            .lock()
+            .expect(PENDING_POISONED_MUTEX) = Some(order_id);
+        self.pending.notify_session_changed();
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .next_valid_id(order_id);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1566,10 +2176,27 @@ where
 
         order_decoder.decode_open(&mut fields_itr)?;
 
+        if order.what_if {
+            let pending = self
+                .pending
+                .what_if
+                .lock()
+                .expect(PENDING_POISONED_MUTEX)
+                .remove(&order.order_id);
+            if let Some(sender) = pending {
+                // The caller may have stopped waiting, so a closed channel is not an error
+                let _ = sender.send(MarginPreview::from_order_state(
+                    order.order_id,
+                    &order_state,
+                ));
+            }
+        }
+
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .open_order(order.order_id, contract, order, order_state);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1597,6 +2224,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .order_bound(req_id, api_client_id, api_order_id);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1643,6 +2271,29 @@ where
             mkt_cap_price = decode_f64(&mut fields_itr)?;
         }
 
+        self.pending
+            .order_latency
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .record_status(order_id, Instant::now());
+
+        {
+            let mut exercises = self.pending.exercises.lock().expect(PENDING_POISONED_MUTEX);
+            if let Some(sender) = exercises.get(&order_id) {
+                let exercise_status = ExerciseStatus {
+                    req_id: order_id,
+                    status: status.clone(),
+                    phase: OrderPhase::from_status(&status, filled),
+                    filled,
+                    remaining,
+                };
+                let terminal = exercise_status.is_terminal();
+                if sender.send(exercise_status).is_err() || terminal {
+                    exercises.remove(&order_id);
+                }
+            }
+        }
+
         self.wrapper
             .try_lock()
             .expect(WRAPPER_POISONED_MUTEX)
@@ -1659,6 +2310,7 @@ where
                 why_held.as_ref(),
                 mkt_cap_price,
             );
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1688,6 +2340,7 @@ where
             unrealized_pnl,
             realized_pnl,
         );
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1718,6 +2371,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .pnl_single(req_id, pos, daily_pnl, unrealized_pnl, realized_pnl, value);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1734,17 +2388,17 @@ where
         let mut contract = Contract::default();
         contract.con_id = decode_i32(&mut fields_itr)?; // ver 6 field
         contract.symbol = decode_string(&mut fields_itr)?;
-        contract.sec_type = decode_string(&mut fields_itr)?;
+        contract.sec_type = SecType::from(decode_string(&mut fields_itr)?);
         contract.last_trade_date_or_contract_month = decode_string(&mut fields_itr)?;
         contract.strike = decode_f64(&mut fields_itr)?;
         contract.right = decode_string(&mut fields_itr)?;
 
         if version >= 7 {
             contract.multiplier = decode_string(&mut fields_itr)?;
-            contract.primary_exchange = decode_string(&mut fields_itr)?;
+            contract.primary_exchange = Exchange::from(decode_string(&mut fields_itr)?);
         }
 
-        contract.currency = decode_string(&mut fields_itr)?;
+        contract.currency = Currency::from(decode_string(&mut fields_itr)?);
         contract.local_symbol = decode_string(&mut fields_itr)?; // ver 2 field
         if version >= 8 {
             contract.trading_class = decode_string(&mut fields_itr)?;
@@ -1766,7 +2420,7 @@ where
         let account_name = decode_string(&mut fields_itr)?; // ver 4 field
 
         if version == 6 && self.server_version == 39 {
-            contract.primary_exchange = decode_string(&mut fields_itr)?;
+            contract.primary_exchange = Exchange::from(decode_string(&mut fields_itr)?);
         }
 
         self.wrapper
@@ -1782,6 +2436,7 @@ where
                 realized_pnl,
                 account_name.as_ref(),
             );
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1800,13 +2455,13 @@ where
         let mut contract = Contract::default();
         contract.con_id = decode_i32(&mut fields_itr)?;
         contract.symbol = decode_string(&mut fields_itr)?;
-        contract.sec_type = decode_string(&mut fields_itr)?;
+        contract.sec_type = SecType::from(decode_string(&mut fields_itr)?);
         contract.last_trade_date_or_contract_month = decode_string(&mut fields_itr)?;
         contract.strike = decode_f64(&mut fields_itr)?;
         contract.right = decode_string(&mut fields_itr)?;
         contract.multiplier = decode_string(&mut fields_itr)?;
-        contract.exchange = decode_string(&mut fields_itr)?;
-        contract.currency = decode_string(&mut fields_itr)?;
+        contract.exchange = Exchange::from(decode_string(&mut fields_itr)?);
+        contract.currency = Currency::from(decode_string(&mut fields_itr)?);
         contract.local_symbol = decode_string(&mut fields_itr)?;
         if version >= 2 {
             contract.trading_class = decode_string(&mut fields_itr)?;
@@ -1830,6 +2485,7 @@ where
             position,
             avg_cost,
         );
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1859,13 +2515,13 @@ where
         let mut contract = Contract::default();
         contract.con_id = decode_i32(&mut fields_itr)?;
         contract.symbol = decode_string(&mut fields_itr)?;
-        contract.sec_type = decode_string(&mut fields_itr)?;
+        contract.sec_type = SecType::from(decode_string(&mut fields_itr)?);
         contract.last_trade_date_or_contract_month = decode_string(&mut fields_itr)?;
         contract.strike = decode_f64(&mut fields_itr)?;
         contract.right = decode_string(&mut fields_itr)?;
         contract.multiplier = decode_string(&mut fields_itr)?;
-        contract.exchange = decode_string(&mut fields_itr)?;
-        contract.currency = decode_string(&mut fields_itr)?;
+        contract.exchange = Exchange::from(decode_string(&mut fields_itr)?);
+        contract.currency = Currency::from(decode_string(&mut fields_itr)?);
         contract.local_symbol = decode_string(&mut fields_itr)?;
         contract.trading_class = decode_string(&mut fields_itr)?;
 
@@ -1885,6 +2541,7 @@ where
                 avg_cost,
             );
 
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1902,6 +2559,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .position_multi_end(req_id);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1922,7 +2580,7 @@ where
         bar.high = decode_f64(&mut fields_itr)?;
         bar.low = decode_f64(&mut fields_itr)?;
         bar.close = decode_f64(&mut fields_itr)?;
-        bar.volume = decode_i64(&mut fields_itr)?;
+        bar.volume = decode_decimal(&mut fields_itr)?;
         bar.wap = decode_f64(&mut fields_itr)?;
         bar.count = decode_i32(&mut fields_itr)?;
 
@@ -1930,6 +2588,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .realtime_bar(req_id, bar);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1944,11 +2603,22 @@ where
 
         let fa_data_type = decode_i32(&mut fields_itr)?;
         let xml = decode_string(&mut fields_itr)?;
+        if fa_data_type == FaDataType::ALIASES as i32 {
+            match FaAliases::from_xml(&xml) {
+                Ok(aliases) => {
+                    let mut accounts = self.pending.accounts.lock().expect(PENDING_POISONED_MUTEX);
+                    accounts.set_aliases(&aliases);
+                    info!(target: DECODER, "Account aliases received -- {}", accounts);
+                }
+                Err(err) => warn!(target: DECODER, "Could not read the account aliases: {}", err),
+            }
+        }
 
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .receive_fa(FromPrimitive::from_i32(fa_data_type).unwrap(), xml.as_ref());
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1967,6 +2637,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .reroute_mkt_data_req(req_id, con_id, exchange.as_ref());
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -1985,6 +2656,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .reroute_mkt_depth_req(req_id, con_id, exchange.as_ref());
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -2008,13 +2680,13 @@ where
             data.rank = decode_i32(&mut fields_itr)?;
             data.contract.contract.con_id = decode_i32(&mut fields_itr)?; // ver 3 field
             data.contract.contract.symbol = decode_string(&mut fields_itr)?;
-            data.contract.contract.sec_type = decode_string(&mut fields_itr)?;
+            data.contract.contract.sec_type = SecType::from(decode_string(&mut fields_itr)?);
             data.contract.contract.last_trade_date_or_contract_month =
                 decode_string(&mut fields_itr)?;
             data.contract.contract.strike = decode_f64(&mut fields_itr)?;
             data.contract.contract.right = decode_string(&mut fields_itr)?;
-            data.contract.contract.exchange = decode_string(&mut fields_itr)?;
-            data.contract.contract.currency = decode_string(&mut fields_itr)?;
+            data.contract.contract.exchange = Exchange::from(decode_string(&mut fields_itr)?);
+            data.contract.contract.currency = Currency::from(decode_string(&mut fields_itr)?);
             data.contract.contract.local_symbol = decode_string(&mut fields_itr)?;
             data.contract.market_name = decode_string(&mut fields_itr)?;
             data.contract.contract.trading_class = decode_string(&mut fields_itr)?;
@@ -2040,6 +2712,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .scanner_data_end(req_id);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -2057,6 +2730,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .scanner_parameters(xml.as_ref());
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -2086,10 +2760,34 @@ where
 
         let strike_count = decode_i32(&mut fields_itr)?;
         let mut strikes = HashSet::new();
+        let mut strike_values = Vec::new();
         for _ in 0..strike_count {
             let strike = decode_f64(&mut fields_itr)?;
             let big_strike = BigDecimal::from_f64(strike).unwrap();
             strikes.insert(big_strike);
+            strike_values.push(strike);
+        }
+        self.record_unparsed(fields, fields_itr.len());
+
+        if let Some(pending) = self
+            .pending
+            .option_chains
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .get_mut(&req_id)
+        {
+            let mut chain = OptionChain {
+                exchange: exchange.clone(),
+                underlying_con_id,
+                trading_class: trading_class.clone(),
+                multiplier: multiplier.clone(),
+                expirations: expirations.iter().cloned().collect(),
+                strikes: strike_values,
+            };
+            chain.expirations.sort();
+            chain.strikes.sort_by(|a, b| a.total_cmp(b));
+            chain.strikes.dedup();
+            pending.chains.push(chain);
         }
 
         self.wrapper
@@ -2118,6 +2816,18 @@ where
         fields_itr.next();
 
         let req_id = decode_i32(&mut fields_itr)?;
+
+        let pending = self
+            .pending
+            .option_chains
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .remove(&req_id);
+        if let Some(pending) = pending {
+            // The caller may have stopped waiting, so a closed channel is not an error
+            let _ = pending.sender.send(pending.chains);
+        }
+
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
@@ -2138,17 +2848,31 @@ where
 
         let mut smart_components = vec![];
         for _ in 0..count {
-            let mut smart_component = SmartComponent::default();
-            smart_component.bit_number = decode_i32(&mut fields_itr)?;
-            smart_component.exchange = decode_string(&mut fields_itr)?;
-            smart_component.exchange_letter = decode_string(&mut fields_itr)?;
-            smart_components.push(smart_component)
+            smart_components.push(SmartComponent {
+                bit_number: decode_i32(&mut fields_itr)?,
+                exchange: decode_string(&mut fields_itr)?,
+                exchange_letter: decode_string(&mut fields_itr)?,
+            })
+        }
+
+        let bbo_exchange = self
+            .pending
+            .smart_component_requests
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .remove(&req_id);
+        if let Some(bbo_exchange) = bbo_exchange {
+            self.pending
+                .smart_components
+                .lock()
+                .expect(PENDING_POISONED_MUTEX)
+                .insert(&bbo_exchange, smart_component_map(&smart_components));
         }
-
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .smart_components(req_id, smart_components);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -2165,17 +2889,28 @@ where
 
         let mut tiers = vec![];
         for _ in 0..count {
-            let mut tier = SoftDollarTier::default();
-            tier.name = decode_string(&mut fields_itr)?;
-            tier.val = decode_string(&mut fields_itr)?;
-            tier.display_name = decode_string(&mut fields_itr)?;
-            tiers.push(tier);
+            tiers.push(SoftDollarTier {
+                name: decode_string(&mut fields_itr)?,
+                value: decode_string(&mut fields_itr)?,
+                display_name: decode_string(&mut fields_itr)?,
+            });
+        }
+
+        if let Some(sender) = self
+            .pending
+            .soft_dollar_tiers
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .remove(&req_id)
+        {
+            let _ = sender.send(tiers.clone());
         }
 
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .soft_dollar_tiers(req_id, tiers);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -2194,9 +2929,9 @@ where
             let mut con_desc = ContractDescription::default();
             con_desc.contract.con_id = decode_i32(&mut fields_itr)?;
             con_desc.contract.symbol = decode_string(&mut fields_itr)?;
-            con_desc.contract.sec_type = decode_string(&mut fields_itr)?;
-            con_desc.contract.primary_exchange = decode_string(&mut fields_itr)?;
-            con_desc.contract.currency = decode_string(&mut fields_itr)?;
+            con_desc.contract.sec_type = SecType::from(decode_string(&mut fields_itr)?);
+            con_desc.contract.primary_exchange = Exchange::from(decode_string(&mut fields_itr)?);
+            con_desc.contract.currency = Currency::from(decode_string(&mut fields_itr)?);
 
             let derivative_sec_types_cnt = decode_i32(&mut fields_itr)?;
             con_desc.derivative_sec_types = vec![];
@@ -2206,10 +2941,21 @@ where
             }
             contract_descriptions.push(con_desc)
         }
+
+        if let Some(sender) = self
+            .pending
+            .symbol_searches
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .remove(&req_id)
+        {
+            let _ = sender.send(contract_descriptions.clone());
+        }
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .symbol_samples(req_id, contract_descriptions);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -2225,68 +2971,94 @@ where
         let tick_type = decode_i32(&mut fields_itr)?;
         let time = decode_i64(&mut fields_itr)?;
 
-        match tick_type {
-            0 => return Ok(()), // None
-            1..=2 =>
-            // Last (1) or AllLast (2)
-            {
+        let event = match tick_type {
+            // Last or AllLast
+            1..=2 => {
                 let price = decode_f64(&mut fields_itr)?;
-                let size = decode_i32(&mut fields_itr)?;
+                let size = decode_decimal(&mut fields_itr)?;
                 let mask = decode_i32(&mut fields_itr)?;
-                let mut tick_attrib_last = TickAttribLast::default();
-                tick_attrib_last.past_limit = mask & 1 != 0;
-                tick_attrib_last.unreported = mask & 2 != 0;
-                let exchange = decode_string(&mut fields_itr)?;
-                let special_conditions = decode_string(&mut fields_itr)?;
-                self.wrapper
-                    .lock()
-                    .expect(WRAPPER_POISONED_MUTEX)
-                    .tick_by_tick_all_last(
-                        req_id,
-                        FromPrimitive::from_i32(tick_type).unwrap(),
-                        time,
-                        price,
-                        size,
-                        tick_attrib_last,
-                        exchange.as_ref(),
-                        special_conditions.as_ref(),
-                    );
+                let tick = LastTick {
+                    time,
+                    price,
+                    size,
+                    past_limit: mask & 1 != 0,
+                    unreported: mask & 2 != 0,
+                    exchange: decode_string(&mut fields_itr)?,
+                    conditions: decode_string(&mut fields_itr)?,
+                };
+                if tick_type == 1 {
+                    TickByTickEvent::Last(tick)
+                } else {
+                    TickByTickEvent::AllLast(tick)
+                }
             }
-            3 =>
-            // BidAsk
-            {
+            3 => {
                 let bid_price = decode_f64(&mut fields_itr)?;
                 let ask_price = decode_f64(&mut fields_itr)?;
-                let bid_size = decode_i32(&mut fields_itr)?;
-                let ask_size = decode_i32(&mut fields_itr)?;
+                let bid_size = decode_decimal(&mut fields_itr)?;
+                let ask_size = decode_decimal(&mut fields_itr)?;
                 let mask = decode_i32(&mut fields_itr)?;
-                let mut tick_attrib_bid_ask = TickAttribBidAsk::default();
-                tick_attrib_bid_ask.bid_past_low = mask & 1 != 0;
-                tick_attrib_bid_ask.ask_past_high = mask & 2 != 0;
-                self.wrapper
-                    .lock()
-                    .expect(WRAPPER_POISONED_MUTEX)
-                    .tick_by_tick_bid_ask(
-                        req_id,
-                        time,
-                        bid_price,
-                        ask_price,
-                        bid_size,
-                        ask_size,
-                        tick_attrib_bid_ask,
-                    );
-            }
-            4 =>
-            // MidPoint
-            {
-                let mid_point = decode_f64(&mut fields_itr)?;
-                self.wrapper
-                    .lock()
-                    .expect(WRAPPER_POISONED_MUTEX)
-                    .tick_by_tick_mid_point(req_id, time, mid_point);
+                TickByTickEvent::BidAsk(BidAskTick {
+                    time,
+                    bid_price,
+                    ask_price,
+                    bid_size,
+                    ask_size,
+                    bid_past_low: mask & 1 != 0,
+                    ask_past_high: mask & 2 != 0,
+                })
             }
+            4 => TickByTickEvent::MidPoint(MidPointTick {
+                time,
+                mid_point: decode_f64(&mut fields_itr)?,
+            }),
+            // None
             _ => return Ok(()),
+        };
+
+        {
+            let mut listeners = self
+                .pending
+                .tick_by_tick
+                .lock()
+                .expect(PENDING_POISONED_MUTEX);
+            let sent = listeners
+                .get(&req_id)
+                .map(|listener| listener.send(event.clone()).is_ok());
+            if sent == Some(false) {
+                listeners.remove(&req_id);
+            }
         }
+
+        let by_tick_type = event.tick_type();
+        let mut wrapper = self.wrapper.lock().expect(WRAPPER_POISONED_MUTEX);
+        match event {
+            TickByTickEvent::Last(tick) | TickByTickEvent::AllLast(tick) => wrapper
+                .tick_by_tick_all_last(
+                    req_id,
+                    by_tick_type,
+                    tick.time,
+                    tick.price,
+                    tick.size.clone(),
+                    tick.attrib(),
+                    tick.exchange.as_ref(),
+                    tick.conditions.as_ref(),
+                ),
+            TickByTickEvent::BidAsk(tick) => wrapper.tick_by_tick_bid_ask(
+                req_id,
+                tick.time,
+                tick.bid_price,
+                tick.ask_price,
+                tick.bid_size.clone(),
+                tick.ask_size.clone(),
+                tick.attrib(),
+            ),
+            TickByTickEvent::MidPoint(tick) => {
+                wrapper.tick_by_tick_mid_point(req_id, tick.time, tick.mid_point)
+            }
+        }
+        drop(wrapper);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -2311,7 +3083,7 @@ where
         let dividends_to_last_trade_date = decode_f64(&mut fields_itr)?;
         self.wrapper.lock().expect(WRAPPER_POISONED_MUTEX).tick_efp(
             ticker_id,
-            FromPrimitive::from_i32(tick_type).unwrap(),
+            TickType::from_code(tick_type),
             basis_points,
             formatted_basis_points.as_ref(),
             implied_futures_price,
@@ -2320,6 +3092,7 @@ where
             dividend_impact,
             dividends_to_last_trade_date,
         );
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -2339,11 +3112,8 @@ where
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
-            .tick_generic(
-                ticker_id,
-                FromPrimitive::from_i32(tick_type).unwrap(),
-                value,
-            );
+            .tick_generic(ticker_id, TickType::from_code(tick_type), value);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -2354,7 +3124,7 @@ where
         //throw away message_id
         fields_itr.next();
         let ticker_id = decode_i32(&mut fields_itr)?;
-        let time_stamp = decode_i32(&mut fields_itr)?;
+        let time_stamp = timestamp_from_wire(&decode_string(&mut fields_itr)?);
         let provider_code = decode_string(&mut fields_itr)?;
         let article_id = decode_string(&mut fields_itr)?;
         let headline = decode_string(&mut fields_itr)?;
@@ -2364,12 +3134,13 @@ where
             .expect(WRAPPER_POISONED_MUTEX)
             .tick_news(
                 ticker_id,
-                time_stamp,
+                &time_stamp,
                 provider_code.as_ref(),
                 article_id.as_ref(),
                 headline.as_ref(),
                 extra_data.as_ref(),
             );
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -2459,7 +3230,7 @@ where
             .expect(WRAPPER_POISONED_MUTEX)
             .tick_option_computation(
                 ticker_id,
-                FromPrimitive::from_i32(tick_type).unwrap(),
+                TickType::from_code(tick_type),
                 tick_attribute,
                 implied_vol,
                 delta,
@@ -2484,6 +3255,11 @@ where
         let min_tick = decode_f64(&mut fields_itr)?;
         let bbo_exchange = decode_string(&mut fields_itr)?;
         let snapshot_permissions = decode_i32(&mut fields_itr)?;
+        self.pending
+            .smart_components
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .set_bbo_exchange(ticker_id, &bbo_exchange);
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
@@ -2493,6 +3269,7 @@ where
                 bbo_exchange.as_ref(),
                 snapshot_permissions,
             );
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -2507,12 +3284,25 @@ where
 
         let ticker_id = decode_i32(&mut fields_itr)?;
         let tick_type = decode_i32(&mut fields_itr)?;
-        let size = decode_i32(&mut fields_itr)?;
+        let size = decode_decimal(&mut fields_itr)?;
+
+        if let Some(pending) = self
+            .pending
+            .snapshots
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .get_mut(&ticker_id)
+        {
+            pending
+                .quote
+                .apply_size(TickType::from_code(tick_type), size.clone());
+        }
 
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
-            .tick_size(ticker_id, FromPrimitive::from_i32(tick_type).unwrap(), size);
+            .tick_size(ticker_id, TickType::from_code(tick_type), size);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -2527,10 +3317,22 @@ where
 
         let req_id = decode_i32(&mut fields_itr)?;
 
+        let pending = self
+            .pending
+            .snapshots
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .remove(&req_id);
+        if let Some(pending) = pending {
+            // The caller may have stopped waiting, so a closed channel is not an error
+            let _ = pending.sender.send(pending.quote);
+        }
+
         self.wrapper
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .tick_snapshot_end(req_id);
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -2594,6 +3396,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .verify_completed(is_successful, error_text.as_ref());
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -2611,6 +3414,7 @@ where
             .expect(WRAPPER_POISONED_MUTEX)
             .replace_fa_end(req_id, text.as_str());
 
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -2628,6 +3432,7 @@ where
             .expect(WRAPPER_POISONED_MUTEX)
             .wsh_metadata(req_id, data_json.as_str());
 
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -2645,6 +3450,7 @@ where
             .expect(WRAPPER_POISONED_MUTEX)
             .wsh_event_data(req_id, data_json.as_str());
 
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -2663,6 +3469,7 @@ where
             .lock()
             .expect(WRAPPER_POISONED_MUTEX)
             .verify_message_api(api_data.as_ref());
+        self.record_unparsed(fields, fields_itr.len());
         Ok(())
     }
 
@@ -2693,55 +3500,97 @@ where
         Ok(())
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Records the fields left over after decoding a message, in lenient mode
+    fn record_unparsed(&self, fields: &[String], unparsed: usize) {
+        if self.pending.lenient_decoding.load(Ordering::Relaxed) {
+            self.pending
+                .field_coverage
+                .lock()
+                .expect(PENDING_POISONED_MUTEX)
+                .record(fields, unparsed);
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Passes the error of a frame the reader couldn't read to the wrapper and the message
+    /// listeners
+    fn report_message_error(&mut self, err: &MessageError) {
+        let message = err.to_string();
+        self.pending
+            .message_listeners
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .retain(|listener| {
+                listener
+                    .send(TwsMessage::new(NO_VALID_ID, err.code(), message.clone()))
+                    .is_ok()
+            });
+        self.wrapper.lock().expect(WRAPPER_POISONED_MUTEX).error(
+            NO_VALID_ID,
+            err.code(),
+            message.as_str(),
+        );
+    }
+
     //----------------------------------------------------------------------------------------------
     pub fn run(&mut self) -> Result<(), IBKRApiLibError> {
         //This is the function that has the message loop.
         const CONN_STATE_POISONED: &str = "Connection state mutex was poisoned";
-        info!("Starting run...");
+        info!(target: DECODER, "Starting run...");
         // !self.done &&
         loop {
             // debug!("Client waiting for message...");
 
             let text = self.msg_queue.recv();
             match text {
-                Result::Ok(val) => {
-                    if val.len() > MAX_MSG_LEN as usize {
-                        self.wrapper.lock().expect(WRAPPER_POISONED_MUTEX).error(
-                            NO_VALID_ID,
-                            TwsError::NotConnected.code(),
-                            format!("{}:{}:{}", TwsError::NotConnected.message(), val.len(), val)
-                                .as_str(),
-                        );
-                        error!("Error receiving message.  Disconnected: Message too big");
-                        self.wrapper
-                            .lock()
-                            .expect(WRAPPER_POISONED_MUTEX)
-                            .connection_closed();
+                Result::Ok(Err(err)) => {
+                    self.report_message_error(&err);
+                    if err.is_fatal() {
+                        error!(target: DECODER, "Error receiving message.  Disconnected: {}", err);
+                        if self.reports_connection_closed {
+                            self.wrapper
+                                .lock()
+                                .expect(WRAPPER_POISONED_MUTEX)
+                                .connection_closed();
+                        }
                         *self.conn_state.lock().expect(CONN_STATE_POISONED) =
                             ConnStatus::DISCONNECTED;
-                        error!("Error receiving message.  Invalid size.  Disconnected.");
                         return Ok(());
-                    } else {
-                        let fields = read_fields((&val).as_ref());
-
-                        self.interpret(fields.as_slice())?;
+                    }
+                }
+                Result::Ok(Ok(val)) => {
+                    let fields = read_fields((&val).as_ref());
+
+                    if let Err(err) = self.interpret(fields.as_slice()) {
+                        if !self.pending.lenient_decoding.load(Ordering::Relaxed) {
+                            return Err(err);
+                        }
+                        warn!(target: DECODER, "Skipping undecodable message {:?}: {}", fields, err);
+                        self.wrapper.lock().expect(WRAPPER_POISONED_MUTEX).error(
+                            NO_VALID_ID,
+                            TwsError::BadMessage.code(),
+                            format!("{} {}", TwsError::BadMessage.message(), err).as_str(),
+                        );
                     }
                 }
                 Result::Err(err) => {
                     if *self.conn_state.lock().expect(CONN_STATE_POISONED).deref() as i32
                         != ConnStatus::DISCONNECTED as i32
                     {
-                        info!("Error receiving message.  Disconnected: {:?}", err);
-                        self.wrapper
-                            .lock()
-                            .expect(WRAPPER_POISONED_MUTEX)
-                            .connection_closed();
+                        info!(target: DECODER, "Error receiving message.  Disconnected: {:?}", err);
+                        if self.reports_connection_closed {
+                            self.wrapper
+                                .lock()
+                                .expect(WRAPPER_POISONED_MUTEX)
+                                .connection_closed();
+                        }
                         *self.conn_state.lock().expect(CONN_STATE_POISONED) =
                             ConnStatus::DISCONNECTED;
 
                         return Ok(());
                     } else {
-                        error!("Disconnected...");
+                        error!(target: DECODER, "Disconnected...");
                         return Ok(());
                     }
                 }
@@ -0,0 +1,245 @@
+//! Typed integration with the window groups of TWS
+//!
+//! TWS links its windows in display groups: selecting a contract in one window of a group shows
+//! it in the others.  The display group requests of EClient pass group ids as bare integers and
+//! selections as encoded strings like "8314@SMART".  DisplayGroups names the groups with
+//! DisplayGroupId and the selections with GroupSelection, and yields a DisplayGroupEvent every
+//! time the selection of a subscribed group changes, so tools can follow and drive the
+//! instrument shown in a TWS window.
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::client::EClient;
+use crate::core::common::NO_VALID_ID;
+use crate::core::contract::Contract;
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+use crate::core::wrapper::Wrapper;
+
+//==================================================================================================
+/// The id of a TWS window group
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DisplayGroupId(pub i32);
+
+impl DisplayGroupId {
+    /// Parses the groups of Wrapper::display_group_list, e.g. "4|1|2", keeping their order of
+    /// most used first.  Ids that are not integers are left out.
+    pub fn parse_list(groups: &str) -> Vec<DisplayGroupId> {
+        groups
+            .split('|')
+            .filter_map(|group| group.trim().parse().ok())
+            .map(DisplayGroupId)
+            .collect()
+    }
+}
+
+impl fmt::Display for DisplayGroupId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+//==================================================================================================
+/// The contract selected in a display group
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum GroupSelection {
+    /// nothing selected
+    None,
+    /// a single contract, e.g. 8314@SMART for IBM on SMART
+    Contract { con_id: i32, exchange: String },
+    /// a combo
+    Combo,
+    /// an encoding this library does not know
+    Other(String),
+}
+
+impl GroupSelection {
+    /// The selection of a contract on its exchange
+    pub fn contract(contract: &Contract) -> Self {
+        GroupSelection::Contract {
+            con_id: contract.con_id,
+            exchange: contract.exchange.to_string(),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Parses the contract_info of Wrapper::display_group_updated
+    pub fn from_code(contract_info: &str) -> Self {
+        match contract_info {
+            "" | "none" => GroupSelection::None,
+            "combo" => GroupSelection::Combo,
+            _ => match contract_info.split_once('@') {
+                Some((con_id, exchange)) => match con_id.parse() {
+                    Ok(con_id) => GroupSelection::Contract {
+                        con_id,
+                        exchange: exchange.to_string(),
+                    },
+                    Err(_) => GroupSelection::Other(contract_info.to_string()),
+                },
+                None => GroupSelection::Other(contract_info.to_string()),
+            },
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The contract_info TWS knows the selection by
+    pub fn code(&self) -> String {
+        match self {
+            GroupSelection::None => "none".to_string(),
+            GroupSelection::Contract { con_id, exchange } => format!("{}@{}", con_id, exchange),
+            GroupSelection::Combo => "combo".to_string(),
+            GroupSelection::Other(code) => code.clone(),
+        }
+    }
+}
+
+impl fmt::Display for GroupSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+//==================================================================================================
+/// The selection of a subscribed display group, sent once on subscribing and again on every
+/// change
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DisplayGroupEvent {
+    pub req_id: i32,
+    pub group_id: DisplayGroupId,
+    pub selection: GroupSelection,
+}
+
+impl fmt::Display for DisplayGroupEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "req_id: {}, group_id: {}, selection: {}",
+            self.req_id, self.group_id, self.selection
+        )
+    }
+}
+
+//==================================================================================================
+/// The display groups a client subscribed to, and the req_id of each subscription
+#[derive(Debug, Default)]
+pub struct DisplayGroups {
+    subscriptions: HashMap<DisplayGroupId, i32>,
+}
+
+impl DisplayGroups {
+    pub fn new() -> Self {
+        DisplayGroups::default()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Requests the groups of the TWS session, most used first.  Blocks until TWS answers, so
+    /// call it from a thread other than the one processing messages.
+    ///
+    /// # Arguments
+    /// * client - the connected client
+    /// * req_id - the id of the query_display_groups request
+    /// * timeout - how long to wait for the groups
+    pub fn query<T>(
+        client: &mut EClient<T>,
+        req_id: i32,
+        timeout: Duration,
+    ) -> Result<Vec<DisplayGroupId>, IBKRApiLibError>
+    where
+        T: Wrapper + Send + Sync + 'static,
+    {
+        Ok(client.display_groups(req_id)?.recv_timeout(timeout)?)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Subscribes to the selection of a group.  Returns a receiver of its DisplayGroupEvents,
+    /// which unsubscribe ends.
+    ///
+    /// Returns an error with code TwsError::InvalidRequest if the group is already subscribed.
+    pub fn subscribe<T>(
+        &mut self,
+        client: &mut EClient<T>,
+        req_id: i32,
+        group_id: DisplayGroupId,
+    ) -> Result<Receiver<DisplayGroupEvent>, IBKRApiLibError>
+    where
+        T: Wrapper + Send + Sync + 'static,
+    {
+        if let Some(subscribed) = self.subscriptions.get(&group_id) {
+            return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                req_id,
+                TwsError::InvalidRequest.code().to_string(),
+                format!(
+                    "{} Display group {} is already subscribed with req_id {}.",
+                    TwsError::InvalidRequest.message(),
+                    group_id,
+                    subscribed
+                ),
+            )));
+        }
+        let events = client.subscribe_display_group(req_id, group_id)?;
+        self.subscriptions.insert(group_id, req_id);
+        Ok(events)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Shows a selection in the windows of a subscribed group.  TWS only answers with an error,
+    /// or with a DisplayGroupEvent once the selection changed.
+    ///
+    /// Returns an error with code TwsError::InvalidRequest if the group is not subscribed.
+    pub fn select<T>(
+        &self,
+        client: &mut EClient<T>,
+        group_id: DisplayGroupId,
+        selection: &GroupSelection,
+    ) -> Result<(), IBKRApiLibError>
+    where
+        T: Wrapper + Send + Sync + 'static,
+    {
+        let req_id = self.req_id(group_id)?;
+        client.update_display_group(req_id, selection.code().as_str())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Ends the subscription of a group.
+    ///
+    /// Returns an error with code TwsError::InvalidRequest if the group is not subscribed.
+    pub fn unsubscribe<T>(
+        &mut self,
+        client: &mut EClient<T>,
+        group_id: DisplayGroupId,
+    ) -> Result<(), IBKRApiLibError>
+    where
+        T: Wrapper + Send + Sync + 'static,
+    {
+        let req_id = self.req_id(group_id)?;
+        client.unsubscribe_from_group_events(req_id)?;
+        self.subscriptions.remove(&group_id);
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The subscribed groups, ordered by id
+    pub fn subscribed(&self) -> Vec<DisplayGroupId> {
+        let mut groups: Vec<DisplayGroupId> = self.subscriptions.keys().copied().collect();
+        groups.sort();
+        groups
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn req_id(&self, group_id: DisplayGroupId) -> Result<i32, IBKRApiLibError> {
+        self.subscriptions.get(&group_id).copied().ok_or_else(|| {
+            IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                NO_VALID_ID,
+                TwsError::InvalidRequest.code().to_string(),
+                format!(
+                    "{} Display group {} is not subscribed.",
+                    TwsError::InvalidRequest.message(),
+                    group_id
+                ),
+            ))
+        })
+    }
+}
@@ -3,6 +3,8 @@ use std::num::{ParseFloatError, ParseIntError};
 use std::sync::mpsc::{RecvError, RecvTimeoutError};
 use std::{error, fmt, io};
 
+use serde::{Deserialize, Serialize};
+
 const ALREADY_CONNECTED: (i32, &str) = (501, "Already connected.");
 const CONNECT_FAIL: (i32, &str) = (502, "Couldn't connect to TWS. Confirm that \"Enable ActiveX and Socket EClients\"
                                             is enabled and connection port is the same as \"Socket Port\" on the
@@ -18,6 +20,12 @@ const BAD_MESSAGE: (i32, &str) = (508, "Bad message.");
 const SOCKET_EXCEPTION: (i32, &str) = (509, "Exception caught while reading socket.");
 const FAIL_CREATE_SOCK: (i32, &str) = (520, "Failed to create socket.");
 const SSL_FAIL: (i32, &str) = (530, "SSL specific TwsError.");
+const PRECAUTION_VIOLATION: (i32, &str) = (
+    540,
+    "Order rejected by the client-side precautionary settings.",
+);
+const NO_LINES_LEFT: (i32, &str) = (541, "No market data lines left on any pooled connection.");
+const INVALID_REQUEST: (i32, &str) = (542, "Request rejected by client-side validation.");
 
 #[derive(Clone, Debug)]
 pub enum TwsError {
@@ -32,6 +40,9 @@ pub enum TwsError {
     SocketException,
     FailCreateSock,
     SslFail,
+    PrecautionViolation,
+    NoLinesLeft,
+    InvalidRequest,
 }
 
 impl TwsError {
@@ -48,6 +59,9 @@ impl TwsError {
             TwsError::SocketException => SOCKET_EXCEPTION.0,
             TwsError::FailCreateSock => FAIL_CREATE_SOCK.0,
             TwsError::SslFail => SSL_FAIL.0,
+            TwsError::PrecautionViolation => PRECAUTION_VIOLATION.0,
+            TwsError::NoLinesLeft => NO_LINES_LEFT.0,
+            TwsError::InvalidRequest => INVALID_REQUEST.0,
         }
     }
     pub fn message(&self) -> &'static str {
@@ -63,6 +77,9 @@ impl TwsError {
             TwsError::SocketException => SOCKET_EXCEPTION.1,
             TwsError::FailCreateSock => FAIL_CREATE_SOCK.1,
             TwsError::SslFail => SSL_FAIL.1,
+            TwsError::PrecautionViolation => PRECAUTION_VIOLATION.1,
+            TwsError::NoLinesLeft => NO_LINES_LEFT.1,
+            TwsError::InvalidRequest => INVALID_REQUEST.1,
         }
     }
 }
@@ -83,6 +100,7 @@ pub enum IBKRApiLibError {
     RecvError(RecvError),
     RecvTimeoutError(RecvTimeoutError),
     ApiError(TwsApiReportableError),
+    Message(MessageError),
 }
 
 impl fmt::Display for IBKRApiLibError {
@@ -96,6 +114,7 @@ impl fmt::Display for IBKRApiLibError {
             IBKRApiLibError::RecvError(ref err) => write!(f, "Recieve error: {}", err),
             IBKRApiLibError::RecvTimeoutError(ref err) => write!(f, "Reader Send error {}", err),
             IBKRApiLibError::ApiError(ref err) => write!(f, "TWS Error: {}", err),
+            IBKRApiLibError::Message(ref err) => write!(f, "Message error: {}", err),
         }
     }
 }
@@ -111,6 +130,7 @@ impl fmt::Debug for IBKRApiLibError {
             IBKRApiLibError::RecvError(ref err) => write!(f, "Recieve error: {}", err),
             IBKRApiLibError::RecvTimeoutError(ref err) => write!(f, "Reader Send error {}", err),
             IBKRApiLibError::ApiError(ref err) => write!(f, "TWS Error: {}", err),
+            IBKRApiLibError::Message(ref err) => write!(f, "Message error: {}", err),
         }
     }
 }
@@ -128,6 +148,7 @@ impl error::Error for IBKRApiLibError {
             IBKRApiLibError::RecvError(ref err) => Some(err),
             IBKRApiLibError::RecvTimeoutError(ref err) => Some(err),
             IBKRApiLibError::ApiError(ref err) => Some(err),
+            IBKRApiLibError::Message(ref err) => Some(err),
         }
     }
 }
@@ -162,6 +183,12 @@ impl From<RecvTimeoutError> for IBKRApiLibError {
     }
 }
 
+impl From<MessageError> for IBKRApiLibError {
+    fn from(err: MessageError) -> IBKRApiLibError {
+        IBKRApiLibError::Message(err)
+    }
+}
+
 impl From<TwsApiReportableError> for IBKRApiLibError {
     fn from(err: TwsApiReportableError) -> IBKRApiLibError {
         IBKRApiLibError::ApiError(err)
@@ -195,4 +222,355 @@ impl fmt::Display for TwsApiReportableError {
     }
 }
 
+impl TwsApiReportableError {
+    /// The categorized meaning of the code, None if the code is not a number
+    pub fn kind(&self) -> Option<IbError> {
+        self.code.parse().ok().map(IbError::from_code)
+    }
+}
+
 impl error::Error for TwsApiReportableError {}
+
+//==================================================================================================
+/// A frame read from TWS that can't be turned into a message
+#[derive(Clone, Debug, PartialEq)]
+pub enum MessageError {
+    /// The length prefix is negative or larger than MAX_MSG_LEN.  The stream can't be split
+    /// into frames after it.
+    InvalidLength(i32),
+    /// The payload of a size byte frame is not UTF-8.  The frames after it are unaffected.
+    /// valid_up_to - how many bytes of the payload are valid UTF-8
+    InvalidUtf8 { size: usize, valid_up_to: usize },
+    /// The Decoder fell behind by more than the capacity of the message queue, with
+    /// QueuePolicy::Error.  The messages after it were discarded.
+    QueueOverflow { capacity: usize },
+}
+
+impl MessageError {
+    /// The TwsError code the error is reported with
+    pub fn code(&self) -> i32 {
+        match *self {
+            MessageError::InvalidLength(_) => TwsError::BadLength.code(),
+            MessageError::InvalidUtf8 { .. } => TwsError::BadMessage.code(),
+            MessageError::QueueOverflow { .. } => TwsError::SocketException.code(),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether the connection can't continue after the error
+    pub fn is_fatal(&self) -> bool {
+        matches!(
+            self,
+            MessageError::InvalidLength(_) | MessageError::QueueOverflow { .. }
+        )
+    }
+}
+
+impl fmt::Display for MessageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MessageError::InvalidLength(size) => write!(
+                f,
+                "{} Invalid frame length {}.",
+                TwsError::BadLength.message(),
+                size
+            ),
+            MessageError::InvalidUtf8 { size, valid_up_to } => write!(
+                f,
+                "{} The {} byte frame is not UTF-8 after byte {}.",
+                TwsError::BadMessage.message(),
+                size,
+                valid_up_to
+            ),
+            MessageError::QueueOverflow { capacity } => write!(
+                f,
+                "{} The message queue is full with {} messages.",
+                TwsError::SocketException.message(),
+                capacity
+            ),
+        }
+    }
+}
+
+impl error::Error for MessageError {}
+
+//==================================================================================================
+/// Returns true for the informational codes TWS sends through the error message, such as the
+/// 2100 range of connectivity and market data farm notices.  These are passed to
+/// Wrapper::warning instead of Wrapper::error.
+pub fn is_warning_code(code: i32) -> bool {
+    (2100..2200).contains(&code)
+}
+
+/// Code of the "Order Message: Warning" TWS sends about an order it goes on to handle
+pub const ORDER_MESSAGE_WARNING: i32 = 399;
+
+//==================================================================================================
+/// Returns true for the codes that warn about an order rather than reject it: 399 and the
+/// informational 2100 range
+pub fn is_order_warning(code: i32) -> bool {
+    code == ORDER_MESSAGE_WARNING || is_warning_code(code)
+}
+
+//==================================================================================================
+/// An error or warning message received from TWS
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TwsMessage {
+    pub req_id: i32,
+    pub code: i32,
+    pub message: String,
+}
+
+impl TwsMessage {
+    pub fn new(req_id: i32, code: i32, message: String) -> Self {
+        TwsMessage {
+            req_id,
+            code,
+            message,
+        }
+    }
+
+    pub fn is_warning(&self) -> bool {
+        is_warning_code(self.code)
+    }
+
+    /// The categorized meaning of the code
+    pub fn kind(&self) -> IbError {
+        IbError::from_code(self.code)
+    }
+
+    /// The category of the code and message, see IbError::category_of
+    pub fn category(&self) -> ErrorCategory {
+        self.kind().category_of(&self.message)
+    }
+}
+
+impl fmt::Display for TwsMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "req_id: {}, code: {}, message: {}",
+            self.req_id, self.code, self.message
+        )
+    }
+}
+
+//==================================================================================================
+/// What an error code concerns
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// The connection between TWS and IB's servers, or to a data farm
+    Connectivity,
+    /// Requests sent faster than allowed
+    Pacing,
+    /// An order not accepted or cancelled by TWS
+    OrderReject,
+    MarketData,
+    /// A contract that could not be resolved
+    Contract,
+    /// Any other request TWS refused
+    Request,
+    /// Errors raised by this library rather than TWS, codes 501 to 599
+    Client,
+    Unknown,
+}
+
+//==================================================================================================
+/// How serious an error code is
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorSeverity {
+    /// A notice that something works, e.g. a data farm connection is OK
+    Info,
+    /// Degraded but working, e.g. a data farm connection is broken and will be retried
+    Warning,
+    /// A request failed
+    Error,
+    /// The session can't continue without intervention
+    Fatal,
+}
+
+//==================================================================================================
+/// The error codes TWS sends, as far as callers usually act on them
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IbError {
+    /// 100 - max rate of messages per second exceeded
+    MaxRateExceeded,
+    /// 101 - max number of tickers reached
+    MaxTickersReached,
+    /// 103 - duplicate order id
+    DuplicateOrderId,
+    /// 162 - historical market data service error: a pacing violation, or a request TWS
+    /// refused, e.g. for a contract without data
+    HistoricalDataError,
+    /// 200 - no security definition found
+    NoSecurityDefinition,
+    /// 201 - order rejected
+    OrderRejected,
+    /// 202 - order cancelled
+    OrderCancelled,
+    /// 354 - requested market data is not subscribed
+    MarketDataNotSubscribed,
+    /// 1100 - connectivity between IB and TWS lost, which TWS restores by itself
+    ConnectivityLost,
+    /// 1101 - connectivity restored, market data subscriptions lost and must be requested again
+    ConnectivityRestoredDataLost,
+    /// 1102 - connectivity restored with data maintained
+    ConnectivityRestored,
+    /// 2103 - a market data farm connection is broken
+    MarketDataFarmBroken,
+    /// 2104 - a market data farm connection is OK
+    MarketDataFarmOk,
+    /// 2105 - a historical data farm connection is broken
+    HistoricalDataFarmBroken,
+    /// 2106 - a historical data farm connection is OK
+    HistoricalDataFarmOk,
+    /// 2107 - a historical data farm connection is inactive but available on demand
+    HistoricalDataFarmInactive,
+    /// 2108 - a market data farm connection is inactive but available on demand
+    MarketDataFarmInactive,
+    /// 2158 - a security definition data farm connection is OK
+    SecDefFarmOk,
+    /// 10167 - market data not subscribed, delayed data displayed
+    DelayedMarketData,
+    /// Any TWS code without a variant of its own
+    Other(i32),
+    /// A code of this library, from TwsError
+    Client(i32),
+}
+
+impl IbError {
+    pub fn from_code(code: i32) -> Self {
+        match code {
+            100 => IbError::MaxRateExceeded,
+            101 => IbError::MaxTickersReached,
+            103 => IbError::DuplicateOrderId,
+            162 => IbError::HistoricalDataError,
+            200 => IbError::NoSecurityDefinition,
+            201 => IbError::OrderRejected,
+            202 => IbError::OrderCancelled,
+            354 => IbError::MarketDataNotSubscribed,
+            1100 => IbError::ConnectivityLost,
+            1101 => IbError::ConnectivityRestoredDataLost,
+            1102 => IbError::ConnectivityRestored,
+            2103 => IbError::MarketDataFarmBroken,
+            2104 => IbError::MarketDataFarmOk,
+            2105 => IbError::HistoricalDataFarmBroken,
+            2106 => IbError::HistoricalDataFarmOk,
+            2107 => IbError::HistoricalDataFarmInactive,
+            2108 => IbError::MarketDataFarmInactive,
+            2158 => IbError::SecDefFarmOk,
+            10167 => IbError::DelayedMarketData,
+            501..=599 => IbError::Client(code),
+            _ => IbError::Other(code),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn code(&self) -> i32 {
+        match *self {
+            IbError::MaxRateExceeded => 100,
+            IbError::MaxTickersReached => 101,
+            IbError::DuplicateOrderId => 103,
+            IbError::HistoricalDataError => 162,
+            IbError::NoSecurityDefinition => 200,
+            IbError::OrderRejected => 201,
+            IbError::OrderCancelled => 202,
+            IbError::MarketDataNotSubscribed => 354,
+            IbError::ConnectivityLost => 1100,
+            IbError::ConnectivityRestoredDataLost => 1101,
+            IbError::ConnectivityRestored => 1102,
+            IbError::MarketDataFarmBroken => 2103,
+            IbError::MarketDataFarmOk => 2104,
+            IbError::HistoricalDataFarmBroken => 2105,
+            IbError::HistoricalDataFarmOk => 2106,
+            IbError::HistoricalDataFarmInactive => 2107,
+            IbError::MarketDataFarmInactive => 2108,
+            IbError::SecDefFarmOk => 2158,
+            IbError::DelayedMarketData => 10167,
+            IbError::Other(code) | IbError::Client(code) => code,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn category(&self) -> ErrorCategory {
+        match *self {
+            IbError::MaxRateExceeded => ErrorCategory::Pacing,
+            IbError::OrderRejected | IbError::OrderCancelled | IbError::DuplicateOrderId => {
+                ErrorCategory::OrderReject
+            }
+            IbError::NoSecurityDefinition => ErrorCategory::Contract,
+            IbError::HistoricalDataError => ErrorCategory::Request,
+            IbError::MaxTickersReached
+            | IbError::MarketDataNotSubscribed
+            | IbError::DelayedMarketData => ErrorCategory::MarketData,
+            IbError::ConnectivityLost
+            | IbError::ConnectivityRestoredDataLost
+            | IbError::ConnectivityRestored
+            | IbError::MarketDataFarmBroken
+            | IbError::MarketDataFarmOk
+            | IbError::HistoricalDataFarmBroken
+            | IbError::HistoricalDataFarmOk
+            | IbError::HistoricalDataFarmInactive
+            | IbError::MarketDataFarmInactive
+            | IbError::SecDefFarmOk => ErrorCategory::Connectivity,
+            IbError::Client(_) => ErrorCategory::Client,
+            IbError::Other(code) if (2100..2200).contains(&code) => ErrorCategory::Connectivity,
+            IbError::Other(code) if (100..1000).contains(&code) => ErrorCategory::Request,
+            IbError::Other(code) if (10000..11000).contains(&code) => ErrorCategory::Request,
+            IbError::Other(_) => ErrorCategory::Unknown,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The category of the code given the message TWS sent with it: a 162 is Pacing when its
+    /// message reports a pacing violation, and Request otherwise
+    pub fn category_of(&self, message: &str) -> ErrorCategory {
+        match *self {
+            IbError::HistoricalDataError if message.to_lowercase().contains("pacing violation") => {
+                ErrorCategory::Pacing
+            }
+            _ => self.category(),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn severity(&self) -> ErrorSeverity {
+        match *self {
+            IbError::ConnectivityRestored
+            | IbError::MarketDataFarmOk
+            | IbError::HistoricalDataFarmOk
+            | IbError::HistoricalDataFarmInactive
+            | IbError::MarketDataFarmInactive
+            | IbError::SecDefFarmOk => ErrorSeverity::Info,
+            IbError::ConnectivityRestoredDataLost
+            | IbError::MarketDataFarmBroken
+            | IbError::HistoricalDataFarmBroken
+            | IbError::DelayedMarketData
+            | IbError::ConnectivityLost => ErrorSeverity::Warning,
+            IbError::Client(code)
+                if code == TwsError::ConnectFail.code()
+                    || code == TwsError::NotConnected.code()
+                    || code == TwsError::SocketException.code() =>
+            {
+                ErrorSeverity::Fatal
+            }
+            IbError::Other(code) if is_warning_code(code) => ErrorSeverity::Warning,
+            _ => ErrorSeverity::Error,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether the code is a notice rather than a failure
+    pub fn is_informational(&self) -> bool {
+        self.severity() <= ErrorSeverity::Warning
+    }
+}
+
+impl fmt::Display for IbError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IbError::Other(code) | IbError::Client(code) => write!(f, "{}", code),
+            _ => write!(f, "{:?} ({})", self, self.code()),
+        }
+    }
+}
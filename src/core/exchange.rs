@@ -0,0 +1,244 @@
+//! Exchange identifiers
+//!
+//! Contracts name their exchange by IB's code, e.g. "SMART" or "GLOBEX".  TWS answers a
+//! misspelled code with a generic "no security definition" error, so Exchange names the known
+//! venues and keeps unknown codes as Other.  EClient rejects the codes of a contract that look
+//! like a misspelled known one before sending the request.
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+//==================================================================================================
+/// The exchange of a contract or market depth row.  Unknown venues are kept as Other with
+/// their code.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(from = "String", into = "String")]
+pub enum Exchange {
+    /// no exchange given
+    #[default]
+    None,
+    Smart,
+    Island,
+    Nasdaq,
+    Arca,
+    Nyse,
+    Amex,
+    Bats,
+    Iex,
+    Edgx,
+    Chx,
+    Psx,
+    Memx,
+    Ltse,
+    Pearl,
+    Globex,
+    Cme,
+    Cbot,
+    Ecbot,
+    Nymex,
+    Comex,
+    Cfe,
+    IceUs,
+    Nybot,
+    Eurex,
+    IceEu,
+    Cboe,
+    Box,
+    Ise,
+    Phlx,
+    Miax,
+    IdealPro,
+    /// the IBKR alternative trading system, the only route accepting not held orders
+    Ibkrats,
+    Paxos,
+    ZeroHash,
+    Lse,
+    Sehk,
+    Tsej,
+    Asx,
+    Tse,
+    Ibis,
+    Fwb,
+    Sbf,
+    Aeb,
+    Ebs,
+    Sgx,
+    Nse,
+    Other(String),
+}
+
+/// The known exchanges, by their IB code
+const KNOWN: &[(&str, Exchange)] = &[
+    ("SMART", Exchange::Smart),
+    ("ISLAND", Exchange::Island),
+    ("NASDAQ", Exchange::Nasdaq),
+    ("ARCA", Exchange::Arca),
+    ("NYSE", Exchange::Nyse),
+    ("AMEX", Exchange::Amex),
+    ("BATS", Exchange::Bats),
+    ("IEX", Exchange::Iex),
+    ("EDGX", Exchange::Edgx),
+    ("CHX", Exchange::Chx),
+    ("PSX", Exchange::Psx),
+    ("MEMX", Exchange::Memx),
+    ("LTSE", Exchange::Ltse),
+    ("PEARL", Exchange::Pearl),
+    ("GLOBEX", Exchange::Globex),
+    ("CME", Exchange::Cme),
+    ("CBOT", Exchange::Cbot),
+    ("ECBOT", Exchange::Ecbot),
+    ("NYMEX", Exchange::Nymex),
+    ("COMEX", Exchange::Comex),
+    ("CFE", Exchange::Cfe),
+    ("ICEUS", Exchange::IceUs),
+    ("NYBOT", Exchange::Nybot),
+    ("EUREX", Exchange::Eurex),
+    ("ICEEU", Exchange::IceEu),
+    ("CBOE", Exchange::Cboe),
+    ("BOX", Exchange::Box),
+    ("ISE", Exchange::Ise),
+    ("PHLX", Exchange::Phlx),
+    ("MIAX", Exchange::Miax),
+    ("IDEALPRO", Exchange::IdealPro),
+    ("IBKRATS", Exchange::Ibkrats),
+    ("PAXOS", Exchange::Paxos),
+    ("ZEROHASH", Exchange::ZeroHash),
+    ("LSE", Exchange::Lse),
+    ("SEHK", Exchange::Sehk),
+    ("TSEJ", Exchange::Tsej),
+    ("ASX", Exchange::Asx),
+    ("TSE", Exchange::Tse),
+    ("IBIS", Exchange::Ibis),
+    ("FWB", Exchange::Fwb),
+    ("SBF", Exchange::Sbf),
+    ("AEB", Exchange::Aeb),
+    ("EBS", Exchange::Ebs),
+    ("SGX", Exchange::Sgx),
+    ("NSE", Exchange::Nse),
+];
+
+impl Exchange {
+    /// The exchange of a code.  Known codes are matched ignoring case.
+    pub fn from_code(code: &str) -> Exchange {
+        let code = code.trim();
+        if code.is_empty() {
+            return Exchange::None;
+        }
+        KNOWN
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(code))
+            .map(|(_, exchange)| exchange.clone())
+            .unwrap_or_else(|| Exchange::Other(code.to_string()))
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The IB code of the exchange, empty for None
+    pub fn code(&self) -> &str {
+        match self {
+            Exchange::None => "",
+            Exchange::Other(code) => code,
+            known => KNOWN
+                .iter()
+                .find(|(_, exchange)| exchange == known)
+                .map(|(code, _)| *code)
+                .unwrap_or_default(),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn is_set(&self) -> bool {
+        *self != Exchange::None
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The known exchange an Other code is one typo away from, e.g. GLOBEX for "GLOBAX", "GLOBX"
+    /// or SMART for "SMRAT".  None for known exchanges and for codes unlike any known one.
+    ///
+    /// Short codes and codes with digits are left alone, as many real venues are one letter
+    /// or digit away from a known one, e.g. BEX and BOX or IBIS2 and IBIS.
+    pub fn likely_misspelling_of(&self) -> Option<Exchange> {
+        let code = match self {
+            Exchange::Other(code) if code.chars().all(|c| c.is_ascii_alphabetic()) => {
+                code.to_ascii_uppercase()
+            }
+            _ => return None,
+        };
+        KNOWN
+            .iter()
+            .filter(|(known, _)| {
+                // a letter missing or added only counts for the longer codes
+                let min_len = if known.len() == code.len() { 4 } else { 5 };
+                known.len() >= min_len && edit_distance(known, &code) == 1
+            })
+            .map(|(_, exchange)| exchange.clone())
+            .next()
+    }
+}
+
+impl fmt::Display for Exchange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl From<&str> for Exchange {
+    fn from(code: &str) -> Self {
+        Exchange::from_code(code)
+    }
+}
+
+impl From<String> for Exchange {
+    fn from(code: String) -> Self {
+        Exchange::from_code(&code)
+    }
+}
+
+impl From<Exchange> for String {
+    fn from(exchange: Exchange) -> Self {
+        exchange.code().to_string()
+    }
+}
+
+impl PartialEq<str> for Exchange {
+    fn eq(&self, code: &str) -> bool {
+        self.code() == code
+    }
+}
+
+impl PartialEq<&str> for Exchange {
+    fn eq(&self, code: &&str) -> bool {
+        self.code() == *code
+    }
+}
+
+impl PartialEq<String> for Exchange {
+    fn eq(&self, code: &String) -> bool {
+        self.code() == code
+    }
+}
+
+//==================================================================================================
+/// Edits turning one code into the other, counting a swap of adjacent letters as one
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + substitution);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
@@ -1,23 +1,27 @@
 //! Types related to executions
 use std::fmt::{Display, Error, Formatter};
 
+use bigdecimal::BigDecimal;
 use serde::{Deserialize, Serialize};
 
+use crate::core::timestamps::{timestamp_to_wire, Timestamp};
+
 //==================================================================================================
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct Execution {
     pub exec_id: String,
-    pub time: String,
+    #[serde(with = "crate::core::timestamps::serde_timestamp")]
+    pub time: Timestamp,
     pub acct_number: String,
     pub exchange: String,
     pub side: String,
-    pub shares: f64,
+    pub shares: BigDecimal,
     pub price: f64,
     pub perm_id: i32,
     pub client_id: i32,
     pub order_id: i32,
     pub liquidation: i32,
-    pub cum_qty: f64,
+    pub cum_qty: BigDecimal,
     pub avg_price: f64,
     pub order_ref: String,
     pub ev_rule: String,
@@ -29,17 +33,17 @@ pub struct Execution {
 impl Execution {
     pub fn new(
         exec_id: String,
-        time: String,
+        time: Timestamp,
         acct_number: String,
         exchange: String,
         side: String,
-        shares: f64,
+        shares: BigDecimal,
         price: f64,
         perm_id: i32,
         client_id: i32,
         order_id: i32,
         liquidation: i32,
-        cum_qty: f64,
+        cum_qty: BigDecimal,
         avg_price: f64,
         order_ref: String,
         ev_rule: String,
@@ -93,7 +97,7 @@ impl Display for Execution {
             model_code: : {},
             last_liquidity: : {} ",
             self.exec_id,
-            self.time,
+            timestamp_to_wire(&self.time),
             self.acct_number,
             self.exchange,
             self.side,
@@ -0,0 +1,126 @@
+//! Typed requests and status updates for exercising options
+//!
+//! EClient::exercise_options takes the action and override flag as bare integers and sends
+//! whatever contract it is given, so a typo exercises nothing or the wrong series.
+//! OptionExercise names the action with ExerciseAction, and EClient::exercise resolves the
+//! option to a single contract before sending the request, then yields an ExerciseStatus for
+//! each order status TWS reports for it.
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::common::{SecType, NO_VALID_ID};
+use crate::core::contract::Contract;
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+use crate::core::order_tracker::OrderPhase;
+
+//==================================================================================================
+/// What to do with an option
+#[repr(i32)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ExerciseAction {
+    Exercise = 1,
+    Lapse = 2,
+}
+
+impl ExerciseAction {
+    /// The code TWS knows the action by
+    pub fn code(&self) -> i32 {
+        *self as i32
+    }
+}
+
+impl fmt::Display for ExerciseAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+//==================================================================================================
+/// A request to exercise or lapse a quantity of an option
+/// account - the account holding the option, empty for the only account of the login
+/// override_natural - whether to act against the natural action, e.g. exercise an option out
+/// of the money, which TWS would otherwise let lapse
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OptionExercise {
+    pub contract: Contract,
+    pub action: ExerciseAction,
+    pub quantity: i32,
+    pub account: String,
+    pub override_natural: bool,
+}
+
+impl OptionExercise {
+    pub fn new(contract: Contract, action: ExerciseAction, quantity: i32, account: &str) -> Self {
+        OptionExercise {
+            contract,
+            action,
+            quantity,
+            account: account.to_string(),
+            override_natural: false,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn override_natural(mut self, override_natural: bool) -> Self {
+        self.override_natural = override_natural;
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Checks that the request is for a positive quantity of an OPT or FOP contract.  Returns
+    /// an error with code TwsError::InvalidRequest otherwise.
+    pub fn check(&self) -> Result<(), IBKRApiLibError> {
+        let rejected = if !matches!(self.contract.sec_type, SecType::OPT | SecType::FOP) {
+            Some(format!(
+                "only OPT and FOP contracts are exercised, not {}",
+                self.contract.sec_type
+            ))
+        } else if self.quantity <= 0 {
+            Some(format!(
+                "the quantity to exercise must be greater than 0, got {}",
+                self.quantity
+            ))
+        } else {
+            None
+        };
+        match rejected {
+            Some(detail) => Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                NO_VALID_ID,
+                TwsError::InvalidRequest.code().to_string(),
+                format!("{} {}.", TwsError::InvalidRequest.message(), detail),
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+//==================================================================================================
+/// An order status TWS reported for an exercise request
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ExerciseStatus {
+    pub req_id: i32,
+    /// the status as TWS sent it, e.g. "Submitted"
+    pub status: String,
+    /// the phase of the status, None for statuses not describing one
+    pub phase: Option<OrderPhase>,
+    pub filled: f64,
+    pub remaining: f64,
+}
+
+impl ExerciseStatus {
+    /// Whether the exercise is done: filled, cancelled or rejected
+    pub fn is_terminal(&self) -> bool {
+        self.phase.is_some_and(|phase| phase.is_terminal())
+    }
+}
+
+impl fmt::Display for ExerciseStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "req_id: {}, status: {}, filled: {}, remaining: {}",
+            self.req_id, self.status, self.filled, self.remaining
+        )
+    }
+}
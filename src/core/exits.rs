@@ -0,0 +1,293 @@
+//! Managing the exit of a bracketed position
+//!
+//! ExitManager follows a position entered with a bracket: the entry order and its take profit
+//! and stop loss children, e.g. from order_samples::bracket_order.  Fed the price, and the
+//! OrderTracker that knows the fills of the entry, it adjusts the exit by modifying the
+//! children:
+//!
+//! * breakeven - once the price moved trigger in favour of the position, the stop moves to
+//!   the entry price, plus offset in favour
+//! * ATR trail - the stop trails the best price since the entry by a multiple of the average
+//!   true range the caller passes
+//! * scale out - at each target move part of the position is closed with a market order and
+//!   the children are resized to what is left; with two targets the position is closed in
+//!   thirds, the last third by the children.  Only once the entry filled completely, as the
+//!   children cover the whole entry until then.
+//!
+//! The stop only ever moves in favour of the position.  on_price returns the orders to place
+//! and apply places them and tracks them in the OrderTracker; an order with the id of a child
+//! modifies the child.
+use std::fmt;
+
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use crate::core::client::EClient;
+use crate::core::contract::Contract;
+use crate::core::errors::IBKRApiLibError;
+use crate::core::log_targets::ORDERS;
+use crate::core::order::{Order, OrderType};
+use crate::core::order_tracker::{OrderPhase, OrderTracker};
+use crate::core::wrapper::Wrapper;
+
+//==================================================================================================
+/// Why an ExitOrder is placed
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ExitReason {
+    Breakeven,
+    Trail,
+    ScaleOut,
+    /// the children resized after a scale out
+    Resize,
+}
+
+//==================================================================================================
+/// An order to place for the exit: a modified child, or a new order closing part of the
+/// position
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ExitOrder {
+    pub order_id: i32,
+    pub order: Order,
+    pub reason: ExitReason,
+}
+
+impl fmt::Display for ExitOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "order_id: {}, reason: {:?}, action: {}, order_type: {}, total_quantity: {}, aux_price: {}",
+            self.order_id,
+            self.reason,
+            self.order.action,
+            self.order.order_type,
+            self.order.total_quantity,
+            self.order.aux_price
+        )
+    }
+}
+
+//==================================================================================================
+/// Adjusts the take profit and stop loss of one bracketed position
+pub struct ExitManager {
+    contract: Contract,
+    parent_id: i32,
+    is_long: bool,
+    take_profit: Order,
+    stop_loss: Order,
+    breakeven: Option<(f64, f64)>,
+    trail_multiple: Option<f64>,
+    scale_out_targets: Vec<f64>,
+    moved_to_breakeven: bool,
+    scaled_out: usize,
+    closed_quantity: f64,
+    best_price: Option<f64>,
+}
+
+impl ExitManager {
+    /// # Arguments
+    /// * contract - the contract of the position
+    /// * parent - the entry order
+    /// * take_profit - the limit order child of the entry
+    /// * stop_loss - the stop order child of the entry
+    pub fn new(
+        contract: &Contract,
+        parent: &Order,
+        take_profit: &Order,
+        stop_loss: &Order,
+    ) -> Self {
+        ExitManager {
+            contract: contract.clone(),
+            parent_id: parent.order_id,
+            is_long: parent.action.is_buy(),
+            take_profit: take_profit.clone(),
+            stop_loss: stop_loss.clone(),
+            breakeven: None,
+            trail_multiple: None,
+            scale_out_targets: vec![],
+            moved_to_breakeven: false,
+            scaled_out: 0,
+            closed_quantity: 0.0,
+            best_price: None,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Moves the stop to the entry price plus offset once the price moved trigger in favour
+    pub fn with_breakeven(mut self, trigger: f64, offset: f64) -> Self {
+        self.breakeven = Some((trigger, offset));
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Trails the stop multiple times the ATR passed to on_price behind the best price
+    pub fn with_atr_trail(mut self, multiple: f64) -> Self {
+        self.trail_multiple = Some(multiple);
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Closes an equal part of the position at each of these moves in favour of the entry,
+    /// leaving one part to the children
+    pub fn with_scale_out(mut self, targets: &[f64]) -> Self {
+        self.scale_out_targets = targets.to_vec();
+        self.scale_out_targets
+            .sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The current stop price
+    pub fn stop_price(&self) -> f64 {
+        self.stop_loss.aux_price
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The quantity the children still close
+    pub fn remaining_quantity(&self) -> f64 {
+        self.stop_loss.total_quantity
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Takes a price of the contract and returns the orders adjusting the exit, none before
+    /// the entry filled in part and after a child filled.  Scale outs and resizes wait for the
+    /// entry to fill completely.
+    ///
+    /// # Arguments
+    /// * price - the last price of the contract
+    /// * atr - the current average true range, for the ATR trail
+    /// * tracker - the tracker of the bracket's orders
+    /// * next_order_id - the id for an order closing part of the position, incremented when
+    ///   used
+    pub fn on_price(
+        &mut self,
+        price: f64,
+        atr: Option<f64>,
+        tracker: &OrderTracker,
+        next_order_id: &mut i32,
+    ) -> Vec<ExitOrder> {
+        let entry = match tracker.get(self.parent_id) {
+            Some(parent) if parent.filled > 0.0 => parent,
+            _ => return vec![],
+        };
+        let children_done = [self.take_profit.order_id, self.stop_loss.order_id]
+            .iter()
+            .any(|child| {
+                tracker
+                    .get(*child)
+                    .is_some_and(|child| child.phase.is_terminal())
+            });
+        if children_done {
+            return vec![];
+        }
+        let entry_price = entry.avg_fill_price;
+        let filled = entry.filled;
+        let entry_complete = entry.phase == OrderPhase::Filled;
+        let sign = if self.is_long { 1.0 } else { -1.0 };
+        let favourable = (price - entry_price) * sign;
+        let best = match self.best_price {
+            Some(best) if (best - price) * sign >= 0.0 => best,
+            _ => price,
+        };
+        self.best_price = Some(best);
+
+        let mut stop = self.stop_loss.aux_price;
+        let mut reason = None;
+        if let Some((trigger, offset)) = self.breakeven {
+            if !self.moved_to_breakeven && favourable >= trigger {
+                self.moved_to_breakeven = true;
+                if self.is_protective(entry_price + sign * offset, stop) {
+                    stop = entry_price + sign * offset;
+                    reason = Some(ExitReason::Breakeven);
+                }
+            }
+        }
+        if let (Some(multiple), Some(atr)) = (self.trail_multiple, atr) {
+            let trailed = best - sign * multiple * atr;
+            if self.is_protective(trailed, stop) {
+                stop = trailed;
+                reason = Some(ExitReason::Trail);
+            }
+        }
+
+        let mut orders = vec![];
+        let parts = self.scale_out_targets.len() + 1;
+        while entry_complete
+            && self.scaled_out < self.scale_out_targets.len()
+            && favourable >= self.scale_out_targets[self.scaled_out]
+        {
+            self.scaled_out += 1;
+            let part = (filled / parts as f64).floor();
+            if part <= 0.0 || self.closed_quantity + part >= filled {
+                continue;
+            }
+            self.closed_quantity += part;
+            let close = Order {
+                action: self.stop_loss.action.clone(),
+                order_type: OrderType::Mkt,
+                total_quantity: part,
+                transmit: true,
+                ..Default::default()
+            };
+            orders.push(ExitOrder {
+                order_id: *next_order_id,
+                order: close,
+                reason: ExitReason::ScaleOut,
+            });
+            *next_order_id += 1;
+        }
+
+        let remaining = filled - self.closed_quantity;
+        let resized = entry_complete && remaining != self.stop_loss.total_quantity;
+        if resized {
+            self.take_profit.total_quantity = remaining;
+            self.take_profit.transmit = true;
+            orders.push(ExitOrder {
+                order_id: self.take_profit.order_id,
+                order: self.take_profit.clone(),
+                reason: ExitReason::Resize,
+            });
+        }
+        if resized || reason.is_some() {
+            self.stop_loss.aux_price = stop;
+            self.stop_loss.total_quantity = remaining;
+            self.stop_loss.transmit = true;
+            orders.push(ExitOrder {
+                order_id: self.stop_loss.order_id,
+                order: self.stop_loss.clone(),
+                reason: reason.unwrap_or(ExitReason::Resize),
+            });
+        }
+        for order in orders.iter() {
+            debug!(target: ORDERS, "Exit of order {} -- {}", self.parent_id, order);
+        }
+        orders
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Places the orders on_price returned and tracks them, the new ones from PendingSubmit
+    pub fn apply<T>(
+        &self,
+        client: &mut EClient<T>,
+        tracker: &mut OrderTracker,
+        orders: &[ExitOrder],
+    ) -> Result<(), IBKRApiLibError>
+    where
+        T: Wrapper + Send + Sync + 'static,
+    {
+        for exit in orders {
+            client.place_order(exit.order_id, &self.contract, &exit.order)?;
+            tracker.track(exit.order_id, &self.contract, &exit.order);
+        }
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether a stop at candidate protects more of the position than one at stop
+    fn is_protective(&self, candidate: f64, stop: f64) -> bool {
+        if self.is_long {
+            candidate > stop
+        } else {
+            candidate < stop
+        }
+    }
+}
@@ -0,0 +1,499 @@
+//! Writes historical data and executions to CSV and Apache Parquet files.  Enabled by the
+//! "export" feature.
+//!
+//! In CSV, prices are written with Rust's shortest round-trip float formatting so reading a
+//! file back yields the exact values received from TWS.  Times are written both as received
+//! and as an RFC 3339 UTC timestamp column.
+//!
+//! In Parquet, the columns are typed: prices and sizes as doubles, counts and ids as integers,
+//! flags as booleans, and the timestamp column as microseconds since the epoch in UTC, null
+//! where TWS sent no readable time.  The times as received are kept in a text column too.
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow_array::{
+    ArrayRef, BooleanArray, Float64Array, Int32Array, RecordBatch, StringArray,
+    TimestampMicrosecondArray,
+};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use bigdecimal::BigDecimal;
+use chrono::{TimeZone, Utc};
+use num_traits::ToPrimitive;
+use parquet::arrow::ArrowWriter;
+
+use crate::core::common::{BarData, HistoricalTick, HistoricalTickBidAsk, HistoricalTickLast};
+use crate::core::errors::IBKRApiLibError;
+use crate::core::execution::Execution;
+use crate::core::history::bar_timestamp;
+use crate::core::timestamps::{timestamp_seconds, timestamp_time, timestamp_to_wire, Timestamp};
+
+/// Time zone of the timestamp columns of Parquet files
+const PARQUET_TIME_ZONE: &str = "UTC";
+
+//==================================================================================================
+/// A struct that can be written as one CSV row
+pub trait CsvRecord {
+    /// Column names
+    fn header() -> &'static [&'static str];
+    /// Column values, in the order of header()
+    fn record(&self) -> Vec<String>;
+}
+
+//==================================================================================================
+/// Writes a header row and one row per record
+pub fn write_csv<W: Write, R: CsvRecord>(writer: W, records: &[R]) -> Result<(), IBKRApiLibError> {
+    let mut writer = BufWriter::new(writer);
+    write_row(
+        &mut writer,
+        R::header().iter().map(|column| column.to_string()),
+    )?;
+    for record in records {
+        write_row(&mut writer, record.record().into_iter())?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+//==================================================================================================
+/// Creates (or truncates) a file and writes the records to it
+pub fn write_csv_file<P: AsRef<Path>, R: CsvRecord>(
+    path: P,
+    records: &[R],
+) -> Result<(), IBKRApiLibError> {
+    write_csv(File::create(path)?, records)
+}
+
+//==================================================================================================
+fn write_row<W: Write>(
+    writer: &mut W,
+    fields: impl Iterator<Item = String>,
+) -> Result<(), IBKRApiLibError> {
+    let row = fields
+        .map(|field| escape(&field))
+        .collect::<Vec<_>>()
+        .join(",");
+    writeln!(writer, "{}", row)?;
+    Ok(())
+}
+
+//==================================================================================================
+fn escape(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+//==================================================================================================
+fn rfc3339(seconds: Option<i64>) -> String {
+    seconds
+        .and_then(|seconds| Utc.timestamp_opt(seconds, 0).single())
+        .map(|time| time.to_rfc3339())
+        .unwrap_or_default()
+}
+
+//==================================================================================================
+impl CsvRecord for BarData {
+    fn header() -> &'static [&'static str] {
+        &[
+            "timestamp",
+            "date",
+            "open",
+            "high",
+            "low",
+            "close",
+            "volume",
+            "bar_count",
+            "average",
+        ]
+    }
+
+    fn record(&self) -> Vec<String> {
+        vec![
+            rfc3339(bar_timestamp(self)),
+            timestamp_to_wire(&self.date),
+            self.open.to_string(),
+            self.high.to_string(),
+            self.low.to_string(),
+            self.close.to_string(),
+            self.volume.to_string(),
+            self.bar_count.to_string(),
+            self.average.to_string(),
+        ]
+    }
+}
+
+impl CsvRecord for HistoricalTick {
+    fn header() -> &'static [&'static str] {
+        &["timestamp", "time", "price", "size"]
+    }
+
+    fn record(&self) -> Vec<String> {
+        vec![
+            rfc3339(timestamp_seconds(&self.time)),
+            timestamp_to_wire(&self.time),
+            self.price.to_string(),
+            self.size.to_string(),
+        ]
+    }
+}
+
+impl CsvRecord for HistoricalTickBidAsk {
+    fn header() -> &'static [&'static str] {
+        &[
+            "timestamp",
+            "time",
+            "price_bid",
+            "price_ask",
+            "size_bid",
+            "size_ask",
+            "bid_past_low",
+            "ask_past_high",
+        ]
+    }
+
+    fn record(&self) -> Vec<String> {
+        vec![
+            rfc3339(timestamp_seconds(&self.time)),
+            timestamp_to_wire(&self.time),
+            self.price_bid.to_string(),
+            self.price_ask.to_string(),
+            self.size_bid.to_string(),
+            self.size_ask.to_string(),
+            self.tick_attrib_bid_ask.bid_past_low.to_string(),
+            self.tick_attrib_bid_ask.ask_past_high.to_string(),
+        ]
+    }
+}
+
+impl CsvRecord for HistoricalTickLast {
+    fn header() -> &'static [&'static str] {
+        &[
+            "timestamp",
+            "time",
+            "price",
+            "size",
+            "exchange",
+            "special_conditions",
+            "past_limit",
+            "unreported",
+        ]
+    }
+
+    fn record(&self) -> Vec<String> {
+        vec![
+            rfc3339(timestamp_seconds(&self.time)),
+            timestamp_to_wire(&self.time),
+            self.price.to_string(),
+            self.size.to_string(),
+            self.exchange.clone(),
+            self.special_conditions.clone(),
+            self.tick_attrib_last.past_limit.to_string(),
+            self.tick_attrib_last.unreported.to_string(),
+        ]
+    }
+}
+
+impl CsvRecord for Execution {
+    fn header() -> &'static [&'static str] {
+        &[
+            "exec_id",
+            "time",
+            "acct_number",
+            "exchange",
+            "side",
+            "shares",
+            "price",
+            "perm_id",
+            "client_id",
+            "order_id",
+            "liquidation",
+            "cum_qty",
+            "avg_price",
+            "order_ref",
+            "ev_rule",
+            "ev_multiplier",
+            "model_code",
+            "last_liquidity",
+        ]
+    }
+
+    fn record(&self) -> Vec<String> {
+        vec![
+            self.exec_id.clone(),
+            timestamp_to_wire(&self.time),
+            self.acct_number.clone(),
+            self.exchange.clone(),
+            self.side.clone(),
+            self.shares.to_string(),
+            self.price.to_string(),
+            self.perm_id.to_string(),
+            self.client_id.to_string(),
+            self.order_id.to_string(),
+            self.liquidation.to_string(),
+            self.cum_qty.to_string(),
+            self.avg_price.to_string(),
+            self.order_ref.clone(),
+            self.ev_rule.clone(),
+            self.ev_multiplier.to_string(),
+            self.model_code.clone(),
+            self.last_liquidity.to_string(),
+        ]
+    }
+}
+
+//==================================================================================================
+/// A struct that can be written as one row of a Parquet file
+pub trait ParquetRecord: Sized {
+    /// Columns with their types
+    fn schema() -> Schema;
+    /// Column values of the records, in the order of schema()
+    fn columns(records: &[Self]) -> Vec<ArrayRef>;
+}
+
+//==================================================================================================
+/// Writes the records as one row group of a Parquet file
+pub fn write_parquet<W: Write + Send, R: ParquetRecord>(
+    writer: W,
+    records: &[R],
+) -> Result<(), IBKRApiLibError> {
+    let schema = Arc::new(R::schema());
+    let batch = RecordBatch::try_new(schema.clone(), R::columns(records)).map_err(parquet_error)?;
+    let mut writer = ArrowWriter::try_new(writer, schema, None).map_err(parquet_error)?;
+    writer.write(&batch).map_err(parquet_error)?;
+    writer.close().map_err(parquet_error)?;
+    Ok(())
+}
+
+//==================================================================================================
+/// Creates (or truncates) a file and writes the records to it as Parquet
+pub fn write_parquet_file<P: AsRef<Path>, R: ParquetRecord>(
+    path: P,
+    records: &[R],
+) -> Result<(), IBKRApiLibError> {
+    write_parquet(File::create(path)?, records)
+}
+
+//==================================================================================================
+fn parquet_error<E: std::error::Error + Send + Sync + 'static>(err: E) -> IBKRApiLibError {
+    IBKRApiLibError::Io(io::Error::other(err))
+}
+
+//==================================================================================================
+fn timestamp_field() -> Field {
+    Field::new(
+        "timestamp",
+        DataType::Timestamp(TimeUnit::Microsecond, Some(PARQUET_TIME_ZONE.into())),
+        true,
+    )
+}
+
+//==================================================================================================
+fn timestamp_column<'a>(times: impl Iterator<Item = &'a Timestamp>) -> ArrayRef {
+    let micros: Vec<Option<i64>> = times
+        .map(|time| timestamp_time(time).map(|time| time.timestamp_micros()))
+        .collect();
+    Arc::new(TimestampMicrosecondArray::from(micros).with_timezone(PARQUET_TIME_ZONE))
+}
+
+//==================================================================================================
+fn text_column(values: impl Iterator<Item = String>) -> ArrayRef {
+    Arc::new(StringArray::from(values.collect::<Vec<String>>()))
+}
+
+//==================================================================================================
+fn double_column(values: impl Iterator<Item = f64>) -> ArrayRef {
+    Arc::new(Float64Array::from(values.collect::<Vec<f64>>()))
+}
+
+//==================================================================================================
+/// Decimal sizes as doubles, which hold the fractional sizes TWS sends
+fn decimal_column<'a>(values: impl Iterator<Item = &'a BigDecimal>) -> ArrayRef {
+    double_column(values.map(|value| value.to_f64().unwrap_or(0.0)))
+}
+
+//==================================================================================================
+fn int_column(values: impl Iterator<Item = i32>) -> ArrayRef {
+    Arc::new(Int32Array::from(values.collect::<Vec<i32>>()))
+}
+
+//==================================================================================================
+fn bool_column(values: impl Iterator<Item = bool>) -> ArrayRef {
+    Arc::new(BooleanArray::from(values.collect::<Vec<bool>>()))
+}
+
+//==================================================================================================
+impl ParquetRecord for BarData {
+    fn schema() -> Schema {
+        Schema::new(vec![
+            timestamp_field(),
+            Field::new("date", DataType::Utf8, false),
+            Field::new("open", DataType::Float64, false),
+            Field::new("high", DataType::Float64, false),
+            Field::new("low", DataType::Float64, false),
+            Field::new("close", DataType::Float64, false),
+            Field::new("volume", DataType::Float64, false),
+            Field::new("bar_count", DataType::Int32, false),
+            Field::new("average", DataType::Float64, false),
+        ])
+    }
+
+    fn columns(records: &[Self]) -> Vec<ArrayRef> {
+        vec![
+            timestamp_column(records.iter().map(|bar| &bar.date)),
+            text_column(records.iter().map(|bar| timestamp_to_wire(&bar.date))),
+            double_column(records.iter().map(|bar| bar.open)),
+            double_column(records.iter().map(|bar| bar.high)),
+            double_column(records.iter().map(|bar| bar.low)),
+            double_column(records.iter().map(|bar| bar.close)),
+            decimal_column(records.iter().map(|bar| &bar.volume)),
+            int_column(records.iter().map(|bar| bar.bar_count)),
+            double_column(records.iter().map(|bar| bar.average)),
+        ]
+    }
+}
+
+impl ParquetRecord for HistoricalTick {
+    fn schema() -> Schema {
+        Schema::new(vec![
+            timestamp_field(),
+            Field::new("time", DataType::Utf8, false),
+            Field::new("price", DataType::Float64, false),
+            Field::new("size", DataType::Float64, false),
+        ])
+    }
+
+    fn columns(records: &[Self]) -> Vec<ArrayRef> {
+        vec![
+            timestamp_column(records.iter().map(|tick| &tick.time)),
+            text_column(records.iter().map(|tick| timestamp_to_wire(&tick.time))),
+            double_column(records.iter().map(|tick| tick.price)),
+            decimal_column(records.iter().map(|tick| &tick.size)),
+        ]
+    }
+}
+
+impl ParquetRecord for HistoricalTickBidAsk {
+    fn schema() -> Schema {
+        Schema::new(vec![
+            timestamp_field(),
+            Field::new("time", DataType::Utf8, false),
+            Field::new("price_bid", DataType::Float64, false),
+            Field::new("price_ask", DataType::Float64, false),
+            Field::new("size_bid", DataType::Float64, false),
+            Field::new("size_ask", DataType::Float64, false),
+            Field::new("bid_past_low", DataType::Boolean, false),
+            Field::new("ask_past_high", DataType::Boolean, false),
+        ])
+    }
+
+    fn columns(records: &[Self]) -> Vec<ArrayRef> {
+        vec![
+            timestamp_column(records.iter().map(|tick| &tick.time)),
+            text_column(records.iter().map(|tick| timestamp_to_wire(&tick.time))),
+            double_column(records.iter().map(|tick| tick.price_bid)),
+            double_column(records.iter().map(|tick| tick.price_ask)),
+            decimal_column(records.iter().map(|tick| &tick.size_bid)),
+            decimal_column(records.iter().map(|tick| &tick.size_ask)),
+            bool_column(
+                records
+                    .iter()
+                    .map(|tick| tick.tick_attrib_bid_ask.bid_past_low),
+            ),
+            bool_column(
+                records
+                    .iter()
+                    .map(|tick| tick.tick_attrib_bid_ask.ask_past_high),
+            ),
+        ]
+    }
+}
+
+impl ParquetRecord for HistoricalTickLast {
+    fn schema() -> Schema {
+        Schema::new(vec![
+            timestamp_field(),
+            Field::new("time", DataType::Utf8, false),
+            Field::new("price", DataType::Float64, false),
+            Field::new("size", DataType::Float64, false),
+            Field::new("exchange", DataType::Utf8, false),
+            Field::new("special_conditions", DataType::Utf8, false),
+            Field::new("past_limit", DataType::Boolean, false),
+            Field::new("unreported", DataType::Boolean, false),
+        ])
+    }
+
+    fn columns(records: &[Self]) -> Vec<ArrayRef> {
+        vec![
+            timestamp_column(records.iter().map(|tick| &tick.time)),
+            text_column(records.iter().map(|tick| timestamp_to_wire(&tick.time))),
+            double_column(records.iter().map(|tick| tick.price)),
+            decimal_column(records.iter().map(|tick| &tick.size)),
+            text_column(records.iter().map(|tick| tick.exchange.clone())),
+            text_column(records.iter().map(|tick| tick.special_conditions.clone())),
+            bool_column(records.iter().map(|tick| tick.tick_attrib_last.past_limit)),
+            bool_column(records.iter().map(|tick| tick.tick_attrib_last.unreported)),
+        ]
+    }
+}
+
+impl ParquetRecord for Execution {
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("exec_id", DataType::Utf8, false),
+            timestamp_field(),
+            Field::new("time", DataType::Utf8, false),
+            Field::new("acct_number", DataType::Utf8, false),
+            Field::new("exchange", DataType::Utf8, false),
+            Field::new("side", DataType::Utf8, false),
+            Field::new("shares", DataType::Float64, false),
+            Field::new("price", DataType::Float64, false),
+            Field::new("perm_id", DataType::Int32, false),
+            Field::new("client_id", DataType::Int32, false),
+            Field::new("order_id", DataType::Int32, false),
+            Field::new("liquidation", DataType::Int32, false),
+            Field::new("cum_qty", DataType::Float64, false),
+            Field::new("avg_price", DataType::Float64, false),
+            Field::new("order_ref", DataType::Utf8, false),
+            Field::new("ev_rule", DataType::Utf8, false),
+            Field::new("ev_multiplier", DataType::Float64, false),
+            Field::new("model_code", DataType::Utf8, false),
+            Field::new("last_liquidity", DataType::Int32, false),
+        ])
+    }
+
+    fn columns(records: &[Self]) -> Vec<ArrayRef> {
+        vec![
+            text_column(records.iter().map(|execution| execution.exec_id.clone())),
+            timestamp_column(records.iter().map(|execution| &execution.time)),
+            text_column(
+                records
+                    .iter()
+                    .map(|execution| timestamp_to_wire(&execution.time)),
+            ),
+            text_column(
+                records
+                    .iter()
+                    .map(|execution| execution.acct_number.clone()),
+            ),
+            text_column(records.iter().map(|execution| execution.exchange.clone())),
+            text_column(records.iter().map(|execution| execution.side.clone())),
+            decimal_column(records.iter().map(|execution| &execution.shares)),
+            double_column(records.iter().map(|execution| execution.price)),
+            int_column(records.iter().map(|execution| execution.perm_id)),
+            int_column(records.iter().map(|execution| execution.client_id)),
+            int_column(records.iter().map(|execution| execution.order_id)),
+            int_column(records.iter().map(|execution| execution.liquidation)),
+            decimal_column(records.iter().map(|execution| &execution.cum_qty)),
+            double_column(records.iter().map(|execution| execution.avg_price)),
+            text_column(records.iter().map(|execution| execution.order_ref.clone())),
+            text_column(records.iter().map(|execution| execution.ev_rule.clone())),
+            double_column(records.iter().map(|execution| execution.ev_multiplier)),
+            text_column(records.iter().map(|execution| execution.model_code.clone())),
+            int_column(records.iter().map(|execution| execution.last_liquidity)),
+        ]
+    }
+}
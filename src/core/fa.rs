@@ -0,0 +1,407 @@
+//! Typed Financial Advisor configuration
+//!
+//! Wrapper::receive_fa delivers, and EClient::replace_fa takes, the FA groups, allocation
+//! profiles and account aliases as XML.  FaGroups, FaProfiles and FaAliases parse that XML
+//! with from_xml and write it back with to_xml; EClient::replace_fa_config sends them.
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::common::FaDataType;
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+use crate::core::xml::{self, Element};
+
+//==================================================================================================
+/// An FA configuration document
+pub trait FaConfig: Sized {
+    /// The FaDataType to request and replace it with
+    fn fa_data_type() -> FaDataType;
+
+    //----------------------------------------------------------------------------------------------
+    /// Parses the XML received in Wrapper::receive_fa
+    fn from_xml(xml: &str) -> Result<Self, IBKRApiLibError>;
+
+    //----------------------------------------------------------------------------------------------
+    /// The XML to pass to EClient::replace_fa
+    fn to_xml(&self) -> String;
+}
+
+//==================================================================================================
+/// A group of accounts orders can be allocated to
+/// default_method - allocation method of the group, e.g. EqualQuantity, NetLiq, AvailableEquity
+///   or PctChange
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct FaGroup {
+    pub name: String,
+    pub accounts: Vec<String>,
+    pub default_method: String,
+}
+
+impl FaGroup {
+    pub fn new(name: &str, accounts: Vec<String>, default_method: &str) -> Self {
+        FaGroup {
+            name: name.to_string(),
+            accounts,
+            default_method: default_method.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for FaGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "name: {}, accounts: {:?}, default_method: {}",
+            self.name, self.accounts, self.default_method
+        )
+    }
+}
+
+//==================================================================================================
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct FaGroups {
+    pub groups: Vec<FaGroup>,
+}
+
+impl FaGroups {
+    pub fn new(groups: Vec<FaGroup>) -> Self {
+        FaGroups { groups }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn group(&self, name: &str) -> Option<&FaGroup> {
+        self.groups.iter().find(|group| group.name == name)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Adds a group, replacing one with the same name
+    pub fn set_group(&mut self, group: FaGroup) {
+        match self
+            .groups
+            .iter_mut()
+            .find(|other| other.name == group.name)
+        {
+            Some(existing) => *existing = group,
+            None => self.groups.push(group),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn remove_group(&mut self, name: &str) -> Option<FaGroup> {
+        let index = self.groups.iter().position(|group| group.name == name)?;
+        Some(self.groups.remove(index))
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Adds an account to a group.  Returns false if there is no such group or the account is
+    /// already in it.
+    pub fn add_account(&mut self, group: &str, account: &str) -> bool {
+        match self.groups.iter_mut().find(|other| other.name == group) {
+            Some(group) if !group.accounts.iter().any(|other| other == account) => {
+                group.accounts.push(account.to_string());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Removes an account from a group.  Returns false if it wasn't in the group.
+    pub fn remove_account(&mut self, group: &str, account: &str) -> bool {
+        match self.groups.iter_mut().find(|other| other.name == group) {
+            Some(group) => {
+                let before = group.accounts.len();
+                group.accounts.retain(|other| other != account);
+                group.accounts.len() < before
+            }
+            None => false,
+        }
+    }
+}
+
+impl FaConfig for FaGroups {
+    fn fa_data_type() -> FaDataType {
+        FaDataType::GROUPS
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn from_xml(xml: &str) -> Result<Self, IBKRApiLibError> {
+        let root = root(xml, "ListOfGroups")?;
+        let groups = root
+            .find_all("Group")
+            .map(|group| FaGroup {
+                name: group.child_text("name"),
+                accounts: group
+                    .find("ListOfAccts")
+                    .map(|accounts| {
+                        accounts
+                            .find_all("String")
+                            .map(|account| account.text.trim().to_string())
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                default_method: group.child_text("defaultMethod"),
+            })
+            .collect();
+        Ok(FaGroups { groups })
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn to_xml(&self) -> String {
+        let mut root = Element::new("ListOfGroups");
+        for group in self.groups.iter() {
+            let mut accounts = Element::new("ListOfAccts").attribute("varName", "list");
+            for account in group.accounts.iter() {
+                accounts = accounts.child(Element::with_text("String", account));
+            }
+            root = root.child(
+                Element::new("Group")
+                    .child(Element::with_text("name", &group.name))
+                    .child(accounts)
+                    .child(Element::with_text("defaultMethod", &group.default_method)),
+            );
+        }
+        root.to_document()
+    }
+}
+
+//==================================================================================================
+/// Share of an account in an allocation profile, its meaning depending on the profile type
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct FaAllocation {
+    pub account: String,
+    pub amount: f64,
+}
+
+impl FaAllocation {
+    pub fn new(account: &str, amount: f64) -> Self {
+        FaAllocation {
+            account: account.to_string(),
+            amount,
+        }
+    }
+}
+
+//==================================================================================================
+/// An allocation profile
+/// profile_type - 1 percentages, 2 financial ratios, 3 shares
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct FaProfile {
+    pub name: String,
+    pub profile_type: i32,
+    pub allocations: Vec<FaAllocation>,
+}
+
+impl FaProfile {
+    pub fn new(name: &str, profile_type: i32, allocations: Vec<FaAllocation>) -> Self {
+        FaProfile {
+            name: name.to_string(),
+            profile_type,
+            allocations,
+        }
+    }
+}
+
+impl fmt::Display for FaProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "name: {}, profile_type: {}, allocations: {:?}",
+            self.name, self.profile_type, self.allocations
+        )
+    }
+}
+
+//==================================================================================================
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct FaProfiles {
+    pub profiles: Vec<FaProfile>,
+}
+
+impl FaProfiles {
+    pub fn new(profiles: Vec<FaProfile>) -> Self {
+        FaProfiles { profiles }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn profile(&self, name: &str) -> Option<&FaProfile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Adds a profile, replacing one with the same name
+    pub fn set_profile(&mut self, profile: FaProfile) {
+        match self
+            .profiles
+            .iter_mut()
+            .find(|other| other.name == profile.name)
+        {
+            Some(existing) => *existing = profile,
+            None => self.profiles.push(profile),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn remove_profile(&mut self, name: &str) -> Option<FaProfile> {
+        let index = self
+            .profiles
+            .iter()
+            .position(|profile| profile.name == name)?;
+        Some(self.profiles.remove(index))
+    }
+}
+
+impl FaConfig for FaProfiles {
+    fn fa_data_type() -> FaDataType {
+        FaDataType::PROFILES
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn from_xml(xml: &str) -> Result<Self, IBKRApiLibError> {
+        let root = root(xml, "ListOfAllocationProfiles")?;
+        let mut profiles = Vec::new();
+        for profile in root.find_all("AllocationProfile") {
+            let mut allocations = Vec::new();
+            if let Some(list) = profile.find("ListOfAllocations") {
+                for allocation in list.find_all("Allocation") {
+                    allocations.push(FaAllocation {
+                        account: allocation.child_text("acct"),
+                        amount: allocation.child_text("amount").parse()?,
+                    });
+                }
+            }
+            profiles.push(FaProfile {
+                name: profile.child_text("name"),
+                profile_type: profile.child_text("type").parse()?,
+                allocations,
+            });
+        }
+        Ok(FaProfiles { profiles })
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn to_xml(&self) -> String {
+        let mut root = Element::new("ListOfAllocationProfiles");
+        for profile in self.profiles.iter() {
+            let mut allocations =
+                Element::new("ListOfAllocations").attribute("varName", "listOfAllocations");
+            for allocation in profile.allocations.iter() {
+                allocations = allocations.child(
+                    Element::new("Allocation")
+                        .child(Element::with_text("acct", &allocation.account))
+                        .child(Element::with_text("amount", &allocation.amount.to_string())),
+                );
+            }
+            root = root.child(
+                Element::new("AllocationProfile")
+                    .child(Element::with_text("name", &profile.name))
+                    .child(Element::with_text(
+                        "type",
+                        &profile.profile_type.to_string(),
+                    ))
+                    .child(allocations),
+            );
+        }
+        root.to_document()
+    }
+}
+
+//==================================================================================================
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct FaAlias {
+    pub account: String,
+    pub alias: String,
+}
+
+impl FaAlias {
+    pub fn new(account: &str, alias: &str) -> Self {
+        FaAlias {
+            account: account.to_string(),
+            alias: alias.to_string(),
+        }
+    }
+}
+
+//==================================================================================================
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct FaAliases {
+    pub aliases: Vec<FaAlias>,
+}
+
+impl FaAliases {
+    pub fn new(aliases: Vec<FaAlias>) -> Self {
+        FaAliases { aliases }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The alias of an account
+    pub fn alias(&self, account: &str) -> Option<&str> {
+        self.aliases
+            .iter()
+            .find(|alias| alias.account == account)
+            .map(|alias| alias.alias.as_str())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Sets the alias of an account
+    pub fn set_alias(&mut self, account: &str, alias: &str) {
+        match self
+            .aliases
+            .iter_mut()
+            .find(|other| other.account == account)
+        {
+            Some(existing) => existing.alias = alias.to_string(),
+            None => self.aliases.push(FaAlias::new(account, alias)),
+        }
+    }
+}
+
+impl FaConfig for FaAliases {
+    fn fa_data_type() -> FaDataType {
+        FaDataType::ALIASES
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn from_xml(xml: &str) -> Result<Self, IBKRApiLibError> {
+        let root = root(xml, "ListOfAccountAliases")?;
+        let aliases = root
+            .find_all("AccountAlias")
+            .map(|alias| FaAlias {
+                account: alias.child_text("account"),
+                alias: alias.child_text("alias"),
+            })
+            .collect();
+        Ok(FaAliases { aliases })
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn to_xml(&self) -> String {
+        let mut root = Element::new("ListOfAccountAliases");
+        for alias in self.aliases.iter() {
+            root = root.child(
+                Element::new("AccountAlias")
+                    .child(Element::with_text("account", &alias.account))
+                    .child(Element::with_text("alias", &alias.alias)),
+            );
+        }
+        root.to_document()
+    }
+}
+
+//==================================================================================================
+fn root(xml: &str, name: &str) -> Result<Element, IBKRApiLibError> {
+    let root = xml::parse(xml)?;
+    if root.name != name {
+        return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+            -1,
+            TwsError::BadMessage.code().to_string(),
+            format!(
+                "{} Expected an FA document with root {}, got {}.",
+                TwsError::BadMessage.message(),
+                name,
+                root.name
+            ),
+        )));
+    }
+    Ok(root)
+}
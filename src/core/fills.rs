@@ -0,0 +1,270 @@
+//! Executions joined with their commission reports
+//!
+//! TWS sends an execution as ExecutionData and its commission as a separate CommissionReport,
+//! the two joined only by exec_id.  FillCorrelator takes both, in either order, and delivers
+//! one Fill per execution once its report arrived, or without a commission once the report
+//! is overdue.  A report arriving after its execution was delivered without one is delivered
+//! as a CommissionUpdate, so each fill is still delivered once.  Executions TWS sends again,
+//! e.g. in answer to
+//! req_executions, are delivered only the first time, as long as they are among the last
+//! DELIVERED_CAPACITY executions delivered.
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use crate::core::common::CommissionReport;
+use crate::core::contract::Contract;
+use crate::core::execution::Execution;
+use crate::core::log_targets::ORDERS;
+
+/// How long FillCorrelator waits for a commission report by default
+pub const COMMISSION_REPORT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How many delivered executions FillCorrelator remembers, to skip executions sent again and to
+/// attach late commission reports
+pub const DELIVERED_CAPACITY: usize = 10_000;
+
+//==================================================================================================
+/// An execution with its commission
+/// commission - None if the report didn't arrive within the correlator's timeout
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Fill {
+    pub contract: Contract,
+    pub execution: Execution,
+    pub commission: Option<CommissionReport>,
+}
+
+impl Fill {
+    pub fn new(
+        contract: Contract,
+        execution: Execution,
+        commission: Option<CommissionReport>,
+    ) -> Self {
+        Fill {
+            contract,
+            execution,
+            commission,
+        }
+    }
+}
+
+impl fmt::Display for Fill {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "symbol: {}, exec_id: {}, side: {}, shares: {}, price: {}, commission: {:?}",
+            self.contract.symbol,
+            self.execution.exec_id,
+            self.execution.side,
+            self.execution.shares,
+            self.execution.price,
+            self.commission.as_ref().map(|report| report.commission)
+        )
+    }
+}
+
+//==================================================================================================
+/// The commission of a fill delivered without one, the report having arrived after the timeout
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CommissionUpdate {
+    pub exec_id: String,
+    pub commission: CommissionReport,
+}
+
+impl fmt::Display for CommissionUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "exec_id: {}, commission: {}",
+            self.exec_id, self.commission.commission
+        )
+    }
+}
+
+//==================================================================================================
+#[derive(Clone, Debug)]
+struct Waiting<T> {
+    item: T,
+    received: Instant,
+}
+
+//==================================================================================================
+/// Matches executions from Wrapper::exec_details with reports from Wrapper::commission_report
+/// timeout - how long an execution waits for its report, and a report for its execution
+#[derive(Debug)]
+pub struct FillCorrelator {
+    timeout: Duration,
+    executions: HashMap<String, Waiting<(Contract, Execution)>>,
+    reports: HashMap<String, Waiting<CommissionReport>>,
+    /// the fills delivered, true for those still without a commission
+    delivered: HashMap<String, bool>,
+    delivery_order: VecDeque<String>,
+    subscribers: Vec<Sender<Fill>>,
+    update_subscribers: Vec<Sender<CommissionUpdate>>,
+}
+
+impl FillCorrelator {
+    pub fn new(timeout: Duration) -> Self {
+        FillCorrelator {
+            timeout,
+            executions: HashMap::new(),
+            reports: HashMap::new(),
+            delivered: HashMap::new(),
+            delivery_order: VecDeque::new(),
+            subscribers: vec![],
+            update_subscribers: vec![],
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Receives every Fill delivered
+    pub fn subscribe(&mut self) -> Receiver<Fill> {
+        let (sender, receiver) = channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Receives the commission of every fill delivered without one
+    pub fn subscribe_commission_updates(&mut self) -> Receiver<CommissionUpdate> {
+        let (sender, receiver) = channel();
+        self.update_subscribers.push(sender);
+        receiver
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Number of executions waiting for their report
+    pub fn waiting(&self) -> usize {
+        self.executions.len()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Takes an execution received at now.  Returns its Fill if its report already arrived.
+    pub fn on_execution(
+        &mut self,
+        contract: Contract,
+        execution: Execution,
+        now: Instant,
+    ) -> Option<Fill> {
+        let exec_id = execution.exec_id.clone();
+        if self.delivered.contains_key(&exec_id) || self.executions.contains_key(&exec_id) {
+            return None;
+        }
+        match self.reports.remove(&exec_id) {
+            Some(report) => Some(self.deliver(Fill::new(contract, execution, Some(report.item)))),
+            None => {
+                self.executions.insert(
+                    exec_id,
+                    Waiting {
+                        item: (contract, execution),
+                        received: now,
+                    },
+                );
+                None
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Takes a commission report received at now.  Returns the Fill of its execution if that
+    /// already arrived.  The report of a fill delivered without a commission is delivered as a
+    /// CommissionUpdate instead.
+    pub fn on_commission_report(&mut self, report: CommissionReport, now: Instant) -> Option<Fill> {
+        if let Some(without_commission) = self.delivered.get_mut(&report.exec_id) {
+            // a late report completes the fill once; reports sent again are skipped
+            if std::mem::replace(without_commission, false) {
+                let update = CommissionUpdate {
+                    exec_id: report.exec_id.clone(),
+                    commission: report,
+                };
+                self.update_subscribers
+                    .retain(|subscriber| subscriber.send(update.clone()).is_ok());
+            }
+            return None;
+        }
+        match self.executions.remove(&report.exec_id) {
+            Some(waiting) => {
+                let (contract, execution) = waiting.item;
+                Some(self.deliver(Fill::new(contract, execution, Some(report))))
+            }
+            None => {
+                self.reports.insert(
+                    report.exec_id.clone(),
+                    Waiting {
+                        item: report,
+                        received: now,
+                    },
+                );
+                None
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Delivers without a commission the executions that waited longer than the timeout at
+    /// now, and drops the reports whose execution never arrived.  Call periodically, e.g.
+    /// from a timer.
+    ///
+    /// # Returns
+    /// The fills delivered, oldest first
+    pub fn expire(&mut self, now: Instant) -> Vec<Fill> {
+        let timeout = self.timeout;
+        let overdue = |received: Instant| now.saturating_duration_since(received) >= timeout;
+
+        self.reports.retain(|exec_id, waiting| {
+            if overdue(waiting.received) {
+                warn!(target: ORDERS, "No execution for commission report -- exec_id: {}", exec_id);
+                false
+            } else {
+                true
+            }
+        });
+
+        let mut expired: Vec<String> = self
+            .executions
+            .iter()
+            .filter(|(_, waiting)| overdue(waiting.received))
+            .map(|(exec_id, _)| exec_id.clone())
+            .collect();
+        expired.sort_by_key(|exec_id| self.executions[exec_id].received);
+        let mut fills = Vec::with_capacity(expired.len());
+        for exec_id in expired {
+            if let Some(waiting) = self.executions.remove(&exec_id) {
+                let (contract, execution) = waiting.item;
+                warn!(target: ORDERS, "No commission report for execution -- exec_id: {}", exec_id);
+                fills.push(self.deliver(Fill::new(contract, execution, None)));
+            }
+        }
+        fills
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn deliver(&mut self, fill: Fill) -> Fill {
+        let exec_id = fill.execution.exec_id.clone();
+        self.delivered
+            .insert(exec_id.clone(), fill.commission.is_none());
+        self.delivery_order.push_back(exec_id);
+        while self.delivery_order.len() > DELIVERED_CAPACITY {
+            if let Some(oldest) = self.delivery_order.pop_front() {
+                self.delivered.remove(&oldest);
+            }
+        }
+        self.publish(&fill);
+        fill
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn publish(&mut self, fill: &Fill) {
+        self.subscribers
+            .retain(|subscriber| subscriber.send(fill.clone()).is_ok());
+    }
+}
+
+impl Default for FillCorrelator {
+    fn default() -> Self {
+        FillCorrelator::new(COMMISSION_REPORT_TIMEOUT)
+    }
+}
@@ -0,0 +1,30 @@
+//! When derived data was last updated
+//!
+//! Quote, OrderBook, PnlSnapshot and PositionPnl record when the last message contributing to
+//! them was applied, whether or not it changed a value.  A price that hasn't moved and a feed
+//! that died look the same in the values; is_stale tells them apart for checks that need
+//! fresh data, by requiring an update within a maximum age.
+use std::time::{Duration, Instant};
+
+//==================================================================================================
+/// Data stamped with the time of its last update
+pub trait Freshness {
+    /// When the last contributing message was applied, None before the first
+    fn last_update(&self) -> Option<Instant>;
+
+    //----------------------------------------------------------------------------------------------
+    /// How long before now the last update was, None before the first
+    fn age(&self, now: Instant) -> Option<Duration> {
+        self.last_update()
+            .map(|updated| now.saturating_duration_since(updated))
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether there was no update within max_age before now.  Data never updated is stale.
+    fn is_stale(&self, max_age: Duration, now: Instant) -> bool {
+        match self.age(now) {
+            Some(age) => age > max_age,
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Watchdog detecting connections that stopped delivering messages
+//!
+//! A TCP connection can go half-open without an error: the socket stays up, nothing arrives
+//! and the reader waits forever.  HeartbeatWatchdog probes an idle connection with
+//! req_current_time and disconnects it once nothing at all has arrived for the timeout.
+use std::fmt;
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::*;
+
+use crate::core::client::{EClient, POISONED_MUTEX};
+use crate::core::errors::IBKRApiLibError;
+use crate::core::log_targets::CONNECTION;
+use crate::core::wrapper::Wrapper;
+
+//==================================================================================================
+/// interval - how long the connection may be idle before it is probed, and between probes
+/// timeout - how long the connection may be silent before it is declared lost
+#[derive(Clone, Copy, Debug)]
+pub struct HeartbeatConfig {
+    pub interval: Duration,
+    pub timeout: Duration,
+}
+
+impl HeartbeatConfig {
+    pub fn new(interval: Duration, timeout: Duration) -> Self {
+        HeartbeatConfig { interval, timeout }
+    }
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        HeartbeatConfig::new(Duration::from_secs(10), Duration::from_secs(30))
+    }
+}
+
+//==================================================================================================
+/// The watchdog disconnected a connection which was silent for silent_for
+/// probes - the req_current_time probes sent while it was silent
+#[derive(Clone, Debug)]
+pub struct ConnectionLost {
+    pub silent_for: Duration,
+    pub probes: u32,
+}
+
+impl fmt::Display for ConnectionLost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "silent_for: {:?}, probes: {}",
+            self.silent_for, self.probes
+        )
+    }
+}
+
+//==================================================================================================
+/// Probes an idle connection and declares it lost when it stays silent.  Call poll
+/// periodically, or let spawn do it on a thread.
+#[derive(Clone, Debug)]
+pub struct HeartbeatWatchdog {
+    config: HeartbeatConfig,
+    started: Instant,
+    last_probe: Option<Instant>,
+    probes: u32,
+}
+
+impl HeartbeatWatchdog {
+    pub fn new(config: HeartbeatConfig) -> Self {
+        HeartbeatWatchdog {
+            config,
+            started: Instant::now(),
+            last_probe: None,
+            probes: 0,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Checks the connection.  Sends a probe if it has been idle for the interval, and
+    /// disconnects it if it has been silent for the timeout.
+    ///
+    /// # Returns
+    /// ConnectionLost if the connection was disconnected by this call
+    pub fn poll<T: Wrapper + Send + Sync + 'static>(
+        &mut self,
+        client: &mut EClient<T>,
+        now: Instant,
+    ) -> Result<Option<ConnectionLost>, IBKRApiLibError> {
+        if !client.is_connected() {
+            return Ok(None);
+        }
+        let last_inbound = client.last_inbound().unwrap_or(self.started);
+        if matches!(self.last_probe, Some(probe) if probe < last_inbound) {
+            // the connection spoke since the last probe
+            self.last_probe = None;
+            self.probes = 0;
+        }
+        let silent_for = now.saturating_duration_since(last_inbound);
+
+        if silent_for >= self.config.timeout {
+            warn!(target: CONNECTION, "No message for {:?}, disconnecting", silent_for);
+            client.disconnect()?;
+            return Ok(Some(ConnectionLost {
+                silent_for,
+                probes: self.probes,
+            }));
+        }
+        let probe_due = match self.last_probe {
+            Some(probe) => now.saturating_duration_since(probe) >= self.config.interval,
+            None => silent_for >= self.config.interval,
+        };
+        if probe_due {
+            debug!(target: CONNECTION, "Idle for {:?}, probing", silent_for);
+            client.req_current_time()?;
+            self.last_probe = Some(now);
+            self.probes += 1;
+        }
+        Ok(None)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Polls a shared client on a thread until it disconnects.  The receiver gets a
+    /// ConnectionLost if the watchdog disconnected it.
+    pub fn spawn<T: Wrapper + Send + Sync + 'static>(
+        client: Arc<Mutex<EClient<T>>>,
+        config: HeartbeatConfig,
+    ) -> Receiver<ConnectionLost> {
+        let (tx, rx) = channel();
+        let tick = (config.interval.min(config.timeout) / 4).max(Duration::from_millis(10));
+        thread::spawn(move || {
+            let mut watchdog = HeartbeatWatchdog::new(config);
+            loop {
+                thread::sleep(tick);
+                let mut client = client.lock().expect(POISONED_MUTEX);
+                if !client.is_connected() {
+                    return;
+                }
+                match watchdog.poll(&mut client, Instant::now()) {
+                    Ok(Some(lost)) => {
+                        // nobody may be listening, the connection is closed either way
+                        let _ = tx.send(lost);
+                        return;
+                    }
+                    Ok(None) => {}
+                    Err(err) => error!(target: CONNECTION, "Heartbeat failed: {}", err),
+                }
+            }
+        });
+        rx
+    }
+}
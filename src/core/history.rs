@@ -0,0 +1,398 @@
+//! Splits long historical data requests into chunks TWS accepts and stitches the results,
+//! checks downloaded bars for holes, duplicates and unadjusted splits, and tells the final
+//! bars of a keep_up_to_date request from the revisions of the bar in progress
+use std::fmt;
+use std::time;
+
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Utc, Weekday};
+use log::*;
+use num_traits::Zero;
+
+use crate::core::common::{BarData, BarSize, Duration};
+use crate::core::log_targets::MARKET_DATA;
+use crate::core::timestamps::{parse_timestamp, timestamp_seconds, timestamp_to_wire};
+
+/// Wait between chunk requests.  TWS allows 60 historical data requests per 10 minutes.
+pub const HISTORICAL_DATA_PACING: time::Duration = time::Duration::from_secs(10);
+
+/// Error code TWS sends when a historical data query returned no bars
+pub const NO_HISTORICAL_DATA_CODE: i32 = 162;
+
+const DAY_SECONDS: i64 = 24 * 60 * 60;
+
+//==================================================================================================
+/// One req_historical_data call of a download
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryChunk {
+    /// end_date_time argument, in UTC
+    pub end_date_time: String,
+    /// duration_str argument
+    pub duration: Duration,
+}
+
+//==================================================================================================
+/// Longest duration TWS serves in one request for a bar size, with its seconds.  The seconds
+/// are at most the duration so consecutive chunks never leave a hole.
+pub fn max_chunk_duration(bar_size: &BarSize) -> (Duration, i64) {
+    match *bar_size {
+        BarSize::_1Secs => (Duration::Seconds(1800), 1800),
+        BarSize::_5Secs => (Duration::Seconds(7200), 7200),
+        BarSize::_10Secs | BarSize::_15Secs => (Duration::Seconds(14400), 14400),
+        BarSize::_30Secs => (Duration::Seconds(28800), 28800),
+        BarSize::_1Min => (Duration::Days(1), DAY_SECONDS),
+        BarSize::_2Mins => (Duration::Days(2), 2 * DAY_SECONDS),
+        BarSize::_3Mins | BarSize::_5Mins => (Duration::Weeks(1), 7 * DAY_SECONDS),
+        BarSize::_10Mins
+        | BarSize::_15Mins
+        | BarSize::_20Mins
+        | BarSize::_30Mins
+        | BarSize::_1Hour
+        | BarSize::_2Hours
+        | BarSize::_3Hours
+        | BarSize::_4Hours
+        | BarSize::_8Hours => (Duration::Months(1), 28 * DAY_SECONDS),
+        BarSize::_1Day | BarSize::_1Week | BarSize::_1Month => {
+            (Duration::Years(1), 365 * DAY_SECONDS)
+        }
+    }
+}
+
+//==================================================================================================
+/// Splits [start, end) into the chunks to request, oldest first
+pub fn plan_chunks(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    bar_size: &BarSize,
+) -> Vec<HistoryChunk> {
+    let (duration, seconds) = max_chunk_duration(bar_size);
+    let mut chunks = Vec::new();
+    let mut chunk_start = start;
+    while chunk_start < end {
+        let chunk_end = (chunk_start + chrono::Duration::seconds(seconds)).min(end);
+        chunks.push(HistoryChunk {
+            end_date_time: chunk_end.format("%Y%m%d %H:%M:%S GMT").to_string(),
+            duration,
+        });
+        chunk_start = chunk_end;
+    }
+    chunks
+}
+
+//==================================================================================================
+/// Bar start time in epoch seconds, None if TWS sent a date parse_timestamp does not read
+pub fn bar_timestamp(bar: &BarData) -> Option<i64> {
+    timestamp_seconds(&bar.date)
+}
+
+//==================================================================================================
+/// The time of a HeadTimestamp message, in either format TWS sends depending on the
+/// format_date of the request: epoch seconds, or "yyyymmdd hh:mm:ss" followed by the time zone
+/// of TWS, e.g. "US/Eastern".  Read like timestamps::parse_timestamp, which reads a time
+/// without a known time zone in local time.
+pub fn parse_head_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    parse_timestamp(timestamp)
+}
+
+//==================================================================================================
+/// Joins the bars of consecutive chunks into one list ordered by time.  Bars outside
+/// [start, end) and the duplicates where chunks overlap are dropped, keeping the bar of the
+/// later chunk.
+pub fn stitch(chunks: Vec<Vec<BarData>>, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<BarData> {
+    stitch_between(chunks, start.timestamp(), end.timestamp())
+}
+
+//==================================================================================================
+/// Like stitch, without dropping bars outside a range
+pub fn merge_bars(chunks: Vec<Vec<BarData>>) -> Vec<BarData> {
+    stitch_between(chunks, i64::MIN, i64::MAX)
+}
+
+//==================================================================================================
+fn stitch_between(chunks: Vec<Vec<BarData>>, start: i64, end: i64) -> Vec<BarData> {
+    let mut bars: Vec<(i64, BarData)> = Vec::new();
+    for bar in chunks.into_iter().flatten() {
+        match bar_timestamp(&bar) {
+            Some(time) if time >= start && time < end => bars.push((time, bar)),
+            Some(_) => (),
+            None => warn!(
+                target: MARKET_DATA,
+                "Dropping historical bar with unreadable date: {}",
+                timestamp_to_wire(&bar.date)
+            ),
+        }
+    }
+    // stable sort keeps chunk order among equal times, so dedup keeps the later chunk's bar
+    bars.sort_by_key(|(time, _)| *time);
+    bars.reverse();
+    bars.dedup_by_key(|(time, _)| *time);
+    bars.reverse();
+    bars.into_iter().map(|(_, bar)| bar).collect()
+}
+
+//==================================================================================================
+/// Length of a bar in seconds, None for weeks and months
+pub fn bar_seconds(bar_size: &BarSize) -> Option<i64> {
+    match *bar_size {
+        BarSize::_1Secs => Some(1),
+        BarSize::_5Secs => Some(5),
+        BarSize::_10Secs => Some(10),
+        BarSize::_15Secs => Some(15),
+        BarSize::_30Secs => Some(30),
+        BarSize::_1Min => Some(60),
+        BarSize::_2Mins => Some(120),
+        BarSize::_3Mins => Some(180),
+        BarSize::_5Mins => Some(300),
+        BarSize::_10Mins => Some(600),
+        BarSize::_15Mins => Some(900),
+        BarSize::_20Mins => Some(1200),
+        BarSize::_30Mins => Some(1800),
+        BarSize::_1Hour => Some(3600),
+        BarSize::_2Hours => Some(2 * 3600),
+        BarSize::_3Hours => Some(3 * 3600),
+        BarSize::_4Hours => Some(4 * 3600),
+        BarSize::_8Hours => Some(8 * 3600),
+        BarSize::_1Day => Some(DAY_SECONDS),
+        BarSize::_1Week | BarSize::_1Month => None,
+    }
+}
+
+//==================================================================================================
+/// What check_bars found wrong with a bar
+#[derive(Clone, Debug, PartialEq)]
+pub enum BarIssueKind {
+    UnreadableDate,
+    /// the bar starts before the previous one
+    OutOfOrder,
+    /// the bar starts at the same time as the previous one
+    Duplicate,
+    ZeroVolume,
+    /// bars missing within a day before this bar
+    Gap {
+        missing_bars: i64,
+    },
+    /// a weekday before this bar without any bar
+    MissingSession {
+        date: NaiveDate,
+    },
+    /// the open moved by this ratio from the previous close.  split is the split ratio the move
+    /// matches, e.g. 0.5 for a 2 for 1 split not adjusted for.
+    PriceJump {
+        ratio: f64,
+        split: Option<f64>,
+    },
+}
+
+//==================================================================================================
+/// index - position of the bar in the checked list
+/// time - bar start time in epoch seconds, if readable
+#[derive(Clone, Debug, PartialEq)]
+pub struct BarIssue {
+    pub index: usize,
+    pub time: Option<i64>,
+    pub kind: BarIssueKind,
+}
+
+impl fmt::Display for BarIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "index: {}, time: {:?}, kind: {:?}",
+            self.index, self.time, self.kind
+        )
+    }
+}
+
+//==================================================================================================
+/// check_volume - flag zero volume bars.  Off for data without volume, e.g. MIDPOINT.
+/// max_missing_bars - bars that may be missing within a day without a Gap, e.g. for thinly
+///   traded contracts
+/// max_jump - largest relative move from one close to the next open not flagged
+/// holidays - weekdays without a session
+#[derive(Clone, Debug)]
+pub struct ConsistencyConfig {
+    pub check_volume: bool,
+    pub max_missing_bars: i64,
+    pub max_jump: f64,
+    pub holidays: Vec<NaiveDate>,
+}
+
+impl Default for ConsistencyConfig {
+    fn default() -> Self {
+        ConsistencyConfig {
+            check_volume: true,
+            max_missing_bars: 0,
+            max_jump: 0.25,
+            holidays: vec![],
+        }
+    }
+}
+
+/// Split ratios PriceJump is matched against
+const SPLIT_RATIOS: [f64; 8] = [2.0, 3.0, 4.0, 5.0, 10.0, 1.5, 20.0, 8.0];
+
+/// How close a move must be to a split ratio to match it
+const SPLIT_TOLERANCE: f64 = 0.05;
+
+//==================================================================================================
+/// Checks bars, oldest first, for unreadable dates, bars out of order or duplicated, zero
+/// volume, holes within days, weekdays without bars and price jumps that look like unadjusted
+/// splits.  Days are UTC days, so sessions crossing midnight UTC show as two days.
+pub fn check_bars(
+    bars: &[BarData],
+    bar_size: &BarSize,
+    config: &ConsistencyConfig,
+) -> Vec<BarIssue> {
+    let seconds = bar_seconds(bar_size);
+    let mut issues = Vec::new();
+    let mut previous: Option<(i64, &BarData)> = None;
+    for (index, bar) in bars.iter().enumerate() {
+        let time = bar_timestamp(bar);
+        let mut issue = |kind: BarIssueKind| issues.push(BarIssue { index, time, kind });
+        let time = match time {
+            Some(time) => time,
+            None => {
+                issue(BarIssueKind::UnreadableDate);
+                continue;
+            }
+        };
+        if config.check_volume && bar.volume.is_zero() {
+            issue(BarIssueKind::ZeroVolume);
+        }
+
+        if let Some((previous_time, previous_bar)) = previous {
+            if time < previous_time {
+                issue(BarIssueKind::OutOfOrder);
+                continue;
+            }
+            if time == previous_time {
+                issue(BarIssueKind::Duplicate);
+                continue;
+            }
+
+            let day = utc_day(time);
+            let previous_day = utc_day(previous_time);
+            match seconds {
+                Some(seconds) if seconds < DAY_SECONDS && day == previous_day => {
+                    let missing_bars = (time - previous_time) / seconds - 1;
+                    if missing_bars > config.max_missing_bars {
+                        issue(BarIssueKind::Gap { missing_bars });
+                    }
+                }
+                Some(_) => {
+                    let mut date = previous_day.succ_opt();
+                    while let Some(missing) = date.filter(|date| *date < day) {
+                        let weekend = matches!(missing.weekday(), Weekday::Sat | Weekday::Sun);
+                        if !weekend && !config.holidays.contains(&missing) {
+                            issue(BarIssueKind::MissingSession { date: missing });
+                        }
+                        date = missing.succ_opt();
+                    }
+                }
+                None => {}
+            }
+
+            if previous_bar.close > 0.0 && bar.open > 0.0 {
+                let ratio = bar.open / previous_bar.close;
+                if (ratio - 1.0).abs() > config.max_jump {
+                    issue(BarIssueKind::PriceJump {
+                        ratio,
+                        split: matching_split(ratio),
+                    });
+                }
+            }
+        }
+        previous = Some((time, bar));
+    }
+    issues
+}
+
+//==================================================================================================
+fn utc_day(time: i64) -> NaiveDate {
+    Utc.timestamp_opt(time, 0)
+        .single()
+        .map(|date_time| date_time.date_naive())
+        .unwrap_or_default()
+}
+
+//==================================================================================================
+/// The split ratio, or reverse split ratio, a price ratio is within SPLIT_TOLERANCE of
+fn matching_split(ratio: f64) -> Option<f64> {
+    SPLIT_RATIOS
+        .iter()
+        .flat_map(|split| vec![1.0 / split, *split])
+        .find(|split| (ratio / split - 1.0).abs() <= SPLIT_TOLERANCE)
+}
+
+//==================================================================================================
+/// A bar of the HistoricalDataUpdate messages of a keep_up_to_date request.  TWS sends the bar
+/// in progress again whenever it changes, so only a Final bar is complete; it is final once TWS
+/// sends the first bar of the next period.
+#[derive(Clone, Debug)]
+pub enum BarUpdate {
+    /// the current bar, replacing the previous InProgress bar of the same date
+    InProgress(BarData),
+    /// the last version of a bar whose period ended
+    Final(BarData),
+}
+
+impl BarUpdate {
+    pub fn bar(&self) -> &BarData {
+        match self {
+            BarUpdate::InProgress(bar) | BarUpdate::Final(bar) => bar,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn is_final(&self) -> bool {
+        matches!(self, BarUpdate::Final(_))
+    }
+}
+
+impl fmt::Display for BarUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BarUpdate::InProgress(bar) => write!(f, "InProgress -- {}", bar),
+            BarUpdate::Final(bar) => write!(f, "Final -- {}", bar),
+        }
+    }
+}
+
+//==================================================================================================
+/// Turns the bars of Wrapper::historical_data_update of one request into BarUpdates
+#[derive(Clone, Debug, Default)]
+pub struct BarUpdateTracker {
+    current: Option<BarData>,
+}
+
+impl BarUpdateTracker {
+    pub fn new() -> Self {
+        BarUpdateTracker::default()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Takes an updated bar and returns the final previous bar if the period rolled over,
+    /// followed by the bar in progress
+    pub fn on_update(&mut self, bar: BarData) -> Vec<BarUpdate> {
+        let mut updates = vec![];
+        match self.current.take() {
+            Some(previous) if previous.date != bar.date => updates.push(BarUpdate::Final(previous)),
+            _ => (),
+        }
+        self.current = Some(bar.clone());
+        updates.push(BarUpdate::InProgress(bar));
+        updates
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The bar in progress
+    pub fn current(&self) -> Option<&BarData> {
+        self.current.as_ref()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Ends the updates, e.g. when the request is cancelled, returning the bar in progress.  Its
+    /// period did not end, so it is not final.
+    pub fn finish(&mut self) -> Option<BarData> {
+        self.current.take()
+    }
+}
@@ -0,0 +1,259 @@
+//! On-disk cache of downloaded historical bars
+//!
+//! Bars are stored per contract, bar size, whatToShow and use_rth, one file each, together
+//! with the time ranges already downloaded.  EClient::download_history_cached only requests
+//! the parts of a range missing from the cache.
+use std::fs;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+
+use crate::core::common::{BarData, BarSize, WhatToShow};
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+use crate::core::history::{bar_seconds, bar_timestamp, merge_bars};
+use crate::core::timestamps::{timestamp_from_wire, timestamp_to_wire};
+
+const RANGES_PREFIX: &str = "ranges";
+/// Longest a week or month bar may take to complete
+const LONGEST_BAR_SECONDS: i64 = 31 * 24 * 60 * 60;
+
+//==================================================================================================
+/// Identifies one cached series
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryCacheKey {
+    pub con_id: i32,
+    pub bar_size: String,
+    pub what_to_show: String,
+    pub use_rth: i32,
+}
+
+impl HistoryCacheKey {
+    pub fn new(con_id: i32, bar_size: &BarSize, what_to_show: &WhatToShow, use_rth: i32) -> Self {
+        HistoryCacheKey {
+            con_id,
+            bar_size: bar_size.to_string(),
+            what_to_show: what_to_show.to_string(),
+            use_rth,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn file_name(&self) -> String {
+        format!(
+            "{}_{}_{}_{}.bars",
+            self.con_id,
+            self.bar_size.replace(' ', ""),
+            self.what_to_show,
+            self.use_rth
+        )
+    }
+}
+
+//==================================================================================================
+/// Bars of one series and the ranges [start, end) of epoch seconds they cover
+#[derive(Clone, Debug, Default)]
+pub struct CachedSeries {
+    ranges: Vec<(i64, i64)>,
+    bars: Vec<BarData>,
+}
+
+impl CachedSeries {
+    pub fn new() -> Self {
+        CachedSeries::default()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Downloaded ranges, ordered and not overlapping
+    pub fn ranges(&self) -> &[(i64, i64)] {
+        &self.ranges
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Parts of [start, end) not covered yet, oldest first
+    pub fn missing(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<(i64, i64)> {
+        let mut missing = Vec::new();
+        let mut from = start.timestamp();
+        for &(range_start, range_end) in self.ranges.iter() {
+            if range_end <= from {
+                continue;
+            }
+            if range_start >= end.timestamp() {
+                break;
+            }
+            if range_start > from {
+                missing.push((from, range_start));
+            }
+            from = from.max(range_end);
+        }
+        if from < end.timestamp() {
+            missing.push((from, end.timestamp()));
+        }
+        missing
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Cached bars starting in [start, end), in time order
+    pub fn bars(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<BarData> {
+        self.bars
+            .iter()
+            .filter(|bar| match bar_timestamp(bar) {
+                Some(time) => time >= start.timestamp() && time < end.timestamp(),
+                None => false,
+            })
+            .cloned()
+            .collect()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Adds the bars downloaded for [start, end).  Bars already cached for the same time are
+    /// replaced.  An empty range adds the bars only.
+    pub fn insert(&mut self, start: i64, end: i64, bars: Vec<BarData>) {
+        let cached = std::mem::take(&mut self.bars);
+        self.bars = merge_bars(vec![cached, bars]);
+        if end <= start {
+            return;
+        }
+
+        self.ranges.push((start, end));
+        self.ranges.sort_unstable();
+        let mut merged: Vec<(i64, i64)> = Vec::new();
+        for (range_start, range_end) in self.ranges.drain(..) {
+            match merged.last_mut() {
+                Some(last) if range_start <= last.1 => last.1 = last.1.max(range_end),
+                _ => merged.push((range_start, range_end)),
+            }
+        }
+        self.ranges = merged;
+    }
+}
+
+//==================================================================================================
+/// The part of [start, end) that bars downloaded at now cover for good: it ends by now, at the
+/// end of the last completed bar, and before the last downloaded bar if it may still be
+/// forming.  Recording only this part has the rest downloaded again by a later request.
+pub fn completed_end(start: i64, end: i64, bars: &[BarData], bar_size: &BarSize, now: i64) -> i64 {
+    let mut completed = end.min(now);
+    if let Some(seconds) = bar_seconds(bar_size) {
+        completed = completed.min(now - now.rem_euclid(seconds));
+    }
+    if let Some(last) = bars.iter().filter_map(bar_timestamp).max() {
+        let seconds = bar_seconds(bar_size).unwrap_or(LONGEST_BAR_SECONDS);
+        if last + seconds > now {
+            completed = completed.min(last);
+        }
+    }
+    completed.max(start)
+}
+
+//==================================================================================================
+/// Directory holding cached series
+#[derive(Clone, Debug)]
+pub struct HistoryCache {
+    dir: PathBuf,
+}
+
+impl HistoryCache {
+    /// # Arguments
+    /// * dir - the cache directory.  Created when the first series is saved.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        HistoryCache {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Reads a series.  Returns an empty series if nothing is cached for the key.
+    pub fn load(&self, key: &HistoryCacheKey) -> Result<CachedSeries, IBKRApiLibError> {
+        let path = self.dir.join(key.file_name());
+        if !path.exists() {
+            return Ok(CachedSeries::new());
+        }
+        let mut series = CachedSeries::new();
+        for (i, line) in BufReader::new(fs::File::open(&path)?).lines().enumerate() {
+            let line = line?;
+            let fields: Vec<&str> = line.split(',').collect();
+            if i == 0 {
+                if fields.first() != Some(&RANGES_PREFIX) {
+                    return Err(bad_cache_file(&path));
+                }
+                for range in fields[1..].iter().filter(|range| !range.is_empty()) {
+                    let mut bounds = range.split(':');
+                    match (bounds.next(), bounds.next()) {
+                        (Some(start), Some(end)) => {
+                            series.ranges.push((start.parse()?, end.parse()?))
+                        }
+                        _ => return Err(bad_cache_file(&path)),
+                    }
+                }
+                continue;
+            }
+            if fields.len() != 8 {
+                return Err(bad_cache_file(&path));
+            }
+            series.bars.push(BarData::new(
+                timestamp_from_wire(fields[0]),
+                fields[1].parse()?,
+                fields[2].parse()?,
+                fields[3].parse()?,
+                fields[4].parse()?,
+                fields[5].parse().map_err(|_| bad_cache_file(&path))?,
+                fields[6].parse()?,
+                fields[7].parse()?,
+            ));
+        }
+        Ok(series)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Writes a series, replacing what was cached for the key
+    pub fn save(
+        &self,
+        key: &HistoryCacheKey,
+        series: &CachedSeries,
+    ) -> Result<(), IBKRApiLibError> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(key.file_name());
+        // write to a temporary file first so an interrupted save keeps the old series
+        let temp_path = path.with_extension("tmp");
+        let mut writer = BufWriter::new(fs::File::create(&temp_path)?);
+
+        let ranges: Vec<String> = series
+            .ranges
+            .iter()
+            .map(|(start, end)| format!("{}:{}", start, end))
+            .collect();
+        writeln!(writer, "{},{}", RANGES_PREFIX, ranges.join(","))?;
+        for bar in series.bars.iter() {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{}",
+                timestamp_to_wire(&bar.date),
+                bar.open,
+                bar.high,
+                bar.low,
+                bar.close,
+                bar.volume,
+                bar.bar_count,
+                bar.average
+            )?;
+        }
+        writer.flush()?;
+        drop(writer);
+        fs::rename(&temp_path, &path)?;
+        Ok(())
+    }
+}
+
+//==================================================================================================
+fn bad_cache_file(path: &Path) -> IBKRApiLibError {
+    IBKRApiLibError::ApiError(TwsApiReportableError::new(
+        -1,
+        TwsError::BadMessage.code().to_string(),
+        format!(
+            "{} History cache file {} is corrupt.",
+            TwsError::BadMessage.message(),
+            path.display()
+        ),
+    ))
+}
@@ -0,0 +1,270 @@
+//! A resolved contract with what trading it needs to know
+//!
+//! Instrument bundles the ContractDetails of a contract with its parsed trading and liquid
+//! sessions and the price increments of its market rules, so order builders, risk checks and
+//! analytics take one handle instead of the contract, its details and the rules separately:
+//! order_templates::instrument_limit rounds limit prices to its tick,
+//! PrecautionarySettings::check_instrument checks orders against its multiplier and tick, and
+//! RiskAggregator::track_instrument scales the greeks of its positions by its multiplier.
+//! Build it from the details of Wrapper::contract_details and add the rules of
+//! Wrapper::market_rule for the market_rule_ids it lists.
+//!
+//! Session times are local times of the contract's time_zone_id, as TWS reports them.
+use std::fmt;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::core::common::PriceIncrement;
+use crate::core::contract::{Contract, ContractDetails};
+use crate::core::market_rules::{self, increment_at, round_price, MarketRules, RoundDirection};
+
+//==================================================================================================
+/// A period the contract trades in, in the time zone of the contract
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TradingSession {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+}
+
+impl TradingSession {
+    pub fn new(start: NaiveDateTime, end: NaiveDateTime) -> Self {
+        TradingSession { start, end }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn contains(&self, at: NaiveDateTime) -> bool {
+        self.start <= at && at < self.end
+    }
+}
+
+impl fmt::Display for TradingSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "start: {}, end: {}",
+            self.start.format("%Y%m%d %H:%M"),
+            self.end.format("%Y%m%d %H:%M")
+        )
+    }
+}
+
+//==================================================================================================
+/// Parses the trading_hours or liquid_hours of ContractDetails, in either format TWS sends:
+/// "20090507:0700-1830,1830-2330;20090508:CLOSED" or
+/// "20180323:0400-20180323:2000;20180326:0400-20180326:2000".  Closed days and malformed
+/// periods are skipped.
+pub fn parse_trading_hours(hours: &str) -> Vec<TradingSession> {
+    let mut sessions = vec![];
+    for day in hours.split(';') {
+        let (date, periods) = match day.trim().split_once(':') {
+            Some((date, periods)) => (date, periods),
+            None => continue,
+        };
+        let date = match NaiveDate::parse_from_str(date, "%Y%m%d") {
+            Ok(date) => date,
+            Err(_) => continue,
+        };
+        for period in periods.split(',') {
+            if let Some(session) = parse_period(date, period) {
+                sessions.push(session);
+            }
+        }
+    }
+    sessions.sort_by_key(|session| session.start);
+    sessions
+}
+
+//==================================================================================================
+fn parse_period(date: NaiveDate, period: &str) -> Option<TradingSession> {
+    let (start, end) = period.trim().split_once('-')?;
+    let start = parse_time(date, start)?;
+    let mut end = parse_time(date, end)?;
+    if end <= start && !period.contains(':') {
+        // a period of the old format past midnight
+        end += Duration::days(1);
+    }
+    Some(TradingSession::new(start, end))
+}
+
+//==================================================================================================
+fn parse_time(date: NaiveDate, time: &str) -> Option<NaiveDateTime> {
+    let (date, time) = match time.split_once(':') {
+        Some((date, time)) => (NaiveDate::parse_from_str(date, "%Y%m%d").ok()?, time),
+        None => (date, time),
+    };
+    let time = NaiveTime::parse_from_str(time, "%H%M").ok()?;
+    Some(date.and_time(time))
+}
+
+//==================================================================================================
+/// A contract with its details, sessions and price rules
+#[derive(Clone, Debug)]
+pub struct Instrument {
+    details: ContractDetails,
+    trading_sessions: Vec<TradingSession>,
+    liquid_sessions: Vec<TradingSession>,
+    market_rules: MarketRules,
+}
+
+impl Instrument {
+    pub fn new(details: ContractDetails) -> Self {
+        Instrument {
+            trading_sessions: parse_trading_hours(&details.trading_hours),
+            liquid_sessions: parse_trading_hours(&details.liquid_hours),
+            details,
+            market_rules: MarketRules::new(),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Adds the price increments of one of the market rules of the contract
+    pub fn with_market_rule(
+        mut self,
+        market_rule_id: i32,
+        price_increments: Vec<PriceIncrement>,
+    ) -> Self {
+        self.set_market_rule(market_rule_id, price_increments);
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Sets the price increments of a market rule, e.g. from Wrapper::market_rule
+    pub fn set_market_rule(&mut self, market_rule_id: i32, price_increments: Vec<PriceIncrement>) {
+        self.market_rules.insert(market_rule_id, price_increments);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn contract(&self) -> &Contract {
+        &self.details.contract
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn details(&self) -> &ContractDetails {
+        &self.details
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn con_id(&self) -> i32 {
+        self.details.contract.con_id
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The multiplier of the contract, 1 for contracts without one
+    pub fn multiplier(&self) -> f64 {
+        self.details.contract.multiplier.parse().unwrap_or(1.0)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn min_tick(&self) -> f64 {
+        self.details.min_tick
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The value of a quantity at a price: quantity * price * multiplier
+    pub fn notional(&self, quantity: f64, price: f64) -> f64 {
+        quantity * price * self.multiplier()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The ids of the market rules of the contract, one per valid exchange
+    pub fn market_rule_ids(&self) -> Vec<i32> {
+        self.details
+            .market_rule_ids
+            .split(',')
+            .filter_map(|id| id.trim().parse().ok())
+            .collect()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The id of the market rule on an exchange, None if the contract does not trade there
+    pub fn market_rule_id(&self, exchange: &str) -> Option<i32> {
+        market_rules::market_rule_id(&self.details, exchange)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The price increments on an exchange, None without its market rule
+    pub fn price_increments(&self, exchange: &str) -> Option<&[PriceIncrement]> {
+        self.market_rule_id(exchange)
+            .and_then(|id| self.market_rules.get(id))
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The tick size at a price on an exchange, the min_tick without its market rule
+    pub fn tick_size(&self, exchange: &str, price: f64) -> f64 {
+        self.price_increments(exchange)
+            .and_then(|increments| increment_at(increments, price))
+            .unwrap_or(self.details.min_tick)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// A price rounded to the nearest tick on an exchange
+    pub fn round_to_tick(&self, exchange: &str, price: f64) -> f64 {
+        self.round_to_tick_toward(exchange, price, RoundDirection::Nearest)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// A price rounded to a tick on an exchange in a direction
+    pub fn round_to_tick_toward(
+        &self,
+        exchange: &str,
+        price: f64,
+        direction: RoundDirection,
+    ) -> f64 {
+        round_price(price, self.tick_size(exchange, price), direction)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn trading_sessions(&self) -> &[TradingSession] {
+        &self.trading_sessions
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn liquid_sessions(&self) -> &[TradingSession] {
+        &self.liquid_sessions
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether the contract trades at a local time of its time zone
+    pub fn is_trading(&self, at: NaiveDateTime) -> bool {
+        self.trading_sessions
+            .iter()
+            .any(|session| session.contains(at))
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether a local time is in the liquid hours of the contract
+    pub fn is_liquid(&self, at: NaiveDateTime) -> bool {
+        self.liquid_sessions
+            .iter()
+            .any(|session| session.contains(at))
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The session in progress at a local time, or the next one to start
+    pub fn next_session(&self, at: NaiveDateTime) -> Option<&TradingSession> {
+        self.trading_sessions
+            .iter()
+            .find(|session| at < session.end)
+    }
+}
+
+impl fmt::Display for Instrument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let contract = &self.details.contract;
+        write!(
+            f,
+            "con_id: {}, symbol: {}, sec_type: {}, exchange: {}, currency: {}, multiplier: {}, \
+             min_tick: {}, time_zone_id: {}, trading_sessions: {}, market_rules: {}",
+            contract.con_id,
+            contract.symbol,
+            contract.sec_type,
+            contract.exchange,
+            contract.currency,
+            self.multiplier(),
+            self.details.min_tick,
+            self.details.time_zone_id,
+            self.trading_sessions.len(),
+            self.market_rules.len()
+        )
+    }
+}
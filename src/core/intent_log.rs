@@ -0,0 +1,454 @@
+//! Persistent log of order intents, reconciled against TWS after a crash
+//!
+//! With EClient::set_intent_log, place_order, cancel_order and req_global_cancel append an
+//! OrderIntent to the log, synced to disk, before their message is sent.  What-if orders are
+//! not logged, as TWS never works them.  After a restart,
+//! read_intents returns what the previous session meant to do, and reconcile compares it with
+//! the open orders and executions TWS reports to tell which intents took effect.
+//!
+//! The log is a text file with one intent per line, its fields separated by tabs.  A last line
+//! cut short by a crash is ignored.
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{BufReader, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::core::contract::Contract;
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+use crate::core::execution::Execution;
+use crate::core::order::{Order, OrderState};
+use crate::core::order_tracker::OrderPhase;
+
+const INTENT_LOG_POISONED_MUTEX: &str = "Intent log mutex was poisoned";
+const FIELD_COUNT: usize = 12;
+
+//==================================================================================================
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum IntentKind {
+    /// A new order, or a modification of the order with the same order_id
+    Place,
+    Cancel,
+    GlobalCancel,
+}
+
+impl IntentKind {
+    fn code(&self) -> &'static str {
+        match self {
+            IntentKind::Place => "PLACE",
+            IntentKind::Cancel => "CANCEL",
+            IntentKind::GlobalCancel => "GLOBAL_CANCEL",
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "PLACE" => Some(IntentKind::Place),
+            "CANCEL" => Some(IntentKind::Cancel),
+            "GLOBAL_CANCEL" => Some(IntentKind::GlobalCancel),
+            _ => None,
+        }
+    }
+}
+
+//==================================================================================================
+/// An order-mutating request about to be sent
+/// time_micros - when it was logged, in microseconds since the epoch
+/// order_id - 0 for a global cancel
+/// The contract and order fields are only set for Place intents.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct OrderIntent {
+    pub time_micros: i64,
+    pub kind: IntentKind,
+    pub order_id: i32,
+    pub con_id: i32,
+    pub symbol: String,
+    pub sec_type: String,
+    pub action: String,
+    pub total_quantity: f64,
+    pub order_type: String,
+    pub lmt_price: f64,
+    pub aux_price: f64,
+    pub order_ref: String,
+}
+
+impl OrderIntent {
+    pub fn place(order_id: i32, contract: &Contract, order: &Order) -> Self {
+        OrderIntent {
+            time_micros: now_micros(),
+            kind: IntentKind::Place,
+            order_id,
+            con_id: contract.con_id,
+            symbol: contract.symbol.clone(),
+            sec_type: contract.sec_type.to_string(),
+            action: order.action.to_string(),
+            total_quantity: order.total_quantity,
+            order_type: order.order_type.to_string(),
+            lmt_price: order.lmt_price,
+            aux_price: order.aux_price,
+            order_ref: order.order_ref.clone(),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn cancel(order_id: i32) -> Self {
+        OrderIntent {
+            kind: IntentKind::Cancel,
+            order_id,
+            ..OrderIntent::global_cancel()
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn global_cancel() -> Self {
+        OrderIntent {
+            time_micros: now_micros(),
+            kind: IntentKind::GlobalCancel,
+            order_id: 0,
+            con_id: 0,
+            symbol: "".to_string(),
+            sec_type: "".to_string(),
+            action: "".to_string(),
+            total_quantity: 0.0,
+            order_type: "".to_string(),
+            lmt_price: 0.0,
+            aux_price: 0.0,
+            order_ref: "".to_string(),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn to_line(&self) -> String {
+        let fields = [
+            self.time_micros.to_string(),
+            self.kind.code().to_string(),
+            self.order_id.to_string(),
+            self.con_id.to_string(),
+            escape(&self.symbol),
+            escape(&self.sec_type),
+            escape(&self.action),
+            self.total_quantity.to_string(),
+            escape(&self.order_type),
+            self.lmt_price.to_string(),
+            self.aux_price.to_string(),
+            escape(&self.order_ref),
+        ];
+        fields.join("\t")
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn from_line(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != FIELD_COUNT {
+            return None;
+        }
+        Some(OrderIntent {
+            time_micros: fields[0].parse().ok()?,
+            kind: IntentKind::from_code(fields[1])?,
+            order_id: fields[2].parse().ok()?,
+            con_id: fields[3].parse().ok()?,
+            symbol: unescape(fields[4]),
+            sec_type: unescape(fields[5]),
+            action: unescape(fields[6]),
+            total_quantity: fields[7].parse().ok()?,
+            order_type: unescape(fields[8]),
+            lmt_price: fields[9].parse().ok()?,
+            aux_price: fields[10].parse().ok()?,
+            order_ref: unescape(fields[11]),
+        })
+    }
+}
+
+impl fmt::Display for OrderIntent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "kind: {:?}, order_id: {}, symbol: {}, action: {}, total_quantity: {}, order_type: {}",
+            self.kind,
+            self.order_id,
+            self.symbol,
+            self.action,
+            self.total_quantity,
+            self.order_type
+        )
+    }
+}
+
+//==================================================================================================
+/// Intent log being appended to.  Clones append to the same file.
+#[derive(Clone)]
+pub struct OrderIntentLog {
+    file: Arc<Mutex<fs::File>>,
+}
+
+impl OrderIntentLog {
+    /// Opens the log for appending, creating it if needed
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, IBKRApiLibError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(OrderIntentLog {
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Appends an intent and waits for it to reach the disk
+    pub fn record(&self, intent: &OrderIntent) -> Result<(), IBKRApiLibError> {
+        let mut file = self.file.lock().expect(INTENT_LOG_POISONED_MUTEX);
+        file.write_all(format!("{}\n", intent.to_line()).as_bytes())?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Empties the log, e.g. once its intents are reconciled
+    pub fn clear(&self) -> Result<(), IBKRApiLibError> {
+        let file = self.file.lock().expect(INTENT_LOG_POISONED_MUTEX);
+        file.set_len(0)?;
+        file.sync_data()?;
+        Ok(())
+    }
+}
+
+//==================================================================================================
+/// Reads every complete intent of a log, in the order logged.  A missing log has none.
+pub fn read_intents<P: AsRef<Path>>(path: P) -> Result<Vec<OrderIntent>, IBKRApiLibError> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let mut text = String::new();
+    BufReader::new(fs::File::open(path)?).read_to_string(&mut text)?;
+    // whatever follows the last newline was cut short by a crash
+    let complete = match text.rfind('\n') {
+        Some(end) => &text[..end],
+        None => return Ok(vec![]),
+    };
+    let mut intents = Vec::new();
+    for (index, line) in complete.split('\n').enumerate() {
+        let intent = OrderIntent::from_line(line).ok_or_else(|| {
+            IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                -1,
+                TwsError::BadMessage.code().to_string(),
+                format!(
+                    "{} Line {} of {} is not an order intent.",
+                    TwsError::BadMessage.message(),
+                    index + 1,
+                    path.display()
+                ),
+            ))
+        })?;
+        intents.push(intent);
+    }
+    Ok(intents)
+}
+
+//==================================================================================================
+/// Orders and executions TWS reported after reconnecting, from req_all_open_orders and
+/// req_executions
+#[derive(Clone, Debug, Default)]
+pub struct OrderSnapshot {
+    statuses: HashMap<i32, String>,
+    executed: HashMap<i32, f64>,
+}
+
+impl OrderSnapshot {
+    pub fn new() -> Self {
+        OrderSnapshot::default()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Records a Wrapper::open_order callback
+    pub fn on_open_order(&mut self, order_id: i32, order_state: &OrderState) {
+        self.statuses.insert(order_id, order_state.status.clone());
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Records a Wrapper::order_status callback
+    pub fn on_order_status(&mut self, order_id: i32, status: &str) {
+        self.statuses.insert(order_id, status.to_string());
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Records a Wrapper::exec_details callback
+    pub fn on_execution(&mut self, execution: &Execution) {
+        *self.executed.entry(execution.order_id).or_insert(0.0) +=
+            execution.shares.to_f64().unwrap_or(0.0);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether TWS reported the order as working
+    pub fn is_working(&self, order_id: i32) -> bool {
+        matches!(self.statuses.get(&order_id), Some(status) if !is_inactive(status))
+    }
+}
+
+//==================================================================================================
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum IntentStatus {
+    /// TWS shows the intent took effect
+    Confirmed,
+    /// TWS shows nothing either way, e.g. the request may never have been sent
+    Unknown,
+    /// TWS shows the intent did not take effect
+    Failed,
+}
+
+//==================================================================================================
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReconciledIntent {
+    pub intent: OrderIntent,
+    pub status: IntentStatus,
+    pub detail: String,
+}
+
+impl fmt::Display for ReconciledIntent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {} ({})", self.status, self.intent, self.detail)
+    }
+}
+
+//==================================================================================================
+/// The last intent logged for each order, and for global cancels, classified
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ReconciliationReport {
+    pub intents: Vec<ReconciledIntent>,
+}
+
+impl ReconciliationReport {
+    pub fn with_status(&self, status: IntentStatus) -> Vec<&ReconciledIntent> {
+        self.intents
+            .iter()
+            .filter(|reconciled| reconciled.status == status)
+            .collect()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether every intent is confirmed
+    pub fn is_clean(&self) -> bool {
+        self.intents
+            .iter()
+            .all(|reconciled| reconciled.status == IntentStatus::Confirmed)
+    }
+}
+
+//==================================================================================================
+/// Classifies the last intent for each order against what TWS reports:
+/// * Place - confirmed if the order is working, filled or executed, failed if TWS reports it
+///   cancelled or inactive without executions, unknown if TWS doesn't know the order
+/// * Cancel - failed if the order is still working, confirmed otherwise
+/// * GlobalCancel - failed if any order is still working, confirmed otherwise
+pub fn reconcile(intents: &[OrderIntent], snapshot: &OrderSnapshot) -> ReconciliationReport {
+    let mut last: Vec<&OrderIntent> = Vec::new();
+    for intent in intents.iter() {
+        last.retain(|other| {
+            other.kind == IntentKind::GlobalCancel || other.order_id != intent.order_id
+        });
+        if intent.kind == IntentKind::GlobalCancel {
+            last.retain(|other| other.kind != IntentKind::GlobalCancel);
+        }
+        last.push(intent);
+    }
+
+    let intents = last
+        .into_iter()
+        .map(|intent| {
+            let executed = snapshot
+                .executed
+                .get(&intent.order_id)
+                .copied()
+                .unwrap_or(0.0);
+            let (status, detail) = match intent.kind {
+                IntentKind::Place => match snapshot.statuses.get(&intent.order_id) {
+                    _ if executed > 0.0 => {
+                        (IntentStatus::Confirmed, format!("{} executed", executed))
+                    }
+                    Some(status) if phase_of(status) == Some(OrderPhase::Filled) => {
+                        (IntentStatus::Confirmed, status.clone())
+                    }
+                    Some(status) if is_inactive(status) => (IntentStatus::Failed, status.clone()),
+                    Some(status) => (IntentStatus::Confirmed, status.clone()),
+                    None => (IntentStatus::Unknown, "order not known".to_string()),
+                },
+                IntentKind::Cancel if snapshot.is_working(intent.order_id) => {
+                    (IntentStatus::Failed, "order still working".to_string())
+                }
+                IntentKind::Cancel => (IntentStatus::Confirmed, "order not working".to_string()),
+                IntentKind::GlobalCancel => {
+                    let working: Vec<i32> = snapshot
+                        .statuses
+                        .keys()
+                        .copied()
+                        .filter(|order_id| snapshot.is_working(*order_id))
+                        .collect();
+                    if working.is_empty() {
+                        (IntentStatus::Confirmed, "no order working".to_string())
+                    } else {
+                        (
+                            IntentStatus::Failed,
+                            format!("orders still working: {:?}", working),
+                        )
+                    }
+                }
+            };
+            ReconciledIntent {
+                intent: intent.clone(),
+                status,
+                detail,
+            }
+        })
+        .collect();
+    ReconciliationReport { intents }
+}
+
+//==================================================================================================
+fn phase_of(status: &str) -> Option<OrderPhase> {
+    OrderPhase::from_status(status, 0.0)
+}
+
+//==================================================================================================
+/// Whether an order with the status is no longer working and never will be
+fn is_inactive(status: &str) -> bool {
+    matches!(phase_of(status), Some(phase) if !phase.is_working())
+}
+
+//==================================================================================================
+fn now_micros() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_micros() as i64)
+        .unwrap_or(0)
+}
+
+//==================================================================================================
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+//==================================================================================================
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
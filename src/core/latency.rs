@@ -0,0 +1,197 @@
+//! Timestamps of the place order path and their percentile summaries
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// Orders whose latency is kept.  The oldest are forgotten first.
+pub const MAX_TRACKED_ORDERS: usize = 10000;
+
+//==================================================================================================
+/// Time spent in each stage of placing one order, measured from the place_order call
+/// encode - until the message was encoded
+/// write - until the message was written to the socket
+/// first_status - until the first order_status for the order was decoded, if it arrived yet
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct OrderLatency {
+    pub order_id: i32,
+    pub encode: Duration,
+    pub write: Duration,
+    pub first_status: Option<Duration>,
+}
+
+impl fmt::Display for OrderLatency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "order_id: {}, encode: {:?}, write: {:?}, first_status: {:?}",
+            self.order_id, self.encode, self.write, self.first_status
+        )
+    }
+}
+
+//==================================================================================================
+/// Percentiles of one stage over the tracked orders
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct LatencySummary {
+    pub count: usize,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl LatencySummary {
+    /// Summarizes a set of samples.  All fields are zero when there are none.
+    pub fn from_samples(mut samples: Vec<Duration>) -> Self {
+        if samples.is_empty() {
+            return LatencySummary::default();
+        }
+        samples.sort();
+        let percentile = |p: usize| samples[(samples.len() - 1) * p / 100];
+        LatencySummary {
+            count: samples.len(),
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+impl fmt::Display for LatencySummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "count: {}, p50: {:?}, p90: {:?}, p99: {:?}, max: {:?}",
+            self.count, self.p50, self.p90, self.p99, self.max
+        )
+    }
+}
+
+//==================================================================================================
+/// Summaries of every stage of the place order path
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct OrderLatencyStats {
+    pub encode: LatencySummary,
+    pub write: LatencySummary,
+    pub first_status: LatencySummary,
+}
+
+impl fmt::Display for OrderLatencyStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "encode: [{}], write: [{}], first_status: [{}]",
+            self.encode, self.write, self.first_status
+        )
+    }
+}
+
+//==================================================================================================
+/// Latencies of the most recently placed orders.  EClient records the submission and the
+/// Decoder the first order status.
+#[derive(Debug)]
+pub struct OrderLatencyRecorder {
+    capacity: usize,
+    orders: HashMap<i32, (Instant, OrderLatency)>,
+    placed: VecDeque<i32>,
+}
+
+impl Default for OrderLatencyRecorder {
+    fn default() -> Self {
+        OrderLatencyRecorder::new(MAX_TRACKED_ORDERS)
+    }
+}
+
+impl OrderLatencyRecorder {
+    pub fn new(capacity: usize) -> Self {
+        OrderLatencyRecorder {
+            capacity,
+            orders: HashMap::new(),
+            placed: VecDeque::new(),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Records an order written to the socket.  Placing an order id again (to modify the
+    /// order) starts a new measurement.
+    ///
+    /// # Arguments
+    /// * order_id - the order id
+    /// * started - when place_order was called
+    /// * encoded - when the message was encoded
+    /// * written - when the message was written to the socket
+    pub fn record_submission(
+        &mut self,
+        order_id: i32,
+        started: Instant,
+        encoded: Instant,
+        written: Instant,
+    ) {
+        self.record_encoded(order_id, started, encoded);
+        self.record_written(order_id, written);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Starts the measurement of an order once its message is encoded, before it is written,
+    /// so a status that arrives before record_written returns still counts
+    pub fn record_encoded(&mut self, order_id: i32, started: Instant, encoded: Instant) {
+        let latency = OrderLatency {
+            order_id,
+            encode: encoded.saturating_duration_since(started),
+            write: encoded.saturating_duration_since(started),
+            first_status: None,
+        };
+        if self.orders.insert(order_id, (started, latency)).is_some() {
+            self.placed.retain(|placed_id| *placed_id != order_id);
+        }
+        self.placed.push_back(order_id);
+        while self.placed.len() > self.capacity {
+            if let Some(oldest) = self.placed.pop_front() {
+                self.orders.remove(&oldest);
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Records when the message of an order was written to the socket
+    pub fn record_written(&mut self, order_id: i32, written: Instant) {
+        if let Some((started, latency)) = self.orders.get_mut(&order_id) {
+            latency.write = written.saturating_duration_since(*started);
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Records an order status.  Only the first status after a submission counts.
+    pub fn record_status(&mut self, order_id: i32, received: Instant) {
+        if let Some((started, latency)) = self.orders.get_mut(&order_id) {
+            if latency.first_status.is_none() {
+                latency.first_status = Some(received.saturating_duration_since(*started));
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Latency of one order, if it is still tracked
+    pub fn get(&self, order_id: i32) -> Option<OrderLatency> {
+        self.orders
+            .get(&order_id)
+            .map(|(_, latency)| latency.clone())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Percentiles over the tracked orders.  first_status covers only the orders which
+    /// received a status.
+    pub fn stats(&self) -> OrderLatencyStats {
+        let latencies: Vec<&OrderLatency> = self.orders.values().map(|(_, l)| l).collect();
+        OrderLatencyStats {
+            encode: LatencySummary::from_samples(latencies.iter().map(|l| l.encode).collect()),
+            write: LatencySummary::from_samples(latencies.iter().map(|l| l.write).collect()),
+            first_status: LatencySummary::from_samples(
+                latencies.iter().filter_map(|l| l.first_status).collect(),
+            ),
+        }
+    }
+}
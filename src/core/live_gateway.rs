@@ -0,0 +1,243 @@
+//! Smoke test against a live paper trading gateway
+//!
+//! smoke_test checks an environment end to end: it connects, looks up a contract, takes a
+//! snapshot quote and previews a one-share what-if order, then shuts the connection down
+//! whatever failed.  The what-if order is never transmitted, and is only previewed when every
+//! managed account is a paper account.
+//!
+//! The crate's own live test runs it when IBKR_TEST_HOST is set, and is skipped otherwise:
+//!
+//! ```text
+//! IBKR_TEST_HOST=127.0.0.1 IBKR_TEST_PORT=4002 cargo test test_live_gateway -- --nocapture
+//! ```
+//!
+//! Downstream crates can call live_gateway_config and smoke_test from their own tests.
+use std::env;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use log::*;
+
+use crate::core::client::EClient;
+use crate::core::common::SecType;
+use crate::core::connection::ConnectionConfig;
+use crate::core::contract::Contract;
+use crate::core::currency::Currency;
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+use crate::core::exchange::Exchange;
+use crate::core::log_targets::CONNECTION;
+use crate::core::order::{Action, Order, OrderType};
+use crate::core::quote::Quote;
+use crate::core::what_if::MarginPreview;
+use crate::core::wrapper::Wrapper;
+
+/// The host of the gateway; the live test is skipped without it
+pub const TEST_HOST_VAR: &str = "IBKR_TEST_HOST";
+/// The port of the gateway, DEFAULT_TEST_PORT if not set
+pub const TEST_PORT_VAR: &str = "IBKR_TEST_PORT";
+/// The client id to connect as, DEFAULT_TEST_CLIENT_ID if not set
+pub const TEST_CLIENT_ID_VAR: &str = "IBKR_TEST_CLIENT_ID";
+/// The symbol of the stock to look up, quote and preview, DEFAULT_TEST_SYMBOL if not set
+pub const TEST_SYMBOL_VAR: &str = "IBKR_TEST_SYMBOL";
+
+/// The port of IB Gateway for paper trading
+pub const DEFAULT_TEST_PORT: u32 = 4002;
+pub const DEFAULT_TEST_CLIENT_ID: i32 = 917;
+pub const DEFAULT_TEST_SYMBOL: &str = "SPY";
+
+/// Delayed frozen market data, so accounts without market data subscriptions get a quote
+const DELAYED_FROZEN: i32 = 4;
+
+//==================================================================================================
+/// The connection config of the environment variables, None if TEST_HOST_VAR is not set
+pub fn live_gateway_config() -> Option<ConnectionConfig> {
+    let host = env::var(TEST_HOST_VAR)
+        .ok()
+        .filter(|host| !host.is_empty())?;
+    let port = env::var(TEST_PORT_VAR)
+        .ok()
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(DEFAULT_TEST_PORT);
+    let client_id = env::var(TEST_CLIENT_ID_VAR)
+        .ok()
+        .and_then(|client_id| client_id.parse().ok())
+        .unwrap_or(DEFAULT_TEST_CLIENT_ID);
+    Some(
+        ConnectionConfig::new(&host, port, client_id)
+            .with_connect_timeout(Duration::from_secs(10))
+            .with_read_timeout(Duration::from_secs(10))
+            .with_client_id_retries(5),
+    )
+}
+
+//==================================================================================================
+/// The stock of TEST_SYMBOL_VAR at SMART in USD
+pub fn live_gateway_contract() -> Contract {
+    Contract {
+        symbol: env::var(TEST_SYMBOL_VAR).unwrap_or_else(|_| DEFAULT_TEST_SYMBOL.to_string()),
+        sec_type: SecType::STK,
+        exchange: Exchange::Smart,
+        currency: Currency::usd(),
+        ..Default::default()
+    }
+}
+
+//==================================================================================================
+/// What smoke_test found
+/// contract - the contract as the gateway resolved it
+/// quote - the snapshot quote, None if none arrived in time, e.g. outside trading hours
+/// margin_preview - the preview of the what-if order, None if the accounts are not paper
+///   accounts or no preview arrived in time
+#[derive(Clone, Debug, Default)]
+pub struct SmokeTestReport {
+    pub server_version: i32,
+    pub client_id: i32,
+    pub accounts: Vec<String>,
+    pub contract: Contract,
+    pub quote: Option<Quote>,
+    pub margin_preview: Option<MarginPreview>,
+}
+
+impl fmt::Display for SmokeTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "server_version: {}, client_id: {}, accounts: {}, con_id: {}, quote: {}, \
+             margin_preview: {}",
+            self.server_version,
+            self.client_id,
+            self.accounts.join(","),
+            self.contract.con_id,
+            self.quote.is_some(),
+            self.margin_preview.is_some()
+        )
+    }
+}
+
+//==================================================================================================
+/// Runs the smoke test on a client that is not connected, shutting the connection down
+/// before it returns
+///
+/// # Arguments
+/// * client - the client to connect
+/// * config - where to connect, e.g. from live_gateway_config
+/// * contract - a stock to look up, quote and preview
+/// * timeout - how long to wait for each answer of the gateway
+pub fn smoke_test<T>(
+    client: &mut EClient<T>,
+    config: &ConnectionConfig,
+    contract: &Contract,
+    timeout: Duration,
+) -> Result<SmokeTestReport, IBKRApiLibError>
+where
+    T: Wrapper + Send + Sync + 'static,
+{
+    client.connect_with(config)?;
+    let report = run_smoke_test(client, contract, timeout);
+    let shutdown = client.shutdown();
+    match &report {
+        Ok(report) => info!(target: CONNECTION, "Smoke test passed -- {}", report),
+        Err(err) => error!(target: CONNECTION, "Smoke test failed: {}", err),
+    }
+    let report = report?;
+    shutdown?;
+    Ok(report)
+}
+
+//==================================================================================================
+fn run_smoke_test<T>(
+    client: &mut EClient<T>,
+    contract: &Contract,
+    timeout: Duration,
+) -> Result<SmokeTestReport, IBKRApiLibError>
+where
+    T: Wrapper + Send + Sync + 'static,
+{
+    let deadline = Instant::now() + timeout;
+    let mut order_id = client.next_valid_id();
+    while order_id.is_none() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+        order_id = client.next_valid_id();
+    }
+    let order_id = order_id.ok_or_else(|| smoke_error("no next valid order id"))?;
+    let accounts: Vec<String> = client
+        .accounts()
+        .accounts()
+        .iter()
+        .map(|account| account.to_string())
+        .collect();
+
+    let mut details = client
+        .contract_details_bulk(order_id, std::slice::from_ref(contract), 1, timeout)?
+        .pop()
+        .ok_or_else(|| smoke_error("no contract details"))??;
+    if details.is_empty() {
+        return Err(smoke_error("the contract is unknown"));
+    }
+    let resolved = details.swap_remove(0).contract;
+
+    client.req_market_data_type(DELAYED_FROZEN)?;
+    let quote = client
+        .snapshot(order_id + 1, &resolved)?
+        .recv_timeout(timeout)
+        .ok();
+    if quote.is_none() {
+        warn!(target: CONNECTION, "Smoke test -- no snapshot quote of {}", resolved.symbol);
+    }
+
+    let is_paper = !accounts.is_empty() && accounts.iter().all(|account| is_paper_account(account));
+    let margin_preview = if is_paper {
+        // a limit far below the market, in case TWS ignored what_if
+        let order = Order {
+            action: Action::Buy,
+            order_type: OrderType::Lmt,
+            total_quantity: 1.0,
+            lmt_price: 1.0,
+            ..Default::default()
+        };
+        let preview = client
+            .what_if(order_id + 2, &resolved, &order)?
+            .recv_timeout(timeout)
+            .ok();
+        if preview.is_none() {
+            // leave nothing working on the account
+            client.cancel_order(order_id + 2)?;
+        }
+        preview
+    } else {
+        warn!(
+            target: CONNECTION,
+            "Smoke test -- skipping the what-if order, {} are not paper accounts",
+            accounts.join(",")
+        );
+        None
+    };
+
+    Ok(SmokeTestReport {
+        server_version: client.server_version(),
+        client_id: client.client_id(),
+        accounts,
+        contract: resolved,
+        quote,
+        margin_preview,
+    })
+}
+
+//==================================================================================================
+/// Whether an account code is that of a paper trading account, e.g. DU1234567
+pub fn is_paper_account(account: &str) -> bool {
+    account.starts_with("DU") || account.starts_with("DF")
+}
+
+//==================================================================================================
+fn smoke_error(problem: &str) -> IBKRApiLibError {
+    IBKRApiLibError::ApiError(TwsApiReportableError::new(
+        -1,
+        TwsError::ConnectFail.code().to_string(),
+        format!(
+            "{} smoke test: {}",
+            TwsError::ConnectFail.message(),
+            problem
+        ),
+    ))
+}
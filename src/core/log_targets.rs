@@ -0,0 +1,226 @@
+//! Log targets of the crate's subsystems and per-target verbosity adjustable at runtime
+//!
+//! The crate logs through the log crate with one target per subsystem.  Install the logger of
+//! your choice wrapped in TargetFilter, then raise or lower each target's level while the
+//! program runs:
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use log::LevelFilter;
+//! use twsapi::core::log_targets::{self, TargetFilter};
+//!
+//! # struct MyLogger;
+//! # impl log::Log for MyLogger {
+//! #     fn enabled(&self, _: &log::Metadata) -> bool { true }
+//! #     fn log(&self, record: &log::Record) { println!("{}", record.args()) }
+//! #     fn flush(&self) {}
+//! # }
+//! let logger = Box::new(TargetFilter::new(MyLogger, LevelFilter::Info));
+//! log::set_logger(Box::leak(logger)).unwrap();
+//! log::set_max_level(LevelFilter::Info);
+//!
+//! // deep decoding logs for five minutes
+//! let five_minutes = Duration::from_secs(300);
+//! log_targets::set_target_level_for(log_targets::DECODER, LevelFilter::Trace, five_minutes);
+//! ```
+use std::sync::RwLock;
+use std::thread;
+use std::time::Duration;
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Socket reads and message framing
+pub const READER: &str = "twsapi::reader";
+/// Message decoding and wrapper callbacks
+pub const DECODER: &str = "twsapi::decoder";
+/// Order placement, ids and order events
+pub const ORDERS: &str = "twsapi::orders";
+/// Market data, scanner and historical data requests
+pub const MARKET_DATA: &str = "twsapi::marketdata";
+/// Waits imposed by TWS pacing limits
+pub const PACING: &str = "twsapi::pacing";
+/// Connecting, handshake and disconnecting
+pub const CONNECTION: &str = "twsapi::connection";
+
+/// Every target of the crate
+pub const ALL_TARGETS: [&str; 6] = [READER, DECODER, ORDERS, MARKET_DATA, PACING, CONNECTION];
+
+const LEVELS_POISONED_LOCK: &str = "Target levels lock was poisoned";
+
+static LEVELS: RwLock<TargetLevels> = RwLock::new(TargetLevels {
+    targets: Vec::new(),
+    generation: 0,
+    max_level_before: None,
+});
+
+//==================================================================================================
+/// level - set by set_target_level
+/// temporary - set by set_target_level_for, with the generation of the call, overriding level
+/// until it expires
+struct TargetLevel {
+    target: String,
+    level: Option<LevelFilter>,
+    temporary: Option<(LevelFilter, u64)>,
+}
+
+impl TargetLevel {
+    fn effective(&self) -> Option<LevelFilter> {
+        self.temporary.map(|(level, _)| level).or(self.level)
+    }
+}
+
+//==================================================================================================
+struct TargetLevels {
+    targets: Vec<TargetLevel>,
+    /// counts the calls of set_target_level_for, to tell which one a temporary level is from
+    generation: u64,
+    /// log's global max level before the target levels raised it
+    max_level_before: Option<LevelFilter>,
+}
+
+impl TargetLevels {
+    fn entry(&mut self, target: &str) -> &mut TargetLevel {
+        match self.targets.iter().position(|entry| entry.target == target) {
+            Some(position) => &mut self.targets[position],
+            None => {
+                self.targets.push(TargetLevel {
+                    target: target.to_string(),
+                    level: None,
+                    temporary: None,
+                });
+                self.targets.last_mut().expect("an entry was just pushed")
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn raise_max_level(&mut self, level: LevelFilter) {
+        if level > log::max_level() {
+            self.max_level_before.get_or_insert(log::max_level());
+            log::set_max_level(level);
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Lowers log's global max level back as far as the remaining target levels allow
+    fn lower_max_level(&mut self) {
+        self.targets
+            .retain(|entry| entry.level.is_some() || entry.temporary.is_some());
+        let before = match self.max_level_before {
+            Some(before) => before,
+            None => return,
+        };
+        let needed = self
+            .targets
+            .iter()
+            .filter_map(TargetLevel::effective)
+            .fold(before, Ord::max);
+        if needed < log::max_level() {
+            log::set_max_level(needed);
+        }
+        if needed <= before {
+            self.max_level_before = None;
+        }
+    }
+}
+
+//==================================================================================================
+/// Sets the level of a target, overriding the TargetFilter default and any level
+/// set_target_level_for set for a while.  The level also applies to targets below it, e.g.
+/// "twsapi" covers every target of the crate.  Raises log's global max level if it would hide
+/// the records.
+pub fn set_target_level(target: &str, level: LevelFilter) {
+    let mut levels = LEVELS.write().expect(LEVELS_POISONED_LOCK);
+    let entry = levels.entry(target);
+    entry.level = Some(level);
+    entry.temporary = None;
+    levels.raise_max_level(level);
+    levels.lower_max_level();
+}
+
+//==================================================================================================
+/// Removes the level set for a target, returning it to the TargetFilter default
+pub fn clear_target_level(target: &str) {
+    let mut levels = LEVELS.write().expect(LEVELS_POISONED_LOCK);
+    levels.targets.retain(|entry| entry.target != target);
+    levels.lower_max_level();
+}
+
+//==================================================================================================
+/// Sets the level of a target for a while, then restores the level set by set_target_level,
+/// and log's global max level with it.  A later call for the same target replaces the level
+/// and its duration; set_target_level and clear_target_level end it early.
+pub fn set_target_level_for(target: &str, level: LevelFilter, duration: Duration) {
+    let generation = {
+        let mut levels = LEVELS.write().expect(LEVELS_POISONED_LOCK);
+        levels.generation += 1;
+        let generation = levels.generation;
+        levels.entry(target).temporary = Some((level, generation));
+        levels.raise_max_level(level);
+        generation
+    };
+
+    let target = target.to_string();
+    thread::spawn(move || {
+        thread::sleep(duration);
+        let mut levels = LEVELS.write().expect(LEVELS_POISONED_LOCK);
+        if let Some(entry) = levels
+            .targets
+            .iter_mut()
+            .find(|entry| entry.target == target)
+        {
+            // unless a later call or set_target_level replaced the level
+            if entry.temporary.map(|(_, set_by)| set_by) == Some(generation) {
+                entry.temporary = None;
+                levels.lower_max_level();
+            }
+        }
+    });
+}
+
+//==================================================================================================
+/// Level that applies to a target: the level set for the longest matching target, if any
+pub fn target_level(target: &str) -> Option<LevelFilter> {
+    LEVELS
+        .read()
+        .expect(LEVELS_POISONED_LOCK)
+        .targets
+        .iter()
+        .filter(|entry| {
+            let name = entry.target.as_str();
+            target == name || (target.starts_with(name) && target[name.len()..].starts_with("::"))
+        })
+        .max_by_key(|entry| entry.target.len())
+        .and_then(TargetLevel::effective)
+}
+
+//==================================================================================================
+/// Logger wrapping another logger.  Passes on the records enabled by the target levels, or by
+/// the default level for targets without one.
+pub struct TargetFilter<L: Log> {
+    inner: L,
+    default: LevelFilter,
+}
+
+impl<L: Log> TargetFilter<L> {
+    pub fn new(inner: L, default: LevelFilter) -> Self {
+        TargetFilter { inner, default }
+    }
+}
+
+impl<L: Log> Log for TargetFilter<L> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let level = target_level(metadata.target()).unwrap_or(self.default);
+        metadata.level() <= level && self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
@@ -0,0 +1,166 @@
+//! Price increments of market rules, and rounding prices to valid ticks
+//!
+//! The tick size of a contract may depend on its price and on the exchange: ContractDetails
+//! lists one market rule id per valid exchange, and EClient::req_market_rule returns the
+//! PriceIncrement table of a rule.  The Decoder caches every table it receives, so
+//! EClient::round_to_tick can round a limit price to a tick the exchange accepts instead of
+//! having the order rejected.  Without the rule the min_tick of the details is used.
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::common::PriceIncrement;
+use crate::core::contract::ContractDetails;
+use crate::core::order::Action;
+
+//==================================================================================================
+/// Which tick a price between two ticks is rounded to
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum RoundDirection {
+    #[default]
+    Nearest,
+    Up,
+    Down,
+}
+
+impl RoundDirection {
+    /// The direction keeping a limit price from paying more than asked: down for buys, up for
+    /// sells
+    pub fn for_limit(action: &Action) -> Self {
+        if action.is_sell() {
+            RoundDirection::Up
+        } else {
+            RoundDirection::Down
+        }
+    }
+}
+
+//==================================================================================================
+/// The increment of a rule at a price: that of the highest low edge not above it
+pub fn increment_at(increments: &[PriceIncrement], price: f64) -> Option<f64> {
+    increments
+        .iter()
+        .filter(|increment| increment.low_edge <= price.abs())
+        .max_by(|a, b| {
+            a.low_edge
+                .partial_cmp(&b.low_edge)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .or_else(|| increments.first())
+        .map(|increment| increment.increment)
+}
+
+//==================================================================================================
+/// Rounds a price to a multiple of tick.  A price already on a tick is left as it is.
+pub fn round_price(price: f64, tick: f64, direction: RoundDirection) -> f64 {
+    if tick <= 0.0 || !price.is_finite() {
+        return price;
+    }
+    // tolerate the float error of a price already on a tick, e.g. 0.1 + 0.2
+    let ticks = price / tick;
+    let ticks = match direction {
+        RoundDirection::Nearest => ticks.round(),
+        RoundDirection::Up => (ticks - 1e-9).ceil(),
+        RoundDirection::Down => (ticks + 1e-9).floor(),
+    };
+    let rounded = ticks * tick;
+    // drop the float noise of the multiplication, e.g. 0.30000000000000004
+    let decimals = (0..10)
+        .find(|decimals| {
+            let scaled = tick * 10f64.powi(*decimals);
+            (scaled - scaled.round()).abs() < 1e-9
+        })
+        .unwrap_or(10);
+    format!("{:.*}", decimals as usize, rounded)
+        .parse()
+        .unwrap_or(rounded)
+}
+
+//==================================================================================================
+/// The market rule of a contract on an exchange, None if the contract does not trade there
+pub fn market_rule_id(details: &ContractDetails, exchange: &str) -> Option<i32> {
+    let index = details
+        .valid_exchanges
+        .split(',')
+        .position(|valid| valid.trim() == exchange)?;
+    details
+        .market_rule_ids
+        .split(',')
+        .nth(index)
+        .and_then(|id| id.trim().parse().ok())
+}
+
+//==================================================================================================
+/// The price increments of the market rules received, keyed by rule id
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MarketRules {
+    rules: HashMap<i32, Vec<PriceIncrement>>,
+}
+
+impl MarketRules {
+    pub fn new() -> Self {
+        MarketRules::default()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Records the increments of a rule, e.g. from Wrapper::market_rule
+    pub fn insert(&mut self, market_rule_id: i32, mut price_increments: Vec<PriceIncrement>) {
+        price_increments.sort_by(|a, b| {
+            a.low_edge
+                .partial_cmp(&b.low_edge)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        self.rules.insert(market_rule_id, price_increments);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn get(&self, market_rule_id: i32) -> Option<&[PriceIncrement]> {
+        self.rules
+            .get(&market_rule_id)
+            .map(|increments| increments.as_slice())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The rule ids of a contract not received yet, to request with EClient::req_market_rule
+    pub fn missing(&self, details: &ContractDetails) -> Vec<i32> {
+        let mut missing: Vec<i32> = details
+            .market_rule_ids
+            .split(',')
+            .filter_map(|id| id.trim().parse().ok())
+            .filter(|id| !self.rules.contains_key(id))
+            .collect();
+        missing.sort_unstable();
+        missing.dedup();
+        missing
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The tick size of a contract at a price on the exchange of its contract
+    pub fn tick_size(&self, details: &ContractDetails, price: f64) -> f64 {
+        market_rule_id(details, details.contract.exchange.code())
+            .and_then(|id| self.get(id))
+            .and_then(|increments| increment_at(increments, price))
+            .unwrap_or(details.min_tick)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// A price rounded to a valid tick of a contract on the exchange of its contract
+    pub fn round_to_tick(
+        &self,
+        details: &ContractDetails,
+        price: f64,
+        direction: RoundDirection,
+    ) -> f64 {
+        round_price(price, self.tick_size(details, price), direction)
+    }
+}
@@ -0,0 +1,400 @@
+//! The queue between the reader and the Decoder
+//!
+//! The reader queues each message it reads for the Decoder thread.  Unbounded, a wrapper
+//! slower than the feed, e.g. during a burst of ticks, lets the queue grow without limit.
+//! With a capacity, QueuePolicy decides what a full queue does to the reader:
+//!
+//! * Block - the reader waits for the Decoder, and so stops reading the socket; TWS buffers
+//!   the messages meanwhile and may disconnect a client that stays behind
+//! * DropOldestMarketData - the oldest queued tick that a newer tick of the same request and
+//!   tick type replaces is dropped for the new message; without such a tick queued the reader
+//!   waits as with Block.  Depth, bars and tick-by-tick data are never dropped, as each of
+//!   them counts.
+//! * Error - the Decoder reports a QueueOverflow error once it reaches the end of the queue,
+//!   and the connection closes
+//!
+//! QueueStats tells how deep the queue is and was, and how often it was full.
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::mpsc::{RecvError, SendError};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::errors::MessageError;
+use crate::core::messages::{InboundMessage, IncomingMessageIds};
+
+const QUEUE_POISONED_MUTEX: &str = "Message queue mutex was poisoned";
+
+/// Capacity of a bounded queue unless configured otherwise
+pub const DEFAULT_QUEUE_CAPACITY: usize = 10_000;
+
+/// Ids of the messages QueuePolicy::DropOldestMarketData may drop: ticks whose value a newer
+/// tick of the same request and tick type replaces
+pub const MARKET_DATA_MESSAGES: [i32; 6] = [
+    IncomingMessageIds::TickPrice as i32,
+    IncomingMessageIds::TickSize as i32,
+    IncomingMessageIds::TickOptionComputation as i32,
+    IncomingMessageIds::TickGeneric as i32,
+    IncomingMessageIds::TickString as i32,
+    IncomingMessageIds::TickEfp as i32,
+];
+
+//==================================================================================================
+/// Whether a message is market data a full queue may drop
+pub fn is_market_data_message(msg: &str) -> bool {
+    let msg_id = msg.split('\0').next().and_then(|id| id.parse::<i32>().ok());
+    msg_id.is_some_and(|msg_id| MARKET_DATA_MESSAGES.contains(&msg_id))
+}
+
+//==================================================================================================
+/// The fields telling which value a tick updates: the message id, the request and the tick
+/// type, with the version or the attributes TWS sends among them.  None for other messages.
+fn tick_key(msg: &str) -> Option<&str> {
+    if !is_market_data_message(msg) {
+        return None;
+    }
+    let end = msg.match_indices('\0').nth(3)?.0;
+    Some(&msg[..end])
+}
+
+//==================================================================================================
+/// What queueing a message does when the queue is full
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum QueuePolicy {
+    /// The queue is never full
+    #[default]
+    Unbounded,
+    Block,
+    DropOldestMarketData,
+    Error,
+}
+
+//==================================================================================================
+/// capacity - how many messages the queue holds, unused by QueuePolicy::Unbounded
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct QueueConfig {
+    pub capacity: usize,
+    pub policy: QueuePolicy,
+}
+
+impl QueueConfig {
+    pub fn new(capacity: usize, policy: QueuePolicy) -> Self {
+        QueueConfig { capacity, policy }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn is_full(&self, depth: usize) -> bool {
+        self.policy != QueuePolicy::Unbounded && depth >= self.capacity.max(1)
+    }
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        QueueConfig::new(DEFAULT_QUEUE_CAPACITY, QueuePolicy::Unbounded)
+    }
+}
+
+//==================================================================================================
+/// depth - messages queued now
+/// max_depth - the most messages queued at once
+/// full - how many messages found the queue full
+/// dropped - replaced ticks dropped by QueuePolicy::DropOldestMarketData
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct QueueStats {
+    pub depth: usize,
+    pub max_depth: usize,
+    pub full: u64,
+    pub dropped: u64,
+}
+
+impl fmt::Display for QueueStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "depth: {}, max_depth: {}, full: {}, dropped: {}",
+            self.depth, self.max_depth, self.full, self.dropped
+        )
+    }
+}
+
+//==================================================================================================
+struct State {
+    messages: VecDeque<InboundMessage>,
+    /// how many queued ticks there are of each tick_key, kept for DropOldestMarketData only
+    tick_counts: HashMap<String, usize>,
+    /// how many keys of tick_counts have more than one tick queued
+    replaceable_keys: usize,
+    senders: usize,
+    receiving: bool,
+    overflowed: bool,
+    stats: QueueStats,
+}
+
+impl State {
+    fn push_back(&mut self, msg: InboundMessage, policy: QueuePolicy) {
+        if policy == QueuePolicy::DropOldestMarketData {
+            if let Some(key) = queued_tick_key(&msg) {
+                let count = self.tick_counts.entry(key.to_string()).or_insert(0);
+                *count += 1;
+                if *count == 2 {
+                    self.replaceable_keys += 1;
+                }
+            }
+        }
+        self.messages.push_back(msg);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn pop_front(&mut self) -> Option<InboundMessage> {
+        let msg = self.messages.pop_front()?;
+        self.forget(&msg);
+        Some(msg)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn remove(&mut self, position: usize) {
+        if let Some(msg) = self.messages.remove(position) {
+            self.forget(&msg);
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn forget(&mut self, msg: &InboundMessage) {
+        let key = match queued_tick_key(msg) {
+            Some(key) => key,
+            None => return,
+        };
+        if let Some(count) = self.tick_counts.get_mut(key) {
+            *count -= 1;
+            if *count == 1 {
+                self.replaceable_keys -= 1;
+            }
+            if *count == 0 {
+                self.tick_counts.remove(key);
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The position of the oldest queued tick that a later queued message or msg replaces, found
+    /// in one pass, and without one when no queued tick is replaced
+    fn replaced_tick(&self, msg: &InboundMessage) -> Option<usize> {
+        let incoming = queued_tick_key(msg);
+        let incoming_queued = incoming.is_some_and(|key| self.tick_counts.contains_key(key));
+        if self.replaceable_keys == 0 && !incoming_queued {
+            return None;
+        }
+        self.messages
+            .iter()
+            .position(|queued| match queued_tick_key(queued) {
+                Some(key) => Some(key) == incoming || self.tick_counts.get(key) > Some(&1),
+                None => false,
+            })
+    }
+}
+
+struct Shared {
+    config: QueueConfig,
+    state: Mutex<State>,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().expect(QUEUE_POISONED_MUTEX)
+    }
+}
+
+//==================================================================================================
+/// A queue with one sending and one receiving end, like std::sync::mpsc::channel
+pub fn message_queue(config: QueueConfig) -> (QueueSender, QueueReceiver) {
+    let shared = Arc::new(Shared {
+        config,
+        state: Mutex::new(State {
+            messages: VecDeque::new(),
+            tick_counts: HashMap::new(),
+            replaceable_keys: 0,
+            senders: 1,
+            receiving: true,
+            overflowed: false,
+            stats: QueueStats::default(),
+        }),
+        not_empty: Condvar::new(),
+        not_full: Condvar::new(),
+    });
+    (
+        QueueSender {
+            shared: shared.clone(),
+        },
+        QueueReceiver { shared },
+    )
+}
+
+fn queued_tick_key(message: &InboundMessage) -> Option<&str> {
+    message.as_ref().ok().and_then(|text| tick_key(text))
+}
+
+//==================================================================================================
+/// The sending end of a message queue.  The receiver is disconnected once every clone is
+/// dropped.
+pub struct QueueSender {
+    shared: Arc<Shared>,
+}
+
+impl QueueSender {
+    /// Queues a message, applying the queue's policy if it is full
+    ///
+    /// # Returns
+    /// An error with the message if the receiver was dropped
+    pub fn send(&self, msg: InboundMessage) -> Result<(), SendError<InboundMessage>> {
+        let config = self.shared.config;
+        let mut state = self.shared.lock();
+        let mut counted = false;
+        loop {
+            if !state.receiving {
+                return Err(SendError(msg));
+            }
+            if state.overflowed {
+                // the Decoder closes the connection at the overflow error
+                return Ok(());
+            }
+            if !config.is_full(state.messages.len()) {
+                break;
+            }
+            if !counted {
+                state.stats.full += 1;
+                counted = true;
+            }
+            if config.policy == QueuePolicy::Error {
+                state.overflowed = true;
+                state.push_back(
+                    Err(MessageError::QueueOverflow {
+                        capacity: config.capacity,
+                    }),
+                    config.policy,
+                );
+                self.shared.not_empty.notify_one();
+                return Ok(());
+            }
+            if config.policy == QueuePolicy::DropOldestMarketData {
+                let oldest = state.replaced_tick(&msg);
+                if let Some(oldest) = oldest {
+                    state.remove(oldest);
+                    state.stats.dropped += 1;
+                    continue;
+                }
+            }
+            state = self
+                .shared
+                .not_full
+                .wait(state)
+                .expect(QUEUE_POISONED_MUTEX);
+        }
+        state.push_back(msg, config.policy);
+        state.stats.depth = state.messages.len();
+        state.stats.max_depth = state.stats.max_depth.max(state.stats.depth);
+        self.shared.not_empty.notify_one();
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether the queue overflowed under QueuePolicy::Error.  Later messages are discarded.
+    pub fn has_overflowed(&self) -> bool {
+        self.shared.lock().overflowed
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// A handle to the statistics of the queue, not keeping the receiver connected
+    pub fn monitor(&self) -> QueueMonitor {
+        QueueMonitor {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl Clone for QueueSender {
+    fn clone(&self) -> Self {
+        self.shared.lock().senders += 1;
+        QueueSender {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl Drop for QueueSender {
+    fn drop(&mut self) {
+        self.shared.lock().senders -= 1;
+        self.shared.not_empty.notify_all();
+    }
+}
+
+//==================================================================================================
+/// The receiving end of a message queue
+pub struct QueueReceiver {
+    shared: Arc<Shared>,
+}
+
+impl QueueReceiver {
+    /// Waits for the next message
+    ///
+    /// # Returns
+    /// An error once the queue is empty and every sender was dropped
+    pub fn recv(&self) -> Result<InboundMessage, RecvError> {
+        let mut state = self.shared.lock();
+        loop {
+            if let Some(msg) = state.pop_front() {
+                state.stats.depth = state.messages.len();
+                self.shared.not_full.notify_one();
+                return Ok(msg);
+            }
+            if state.senders == 0 {
+                return Err(RecvError);
+            }
+            state = self
+                .shared
+                .not_empty
+                .wait(state)
+                .expect(QUEUE_POISONED_MUTEX);
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn monitor(&self) -> QueueMonitor {
+        QueueMonitor {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl Drop for QueueReceiver {
+    fn drop(&mut self) {
+        self.shared.lock().receiving = false;
+        self.shared.not_full.notify_all();
+    }
+}
+
+//==================================================================================================
+/// Reads the statistics of a message queue
+#[derive(Clone)]
+pub struct QueueMonitor {
+    shared: Arc<Shared>,
+}
+
+impl QueueMonitor {
+    pub fn stats(&self) -> QueueStats {
+        self.shared.lock().stats
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn config(&self) -> QueueConfig {
+        self.shared.config
+    }
+}
+
+impl fmt::Debug for QueueMonitor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "QueueMonitor {{ {} }}", self.stats())
+    }
+}
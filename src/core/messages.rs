@@ -1,19 +1,18 @@
 //! Functions for processing messages
 use std::any::Any;
-use std::convert::TryInto;
-use std::io::Write;
 use std::string::String;
 
 use std::vec::Vec;
 
-use ascii;
-use ascii::AsAsciiStr;
-
 use log::*;
 use num_derive::FromPrimitive;
 
-use crate::core::common::{UNSET_DOUBLE, UNSET_INTEGER};
-use crate::core::errors::IBKRApiLibError;
+use crate::core::common::{HedgeType, SecType, MAX_MSG_LEN, UNSET_DOUBLE, UNSET_INTEGER};
+use crate::core::currency::Currency;
+use crate::core::errors::{IBKRApiLibError, MessageError};
+use crate::core::exchange::Exchange;
+use crate::core::log_targets::READER;
+use crate::core::order::{Action, OrderType, Tif};
 
 //==================================================================================================
 trait EClientMsgSink {
@@ -31,7 +30,7 @@ pub enum FAMessageDataTypes {
 
 //==================================================================================================
 /// incoming msg id's
-#[derive(FromPrimitive)]
+#[derive(FromPrimitive, Debug)]
 #[repr(i32)]
 pub enum IncomingMessageIds {
     TickPrice = 1,
@@ -203,41 +202,68 @@ pub enum OutgoingMessageIds {
 }
 
 //==================================================================================================
+/// Frames a message: its length in bytes, then its UTF-8 bytes
 pub fn make_message(msg: &str) -> Result<Vec<u8>, IBKRApiLibError> {
-    //let mut buffer = ByteBuffer::new();
-    let mut buffer: Vec<u8> = Vec::new();
-
+    if msg.len() as i64 > MAX_MSG_LEN {
+        return Err(MessageError::InvalidLength(msg.len().min(i32::MAX as usize) as i32).into());
+    }
+    let mut buffer: Vec<u8> = Vec::with_capacity(4 + msg.len());
     buffer.extend_from_slice(&i32::to_be_bytes(msg.len() as i32));
-
-    buffer.write(msg.as_ascii_str().unwrap().as_bytes())?;
-    let tmp = buffer.clone();
+    buffer.extend_from_slice(msg.as_bytes());
     //debug!("Message after create: {:?}", buffer);
-
-    let (_size, _msg, _buf) = read_msg(tmp.as_slice())?;
-    //debug!("Message read: size:{}, msg:{}, bytes: {:?}", size, msg, buf);
-
-    Ok(tmp)
+    Ok(buffer)
 }
 
 //==================================================================================================
-pub fn read_msg<'a>(buf: &[u8]) -> Result<(usize, String, Vec<u8>), IBKRApiLibError> {
+/// A message the reader passes to the Decoder, or the error reading its frame
+pub type InboundMessage = Result<String, MessageError>;
+
+//==================================================================================================
+/// Reads the first frame of buf
+///
+/// # Returns
+/// The payload size, the payload, empty if the frame is not complete yet, and the bytes after
+/// the frame.  An error for a frame with an invalid length or a payload that isn't UTF-8; after
+/// the latter the frame can be skipped as its size is known.
+pub fn read_msg(buf: &[u8]) -> Result<(usize, String, Vec<u8>), MessageError> {
     // first the size prefix and then the corresponding msg payload ""
 
     if buf.len() < 4 {
-        debug!("read_msg:  buffer too small!! {:?}", buf.len());
-        return Ok((0, String::new(), buf.to_vec()));
+        debug!(target: READER, "read_msg:  buffer too small!! {:?}", buf.len());
+    }
+    match parse_frame(buf)? {
+        (size, Some(text)) => Ok((size, text, buf[4 + size..].to_vec())),
+        (size, None) => Ok((size, String::new(), buf.to_vec())),
+    }
+}
+
+//==================================================================================================
+/// Parses the first frame of buf without copying the bytes after it, for readers keeping
+/// their own buffer
+///
+/// # Returns
+/// The payload size, 0 while buf is shorter than the size prefix, and the payload, None if the
+/// frame is not complete yet.  The same errors as read_msg.
+pub fn parse_frame(buf: &[u8]) -> Result<(usize, Option<String>), MessageError> {
+    if buf.len() < 4 {
+        return Ok((0, None));
     }
 
-    let size = i32::from_be_bytes(buf[0..4].try_into().unwrap()) as usize;
-    //debug!("read_msg: Message size: {:?}", size);
+    let size = i32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]);
+    if size < 0 || size as i64 > MAX_MSG_LEN {
+        return Err(MessageError::InvalidLength(size));
+    }
+    let size = size as usize;
 
-    if buf.len() - 4 >= size {
-        let text = String::from_utf8(buf[4..4 + size].to_vec()).unwrap();
-        //debug!("read_msg: text in read message: {:?}", text);
-        Ok((size, text, buf[4 + size..].to_vec()))
-    } else {
-        Ok((size, String::new(), buf.to_vec()))
+    if buf.len() - 4 < size {
+        return Ok((size, None));
     }
+    let text =
+        String::from_utf8(buf[4..4 + size].to_vec()).map_err(|err| MessageError::InvalidUtf8 {
+            size,
+            valid_up_to: err.utf8_error().valid_up_to(),
+        })?;
+    Ok((size, Some(text)))
 }
 
 //==================================================================================================
@@ -281,6 +307,20 @@ pub fn make_field(val: &dyn Any) -> Result<String, IBKRApiLibError> {
         field = format!("{}\0", stringval);
     } else if let Some(stringval) = val.downcast_ref::<&str>() {
         field = format!("{}\0", stringval);
+    } else if let Some(exchange) = val.downcast_ref::<Exchange>() {
+        field = format!("{}\0", exchange.code());
+    } else if let Some(currency) = val.downcast_ref::<Currency>() {
+        field = format!("{}\0", currency.code());
+    } else if let Some(sec_type) = val.downcast_ref::<SecType>() {
+        field = format!("{}\0", sec_type.code());
+    } else if let Some(order_type) = val.downcast_ref::<OrderType>() {
+        field = format!("{}\0", order_type.code());
+    } else if let Some(tif) = val.downcast_ref::<Tif>() {
+        field = format!("{}\0", tif.code());
+    } else if let Some(action) = val.downcast_ref::<Action>() {
+        field = format!("{}\0", action.code());
+    } else if let Some(hedge_type) = val.downcast_ref::<HedgeType>() {
+        field = format!("{}\0", hedge_type.code());
     }
 
     Ok(field)
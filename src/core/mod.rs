@@ -1,18 +1,74 @@
 //! Core structs, enums, and functions
 pub mod account_summary_tags;
+pub mod account_values;
+pub mod accounts;
+pub mod aggregation;
 pub mod algo_params;
+pub mod capture;
 pub mod client;
+pub mod combo_fills;
 pub mod common;
+pub mod confirmation;
+pub mod connection;
 pub mod contract;
+pub mod contract_cache;
+pub mod currency;
+pub mod coverage;
 pub mod decoder;
+pub mod display_groups;
 pub mod errors;
+pub mod exchange;
 pub mod execution;
+pub mod exercise;
+pub mod exits;
+#[cfg(feature = "export")]
+pub mod export;
+pub mod fa;
+pub mod fills;
+pub mod freshness;
+pub mod heartbeat;
+pub mod history;
+pub mod history_cache;
+pub mod instrument;
+pub mod intent_log;
+pub mod latency;
+pub mod live_gateway;
+pub mod log_targets;
+pub mod market_rules;
+pub mod message_queue;
 pub mod messages;
+pub mod news_bulletins;
+pub mod option_chains;
 pub mod order;
+pub mod order_book;
 pub mod order_condition;
 pub mod order_decoder;
+pub mod order_flow;
+pub mod order_templates;
+pub mod order_tracker;
+pub(crate) mod pending;
+pub mod pnl;
+pub mod pool;
+pub mod portfolio;
+pub mod precautions;
+pub mod quote;
 pub mod reader;
+pub mod request_context;
+pub mod risk;
+pub mod roll_calendar;
 pub mod scanner;
+pub mod serde_unset;
 pub mod server_versions;
+pub mod smart_components;
+pub mod strategy;
 pub mod streamer;
+pub mod subscriptions;
+pub mod testing;
+pub mod tick_by_tick;
+pub mod tick_timeline;
+pub mod time_zones;
+pub mod timers;
+pub mod timestamps;
+pub mod what_if;
 pub mod wrapper;
+pub(crate) mod xml;
@@ -0,0 +1,130 @@
+//! Typed news bulletins of req_news_bulletins
+//!
+//! TWS sends bulletins about the trading of IB and the exchanges, e.g. an exchange halting or
+//! resuming trading.  The decoder turns each into a Bulletin for Wrapper::update_news_bulletin,
+//! and EClient::subscribe_news_bulletins returns a NewsBulletins handle receiving them until it
+//! is cancelled.
+use std::fmt;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, TryIter};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::client::EClient;
+use crate::core::errors::IBKRApiLibError;
+use crate::core::exchange::Exchange;
+use crate::core::wrapper::Wrapper;
+
+//==================================================================================================
+/// What a bulletin is about
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BulletinType {
+    /// a regular news bulletin
+    Regular,
+    /// the exchange of the bulletin is no longer available for trading
+    ExchangeUnavailable,
+    /// the exchange of the bulletin is available for trading again
+    ExchangeAvailable,
+    /// a type this library does not know
+    Other(i32),
+}
+
+impl BulletinType {
+    /// The type of a msg_type code of TWS
+    pub fn from_code(code: i32) -> Self {
+        match code {
+            1 => BulletinType::Regular,
+            2 => BulletinType::ExchangeUnavailable,
+            3 => BulletinType::ExchangeAvailable,
+            code => BulletinType::Other(code),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The msg_type code TWS knows the type by
+    pub fn code(&self) -> i32 {
+        match self {
+            BulletinType::Regular => 1,
+            BulletinType::ExchangeUnavailable => 2,
+            BulletinType::ExchangeAvailable => 3,
+            BulletinType::Other(code) => *code,
+        }
+    }
+}
+
+impl fmt::Display for BulletinType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BulletinType::Other(code) => write!(f, "Other({})", code),
+            known => write!(f, "{:?}", known),
+        }
+    }
+}
+
+//==================================================================================================
+/// A news bulletin
+/// exchange - the exchange the bulletin comes from, Exchange::None for IB's own
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Bulletin {
+    pub id: i32,
+    pub kind: BulletinType,
+    pub message: String,
+    pub exchange: Exchange,
+}
+
+impl Bulletin {
+    pub fn new(id: i32, kind: BulletinType, message: &str, exchange: &str) -> Self {
+        Bulletin {
+            id,
+            kind,
+            message: message.to_string(),
+            exchange: Exchange::from_code(exchange),
+        }
+    }
+}
+
+impl fmt::Display for Bulletin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "id: {}, kind: {}, message: {}, exchange: {}",
+            self.id, self.kind, self.message, self.exchange
+        )
+    }
+}
+
+//==================================================================================================
+/// The bulletins of EClient::subscribe_news_bulletins.  The receiver ends once the subscription
+/// is cancelled, with cancel or EClient::cancel_news_bulletins.
+#[derive(Debug)]
+pub struct NewsBulletins {
+    receiver: Receiver<Bulletin>,
+}
+
+impl NewsBulletins {
+    pub(crate) fn new(receiver: Receiver<Bulletin>) -> Self {
+        NewsBulletins { receiver }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Waits for the next bulletin
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Bulletin, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The bulletins received so far, without waiting
+    pub fn try_iter(&self) -> TryIter<'_, Bulletin> {
+        self.receiver.try_iter()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Stops the bulletins.  TWS has one bulletin subscription per client, so this ends every
+    /// NewsBulletins of the client.
+    pub fn cancel<T>(self, client: &mut EClient<T>) -> Result<(), IBKRApiLibError>
+    where
+        T: Wrapper + Send + Sync + 'static,
+    {
+        client.cancel_news_bulletins()
+    }
+}
@@ -0,0 +1,106 @@
+//! Option chains of req_sec_def_opt_params and a cache of them
+//!
+//! The expiries and strikes of the options on an underlying change a few times a day at most,
+//! when new series are listed, but each request for them counts against the pacing limits of
+//! TWS.  OptionChainCache keeps the chains of each underlying, keyed by its con_id, for a time
+//! to live; EClient::option_chains_cached only requests the chains of an underlying missing
+//! from the cache or older than that.
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+//==================================================================================================
+/// The options on an underlying trading on one exchange under one trading class, as TWS sends
+/// them to Wrapper::security_definition_option_parameter.  Expirations and strikes are sorted.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct OptionChain {
+    pub exchange: String,
+    pub underlying_con_id: i32,
+    pub trading_class: String,
+    pub multiplier: String,
+    /// "yyyymmdd" dates
+    pub expirations: Vec<String>,
+    pub strikes: Vec<f64>,
+}
+
+impl fmt::Display for OptionChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "exchange: {}, underlying_con_id: {}, trading_class: {}, multiplier: {}, \
+             expirations: {}, strikes: {}",
+            self.exchange,
+            self.underlying_con_id,
+            self.trading_class,
+            self.multiplier,
+            self.expirations.len(),
+            self.strikes.len()
+        )
+    }
+}
+
+//==================================================================================================
+#[derive(Clone, Debug)]
+struct CachedChains {
+    chains: Vec<OptionChain>,
+    fetched: Instant,
+}
+
+//==================================================================================================
+/// The option chains of underlyings keyed by their con_id, with when each was fetched
+/// ttl - how long chains are served before they are requested again
+#[derive(Debug)]
+pub struct OptionChainCache {
+    ttl: Duration,
+    entries: HashMap<i32, CachedChains>,
+}
+
+impl OptionChainCache {
+    pub fn new(ttl: Duration) -> Self {
+        OptionChainCache {
+            ttl,
+            entries: HashMap::new(),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The chains of an underlying on every exchange, if cached and younger than ttl at now
+    pub fn get(&self, underlying_con_id: i32, now: Instant) -> Option<&[OptionChain]> {
+        self.entries
+            .get(&underlying_con_id)
+            .filter(|entry| now.saturating_duration_since(entry.fetched) < self.ttl)
+            .map(|entry| entry.chains.as_slice())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Stores the chains of an underlying fetched at now, replacing those cached
+    pub fn insert(&mut self, underlying_con_id: i32, chains: Vec<OptionChain>, now: Instant) {
+        self.entries.insert(
+            underlying_con_id,
+            CachedChains {
+                chains,
+                fetched: now,
+            },
+        );
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Drops the chains of an underlying, e.g. after a corporate action changed its options
+    pub fn remove(&mut self, underlying_con_id: i32) -> Option<Vec<OptionChain>> {
+        self.entries
+            .remove(&underlying_con_id)
+            .map(|entry| entry.chains)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -5,10 +5,14 @@ use num_derive::FromPrimitive;
 
 use serde::{Deserialize, Serialize};
 
-use crate::core::common::{TagValue, UNSET_DOUBLE, UNSET_INTEGER};
+use crate::core::common::{HedgeType, SecType, TagValue, UNSET_DOUBLE, UNSET_INTEGER};
+use crate::core::contract::Contract;
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+use crate::core::exchange::Exchange;
 use crate::core::order::AuctionStrategy::AuctionUnset;
 use crate::core::order::Origin::Customer;
 use crate::core::order_condition::{Condition, OrderConditionEnum};
+use crate::core::timestamps::{is_set, Timestamp};
 
 //==================================================================================================
 #[repr(i32)]
@@ -43,21 +47,387 @@ impl Default for AuctionStrategy {
 }
 
 //==================================================================================================
-#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+/// The order_type of an order.  Types TWS sends that are not listed are kept as Other.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(from = "String", into = "String")]
+pub enum OrderType {
+    /// no order type given
+    #[default]
+    None,
+    Mkt,
+    Lmt,
+    Stp,
+    StpLmt,
+    StpPrt,
+    Trail,
+    TrailLimit,
+    TrailLit,
+    TrailMit,
+    Mit,
+    Lit,
+    Moc,
+    Loc,
+    Mtl,
+    MktPrt,
+    PegMid,
+    PegMkt,
+    PegBest,
+    PegStk,
+    PegBench,
+    Rel,
+    RelLmt,
+    RelMkt,
+    LmtMkt,
+    PassvRel,
+    SnapMid,
+    SnapMkt,
+    SnapPrim,
+    MidPrice,
+    BoxTop,
+    Vol,
+    Vwap,
+    Other(String),
+}
+
+/// The order types by their code, with the code ContractDetails::order_types lists them by
+/// where it is known
+const ORDER_TYPES: &[(&str, Option<&str>, OrderType)] = &[
+    ("MKT", Some("MKT"), OrderType::Mkt),
+    ("LMT", Some("LMT"), OrderType::Lmt),
+    ("STP", Some("STP"), OrderType::Stp),
+    ("STP LMT", Some("STPLMT"), OrderType::StpLmt),
+    ("STP PRT", None, OrderType::StpPrt),
+    ("TRAIL", Some("TRAIL"), OrderType::Trail),
+    ("TRAIL LIMIT", Some("TRAILLMT"), OrderType::TrailLimit),
+    ("TRAIL LIT", Some("TRAILLIT"), OrderType::TrailLit),
+    ("TRAIL MIT", Some("TRAILMIT"), OrderType::TrailMit),
+    ("MIT", Some("MIT"), OrderType::Mit),
+    ("LIT", Some("LIT"), OrderType::Lit),
+    ("MOC", Some("MOC"), OrderType::Moc),
+    ("LOC", Some("LOC"), OrderType::Loc),
+    ("MTL", Some("MTL"), OrderType::Mtl),
+    ("MKT PRT", None, OrderType::MktPrt),
+    ("PEG MID", Some("PEGMID"), OrderType::PegMid),
+    ("PEG MKT", Some("PEGMKT"), OrderType::PegMkt),
+    ("PEG BEST", Some("PEGBEST"), OrderType::PegBest),
+    ("PEG STK", Some("PEGSTK"), OrderType::PegStk),
+    ("PEG BENCH", Some("PEGBENCH"), OrderType::PegBench),
+    ("REL", Some("REL"), OrderType::Rel),
+    ("REL + LMT", None, OrderType::RelLmt),
+    ("REL + MKT", None, OrderType::RelMkt),
+    ("LMT + MKT", None, OrderType::LmtMkt),
+    ("PASSV REL", None, OrderType::PassvRel),
+    ("SNAP MID", Some("SNAPMID"), OrderType::SnapMid),
+    ("SNAP MKT", Some("SNAPMKT"), OrderType::SnapMkt),
+    ("SNAP PRIM", None, OrderType::SnapPrim),
+    ("MIDPRICE", Some("MIDPX"), OrderType::MidPrice),
+    ("BOX TOP", None, OrderType::BoxTop),
+    ("VOL", Some("VOL"), OrderType::Vol),
+    ("VWAP", Some("VWAP"), OrderType::Vwap),
+];
+
+impl OrderType {
+    /// The order type of a code, e.g. "STP LMT".  Known codes are matched ignoring case.
+    pub fn from_code(code: &str) -> OrderType {
+        let code = code.trim();
+        if code.is_empty() {
+            return OrderType::None;
+        }
+        ORDER_TYPES
+            .iter()
+            .find(|(known, _, _)| known.eq_ignore_ascii_case(code))
+            .map(|(_, _, order_type)| order_type.clone())
+            .unwrap_or_else(|| OrderType::Other(code.to_string()))
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The code TWS knows the order type by, empty for None
+    pub fn code(&self) -> &str {
+        match self {
+            OrderType::None => "",
+            OrderType::Other(code) => code,
+            known => ORDER_TYPES
+                .iter()
+                .find(|(_, _, order_type)| order_type == known)
+                .map(|(code, _, _)| *code)
+                .unwrap_or_default(),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The code ContractDetails::order_types lists the order type by, e.g. "STPLMT", None if
+    /// not known
+    pub fn capability(&self) -> Option<&'static str> {
+        ORDER_TYPES
+            .iter()
+            .find(|(_, _, order_type)| order_type == self)
+            .and_then(|(_, capability, _)| *capability)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether a contract with the order_types of its ContractDetails accepts the order type.
+    /// True when order_types is empty or the code the order type is listed by is not known.
+    pub fn is_supported_by(&self, order_types: &str) -> bool {
+        match self.capability() {
+            Some(capability) if !order_types.trim().is_empty() => order_types
+                .split(',')
+                .any(|listed| listed.trim() == capability),
+            _ => true,
+        }
+    }
+}
+
+impl Display for OrderType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl From<&str> for OrderType {
+    fn from(code: &str) -> Self {
+        OrderType::from_code(code)
+    }
+}
+
+impl From<String> for OrderType {
+    fn from(code: String) -> Self {
+        OrderType::from_code(&code)
+    }
+}
+
+impl From<OrderType> for String {
+    fn from(order_type: OrderType) -> Self {
+        order_type.code().to_string()
+    }
+}
+
+impl PartialEq<&str> for OrderType {
+    fn eq(&self, code: &&str) -> bool {
+        self.code() == *code
+    }
+}
+
+impl PartialEq<String> for OrderType {
+    fn eq(&self, code: &String) -> bool {
+        self.code() == code
+    }
+}
+
+//==================================================================================================
+/// The side of an order or combo leg.  Values TWS sends that are not listed are kept as Other.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(from = "String", into = "String")]
+pub enum Action {
+    /// no action given
+    #[default]
+    None,
+    Buy,
+    Sell,
+    /// sell short
+    SShort,
+    /// sell long, for the allocations of FA accounts
+    SLong,
+    Other(String),
+}
+
+impl Action {
+    /// The action of a code, e.g. "BUY".  Known codes are matched ignoring case.
+    pub fn from_code(code: &str) -> Action {
+        match code.trim().to_ascii_uppercase().as_str() {
+            "" => Action::None,
+            "BUY" => Action::Buy,
+            "SELL" => Action::Sell,
+            "SSHORT" => Action::SShort,
+            "SLONG" => Action::SLong,
+            _ => Action::Other(code.trim().to_string()),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The code TWS knows the action by, empty for None
+    pub fn code(&self) -> &str {
+        match self {
+            Action::None => "",
+            Action::Buy => "BUY",
+            Action::Sell => "SELL",
+            Action::SShort => "SSHORT",
+            Action::SLong => "SLONG",
+            Action::Other(code) => code,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn is_buy(&self) -> bool {
+        *self == Action::Buy
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether the action is SELL, SSHORT or SLONG
+    pub fn is_sell(&self) -> bool {
+        matches!(self, Action::Sell | Action::SShort | Action::SLong)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The action closing a position this action opened: SELL for BUY and BUY for the sells.
+    /// None and Other are kept as they are.
+    pub fn opposite(&self) -> Action {
+        match self {
+            Action::Buy => Action::Sell,
+            Action::Sell | Action::SShort | Action::SLong => Action::Buy,
+            other => other.clone(),
+        }
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl From<&str> for Action {
+    fn from(code: &str) -> Self {
+        Action::from_code(code)
+    }
+}
+
+impl From<String> for Action {
+    fn from(code: String) -> Self {
+        Action::from_code(&code)
+    }
+}
+
+impl From<Action> for String {
+    fn from(action: Action) -> Self {
+        action.code().to_string()
+    }
+}
+
+impl PartialEq<&str> for Action {
+    fn eq(&self, code: &&str) -> bool {
+        self.code() == *code
+    }
+}
+
+//==================================================================================================
+/// The time in force of an order.  Values TWS sends that are not listed are kept as Other.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(from = "String", into = "String")]
+pub enum Tif {
+    /// no time in force given, which TWS takes as DAY
+    #[default]
+    None,
+    Day,
+    /// good till canceled
+    Gtc,
+    /// immediate or cancel
+    Ioc,
+    /// good till date, until the good_till_date of the order
+    Gtd,
+    /// at the opening auction
+    Opg,
+    /// fill or kill
+    Fok,
+    /// day till canceled
+    Dtc,
+    /// at the pre-market opening price auction
+    Auc,
+    Other(String),
+}
+
+impl Tif {
+    /// The time in force of a code, e.g. "GTC".  Known codes are matched ignoring case.
+    pub fn from_code(code: &str) -> Tif {
+        match code.trim().to_ascii_uppercase().as_str() {
+            "" => Tif::None,
+            "DAY" => Tif::Day,
+            "GTC" => Tif::Gtc,
+            "IOC" => Tif::Ioc,
+            "GTD" => Tif::Gtd,
+            "OPG" => Tif::Opg,
+            "FOK" => Tif::Fok,
+            "DTC" => Tif::Dtc,
+            "AUC" => Tif::Auc,
+            _ => Tif::Other(code.trim().to_string()),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The code TWS knows the time in force by, empty for None
+    pub fn code(&self) -> &str {
+        match self {
+            Tif::None => "",
+            Tif::Day => "DAY",
+            Tif::Gtc => "GTC",
+            Tif::Ioc => "IOC",
+            Tif::Gtd => "GTD",
+            Tif::Opg => "OPG",
+            Tif::Fok => "FOK",
+            Tif::Dtc => "DTC",
+            Tif::Auc => "AUC",
+            Tif::Other(code) => code,
+        }
+    }
+}
+
+impl Display for Tif {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl From<&str> for Tif {
+    fn from(code: &str) -> Self {
+        Tif::from_code(code)
+    }
+}
+
+impl From<String> for Tif {
+    fn from(code: String) -> Self {
+        Tif::from_code(&code)
+    }
+}
+
+impl From<Tif> for String {
+    fn from(tif: Tif) -> Self {
+        tif.code().to_string()
+    }
+}
+
+impl PartialEq<&str> for Tif {
+    fn eq(&self, code: &&str) -> bool {
+        self.code() == *code
+    }
+}
+
+//==================================================================================================
+/// A soft dollar tier of Wrapper::soft_dollar_tiers, which an order pays its commissions by
+/// when set as its soft_dollar_tier
+/// name - the name TWS identifies the tier by
+/// value - the value TWS identifies the tier by
+/// display_name - the name to show
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
 pub struct SoftDollarTier {
     pub name: String,
-    pub val: String,
+    #[serde(alias = "val")]
+    pub value: String,
     pub display_name: String,
 }
 
 impl SoftDollarTier {
-    pub fn new(name: String, val: String, display_name: String) -> Self {
+    pub fn new(name: String, value: String, display_name: String) -> Self {
         SoftDollarTier {
             name,
-            val,
+            value,
             display_name,
         }
     }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether the tier names one, which needs MIN_SERVER_VER_SOFT_DOLLAR_TIER to be sent
+    pub fn is_set(&self) -> bool {
+        !self.name.is_empty() || !self.value.is_empty()
+    }
 }
 
 impl Display for SoftDollarTier {
@@ -65,7 +435,7 @@ impl Display for SoftDollarTier {
         write!(
             f,
             "name: {}, value: {}, display_name: {}",
-            self.name, self.val, self.display_name
+            self.name, self.value, self.display_name
         )
     }
 }
@@ -83,8 +453,29 @@ pub struct OrderState {
     pub init_margin_after: String,
     pub maint_margin_after: String,
     pub equity_with_loan_after: String,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub commission: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub min_commission: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub max_commission: f64,
     pub commission_currency: String,
     pub warning_text: String,
@@ -216,14 +607,28 @@ pub struct Order {
     pub perm_id: i32,
 
     // main order fields
-    pub action: String,
+    pub action: Action,
     pub total_quantity: f64,
-    pub order_type: String,
+    pub order_type: OrderType,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub lmt_price: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub aux_price: f64,
 
     // extended order fields
-    pub tif: String,
+    pub tif: Tif,
     // "Time in Force" - DAY, GTC, etc.
     pub active_start_time: String,
     // for GTC orders
@@ -245,20 +650,50 @@ pub struct Order {
     // 0=Default, 1=Double_Bid_Ask, 2=Last, 3=Double_Last, 4=Bid_Ask, 7=Last_or_Bid_Ask, 8=Mid-point
     pub outside_rth: bool,
     pub hidden: bool,
-    pub good_after_time: String,
-    // Format: 20060505 08:00:00 {time zone}
-    pub good_till_date: String,
-    // Format: 20060505 08:00:00 {time zone}
+    #[serde(with = "crate::core::timestamps::serde_timestamp")]
+    pub good_after_time: Timestamp,
+    // sent in UTC, see timestamps::timestamp_to_wire
+    #[serde(with = "crate::core::timestamps::serde_timestamp")]
+    pub good_till_date: Timestamp,
+    // sent in UTC, see timestamps::timestamp_to_wire
     pub rule80a: String,
     // Individual = 'I', Agency = 'A', AgentOtherMember = 'W', IndividualPTIA = 'J', AgencyPTIA = 'U', AgentOtherMemberPTIA = 'M', IndividualPT = 'K', AgencyPT = 'Y', AgentOtherMemberPT = 'N'
     pub all_or_none: bool,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_integer::unset",
+            with = "crate::core::serde_unset::unset_integer"
+        )
+    )]
     pub min_qty: i32,
     //type: int
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub percent_offset: f64,
     // type: float; REL orders only
     pub override_percentage_constraints: bool,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub trail_stop_price: f64,
     // type: float
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub trailing_percent: f64, // type: float; TRAILLIMIT orders only
 
     // financial advisors only
@@ -282,6 +717,13 @@ pub struct Order {
     pub discretionary_amt: f64,
     pub e_trade_only: bool,
     pub firm_quote_only: bool,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub nbbo_price_cap: f64,
     // type: float
     pub opt_out_smart_routing: bool,
@@ -289,26 +731,83 @@ pub struct Order {
     // BOX exchange orders only
     pub auction_strategy: AuctionStrategy,
     // type: int; AuctionMatch, AuctionImprovement, AuctionTransparent
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub starting_price: f64,
     // type: float
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub stock_ref_price: f64,
     // type: float
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub delta: f64, // type: float
 
     // pegged to stock and VOL orders only
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub stock_range_lower: f64,
     // type: float
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub stock_range_upper: f64, // type: float
 
     pub randomize_price: bool,
     pub randomize_size: bool,
 
     // VOLATILITY ORDERS ONLY
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub volatility: f64,
     // type: float
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_integer::unset",
+            with = "crate::core::serde_unset::unset_integer"
+        )
+    )]
     pub volatility_type: i32,
     // type: int   // 1=daily, 2=annual
-    pub delta_neutral_order_type: String,
+    /// the type of the order hedging the delta of a VOL order as it fills, None for no hedge
+    pub delta_neutral_order_type: OrderType,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub delta_neutral_aux_price: f64,
     // type: float
     pub delta_neutral_con_id: i32,
@@ -320,36 +819,113 @@ pub struct Order {
     pub delta_neutral_short_sale_slot: i32,
     pub delta_neutral_designated_location: String,
     pub continuous_update: bool,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_integer::unset",
+            with = "crate::core::serde_unset::unset_integer"
+        )
+    )]
     pub reference_price_type: i32, // type: int; 1=Average, 2 = BidOrAsk
 
     // COMBO ORDERS ONLY
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub basis_points: f64,
     // type: float; EFP orders only
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_integer::unset",
+            with = "crate::core::serde_unset::unset_integer"
+        )
+    )]
     pub basis_points_type: i32, // type: int;  EFP orders only
 
     // SCALE ORDERS ONLY
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_integer::unset",
+            with = "crate::core::serde_unset::unset_integer"
+        )
+    )]
     pub scale_init_level_size: i32,
     // type: int
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_integer::unset",
+            with = "crate::core::serde_unset::unset_integer"
+        )
+    )]
     pub scale_subs_level_size: i32,
     // type: int
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub scale_price_increment: f64,
     // type: float
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub scale_price_adjust_value: f64,
     // type: float
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_integer::unset",
+            with = "crate::core::serde_unset::unset_integer"
+        )
+    )]
     pub scale_price_adjust_interval: i32,
     // type: int
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub scale_profit_offset: f64,
     // type: float
     pub scale_auto_reset: bool,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_integer::unset",
+            with = "crate::core::serde_unset::unset_integer"
+        )
+    )]
     pub scale_init_position: i32,
     // type: int
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_integer::unset",
+            with = "crate::core::serde_unset::unset_integer"
+        )
+    )]
     pub scale_init_fill_qty: i32,
     // type: int
     pub scale_random_percent: bool,
     pub scale_table: String,
 
     // HEDGE ORDERS
-    pub hedge_type: String,
+    pub hedge_type: HedgeType,
     // 'D' - delta, 'B' - beta, 'F' - FX, 'P' - pair
     pub hedge_param: String, // 'beta=X' value for beta hedge, 'ratio=Y' for pair hedge
 
@@ -391,13 +967,51 @@ pub struct Order {
     pub is_pegged_change_amount_decrease: bool,
     pub reference_change_amount: f64,
     pub reference_exchange_id: String,
-    pub adjusted_order_type: String,
-
+    // adjusted (attached) stops: once trigger_price trades, TWS turns the parent of this
+    // attached order into an order of adjusted_order_type with the adjusted prices
+    pub adjusted_order_type: OrderType,
+
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub trigger_price: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub adjusted_stop_price: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub adjusted_stop_limit_price: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub adjusted_trailing_amount: f64,
+    // TRAILING_UNIT_AMOUNT or TRAILING_UNIT_PERCENT
     pub adjustable_trailing_unit: i32,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub lmt_price_offset: f64,
 
     pub conditions: Vec<OrderConditionEnum>,
@@ -409,11 +1023,24 @@ pub struct Order {
     pub ext_operator: String,
 
     // native cash quantity
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub cash_qty: f64,
 
+    // MiFID II short codes, required of EU regulated accounts.  TWS does not send them back in
+    // open orders, so orders from open_order and completed_order have them empty.
+    /// the "investment decision within the firm" person, if not mifid2decision_algo
     pub mifid2decision_maker: String,
+    /// the "investment decision within the firm" algo, if not mifid2decision_maker
     pub mifid2decision_algo: String,
+    /// the person responsible for the execution, if not mifid2execution_algo
     pub mifid2execution_trader: String,
+    /// the algo responsible for the execution, if not mifid2execution_trader
     pub mifid2execution_algo: String,
 
     pub dont_use_auto_price_for_hedge: bool,
@@ -423,6 +1050,13 @@ pub struct Order {
     pub discretionary_up_to_limit_price: bool,
 
     pub auto_cancel_date: String,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub filled_quantity: f64,
     pub ref_futures_con_id: i32,
     pub auto_cancel_parent: bool,
@@ -432,7 +1066,21 @@ pub struct Order {
     pub parent_perm_id: i32,
 
     pub use_price_mgmt_algo: bool,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_integer::unset",
+            with = "crate::core::serde_unset::unset_integer"
+        )
+    )]
     pub duration: i32,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_integer::unset",
+            with = "crate::core::serde_unset::unset_integer"
+        )
+    )]
     pub post_to_ats: i32,
 }
 
@@ -442,12 +1090,12 @@ impl Order {
         order_id: i32,
         client_id: i32,
         perm_id: i32,
-        action: String,
+        action: Action,
         total_quantity: f64,
-        order_type: String,
+        order_type: OrderType,
         lmt_price: f64,
         aux_price: f64,
-        tif: String,
+        tif: Tif,
         active_start_time: String,
         active_stop_time: String,
         oca_group: String,
@@ -461,8 +1109,8 @@ impl Order {
         trigger_method: i32,
         outside_rth: bool,
         hidden: bool,
-        good_after_time: String,
-        good_till_date: String,
+        good_after_time: Timestamp,
+        good_till_date: Timestamp,
         rule80a: String,
         all_or_none: bool,
         min_qty: i32,
@@ -494,7 +1142,7 @@ impl Order {
         randomize_size: bool,
         volatility: f64,
         volatility_type: i32,
-        delta_neutral_order_type: String,
+        delta_neutral_order_type: OrderType,
         delta_neutral_aux_price: f64,
         delta_neutral_con_id: i32,
         delta_neutral_settling_firm: String,
@@ -519,7 +1167,7 @@ impl Order {
         scale_init_fill_qty: i32,
         scale_random_percent: bool,
         scale_table: String,
-        hedge_type: String,
+        hedge_type: HedgeType,
         hedge_param: String,
         account: String,
         settling_firm: String,
@@ -540,7 +1188,7 @@ impl Order {
         is_pegged_change_amount_decrease: bool,
         reference_change_amount: f64,
         reference_exchange_id: String,
-        adjusted_order_type: String,
+        adjusted_order_type: OrderType,
         trigger_price: f64,
         adjusted_stop_price: f64,
         adjusted_stop_limit_price: f64,
@@ -779,14 +1427,14 @@ impl Default for Order {
             perm_id: 0,
 
             // main order fields
-            action: "".to_string(),
+            action: Action::None,
             total_quantity: 0.0,
-            order_type: "".to_string(),
+            order_type: OrderType::None,
             lmt_price: UNSET_DOUBLE,
             aux_price: UNSET_DOUBLE,
 
             // extended order fields
-            tif: "".to_string(),               // "Time in Force" - DAY, GTC, etc.
+            tif: Tif::None,                    // "Time in Force" - DAY, GTC, etc.
             active_start_time: "".to_string(), // for GTC orders
             active_stop_time: "".to_string(),  // for GTC orders
             oca_group: "".to_string(),         // one cancels all group name
@@ -800,8 +1448,8 @@ impl Default for Order {
             trigger_method: 0, // 0=Default, 1=Double_Bid_Ask, 2=Last, 3=Double_Last, 4=Bid_Ask, 7=Last_or_Bid_Ask, 8=Mid-point
             outside_rth: false,
             hidden: false,
-            good_after_time: "".to_string(), // Format: 20060505 08:00:00 {time zone}
-            good_till_date: "".to_string(),  // Format: 20060505 08:00:00 {time zone}
+            good_after_time: Timestamp::default(),
+            good_till_date: Timestamp::default(),
             rule80a: "".to_string(), // Individual = 'I', Agency = 'A', AgentOtherMember = 'W', IndividualPTIA = 'J', AgencyPTIA = 'U', AgentOtherMemberPTIA = 'M', IndividualPT = 'K', AgencyPT = 'Y', AgentOtherMemberPT = 'N'
             all_or_none: false,
             min_qty: UNSET_INTEGER,       //type: int
@@ -846,7 +1494,7 @@ impl Default for Order {
             // VOLATILITY ORDERS ONLY
             volatility: UNSET_DOUBLE,       // type: float
             volatility_type: UNSET_INTEGER, // type: int   // 1=daily, 2=annual
-            delta_neutral_order_type: "".to_string(),
+            delta_neutral_order_type: OrderType::None,
             delta_neutral_aux_price: UNSET_DOUBLE, // type: float
             delta_neutral_con_id: 0,
             delta_neutral_settling_firm: "".to_string(),
@@ -877,7 +1525,7 @@ impl Default for Order {
             scale_table: "".to_string(),
 
             // HEDGE ORDERS
-            hedge_type: "".to_string(), // 'D' - delta, 'B' - beta, 'F' - FX, 'P' - pair
+            hedge_type: HedgeType::None,
             hedge_param: "".to_string(), // 'beta=X' value for beta hedge, 'ratio=Y' for pair hedge
 
             // Clearing info
@@ -915,7 +1563,7 @@ impl Default for Order {
             is_pegged_change_amount_decrease: false,
             reference_change_amount: 0.0,
             reference_exchange_id: "".to_string(),
-            adjusted_order_type: "".to_string(),
+            adjusted_order_type: OrderType::None,
 
             trigger_price: UNSET_DOUBLE,
             adjusted_stop_price: UNSET_DOUBLE,
@@ -960,3 +1608,467 @@ impl Default for Order {
         }
     }
 }
+
+//==================================================================================================
+/// The adjustable_trailing_unit of an adjusted_trailing_amount in the currency of the contract
+pub const TRAILING_UNIT_AMOUNT: i32 = 0;
+/// The adjustable_trailing_unit of an adjusted_trailing_amount in percent of the price
+pub const TRAILING_UNIT_PERCENT: i32 = 1;
+
+//==================================================================================================
+/// Security types sweep_to_fill orders are accepted for
+pub const SWEEP_TO_FILL_SEC_TYPES: [SecType; 3] = [SecType::STK, SecType::CFD, SecType::WAR];
+/// Security types all_or_none orders are accepted for
+pub const ALL_OR_NONE_SEC_TYPES: [SecType; 3] = [SecType::STK, SecType::OPT, SecType::BOND];
+
+//==================================================================================================
+/// Order types IB accepts for CRYPTO contracts
+pub const CRYPTO_ORDER_TYPES: [OrderType; 3] = [OrderType::Mkt, OrderType::Lmt, OrderType::StpLmt];
+
+/// Times in force IB accepts for CRYPTO contracts, None being DAY
+pub const CRYPTO_TIFS: [Tif; 4] = [Tif::None, Tif::Day, Tif::Gtc, Tif::Ioc];
+
+//==================================================================================================
+/// Checks that a GTD order carries its good_till_date, since TWS rejects GTD orders without
+/// one as invalid
+///
+/// Returns an error with code TwsError::InvalidRequest if it does not
+pub fn check_time_in_force(order_id: i32, order: &Order) -> Result<(), IBKRApiLibError> {
+    if order.tif == Tif::Gtd && !is_set(&order.good_till_date) {
+        return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+            order_id,
+            TwsError::InvalidRequest.code().to_string(),
+            format!(
+                "{} GTD orders need a good_till_date.",
+                TwsError::InvalidRequest.message()
+            ),
+        )));
+    }
+    Ok(())
+}
+
+//==================================================================================================
+/// Checks the adjusted stop of an order, if it has an adjusted_order_type: it must be STP,
+/// STP LMT, TRAIL or TRAIL LIMIT, the order must be attached to a parent, and the prices the
+/// adjusted type needs must be set.  TWS otherwise accepts the order and never adjusts it.
+///
+/// Returns an error with code TwsError::InvalidRequest for the first problem found
+pub fn check_adjusted_order(order_id: i32, order: &Order) -> Result<(), IBKRApiLibError> {
+    let adjusted = &order.adjusted_order_type;
+    let limit = matches!(adjusted, OrderType::StpLmt | OrderType::TrailLimit);
+    let trailing = matches!(adjusted, OrderType::Trail | OrderType::TrailLimit);
+    let rejected = if *adjusted == OrderType::None {
+        None
+    } else if !limit && !trailing && *adjusted != OrderType::Stp {
+        Some(format!(
+            "adjusted_order_type must be STP, STP LMT, TRAIL or TRAIL LIMIT, not {}",
+            adjusted
+        ))
+    } else if order.parent_id == 0 {
+        Some("adjusted orders must be attached to a parent_id".to_string())
+    } else if order.trigger_price == UNSET_DOUBLE {
+        Some("adjusted orders need a trigger_price".to_string())
+    } else if order.adjusted_stop_price == UNSET_DOUBLE {
+        Some("adjusted orders need an adjusted_stop_price".to_string())
+    } else if limit && order.adjusted_stop_limit_price == UNSET_DOUBLE {
+        Some(format!(
+            "{} adjustments need an adjusted_stop_limit_price",
+            adjusted
+        ))
+    } else if trailing && order.adjusted_trailing_amount == UNSET_DOUBLE {
+        Some(format!(
+            "{} adjustments need an adjusted_trailing_amount",
+            adjusted
+        ))
+    } else if trailing
+        && order.adjustable_trailing_unit != TRAILING_UNIT_AMOUNT
+        && order.adjustable_trailing_unit != TRAILING_UNIT_PERCENT
+    {
+        Some(format!(
+            "adjustable_trailing_unit must be {} or {}, not {}",
+            TRAILING_UNIT_AMOUNT, TRAILING_UNIT_PERCENT, order.adjustable_trailing_unit
+        ))
+    } else {
+        None
+    };
+    match rejected {
+        Some(detail) => Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+            order_id,
+            TwsError::InvalidRequest.code().to_string(),
+            format!("{} {}.", TwsError::InvalidRequest.message(), detail),
+        ))),
+        None => Ok(()),
+    }
+}
+
+//==================================================================================================
+/// Checks that a PEG BENCH order names its reference contract and exchange and how its price
+/// follows the reference, since TWS rejects it otherwise
+///
+/// Returns an error with code TwsError::InvalidRequest for the first field missing
+pub fn check_pegged_to_benchmark(order_id: i32, order: &Order) -> Result<(), IBKRApiLibError> {
+    let is_change = |amount: f64| amount != UNSET_DOUBLE && amount > 0.0;
+    let missing = if order.order_type != OrderType::PegBench {
+        None
+    } else if order.reference_contract_id == 0 || order.reference_contract_id == UNSET_INTEGER {
+        Some("reference_contract_id")
+    } else if order.reference_exchange_id.is_empty() {
+        Some("reference_exchange_id")
+    } else if !is_change(order.pegged_change_amount) {
+        Some("pegged_change_amount")
+    } else if !is_change(order.reference_change_amount) {
+        Some("reference_change_amount")
+    } else {
+        None
+    };
+    match missing {
+        Some(field) => Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+            order_id,
+            TwsError::InvalidRequest.code().to_string(),
+            format!(
+                "{} PEG BENCH orders need a {}.",
+                TwsError::InvalidRequest.message(),
+                field
+            ),
+        ))),
+        None => Ok(()),
+    }
+}
+
+//==================================================================================================
+/// Checks a cash quantity order, one for an amount of currency rather than a number of shares
+/// or units: its cash_qty must be positive and it must be a MKT or LMT order, the types TWS
+/// takes cash quantities for.
+///
+/// Orders without a cash_qty pass.  Returns an error with code TwsError::InvalidRequest
+/// otherwise.
+pub fn check_cash_quantity(order_id: i32, order: &Order) -> Result<(), IBKRApiLibError> {
+    let rejected = if order.cash_qty == UNSET_DOUBLE {
+        None
+    } else if !(order.cash_qty.is_finite() && order.cash_qty > 0.0) {
+        Some(format!(
+            "the cash_qty must be positive, not {}",
+            order.cash_qty
+        ))
+    } else if order.order_type != OrderType::Mkt && order.order_type != OrderType::Lmt {
+        Some(format!(
+            "cash quantities are only taken for MKT and LMT orders, not {}",
+            order.order_type
+        ))
+    } else {
+        None
+    };
+    match rejected {
+        Some(detail) => Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+            order_id,
+            TwsError::InvalidRequest.code().to_string(),
+            format!("{} {}.", TwsError::InvalidRequest.message(), detail),
+        ))),
+        None => Ok(()),
+    }
+}
+
+//==================================================================================================
+/// Checks that an order names either a person or an algo for each of the MiFID II decision
+/// maker and execution, not both, since TWS rejects it otherwise
+///
+/// Returns an error with code TwsError::InvalidRequest if both are set
+pub fn check_mifid2(order_id: i32, order: &Order) -> Result<(), IBKRApiLibError> {
+    let rejected = if !order.mifid2decision_maker.is_empty()
+        && !order.mifid2decision_algo.is_empty()
+    {
+        Some("mifid2decision_maker and mifid2decision_algo")
+    } else if !order.mifid2execution_trader.is_empty() && !order.mifid2execution_algo.is_empty() {
+        Some("mifid2execution_trader and mifid2execution_algo")
+    } else {
+        None
+    };
+    match rejected {
+        Some(fields) => Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+            order_id,
+            TwsError::InvalidRequest.code().to_string(),
+            format!(
+                "{} Orders set only one of {}.",
+                TwsError::InvalidRequest.message(),
+                fields
+            ),
+        ))),
+        None => Ok(()),
+    }
+}
+
+//==================================================================================================
+/// Checks the scale fields of an order against the constraints of IB, since TWS rejects a
+/// scale order breaking them and place_order only sends the fields after
+/// scale_price_increment when the increment is set:
+/// * scale orders are LMT or REL orders with a positive scale_init_level_size, no larger than
+///   the total_quantity, and a positive scale_price_increment
+/// * scale_subs_level_size, if set, is positive
+/// * scale_price_adjust_value and scale_price_adjust_interval, in seconds, are set together
+/// * scale_profit_offset, if set, is positive, and scale_auto_reset needs it
+/// * scale_init_position and scale_init_fill_qty are set together and not negative
+///
+/// Orders without any scale field set are not scale orders and pass.  Returns an error with
+/// code TwsError::InvalidRequest for the first constraint broken.
+pub fn check_scale_order(order_id: i32, order: &Order) -> Result<(), IBKRApiLibError> {
+    let is_set_i32 = |value: i32| value != UNSET_INTEGER;
+    let is_set_f64 = |value: f64| value != UNSET_DOUBLE;
+    let is_positive_i32 = |value: i32| is_set_i32(value) && value > 0;
+    let is_positive_f64 = |value: f64| is_set_f64(value) && value > 0.0;
+    let is_scale = is_set_i32(order.scale_init_level_size)
+        || is_set_i32(order.scale_subs_level_size)
+        || is_set_f64(order.scale_price_increment)
+        || is_set_f64(order.scale_price_adjust_value)
+        || is_set_i32(order.scale_price_adjust_interval)
+        || is_set_f64(order.scale_profit_offset)
+        || order.scale_auto_reset
+        || is_set_i32(order.scale_init_position)
+        || is_set_i32(order.scale_init_fill_qty)
+        || order.scale_random_percent;
+    let rejected = if !is_scale {
+        None
+    } else if order.order_type != OrderType::Lmt && order.order_type != OrderType::Rel {
+        Some(format!(
+            "scale orders are LMT or REL orders, not {}",
+            order.order_type
+        ))
+    } else if !is_positive_i32(order.scale_init_level_size) {
+        Some("scale orders need a positive scale_init_level_size".to_string())
+    } else if order.scale_init_level_size as f64 > order.total_quantity {
+        Some(format!(
+            "the scale_init_level_size {} is larger than the total_quantity {}",
+            order.scale_init_level_size, order.total_quantity
+        ))
+    } else if is_set_i32(order.scale_subs_level_size)
+        && !is_positive_i32(order.scale_subs_level_size)
+    {
+        Some("the scale_subs_level_size must be positive".to_string())
+    } else if !is_positive_f64(order.scale_price_increment) {
+        Some("scale orders need a positive scale_price_increment".to_string())
+    } else if is_set_f64(order.scale_price_adjust_value)
+        != is_set_i32(order.scale_price_adjust_interval)
+    {
+        Some(
+            "scale_price_adjust_value and scale_price_adjust_interval are set together".to_string(),
+        )
+    } else if is_set_i32(order.scale_price_adjust_interval)
+        && !is_positive_i32(order.scale_price_adjust_interval)
+    {
+        Some("the scale_price_adjust_interval must be a positive number of seconds".to_string())
+    } else if is_set_f64(order.scale_profit_offset) && !is_positive_f64(order.scale_profit_offset) {
+        Some("the scale_profit_offset must be positive".to_string())
+    } else if order.scale_auto_reset && !is_set_f64(order.scale_profit_offset) {
+        Some("scale_auto_reset needs a scale_profit_offset".to_string())
+    } else if is_set_i32(order.scale_init_position) != is_set_i32(order.scale_init_fill_qty) {
+        Some("scale_init_position and scale_init_fill_qty are set together".to_string())
+    } else if (is_set_i32(order.scale_init_position) && order.scale_init_position < 0)
+        || (is_set_i32(order.scale_init_fill_qty) && order.scale_init_fill_qty < 0)
+    {
+        Some("scale_init_position and scale_init_fill_qty can't be negative".to_string())
+    } else {
+        None
+    };
+    match rejected {
+        Some(detail) => Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+            order_id,
+            TwsError::InvalidRequest.code().to_string(),
+            format!("{} {}.", TwsError::InvalidRequest.message(), detail),
+        ))),
+        None => Ok(()),
+    }
+}
+
+//==================================================================================================
+/// Checks that a hedge order is attached to the order it hedges and has the hedge_param its
+/// hedge_type needs, since TWS rejects it otherwise:
+/// * Beta - "beta=X", X a number
+/// * Pair - "ratio=Y", Y a number
+/// * Fx - a total_quantity of 0, as TWS works out the quantity from the fill of the parent
+///
+/// Returns an error with code TwsError::InvalidRequest for the first value missing
+pub fn check_hedge_order(order_id: i32, order: &Order) -> Result<(), IBKRApiLibError> {
+    let has_param = |key: &str| {
+        order
+            .hedge_param
+            .strip_prefix(key)
+            .and_then(|value| value.strip_prefix('='))
+            .is_some_and(|value| value.trim().parse::<f64>().is_ok())
+    };
+    let rejected = match &order.hedge_type {
+        HedgeType::None => None,
+        HedgeType::Other(code) => Some(format!("{:?} is not a hedge type", code)),
+        _ if order.parent_id == 0 || order.parent_id == UNSET_INTEGER => {
+            Some("hedge orders need the parent_id of the order they hedge".to_string())
+        }
+        HedgeType::Beta if !has_param("beta") => {
+            Some("beta hedges need a hedge_param of \"beta=X\"".to_string())
+        }
+        HedgeType::Pair if !has_param("ratio") => {
+            Some("pair hedges need a hedge_param of \"ratio=Y\"".to_string())
+        }
+        HedgeType::Fx if order.total_quantity != 0.0 => {
+            Some("FX hedges need a total_quantity of 0".to_string())
+        }
+        _ => None,
+    };
+    match rejected {
+        Some(detail) => Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+            order_id,
+            TwsError::InvalidRequest.code().to_string(),
+            format!("{} {}.", TwsError::InvalidRequest.message(), detail),
+        ))),
+        None => Ok(()),
+    }
+}
+
+//==================================================================================================
+/// Checks an order on a CRYPTO contract against the restrictions of IB on crypto orders:
+/// * the type is one of CRYPTO_ORDER_TYPES and the time in force one of CRYPTO_TIFS
+/// * MKT orders are IOC, and MKT buys are for a cash_qty rather than a total_quantity
+///
+/// Orders on other contracts pass.  Returns an error with code TwsError::InvalidRequest for
+/// the first restriction broken.
+pub fn check_crypto_order(
+    order_id: i32,
+    contract: &Contract,
+    order: &Order,
+) -> Result<(), IBKRApiLibError> {
+    let rejected = if contract.sec_type != SecType::CRYPTO {
+        None
+    } else if !CRYPTO_ORDER_TYPES.contains(&order.order_type) {
+        Some(format!(
+            "crypto orders are MKT, LMT or STP LMT orders, not {}",
+            order.order_type
+        ))
+    } else if !CRYPTO_TIFS.contains(&order.tif) {
+        Some(format!(
+            "crypto orders are DAY, GTC or IOC orders, not {}",
+            order.tif
+        ))
+    } else if order.order_type == OrderType::Mkt && order.tif != Tif::Ioc {
+        Some("crypto MKT orders must be IOC".to_string())
+    } else if order.order_type == OrderType::Mkt
+        && order.action.is_buy()
+        && order.cash_qty == UNSET_DOUBLE
+    {
+        Some("crypto MKT buys must be for a cash_qty".to_string())
+    } else {
+        None
+    };
+    match rejected {
+        Some(detail) => Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+            order_id,
+            TwsError::InvalidRequest.code().to_string(),
+            format!("{} {}.", TwsError::InvalidRequest.message(), detail),
+        ))),
+        None => Ok(()),
+    }
+}
+
+//==================================================================================================
+/// Checks the delta neutral parts of an order and its contract:
+/// * a delta_neutral_contract names the con_id of its hedge and is on an OPT, FOP or BAG
+///   contract
+/// * a delta_neutral_order_type is only given for VOL orders
+/// * the delta_neutral_con_id, clearing and short sale attributes come with a
+///   delta_neutral_order_type, as they are not sent without one
+///
+/// Returns an error with code TwsError::InvalidRequest for the first part broken.
+pub fn check_delta_neutral(
+    order_id: i32,
+    contract: &Contract,
+    order: &Order,
+) -> Result<(), IBKRApiLibError> {
+    let hedge_attributes = order.delta_neutral_con_id > 0
+        || !order.delta_neutral_settling_firm.is_empty()
+        || !order.delta_neutral_clearing_account.is_empty()
+        || !order.delta_neutral_clearing_intent.is_empty()
+        || !order.delta_neutral_open_close.is_empty()
+        || order.delta_neutral_short_sale
+        || order.delta_neutral_short_sale_slot > 0
+        || !order.delta_neutral_designated_location.is_empty();
+    let rejected = match contract.delta_neutral_contract.as_ref() {
+        Some(delta_neutral) if delta_neutral.con_id <= 0 => {
+            Some("a delta_neutral_contract needs the con_id of its hedge".to_string())
+        }
+        Some(_)
+            if !matches!(
+                contract.sec_type,
+                SecType::OPT | SecType::FOP | SecType::BAG
+            ) =>
+        {
+            Some(format!(
+                "delta neutral contracts are for OPT, FOP and BAG contracts, not {}",
+                contract.sec_type
+            ))
+        }
+        _ if order.delta_neutral_order_type != OrderType::None
+            && order.order_type != OrderType::Vol =>
+        {
+            Some(format!(
+                "a delta_neutral_order_type is for VOL orders, not {}",
+                order.order_type
+            ))
+        }
+        _ if order.delta_neutral_order_type == OrderType::None && hedge_attributes => {
+            Some("delta neutral hedge attributes need a delta_neutral_order_type".to_string())
+        }
+        _ => None,
+    };
+    match rejected {
+        Some(detail) => Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+            order_id,
+            TwsError::InvalidRequest.code().to_string(),
+            format!("{} {}.", TwsError::InvalidRequest.message(), detail),
+        ))),
+        None => Ok(()),
+    }
+}
+
+//==================================================================================================
+/// Checks the routing attributes of an order against the contract, since TWS rejects or
+/// silently ignores them elsewhere:
+/// * not_held - only for orders routed to IBKRATS
+/// * all_or_none - only for STK, OPT and BOND contracts
+/// * block_order - only for options, the ISE block order attribute
+/// * sweep_to_fill - only for STK, CFD and WAR contracts
+/// * opt_out_smart_routing - only for orders routed directly to ASX
+///
+/// origin is accepted on every route; an origin TWS reports that is neither Customer nor Firm
+/// fails the decoding of the order.  The professional customer and customer account fields
+/// need server versions above MAX_CLIENT_VER, so they are neither sent nor decoded.
+///
+/// Returns an error with code TwsError::InvalidRequest for the first attribute not accepted
+pub fn check_order_attributes(
+    order_id: i32,
+    contract: &Contract,
+    order: &Order,
+) -> Result<(), IBKRApiLibError> {
+    let rejected = if order.not_held && contract.exchange != Exchange::Ibkrats {
+        Some("not_held is only accepted for orders routed to IBKRATS")
+    } else if order.all_or_none && !ALL_OR_NONE_SEC_TYPES.contains(&contract.sec_type) {
+        Some("all_or_none is only accepted for STK, OPT and BOND contracts")
+    } else if order.block_order && contract.sec_type != SecType::OPT {
+        Some("block_order is only accepted for options")
+    } else if order.sweep_to_fill && !SWEEP_TO_FILL_SEC_TYPES.contains(&contract.sec_type) {
+        Some("sweep_to_fill is only accepted for STK, CFD and WAR contracts")
+    } else if order.opt_out_smart_routing && contract.exchange != Exchange::Asx {
+        Some("opt_out_smart_routing is only accepted for orders routed directly to ASX")
+    } else {
+        None
+    };
+    match rejected {
+        Some(detail) => Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+            order_id,
+            TwsError::InvalidRequest.code().to_string(),
+            format!(
+                "{} {}, not {} {}.",
+                TwsError::InvalidRequest.message(),
+                detail,
+                contract.sec_type,
+                contract.exchange
+            ),
+        ))),
+        None => Ok(()),
+    }
+}
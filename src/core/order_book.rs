@@ -0,0 +1,182 @@
+//! Maintains a level 2 order book from market depth updates
+use std::fmt;
+use std::time::Instant;
+
+use bigdecimal::BigDecimal;
+use num_traits::{FromPrimitive, ToPrimitive, Zero};
+use serde::{Deserialize, Serialize};
+
+use crate::core::common::{DeepSide, DeepType};
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+use crate::core::freshness::Freshness;
+
+//==================================================================================================
+/// One row of the order book
+/// market_maker - the exchange or market maker holding the order.  Empty for update_mkt_depth.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct BookLevel {
+    pub price: f64,
+    pub size: BigDecimal,
+    pub market_maker: String,
+}
+
+impl BookLevel {
+    pub fn new(price: f64, size: BigDecimal, market_maker: String) -> Self {
+        BookLevel {
+            price,
+            size,
+            market_maker,
+        }
+    }
+}
+
+impl fmt::Display for BookLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "price: {}, size: {}, market_maker: {}",
+            self.price, self.size, self.market_maker
+        )
+    }
+}
+
+//==================================================================================================
+/// Order book for one market depth request.  Feed it every update_mkt_depth and
+/// update_mkt_depth_l2 callback of the request via apply().
+///
+/// TWS addresses rows by position: an insert shifts the rows at and below the position
+/// down, a delete shifts them up, and rows arrive already sorted best price first.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct OrderBook {
+    bids: Vec<BookLevel>,
+    asks: Vec<BookLevel>,
+    #[serde(skip)]
+    updated: Option<Instant>,
+}
+
+impl OrderBook {
+    pub fn new() -> Self {
+        OrderBook::default()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Applies one market depth operation
+    ///
+    /// # Arguments
+    /// * position - the order book's row being updated
+    /// * market_maker - the exchange holding the order, or "" for update_mkt_depth
+    /// * operation - 0 = insert, 1 = update, 2 = delete
+    /// * side - 0 for ask, 1 for bid
+    /// * price - the order's price
+    /// * size - the order's size
+    pub fn apply(
+        &mut self,
+        position: i32,
+        market_maker: &str,
+        operation: i32,
+        side: i32,
+        price: f64,
+        size: BigDecimal,
+    ) -> Result<(), IBKRApiLibError> {
+        let rows = match FromPrimitive::from_i32(side) {
+            Some(DeepSide::Buy) => &mut self.bids,
+            Some(DeepSide::Sell) => &mut self.asks,
+            None => return Err(bad_depth_message(format!("unknown side {}", side))),
+        };
+        if position < 0 {
+            return Err(bad_depth_message(format!("negative position {}", position)));
+        }
+        let position = position as usize;
+        let level = BookLevel::new(price, size, market_maker.to_string());
+
+        match FromPrimitive::from_i32(operation) {
+            Some(DeepType::INSERT) => {
+                rows.insert(position.min(rows.len()), level);
+            }
+            Some(DeepType::UPDATE) => {
+                if position < rows.len() {
+                    rows[position] = level;
+                } else {
+                    // TWS occasionally updates a row it never inserted
+                    rows.push(level);
+                }
+            }
+            Some(DeepType::DELETE) => {
+                if position < rows.len() {
+                    rows.remove(position);
+                }
+            }
+            None => {
+                return Err(bad_depth_message(format!(
+                    "unknown operation {}",
+                    operation
+                )))
+            }
+        }
+        self.updated = Some(Instant::now());
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Highest bid, if any
+    pub fn best_bid(&self) -> Option<&BookLevel> {
+        self.bids.first()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Lowest ask, if any
+    pub fn best_ask(&self) -> Option<&BookLevel> {
+        self.asks.first()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Top n rows of each side, best price first, as (bids, asks)
+    pub fn depth(&self, n: usize) -> (&[BookLevel], &[BookLevel]) {
+        (
+            &self.bids[..n.min(self.bids.len())],
+            &self.asks[..n.min(self.asks.len())],
+        )
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Size imbalance over the top n rows: (bid size - ask size) / (bid size + ask size).
+    /// Ranges from -1.0 (only asks) to 1.0 (only bids).  None if the rows hold no size.
+    pub fn imbalance(&self, n: usize) -> Option<f64> {
+        let (bids, asks) = self.depth(n);
+        let bid_size: BigDecimal = bids.iter().map(|level| &level.size).sum();
+        let ask_size: BigDecimal = asks.iter().map(|level| &level.size).sum();
+        let total = &bid_size + &ask_size;
+        if total.is_zero() {
+            None
+        } else {
+            ((bid_size - ask_size) / total).to_f64()
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Empties the book, e.g. after the depth request was cancelled or reset by TWS
+    pub fn clear(&mut self) {
+        self.bids.clear();
+        self.asks.clear();
+    }
+}
+
+impl Freshness for OrderBook {
+    /// When the last market depth operation was applied
+    fn last_update(&self) -> Option<Instant> {
+        self.updated
+    }
+}
+
+//==================================================================================================
+fn bad_depth_message(detail: String) -> IBKRApiLibError {
+    IBKRApiLibError::ApiError(TwsApiReportableError::new(
+        -1,
+        TwsError::BadMessage.code().to_string(),
+        format!(
+            "{} Market depth: {}",
+            TwsError::BadMessage.message(),
+            detail
+        ),
+    ))
+}
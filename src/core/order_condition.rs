@@ -7,13 +7,14 @@ use num_traits::FromPrimitive;
 
 use serde::{Deserialize, Serialize};
 
+use crate::core::common::NO_VALID_ID;
 use crate::core::decoder::{decode_bool, decode_f64, decode_i32, decode_string};
-use crate::core::errors::IBKRApiLibError;
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
 use crate::core::messages::make_field;
 
 //==================================================================================================
 #[repr(i32)]
-#[derive(Serialize, Deserialize, Clone, FromPrimitive, Copy)]
+#[derive(Serialize, Deserialize, Clone, FromPrimitive, Copy, PartialEq, Eq)]
 pub enum ConditionType {
     Price = 1,
     Time = 3,
@@ -50,7 +51,7 @@ impl Debug for ConditionType {
 
 //==================================================================================================
 #[repr(i32)]
-#[derive(Serialize, Deserialize, Clone, FromPrimitive, Copy)]
+#[derive(Serialize, Deserialize, Clone, FromPrimitive, Copy, PartialEq, Eq)]
 pub enum TriggerMethod {
     Default = 0,
     DoubleBidAsk = 1,
@@ -92,7 +93,9 @@ impl Debug for TriggerMethod {
 }
 
 //==================================================================================================
-#[derive(Serialize, Deserialize, Clone)]
+/// A condition of Order::conditions.  Each is joined to the next one by its connector, AND
+/// if is_conjunction_connection, else OR.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub enum OrderConditionEnum {
     Price(PriceCondition),
     Time(TimeCondition),
@@ -102,6 +105,63 @@ pub enum OrderConditionEnum {
     PercentChange(PercentChangeCondition),
 }
 
+impl OrderConditionEnum {
+    /// The connector of the condition to the next one
+    pub fn order_condition(&self) -> &OrderCondition {
+        match self {
+            OrderConditionEnum::Execution(s) => &s.order_condition,
+            OrderConditionEnum::Price(p) => {
+                &p.contract_condition.operator_condition.order_condition
+            }
+            OrderConditionEnum::Margin(m) => &m.operator_condition.order_condition,
+            OrderConditionEnum::Time(t) => &t.operator_condition.order_condition,
+            OrderConditionEnum::Volume(v) => {
+                &v.contract_condition.operator_condition.order_condition
+            }
+            OrderConditionEnum::PercentChange(pch) => {
+                &pch.contract_condition.operator_condition.order_condition
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn order_condition_mut(&mut self) -> &mut OrderCondition {
+        match self {
+            OrderConditionEnum::Execution(s) => &mut s.order_condition,
+            OrderConditionEnum::Price(p) => {
+                &mut p.contract_condition.operator_condition.order_condition
+            }
+            OrderConditionEnum::Margin(m) => &mut m.operator_condition.order_condition,
+            OrderConditionEnum::Time(t) => &mut t.operator_condition.order_condition,
+            OrderConditionEnum::Volume(v) => {
+                &mut v.contract_condition.operator_condition.order_condition
+            }
+            OrderConditionEnum::PercentChange(pch) => {
+                &mut pch.contract_condition.operator_condition.order_condition
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The condition, joined to the next one with AND
+    pub fn and(mut self) -> Self {
+        self.order_condition_mut().and();
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The condition, joined to the next one with OR
+    pub fn or(mut self) -> Self {
+        self.order_condition_mut().or();
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn is_conjunction(&self) -> bool {
+        self.order_condition().is_conjunction_connection
+    }
+}
+
 impl Condition for OrderConditionEnum {
     fn decode(&mut self, fields_iter: &mut Iter<String>) -> Result<(), IBKRApiLibError> {
         match self {
@@ -196,7 +256,7 @@ pub trait Condition: Display + Debug + Serialize {
 }
 
 //==================================================================================================
-#[derive(Serialize, Deserialize, Clone, Debug, Copy, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug, Copy, Default, PartialEq)]
 pub struct OrderCondition {
     pub cond_type: ConditionType,
     pub is_conjunction_connection: bool,
@@ -247,7 +307,7 @@ impl OrderCondition {
     }
 }
 //==================================================================================================
-#[derive(Serialize, Deserialize, Clone, Default)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct ExecutionCondition {
     pub sec_type: String,
     pub exchange: String,
@@ -266,6 +326,12 @@ impl ExecutionCondition {
     }
 }
 
+impl Default for ExecutionCondition {
+    fn default() -> Self {
+        ExecutionCondition::new("".to_string(), "".to_string(), "".to_string())
+    }
+}
+
 impl Condition for ExecutionCondition {
     //----------------------------------------------------------------------------------------------
     fn decode(&mut self, fields_iter: &mut Iter<String>) -> Result<(), IBKRApiLibError> {
@@ -300,7 +366,7 @@ impl Condition for ExecutionCondition {
 
     //----------------------------------------------------------------------------------------------
     fn get_type(&self) -> ConditionType {
-        self.order_condition.cond_type
+        ConditionType::Execution
     }
 }
 
@@ -320,13 +386,23 @@ impl Debug for ExecutionCondition {
 
 impl From<OrderConditionEnum> for ExecutionCondition {
     //----------------------------------------------------------------------------------------------
-    fn from(_: OrderConditionEnum) -> Self {
-        ExecutionCondition::default()
+    /// The condition of a Execution OrderConditionEnum, a default one for the other kinds
+    fn from(condition: OrderConditionEnum) -> Self {
+        match condition {
+            OrderConditionEnum::Execution(condition) => condition,
+            _ => ExecutionCondition::default(),
+        }
+    }
+}
+
+impl From<ExecutionCondition> for OrderConditionEnum {
+    fn from(condition: ExecutionCondition) -> Self {
+        OrderConditionEnum::Execution(condition)
     }
 }
 
 //==================================================================================================
-#[derive(Serialize, Deserialize, Clone, Debug, Copy, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug, Copy, Default, PartialEq)]
 pub struct OperatorCondition {
     pub order_condition: OrderCondition,
     pub is_more: bool,
@@ -366,7 +442,7 @@ impl OperatorCondition {
 }
 
 //==================================================================================================
-#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub struct MarginCondition {
     pub operator_condition: OperatorCondition,
     pub percent: f64,
@@ -382,11 +458,17 @@ impl MarginCondition {
     }
 }
 
+impl Default for MarginCondition {
+    fn default() -> Self {
+        MarginCondition::new(false, 0.0)
+    }
+}
+
 impl Condition for MarginCondition {
     //----------------------------------------------------------------------------------------------
     fn decode(&mut self, fields_iter: &mut Iter<String>) -> Result<(), IBKRApiLibError> {
         self.operator_condition.decode(fields_iter)?;
-        self.percent = decode_f64(fields_iter).unwrap();
+        self.percent = decode_f64(fields_iter)?;
         Ok(())
     }
 
@@ -402,11 +484,13 @@ impl Condition for MarginCondition {
     }
 
     fn set_value_from_string(&mut self, text: String) {
-        self.percent = text.parse().unwrap();
+        if let Ok(percent) = text.parse() {
+            self.percent = percent;
+        }
     }
 
     fn get_type(&self) -> ConditionType {
-        self.operator_condition.order_condition.cond_type
+        ConditionType::Margin
     }
 }
 
@@ -426,12 +510,22 @@ impl Debug for MarginCondition {
 
 impl From<OrderConditionEnum> for MarginCondition {
     //----------------------------------------------------------------------------------------------
-    fn from(_: OrderConditionEnum) -> Self {
-        MarginCondition::default()
+    /// The condition of a Margin OrderConditionEnum, a default one for the other kinds
+    fn from(condition: OrderConditionEnum) -> Self {
+        match condition {
+            OrderConditionEnum::Margin(condition) => condition,
+            _ => MarginCondition::default(),
+        }
+    }
+}
+
+impl From<MarginCondition> for OrderConditionEnum {
+    fn from(condition: MarginCondition) -> Self {
+        OrderConditionEnum::Margin(condition)
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Default)]
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
 pub struct ContractCondition {
     pub operator_condition: OperatorCondition,
     pub con_id: i32,
@@ -503,7 +597,7 @@ impl From<OrderConditionEnum> for ContractCondition {
     }
 }
 //==================================================================================================
-#[derive(Serialize, Deserialize, Clone, Default)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct TimeCondition {
     pub operator_condition: OperatorCondition,
     pub time: String,
@@ -519,11 +613,17 @@ impl TimeCondition {
     }
 }
 
+impl Default for TimeCondition {
+    fn default() -> Self {
+        TimeCondition::new(false, "".to_string())
+    }
+}
+
 impl Condition for TimeCondition {
     //----------------------------------------------------------------------------------------------
     fn decode(&mut self, fields_iter: &mut Iter<String>) -> Result<(), IBKRApiLibError> {
         self.operator_condition.decode(fields_iter)?;
-        self.time = decode_string(fields_iter).unwrap();
+        self.time = decode_string(fields_iter)?;
         Ok(())
     }
 
@@ -546,7 +646,7 @@ impl Condition for TimeCondition {
 
     //----------------------------------------------------------------------------------------------
     fn get_type(&self) -> ConditionType {
-        self.operator_condition.order_condition.cond_type
+        ConditionType::Time
     }
 }
 
@@ -568,13 +668,23 @@ impl Debug for TimeCondition {
 //==================================================================================================
 impl From<OrderConditionEnum> for TimeCondition {
     //----------------------------------------------------------------------------------------------
-    fn from(_: OrderConditionEnum) -> Self {
-        TimeCondition::default()
+    /// The condition of a Time OrderConditionEnum, a default one for the other kinds
+    fn from(condition: OrderConditionEnum) -> Self {
+        match condition {
+            OrderConditionEnum::Time(condition) => condition,
+            _ => TimeCondition::default(),
+        }
+    }
+}
+
+impl From<TimeCondition> for OrderConditionEnum {
+    fn from(condition: TimeCondition) -> Self {
+        OrderConditionEnum::Time(condition)
     }
 }
 
 //==================================================================================================
-#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
 pub struct PriceCondition {
     pub contract_condition: ContractCondition,
     pub price: f64,
@@ -606,9 +716,16 @@ impl PriceCondition {
 impl Condition for PriceCondition {
     //----------------------------------------------------------------------------------------------
     fn decode(&mut self, fields_iter: &mut Iter<String>) -> Result<(), IBKRApiLibError> {
+        // TWS sends the value between is_more and the contract
+        self.contract_condition
+            .operator_condition
+            .decode(fields_iter)?;
         self.price = decode_f64(fields_iter)?;
-        self.contract_condition.decode(fields_iter)?;
-        self.trigger_method = FromPrimitive::from_i32(decode_i32(fields_iter)?).unwrap();
+        self.contract_condition.con_id = decode_i32(fields_iter)?;
+        self.contract_condition.exchange = decode_string(fields_iter)?;
+        let trigger_method = decode_i32(fields_iter)?;
+        self.trigger_method = FromPrimitive::from_i32(trigger_method)
+            .ok_or_else(|| condition_error("trigger method", trigger_method))?;
         Ok(())
     }
 
@@ -637,15 +754,14 @@ impl Condition for PriceCondition {
 
     //----------------------------------------------------------------------------------------------
     fn set_value_from_string(&mut self, text: String) {
-        self.price = text.parse().unwrap();
+        if let Ok(price) = text.parse() {
+            self.price = price;
+        }
     }
 
     //----------------------------------------------------------------------------------------------
     fn get_type(&self) -> ConditionType {
-        self.contract_condition
-            .operator_condition
-            .order_condition
-            .cond_type
+        ConditionType::Price
     }
 }
 
@@ -663,13 +779,23 @@ impl Display for PriceCondition {
 
 impl From<OrderConditionEnum> for PriceCondition {
     //----------------------------------------------------------------------------------------------
-    fn from(_: OrderConditionEnum) -> Self {
-        PriceCondition::default()
+    /// The condition of a Price OrderConditionEnum, a default one for the other kinds
+    fn from(condition: OrderConditionEnum) -> Self {
+        match condition {
+            OrderConditionEnum::Price(condition) => condition,
+            _ => PriceCondition::default(),
+        }
+    }
+}
+
+impl From<PriceCondition> for OrderConditionEnum {
+    fn from(condition: PriceCondition) -> Self {
+        OrderConditionEnum::Price(condition)
     }
 }
 
 //==================================================================================================
-#[derive(Serialize, Deserialize, Clone, Default)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct PercentChangeCondition {
     pub contract_condition: ContractCondition,
     pub change_percent: f64,
@@ -690,11 +816,22 @@ impl PercentChangeCondition {
     }
 }
 
+impl Default for PercentChangeCondition {
+    fn default() -> Self {
+        PercentChangeCondition::new(0, "".to_string(), false, 0.0)
+    }
+}
+
 impl Condition for PercentChangeCondition {
     //----------------------------------------------------------------------------------------------
     fn decode(&mut self, fields_iter: &mut Iter<String>) -> Result<(), IBKRApiLibError> {
+        // TWS sends the value between is_more and the contract
+        self.contract_condition
+            .operator_condition
+            .decode(fields_iter)?;
         self.change_percent = decode_f64(fields_iter)?;
-        self.contract_condition.decode(fields_iter)?;
+        self.contract_condition.con_id = decode_i32(fields_iter)?;
+        self.contract_condition.exchange = decode_string(fields_iter)?;
         Ok(())
     }
 
@@ -722,15 +859,14 @@ impl Condition for PercentChangeCondition {
 
     //----------------------------------------------------------------------------------------------
     fn set_value_from_string(&mut self, text: String) {
-        self.change_percent = text.parse().unwrap();
+        if let Ok(change_percent) = text.parse() {
+            self.change_percent = change_percent;
+        }
     }
 
     //----------------------------------------------------------------------------------------------
     fn get_type(&self) -> ConditionType {
-        self.contract_condition
-            .operator_condition
-            .order_condition
-            .cond_type
+        ConditionType::PercentChange
     }
 }
 
@@ -749,13 +885,23 @@ impl Debug for PercentChangeCondition {
 
 impl From<OrderConditionEnum> for PercentChangeCondition {
     //----------------------------------------------------------------------------------------------
-    fn from(_: OrderConditionEnum) -> Self {
-        PercentChangeCondition::default()
+    /// The condition of a PercentChange OrderConditionEnum, a default one for the other kinds
+    fn from(condition: OrderConditionEnum) -> Self {
+        match condition {
+            OrderConditionEnum::PercentChange(condition) => condition,
+            _ => PercentChangeCondition::default(),
+        }
+    }
+}
+
+impl From<PercentChangeCondition> for OrderConditionEnum {
+    fn from(condition: PercentChangeCondition) -> Self {
+        OrderConditionEnum::PercentChange(condition)
     }
 }
 
 //==================================================================================================
-#[derive(Serialize, Deserialize, Clone, Default)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct VolumeCondition {
     pub contract_condition: ContractCondition,
     pub volume: i32,
@@ -776,11 +922,22 @@ impl VolumeCondition {
     }
 }
 
+impl Default for VolumeCondition {
+    fn default() -> Self {
+        VolumeCondition::new(0, "", false, 0)
+    }
+}
+
 impl Condition for VolumeCondition {
     //----------------------------------------------------------------------------------------------
     fn decode(&mut self, fields_iter: &mut Iter<String>) -> Result<(), IBKRApiLibError> {
-        self.contract_condition.decode(fields_iter)?;
+        // TWS sends the value between is_more and the contract
+        self.contract_condition
+            .operator_condition
+            .decode(fields_iter)?;
         self.volume = decode_i32(fields_iter)?;
+        self.contract_condition.con_id = decode_i32(fields_iter)?;
+        self.contract_condition.exchange = decode_string(fields_iter)?;
 
         Ok(())
     }
@@ -808,15 +965,14 @@ impl Condition for VolumeCondition {
 
     //----------------------------------------------------------------------------------------------
     fn set_value_from_string(&mut self, text: String) {
-        self.volume = text.parse().unwrap();
+        if let Ok(volume) = text.parse() {
+            self.volume = volume;
+        }
     }
 
     //----------------------------------------------------------------------------------------------
     fn get_type(&self) -> ConditionType {
-        self.contract_condition
-            .operator_condition
-            .order_condition
-            .cond_type
+        ConditionType::Volume
     }
 }
 
@@ -836,13 +992,24 @@ impl Debug for VolumeCondition {
 
 impl From<OrderConditionEnum> for VolumeCondition {
     //----------------------------------------------------------------------------------------------
-    fn from(_: OrderConditionEnum) -> Self {
-        VolumeCondition::default()
+    /// The condition of a Volume OrderConditionEnum, a default one for the other kinds
+    fn from(condition: OrderConditionEnum) -> Self {
+        match condition {
+            OrderConditionEnum::Volume(condition) => condition,
+            _ => VolumeCondition::default(),
+        }
     }
 }
 
-//----------------------------------------------------------------------------------------------
-pub fn create_condition<'a>(cond_type: ConditionType) -> OrderConditionEnum {
+impl From<VolumeCondition> for OrderConditionEnum {
+    fn from(condition: VolumeCondition) -> Self {
+        OrderConditionEnum::Volume(condition)
+    }
+}
+
+//==================================================================================================
+/// A default condition of a type, to decode into
+pub fn create_condition(cond_type: ConditionType) -> OrderConditionEnum {
     match cond_type {
         ConditionType::Execution => OrderConditionEnum::Execution(ExecutionCondition::default()),
         ConditionType::Margin => OrderConditionEnum::Margin(MarginCondition::default()),
@@ -854,3 +1021,31 @@ pub fn create_condition<'a>(cond_type: ConditionType) -> OrderConditionEnum {
         ConditionType::Volume => OrderConditionEnum::Volume(VolumeCondition::default()),
     }
 }
+
+//==================================================================================================
+/// Decodes a condition of an OpenOrder or CompletedOrder message: its type, then its fields
+pub fn decode_condition(
+    fields_iter: &mut Iter<String>,
+) -> Result<OrderConditionEnum, IBKRApiLibError> {
+    let cond_type = decode_i32(fields_iter)?;
+    let mut condition = create_condition(
+        FromPrimitive::from_i32(cond_type)
+            .ok_or_else(|| condition_error("condition type", cond_type))?,
+    );
+    condition.decode(fields_iter)?;
+    Ok(condition)
+}
+
+//==================================================================================================
+fn condition_error(what: &str, value: i32) -> IBKRApiLibError {
+    IBKRApiLibError::ApiError(TwsApiReportableError::new(
+        NO_VALID_ID,
+        TwsError::BadMessage.code().to_string(),
+        format!(
+            "{} Unknown {} {}.",
+            TwsError::BadMessage.message(),
+            what,
+            value
+        ),
+    ))
+}
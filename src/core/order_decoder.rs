@@ -3,15 +3,19 @@ use std::slice::Iter;
 
 use num_traits::FromPrimitive;
 
-use crate::core::common::{TagValue, UNSET_DOUBLE};
+use crate::core::common::{HedgeType, SecType, TagValue, UNSET_DOUBLE};
 use crate::core::contract::{ComboLeg, Contract, DeltaNeutralContract};
+use crate::core::currency::Currency;
 use crate::core::decoder::{
     decode_bool, decode_f64, decode_f64_show_unset, decode_i32, decode_i32_show_unset,
     decode_string,
 };
-use crate::core::errors::IBKRApiLibError;
-use crate::core::order::{Order, OrderComboLeg, OrderState, SoftDollarTier};
-use crate::core::order_condition::{create_condition, Condition};
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+use crate::core::exchange::Exchange;
+use crate::core::order::{
+    Action, Order, OrderComboLeg, OrderState, OrderType, SoftDollarTier, Tif,
+};
+use crate::core::order_condition::decode_condition;
 use crate::core::server_versions::{
     MIN_SERVER_VER_AUTO_PRICE_FOR_HEDGE, MIN_SERVER_VER_CASH_QTY, MIN_SERVER_VER_D_PEG_ORDERS,
     MIN_SERVER_VER_FRACTIONAL_POSITIONS, MIN_SERVER_VER_MODELS_SUPPORT,
@@ -19,6 +23,7 @@ use crate::core::server_versions::{
     MIN_SERVER_VER_PRICE_MGMT_ALGO, MIN_SERVER_VER_SOFT_DOLLAR_TIER, MIN_SERVER_VER_SSHORTX_OLD,
     MIN_SERVER_VER_WHAT_IF_EXT_FIELDS,
 };
+use crate::core::timestamps::timestamp_from_wire;
 
 use super::server_versions::{MIN_SERVER_VER_AUTO_CANCEL_PARENT, MIN_SERVER_VER_DURATION};
 
@@ -214,15 +219,15 @@ impl<'a> OrderDecoder<'a> {
     ) -> Result<(), IBKRApiLibError> {
         self.contract.con_id = decode_i32(fields_iter)?;
         self.contract.symbol = decode_string(fields_iter)?;
-        self.contract.sec_type = decode_string(fields_iter)?;
+        self.contract.sec_type = SecType::from(decode_string(fields_iter)?);
         self.contract.last_trade_date_or_contract_month = decode_string(fields_iter)?;
         self.contract.strike = decode_f64(fields_iter)?;
         self.contract.right = decode_string(fields_iter)?;
         if self.version >= 32 {
             self.contract.multiplier = decode_string(fields_iter)?;
         }
-        self.contract.exchange = decode_string(fields_iter)?;
-        self.contract.currency = decode_string(fields_iter)?;
+        self.contract.exchange = Exchange::from(decode_string(fields_iter)?);
+        self.contract.currency = Currency::from(decode_string(fields_iter)?);
         self.contract.local_symbol = decode_string(fields_iter)?;
         if self.version >= 32 {
             self.contract.trading_class = decode_string(fields_iter)?;
@@ -233,7 +238,7 @@ impl<'a> OrderDecoder<'a> {
 
     //----------------------------------------------------------------------------------------------
     fn decode_action(&mut self, fields_iter: &mut Iter<String>) -> Result<(), IBKRApiLibError> {
-        self.order.action = decode_string(fields_iter)?;
+        self.order.action = Action::from(decode_string(fields_iter)?);
         Ok(())
     }
 
@@ -252,7 +257,7 @@ impl<'a> OrderDecoder<'a> {
 
     //----------------------------------------------------------------------------------------------
     fn decode_order_type(&mut self, fields_iter: &mut Iter<String>) -> Result<(), IBKRApiLibError> {
-        self.order.order_type = decode_string(fields_iter)?;
+        self.order.order_type = OrderType::from(decode_string(fields_iter)?);
         Ok(())
     }
 
@@ -279,7 +284,7 @@ impl<'a> OrderDecoder<'a> {
 
     //----------------------------------------------------------------------------------------------
     fn decode_tif(&mut self, fields_iter: &mut Iter<String>) -> Result<(), IBKRApiLibError> {
-        self.order.tif = decode_string(fields_iter)?;
+        self.order.tif = Tif::from(decode_string(fields_iter)?);
 
         Ok(())
     }
@@ -305,7 +310,18 @@ impl<'a> OrderDecoder<'a> {
 
     //----------------------------------------------------------------------------------------------
     fn decode_origin(&mut self, fields_iter: &mut Iter<String>) -> Result<(), IBKRApiLibError> {
-        self.order.origin = FromPrimitive::from_i32(decode_i32(fields_iter)?).unwrap();
+        let origin = decode_i32(fields_iter)?;
+        self.order.origin = FromPrimitive::from_i32(origin).ok_or_else(|| {
+            IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                self.order.order_id,
+                TwsError::BadMessage.code().to_string(),
+                format!(
+                    "{} Unknown origin {}.",
+                    TwsError::BadMessage.message(),
+                    origin
+                ),
+            ))
+        })?;
         Ok(())
     }
 
@@ -356,7 +372,7 @@ impl<'a> OrderDecoder<'a> {
         &mut self,
         fields_iter: &mut Iter<String>,
     ) -> Result<(), IBKRApiLibError> {
-        self.order.good_after_time = decode_string(fields_iter)?;
+        self.order.good_after_time = timestamp_from_wire(&decode_string(fields_iter)?);
         Ok(())
     }
 
@@ -391,7 +407,7 @@ impl<'a> OrderDecoder<'a> {
         &mut self,
         fields_iter: &mut Iter<String>,
     ) -> Result<(), IBKRApiLibError> {
-        self.order.good_till_date = decode_string(fields_iter)?;
+        self.order.good_till_date = timestamp_from_wire(&decode_string(fields_iter)?);
         Ok(())
     }
 
@@ -560,10 +576,10 @@ impl<'a> OrderDecoder<'a> {
     ) -> Result<(), IBKRApiLibError> {
         self.order.volatility = decode_f64_show_unset(fields_iter)?;
         self.order.volatility_type = decode_i32(fields_iter)?;
-        self.order.delta_neutral_order_type = decode_string(fields_iter)?;
+        self.order.delta_neutral_order_type = OrderType::from(decode_string(fields_iter)?);
         self.order.delta_neutral_aux_price = decode_f64_show_unset(fields_iter)?;
 
-        if self.version >= 27 && self.order.delta_neutral_order_type != "" {
+        if self.version >= 27 && self.order.delta_neutral_order_type != OrderType::None {
             self.order.delta_neutral_con_id = decode_i32(fields_iter)?;
             if read_open_order_attribs {
                 self.order.delta_neutral_settling_firm = decode_string(fields_iter)?;
@@ -572,7 +588,7 @@ impl<'a> OrderDecoder<'a> {
             }
         }
 
-        if self.version >= 31 && self.order.delta_neutral_order_type != "" {
+        if self.version >= 31 && self.order.delta_neutral_order_type != OrderType::None {
             if read_open_order_attribs {
                 self.order.delta_neutral_open_close = decode_string(fields_iter)?;
             }
@@ -621,7 +637,7 @@ impl<'a> OrderDecoder<'a> {
                     let mut combo_leg: ComboLeg = ComboLeg::default();
                     combo_leg.con_id = decode_i32(fields_iter)?;
                     combo_leg.ratio = decode_f64(fields_iter)?;
-                    combo_leg.action = decode_string(fields_iter)?;
+                    combo_leg.action = Action::from(decode_string(fields_iter)?);
                     combo_leg.exchange = decode_string(fields_iter)?;
                     combo_leg.open_close =
                         FromPrimitive::from_i32(decode_i32(fields_iter)?).unwrap();
@@ -665,7 +681,7 @@ impl<'a> OrderDecoder<'a> {
     }
 
     //----------------------------------------------------------------------------------------------
-    fn decode_scale_order_params(
+    pub(crate) fn decode_scale_order_params(
         &mut self,
         fields_iter: &mut Iter<String>,
     ) -> Result<(), IBKRApiLibError> {
@@ -699,9 +715,9 @@ impl<'a> OrderDecoder<'a> {
         fields_iter: &mut Iter<String>,
     ) -> Result<(), IBKRApiLibError> {
         if self.version >= 24 {
-            self.order.hedge_type = decode_string(fields_iter)?;
+            self.order.hedge_type = HedgeType::from(decode_string(fields_iter)?);
         }
-        if self.order.hedge_type != "" {
+        if self.order.hedge_type.is_set() {
             self.order.hedge_param = decode_string(fields_iter)?;
         }
         Ok(())
@@ -846,7 +862,7 @@ impl<'a> OrderDecoder<'a> {
         fields_iter: &mut Iter<String>,
     ) -> Result<(), IBKRApiLibError> {
         if self.server_version >= MIN_SERVER_VER_PEGGED_TO_BENCHMARK {
-            if self.order.order_type == "PEG BENCH" {
+            if self.order.order_type == OrderType::PegBench {
                 self.order.reference_contract_id = decode_i32(fields_iter)?;
                 self.order.is_pegged_change_amount_decrease = decode_bool(fields_iter)?;
                 self.order.pegged_change_amount = decode_f64(fields_iter)?;
@@ -865,12 +881,7 @@ impl<'a> OrderDecoder<'a> {
             if conditions_size > 0 {
                 self.order.conditions = vec![];
                 for _ in 0..conditions_size {
-                    let condition_type = FromPrimitive::from_i32(decode_i32(fields_iter)?).unwrap();
-
-                    let mut condition = create_condition(condition_type);
-                    condition.decode(fields_iter)?;
-
-                    self.order.conditions.push(condition);
+                    self.order.conditions.push(decode_condition(fields_iter)?);
                 }
                 self.order.conditions_ignore_rth = decode_bool(fields_iter)?;
                 self.order.conditions_cancel_order = decode_bool(fields_iter)?;
@@ -885,7 +896,7 @@ impl<'a> OrderDecoder<'a> {
         fields_iter: &mut Iter<String>,
     ) -> Result<(), IBKRApiLibError> {
         if self.server_version >= MIN_SERVER_VER_PEGGED_TO_BENCHMARK {
-            self.order.adjusted_order_type = decode_string(fields_iter)?;
+            self.order.adjusted_order_type = OrderType::from(decode_string(fields_iter)?);
             self.order.trigger_price = decode_f64(fields_iter)?;
             self.decode_stop_price_and_lmt_price_offset(fields_iter)?;
             self.order.adjusted_stop_price = decode_f64(fields_iter)?;
@@ -923,7 +934,7 @@ impl<'a> OrderDecoder<'a> {
     //----------------------------------------------------------------------------------------------
     fn decode_cash_qty(&mut self, fields_iter: &mut Iter<String>) -> Result<(), IBKRApiLibError> {
         if self.server_version >= MIN_SERVER_VER_CASH_QTY {
-            self.order.cash_qty = decode_f64(fields_iter)?;
+            self.order.cash_qty = decode_f64_show_unset(fields_iter)?;
         }
         Ok(())
     }
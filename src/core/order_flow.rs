@@ -0,0 +1,216 @@
+//! Aggressor side inference and signed volume for order flow analysis
+//!
+//! Trades are classified in the style of Lee and Ready: a trade above the prevailing quote
+//! midpoint was bought by the aggressor, one below it sold.  Trades at the midpoint, or before
+//! any quote, fall back to the tick test against the last different trade price.
+use std::collections::BTreeMap;
+use std::fmt;
+
+use bigdecimal::BigDecimal;
+use num_traits::{ToPrimitive, Zero};
+use serde::{Deserialize, Serialize};
+
+use crate::core::tick_timeline::TimelineEvent;
+
+//==================================================================================================
+/// Side which initiated a trade
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Aggressor {
+    Buy,
+    Sell,
+    #[default]
+    Unknown,
+}
+
+impl fmt::Display for Aggressor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Aggressor::Buy => write!(f, "BUY"),
+            Aggressor::Sell => write!(f, "SELL"),
+            Aggressor::Unknown => write!(f, "UNKNOWN"),
+        }
+    }
+}
+
+//==================================================================================================
+/// A trade and its inferred aggressor side
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ClassifiedTrade {
+    pub time: i64,
+    pub price: f64,
+    pub size: BigDecimal,
+    pub aggressor: Aggressor,
+}
+
+impl ClassifiedTrade {
+    pub fn new(time: i64, price: f64, size: BigDecimal, aggressor: Aggressor) -> Self {
+        ClassifiedTrade {
+            time,
+            price,
+            size,
+            aggressor,
+        }
+    }
+}
+
+impl fmt::Display for ClassifiedTrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "time: {}, price: {}, size: {}, aggressor: {}",
+            self.time, self.price, self.size, self.aggressor
+        )
+    }
+}
+
+//==================================================================================================
+/// Classifies trades in the order they printed.  Feed it the quotes and trades of one contract,
+/// from a merged timeline or from the tick_by_tick_bid_ask and tick_by_tick_all_last callbacks.
+#[derive(Clone, Debug, Default)]
+pub struct TradeClassifier {
+    bid: Option<f64>,
+    ask: Option<f64>,
+    last_price: Option<f64>,
+    last_change: Aggressor,
+}
+
+impl TradeClassifier {
+    pub fn new() -> Self {
+        TradeClassifier::default()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Updates the prevailing quote.  A crossed or one-sided quote has no usable midpoint, so
+    /// trades until the next quote are classified with the tick test.
+    pub fn on_quote(&mut self, bid: f64, ask: f64) {
+        if bid > 0.0 && ask > 0.0 && bid <= ask {
+            self.bid = Some(bid);
+            self.ask = Some(ask);
+        } else {
+            self.bid = None;
+            self.ask = None;
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Classifies a trade and remembers its price for the tick test
+    pub fn on_trade(&mut self, time: i64, price: f64, size: BigDecimal) -> ClassifiedTrade {
+        // tick test: an uptick is a buy, a downtick a sell, an unchanged price keeps the side of
+        // the last price change
+        let tick_side = match self.last_price {
+            Some(last) if price > last => Aggressor::Buy,
+            Some(last) if price < last => Aggressor::Sell,
+            _ => self.last_change,
+        };
+        self.last_change = tick_side;
+        self.last_price = Some(price);
+
+        let aggressor = match (self.bid, self.ask) {
+            (Some(bid), Some(ask)) => {
+                let mid = (bid + ask) / 2.0;
+                if price > mid {
+                    Aggressor::Buy
+                } else if price < mid {
+                    Aggressor::Sell
+                } else {
+                    tick_side
+                }
+            }
+            _ => tick_side,
+        };
+        ClassifiedTrade::new(time, price, size, aggressor)
+    }
+}
+
+//==================================================================================================
+/// Classifies the trades of a timeline built with tick_timeline::merge_ticks
+pub fn classify_timeline(events: &[TimelineEvent]) -> Vec<ClassifiedTrade> {
+    let mut classifier = TradeClassifier::new();
+    let mut trades = Vec::new();
+    for event in events.iter() {
+        match event {
+            TimelineEvent::Quote(quote) => classifier.on_quote(quote.price_bid, quote.price_ask),
+            TimelineEvent::Trade(trade) => {
+                trades.push(classifier.on_trade(event.time(), trade.price, trade.size.clone()))
+            }
+        }
+    }
+    trades
+}
+
+//==================================================================================================
+/// Volume split by aggressor side
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SignedVolume {
+    pub buy_volume: BigDecimal,
+    pub sell_volume: BigDecimal,
+    pub unknown_volume: BigDecimal,
+}
+
+impl SignedVolume {
+    pub fn new() -> Self {
+        SignedVolume::default()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn add(&mut self, trade: &ClassifiedTrade) {
+        let size = &trade.size;
+        match trade.aggressor {
+            Aggressor::Buy => self.buy_volume += size,
+            Aggressor::Sell => self.sell_volume += size,
+            Aggressor::Unknown => self.unknown_volume += size,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Buy volume minus sell volume
+    pub fn net(&self) -> BigDecimal {
+        &self.buy_volume - &self.sell_volume
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Net volume as a fraction of the classified volume, between -1 and 1.  None without
+    /// classified volume.
+    pub fn imbalance(&self) -> Option<f64> {
+        let classified = &self.buy_volume + &self.sell_volume;
+        if classified.is_zero() {
+            return None;
+        }
+        (self.net() / classified).to_f64()
+    }
+}
+
+impl fmt::Display for SignedVolume {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "buy_volume: {}, sell_volume: {}, unknown_volume: {}",
+            self.buy_volume, self.sell_volume, self.unknown_volume
+        )
+    }
+}
+
+//==================================================================================================
+/// Total signed volume of the trades
+pub fn signed_volume(trades: &[ClassifiedTrade]) -> SignedVolume {
+    let mut volume = SignedVolume::new();
+    for trade in trades.iter() {
+        volume.add(trade);
+    }
+    volume
+}
+
+//==================================================================================================
+/// Signed volume per interval of epoch-aligned seconds, keyed and ordered by interval start.
+/// Intervals without trades are left out.
+pub fn signed_volume_by_interval(
+    trades: &[ClassifiedTrade],
+    interval_seconds: i64,
+) -> Vec<(i64, SignedVolume)> {
+    let mut intervals: BTreeMap<i64, SignedVolume> = BTreeMap::new();
+    for trade in trades.iter() {
+        let start = trade.time - trade.time.rem_euclid(interval_seconds.max(1));
+        intervals.entry(start).or_default().add(trade);
+    }
+    intervals.into_iter().collect()
+}
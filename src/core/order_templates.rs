@@ -0,0 +1,685 @@
+//! Templates of the common order types
+//!
+//! An Order has well over a hundred fields, most of which only apply to a few order types.
+//! The functions of this module build the common types from the few values they need, leaving
+//! every other field at the default of Order::default, i.e. UNSET_DOUBLE and UNSET_INTEGER
+//! for the prices and amounts TWS does not apply.  They check those values first, so a zero
+//! quantity or a NaN price is an error here rather than a rejection by TWS.  cash_market and
+//! cash_limit build orders for an amount of currency instead of a quantity, and
+//! delta_neutral_volatility VOL orders hedging their delta.
+//!
+//! The orders are DAY orders without an order_id; set tif, account, order_ref and the like on
+//! the returned Order before placing it.  PeggedToBenchmark builds PEG BENCH orders, which also
+//! need the con_id of their reference contract, from its details or by looking them up.
+//! attach_fx_hedge and place_with_fx_hedge hedge the currency of an order on a contract in
+//! another currency than the base currency of the account, and ScaleOrderBuilder builds
+//! scale orders.
+use std::time::Duration;
+
+use crate::core::client::EClient;
+use crate::core::common::{HedgeType, NO_VALID_ID, UNSET_DOUBLE, UNSET_INTEGER};
+use crate::core::contract::{Contract, ContractDetails};
+use crate::core::currency::Currency;
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+use crate::core::exchange::Exchange;
+use crate::core::instrument::Instrument;
+use crate::core::market_rules::RoundDirection;
+use crate::core::order::{
+    check_adjusted_order, check_cash_quantity, check_hedge_order, check_pegged_to_benchmark,
+    check_scale_order, Action, Order, OrderType,
+};
+use crate::core::wrapper::Wrapper;
+
+//==================================================================================================
+/// What the parent of an adjusted stop turns into once the trigger price trades
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StopAdjustment {
+    /// a STP order at stop_price
+    Stop { stop_price: f64 },
+    /// a STP LMT order at stop_price and limit_price
+    StopLimit { stop_price: f64, limit_price: f64 },
+    /// a TRAIL order starting at stop_price, trailing by trailing_amount in
+    /// trailing_unit, TRAILING_UNIT_AMOUNT or TRAILING_UNIT_PERCENT
+    Trail {
+        stop_price: f64,
+        trailing_amount: f64,
+        trailing_unit: i32,
+    },
+}
+
+//==================================================================================================
+/// A market order
+pub fn market(action: Action, quantity: f64) -> Result<Order, IBKRApiLibError> {
+    template(action, quantity, OrderType::Mkt)
+}
+
+//==================================================================================================
+/// A limit order at limit_price
+pub fn limit(action: Action, quantity: f64, limit_price: f64) -> Result<Order, IBKRApiLibError> {
+    let mut order = template(action, quantity, OrderType::Lmt)?;
+    order.lmt_price = check_price("limit_price", limit_price)?;
+    Ok(order)
+}
+
+//==================================================================================================
+/// A limit order on an instrument, at limit_price rounded to its tick on the exchange of its
+/// contract: down for buys and up for sells, so it never pays more than the price asked
+pub fn instrument_limit(
+    instrument: &Instrument,
+    action: Action,
+    quantity: f64,
+    limit_price: f64,
+) -> Result<Order, IBKRApiLibError> {
+    let limit_price = check_price("limit_price", limit_price)?;
+    let direction = RoundDirection::for_limit(&action);
+    let exchange = instrument.contract().exchange.code();
+    limit(
+        action,
+        quantity,
+        instrument.round_to_tick_toward(exchange, limit_price, direction),
+    )
+}
+
+//==================================================================================================
+/// A stop order, sent as a market order once the stop_price trades
+pub fn stop(action: Action, quantity: f64, stop_price: f64) -> Result<Order, IBKRApiLibError> {
+    let mut order = template(action, quantity, OrderType::Stp)?;
+    order.aux_price = check_price("stop_price", stop_price)?;
+    Ok(order)
+}
+
+//==================================================================================================
+/// A stop limit order, sent as a limit order at limit_price once the stop_price trades
+pub fn stop_limit(
+    action: Action,
+    quantity: f64,
+    limit_price: f64,
+    stop_price: f64,
+) -> Result<Order, IBKRApiLibError> {
+    let mut order = template(action, quantity, OrderType::StpLmt)?;
+    order.lmt_price = check_price("limit_price", limit_price)?;
+    order.aux_price = check_price("stop_price", stop_price)?;
+    Ok(order)
+}
+
+//==================================================================================================
+/// A trailing stop order whose stop trails the market by trailing_amount, in the currency of
+/// the contract
+pub fn trailing_stop(
+    action: Action,
+    quantity: f64,
+    trailing_amount: f64,
+) -> Result<Order, IBKRApiLibError> {
+    let mut order = template(action, quantity, OrderType::Trail)?;
+    order.aux_price = check_offset("trailing_amount", trailing_amount)?;
+    Ok(order)
+}
+
+//==================================================================================================
+/// A trailing stop order whose stop trails the market by trailing_percent of the price, e.g.
+/// 1.5 for 1.5%
+pub fn trailing_stop_percent(
+    action: Action,
+    quantity: f64,
+    trailing_percent: f64,
+) -> Result<Order, IBKRApiLibError> {
+    let mut order = template(action, quantity, OrderType::Trail)?;
+    if !(trailing_percent > 0.0 && trailing_percent < 100.0) {
+        return Err(template_error(&format!(
+            "trailing_percent must be between 0 and 100, got {}",
+            trailing_percent
+        )));
+    }
+    order.trailing_percent = trailing_percent;
+    Ok(order)
+}
+
+//==================================================================================================
+/// A market on close order
+pub fn market_on_close(action: Action, quantity: f64) -> Result<Order, IBKRApiLibError> {
+    template(action, quantity, OrderType::Moc)
+}
+
+//==================================================================================================
+/// A relative order, pegged offset better than the NBB for a buy or the NBO for a sell, and
+/// never worse than price_cap if given
+pub fn relative(
+    action: Action,
+    quantity: f64,
+    offset: f64,
+    price_cap: Option<f64>,
+) -> Result<Order, IBKRApiLibError> {
+    let mut order = template(action, quantity, OrderType::Rel)?;
+    order.aux_price = check_offset("offset", offset)?;
+    order.lmt_price = check_cap(price_cap)?;
+    Ok(order)
+}
+
+//==================================================================================================
+/// A midprice order, filled at the midpoint of the NBBO or better, and never worse than
+/// price_cap if given
+pub fn midprice(
+    action: Action,
+    quantity: f64,
+    price_cap: Option<f64>,
+) -> Result<Order, IBKRApiLibError> {
+    let mut order = template(action, quantity, OrderType::MidPrice)?;
+    order.lmt_price = check_cap(price_cap)?;
+    Ok(order)
+}
+
+//==================================================================================================
+/// A VOL option order at volatility_percent, e.g. 40.0 for 40%, of volatility_type 1 (daily)
+/// or 2 (annual), that hedges its delta with an order of hedge_order_type on the underlying
+/// as it fills, at hedge_aux_price if the type needs one, e.g. the offset of a REL hedge.
+/// Set the delta_neutral_con_id and clearing attributes of the hedge on the returned Order
+/// if TWS doesn't pick them.
+pub fn delta_neutral_volatility(
+    action: Action,
+    quantity: f64,
+    volatility_percent: f64,
+    volatility_type: i32,
+    hedge_order_type: OrderType,
+    hedge_aux_price: Option<f64>,
+) -> Result<Order, IBKRApiLibError> {
+    let mut order = template(action, quantity, OrderType::Vol)?;
+    order.volatility = check_change("volatility_percent", volatility_percent)?;
+    if !matches!(volatility_type, 1 | 2) {
+        return Err(template_error(&format!(
+            "volatility_type must be 1 (daily) or 2 (annual), got {}",
+            volatility_type
+        )));
+    }
+    order.volatility_type = volatility_type;
+    if hedge_order_type == OrderType::None {
+        return Err(template_error("hedge_order_type must be given"));
+    }
+    order.delta_neutral_order_type = hedge_order_type;
+    order.delta_neutral_aux_price = match hedge_aux_price {
+        Some(price) => check_price("hedge_aux_price", price)?,
+        None => UNSET_DOUBLE,
+    };
+    Ok(order)
+}
+
+//==================================================================================================
+/// A MKT order for an amount of currency, cash_qty in the currency of the contract, rather
+/// than a quantity, e.g. to buy $500 of bitcoin or convert 10000 EUR into USD
+pub fn cash_market(action: Action, cash_qty: f64) -> Result<Order, IBKRApiLibError> {
+    cash_template(action, cash_qty, OrderType::Mkt)
+}
+
+//==================================================================================================
+/// A LMT order at limit_price for an amount of currency, cash_qty in the currency of the
+/// contract, rather than a quantity
+pub fn cash_limit(
+    action: Action,
+    cash_qty: f64,
+    limit_price: f64,
+) -> Result<Order, IBKRApiLibError> {
+    let mut order = cash_template(action, cash_qty, OrderType::Lmt)?;
+    order.lmt_price = check_price("limit_price", limit_price)?;
+    Ok(order)
+}
+
+//==================================================================================================
+/// A stop at stop_price attached to parent, which must have its order_id, that closes it.
+/// Once trigger_price trades, TWS adjusts the stop as given by adjustment.
+pub fn attach_adjusted_stop(
+    parent: &Order,
+    stop_price: f64,
+    trigger_price: f64,
+    adjustment: StopAdjustment,
+) -> Result<Order, IBKRApiLibError> {
+    let mut order = stop(parent.action.opposite(), parent.total_quantity, stop_price)?;
+    order.parent_id = parent.order_id;
+    order.trigger_price = check_price("trigger_price", trigger_price)?;
+    match adjustment {
+        StopAdjustment::Stop { stop_price } => {
+            order.adjusted_order_type = OrderType::Stp;
+            order.adjusted_stop_price = check_price("stop_price", stop_price)?;
+        }
+        StopAdjustment::StopLimit {
+            stop_price,
+            limit_price,
+        } => {
+            order.adjusted_order_type = OrderType::StpLmt;
+            order.adjusted_stop_price = check_price("stop_price", stop_price)?;
+            order.adjusted_stop_limit_price = check_price("limit_price", limit_price)?;
+        }
+        StopAdjustment::Trail {
+            stop_price,
+            trailing_amount,
+            trailing_unit,
+        } => {
+            order.adjusted_order_type = OrderType::Trail;
+            order.adjusted_stop_price = check_price("stop_price", stop_price)?;
+            order.adjusted_trailing_amount = check_offset("trailing_amount", trailing_amount)?;
+            order.adjustable_trailing_unit = trailing_unit;
+        }
+    }
+    check_adjusted_order(NO_VALID_ID, &order)?;
+    Ok(order)
+}
+
+//==================================================================================================
+/// An FX hedge attached to parent, which must have its order_id: a market order on the pair
+/// of the currency of contract, the contract of the parent, against base_currency, the base
+/// currency of the account.  TWS works out its quantity from each fill of the parent.  The
+/// hedge buys the currency when the parent buys and sells it when the parent sells, so it
+/// sells the pair when the currency is its quote currency, e.g. GBP.USD for a contract in USD
+/// and an account in GBP.
+///
+/// Sets transmit of the parent to false, so TWS holds the parent until the hedge, which
+/// transmits both, is placed.  Place the parent first, then the hedge on the returned pair.
+pub fn attach_fx_hedge(
+    parent: &mut Order,
+    contract: &Contract,
+    base_currency: &Currency,
+) -> Result<(Contract, Order), IBKRApiLibError> {
+    let buys = if parent.action.is_buy() {
+        true
+    } else if parent.action.is_sell() {
+        false
+    } else {
+        return Err(template_error(&format!(
+            "{:?} is not a buy or sell action",
+            parent.action.code()
+        )));
+    };
+    if !contract.currency.is_iso() || !base_currency.is_iso() {
+        return Err(template_error(&format!(
+            "FX hedges need ISO 4217 currencies, not {:?} and {:?}",
+            contract.currency.code(),
+            base_currency.code()
+        )));
+    }
+    if contract.currency == *base_currency {
+        return Err(template_error(&format!(
+            "a contract in {} has no currency to hedge for an account in {}",
+            contract.currency, base_currency
+        )));
+    }
+    // buying the pair buys its base currency
+    let action = match buys == contract.currency.is_pair_base(base_currency) {
+        true => Action::Buy,
+        false => Action::Sell,
+    };
+    let hedge = Order {
+        action,
+        order_type: OrderType::Mkt,
+        total_quantity: 0.0,
+        parent_id: parent.order_id,
+        hedge_type: HedgeType::Fx,
+        transmit: true,
+        ..Default::default()
+    };
+    check_hedge_order(NO_VALID_ID, &hedge)?;
+    parent.transmit = false;
+    Ok((
+        Contract::forex(&contract.currency.market_pair(base_currency)),
+        hedge,
+    ))
+}
+
+//==================================================================================================
+/// Places parent on contract with order_id and an FX hedge of it with order_id + 1, see
+/// attach_fx_hedge.  Returns the order id of the hedge.
+///
+/// If the hedge can't be placed, the parent, which TWS holds until the hedge arrives, is
+/// canceled and the error of the hedge returned.
+pub fn place_with_fx_hedge<T>(
+    client: &mut EClient<T>,
+    order_id: i32,
+    contract: &Contract,
+    parent: &Order,
+    base_currency: &Currency,
+) -> Result<i32, IBKRApiLibError>
+where
+    T: Wrapper + Send + Sync + 'static,
+{
+    let mut parent = parent.clone();
+    parent.order_id = order_id;
+    let (pair, mut hedge) = attach_fx_hedge(&mut parent, contract, base_currency)?;
+    let hedge_id = order_id + 1;
+    hedge.order_id = hedge_id;
+    client.place_order(order_id, contract, &parent)?;
+    if let Err(err) = client.place_order(hedge_id, &pair, &hedge) {
+        client.cancel_order(order_id)?;
+        return Err(err);
+    }
+    Ok(hedge_id)
+}
+
+//==================================================================================================
+/// Builds a PEG BENCH order, whose price starts at starting_price and moves by
+/// pegged_change_amount whenever the price of a reference contract moves by
+/// reference_change_amount.  The order needs the con_id and exchange of the reference, which
+/// build takes from its details and resolve looks up.
+#[derive(Clone, Debug)]
+pub struct PeggedToBenchmark {
+    action: Action,
+    quantity: f64,
+    starting_price: f64,
+    reference: Contract,
+    pegged_change_amount: f64,
+    reference_change_amount: f64,
+    is_decrease: bool,
+    reference_price: f64,
+    reference_range: (f64, f64),
+}
+
+impl PeggedToBenchmark {
+    pub fn new(action: Action, quantity: f64, starting_price: f64, reference: Contract) -> Self {
+        PeggedToBenchmark {
+            action,
+            quantity,
+            starting_price,
+            reference,
+            pegged_change_amount: UNSET_DOUBLE,
+            reference_change_amount: UNSET_DOUBLE,
+            is_decrease: false,
+            reference_price: UNSET_DOUBLE,
+            reference_range: (UNSET_DOUBLE, UNSET_DOUBLE),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Moves the price of the order by pegged_change_amount in the direction of the reference
+    /// for each reference_change_amount the reference moves, or against it if is_decrease
+    pub fn peg(
+        mut self,
+        pegged_change_amount: f64,
+        reference_change_amount: f64,
+        is_decrease: bool,
+    ) -> Self {
+        self.pegged_change_amount = pegged_change_amount;
+        self.reference_change_amount = reference_change_amount;
+        self.is_decrease = is_decrease;
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The price of the reference the starting_price goes with
+    pub fn reference_price(mut self, price: f64) -> Self {
+        self.reference_price = price;
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The prices of the reference outside of which TWS cancels the order
+    pub fn reference_range(mut self, lower: f64, upper: f64) -> Self {
+        self.reference_range = (lower, upper);
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The order for the details of the reference contract.  Its exchange is that of the
+    /// reference contract given to new unless that is SMART or unset, then the primary
+    /// exchange of the details.
+    pub fn build(&self, reference: &ContractDetails) -> Result<Order, IBKRApiLibError> {
+        let mut order = template(self.action.clone(), self.quantity, OrderType::PegBench)?;
+        order.starting_price = check_price("starting_price", self.starting_price)?;
+        order.pegged_change_amount =
+            check_change("pegged_change_amount", self.pegged_change_amount)?;
+        order.reference_change_amount =
+            check_change("reference_change_amount", self.reference_change_amount)?;
+        order.is_pegged_change_amount_decrease = self.is_decrease;
+        if self.reference_price != UNSET_DOUBLE {
+            order.stock_ref_price = check_price("reference_price", self.reference_price)?;
+        }
+        if self.reference_range != (UNSET_DOUBLE, UNSET_DOUBLE) {
+            let (lower, upper) = self.reference_range;
+            if check_price("lower", lower)? >= check_price("upper", upper)? {
+                return Err(template_error(&format!(
+                    "the reference range must be lower than its upper price, got {} to {}",
+                    lower, upper
+                )));
+            }
+            order.stock_range_lower = lower;
+            order.stock_range_upper = upper;
+        }
+
+        let contract = &reference.contract;
+        order.reference_contract_id = contract.con_id;
+        let exchange = [
+            &self.reference.exchange,
+            &contract.primary_exchange,
+            &contract.exchange,
+        ]
+        .iter()
+        .find(|exchange| exchange.is_set() && ***exchange != Exchange::Smart)
+        .map(|exchange| exchange.code().to_string())
+        .unwrap_or_default();
+        order.reference_exchange_id = exchange;
+        check_pegged_to_benchmark(NO_VALID_ID, &order)?;
+        Ok(order)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Looks up the details of the reference contract, then builds the order for them.  Blocks
+    /// until TWS answers, so call it from a thread other than the one processing messages.
+    ///
+    /// Returns an error with code TwsError::InvalidRequest if the reference is unknown or
+    /// matches several contracts.
+    ///
+    /// # Arguments
+    /// * client - the connected client
+    /// * req_id - the id of the contract details request
+    /// * timeout - how long to wait for the details
+    pub fn resolve<T>(
+        &self,
+        client: &mut EClient<T>,
+        req_id: i32,
+        timeout: Duration,
+    ) -> Result<Order, IBKRApiLibError>
+    where
+        T: Wrapper + Send + Sync + 'static,
+    {
+        let found = client
+            .contract_details_bulk(req_id, std::slice::from_ref(&self.reference), 1, timeout)?
+            .pop()
+            .unwrap_or_else(|| Ok(vec![]))?;
+        match found.as_slice() {
+            [details] => self.build(details),
+            [] => Err(template_error(&format!(
+                "no contract matches the reference {}",
+                self.reference.symbol
+            ))),
+            _ => Err(template_error(&format!(
+                "{} contracts match the reference {}, give its con_id or primary_exchange",
+                found.len(),
+                self.reference.symbol
+            ))),
+        }
+    }
+}
+
+//==================================================================================================
+/// Builds a scale order: a LMT order sent in components of init_level_size, the first at
+/// limit_price and each next one price_increment further from the market.  build checks the
+/// order with order::check_scale_order.
+#[derive(Clone, Debug)]
+pub struct ScaleOrderBuilder {
+    action: Action,
+    quantity: f64,
+    limit_price: f64,
+    init_level_size: i32,
+    price_increment: f64,
+    subs_level_size: i32,
+    price_adjust: (f64, i32),
+    profit_offset: f64,
+    auto_reset: bool,
+    init_position: (i32, i32),
+    random_percent: bool,
+}
+
+impl ScaleOrderBuilder {
+    pub fn new(
+        action: Action,
+        quantity: f64,
+        limit_price: f64,
+        init_level_size: i32,
+        price_increment: f64,
+    ) -> Self {
+        ScaleOrderBuilder {
+            action,
+            quantity,
+            limit_price,
+            init_level_size,
+            price_increment,
+            subs_level_size: UNSET_INTEGER,
+            price_adjust: (UNSET_DOUBLE, UNSET_INTEGER),
+            profit_offset: UNSET_DOUBLE,
+            auto_reset: false,
+            init_position: (UNSET_INTEGER, UNSET_INTEGER),
+            random_percent: false,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The size of the components after the first
+    pub fn subsequent_level_size(mut self, size: i32) -> Self {
+        self.subs_level_size = size;
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Moves the price of the components by value every interval_seconds
+    pub fn price_adjust(mut self, value: f64, interval_seconds: i32) -> Self {
+        self.price_adjust = (value, interval_seconds);
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Takes profit offset away from the price of each filled component, and sends the
+    /// component again once the profit taker fills if auto_reset
+    pub fn profit_taker(mut self, offset: f64, auto_reset: bool) -> Self {
+        self.profit_offset = offset;
+        self.auto_reset = auto_reset;
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Starts the order as if position had already been built, filled_quantity of it by the
+    /// components, e.g. when restarting a scale order
+    pub fn initial_position(mut self, position: i32, filled_quantity: i32) -> Self {
+        self.init_position = (position, filled_quantity);
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Makes TWS vary the size of the components at random around their size
+    pub fn random_size(mut self, random: bool) -> Self {
+        self.random_percent = random;
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn build(&self) -> Result<Order, IBKRApiLibError> {
+        let mut order = limit(self.action.clone(), self.quantity, self.limit_price)?;
+        order.scale_init_level_size = self.init_level_size;
+        order.scale_subs_level_size = self.subs_level_size;
+        order.scale_price_increment = self.price_increment;
+        order.scale_price_adjust_value = self.price_adjust.0;
+        order.scale_price_adjust_interval = self.price_adjust.1;
+        order.scale_profit_offset = self.profit_offset;
+        order.scale_auto_reset = self.auto_reset;
+        order.scale_init_position = self.init_position.0;
+        order.scale_init_fill_qty = self.init_position.1;
+        order.scale_random_percent = self.random_percent;
+        check_scale_order(NO_VALID_ID, &order)?;
+        Ok(order)
+    }
+}
+
+//==================================================================================================
+fn template(
+    action: Action,
+    quantity: f64,
+    order_type: OrderType,
+) -> Result<Order, IBKRApiLibError> {
+    if !action.is_buy() && !action.is_sell() {
+        return Err(template_error(&format!(
+            "action must be BUY, SELL, SSHORT or SLONG, got {:?}",
+            action.code()
+        )));
+    }
+    if !(quantity.is_finite() && quantity > 0.0) {
+        return Err(template_error(&format!(
+            "quantity must be greater than 0, got {}",
+            quantity
+        )));
+    }
+    Ok(Order {
+        action,
+        total_quantity: quantity,
+        order_type,
+        ..Default::default()
+    })
+}
+
+//==================================================================================================
+/// An order of a cash quantity, whose total_quantity is 0 as TWS works the quantity out
+fn cash_template(
+    action: Action,
+    cash_qty: f64,
+    order_type: OrderType,
+) -> Result<Order, IBKRApiLibError> {
+    let mut order = template(action, 1.0, order_type)?;
+    order.total_quantity = 0.0;
+    order.cash_qty = check_change("cash_qty", cash_qty)?;
+    check_cash_quantity(NO_VALID_ID, &order)?;
+    Ok(order)
+}
+
+//==================================================================================================
+/// A price may be negative, as those of some spreads are, but not NaN or UNSET_DOUBLE
+fn check_price(name: &str, price: f64) -> Result<f64, IBKRApiLibError> {
+    match price.is_finite() && price != UNSET_DOUBLE {
+        true => Ok(price),
+        false => Err(template_error(&format!(
+            "{} must be a price, got {}",
+            name, price
+        ))),
+    }
+}
+
+//==================================================================================================
+fn check_offset(name: &str, offset: f64) -> Result<f64, IBKRApiLibError> {
+    match offset.is_finite() && offset >= 0.0 {
+        true => Ok(offset),
+        false => Err(template_error(&format!(
+            "{} must be 0 or more, got {}",
+            name, offset
+        ))),
+    }
+}
+
+//==================================================================================================
+fn check_change(name: &str, change: f64) -> Result<f64, IBKRApiLibError> {
+    match change.is_finite() && change > 0.0 && change != UNSET_DOUBLE {
+        true => Ok(change),
+        false => Err(template_error(&format!(
+            "{} must be greater than 0, got {}",
+            name, change
+        ))),
+    }
+}
+
+//==================================================================================================
+fn check_cap(price_cap: Option<f64>) -> Result<f64, IBKRApiLibError> {
+    match price_cap {
+        Some(price) => check_price("price_cap", price),
+        None => Ok(UNSET_DOUBLE),
+    }
+}
+
+//==================================================================================================
+fn template_error(detail: &str) -> IBKRApiLibError {
+    IBKRApiLibError::ApiError(TwsApiReportableError::new(
+        NO_VALID_ID,
+        TwsError::InvalidRequest.code().to_string(),
+        format!("{} {}.", TwsError::InvalidRequest.message(), detail),
+    ))
+}
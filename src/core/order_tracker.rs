@@ -0,0 +1,336 @@
+//! Lifecycle of placed orders
+//!
+//! TWS reports the progress of an order in OpenOrder, OrderStatus, ExecutionData and error
+//! messages, in no guaranteed order and often more than once.  OrderTracker folds them into a
+//! typed OrderPhase per order id, with the filled quantity and average price, and sends an
+//! OrderTransition to every subscriber each time the phase of an order changes.
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use log::*;
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::core::contract::Contract;
+use crate::core::errors::{IbError, TwsMessage};
+use crate::core::execution::Execution;
+use crate::core::log_targets::ORDERS;
+use crate::core::order::{Order, OrderState};
+
+/// How many exec_ids OrderTracker remembers, to skip executions sent again
+pub const EXEC_ID_CAPACITY: usize = 10_000;
+
+//==================================================================================================
+/// Where an order is in its lifecycle
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OrderPhase {
+    /// Placed, not acknowledged by TWS yet
+    #[default]
+    PendingSubmit,
+    /// Accepted, waiting for its conditions or the market to open
+    PreSubmitted,
+    Submitted,
+    /// Working with part of its quantity filled
+    PartiallyFilled,
+    Filled,
+    PendingCancel,
+    Cancelled,
+    Rejected,
+    /// Not working, e.g. waiting for funds or after an error
+    Inactive,
+}
+
+impl OrderPhase {
+    /// The phase of an OrderStatus or OrderState status, None for statuses not describing a
+    /// phase.  This is the one place statuses are classified.
+    pub fn from_status(status: &str, filled: f64) -> Option<Self> {
+        let phase = match status {
+            "PendingSubmit" | "ApiPending" => OrderPhase::PendingSubmit,
+            "PreSubmitted" => OrderPhase::PreSubmitted,
+            "Submitted" => OrderPhase::Submitted,
+            "Filled" => OrderPhase::Filled,
+            "PendingCancel" => OrderPhase::PendingCancel,
+            "Cancelled" | "ApiCancelled" => OrderPhase::Cancelled,
+            "Inactive" => OrderPhase::Inactive,
+            _ => return None,
+        };
+        match phase {
+            OrderPhase::PreSubmitted | OrderPhase::Submitted if filled > 0.0 => {
+                Some(OrderPhase::PartiallyFilled)
+            }
+            _ => Some(phase),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether the order is done: filled, cancelled or rejected
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            OrderPhase::Filled | OrderPhase::Cancelled | OrderPhase::Rejected
+        )
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether the order can still fill: neither terminal nor inactive
+    pub fn is_working(&self) -> bool {
+        !self.is_terminal() && *self != OrderPhase::Inactive
+    }
+}
+
+impl fmt::Display for OrderPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+//==================================================================================================
+/// What is known of an order
+/// total_quantity - 0 until the order was tracked or an OpenOrder received
+/// last_error - the last error TWS sent for the order
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TrackedOrder {
+    pub order_id: i32,
+    pub perm_id: i32,
+    pub contract: Option<Contract>,
+    pub order: Option<Order>,
+    pub phase: OrderPhase,
+    pub total_quantity: f64,
+    pub filled: f64,
+    pub remaining: f64,
+    pub avg_fill_price: f64,
+    pub last_error: Option<TwsMessage>,
+}
+
+impl TrackedOrder {
+    pub fn new(order_id: i32) -> Self {
+        TrackedOrder {
+            order_id,
+            ..Default::default()
+        }
+    }
+}
+
+impl fmt::Display for TrackedOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "order_id: {}, perm_id: {}, phase: {}, filled: {}, remaining: {}, avg_fill_price: {}",
+            self.order_id,
+            self.perm_id,
+            self.phase,
+            self.filled,
+            self.remaining,
+            self.avg_fill_price
+        )
+    }
+}
+
+//==================================================================================================
+/// A change of the phase of an order
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OrderTransition {
+    pub order_id: i32,
+    pub from: OrderPhase,
+    pub to: OrderPhase,
+    pub filled: f64,
+    pub avg_fill_price: f64,
+}
+
+impl fmt::Display for OrderTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "order_id: {}, from: {}, to: {}, filled: {}, avg_fill_price: {}",
+            self.order_id, self.from, self.to, self.filled, self.avg_fill_price
+        )
+    }
+}
+
+//==================================================================================================
+/// Tracks orders from the wrapper callbacks.  Pass it every open_order, order_status,
+/// exec_details and error callback.  Orders placed by this client can be tracked before TWS
+/// reports them; others are tracked from the first message about them.
+///
+/// An order in a terminal phase stays there: reports arriving late, e.g. a Submitted status
+/// after the Filled one, only update its quantities.
+#[derive(Debug, Default)]
+pub struct OrderTracker {
+    orders: HashMap<i32, TrackedOrder>,
+    exec_ids: HashSet<String>,
+    exec_id_order: VecDeque<String>,
+    subscribers: Vec<Sender<OrderTransition>>,
+}
+
+impl OrderTracker {
+    pub fn new() -> Self {
+        OrderTracker::default()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Receives every OrderTransition
+    pub fn subscribe(&mut self) -> Receiver<OrderTransition> {
+        let (sender, receiver) = channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Starts tracking an order as PendingSubmit, e.g. right after EClient::place_order
+    pub fn track(&mut self, order_id: i32, contract: &Contract, order: &Order) {
+        let tracked = self.entry(order_id);
+        tracked.contract = Some(contract.clone());
+        tracked.order = Some(order.clone());
+        tracked.total_quantity = order.total_quantity;
+        if tracked.filled == 0.0 {
+            tracked.remaining = order.total_quantity;
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn get(&self, order_id: i32) -> Option<&TrackedOrder> {
+        self.orders.get(&order_id)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The ids of the orders not in a terminal phase, ascending
+    pub fn working(&self) -> Vec<i32> {
+        let mut working: Vec<i32> = self
+            .orders
+            .values()
+            .filter(|tracked| !tracked.phase.is_terminal())
+            .map(|tracked| tracked.order_id)
+            .collect();
+        working.sort_unstable();
+        working
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Stops tracking an order and returns what was known of it
+    pub fn remove(&mut self, order_id: i32) -> Option<TrackedOrder> {
+        self.orders.remove(&order_id)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Takes a Wrapper::open_order callback
+    pub fn on_open_order(
+        &mut self,
+        order_id: i32,
+        contract: &Contract,
+        order: &Order,
+        order_state: &OrderState,
+    ) -> Option<OrderTransition> {
+        let tracked = self.entry(order_id);
+        tracked.contract = Some(contract.clone());
+        tracked.order = Some(order.clone());
+        tracked.total_quantity = order.total_quantity;
+        if order.perm_id != 0 {
+            tracked.perm_id = order.perm_id;
+        }
+        let filled = tracked.filled;
+        let phase = OrderPhase::from_status(&order_state.status, filled)?;
+        self.transition(order_id, phase)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Takes a Wrapper::order_status callback
+    pub fn on_order_status(
+        &mut self,
+        order_id: i32,
+        status: &str,
+        filled: f64,
+        remaining: f64,
+        avg_fill_price: f64,
+        perm_id: i32,
+    ) -> Option<OrderTransition> {
+        let tracked = self.entry(order_id);
+        if filled >= tracked.filled {
+            tracked.filled = filled;
+            tracked.remaining = remaining;
+            tracked.avg_fill_price = avg_fill_price;
+        }
+        if perm_id != 0 {
+            tracked.perm_id = perm_id;
+        }
+        let phase = OrderPhase::from_status(status, tracked.filled)?;
+        self.transition(order_id, phase)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Takes a Wrapper::exec_details callback.  Executions seen before are ignored, among the
+    /// last EXEC_ID_CAPACITY ones.
+    pub fn on_execution(&mut self, execution: &Execution) -> Option<OrderTransition> {
+        if !self.exec_ids.insert(execution.exec_id.clone()) {
+            return None;
+        }
+        self.exec_id_order.push_back(execution.exec_id.clone());
+        while self.exec_id_order.len() > EXEC_ID_CAPACITY {
+            if let Some(oldest) = self.exec_id_order.pop_front() {
+                self.exec_ids.remove(&oldest);
+            }
+        }
+        let tracked = self.entry(execution.order_id);
+        if execution.perm_id != 0 {
+            tracked.perm_id = execution.perm_id;
+        }
+        let cum_qty = execution.cum_qty.to_f64().unwrap_or(0.0);
+        if cum_qty <= tracked.filled {
+            return None;
+        }
+        tracked.filled = cum_qty;
+        tracked.avg_fill_price = execution.avg_price;
+        if tracked.total_quantity > 0.0 {
+            tracked.remaining = (tracked.total_quantity - tracked.filled).max(0.0);
+        }
+        let phase = if tracked.total_quantity > 0.0 && tracked.filled >= tracked.total_quantity {
+            OrderPhase::Filled
+        } else {
+            OrderPhase::PartiallyFilled
+        };
+        self.transition(execution.order_id, phase)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Takes a Wrapper::error callback, or a message of EClient::subscribe_messages.  Messages
+    /// for ids not tracked and warnings are ignored.
+    pub fn on_error(&mut self, message: &TwsMessage) -> Option<OrderTransition> {
+        if message.is_warning() {
+            return None;
+        }
+        let tracked = self.orders.get_mut(&message.req_id)?;
+        tracked.last_error = Some(message.clone());
+        match message.kind() {
+            IbError::OrderRejected => self.transition(message.req_id, OrderPhase::Rejected),
+            IbError::OrderCancelled => self.transition(message.req_id, OrderPhase::Cancelled),
+            _ => None,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn entry(&mut self, order_id: i32) -> &mut TrackedOrder {
+        self.orders
+            .entry(order_id)
+            .or_insert_with(|| TrackedOrder::new(order_id))
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn transition(&mut self, order_id: i32, phase: OrderPhase) -> Option<OrderTransition> {
+        let tracked = self.orders.get_mut(&order_id)?;
+        if tracked.phase == phase || tracked.phase.is_terminal() {
+            return None;
+        }
+        let transition = OrderTransition {
+            order_id,
+            from: tracked.phase,
+            to: phase,
+            filled: tracked.filled,
+            avg_fill_price: tracked.avg_fill_price,
+        };
+        tracked.phase = phase;
+        debug!(target: ORDERS, "Order transition -- {}", transition);
+        self.subscribers
+            .retain(|subscriber| subscriber.send(transition.clone()).is_ok());
+        Some(transition)
+    }
+}
@@ -0,0 +1,226 @@
+//! Requests whose responses the Decoder intercepts on behalf of EClient convenience methods
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc::Sender;
+use std::sync::{Condvar, Mutex};
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+
+use crate::core::accounts::Accounts;
+use crate::core::common::{BarData, FamilyCode, HistogramEntry, WhatToShow};
+use crate::core::contract::{ContractDescription, ContractDetails, DeltaNeutralContract};
+use crate::core::coverage::FieldCoverage;
+use crate::core::display_groups::{DisplayGroupEvent, DisplayGroupId};
+use crate::core::errors::TwsMessage;
+use crate::core::exchange::Exchange;
+use crate::core::exercise::ExerciseStatus;
+use crate::core::history::{BarUpdate, BarUpdateTracker};
+use crate::core::latency::OrderLatencyRecorder;
+use crate::core::market_rules::MarketRules;
+use crate::core::news_bulletins::Bulletin;
+use crate::core::option_chains::OptionChain;
+use crate::core::order::SoftDollarTier;
+use crate::core::quote::Quote;
+use crate::core::request_context::RequestContexts;
+use crate::core::smart_components::SmartComponents;
+use crate::core::subscriptions::MetadataStore;
+use crate::core::tick_by_tick::TickByTickEvent;
+use crate::core::time_zones::ExchangeTz;
+use crate::core::what_if::MarginPreview;
+
+pub(crate) const PENDING_POISONED_MUTEX: &str = "Pending requests mutex was poisoned";
+
+//==================================================================================================
+/// Historical data requests whose bars carry something other than prices
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum HistoricalBarKind {
+    Yield,
+    FeeRate,
+}
+
+impl HistoricalBarKind {
+    pub(crate) fn from_what_to_show(what_to_show: &WhatToShow) -> Option<Self> {
+        match *what_to_show {
+            WhatToShow::YieldBid
+            | WhatToShow::YieldAsk
+            | WhatToShow::YieldBidAsk
+            | WhatToShow::YieldLast => Some(HistoricalBarKind::Yield),
+            WhatToShow::FeeRate => Some(HistoricalBarKind::FeeRate),
+            _ => None,
+        }
+    }
+}
+
+//==================================================================================================
+/// Receives the bars of a download_history chunk, or the error TWS sent for it
+pub(crate) type HistoryChunkSender = Sender<Result<Vec<BarData>, TwsMessage>>;
+
+//==================================================================================================
+/// Receives the head timestamp of earliest_data_point, or the error TWS sent for it
+pub(crate) type HeadTimestampSender = Sender<Result<DateTime<Utc>, TwsMessage>>;
+
+//==================================================================================================
+/// A response to a request of contract_details_bulk
+pub(crate) enum ContractDetailsEvent {
+    Details(Box<ContractDetails>),
+    End,
+    Error(TwsMessage),
+}
+
+/// Receives the responses of contract_details_bulk with their req_id
+pub(crate) type ContractDetailsSender = Sender<(i32, ContractDetailsEvent)>;
+
+//==================================================================================================
+/// A snapshot quote being accumulated until TickSnapshotEnd arrives
+pub(crate) struct PendingSnapshot {
+    pub(crate) quote: Quote,
+    pub(crate) sender: Sender<Quote>,
+}
+
+//==================================================================================================
+/// The option chains of a req_sec_def_opt_params request being accumulated until its end
+pub(crate) struct PendingOptionChains {
+    pub(crate) chains: Vec<OptionChain>,
+    pub(crate) sender: Sender<Vec<OptionChain>>,
+}
+
+//==================================================================================================
+/// A display group subscribed to with subscribe_display_group and the receiver of its events
+pub(crate) struct PendingDisplayGroup {
+    pub(crate) group_id: DisplayGroupId,
+    pub(crate) sender: Sender<DisplayGroupEvent>,
+}
+
+//==================================================================================================
+/// The bar updates of a keep_up_to_date historical data request and their subscriber
+pub(crate) struct PendingBarUpdates {
+    pub(crate) tracker: BarUpdateTracker,
+    pub(crate) sender: Sender<BarUpdate>,
+}
+
+//==================================================================================================
+/// The exchange time zone of the bars of a historical data request, and the time of its last
+/// bar to read the repeated hour of the end of daylight saving with.  Removed at the end of
+/// the historical data unless the request keeps it up to date.
+pub(crate) struct PendingBarTimeZone {
+    pub(crate) zone: ExchangeTz,
+    pub(crate) last: Option<DateTime<Utc>>,
+    pub(crate) keep_up_to_date: bool,
+}
+
+//==================================================================================================
+/// State shared between EClient and Decoder.  EClient registers a request here before sending
+/// it, and the Decoder routes the matching responses to the registered entry.
+#[derive(Default)]
+pub(crate) struct PendingRequests {
+    /// what-if orders keyed by order id
+    pub(crate) what_if: Mutex<HashMap<i32, Sender<MarginPreview>>>,
+    /// delta neutral orders of place_delta_neutral keyed by order id
+    pub(crate) delta_neutral_validations: Mutex<HashMap<i32, Sender<DeltaNeutralContract>>>,
+    /// option exercises of exercise keyed by req_id, until their status is terminal
+    pub(crate) exercises: Mutex<HashMap<i32, Sender<ExerciseStatus>>>,
+    /// non-price historical data requests keyed by req_id
+    pub(crate) historical_bar_kinds: Mutex<HashMap<i32, HistoricalBarKind>>,
+    /// historical data chunks of download_history keyed by req_id
+    pub(crate) historical_downloads: Mutex<HashMap<i32, HistoryChunkSender>>,
+    /// contract details requests of contract_details_bulk keyed by req_id
+    pub(crate) contract_details: Mutex<HashMap<i32, ContractDetailsSender>>,
+    /// display groups of display_groups keyed by req_id
+    pub(crate) display_group_lists: Mutex<HashMap<i32, Sender<Vec<DisplayGroupId>>>>,
+    /// display group subscriptions of subscribe_display_group keyed by req_id
+    pub(crate) display_group_events: Mutex<HashMap<i32, PendingDisplayGroup>>,
+    /// receivers of the next family codes of family_codes
+    pub(crate) family_codes: Mutex<Vec<Sender<Vec<FamilyCode>>>>,
+    /// head timestamps of earliest_data_point keyed by req_id
+    pub(crate) head_timestamps: Mutex<HashMap<i32, HeadTimestampSender>>,
+    /// histograms of req_histogram keyed by req_id
+    pub(crate) histograms: Mutex<HashMap<i32, Sender<Vec<HistogramEntry>>>>,
+    /// option chains of option_chains keyed by req_id
+    pub(crate) option_chains: Mutex<HashMap<i32, PendingOptionChains>>,
+    /// soft dollar tiers of req_soft_dollar_tiers keyed by req_id
+    pub(crate) soft_dollar_tiers: Mutex<HashMap<i32, Sender<Vec<SoftDollarTier>>>>,
+    /// matching symbols of search_symbols keyed by req_id
+    pub(crate) symbol_searches: Mutex<HashMap<i32, Sender<Vec<ContractDescription>>>>,
+    /// snapshot quotes keyed by req_id
+    pub(crate) snapshots: Mutex<HashMap<i32, PendingSnapshot>>,
+    /// subscribers of tick-by-tick data keyed by req_id
+    pub(crate) tick_by_tick: Mutex<HashMap<i32, Sender<TickByTickEvent>>>,
+    /// exchange time zones of the bars of historical data requests keyed by req_id
+    pub(crate) bar_time_zones: Mutex<HashMap<i32, PendingBarTimeZone>>,
+    /// subscribed bar updates of keep_up_to_date historical data requests keyed by req_id
+    pub(crate) bar_updates: Mutex<HashMap<i32, PendingBarUpdates>>,
+    /// timestamps of the place order path
+    pub(crate) order_latency: Mutex<OrderLatencyRecorder>,
+    /// decode leniently and record field coverage
+    pub(crate) lenient_decoding: AtomicBool,
+    /// unparsed fields of the decoded messages, recorded in lenient mode
+    pub(crate) field_coverage: Mutex<FieldCoverage>,
+    /// receivers of the news bulletins of subscribe_news_bulletins
+    pub(crate) news_bulletins: Mutex<Vec<Sender<Bulletin>>>,
+    /// receivers of every error and warning message
+    pub(crate) message_listeners: Mutex<Vec<Sender<TwsMessage>>>,
+    /// when the last message was decoded
+    pub(crate) last_inbound: Mutex<Option<Instant>>,
+    /// the managed accounts of the current connection and their aliases
+    pub(crate) accounts: Mutex<Accounts>,
+    /// guards the waits of EClient::wait_until_ready for session_changed
+    pub(crate) session: Mutex<()>,
+    /// signalled when the next valid id or the managed accounts arrive, and when the
+    /// connection closes
+    pub(crate) session_changed: Condvar,
+    /// the contexts of the requests sent, keyed by req_id
+    pub(crate) request_contexts: Mutex<RequestContexts>,
+    /// the RequestMetadata of EClient::track_metadata, whose entries end with their requests
+    pub(crate) metadata: Mutex<Vec<Box<dyn MetadataStore>>>,
+    /// the last next valid order id, None until TWS accepted the client
+    pub(crate) next_valid_id: Mutex<Option<i32>>,
+    /// the price increments of the market rules received
+    pub(crate) market_rules: Mutex<MarketRules>,
+    /// the order_types of the contract details received, keyed by con_id and exchange, as a
+    /// contract accepts other order types on each exchange
+    pub(crate) order_types: Mutex<HashMap<(i32, Exchange), String>>,
+    /// the bbo_exchange of req_smart_components requests keyed by req_id
+    pub(crate) smart_component_requests: Mutex<HashMap<i32, String>>,
+    /// the bbo_exchanges of market data requests and their smart component maps
+    pub(crate) smart_components: Mutex<SmartComponents>,
+}
+
+impl PendingRequests {
+    /// Wakes the waits of EClient::wait_until_ready to check the session again
+    pub(crate) fn notify_session_changed(&self) {
+        let _session = self.session.lock().expect(PENDING_POISONED_MUTEX);
+        self.session_changed.notify_all();
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Drops the context and the tracked metadata of a request that was cancelled or failed
+    pub(crate) fn forget_request(&self, req_id: i32) {
+        self.request_contexts
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .forget(req_id);
+        self.forget_metadata(req_id);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Drops the context and the tracked metadata of a request whose end message arrived,
+    /// unless the request keeps sending updates until it is cancelled
+    pub(crate) fn end_request(&self, req_id: i32) {
+        let ended = self
+            .request_contexts
+            .lock()
+            .expect(PENDING_POISONED_MUTEX)
+            .end(req_id);
+        if ended {
+            self.forget_metadata(req_id);
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn forget_metadata(&self, req_id: i32) {
+        for metadata in self.metadata.lock().expect(PENDING_POISONED_MUTEX).iter() {
+            metadata.forget(req_id);
+        }
+    }
+}
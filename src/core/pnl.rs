@@ -0,0 +1,373 @@
+//! Live profit and loss of an account and its positions
+//!
+//! PnlTracker requests the P&L of an account with req_pnl and of each of its positions with
+//! req_pnl_single, and keeps the latest values.  Feed it from Wrapper::pnl and
+//! Wrapper::pnl_single; snapshot returns the consolidated view and subscribe a stream of the
+//! values as they change.
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::client::EClient;
+use crate::core::common::UNSET_DOUBLE;
+use crate::core::errors::IBKRApiLibError;
+use crate::core::freshness::Freshness;
+use crate::core::portfolio::Portfolio;
+use crate::core::wrapper::Wrapper;
+
+//==================================================================================================
+/// Daily, unrealized and realized P&L.  None where TWS has no value yet.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct PnlValues {
+    pub daily: Option<f64>,
+    pub unrealized: Option<f64>,
+    pub realized: Option<f64>,
+}
+
+impl PnlValues {
+    /// The values of a pnl or pnl_single callback, with UNSET_DOUBLE as None
+    pub fn new(daily: f64, unrealized: f64, realized: f64) -> Self {
+        let value = |value: f64| {
+            if value == UNSET_DOUBLE {
+                None
+            } else {
+                Some(value)
+            }
+        };
+        PnlValues {
+            daily: value(daily),
+            unrealized: value(unrealized),
+            realized: value(realized),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The sum of the values, each None only if it is None in both
+    pub fn add(&self, other: &PnlValues) -> PnlValues {
+        let add = |a: Option<f64>, b: Option<f64>| match (a, b) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+        PnlValues {
+            daily: add(self.daily, other.daily),
+            unrealized: add(self.unrealized, other.unrealized),
+            realized: add(self.realized, other.realized),
+        }
+    }
+}
+
+impl fmt::Display for PnlValues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "daily: {:?}, unrealized: {:?}, realized: {:?}",
+            self.daily, self.unrealized, self.realized
+        )
+    }
+}
+
+//==================================================================================================
+/// The P&L of a position
+/// value - market value of the position
+/// updated - when the last pnl_single callback of the position was recorded
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct PositionPnl {
+    pub con_id: i32,
+    pub position: i32,
+    pub pnl: PnlValues,
+    pub value: Option<f64>,
+    #[serde(skip)]
+    pub updated: Option<Instant>,
+}
+
+impl Freshness for PositionPnl {
+    fn last_update(&self) -> Option<Instant> {
+        self.updated
+    }
+}
+
+//==================================================================================================
+/// What a PnlUpdate is about
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PnlScope {
+    Account,
+    Position(i32),
+}
+
+//==================================================================================================
+/// New values of the account or a position
+/// position, value - the size and market value of a position, None for the account
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct PnlUpdate {
+    pub scope: PnlScope,
+    pub pnl: PnlValues,
+    pub position: Option<i32>,
+    pub value: Option<f64>,
+}
+
+//==================================================================================================
+/// The P&L known at one moment
+/// account - what TWS reported for the whole account
+/// positions - each tracked position by con_id, once TWS reported it
+/// updated - when the last pnl callback of the account was recorded
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct PnlSnapshot {
+    pub account: PnlValues,
+    pub positions: BTreeMap<i32, PositionPnl>,
+    #[serde(skip)]
+    pub updated: Option<Instant>,
+}
+
+impl Freshness for PnlSnapshot {
+    /// When the account P&L was last reported; each position has its own
+    fn last_update(&self) -> Option<Instant> {
+        self.updated
+    }
+}
+
+impl PnlSnapshot {
+    /// The sum over the positions.  It differs from account by the P&L of positions closed
+    /// today and of those not tracked.
+    pub fn positions_total(&self) -> PnlValues {
+        self.positions
+            .values()
+            .fold(PnlValues::default(), |total, position| {
+                total.add(&position.pnl)
+            })
+    }
+}
+
+//==================================================================================================
+/// Tracks the P&L of one account, or of one model within it
+pub struct PnlTracker {
+    account: String,
+    model_code: String,
+    next_req_id: i32,
+    account_req_id: Option<i32>,
+    positions: HashMap<i32, i32>,
+    snapshot: PnlSnapshot,
+    subscribers: Vec<Sender<PnlUpdate>>,
+}
+
+impl PnlTracker {
+    /// # Arguments
+    /// * account - the account
+    /// * model_code - the model, "" for the whole account
+    /// * first_req_id - the requests use consecutive request ids from this one
+    pub fn new(account: &str, model_code: &str, first_req_id: i32) -> Self {
+        PnlTracker {
+            account: account.to_string(),
+            model_code: model_code.to_string(),
+            next_req_id: first_req_id,
+            account_req_id: None,
+            positions: HashMap::new(),
+            snapshot: PnlSnapshot::default(),
+            subscribers: vec![],
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Receives a PnlUpdate every time values change
+    pub fn subscribe(&mut self) -> Receiver<PnlUpdate> {
+        let (sender, receiver) = channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn snapshot(&self) -> PnlSnapshot {
+        self.snapshot.clone()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The con_ids of the tracked positions
+    pub fn tracked(&self) -> Vec<i32> {
+        let mut tracked: Vec<i32> = self.positions.values().copied().collect();
+        tracked.sort_unstable();
+        tracked
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Requests the P&L of the account, once
+    pub fn start<T>(&mut self, client: &mut EClient<T>) -> Result<(), IBKRApiLibError>
+    where
+        T: Wrapper + Send + Sync + 'static,
+    {
+        if self.account_req_id.is_none() {
+            let req_id = self.take_req_id();
+            client.req_pnl(req_id, &self.account, &self.model_code)?;
+            self.account_req_id = Some(req_id);
+        }
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Requests the P&L of a position, unless it is tracked already, and returns its req_id
+    pub fn track_position<T>(
+        &mut self,
+        client: &mut EClient<T>,
+        con_id: i32,
+    ) -> Result<i32, IBKRApiLibError>
+    where
+        T: Wrapper + Send + Sync + 'static,
+    {
+        if let Some(req_id) = self.req_id_of(con_id) {
+            return Ok(req_id);
+        }
+        let req_id = self.take_req_id();
+        client.req_pnl_single(req_id, &self.account, &self.model_code, con_id)?;
+        self.positions.insert(req_id, con_id);
+        Ok(req_id)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Cancels the P&L of a position and drops it from the snapshot
+    pub fn untrack_position<T>(
+        &mut self,
+        client: &mut EClient<T>,
+        con_id: i32,
+    ) -> Result<(), IBKRApiLibError>
+    where
+        T: Wrapper + Send + Sync + 'static,
+    {
+        if let Some(req_id) = self.req_id_of(con_id) {
+            self.positions.remove(&req_id);
+            self.snapshot.positions.remove(&con_id);
+            client.cancel_pnl_single(req_id)?;
+        }
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Tracks every position the portfolio holds in the account and untracks the others
+    pub fn sync_positions<T>(
+        &mut self,
+        client: &mut EClient<T>,
+        portfolio: &Portfolio,
+    ) -> Result<(), IBKRApiLibError>
+    where
+        T: Wrapper + Send + Sync + 'static,
+    {
+        let held: Vec<i32> = portfolio
+            .positions()
+            .iter()
+            .filter(|position| position.account == self.account)
+            .map(|position| position.contract.con_id)
+            .collect();
+        for con_id in self.tracked() {
+            if !held.contains(&con_id) {
+                self.untrack_position(client, con_id)?;
+            }
+        }
+        for con_id in held {
+            self.track_position(client, con_id)?;
+        }
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Cancels every request
+    pub fn stop<T>(&mut self, client: &mut EClient<T>) -> Result<(), IBKRApiLibError>
+    where
+        T: Wrapper + Send + Sync + 'static,
+    {
+        for con_id in self.tracked() {
+            self.untrack_position(client, con_id)?;
+        }
+        if let Some(req_id) = self.account_req_id.take() {
+            client.cancel_pnl(req_id)?;
+        }
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Records a Wrapper::pnl callback.  Returns false if it is for another request.
+    pub fn on_pnl(&mut self, req_id: i32, daily: f64, unrealized: f64, realized: f64) -> bool {
+        if self.account_req_id != Some(req_id) {
+            return false;
+        }
+        let pnl = PnlValues::new(daily, unrealized, realized);
+        self.snapshot.updated = Some(Instant::now());
+        if pnl != self.snapshot.account {
+            self.snapshot.account = pnl;
+            self.publish(PnlUpdate {
+                scope: PnlScope::Account,
+                pnl,
+                position: None,
+                value: None,
+            });
+        }
+        true
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Records a Wrapper::pnl_single callback.  Returns false if it is for another request.
+    pub fn on_pnl_single(
+        &mut self,
+        req_id: i32,
+        position: i32,
+        daily: f64,
+        unrealized: f64,
+        realized: f64,
+        value: f64,
+    ) -> bool {
+        let con_id = match self.positions.get(&req_id) {
+            Some(con_id) => *con_id,
+            None => return false,
+        };
+        let pnl = PnlValues::new(daily, unrealized, realized);
+        let value = if value == UNSET_DOUBLE {
+            None
+        } else {
+            Some(value)
+        };
+        let changed = !matches!(
+            self.snapshot.positions.get(&con_id),
+            Some(previous)
+                if previous.pnl == pnl && previous.position == position && previous.value == value
+        );
+        self.snapshot.positions.insert(
+            con_id,
+            PositionPnl {
+                con_id,
+                position,
+                pnl,
+                value,
+                updated: Some(Instant::now()),
+            },
+        );
+        if changed {
+            self.publish(PnlUpdate {
+                scope: PnlScope::Position(con_id),
+                pnl,
+                position: Some(position),
+                value,
+            });
+        }
+        true
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn req_id_of(&self, con_id: i32) -> Option<i32> {
+        self.positions
+            .iter()
+            .find(|(_, tracked)| **tracked == con_id)
+            .map(|(req_id, _)| *req_id)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn take_req_id(&mut self) -> i32 {
+        let req_id = self.next_req_id;
+        self.next_req_id += 1;
+        req_id
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn publish(&mut self, update: PnlUpdate) {
+        self.subscribers
+            .retain(|subscriber| subscriber.send(update).is_ok());
+    }
+}
@@ -0,0 +1,355 @@
+//! Several connections to the same TWS or IB Gateway, sharing out market data requests
+//!
+//! IB limits the market data lines each connection may hold.  ClientPool connects with several
+//! client ids and sends each streaming market data request over the connection with the most
+//! lines free, so the user requests by req_id as on a single EClient.  Requests other than
+//! market data go through primary, the first connection.  A request TWS rejects frees its line
+//! like a cancelled one.
+use std::collections::HashMap;
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
+
+use log::*;
+
+use crate::core::client::EClient;
+use crate::core::common::{BarSize, TagValue, TickByTickType, WhatToShow, NO_VALID_ID};
+use crate::core::contract::Contract;
+use crate::core::errors::{IBKRApiLibError, IbError, TwsApiReportableError, TwsError, TwsMessage};
+use crate::core::log_targets::{CONNECTION, MARKET_DATA};
+use crate::core::wrapper::Wrapper;
+
+//==================================================================================================
+/// Connections with distinct client ids, holding at most max_lines market data requests each
+pub struct ClientPool<T>
+where
+    T: Wrapper,
+{
+    clients: Vec<EClient<T>>,
+    /// the error messages of each connection, to free the lines of rejected requests
+    messages: Vec<Receiver<TwsMessage>>,
+    max_lines: usize,
+    assignments: HashMap<i32, usize>,
+}
+
+impl<T> ClientPool<T>
+where
+    T: Wrapper + Send + Sync + 'static,
+{
+    /// Connects once per client id, all connections calling the same wrapper.  If a connection
+    /// fails, the ones already made are shut down and the error is returned.  Returns an error
+    /// with code TwsError::InvalidRequest if client_ids is empty.
+    ///
+    /// # Arguments
+    /// * wrapper - receives the callbacks of every connection.  req_ids must be unique across
+    ///   the pool for the callbacks to be told apart.
+    /// * host, port - the TWS or IB Gateway to connect to
+    /// * client_ids - one connection is made per id, the first becomes the primary
+    /// * max_lines - market data lines each connection may hold
+    pub fn connect(
+        wrapper: Arc<Mutex<T>>,
+        host: &str,
+        port: u32,
+        client_ids: &[i32],
+        max_lines: usize,
+    ) -> Result<Self, IBKRApiLibError> {
+        if client_ids.is_empty() {
+            return Err(no_clients());
+        }
+        let mut clients: Vec<EClient<T>> = Vec::with_capacity(client_ids.len());
+        for client_id in client_ids {
+            let mut client = EClient::new(wrapper.clone());
+            if let Err(err) = client.connect(host, port, *client_id) {
+                for connected in clients.iter_mut() {
+                    // keep going, the connect error is the one to report
+                    if let Err(shutdown_err) = connected.shutdown() {
+                        warn!(
+                            target: CONNECTION,
+                            "Could not shut down pooled connection: {}", shutdown_err
+                        );
+                    }
+                }
+                return Err(err);
+            }
+            clients.push(client);
+        }
+        ClientPool::from_clients(clients, max_lines)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Pools clients connected by the caller.  The first becomes the primary.  Returns an error
+    /// with code TwsError::InvalidRequest if there are none.
+    pub fn from_clients(
+        mut clients: Vec<EClient<T>>,
+        max_lines: usize,
+    ) -> Result<Self, IBKRApiLibError> {
+        if clients.is_empty() {
+            return Err(no_clients());
+        }
+        let messages = clients
+            .iter_mut()
+            .map(|client| client.subscribe_messages())
+            .collect();
+        Ok(ClientPool {
+            clients,
+            messages,
+            max_lines,
+            assignments: HashMap::new(),
+        })
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The connection for requests other than market data, e.g. orders and account data
+    pub fn primary(&mut self) -> &mut EClient<T> {
+        &mut self.clients[0]
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Every connection, in the order of their client ids
+    pub fn clients(&mut self) -> &mut [EClient<T>] {
+        &mut self.clients
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Market data lines in use on each connection
+    pub fn lines_in_use(&mut self) -> Vec<usize> {
+        self.release_rejected();
+        self.count_lines()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Index in clients of the connection holding a request
+    pub fn connection_of(&mut self, req_id: i32) -> Option<usize> {
+        self.release_rejected();
+        self.assignments.get(&req_id).copied()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Requests market data on the connection with the most lines free.  Snapshots don't hold
+    /// a line once delivered, so they are sent on the primary outside of the line budget.  See
+    /// EClient::req_mkt_data.
+    pub fn req_mkt_data(
+        &mut self,
+        req_id: i32,
+        contract: &Contract,
+        generic_tick_list: &str,
+        snapshot: bool,
+        regulatory_snapshot: bool,
+        mkt_data_options: Vec<TagValue>,
+    ) -> Result<(), IBKRApiLibError> {
+        let index = if snapshot {
+            0
+        } else {
+            self.least_loaded(req_id)?
+        };
+        self.clients[index].req_mkt_data(
+            req_id,
+            contract,
+            generic_tick_list,
+            snapshot,
+            regulatory_snapshot,
+            mkt_data_options,
+        )?;
+        if !snapshot {
+            self.assign(req_id, index);
+        }
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn cancel_mkt_data(&mut self, req_id: i32) -> Result<(), IBKRApiLibError> {
+        let index = self.release(req_id);
+        self.clients[index].cancel_mkt_data(req_id)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Requests tick by tick data on the connection with the most lines free.  See
+    /// EClient::req_tick_by_tick_data.
+    pub fn req_tick_by_tick_data(
+        &mut self,
+        req_id: i32,
+        contract: &Contract,
+        tick_type: TickByTickType,
+        number_of_ticks: i32,
+        ignore_size: bool,
+    ) -> Result<(), IBKRApiLibError> {
+        let index = self.least_loaded(req_id)?;
+        self.clients[index].req_tick_by_tick_data(
+            req_id,
+            contract,
+            tick_type,
+            number_of_ticks,
+            ignore_size,
+        )?;
+        self.assign(req_id, index);
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn cancel_tick_by_tick_data(&mut self, req_id: i32) -> Result<(), IBKRApiLibError> {
+        let index = self.release(req_id);
+        self.clients[index].cancel_tick_by_tick_data(req_id)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Requests market depth on the connection with the most lines free.  See
+    /// EClient::req_mkt_depth.
+    pub fn req_mkt_depth(
+        &mut self,
+        req_id: i32,
+        contract: &Contract,
+        num_rows: i32,
+        is_smart_depth: bool,
+        mkt_depth_options: Vec<TagValue>,
+    ) -> Result<(), IBKRApiLibError> {
+        let index = self.least_loaded(req_id)?;
+        self.clients[index].req_mkt_depth(
+            req_id,
+            contract,
+            num_rows,
+            is_smart_depth,
+            mkt_depth_options,
+        )?;
+        self.assign(req_id, index);
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn cancel_mkt_depth(
+        &mut self,
+        req_id: i32,
+        is_smart_depth: bool,
+    ) -> Result<(), IBKRApiLibError> {
+        let index = self.release(req_id);
+        self.clients[index].cancel_mkt_depth(req_id, is_smart_depth)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Requests real time bars on the connection with the most lines free.  See
+    /// EClient::req_real_time_bars.
+    pub fn req_real_time_bars(
+        &mut self,
+        req_id: i32,
+        contract: &Contract,
+        bar_size: &BarSize,
+        what_to_show: &WhatToShow,
+        use_rth: bool,
+        real_time_bars_options: Vec<TagValue>,
+    ) -> Result<(), IBKRApiLibError> {
+        let index = self.least_loaded(req_id)?;
+        self.clients[index].req_real_time_bars(
+            req_id,
+            contract,
+            bar_size,
+            what_to_show,
+            use_rth,
+            real_time_bars_options,
+        )?;
+        self.assign(req_id, index);
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn cancel_real_time_bars(&mut self, req_id: i32) -> Result<(), IBKRApiLibError> {
+        let index = self.release(req_id);
+        self.clients[index].cancel_real_time_bars(req_id)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Shuts down every connection, see EClient::shutdown.  Every connection is shut down even
+    /// if one fails; the first error is returned.
+    pub fn shutdown(&mut self) -> Result<(), IBKRApiLibError> {
+        self.assignments.clear();
+        let mut result = Ok(());
+        for client in self.clients.iter_mut() {
+            if let Err(err) = client.shutdown() {
+                if result.is_ok() {
+                    result = Err(err);
+                }
+            }
+        }
+        result
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn least_loaded(&mut self, req_id: i32) -> Result<usize, IBKRApiLibError> {
+        let lines = self.lines_in_use();
+        lines
+            .iter()
+            .enumerate()
+            .filter(|(index, used)| **used < self.max_lines && self.clients[*index].is_connected())
+            .min_by_key(|(_, used)| **used)
+            .map(|(index, _)| index)
+            .ok_or_else(|| {
+                IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                    req_id,
+                    TwsError::NoLinesLeft.code().to_string(),
+                    format!(
+                        "{} {} connections hold {} lines each.",
+                        TwsError::NoLinesLeft.message(),
+                        self.clients.len(),
+                        self.max_lines
+                    ),
+                ))
+            })
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn assign(&mut self, req_id: i32, index: usize) {
+        debug!(
+            target: MARKET_DATA,
+            "Request {} assigned to pooled connection {}", req_id, index
+        );
+        self.assignments.insert(req_id, index);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Frees the line of a request and returns its connection.  Requests not held, such as
+    /// snapshots, are cancelled on the primary.
+    fn release(&mut self, req_id: i32) -> usize {
+        self.assignments.remove(&req_id).unwrap_or(0)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Frees the lines of the requests their connection reported an error for, as TWS holds no
+    /// line for a rejected request.  Informational codes, e.g. of delayed data, keep the line.
+    fn release_rejected(&mut self) {
+        for (index, messages) in self.messages.iter().enumerate() {
+            for message in messages.try_iter() {
+                if IbError::from_code(message.code).is_informational()
+                    || self.assignments.get(&message.req_id) != Some(&index)
+                {
+                    continue;
+                }
+                debug!(
+                    target: MARKET_DATA,
+                    "Request {} rejected with {}, freeing its line on pooled connection {}",
+                    message.req_id,
+                    message.code,
+                    index
+                );
+                self.assignments.remove(&message.req_id);
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn count_lines(&self) -> Vec<usize> {
+        let mut lines = vec![0; self.clients.len()];
+        for index in self.assignments.values() {
+            lines[*index] += 1;
+        }
+        lines
+    }
+}
+
+//==================================================================================================
+fn no_clients() -> IBKRApiLibError {
+    IBKRApiLibError::ApiError(TwsApiReportableError::new(
+        NO_VALID_ID,
+        TwsError::InvalidRequest.code().to_string(),
+        format!(
+            "{} A client pool needs at least one connection.",
+            TwsError::InvalidRequest.message()
+        ),
+    ))
+}
@@ -0,0 +1,365 @@
+//! Positions held and orders that close them
+//!
+//! Portfolio keeps the positions of Wrapper::position or Wrapper::position_multi current:
+//! refresh requests them, on_position_end replaces the positions of the refreshed accounts with
+//! the ones the refresh reported, and on_execution applies fills as they happen.
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::core::accounts::Accounts;
+use crate::core::client::EClient;
+use crate::core::contract::Contract;
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+use crate::core::execution::Execution;
+use crate::core::order::{Action, Order, OrderType};
+use crate::core::wrapper::Wrapper;
+
+/// order_ref of the orders made by Portfolio::closing_order which close a position, including
+/// a flip in one order
+pub const CLOSE_ORDER_REF: &str = "close";
+/// order_ref of the order opening the opposite position when a flip is split
+pub const OPEN_ORDER_REF: &str = "open";
+
+//==================================================================================================
+/// A position as reported by Wrapper::position
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Position {
+    pub account: String,
+    pub contract: Contract,
+    pub position: f64,
+    pub avg_cost: f64,
+}
+
+impl Position {
+    pub fn new(account: &str, contract: Contract, position: f64, avg_cost: f64) -> Self {
+        Position {
+            account: account.to_string(),
+            contract,
+            position,
+            avg_cost,
+        }
+    }
+}
+
+impl Position {
+    /// The position as Display shows it, with the display name of the account, e.g. its alias
+    pub fn display_with(&self, accounts: &Accounts) -> String {
+        self.describe(&accounts.display_name(&self.account))
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn describe(&self, account: &str) -> String {
+        format!(
+            "account: {}, con_id: {}, symbol: {}, position: {}, avg_cost: {}",
+            account, self.contract.con_id, self.contract.symbol, self.position, self.avg_cost
+        )
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.describe(&self.account))
+    }
+}
+
+//==================================================================================================
+/// How Portfolio::closing_order builds orders
+/// split_flips - when the quantity exceeds the position, return one order closing the position
+///   and one opening the opposite position instead of a single order
+/// set_open_close - set Order::open_close to "C" on closing orders and "O" on opening orders.
+///   Only institutional accounts use the field; TWS rejects it on some other accounts.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+pub struct ClosingOrderConfig {
+    pub split_flips: bool,
+    pub set_open_close: bool,
+}
+
+impl ClosingOrderConfig {
+    pub fn new(split_flips: bool, set_open_close: bool) -> Self {
+        ClosingOrderConfig {
+            split_flips,
+            set_open_close,
+        }
+    }
+}
+
+//==================================================================================================
+/// Positions by account and contract id.  Feed it from Wrapper::position and position_end
+/// after EClient::req_positions or refresh, and from Wrapper::exec_details.
+#[derive(Clone, Debug, Default)]
+pub struct Portfolio {
+    positions: HashMap<(String, i32), Position>,
+    config: ClosingOrderConfig,
+    /// the refresh in progress
+    refreshed: Option<Refresh>,
+    /// executions applied, kept across refreshes as TWS may send an execution again after it
+    executions: HashSet<String>,
+}
+
+/// The account a refresh requested, None for every account, and the positions it reported
+#[derive(Clone, Debug, Default)]
+struct Refresh {
+    account: Option<String>,
+    reported: HashSet<(String, i32)>,
+}
+
+impl Portfolio {
+    pub fn new(config: ClosingOrderConfig) -> Self {
+        Portfolio {
+            positions: HashMap::new(),
+            config,
+            refreshed: None,
+            executions: HashSet::new(),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Requests the positions of every account with req_positions.  TWS keeps sending updates
+    /// after position_end until cancel_positions.
+    pub fn refresh<T>(&mut self, client: &mut EClient<T>) -> Result<(), IBKRApiLibError>
+    where
+        T: Wrapper + Send + Sync + 'static,
+    {
+        client.req_positions()?;
+        self.refreshed = Some(Refresh::default());
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Requests the positions of an account or model with req_positions_multi.  Feed the
+    /// position_multi callbacks to on_position and position_multi_end to on_position_end.
+    pub fn refresh_multi<T>(
+        &mut self,
+        client: &mut EClient<T>,
+        req_id: i32,
+        account: &str,
+        model_code: &str,
+    ) -> Result<(), IBKRApiLibError>
+    where
+        T: Wrapper + Send + Sync + 'static,
+    {
+        client.req_positions_multi(req_id, account, model_code)?;
+        self.refreshed = Some(Refresh {
+            account: Some(account.to_string()).filter(|account| !account.is_empty()),
+            reported: HashSet::new(),
+        });
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Ends a refresh: the positions of the refreshed accounts become the ones it reported, so
+    /// the positions it didn't report are closed and removed, also in accounts it reported no
+    /// position for.  A refresh_multi of one account leaves the others alone.
+    pub fn on_position_end(&mut self) {
+        if let Some(refresh) = self.refreshed.take() {
+            self.positions.retain(|key, _| {
+                refresh.reported.contains(key)
+                    || matches!(&refresh.account, Some(account) if *account != key.0)
+            });
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Applies a fill to the position of its account.  Buying adds to the position, selling
+    /// takes from it; the average cost, price times multiplier, moves only when the position
+    /// grows or flips.  An execution is applied once, when the same exec_id comes again, e.g.
+    /// from req_executions, it is ignored.
+    pub fn on_execution(&mut self, contract: &Contract, execution: &Execution) {
+        if !self.executions.insert(execution.exec_id.clone()) {
+            return;
+        }
+        let shares = execution.shares.to_f64().unwrap_or(0.0);
+        let delta = match execution.side.as_str() {
+            "BOT" => shares,
+            "SLD" => -shares,
+            _ => return,
+        };
+        let multiplier = contract.multiplier.parse::<f64>().unwrap_or(1.0);
+        let cost = execution.price * multiplier;
+        let key = (execution.acct_number.clone(), contract.con_id);
+        let (position, avg_cost) = match self.positions.get(&key) {
+            Some(held) => (held.position, held.avg_cost),
+            None => (0.0, 0.0),
+        };
+        let updated = position + delta;
+        let avg_cost = if position == 0.0 || updated * position < 0.0 {
+            cost
+        } else if updated.abs() > position.abs() {
+            (position.abs() * avg_cost + delta.abs() * cost) / updated.abs()
+        } else {
+            avg_cost
+        };
+        self.set_position(&execution.acct_number, contract.clone(), updated, avg_cost);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Records a position update.  A position of 0 removes the contract.
+    pub fn on_position(&mut self, account: &str, contract: Contract, position: f64, avg_cost: f64) {
+        if let Some(refresh) = self.refreshed.as_mut() {
+            refresh
+                .reported
+                .insert((account.to_string(), contract.con_id));
+        }
+        self.set_position(account, contract, position, avg_cost);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The position in a contract summed over every account
+    pub fn net_position(&self, con_id: i32) -> f64 {
+        self.positions
+            .values()
+            .filter(|position| position.contract.con_id == con_id)
+            .map(|position| position.position)
+            .sum()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn set_position(&mut self, account: &str, contract: Contract, position: f64, avg_cost: f64) {
+        let key = (account.to_string(), contract.con_id);
+        if position == 0.0 {
+            self.positions.remove(&key);
+        } else {
+            self.positions
+                .insert(key, Position::new(account, contract, position, avg_cost));
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The position in a contract held by an account
+    pub fn position(&self, account: &str, con_id: i32) -> Option<&Position> {
+        self.positions.get(&(account.to_string(), con_id))
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Every position held
+    pub fn positions(&self) -> Vec<&Position> {
+        self.positions.values().collect()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Orders reducing the position in a contract by up to quantity: SELL for a long position,
+    /// BUY for a short one.  A quantity above the position flips it, either in one order or,
+    /// with ClosingOrderConfig::split_flips, as a closing order followed by an opening one.
+    ///
+    /// The orders are market orders for the position's account, with order_ref set to
+    /// CLOSE_ORDER_REF or OPEN_ORDER_REF.  Change the order type and prices as needed before
+    /// placing them.
+    ///
+    /// # Arguments
+    /// * con_id - the contract id.  Exactly one account must hold a position in it, see
+    ///   closing_order_for_account otherwise.
+    /// * quantity - the quantity to trade, greater than 0
+    pub fn closing_order(&self, con_id: i32, quantity: f64) -> Result<Vec<Order>, IBKRApiLibError> {
+        let mut held = self
+            .positions
+            .values()
+            .filter(|position| position.contract.con_id == con_id);
+        match (held.next(), held.next()) {
+            (Some(position), None) => self.closing_orders(position, quantity),
+            (None, _) => Err(closing_order_error(&format!(
+                "No position in con_id {}.",
+                con_id
+            ))),
+            (Some(_), Some(_)) => Err(closing_order_error(&format!(
+                "Several accounts hold con_id {}, the account must be given.",
+                con_id
+            ))),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// closing_order for the position of one account
+    pub fn closing_order_for_account(
+        &self,
+        account: &str,
+        con_id: i32,
+        quantity: f64,
+    ) -> Result<Vec<Order>, IBKRApiLibError> {
+        match self.position(account, con_id) {
+            Some(position) => self.closing_orders(position, quantity),
+            None => Err(closing_order_error(&format!(
+                "No position in con_id {} for account {}.",
+                con_id, account
+            ))),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn closing_orders(
+        &self,
+        position: &Position,
+        quantity: f64,
+    ) -> Result<Vec<Order>, IBKRApiLibError> {
+        if quantity.is_nan() || quantity <= 0.0 {
+            return Err(closing_order_error(&format!(
+                "Quantity must be greater than 0, got {}.",
+                quantity
+            )));
+        }
+        let action = if position.position > 0.0 {
+            Action::Sell
+        } else {
+            Action::Buy
+        };
+        let held = position.position.abs();
+
+        if quantity <= held {
+            return Ok(vec![self.order(
+                position,
+                &action,
+                quantity,
+                CLOSE_ORDER_REF,
+                "C",
+            )]);
+        }
+        if !self.config.split_flips {
+            // closes and opens at once, so open_close does not apply
+            return Ok(vec![self.order(
+                position,
+                &action,
+                quantity,
+                CLOSE_ORDER_REF,
+                "",
+            )]);
+        }
+        Ok(vec![
+            self.order(position, &action, held, CLOSE_ORDER_REF, "C"),
+            self.order(position, &action, quantity - held, OPEN_ORDER_REF, "O"),
+        ])
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn order(
+        &self,
+        position: &Position,
+        action: &Action,
+        quantity: f64,
+        order_ref: &str,
+        open_close: &str,
+    ) -> Order {
+        let mut order = Order {
+            action: action.clone(),
+            order_type: OrderType::Mkt,
+            total_quantity: quantity,
+            account: position.account.clone(),
+            order_ref: order_ref.to_string(),
+            ..Default::default()
+        };
+        if self.config.set_open_close {
+            order.open_close = open_close.to_string();
+        }
+        order
+    }
+}
+
+//==================================================================================================
+fn closing_order_error(detail: &str) -> IBKRApiLibError {
+    IBKRApiLibError::ApiError(TwsApiReportableError::new(
+        -1,
+        TwsError::BadMessage.code().to_string(),
+        format!("{} {}", TwsError::BadMessage.message(), detail),
+    ))
+}
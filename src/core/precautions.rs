@@ -0,0 +1,298 @@
+//! Client-side copy of the TWS precautionary order settings, and the marketability check of
+//! limit prices against the current quote
+use std::fmt;
+
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use crate::core::common::UNSET_DOUBLE;
+use crate::core::contract::Contract;
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+use crate::core::instrument::Instrument;
+use crate::core::log_targets::ORDERS;
+use crate::core::order::{Order, OrderType};
+use crate::core::quote::Quote;
+
+//==================================================================================================
+/// Limits mirroring the precautionary settings in TWS (Global Configuration -> API ->
+/// Precautions / Presets).  When TWS rejects an order for breaking one of these it opens a
+/// confirmation dialog and the API order stalls until someone clicks it, so checking them
+/// locally turns the stall into an immediate error.  A limit of None is not checked.
+///
+/// max_order_size - maximum order quantity
+/// max_order_value - maximum quantity * price * multiplier, in the contract currency
+/// max_price_deviation_pct - maximum distance of the limit price from the market price,
+///                           in percent of the market price
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct PrecautionarySettings {
+    pub max_order_size: Option<f64>,
+    pub max_order_value: Option<f64>,
+    pub max_price_deviation_pct: Option<f64>,
+}
+
+impl PrecautionarySettings {
+    pub fn new(
+        max_order_size: Option<f64>,
+        max_order_value: Option<f64>,
+        max_price_deviation_pct: Option<f64>,
+    ) -> Self {
+        PrecautionarySettings {
+            max_order_size,
+            max_order_value,
+            max_price_deviation_pct,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Checks an order against the limits
+    ///
+    /// # Arguments
+    /// * order_id - the order id, reported in the error
+    /// * contract - the contract, for its multiplier
+    /// * order - the order to check.  Its value is its cash_qty if set, else it uses lmt_price,
+    ///   or aux_price for stop orders.  Orders without any of them are only checked for size.
+    /// * market_price - the current market price, if known.  The value check falls back to it
+    ///   and the price deviation check is skipped without it.
+    pub fn check(
+        &self,
+        order_id: i32,
+        contract: &Contract,
+        order: &Order,
+        market_price: Option<f64>,
+    ) -> Result<(), IBKRApiLibError> {
+        if let Some(max_size) = self.max_order_size {
+            if order.total_quantity > max_size {
+                return Err(violation(
+                    order_id,
+                    format!(
+                        "Order size {} exceeds the size limit {}.",
+                        order.total_quantity, max_size
+                    ),
+                ));
+            }
+        }
+
+        // a cash quantity order is worth its cash quantity whatever the price
+        let value = if order.cash_qty != UNSET_DOUBLE {
+            Some(order.cash_qty)
+        } else {
+            let multiplier = contract.multiplier.parse::<f64>().unwrap_or(1.0);
+            order_price(order)
+                .or(market_price)
+                .map(|price| order.total_quantity * price * multiplier)
+        };
+
+        if let (Some(max_value), Some(value)) = (self.max_order_value, value) {
+            if value > max_value {
+                return Err(violation(
+                    order_id,
+                    format!(
+                        "Order value {} exceeds the value limit {}.",
+                        value, max_value
+                    ),
+                ));
+            }
+        }
+
+        if let (Some(max_deviation), Some(price), Some(market)) = (
+            self.max_price_deviation_pct,
+            order_price(order),
+            market_price,
+        ) {
+            if market > 0.0 {
+                let deviation = (price - market).abs() / market * 100.0;
+                if deviation > max_deviation {
+                    return Err(violation(
+                        order_id,
+                        format!(
+                            "Order price {} is {:.2}% away from the market price {}, more than the limit of {}%.",
+                            price, deviation, market, max_deviation
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Checks an order on an instrument against the limits like check, and that its price is a
+    /// tick of the instrument on the exchange of its contract, which TWS would reject otherwise
+    pub fn check_instrument(
+        &self,
+        order_id: i32,
+        instrument: &Instrument,
+        order: &Order,
+        market_price: Option<f64>,
+    ) -> Result<(), IBKRApiLibError> {
+        self.check(order_id, instrument.contract(), order, market_price)?;
+        if let Some(price) = order_price(order) {
+            let exchange = instrument.contract().exchange.code();
+            let tick = instrument.tick_size(exchange, price);
+            if (instrument.round_to_tick(exchange, price) - price).abs() > tick * 1e-6 {
+                return Err(violation(
+                    order_id,
+                    format!(
+                        "Order price {} is not a multiple of the tick size {} on {}.",
+                        price, tick, exchange
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+//==================================================================================================
+/// Where a limit price lies relative to the quote
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Marketability {
+    /// Rests in the book: a buy below the ask, a sell above the bid
+    Passive,
+    /// Takes the opposite side at or near its price
+    Marketable,
+    /// Crosses the opposite side by more than the tolerance, likely a typo
+    ThroughMarket,
+}
+
+impl fmt::Display for Marketability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+//==================================================================================================
+/// What to do with an order of a given marketability
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum MarketabilityAction {
+    #[default]
+    Allow,
+    /// Logs a warning and sends the order
+    Warn,
+    /// Returns a TwsError::PrecautionViolation error instead of sending the order
+    Block,
+}
+
+//==================================================================================================
+/// Check of limit prices against the quote, for EClient::place_order_against_quote
+/// through_market_pct - how far in percent of the opposite side a limit price may cross it and
+///   still count as marketable
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MarketabilityCheck {
+    pub through_market_pct: f64,
+    pub on_marketable: MarketabilityAction,
+    pub on_through_market: MarketabilityAction,
+}
+
+impl MarketabilityCheck {
+    pub fn new(
+        through_market_pct: f64,
+        on_marketable: MarketabilityAction,
+        on_through_market: MarketabilityAction,
+    ) -> Self {
+        MarketabilityCheck {
+            through_market_pct,
+            on_marketable,
+            on_through_market,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Where the limit price of an order lies relative to the quote.  None for orders without
+    /// a limit price or a quote missing the side the order would take.
+    pub fn classify(&self, order: &Order, quote: &Quote) -> Option<Marketability> {
+        if order.lmt_price == UNSET_DOUBLE {
+            return None;
+        }
+        let (through, beyond) = if order.action.is_buy() {
+            if quote.ask == UNSET_DOUBLE || quote.ask <= 0.0 {
+                return None;
+            }
+            (
+                order.lmt_price - quote.ask,
+                quote.ask * self.through_market_pct / 100.0,
+            )
+        } else {
+            if quote.bid == UNSET_DOUBLE || quote.bid <= 0.0 {
+                return None;
+            }
+            (
+                quote.bid - order.lmt_price,
+                quote.bid * self.through_market_pct / 100.0,
+            )
+        };
+        if through < 0.0 {
+            Some(Marketability::Passive)
+        } else if through <= beyond {
+            Some(Marketability::Marketable)
+        } else {
+            Some(Marketability::ThroughMarket)
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Classifies an order and applies the configured action
+    ///
+    /// # Returns
+    /// The marketability, None if the order could not be classified, or an error with code
+    /// TwsError::PrecautionViolation if its action is Block
+    pub fn check(
+        &self,
+        order_id: i32,
+        order: &Order,
+        quote: &Quote,
+    ) -> Result<Option<Marketability>, IBKRApiLibError> {
+        let marketability = match self.classify(order, quote) {
+            Some(marketability) => marketability,
+            None => return Ok(None),
+        };
+        let action = match marketability {
+            Marketability::Passive => MarketabilityAction::Allow,
+            Marketability::Marketable => self.on_marketable,
+            Marketability::ThroughMarket => self.on_through_market,
+        };
+        let detail = format!(
+            "{} limit price {} is {} against bid {} / ask {}.",
+            order.action, order.lmt_price, marketability, quote.bid, quote.ask
+        );
+        match action {
+            MarketabilityAction::Allow => {}
+            MarketabilityAction::Warn => warn!(target: ORDERS, "Order {}: {}", order_id, detail),
+            MarketabilityAction::Block => return Err(violation(order_id, detail)),
+        }
+        Ok(Some(marketability))
+    }
+}
+
+impl Default for MarketabilityCheck {
+    /// Blocks orders crossing the market by more than 5%
+    fn default() -> Self {
+        MarketabilityCheck::new(5.0, MarketabilityAction::Allow, MarketabilityAction::Block)
+    }
+}
+
+//==================================================================================================
+/// The limit price of an order, or the trigger price of a stop or if-touched order.  The
+/// aux_price of other orders, e.g. the trailing amount of TRAIL or the offset of REL and PEG
+/// orders, is not a price.
+fn order_price(order: &Order) -> Option<f64> {
+    let is_price = |price: f64| price != UNSET_DOUBLE && price > 0.0;
+    if is_price(order.lmt_price) {
+        return Some(order.lmt_price);
+    }
+    match order.order_type {
+        OrderType::Stp | OrderType::StpPrt | OrderType::Mit if is_price(order.aux_price) => {
+            Some(order.aux_price)
+        }
+        _ => None,
+    }
+}
+
+//==================================================================================================
+fn violation(order_id: i32, detail: String) -> IBKRApiLibError {
+    IBKRApiLibError::ApiError(TwsApiReportableError::new(
+        order_id,
+        TwsError::PrecautionViolation.code().to_string(),
+        format!("{} {}", TwsError::PrecautionViolation.message(), detail),
+    ))
+}
@@ -0,0 +1,181 @@
+//! Consolidated top-of-book quote built from market data ticks
+use std::fmt;
+use std::time::Instant;
+
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+
+use crate::core::common::{TickType, UNSET_DOUBLE};
+use crate::core::freshness::Freshness;
+
+//==================================================================================================
+/// Consolidated quote for one contract.  Delayed ticks fill the same fields as live ones.
+/// Prices TWS did not send are left at UNSET_DOUBLE and sizes at None.  Sizes are fractional
+/// for crypto.
+/// updated - when the last tick of the quote was applied, None before the first
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Quote {
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
+    pub bid: f64,
+    pub bid_size: Option<BigDecimal>,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
+    pub ask: f64,
+    pub ask_size: Option<BigDecimal>,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
+    pub last: f64,
+    pub last_size: Option<BigDecimal>,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
+    pub open: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
+    pub high: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
+    pub low: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
+    pub close: f64,
+    pub volume: Option<BigDecimal>,
+    #[serde(skip)]
+    pub updated: Option<Instant>,
+}
+
+impl Quote {
+    pub fn new() -> Self {
+        Quote {
+            bid: UNSET_DOUBLE,
+            bid_size: None,
+            ask: UNSET_DOUBLE,
+            ask_size: None,
+            last: UNSET_DOUBLE,
+            last_size: None,
+            open: UNSET_DOUBLE,
+            high: UNSET_DOUBLE,
+            low: UNSET_DOUBLE,
+            close: UNSET_DOUBLE,
+            volume: None,
+            updated: None,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Applies a tick_price tick.  Returns false if the tick type is not part of a quote.
+    pub fn apply_price(&mut self, tick_type: TickType, price: f64) -> bool {
+        let field = match tick_type {
+            TickType::Bid | TickType::DelayedBid => &mut self.bid,
+            TickType::Ask | TickType::DelayedAsk => &mut self.ask,
+            TickType::Last | TickType::DelayedLast => &mut self.last,
+            TickType::Open | TickType::DelayedOpen => &mut self.open,
+            TickType::High | TickType::DelayedHigh => &mut self.high,
+            TickType::Low | TickType::DelayedLow => &mut self.low,
+            TickType::Close | TickType::DelayedClose => &mut self.close,
+            _ => return false,
+        };
+        *field = price;
+        self.updated = Some(Instant::now());
+        true
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Applies a tick_size tick.  Returns false if the tick type is not part of a quote.
+    pub fn apply_size(&mut self, tick_type: TickType, size: BigDecimal) -> bool {
+        let field = match tick_type {
+            TickType::BidSize | TickType::DelayedBidSize => &mut self.bid_size,
+            TickType::AskSize | TickType::DelayedAskSize => &mut self.ask_size,
+            TickType::LastSize | TickType::DelayedLastSize => &mut self.last_size,
+            TickType::Volume | TickType::DelayedVolume => &mut self.volume,
+            _ => return false,
+        };
+        *field = Some(size);
+        self.updated = Some(Instant::now());
+        true
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Midpoint of bid and ask, if both are set
+    pub fn midpoint(&self) -> Option<f64> {
+        if self.bid == UNSET_DOUBLE || self.ask == UNSET_DOUBLE {
+            None
+        } else {
+            Some((self.bid + self.ask) / 2.0)
+        }
+    }
+}
+
+impl Freshness for Quote {
+    fn last_update(&self) -> Option<Instant> {
+        self.updated
+    }
+}
+
+impl Default for Quote {
+    fn default() -> Self {
+        Quote::new()
+    }
+}
+
+impl fmt::Display for Quote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "bid: {}, bid_size: {}, ask: {}, ask_size: {}, last: {}, last_size: {}, open: {}, \
+             high: {}, low: {}, close: {}, volume: {}",
+            self.bid,
+            size_text(&self.bid_size),
+            self.ask,
+            size_text(&self.ask_size),
+            self.last,
+            size_text(&self.last_size),
+            self.open,
+            self.high,
+            self.low,
+            self.close,
+            size_text(&self.volume)
+        )
+    }
+}
+
+//==================================================================================================
+/// A size of the quote for Display, empty while unset
+fn size_text(size: &Option<BigDecimal>) -> String {
+    size.as_ref().map(ToString::to_string).unwrap_or_default()
+}
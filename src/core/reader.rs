@@ -1,38 +1,154 @@
 //! Reads and processes messages from the TCP socket
-use std::io::Read;
+//!
+//! The messages read go to the Decoder's channel.  With DispatchMode::PriorityOrders the order
+//! and execution messages go to a channel of their own, decoded by a second Decoder thread, so
+//! a backlog of market data never delays them.
+//!
+//! The bytes read are split into frames by a FrameAssembler.  A frame that isn't UTF-8 goes
+//! to the Decoder as a MessageError and is skipped.  A frame with an invalid length prefix
+//! also goes to the Decoder, which reports the connection closed, as the frames after it
+//! can't be found.
+use std::io::{ErrorKind, Read};
 use std::net::Shutdown;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::Sender;
 use std::sync::Arc;
 
 use log::*;
 
 use super::streamer::Streamer;
-use crate::core::errors::IBKRApiLibError;
-use crate::core::messages::read_msg;
+use crate::core::common::NO_VALID_ID;
+use crate::core::errors::{IBKRApiLibError, MessageError, TwsApiReportableError, TwsError};
+use crate::core::log_targets::READER;
+use crate::core::message_queue::QueueSender;
+use crate::core::messages::{parse_frame, InboundMessage, IncomingMessageIds};
+
+//==================================================================================================
+/// How the messages read are passed to the wrapper
+/// Single - in the order received, by one decoder thread
+/// PriorityOrders - order status, open order, execution and commission messages by a decoder
+///   thread of their own, the others by the main one.  The order thread calls the order
+///   wrapper of EClient::set_order_wrapper, so a market data callback holding the lock of the
+///   main wrapper doesn't delay it; without one both threads wait for the lock of the main
+///   wrapper.  Order messages are no longer ordered with the others, e.g. an order status can
+///   reach its wrapper before an error TWS sent earlier for the order.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DispatchMode {
+    #[default]
+    Single,
+    PriorityOrders,
+}
+
+/// Ids of the messages DispatchMode::PriorityOrders decodes on the priority thread
+pub const PRIORITY_MESSAGES: [i32; 9] = [
+    IncomingMessageIds::OrderStatus as i32,
+    IncomingMessageIds::OpenOrder as i32,
+    IncomingMessageIds::OpenOrderEnd as i32,
+    IncomingMessageIds::ExecutionData as i32,
+    IncomingMessageIds::ExecutionDataEnd as i32,
+    IncomingMessageIds::CommissionReport as i32,
+    IncomingMessageIds::OrderBound as i32,
+    IncomingMessageIds::CompletedOrder as i32,
+    IncomingMessageIds::CompletedOrdersEnd as i32,
+];
+
+//==================================================================================================
+/// Whether a message goes to the priority thread
+pub fn is_priority_message(msg: &str) -> bool {
+    let msg_id = msg.split('\0').next().and_then(|id| id.parse::<i32>().ok());
+    matches!(msg_id, Some(msg_id) if PRIORITY_MESSAGES.contains(&msg_id))
+}
+
+//==================================================================================================
+/// Splits the bytes read into length-prefixed frames.  A read can end inside a frame or hold
+/// many; the bytes of an incomplete frame stay buffered until the reads completing it.
+///
+/// Frames are taken with messages::parse_frame at a cursor into the buffer, and the bytes
+/// taken are only dropped on the next push, so a read holding many frames is not copied once
+/// per frame.
+#[derive(Debug, Default)]
+pub struct FrameAssembler {
+    buffer: Vec<u8>,
+    start: usize,
+}
+
+impl FrameAssembler {
+    pub fn new() -> Self {
+        FrameAssembler::default()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Adds bytes read
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.drain(..self.start);
+        self.start = 0;
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Takes the next complete frame, None until one is complete
+    ///
+    /// # Returns
+    /// The payload, or the error of a frame that isn't UTF-8, after which the next frame is
+    /// taken as usual.  After an invalid length the frames can't be found: the bytes are
+    /// dropped and the error returned.
+    pub fn next_frame(&mut self) -> Option<InboundMessage> {
+        match parse_frame(&self.buffer[self.start..]) {
+            Ok((_, None)) => None,
+            Ok((size, Some(text))) => {
+                self.start += 4 + size;
+                Some(Ok(text))
+            }
+            Err(err) => {
+                if let MessageError::InvalidUtf8 { size, .. } = err {
+                    self.start += 4 + size;
+                } else {
+                    self.buffer.clear();
+                    self.start = 0;
+                }
+                Some(Err(err))
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Number of bytes buffered of incomplete frames
+    pub fn pending(&self) -> usize {
+        self.buffer.len() - self.start
+    }
+}
 
 //==================================================================================================
 pub struct Reader {
     stream: Box<dyn Streamer + 'static>,
-    messages: Sender<String>,
+    messages: QueueSender,
+    priority_messages: Option<QueueSender>,
     disconnect_requested: Arc<AtomicBool>,
     is_connected: bool,
+    frames: FrameAssembler,
 }
 
 impl Reader {
     pub fn new(
-        stream: Box<impl Streamer + 'static>,
-        messages: Sender<String>,
+        stream: Box<dyn Streamer + 'static>,
+        messages: QueueSender,
         disconnect_requested: Arc<AtomicBool>,
     ) -> Self {
         Reader {
             stream,
             messages,
+            priority_messages: None,
             disconnect_requested,
             is_connected: true,
+            frames: FrameAssembler::new(),
         }
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Sends the messages is_priority_message selects to this channel instead
+    pub fn set_priority_messages(&mut self, priority_messages: Option<QueueSender>) {
+        self.priority_messages = priority_messages;
+    }
+
     //----------------------------------------------------------------------------------------------
     pub fn recv_packet(&mut self) -> Result<Vec<u8>, IBKRApiLibError> {
         //debug!("_recv_all_msg");
@@ -41,7 +157,7 @@ impl Reader {
         // closed or broken
         if buf.len() == 0 {
             if !self.disconnect_requested.load(Ordering::Acquire) {
-                info!("socket either closed or broken, disconnecting");
+                info!(target: READER, "socket either closed or broken, disconnecting");
                 self.stream.shutdown(Shutdown::Both)?;
                 self.is_connected = false;
             }
@@ -58,10 +174,11 @@ impl Reader {
         while cont {
             let mut buf: [u8; NUM_BYTES] = [0; NUM_BYTES];
 
-            let bytes_read = self
-                .stream
-                .read(&mut buf)
-                .expect("Couldnt read from reader...");
+            let bytes_read = match self.stream.read(&mut buf) {
+                Ok(bytes_read) => bytes_read,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            };
             allbuf.extend_from_slice(&buf[0..bytes_read]);
             //logger.debug("len %d raw:%s|", len(buf), buf)
 
@@ -72,41 +189,68 @@ impl Reader {
         Ok(allbuf)
     }
 
+    //----------------------------------------------------------------------------------------------
+    /// Reads until a whole frame arrived and returns it, e.g. for the handshake before run
+    pub fn read_frame(&mut self) -> Result<String, IBKRApiLibError> {
+        loop {
+            if let Some(frame) = self.frames.next_frame() {
+                return Ok(frame?);
+            }
+            let packet = self.recv_packet()?;
+            if !self.is_connected {
+                return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                    NO_VALID_ID,
+                    TwsError::NotConnected.code().to_string(),
+                    TwsError::NotConnected.message().to_string(),
+                )));
+            }
+            self.frames.push(&packet);
+        }
+    }
+
     //----------------------------------------------------------------------------------------------
     fn process_reader_msgs(&mut self) -> Result<(), IBKRApiLibError> {
-        // grab a packet of messages from the socket
-        let mut message_packet = self.recv_packet()?;
-        //debug!(" recvd size {}", message_packet.len());
-
-        // Read messages from the packet until there are no more.
-        // When this loop ends, break into the outer loop and grab another packet.
-        // Repeat until the connection is closed
-        //
-        let _msg = String::new();
-        while message_packet.len() > 0 {
-            // Read a message from the packet then add it to the message queue below.
-            let (_size, msg, remaining_messages) = read_msg(message_packet.as_slice())?;
-
-            // clear the Vec that holds the bytes from the packet
-            // and reload with the bytes that haven't been read.
-            // The variable remaining_messages only holds the unread messagesleft in the packet
-            message_packet.clear();
-            message_packet.extend_from_slice(remaining_messages.as_slice());
-
-            if msg.as_str() != "" {
-                self.messages.send(msg).expect("READER CANNOT SEND MESSAGE");
-            } else {
-                //Break to the outer loop in run and get another packet of messages.
-
-                debug!("more incoming packet(s) are needed ");
-                break;
+        // grab a packet of messages from the socket and pass on the frames it completes
+        let packet = self.recv_packet()?;
+        self.frames.push(&packet);
+
+        while let Some(frame) = self.frames.next_frame() {
+            match frame {
+                Ok(msg) if msg.is_empty() => (),
+                Ok(msg) => match self.priority_messages.as_ref() {
+                    Some(priority) if is_priority_message(&msg) => {
+                        priority.send(Ok(msg)).expect("READER CANNOT SEND MESSAGE")
+                    }
+                    _ => self
+                        .messages
+                        .send(Ok(msg))
+                        .expect("READER CANNOT SEND MESSAGE"),
+                },
+                Err(err) => {
+                    error!(target: READER, "Bad frame: {}", err);
+                    let is_fatal = err.is_fatal();
+                    self.messages
+                        .send(Err(err))
+                        .expect("READER CANNOT SEND MESSAGE");
+                    if is_fatal {
+                        self.is_connected = false;
+                        return Ok(());
+                    }
+                }
             }
         }
+        if self.messages.has_overflowed() {
+            error!(target: READER, "Message queue overflowed, disconnecting");
+            self.is_connected = false;
+        } else if self.frames.pending() > 0 {
+            debug!(target: READER, "more incoming packet(s) are needed ");
+        }
         Ok(())
     }
+
     //----------------------------------------------------------------------------------------------
     pub fn run(&mut self) {
-        debug!("starting reader loop");
+        debug!(target: READER, "starting reader loop");
         loop {
             if self.disconnect_requested.load(Ordering::Acquire) || !self.is_connected {
                 return;
@@ -115,7 +259,11 @@ impl Reader {
             if !result.is_err() {
                 continue;
             }
-            error!("{:?}", result);
+            error!(target: READER, "{:?}", result);
+            if let Err(IBKRApiLibError::Io(_)) = result {
+                // the socket is unusable, dropping the senders disconnects the Decoder
+                self.is_connected = false;
+            }
         }
     }
 }
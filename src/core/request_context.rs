@@ -0,0 +1,172 @@
+//! The request a wrapper callback answers
+//!
+//! EClient records a RequestContext for the requests it sends with a req_id: the request
+//! method, and the contract of the requests made for one.  The Decoder reads the req_id of every
+//! message that has one before passing it on, and while the wrapper handles the message, that
+//! req_id and its context are the current ones of the decoder thread.  Wrapper code and the log
+//! lines it writes can name the request without keeping a map of req_ids of their own, and
+//! RequestMetadata::current finds the metadata the application attached to it:
+//!
+//! ```text
+//! fn tick_price(&mut self, req_id: i32, tick_type: TickType, price: f64, attrib: TickAttrib) {
+//!     if let Some(context) = request_context::current() {
+//!         info!("{} -- price: {}", context, price);
+//!     }
+//! }
+//! ```
+//!
+//! A context lives until its request ends: subscriptions until they are cancelled, one-shot
+//! requests until the message ending them or an error for them.
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::contract::Contract;
+
+thread_local! {
+    static CURRENT: RefCell<Option<Current>> = const { RefCell::new(None) };
+}
+
+//==================================================================================================
+/// A request sent with a req_id
+/// request - the EClient method that sent it, e.g. req_mkt_data
+/// contract - the contract of the request, None for requests not made for one
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RequestContext {
+    pub req_id: i32,
+    pub request: String,
+    pub contract: Option<Arc<Contract>>,
+}
+
+impl RequestContext {
+    pub fn new(req_id: i32, request: &str, contract: Option<&Contract>) -> Self {
+        RequestContext {
+            req_id,
+            request: request.to_string(),
+            contract: contract.cloned().map(Arc::new),
+        }
+    }
+}
+
+impl fmt::Display for RequestContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "req_id: {}, request: {}", self.req_id, self.request)?;
+        if let Some(contract) = self.contract.as_ref() {
+            write!(
+                f,
+                ", contract: {} {} {} {}",
+                contract.symbol, contract.sec_type, contract.exchange, contract.currency
+            )?;
+        }
+        Ok(())
+    }
+}
+
+//==================================================================================================
+/// The request the calling thread handles a message of
+#[derive(Clone, Debug)]
+struct Current {
+    req_id: i32,
+    context: Option<Arc<RequestContext>>,
+}
+
+//==================================================================================================
+/// The context of the request the calling thread handles a message of, None outside wrapper
+/// callbacks and in callbacks of requests EClient records no context for
+pub fn current() -> Option<Arc<RequestContext>> {
+    CURRENT.with(|current| current.borrow().as_ref()?.context.clone())
+}
+
+//==================================================================================================
+/// The req_id of the message the calling thread handles, None outside wrapper callbacks and in
+/// callbacks of messages without a req_id
+pub fn current_req_id() -> Option<i32> {
+    CURRENT.with(|current| current.borrow().as_ref().map(|current| current.req_id))
+}
+
+//==================================================================================================
+/// Makes context the current one of the calling thread until the guard is dropped, e.g. to
+/// handle the messages of a request on a thread of the application
+pub fn enter(context: RequestContext) -> ContextGuard {
+    enter_request(context.req_id, Some(Arc::new(context)))
+}
+
+//==================================================================================================
+/// Makes req_id and its context, if EClient recorded one, the current ones of the calling
+/// thread until the guard is dropped
+pub(crate) fn enter_request(req_id: i32, context: Option<Arc<RequestContext>>) -> ContextGuard {
+    let previous =
+        CURRENT.with(|current| current.borrow_mut().replace(Current { req_id, context }));
+    ContextGuard { previous }
+}
+
+//==================================================================================================
+/// Restores the previous context of the thread when dropped
+#[derive(Debug)]
+pub struct ContextGuard {
+    previous: Option<Current>,
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+//==================================================================================================
+/// The contexts of the requests sent, keyed by req_id
+#[derive(Debug, Default)]
+pub(crate) struct RequestContexts {
+    contexts: HashMap<i32, Arc<RequestContext>>,
+    /// the requests whose end message doesn't end them, as they keep sending updates until
+    /// cancelled
+    streaming: HashSet<i32>,
+}
+
+impl RequestContexts {
+    /// Records the context of a request, replacing the one of an earlier request with its req_id
+    pub fn record(&mut self, context: RequestContext) {
+        self.streaming.remove(&context.req_id);
+        self.contexts.insert(context.req_id, Arc::new(context));
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Keeps the context of a request past its end message, until it is forgotten
+    pub fn keep(&mut self, req_id: i32) {
+        if self.contexts.contains_key(&req_id) {
+            self.streaming.insert(req_id);
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn get(&self, req_id: i32) -> Option<Arc<RequestContext>> {
+        self.contexts.get(&req_id).cloned()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Forgets the context of a request whose end message arrived, unless it is kept.  Returns
+    /// whether the request ended.
+    pub fn end(&mut self, req_id: i32) -> bool {
+        if self.streaming.contains(&req_id) {
+            return false;
+        }
+        self.contexts.remove(&req_id);
+        true
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn forget(&mut self, req_id: i32) {
+        self.contexts.remove(&req_id);
+        self.streaming.remove(&req_id);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn clear(&mut self) {
+        self.contexts.clear();
+        self.streaming.clear();
+    }
+}
@@ -0,0 +1,365 @@
+//! Greeks of the positions of a Portfolio, aggregated per underlying and in total, and limits
+//! on them
+//!
+//! Option greeks come from the tick_option_computation callbacks of market data requests for
+//! the options.  The live computations (bid, ask, last) are used where TWS sent them, the
+//! model computation otherwise.  Positions in anything other than options count as their
+//! quantity times multiplier in delta.
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::common::{SecType, TickType, UNSET_DOUBLE};
+use crate::core::instrument::Instrument;
+use crate::core::portfolio::{Portfolio, Position};
+
+//==================================================================================================
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Greek {
+    Delta,
+    Gamma,
+    Vega,
+    Theta,
+}
+
+impl Greek {
+    pub const ALL: [Greek; 4] = [Greek::Delta, Greek::Gamma, Greek::Vega, Greek::Theta];
+}
+
+impl fmt::Display for Greek {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+//==================================================================================================
+/// Greeks of an option, or of positions once aggregated: delta and gamma in units of the
+/// underlying, vega and theta in the currency of the contracts
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Greeks {
+    pub delta: f64,
+    pub gamma: f64,
+    pub vega: f64,
+    pub theta: f64,
+}
+
+impl Greeks {
+    pub fn new(delta: f64, gamma: f64, vega: f64, theta: f64) -> Self {
+        Greeks {
+            delta,
+            gamma,
+            vega,
+            theta,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn get(&self, greek: Greek) -> f64 {
+        match greek {
+            Greek::Delta => self.delta,
+            Greek::Gamma => self.gamma,
+            Greek::Vega => self.vega,
+            Greek::Theta => self.theta,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn add_scaled(&mut self, greeks: &Greeks, scale: f64) {
+        self.delta += greeks.delta * scale;
+        self.gamma += greeks.gamma * scale;
+        self.vega += greeks.vega * scale;
+        self.theta += greeks.theta * scale;
+    }
+}
+
+impl fmt::Display for Greeks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "delta: {}, gamma: {}, vega: {}, theta: {}",
+            self.delta, self.gamma, self.vega, self.theta
+        )
+    }
+}
+
+//==================================================================================================
+/// Greeks of one option as received, None where TWS has not computed them
+#[derive(Clone, Copy, Debug, Default)]
+struct GreekTicks {
+    delta: Option<f64>,
+    gamma: Option<f64>,
+    vega: Option<f64>,
+    theta: Option<f64>,
+}
+
+impl GreekTicks {
+    fn update(&mut self, delta: f64, gamma: f64, vega: f64, theta: f64) {
+        let known = |value: f64| Some(value).filter(|value| *value != UNSET_DOUBLE);
+        self.delta = known(delta).or(self.delta);
+        self.gamma = known(gamma).or(self.gamma);
+        self.vega = known(vega).or(self.vega);
+        self.theta = known(theta).or(self.theta);
+    }
+}
+
+//==================================================================================================
+/// Largest absolute value allowed for each greek, None for no limit
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+pub struct GreekLimits {
+    pub delta: Option<f64>,
+    pub gamma: Option<f64>,
+    pub vega: Option<f64>,
+    pub theta: Option<f64>,
+}
+
+impl GreekLimits {
+    pub fn new(
+        delta: Option<f64>,
+        gamma: Option<f64>,
+        vega: Option<f64>,
+        theta: Option<f64>,
+    ) -> Self {
+        GreekLimits {
+            delta,
+            gamma,
+            vega,
+            theta,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn get(&self, greek: Greek) -> Option<f64> {
+        match greek {
+            Greek::Delta => self.delta,
+            Greek::Gamma => self.gamma,
+            Greek::Vega => self.vega,
+            Greek::Theta => self.theta,
+        }
+    }
+}
+
+//==================================================================================================
+/// What a risk figure or limit covers
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RiskScope {
+    Total,
+    /// the positions whose contract has this symbol
+    Underlying(String),
+}
+
+impl fmt::Display for RiskScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskScope::Total => write!(f, "total"),
+            RiskScope::Underlying(symbol) => write!(f, "{}", symbol),
+        }
+    }
+}
+
+//==================================================================================================
+/// A limit newly exceeded
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RiskLimitBreach {
+    pub scope: RiskScope,
+    pub greek: Greek,
+    pub value: f64,
+    pub limit: f64,
+}
+
+impl fmt::Display for RiskLimitBreach {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "scope: {}, greek: {}, value: {}, limit: {}",
+            self.scope, self.greek, self.value, self.limit
+        )
+    }
+}
+
+//==================================================================================================
+/// Aggregated greeks of a portfolio
+/// without_greeks - con_ids of the option positions left out because no greeks were received
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RiskReport {
+    pub total: Greeks,
+    pub by_underlying: BTreeMap<String, Greeks>,
+    pub without_greeks: Vec<i32>,
+}
+
+//==================================================================================================
+/// Collects option greeks and aggregates them over the positions of a Portfolio
+#[derive(Clone, Debug, Default)]
+pub struct RiskAggregator {
+    requests: HashMap<i32, i32>,
+    multipliers: HashMap<i32, f64>,
+    live: HashMap<i32, GreekTicks>,
+    model: HashMap<i32, GreekTicks>,
+    total_limits: GreekLimits,
+    underlying_limits: GreekLimits,
+    breached: HashSet<(RiskScope, Greek)>,
+}
+
+impl RiskAggregator {
+    pub fn new() -> Self {
+        RiskAggregator::default()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Sets the limits check applies
+    ///
+    /// # Arguments
+    /// * total - limits on the whole portfolio
+    /// * per_underlying - limits on the positions of each underlying
+    pub fn set_limits(&mut self, total: GreekLimits, per_underlying: GreekLimits) {
+        self.total_limits = total;
+        self.underlying_limits = per_underlying;
+        self.breached.clear();
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Associates a market data request with the option it was made for
+    pub fn track(&mut self, req_id: i32, con_id: i32) {
+        self.requests.insert(req_id, con_id);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Associates a market data request with the option of an instrument, whose multiplier
+    /// then scales the greeks of its positions instead of the multiplier of their contracts
+    pub fn track_instrument(&mut self, req_id: i32, instrument: &Instrument) {
+        self.track(req_id, instrument.con_id());
+        self.multipliers
+            .insert(instrument.con_id(), instrument.multiplier());
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Records the greeks of a tick_option_computation callback.  Greeks TWS did not compute
+    /// keep their previous value.
+    pub fn on_option_computation(
+        &mut self,
+        req_id: i32,
+        tick_type: TickType,
+        delta: f64,
+        gamma: f64,
+        vega: f64,
+        theta: f64,
+    ) {
+        let con_id = match self.requests.get(&req_id) {
+            Some(con_id) => *con_id,
+            None => return,
+        };
+        let ticks = match tick_type {
+            TickType::BidOptionComputation
+            | TickType::AskOptionComputation
+            | TickType::LastOptionComputation
+            | TickType::DelayedBidOption
+            | TickType::DelayedAskOption
+            | TickType::DelayedLastOption => &mut self.live,
+            TickType::ModelOption | TickType::DelayedModelOption => &mut self.model,
+            _ => return,
+        };
+        ticks
+            .entry(con_id)
+            .or_default()
+            .update(delta, gamma, vega, theta);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Greeks of an option: each from the live computations if received, from the model
+    /// otherwise.  None until a delta is known.  Greeks other than delta default to 0.
+    pub fn greeks(&self, con_id: i32) -> Option<Greeks> {
+        let live = self.live.get(&con_id).copied().unwrap_or_default();
+        let model = self.model.get(&con_id).copied().unwrap_or_default();
+        Some(Greeks::new(
+            live.delta.or(model.delta)?,
+            live.gamma.or(model.gamma).unwrap_or(0.0),
+            live.vega.or(model.vega).unwrap_or(0.0),
+            live.theta.or(model.theta).unwrap_or(0.0),
+        ))
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Aggregates the greeks of every position of the portfolio
+    pub fn report(&self, portfolio: &Portfolio) -> RiskReport {
+        let mut report = RiskReport::default();
+        let mut positions = portfolio.positions();
+        positions.sort_by_key(|position| position.contract.con_id);
+        for position in positions {
+            let greeks = if is_option(position) {
+                match self.greeks(position.contract.con_id) {
+                    Some(greeks) => greeks,
+                    None => {
+                        report.without_greeks.push(position.contract.con_id);
+                        continue;
+                    }
+                }
+            } else {
+                Greeks::new(1.0, 0.0, 0.0, 0.0)
+            };
+            let multiplier = match self.multipliers.get(&position.contract.con_id) {
+                Some(multiplier) => *multiplier,
+                None => multiplier(position),
+            };
+            let scale = position.position * multiplier;
+            report.total.add_scaled(&greeks, scale);
+            report
+                .by_underlying
+                .entry(position.contract.symbol.clone())
+                .or_default()
+                .add_scaled(&greeks, scale);
+        }
+        report
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Checks the portfolio against the limits.  Each breach is returned once, when the limit
+    /// becomes exceeded, and again only after the figure went back within the limit.
+    pub fn check(&mut self, portfolio: &Portfolio) -> Vec<RiskLimitBreach> {
+        let report = self.report(portfolio);
+        let mut figures = vec![(RiskScope::Total, report.total, self.total_limits)];
+        for (symbol, greeks) in report.by_underlying.iter() {
+            figures.push((
+                RiskScope::Underlying(symbol.clone()),
+                *greeks,
+                self.underlying_limits,
+            ));
+        }
+
+        let mut breaches = Vec::new();
+        let mut breached = HashSet::new();
+        for (scope, greeks, limits) in figures {
+            for greek in Greek::ALL.iter() {
+                let limit = match limits.get(*greek) {
+                    Some(limit) => limit,
+                    None => continue,
+                };
+                let value = greeks.get(*greek);
+                if value.abs() <= limit {
+                    continue;
+                }
+                let key = (scope.clone(), *greek);
+                if !self.breached.contains(&key) {
+                    breaches.push(RiskLimitBreach {
+                        scope: scope.clone(),
+                        greek: *greek,
+                        value,
+                        limit,
+                    });
+                }
+                breached.insert(key);
+            }
+        }
+        self.breached = breached;
+        breaches
+    }
+}
+
+//==================================================================================================
+fn is_option(position: &Position) -> bool {
+    matches!(position.contract.sec_type, SecType::OPT | SecType::FOP)
+}
+
+//==================================================================================================
+fn multiplier(position: &Position) -> f64 {
+    position.contract.multiplier.parse().unwrap_or(1.0)
+}
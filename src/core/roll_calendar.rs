@@ -0,0 +1,232 @@
+//! The contract month to hold of a future, and when to roll to the next one
+//!
+//! A position in a future has to move to a later month before the held one expires.  Feed
+//! RollCalendar the chain of expiries of a root, e.g. with RollCalendar::fetch, and the volume
+//! of each month from the Volume ticks of its market data; update picks the active month by
+//! the RollRule and sends a RollNotice to every subscriber when it changes, so bots holding
+//! positions can roll them.
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use crate::core::client::EClient;
+use crate::core::common::SecType;
+use crate::core::contract::{Contract, ContractDetails};
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+use crate::core::log_targets::ORDERS;
+use crate::core::wrapper::Wrapper;
+
+//==================================================================================================
+/// How RollCalendar picks the active month
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RollRule {
+    /// the earliest month with more than this many days to its last trade date
+    DaysToExpiry(i64),
+    /// the month trading the most volume, never one before the active month.  The active month
+    /// is also rolled off on its last trade date.
+    Volume,
+}
+
+//==================================================================================================
+/// A change of the active month of a root
+#[derive(Clone, Debug)]
+pub struct RollNotice {
+    pub symbol: String,
+    /// the month held until now, None for the first month picked
+    pub from: Option<Contract>,
+    pub to: Contract,
+    pub date: NaiveDate,
+}
+
+impl fmt::Display for RollNotice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "symbol: {}, from: {}, to: {}, date: {}",
+            self.symbol,
+            self.from
+                .as_ref()
+                .map(|from| from.local_symbol.as_str())
+                .unwrap_or_default(),
+            self.to.local_symbol,
+            self.date
+        )
+    }
+}
+
+//==================================================================================================
+/// The chain of expiries of a future root and the month of it to hold
+#[derive(Debug)]
+pub struct RollCalendar {
+    rule: RollRule,
+    chain: Vec<ContractDetails>,
+    volumes: HashMap<i32, f64>,
+    active: Option<Contract>,
+    subscribers: Vec<Sender<RollNotice>>,
+}
+
+impl RollCalendar {
+    pub fn new(rule: RollRule) -> Self {
+        RollCalendar {
+            rule,
+            chain: vec![],
+            volumes: HashMap::new(),
+            active: None,
+            subscribers: vec![],
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Requests the chain of expiries of a root and returns a calendar of them.  Blocks until
+    /// TWS answers, so call it from a thread other than the one processing messages.
+    ///
+    /// Returns an error with code TwsError::InvalidRequest if TWS knows no future of the root.
+    ///
+    /// # Arguments
+    /// * client - the connected client
+    /// * req_id - the id of the contract details request
+    /// * root - the symbol, exchange and currency of the future, its month is ignored
+    /// * rule - how to pick the active month
+    /// * timeout - how long to wait for the details
+    pub fn fetch<T>(
+        client: &mut EClient<T>,
+        req_id: i32,
+        root: &Contract,
+        rule: RollRule,
+        timeout: Duration,
+    ) -> Result<Self, IBKRApiLibError>
+    where
+        T: Wrapper + Send + Sync + 'static,
+    {
+        let root = Contract {
+            sec_type: SecType::FUT,
+            last_trade_date_or_contract_month: String::new(),
+            local_symbol: String::new(),
+            con_id: 0,
+            ..root.clone()
+        };
+        let chain = client
+            .contract_details_bulk(req_id, std::slice::from_ref(&root), 1, timeout)?
+            .pop()
+            .unwrap_or_else(|| Ok(vec![]))?;
+        let mut calendar = RollCalendar::new(rule);
+        calendar.set_chain(chain);
+        if calendar.chain.is_empty() {
+            return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                req_id,
+                TwsError::InvalidRequest.code().to_string(),
+                format!(
+                    "{} No future of {} on {} found.",
+                    TwsError::InvalidRequest.message(),
+                    root.symbol,
+                    root.exchange
+                ),
+            )));
+        }
+        Ok(calendar)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Receives a RollNotice for every change of the active month
+    pub fn subscribe(&mut self) -> Receiver<RollNotice> {
+        let (sender, receiver) = channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Replaces the chain with the FUT details among details, ordered by last trade date.
+    /// Details without a last trade date are left out.
+    pub fn set_chain(&mut self, details: Vec<ContractDetails>) {
+        let mut chain: Vec<ContractDetails> = details
+            .into_iter()
+            .filter(|detail| {
+                detail.contract.sec_type == SecType::FUT && detail.last_trade_date().is_some()
+            })
+            .collect();
+        chain.sort_by_key(|detail| detail.last_trade_date());
+        self.chain = chain;
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn chain(&self) -> &[ContractDetails] {
+        &self.chain
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Records the volume traded in a month, e.g. from a TickType::Volume tick
+    pub fn set_volume(&mut self, con_id: i32, volume: f64) {
+        self.volumes.insert(con_id, volume);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The month picked by the last update, None before the first
+    pub fn active(&self) -> Option<&Contract> {
+        self.active.as_ref()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The month the rule picks on a date, from the months whose last trade date is not past
+    pub fn select(&self, today: NaiveDate) -> Option<&ContractDetails> {
+        let trading: Vec<&ContractDetails> = self
+            .chain
+            .iter()
+            .filter(|detail| detail.last_trade_date().is_some_and(|date| date >= today))
+            .collect();
+        match self.rule {
+            RollRule::DaysToExpiry(days) => trading
+                .iter()
+                .find(|detail| {
+                    detail
+                        .last_trade_date()
+                        .is_some_and(|date| (date - today).num_days() > days)
+                })
+                .or_else(|| trading.last())
+                .copied(),
+            RollRule::Volume => {
+                let active_con_id = self.active.as_ref().map(|active| active.con_id);
+                let start = trading
+                    .iter()
+                    .position(|detail| Some(detail.contract.con_id) == active_con_id)
+                    .unwrap_or(0);
+                let volume = |detail: &ContractDetails| {
+                    self.volumes
+                        .get(&detail.contract.con_id)
+                        .copied()
+                        .unwrap_or(0.0)
+                };
+                trading.get(start..).and_then(|later| {
+                    later.iter().copied().fold(None, |best, detail| match best {
+                        Some(best) if volume(detail) <= volume(best) => Some(best),
+                        _ => Some(detail),
+                    })
+                })
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Picks the active month on a date.  If it changed, sends every subscriber a RollNotice
+    /// and returns it.
+    pub fn update(&mut self, today: NaiveDate) -> Option<RollNotice> {
+        let to = self.select(today)?.contract.clone();
+        if self.active.as_ref().map(|active| active.con_id) == Some(to.con_id) {
+            return None;
+        }
+        let notice = RollNotice {
+            symbol: to.symbol.clone(),
+            from: self.active.replace(to.clone()),
+            to,
+            date: today,
+        };
+        info!(target: ORDERS, "Rolling future -- {}", notice);
+        self.subscribers
+            .retain(|subscriber| subscriber.send(notice.clone()).is_ok());
+        Some(notice)
+    }
+}
@@ -1,9 +1,17 @@
-//! Types for dealing with scanner data and scanner subscriptions
+//! Types for dealing with scanner data and scanner subscriptions, and the scanner parameters
+//! XML describing what scans TWS offers
+use std::collections::HashSet;
 use std::fmt::{Display, Error, Formatter};
 
 use serde::{Deserialize, Serialize};
 
+use crate::core::common::{TagValue, UNSET_DOUBLE, UNSET_INTEGER};
 use crate::core::contract::ContractDetails;
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+use crate::core::xml::{self, Element};
+
+/// Most rows a scanner subscription may return
+pub const MAX_SCANNER_ROWS: i32 = 50;
 
 //==================================================================================================
 
@@ -48,14 +56,56 @@ impl Display for ScanData {
 //==================================================================================================
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct ScannerSubscription {
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_integer::unset",
+            with = "crate::core::serde_unset::unset_integer"
+        )
+    )]
     pub number_of_rows: i32,
     pub instrument: String,
     pub location_code: String,
     pub scan_code: String,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub above_price: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub below_price: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_integer::unset",
+            with = "crate::core::serde_unset::unset_integer"
+        )
+    )]
     pub above_volume: i32,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub market_cap_above: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub market_cap_below: f64,
     pub moody_rating_above: String,
     pub moody_rating_below: String,
@@ -63,9 +113,30 @@ pub struct ScannerSubscription {
     pub sp_rating_below: String,
     pub maturity_date_above: String,
     pub maturity_date_below: String,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub coupon_rate_above: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
     pub coupon_rate_below: f64,
     pub exclude_convertible: bool,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_integer::unset",
+            with = "crate::core::serde_unset::unset_integer"
+        )
+    )]
     pub average_option_volume_above: i32,
     pub scanner_setting_pairs: String,
     pub stock_type_filter: String,
@@ -130,3 +201,592 @@ impl Display for ScannerSubscription {
         )
     }
 }
+
+//==================================================================================================
+/// Scan codes.  Other takes any code of the scanner parameters.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ScanCode {
+    TopPercGain,
+    TopPercLose,
+    MostActive,
+    HotByVolume,
+    HotByPrice,
+    TopTradeCount,
+    TopOpenPercGain,
+    HighOptVolumePutCallRatio,
+    ComboLatestTrade,
+    Other(String),
+}
+
+impl ScanCode {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ScanCode::TopPercGain => "TOP_PERC_GAIN",
+            ScanCode::TopPercLose => "TOP_PERC_LOSE",
+            ScanCode::MostActive => "MOST_ACTIVE",
+            ScanCode::HotByVolume => "HOT_BY_VOLUME",
+            ScanCode::HotByPrice => "HOT_BY_PRICE",
+            ScanCode::TopTradeCount => "TOP_TRADE_COUNT",
+            ScanCode::TopOpenPercGain => "TOP_OPEN_PERC_GAIN",
+            ScanCode::HighOptVolumePutCallRatio => "HIGH_OPT_VOLUME_PUT_CALL_RATIO",
+            ScanCode::ComboLatestTrade => "COMBO_LATEST_TRADE",
+            ScanCode::Other(code) => code,
+        }
+    }
+}
+
+//==================================================================================================
+/// Instruments to scan.  Other takes any instrument type of the scanner parameters.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ScanInstrument {
+    UsStocks,
+    EuStocks,
+    UsFutures,
+    EuFutures,
+    UsIndexes,
+    UsCombos,
+    Other(String),
+}
+
+impl ScanInstrument {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ScanInstrument::UsStocks => "STK",
+            ScanInstrument::EuStocks => "STOCK.EU",
+            ScanInstrument::UsFutures => "FUT.US",
+            ScanInstrument::EuFutures => "FUT.EU",
+            ScanInstrument::UsIndexes => "IND.US",
+            ScanInstrument::UsCombos => "NATCOMB",
+            ScanInstrument::Other(instrument) => instrument,
+        }
+    }
+}
+
+//==================================================================================================
+/// Locations to scan.  Other takes any location code of the scanner parameters.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ScanLocation {
+    UsMajor,
+    UsAll,
+    Ibis,
+    Soffex,
+    UsIndexes,
+    UsComboOptions,
+    Other(String),
+}
+
+impl ScanLocation {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ScanLocation::UsMajor => "STK.US.MAJOR",
+            ScanLocation::UsAll => "STK.US",
+            ScanLocation::Ibis => "STK.EU.IBIS",
+            ScanLocation::Soffex => "FUT.EU.SOFFEX",
+            ScanLocation::UsIndexes => "IND.US",
+            ScanLocation::UsComboOptions => "NATCOMB.OPT.US",
+            ScanLocation::Other(location) => location,
+        }
+    }
+}
+
+//==================================================================================================
+/// Filters sent as scanner subscription filter options.  Market caps are in millions of the
+/// currency.  Other takes any filter tag of the scanner parameters.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ScannerFilter {
+    PriceAbove(f64),
+    PriceBelow(f64),
+    VolumeAbove(i64),
+    AvgVolumeAbove(i64),
+    MarketCapAbove(f64),
+    MarketCapBelow(f64),
+    ChangePercAbove(f64),
+    ChangePercBelow(f64),
+    Other { tag: String, value: String },
+}
+
+impl ScannerFilter {
+    pub fn tag(&self) -> &str {
+        match self {
+            ScannerFilter::PriceAbove(_) => "priceAbove",
+            ScannerFilter::PriceBelow(_) => "priceBelow",
+            ScannerFilter::VolumeAbove(_) => "volumeAbove",
+            ScannerFilter::AvgVolumeAbove(_) => "avgVolumeAbove",
+            ScannerFilter::MarketCapAbove(_) => "marketCapAbove1e6",
+            ScannerFilter::MarketCapBelow(_) => "marketCapBelow1e6",
+            ScannerFilter::ChangePercAbove(_) => "changePercAbove",
+            ScannerFilter::ChangePercBelow(_) => "changePercBelow",
+            ScannerFilter::Other { tag, .. } => tag,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn value(&self) -> String {
+        match self {
+            ScannerFilter::PriceAbove(value)
+            | ScannerFilter::PriceBelow(value)
+            | ScannerFilter::MarketCapAbove(value)
+            | ScannerFilter::MarketCapBelow(value)
+            | ScannerFilter::ChangePercAbove(value)
+            | ScannerFilter::ChangePercBelow(value) => value.to_string(),
+            ScannerFilter::VolumeAbove(value) | ScannerFilter::AvgVolumeAbove(value) => {
+                value.to_string()
+            }
+            ScannerFilter::Other { value, .. } => value.clone(),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn is_valid_number(&self) -> bool {
+        match self {
+            ScannerFilter::PriceAbove(value)
+            | ScannerFilter::PriceBelow(value)
+            | ScannerFilter::MarketCapAbove(value)
+            | ScannerFilter::MarketCapBelow(value)
+            | ScannerFilter::ChangePercAbove(value)
+            | ScannerFilter::ChangePercBelow(value) => value.is_finite(),
+            ScannerFilter::VolumeAbove(value) | ScannerFilter::AvgVolumeAbove(value) => *value >= 0,
+            ScannerFilter::Other { .. } => true,
+        }
+    }
+}
+
+//==================================================================================================
+/// The instruments, locations, scan codes and filters TWS accepts, as listed by
+/// req_scanner_parameters.  ScannerParameters::parameter_list makes one.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ScannerParameterList {
+    pub instruments: HashSet<String>,
+    pub location_codes: HashSet<String>,
+    pub scan_codes: HashSet<String>,
+    pub filters: HashSet<String>,
+}
+
+//==================================================================================================
+/// An instrument scans can run over
+/// instrument_type - the code for ScannerSubscription::instrument, e.g. STK
+/// filters - ids of the ScannerFilterDefinitions that apply to it
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ScannerInstrument {
+    pub name: String,
+    pub instrument_type: String,
+    pub filters: Vec<String>,
+    pub group: String,
+    pub short_name: String,
+}
+
+//==================================================================================================
+/// A location scans can run in.  Locations nest, e.g. STK.US.MAJOR inside STK.US; parent is
+/// the code of the enclosing location, "" at the top.
+/// instruments - the instrument types available in it
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ScannerLocation {
+    pub display_name: String,
+    pub location_code: String,
+    pub instruments: Vec<String>,
+    pub parent: String,
+}
+
+//==================================================================================================
+/// A scan code with the instrument types it applies to
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ScannerScanType {
+    pub display_name: String,
+    pub scan_code: String,
+    pub instruments: Vec<String>,
+}
+
+//==================================================================================================
+/// A value a filter takes
+/// code - the tag to pass in the filter options, e.g. priceAbove
+/// field_type - the type TWS gives in its type attribute, e.g. scanner.filter.DoubleField
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ScannerFilterField {
+    pub code: String,
+    pub display_name: String,
+    pub field_type: String,
+}
+
+//==================================================================================================
+/// A filter, e.g. PRICE with the fields priceAbove and priceBelow
+/// kind - the element TWS lists it as, e.g. RangeFilter or SimpleFilter
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ScannerFilterDefinition {
+    pub id: String,
+    pub kind: String,
+    pub category: String,
+    pub fields: Vec<ScannerFilterField>,
+}
+
+//==================================================================================================
+/// The parsed XML Wrapper::scanner_parameters delivers
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ScannerParameters {
+    pub instruments: Vec<ScannerInstrument>,
+    pub locations: Vec<ScannerLocation>,
+    pub scan_types: Vec<ScannerScanType>,
+    pub filters: Vec<ScannerFilterDefinition>,
+}
+
+impl ScannerParameters {
+    /// Parses the XML received in Wrapper::scanner_parameters
+    pub fn from_xml(xml: &str) -> Result<Self, IBKRApiLibError> {
+        let root = xml::parse(xml)?;
+        if root.name != "ScanParameterResponse" {
+            return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+                -1,
+                TwsError::BadMessage.code().to_string(),
+                format!(
+                    "{} Expected scanner parameters with root ScanParameterResponse, got {}.",
+                    TwsError::BadMessage.message(),
+                    root.name
+                ),
+            )));
+        }
+
+        let instruments = children(&root, "InstrumentList", "Instrument")
+            .map(|instrument| ScannerInstrument {
+                name: instrument.child_text("name"),
+                instrument_type: instrument.child_text("type"),
+                filters: split_list(&instrument.child_text("filters")),
+                group: instrument.child_text("group"),
+                short_name: instrument.child_text("shortName"),
+            })
+            .collect();
+
+        let mut locations = Vec::new();
+        if let Some(tree) = root.find("LocationTree") {
+            add_locations(tree, "", &mut locations);
+        }
+
+        let scan_types = children(&root, "ScanTypeList", "ScanType")
+            .map(|scan_type| ScannerScanType {
+                display_name: scan_type.child_text("displayName"),
+                scan_code: scan_type.child_text("scanCode"),
+                instruments: split_list(&scan_type.child_text("instruments")),
+            })
+            .collect();
+
+        let filters = root
+            .find("FilterList")
+            .map(|list| {
+                list.children
+                    .iter()
+                    .map(|filter| ScannerFilterDefinition {
+                        id: filter.child_text("id"),
+                        kind: filter.name.clone(),
+                        category: filter.child_text("category"),
+                        fields: filter
+                            .find_all("AbstractField")
+                            .map(|field| ScannerFilterField {
+                                code: field.child_text("code"),
+                                display_name: field.child_text("displayName"),
+                                field_type: field
+                                    .attributes
+                                    .iter()
+                                    .find(|(name, _)| name == "type")
+                                    .map(|(_, value)| value.clone())
+                                    .unwrap_or_default(),
+                            })
+                            .collect(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(ScannerParameters {
+            instruments,
+            locations,
+            scan_types,
+            filters,
+        })
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The instrument with this type, e.g. STK
+    pub fn instrument(&self, instrument_type: &str) -> Option<&ScannerInstrument> {
+        self.instruments
+            .iter()
+            .find(|instrument| instrument.instrument_type == instrument_type)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The scan types that apply to an instrument type
+    pub fn scan_types_for(&self, instrument_type: &str) -> Vec<&ScannerScanType> {
+        self.scan_types
+            .iter()
+            .filter(|scan_type| {
+                scan_type
+                    .instruments
+                    .iter()
+                    .any(|other| other == instrument_type)
+            })
+            .collect()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The locations offering an instrument type
+    pub fn locations_for(&self, instrument_type: &str) -> Vec<&ScannerLocation> {
+        self.locations
+            .iter()
+            .filter(|location| {
+                location
+                    .instruments
+                    .iter()
+                    .any(|other| other == instrument_type)
+            })
+            .collect()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The filters that apply to an instrument type
+    pub fn filters_for(&self, instrument_type: &str) -> Vec<&ScannerFilterDefinition> {
+        match self.instrument(instrument_type) {
+            Some(instrument) => self
+                .filters
+                .iter()
+                .filter(|filter| instrument.filters.contains(&filter.id))
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The codes, for ScannerSubscriptionBuilder::build_validated
+    pub fn parameter_list(&self) -> ScannerParameterList {
+        ScannerParameterList {
+            instruments: self
+                .instruments
+                .iter()
+                .map(|instrument| instrument.instrument_type.clone())
+                .collect(),
+            location_codes: self
+                .locations
+                .iter()
+                .map(|location| location.location_code.clone())
+                .collect(),
+            scan_codes: self
+                .scan_types
+                .iter()
+                .map(|scan_type| scan_type.scan_code.clone())
+                .collect(),
+            filters: self
+                .filters
+                .iter()
+                .flat_map(|filter| filter.fields.iter().map(|field| field.code.clone()))
+                .collect(),
+        }
+    }
+}
+
+//==================================================================================================
+/// The elements named item in the list named list under parent
+fn children<'a>(
+    parent: &'a Element,
+    list: &'a str,
+    item: &'a str,
+) -> impl Iterator<Item = &'a Element> + 'a {
+    parent
+        .find(list)
+        .into_iter()
+        .flat_map(move |list| list.find_all(item))
+}
+
+//==================================================================================================
+/// Adds the locations of a LocationTree and of the trees nested in them
+fn add_locations(tree: &Element, parent: &str, locations: &mut Vec<ScannerLocation>) {
+    for location in tree.find_all("Location") {
+        let location_code = location.child_text("locationCode");
+        locations.push(ScannerLocation {
+            display_name: location.child_text("displayName"),
+            location_code: location_code.clone(),
+            instruments: split_list(&location.child_text("instruments")),
+            parent: parent.to_string(),
+        });
+        for nested in location.find_all("LocationTree") {
+            add_locations(nested, &location_code, locations);
+        }
+    }
+}
+
+//==================================================================================================
+fn split_list(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(|item| item.to_string())
+        .collect()
+}
+
+//==================================================================================================
+/// A scanner subscription with its filter options, as passed to
+/// EClient::req_scanner_subscription
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ScannerRequest {
+    pub subscription: ScannerSubscription,
+    pub filter_options: Vec<TagValue>,
+}
+
+//==================================================================================================
+/// Builds a ScannerRequest from typed values, catching the mistakes TWS only reports as
+/// error 165 or an empty scan
+#[derive(Clone, Debug)]
+pub struct ScannerSubscriptionBuilder {
+    instrument: ScanInstrument,
+    location: ScanLocation,
+    scan_code: ScanCode,
+    number_of_rows: i32,
+    stock_type_filter: String,
+    filters: Vec<ScannerFilter>,
+}
+
+impl ScannerSubscriptionBuilder {
+    pub fn new(instrument: ScanInstrument, location: ScanLocation, scan_code: ScanCode) -> Self {
+        ScannerSubscriptionBuilder {
+            instrument,
+            location,
+            scan_code,
+            number_of_rows: UNSET_INTEGER,
+            stock_type_filter: "".to_string(),
+            filters: vec![],
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Rows to return, at most MAX_SCANNER_ROWS.  TWS returns its default without.
+    pub fn rows(mut self, number_of_rows: i32) -> Self {
+        self.number_of_rows = number_of_rows;
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// e.g. "CORP" or "ETF"
+    pub fn stock_type_filter(mut self, stock_type_filter: &str) -> Self {
+        self.stock_type_filter = stock_type_filter.to_string();
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn filter(mut self, filter: ScannerFilter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Checks the values and builds the request.  Fails on a row count out of range, a filter
+    /// given twice, a non-finite or negative filter value, or an empty code.
+    pub fn build(&self) -> Result<ScannerRequest, IBKRApiLibError> {
+        if self.number_of_rows != UNSET_INTEGER
+            && (self.number_of_rows < 1 || self.number_of_rows > MAX_SCANNER_ROWS)
+        {
+            return Err(invalid_scanner(format!(
+                "{} rows requested, 1 to {} allowed",
+                self.number_of_rows, MAX_SCANNER_ROWS
+            )));
+        }
+        for (name, value) in [
+            ("instrument", self.instrument.as_str()),
+            ("location code", self.location.as_str()),
+            ("scan code", self.scan_code.as_str()),
+        ]
+        .iter()
+        {
+            if value.is_empty() {
+                return Err(invalid_scanner(format!("the {} is empty", name)));
+            }
+        }
+        let mut tags = HashSet::new();
+        for filter in self.filters.iter() {
+            if !tags.insert(filter.tag()) {
+                return Err(invalid_scanner(format!(
+                    "filter {} given twice",
+                    filter.tag()
+                )));
+            }
+            if !filter.is_valid_number() {
+                return Err(invalid_scanner(format!(
+                    "filter {} has the invalid value {}",
+                    filter.tag(),
+                    filter.value()
+                )));
+            }
+        }
+
+        Ok(ScannerRequest {
+            subscription: ScannerSubscription {
+                number_of_rows: self.number_of_rows,
+                instrument: self.instrument.as_str().to_string(),
+                location_code: self.location.as_str().to_string(),
+                scan_code: self.scan_code.as_str().to_string(),
+                above_price: UNSET_DOUBLE,
+                below_price: UNSET_DOUBLE,
+                above_volume: UNSET_INTEGER,
+                market_cap_above: UNSET_DOUBLE,
+                market_cap_below: UNSET_DOUBLE,
+                coupon_rate_above: UNSET_DOUBLE,
+                coupon_rate_below: UNSET_DOUBLE,
+                average_option_volume_above: UNSET_INTEGER,
+                stock_type_filter: self.stock_type_filter.clone(),
+                ..Default::default()
+            },
+            filter_options: self
+                .filters
+                .iter()
+                .map(|filter| TagValue::new(filter.tag().to_string(), filter.value()))
+                .collect(),
+        })
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// build, then also checks the instrument, location code, scan code and filters are in the
+    /// scanner parameters
+    pub fn build_validated(
+        &self,
+        parameters: &ScannerParameterList,
+    ) -> Result<ScannerRequest, IBKRApiLibError> {
+        let request = self.build()?;
+        let checks = [
+            (
+                "instrument",
+                self.instrument.as_str(),
+                &parameters.instruments,
+            ),
+            (
+                "location code",
+                self.location.as_str(),
+                &parameters.location_codes,
+            ),
+            ("scan code", self.scan_code.as_str(), &parameters.scan_codes),
+        ];
+        for (name, value, known) in checks.iter() {
+            if !known.contains(*value) {
+                return Err(invalid_scanner(format!(
+                    "{} {} is not in the scanner parameters",
+                    name, value
+                )));
+            }
+        }
+        for filter in self.filters.iter() {
+            if !parameters.filters.contains(filter.tag()) {
+                return Err(invalid_scanner(format!(
+                    "filter {} is not in the scanner parameters",
+                    filter.tag()
+                )));
+            }
+        }
+        Ok(request)
+    }
+}
+
+//==================================================================================================
+fn invalid_scanner(detail: String) -> IBKRApiLibError {
+    IBKRApiLibError::ApiError(TwsApiReportableError::new(
+        -1,
+        TwsError::InvalidRequest.code().to_string(),
+        format!(
+            "{} Invalid scanner: {}.",
+            TwsError::InvalidRequest.message(),
+            detail
+        ),
+    ))
+}
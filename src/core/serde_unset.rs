@@ -0,0 +1,56 @@
+//! serde adapters writing the UNSET_DOUBLE / UNSET_INTEGER sentinels as missing values
+//!
+//! With the "serde-unset-none" feature the domain structs use these for their fields that
+//! default to a sentinel, so a serialized Order has null instead of 1.7976931348623157e308 for
+//! an unset price, and a missing field is read as unset like a null one.  They can also be
+//! used directly with #[serde(default = "...::unset", with = "...")].
+
+//==================================================================================================
+/// f64 fields where UNSET_DOUBLE is written as none
+pub mod unset_double {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::core::common::UNSET_DOUBLE;
+
+    pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        if *value == UNSET_DOUBLE {
+            serializer.serialize_none()
+        } else {
+            serializer.serialize_some(value)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(UNSET_DOUBLE))
+    }
+
+    /// The value of a missing field
+    pub fn unset() -> f64 {
+        UNSET_DOUBLE
+    }
+}
+
+//==================================================================================================
+/// i32 fields where UNSET_INTEGER is written as none
+pub mod unset_integer {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::core::common::UNSET_INTEGER;
+
+    pub fn serialize<S: Serializer>(value: &i32, serializer: S) -> Result<S::Ok, S::Error> {
+        if *value == UNSET_INTEGER {
+            serializer.serialize_none()
+        } else {
+            serializer.serialize_some(value)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+        Ok(Option::<i32>::deserialize(deserializer)?.unwrap_or(UNSET_INTEGER))
+    }
+
+    /// The value of a missing field
+    pub fn unset() -> i32 {
+        UNSET_INTEGER
+    }
+}
@@ -105,6 +105,7 @@ pub const MIN_SERVER_VER_WSHE_CALENDAR: i32 = 161;
 pub const MIN_SERVER_VER_AUTO_CANCEL_PARENT: i32 = 162;
 pub const MIN_SERVER_VER_FRACTIONAL_SIZE_SUPPORT: i32 = 163;
 pub const MIN_SERVER_VER_SIZE_RULES: i32 = 164;
+pub const MIN_SERVER_VER_HISTORICAL_SCHEDULE: i32 = 165;
 
 // 100+ messaging */
 // 100 = enhanced handshake, msg length prefixes
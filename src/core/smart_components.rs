@@ -0,0 +1,103 @@
+//! The exchanges behind the letters of SMART market data
+//!
+//! Market data routed through SMART names exchanges by single letters, e.g. the bid and ask
+//! exchanges of a quote.  Wrapper::tick_req_params reports the bbo_exchange of a market data
+//! request, the id of the letter mapping it uses, and EClient::req_smart_components returns the
+//! mapping of that id.  The Decoder records both, so EClient::exchange_names translates the
+//! letters of a request into exchange names.
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::common::SmartComponent;
+
+//==================================================================================================
+/// Exchange letter -> (bit number, exchange name)
+pub type SmartComponentMap = HashMap<char, (i32, String)>;
+
+//==================================================================================================
+/// The map of a SmartComponents message.  Components without a letter are skipped.
+pub fn smart_component_map(components: &[SmartComponent]) -> SmartComponentMap {
+    components
+        .iter()
+        .filter_map(|component| {
+            let letter = component.exchange_letter.chars().next()?;
+            Some((letter, (component.bit_number, component.exchange.clone())))
+        })
+        .collect()
+}
+
+//==================================================================================================
+/// The exchange names of letters, e.g. "ABQ", keeping the letters missing from the map
+pub fn exchange_names(map: &SmartComponentMap, letters: &str) -> Vec<String> {
+    letters
+        .chars()
+        .filter(|letter| !letter.is_whitespace())
+        .map(|letter| {
+            map.get(&letter)
+                .map(|(_, exchange)| exchange.clone())
+                .unwrap_or_else(|| letter.to_string())
+        })
+        .collect()
+}
+
+//==================================================================================================
+/// The bbo_exchange of the market data requests and the maps received for them
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SmartComponents {
+    bbo_exchanges: HashMap<i32, String>,
+    maps: HashMap<String, SmartComponentMap>,
+}
+
+impl SmartComponents {
+    pub fn new() -> Self {
+        SmartComponents::default()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Records the bbo_exchange of a market data request, e.g. from Wrapper::tick_req_params
+    pub fn set_bbo_exchange(&mut self, ticker_id: i32, bbo_exchange: &str) {
+        if !bbo_exchange.is_empty() {
+            self.bbo_exchanges
+                .insert(ticker_id, bbo_exchange.to_string());
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn bbo_exchange(&self, ticker_id: i32) -> Option<&str> {
+        self.bbo_exchanges.get(&ticker_id).map(|bbo| bbo.as_str())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Records the map of a bbo_exchange, e.g. from Wrapper::smart_components
+    pub fn insert(&mut self, bbo_exchange: &str, map: SmartComponentMap) {
+        self.maps.insert(bbo_exchange.to_string(), map);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn map(&self, bbo_exchange: &str) -> Option<&SmartComponentMap> {
+        self.maps.get(bbo_exchange)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The bbo_exchanges of the requests whose map was not received yet
+    pub fn missing(&self) -> Vec<String> {
+        let mut missing: Vec<String> = self
+            .bbo_exchanges
+            .values()
+            .filter(|bbo| !self.maps.contains_key(*bbo))
+            .cloned()
+            .collect();
+        missing.sort();
+        missing.dedup();
+        missing
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The exchange names of the letters of a market data request, None until its map arrived
+    pub fn exchange_names(&self, ticker_id: i32, letters: &str) -> Option<Vec<String>> {
+        self.bbo_exchange(ticker_id)
+            .and_then(|bbo| self.map(bbo))
+            .map(|map| exchange_names(map, letters))
+    }
+}
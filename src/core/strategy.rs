@@ -0,0 +1,330 @@
+//! Trading strategies written once and run against any source of market data and fills
+//!
+//! A Strategy reacts to bars, ticks, fills and timer events and acts through a
+//! StrategyContext: subscribing to data feeds, placing orders and scheduling timers.  Runner
+//! delivers the events to the strategy.  Where the events come from and where the orders go is
+//! up to the context and whoever sends the events: LiveContext places orders through an
+//! EClient, and a wrapper forwards the callbacks of its feeds to the runner's channel.  A
+//! simulation or backtest implements StrategyContext and sends its own events the same way,
+//! calling Runner::fire_timers as its simulation clock advances.
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::core::client::{EClient, POISONED_MUTEX};
+use crate::core::common::{BarData, BarSize, RealTimeBar, TickByTickType, WhatToShow};
+use crate::core::contract::Contract;
+use crate::core::errors::IBKRApiLibError;
+use crate::core::execution::Execution;
+use crate::core::order::Order;
+use crate::core::tick_timeline::TimelineEvent;
+use crate::core::timers::{TimerId, Timers};
+use crate::core::timestamps::timestamp_from_wire;
+use crate::core::wrapper::Wrapper;
+
+/// Size of the bars LiveContext subscribes to, the only size TWS streams
+pub const LIVE_BAR_SIZE: BarSize = BarSize::_5Secs;
+
+//==================================================================================================
+/// An event for a strategy
+/// feed_id - the id subscribe_bars or subscribe_ticks returned for the feed
+#[derive(Clone, Debug)]
+pub enum StrategyEvent {
+    Bar {
+        feed_id: i32,
+        bar: BarData,
+    },
+    Tick {
+        feed_id: i32,
+        tick: TimelineEvent,
+    },
+    Fill {
+        contract: Box<Contract>,
+        execution: Execution,
+    },
+    /// time - the time the timer was due at
+    Timer {
+        timer_id: TimerId,
+        time: Instant,
+    },
+}
+
+impl StrategyEvent {
+    /// The Bar event for a Wrapper::realtime_bar callback
+    pub fn from_realtime_bar(req_id: i32, bar: &RealTimeBar) -> Self {
+        StrategyEvent::Bar {
+            feed_id: req_id,
+            bar: BarData::new(
+                timestamp_from_wire(&bar.date_time),
+                bar.open,
+                bar.high,
+                bar.low,
+                bar.close,
+                bar.volume.clone(),
+                bar.count,
+                bar.wap,
+            ),
+        }
+    }
+}
+
+//==================================================================================================
+/// What a strategy can do
+pub trait StrategyContext {
+    /// Starts a feed of bars for the contract and returns its feed id
+    fn subscribe_bars(&mut self, contract: &Contract) -> Result<i32, IBKRApiLibError>;
+
+    //----------------------------------------------------------------------------------------------
+    /// Starts a feed of trades for the contract and returns its feed id
+    fn subscribe_ticks(&mut self, contract: &Contract) -> Result<i32, IBKRApiLibError>;
+
+    //----------------------------------------------------------------------------------------------
+    /// Places an order and returns its order id
+    fn place_order(&mut self, contract: &Contract, order: &Order) -> Result<i32, IBKRApiLibError>;
+
+    //----------------------------------------------------------------------------------------------
+    fn cancel_order(&mut self, order_id: i32) -> Result<(), IBKRApiLibError>;
+
+    //----------------------------------------------------------------------------------------------
+    /// The strategy's timers, fired as on_timer
+    fn timers(&mut self) -> &mut Timers;
+
+    //----------------------------------------------------------------------------------------------
+    /// The current time: Instant::now() live, the simulation clock in a backtest
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+//==================================================================================================
+/// A trading strategy.  Every event handler does nothing by default.
+pub trait Strategy {
+    /// Called once by Runner::start, to subscribe to the feeds the strategy needs
+    fn on_start(&mut self, _context: &mut dyn StrategyContext) -> Result<(), IBKRApiLibError> {
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn on_bar(
+        &mut self,
+        _context: &mut dyn StrategyContext,
+        _feed_id: i32,
+        _bar: &BarData,
+    ) -> Result<(), IBKRApiLibError> {
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn on_tick(
+        &mut self,
+        _context: &mut dyn StrategyContext,
+        _feed_id: i32,
+        _tick: &TimelineEvent,
+    ) -> Result<(), IBKRApiLibError> {
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn on_fill(
+        &mut self,
+        _context: &mut dyn StrategyContext,
+        _contract: &Contract,
+        _execution: &Execution,
+    ) -> Result<(), IBKRApiLibError> {
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Called when a timer of the context's timers is due
+    ///
+    /// # Arguments
+    /// * timer_id - the id the timer was scheduled with
+    /// * time - the time the timer was due at
+    fn on_timer(
+        &mut self,
+        _context: &mut dyn StrategyContext,
+        _timer_id: TimerId,
+        _time: Instant,
+    ) -> Result<(), IBKRApiLibError> {
+        Ok(())
+    }
+}
+
+//==================================================================================================
+/// Binds a strategy to a context and delivers events to it
+pub struct Runner<S: Strategy, C: StrategyContext> {
+    strategy: S,
+    context: C,
+}
+
+impl<S: Strategy, C: StrategyContext> Runner<S, C> {
+    pub fn new(strategy: S, context: C) -> Self {
+        Runner { strategy, context }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn strategy(&self) -> &S {
+        &self.strategy
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn context(&mut self) -> &mut C {
+        &mut self.context
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Calls the strategy's on_start
+    pub fn start(&mut self) -> Result<(), IBKRApiLibError> {
+        self.strategy.on_start(&mut self.context)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Passes one event to the strategy
+    pub fn handle(&mut self, event: &StrategyEvent) -> Result<(), IBKRApiLibError> {
+        match event {
+            StrategyEvent::Bar { feed_id, bar } => {
+                self.strategy.on_bar(&mut self.context, *feed_id, bar)
+            }
+            StrategyEvent::Tick { feed_id, tick } => {
+                self.strategy.on_tick(&mut self.context, *feed_id, tick)
+            }
+            StrategyEvent::Fill {
+                contract,
+                execution,
+            } => self
+                .strategy
+                .on_fill(&mut self.context, contract, execution),
+            StrategyEvent::Timer { timer_id, time } => {
+                self.strategy.on_timer(&mut self.context, *timer_id, *time)
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Passes the strategy a Timer event for every timer due at now
+    pub fn fire_timers(&mut self, now: Instant) -> Result<(), IBKRApiLibError> {
+        for (timer_id, time) in self.context.timers().due(now) {
+            self.handle(&StrategyEvent::Timer { timer_id, time })?;
+        }
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Starts the strategy and passes it the events received until every sender is dropped,
+    /// firing the context's timers in between as their deadlines pass.  Timers due when an
+    /// event arrives fire before it, so a burst of events does not delay them.  Stops at the
+    /// first error a handler returns.
+    pub fn run(&mut self, events: &Receiver<StrategyEvent>) -> Result<(), IBKRApiLibError> {
+        self.start()?;
+        loop {
+            let received = match self.context.timers().next_deadline() {
+                Some(deadline) => {
+                    events.recv_timeout(deadline.saturating_duration_since(self.context.now()))
+                }
+                None => events.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            let now = self.context.now();
+            self.fire_timers(now)?;
+            match received {
+                Ok(event) => self.handle(&event)?,
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        }
+    }
+}
+
+//==================================================================================================
+/// Context trading through a connected EClient.  Bar feeds are 5 second TRADES real time bars,
+/// tick feeds tick by tick Last trades.  The wrapper should send the runner
+/// StrategyEvent::from_realtime_bar for realtime_bar, Tick events for tick_by_tick_all_last
+/// and Fill events for exec_details.
+pub struct LiveContext<T>
+where
+    T: Wrapper,
+{
+    client: Arc<Mutex<EClient<T>>>,
+    next_order_id: i32,
+    next_req_id: i32,
+    timers: Timers,
+}
+
+impl<T> LiveContext<T>
+where
+    T: Wrapper + Send + Sync + 'static,
+{
+    /// # Arguments
+    /// * client - the connected client
+    /// * next_order_id - the next valid order id TWS reported
+    /// * first_req_id - the feed ids are consecutive request ids from this one
+    pub fn new(client: Arc<Mutex<EClient<T>>>, next_order_id: i32, first_req_id: i32) -> Self {
+        LiveContext {
+            client,
+            next_order_id,
+            next_req_id: first_req_id,
+            timers: Timers::new(),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn take_req_id(&mut self) -> i32 {
+        let req_id = self.next_req_id;
+        self.next_req_id += 1;
+        req_id
+    }
+}
+
+impl<T> StrategyContext for LiveContext<T>
+where
+    T: Wrapper + Send + Sync + 'static,
+{
+    fn subscribe_bars(&mut self, contract: &Contract) -> Result<i32, IBKRApiLibError> {
+        let req_id = self.take_req_id();
+        self.client
+            .lock()
+            .expect(POISONED_MUTEX)
+            .req_real_time_bars(
+                req_id,
+                contract,
+                &LIVE_BAR_SIZE,
+                &WhatToShow::Trades,
+                false,
+                vec![],
+            )?;
+        Ok(req_id)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn subscribe_ticks(&mut self, contract: &Contract) -> Result<i32, IBKRApiLibError> {
+        let req_id = self.take_req_id();
+        self.client
+            .lock()
+            .expect(POISONED_MUTEX)
+            .req_tick_by_tick_data(req_id, contract, TickByTickType::Last, 0, false)?;
+        Ok(req_id)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn place_order(&mut self, contract: &Contract, order: &Order) -> Result<i32, IBKRApiLibError> {
+        let order_id = self.next_order_id;
+        self.client
+            .lock()
+            .expect(POISONED_MUTEX)
+            .place_order(order_id, contract, order)?;
+        self.next_order_id += 1;
+        Ok(order_id)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn cancel_order(&mut self, order_id: i32) -> Result<(), IBKRApiLibError> {
+        self.client
+            .lock()
+            .expect(POISONED_MUTEX)
+            .cancel_order(order_id)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn timers(&mut self) -> &mut Timers {
+        &mut self.timers
+    }
+}
@@ -0,0 +1,172 @@
+//! Streaming requests EClient keeps track of so they can be cancelled on shutdown, and the
+//! application metadata attached to requests
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::client::POISONED_MUTEX;
+use crate::core::request_context;
+
+//==================================================================================================
+/// A streaming request which stays active until cancelled
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Subscription {
+    MktData(i32),
+    TickByTick(i32),
+    MktDepth { req_id: i32, is_smart_depth: bool },
+    RealTimeBars(i32),
+    HistoricalData(i32),
+    ScannerSubscription(i32),
+    AccountSummary(i32),
+    PositionsMulti(i32),
+    AccountUpdatesMulti(i32),
+    Pnl(i32),
+    PnlSingle(i32),
+    Positions,
+    NewsBulletins,
+    AccountUpdates(String),
+}
+
+impl fmt::Display for Subscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Subscription {
+    /// The request id of the subscription, None for the ones without
+    pub fn req_id(&self) -> Option<i32> {
+        match *self {
+            Subscription::MktData(req_id)
+            | Subscription::TickByTick(req_id)
+            | Subscription::MktDepth { req_id, .. }
+            | Subscription::RealTimeBars(req_id)
+            | Subscription::HistoricalData(req_id)
+            | Subscription::ScannerSubscription(req_id)
+            | Subscription::AccountSummary(req_id)
+            | Subscription::PositionsMulti(req_id)
+            | Subscription::AccountUpdatesMulti(req_id)
+            | Subscription::Pnl(req_id)
+            | Subscription::PnlSingle(req_id) => Some(req_id),
+            Subscription::Positions
+            | Subscription::NewsBulletins
+            | Subscription::AccountUpdates(_) => None,
+        }
+    }
+}
+
+//==================================================================================================
+/// An event with the metadata attached to its request
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tagged<M, E> {
+    pub req_id: i32,
+    pub metadata: Option<M>,
+    pub event: E,
+}
+
+//==================================================================================================
+/// Application metadata keyed by request id, e.g. the strategy or order a subscription
+/// belongs to.  Clones share the entries: attach when making a request, and in the wrapper,
+/// current returns the metadata of the request a callback answers.  Registered with
+/// EClient::track_metadata, the entry of a request is dropped when it ends: a subscription when
+/// it is cancelled, other requests with their end message or an error for them.
+#[derive(Debug)]
+pub struct RequestMetadata<M> {
+    entries: Arc<Mutex<HashMap<i32, M>>>,
+}
+
+impl<M> Clone for RequestMetadata<M> {
+    fn clone(&self) -> Self {
+        RequestMetadata {
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+impl<M> Default for RequestMetadata<M> {
+    fn default() -> Self {
+        RequestMetadata {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl<M> RequestMetadata<M> {
+    pub fn new() -> Self {
+        RequestMetadata::default()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Attaches metadata to a request, returning what was attached before
+    pub fn attach(&self, req_id: i32, metadata: M) -> Option<M> {
+        self.entries
+            .lock()
+            .expect(POISONED_MUTEX)
+            .insert(req_id, metadata)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn detach(&self, req_id: i32) -> Option<M> {
+        self.entries.lock().expect(POISONED_MUTEX).remove(&req_id)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn contains(&self, req_id: i32) -> bool {
+        self.entries
+            .lock()
+            .expect(POISONED_MUTEX)
+            .contains_key(&req_id)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn len(&self) -> usize {
+        self.entries.lock().expect(POISONED_MUTEX).len()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<M: Clone> RequestMetadata<M> {
+    /// The metadata attached to a request
+    pub fn get(&self, req_id: i32) -> Option<M> {
+        self.entries
+            .lock()
+            .expect(POISONED_MUTEX)
+            .get(&req_id)
+            .cloned()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The metadata of the request the calling thread handles a message of, i.e. of the
+    /// message a wrapper callback is called for
+    pub fn current(&self) -> Option<M> {
+        self.get(request_context::current_req_id()?)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Pairs an event of a request with its metadata
+    pub fn tag<E>(&self, req_id: i32, event: E) -> Tagged<M, E> {
+        Tagged {
+            req_id,
+            metadata: self.get(req_id),
+            event,
+        }
+    }
+}
+
+//==================================================================================================
+/// Metadata EClient drops the entries of cancelled subscriptions from
+pub(crate) trait MetadataStore: Send {
+    fn forget(&self, req_id: i32);
+}
+
+impl<M: Send> MetadataStore for RequestMetadata<M> {
+    fn forget(&self, req_id: i32) {
+        self.detach(req_id);
+    }
+}
@@ -0,0 +1,347 @@
+//! Mock TWS server for integration tests
+//!
+//! MockTws listens on a local port and performs the version handshake, so EClient::connect
+//! works against it like against TWS.  It serves one connection at a time, and accepts the
+//! next one once the client disconnected or the connection failed.  Script it with canned responses to requests and the
+//! messages to send, then assert on the requests the client sent:
+//!
+//! ```no_run
+//! use std::sync::{Arc, Mutex};
+//! use std::time::Duration;
+//! use twsapi::core::client::EClient;
+//! use twsapi::core::messages::OutgoingMessageIds;
+//! use twsapi::core::server_versions::MAX_CLIENT_VER;
+//! use twsapi::core::testing::MockTws;
+//! use twsapi::examples::defaults::DefaultWrapper;
+//!
+//! # fn main() -> Result<(), twsapi::core::errors::IBKRApiLibError> {
+//! let mock = MockTws::start(MAX_CLIENT_VER)?;
+//! // TWS sends the next valid order id once the API is started
+//! mock.respond_to(OutgoingMessageIds::StartApi, vec![vec!["9", "1", "1"]]);
+//!
+//! let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+//! client.connect("127.0.0.1", mock.port(), 0)?;
+//! let start_api = mock.expect_message(OutgoingMessageIds::StartApi, Duration::from_secs(1))?;
+//! assert_eq!("0", start_api[2]);
+//! # Ok(())
+//! # }
+//! ```
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use log::*;
+
+use crate::core::connection::CLIENT_ID_IN_USE;
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+use crate::core::log_targets::CONNECTION;
+use crate::core::messages::{make_message, read_fields, OutgoingMessageIds};
+
+const MOCK_POISONED_MUTEX: &str = "MockTws mutex was poisoned";
+const API_PREFIX: &[u8] = b"API\0";
+
+/// Builds the messages to send in response to a request, from the request's fields
+pub type MockResponder = Box<dyn Fn(&[String]) -> Vec<Vec<String>> + Send>;
+
+//==================================================================================================
+#[derive(Default)]
+struct MockState {
+    stream: Option<TcpStream>,
+    handshake: Option<String>,
+    rejected_client_ids: Vec<i32>,
+    queued: Vec<Vec<String>>,
+    unclaimed: VecDeque<Vec<String>>,
+    received: Vec<Vec<String>>,
+    responders: Vec<(i32, MockResponder)>,
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<MockState>,
+    message_received: Condvar,
+}
+
+//==================================================================================================
+/// Local server speaking the TWS protocol to one client connection
+pub struct MockTws {
+    port: u32,
+    shared: Arc<Shared>,
+}
+
+impl MockTws {
+    /// Binds a free port on 127.0.0.1 and waits for a client in the background
+    ///
+    /// # Arguments
+    /// * server_version - the server version reported in the handshake
+    pub fn start(server_version: i32) -> Result<Self, IBKRApiLibError> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let port = listener.local_addr()?.port() as u32;
+        let shared = Arc::new(Shared::default());
+
+        let server_shared = shared.clone();
+        thread::spawn(move || {
+            if let Err(err) = serve(listener, server_version, server_shared) {
+                debug!(target: CONNECTION, "MockTws stopped: {}", err);
+            }
+        });
+        Ok(MockTws { port, shared })
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Port to pass to EClient::connect
+    pub fn port(&self) -> u32 {
+        self.port
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Sends the same messages every time the client sends a request of this type
+    ///
+    /// # Arguments
+    /// * message_id - the request type
+    /// * responses - the fields of each message to send, starting with the message id
+    pub fn respond_to(&self, message_id: OutgoingMessageIds, responses: Vec<Vec<&str>>) {
+        let responses: Vec<Vec<String>> = responses
+            .iter()
+            .map(|fields| fields.iter().map(|field| field.to_string()).collect())
+            .collect();
+        self.respond_with(message_id, Box::new(move |_| responses.clone()));
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Calls a responder every time the client sends a request of this type, e.g. to echo the
+    /// request id.  Responders registered for the same type all run, in registration order.
+    pub fn respond_with(&self, message_id: OutgoingMessageIds, responder: MockResponder) {
+        self.shared
+            .state
+            .lock()
+            .expect(MOCK_POISONED_MUTEX)
+            .responders
+            .push((message_id as i32, responder));
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Sends a message to the client.  Messages sent before the client connected are sent
+    /// once it starts the API.
+    ///
+    /// # Arguments
+    /// * fields - the message fields, starting with the message id
+    pub fn send(&self, fields: &[&str]) -> Result<(), IBKRApiLibError> {
+        let fields: Vec<String> = fields.iter().map(|field| field.to_string()).collect();
+        let mut state = self.shared.state.lock().expect(MOCK_POISONED_MUTEX);
+        match state.stream.as_mut() {
+            Some(stream) => write_message(stream, &fields),
+            None => {
+                state.queued.push(fields);
+                Ok(())
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Waits for the client to send a request of this type and returns its fields.  Each
+    /// request is returned once; requests of other types stay available to later calls.
+    pub fn expect_message(
+        &self,
+        message_id: OutgoingMessageIds,
+        timeout: Duration,
+    ) -> Result<Vec<String>, IBKRApiLibError> {
+        let message_id = (message_id as i32).to_string();
+        let deadline = Instant::now() + timeout;
+        let mut state = self.shared.state.lock().expect(MOCK_POISONED_MUTEX);
+        loop {
+            let position = state
+                .unclaimed
+                .iter()
+                .position(|fields| fields.first() == Some(&message_id));
+            if let Some(position) = position {
+                return Ok(state.unclaimed.remove(position).unwrap_or_default());
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(RecvTimeoutError::Timeout.into());
+            }
+            state = self
+                .shared
+                .message_received
+                .wait_timeout(state, deadline - now)
+                .expect(MOCK_POISONED_MUTEX)
+                .0;
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Answers a start of the API with this client id as TWS does when another client uses it:
+    /// with error 326, closing the connection
+    pub fn reject_client_id(&self, client_id: i32) {
+        self.shared
+            .state
+            .lock()
+            .expect(MOCK_POISONED_MUTEX)
+            .rejected_client_ids
+            .push(client_id);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The version range and connect options the client sent in the handshake, e.g.
+    /// "v100..157 +PACEAPI", None before it connected
+    pub fn handshake(&self) -> Option<String> {
+        self.shared
+            .state
+            .lock()
+            .expect(MOCK_POISONED_MUTEX)
+            .handshake
+            .clone()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Every request the client sent so far, in the order received
+    pub fn received(&self) -> Vec<Vec<String>> {
+        self.shared
+            .state
+            .lock()
+            .expect(MOCK_POISONED_MUTEX)
+            .received
+            .clone()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Closes the connection, as TWS does when it shuts down
+    pub fn close(&self) {
+        if let Some(stream) = self
+            .shared
+            .state
+            .lock()
+            .expect(MOCK_POISONED_MUTEX)
+            .stream
+            .take()
+        {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+impl Drop for MockTws {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+//==================================================================================================
+fn serve(
+    listener: TcpListener,
+    server_version: i32,
+    shared: Arc<Shared>,
+) -> Result<(), IBKRApiLibError> {
+    loop {
+        let (stream, _) = listener.accept()?;
+        // a broken connection must not stop the mock serving the next one
+        if let Err(err) = serve_connection(stream, server_version, &shared) {
+            warn!(target: CONNECTION, "MockTws connection failed: {}", err);
+        }
+        shared.state.lock().expect(MOCK_POISONED_MUTEX).stream = None;
+    }
+}
+
+//==================================================================================================
+fn serve_connection(
+    mut stream: TcpStream,
+    server_version: i32,
+    shared: &Shared,
+) -> Result<(), IBKRApiLibError> {
+    let mut prefix = [0u8; 4];
+    stream.read_exact(&mut prefix)?;
+    if prefix != API_PREFIX {
+        return Err(IBKRApiLibError::ApiError(TwsApiReportableError::new(
+            -1,
+            TwsError::BadMessage.code().to_string(),
+            format!(
+                "{} MockTws got {:?} instead of the API prefix.",
+                TwsError::BadMessage.message(),
+                prefix
+            ),
+        )));
+    }
+    // the supported version range, which the mock does not check
+    let handshake = read_frame(&mut stream)?;
+    shared.state.lock().expect(MOCK_POISONED_MUTEX).handshake = Some(handshake);
+    let conn_time = Utc::now().format("%Y%m%d %H:%M:%S UTC").to_string();
+    write_message(&mut stream, &[server_version.to_string(), conn_time])?;
+
+    loop {
+        let text = match read_frame(&mut stream) {
+            Ok(text) => text,
+            // the client disconnected
+            Err(_) => return Ok(()),
+        };
+        let fields = read_fields(&text);
+        debug!(target: CONNECTION, "MockTws received: {:?}", fields);
+
+        let mut state = shared.state.lock().expect(MOCK_POISONED_MUTEX);
+        if is_rejected_start_api(&fields, &state.rejected_client_ids) {
+            let rejection = [
+                "4",
+                "2",
+                "-1",
+                &CLIENT_ID_IN_USE.to_string(),
+                "Unable to connect as the client id is already in use. Retry with a unique client id.",
+            ]
+            .iter()
+            .map(|field| field.to_string())
+            .collect::<Vec<String>>();
+            write_message(&mut stream, &rejection)?;
+            state.received.push(fields);
+            let _ = stream.shutdown(Shutdown::Both);
+            return Ok(());
+        }
+        if state.stream.is_none() {
+            // EClient::connect only reads the handshake reply, so anything else is sent once
+            // the client starts the API
+            for queued in std::mem::take(&mut state.queued) {
+                write_message(&mut stream, &queued)?;
+            }
+            state.stream = Some(stream.try_clone()?);
+        }
+        let responses: Vec<Vec<String>> = state
+            .responders
+            .iter()
+            .filter(|(message_id, _)| fields.first() == Some(&message_id.to_string()))
+            .flat_map(|(_, responder)| responder(&fields))
+            .collect();
+        for response in responses.iter() {
+            write_message(&mut stream, response)?;
+        }
+        state.received.push(fields.clone());
+        state.unclaimed.push_back(fields);
+        shared.message_received.notify_all();
+    }
+}
+
+//==================================================================================================
+fn is_rejected_start_api(fields: &[String], rejected_client_ids: &[i32]) -> bool {
+    fields.first() == Some(&(OutgoingMessageIds::StartApi as i32).to_string())
+        && fields
+            .get(2)
+            .and_then(|client_id| client_id.parse::<i32>().ok())
+            .is_some_and(|client_id| rejected_client_ids.contains(&client_id))
+}
+
+//==================================================================================================
+fn read_frame(stream: &mut TcpStream) -> Result<String, IBKRApiLibError> {
+    let mut size = [0u8; 4];
+    stream.read_exact(&mut size)?;
+    let mut payload = vec![0u8; u32::from_be_bytes(size) as usize];
+    stream.read_exact(&mut payload)?;
+    Ok(String::from_utf8_lossy(&payload).to_string())
+}
+
+//==================================================================================================
+fn write_message(stream: &mut TcpStream, fields: &[String]) -> Result<(), IBKRApiLibError> {
+    let text: String = fields.iter().map(|field| format!("{}\0", field)).collect();
+    stream.write_all(&make_message(&text)?)?;
+    Ok(())
+}
@@ -0,0 +1,146 @@
+//! Typed tick-by-tick data
+//!
+//! A TickByTick message carries one of three kinds of tick, told apart by its tick type.  The
+//! Decoder decodes it into a TickByTickEvent, whose variant is the tick type, before passing
+//! it to the tick_by_tick_ callbacks of the wrapper.  EClient::subscribe_tick_by_tick returns
+//! the events of a req_tick_by_tick_data request as they are.
+use std::fmt;
+
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+
+use crate::core::common::{TickAttribBidAsk, TickAttribLast, TickByTickType};
+
+//==================================================================================================
+/// A trade, of tick type Last or AllLast
+/// time - epoch seconds
+/// conditions - the special conditions of the trade, space separated
+/// past_limit - the price is past the limit of a limit order of the contract
+/// unreported - the trade was not reported to the tape
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct LastTick {
+    pub time: i64,
+    pub price: f64,
+    pub size: BigDecimal,
+    pub exchange: String,
+    pub conditions: String,
+    pub past_limit: bool,
+    pub unreported: bool,
+}
+
+impl LastTick {
+    pub fn attrib(&self) -> TickAttribLast {
+        TickAttribLast::new(self.past_limit, self.unreported)
+    }
+}
+
+impl fmt::Display for LastTick {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "time: {}, price: {}, size: {}, exchange: {}, conditions: {}, past_limit: {}, \
+             unreported: {}",
+            self.time,
+            self.price,
+            self.size,
+            self.exchange,
+            self.conditions,
+            self.past_limit,
+            self.unreported
+        )
+    }
+}
+
+//==================================================================================================
+/// A quote, of tick type BidAsk
+/// bid_past_low - the bid is below the day's low
+/// ask_past_high - the ask is above the day's high
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct BidAskTick {
+    pub time: i64,
+    pub bid_price: f64,
+    pub ask_price: f64,
+    pub bid_size: BigDecimal,
+    pub ask_size: BigDecimal,
+    pub bid_past_low: bool,
+    pub ask_past_high: bool,
+}
+
+impl BidAskTick {
+    pub fn attrib(&self) -> TickAttribBidAsk {
+        TickAttribBidAsk::new(self.bid_past_low, self.ask_past_high)
+    }
+}
+
+impl fmt::Display for BidAskTick {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "time: {}, bid_price: {}, ask_price: {}, bid_size: {}, ask_size: {}, \
+             bid_past_low: {}, ask_past_high: {}",
+            self.time,
+            self.bid_price,
+            self.ask_price,
+            self.bid_size,
+            self.ask_size,
+            self.bid_past_low,
+            self.ask_past_high
+        )
+    }
+}
+
+//==================================================================================================
+/// A midpoint, of tick type MidPoint
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct MidPointTick {
+    pub time: i64,
+    pub mid_point: f64,
+}
+
+impl fmt::Display for MidPointTick {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "time: {}, mid_point: {}", self.time, self.mid_point)
+    }
+}
+
+//==================================================================================================
+/// A tick of a req_tick_by_tick_data request, one variant per tick type
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum TickByTickEvent {
+    Last(LastTick),
+    AllLast(LastTick),
+    BidAsk(BidAskTick),
+    MidPoint(MidPointTick),
+}
+
+impl TickByTickEvent {
+    pub fn tick_type(&self) -> TickByTickType {
+        match self {
+            TickByTickEvent::Last(_) => TickByTickType::Last,
+            TickByTickEvent::AllLast(_) => TickByTickType::AllLast,
+            TickByTickEvent::BidAsk(_) => TickByTickType::BidAsk,
+            TickByTickEvent::MidPoint(_) => TickByTickType::MidPoint,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Epoch seconds
+    pub fn time(&self) -> i64 {
+        match self {
+            TickByTickEvent::Last(tick) | TickByTickEvent::AllLast(tick) => tick.time,
+            TickByTickEvent::BidAsk(tick) => tick.time,
+            TickByTickEvent::MidPoint(tick) => tick.time,
+        }
+    }
+}
+
+impl fmt::Display for TickByTickEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TickByTickEvent::Last(tick) => write!(f, "Last {}", tick),
+            TickByTickEvent::AllLast(tick) => write!(f, "AllLast {}", tick),
+            TickByTickEvent::BidAsk(tick) => write!(f, "BidAsk {}", tick),
+            TickByTickEvent::MidPoint(tick) => write!(f, "MidPoint {}", tick),
+        }
+    }
+}
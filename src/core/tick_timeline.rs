@@ -0,0 +1,61 @@
+//! Merges historical quotes and trades of one contract into a single timeline
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::common::{HistoricalTickBidAsk, HistoricalTickLast};
+use crate::core::timestamps::timestamp_seconds;
+
+//==================================================================================================
+/// One event of a quote and trade timeline
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum TimelineEvent {
+    Quote(HistoricalTickBidAsk),
+    Trade(HistoricalTickLast),
+}
+
+impl TimelineEvent {
+    /// Event time in epoch seconds, 0 if TWS sent none
+    pub fn time(&self) -> i64 {
+        let time = match self {
+            TimelineEvent::Quote(quote) => &quote.time,
+            TimelineEvent::Trade(trade) => &trade.time,
+        };
+        timestamp_seconds(time).unwrap_or(0)
+    }
+}
+
+impl fmt::Display for TimelineEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimelineEvent::Quote(quote) => write!(f, "Quote {}", quote),
+            TimelineEvent::Trade(trade) => write!(f, "Trade {}", trade),
+        }
+    }
+}
+
+//==================================================================================================
+/// Merges the ticks of historical_ticks_bid_ask and historical_ticks_last for the same
+/// contract into one list ordered by time.
+///
+/// TWS timestamps historical ticks to the second, so many events share a time.  The order is
+/// deterministic: within each list the ticks keep the order TWS sent them in, and at equal
+/// times quotes come before trades, so each trade follows the quotes in force when it printed
+/// as far as the data can tell.
+///
+/// # Arguments
+/// * quotes - the BID_ASK ticks, in the order received
+/// * trades - the TRADES ticks, in the order received
+pub fn merge_ticks(
+    quotes: Vec<HistoricalTickBidAsk>,
+    trades: Vec<HistoricalTickLast>,
+) -> Vec<TimelineEvent> {
+    let mut events: Vec<TimelineEvent> = quotes
+        .into_iter()
+        .map(TimelineEvent::Quote)
+        .chain(trades.into_iter().map(TimelineEvent::Trade))
+        .collect();
+    // stable sort: quotes were added first, so they stay ahead of trades at equal times
+    events.sort_by_key(|event| event.time());
+    events
+}
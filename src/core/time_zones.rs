@@ -0,0 +1,108 @@
+//! The time zones of exchanges, to read bar times TWS sends in exchange time
+//!
+//! Bars of req_historical_data with format_date 1 carry "yyyymmdd hh:mm:ss" in the time zone
+//! of the exchange, the time_zone_id of the ContractDetails, unless the time is followed by the
+//! id of its zone.  Reading them with a fixed offset puts the bars around daylight saving
+//! changes an hour off.  ExchangeTz is a chrono TimeZone backed by the IANA time zone database
+//! of chrono-tz, so times convert with the offset in effect at each of them, including under
+//! the daylight saving rules of past years.
+use std::fmt;
+
+use chrono::{Duration, LocalResult, NaiveDate, NaiveDateTime, TimeZone};
+use chrono_tz::{OffsetComponents, Tz, TzOffset};
+
+use crate::core::contract::ContractDetails;
+
+/// Abbreviations older TWS versions send as time_zone_id, e.g. "EST (Eastern Standard Time)",
+/// which the time zone database does not know or knows as a fixed offset
+const ABBREVIATIONS: &[(&str, Tz)] = &[
+    ("EST", Tz::America__New_York),
+    ("CST", Tz::America__Chicago),
+    ("MST", Tz::America__Denver),
+    ("PST", Tz::America__Los_Angeles),
+    ("JST", Tz::Asia__Tokyo),
+    ("Z", Tz::UTC),
+];
+
+//==================================================================================================
+/// The time zone of an exchange
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ExchangeTz(Tz);
+
+impl ExchangeTz {
+    pub const UTC: ExchangeTz = ExchangeTz(Tz::UTC);
+
+    //----------------------------------------------------------------------------------------------
+    /// The zone of a time zone id, e.g. "US/Eastern", "America/Chicago" or the older
+    /// "EST (Eastern Standard Time)".  None for ids the time zone database does not know.
+    pub fn from_id(id: &str) -> Option<ExchangeTz> {
+        let id = id.split(" (").next().unwrap_or_default().trim();
+        ABBREVIATIONS
+            .iter()
+            .find(|(abbreviation, _)| *abbreviation == id)
+            .map(|(_, zone)| *zone)
+            .or_else(|| id.parse::<Tz>().ok())
+            .map(ExchangeTz)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The zone of the time_zone_id of contract details
+    pub fn of_details(details: &ContractDetails) -> Option<ExchangeTz> {
+        ExchangeTz::from_id(&details.time_zone_id)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The id of the zone in the time zone database, e.g. "US/Eastern"
+    pub fn id(&self) -> &'static str {
+        self.0.name()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether daylight saving time is in effect at a UTC time
+    pub fn is_dst(&self, utc: &NaiveDateTime) -> bool {
+        self.0.offset_from_utc_datetime(utc).dst_offset() != Duration::zero()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn tz(&self) -> Tz {
+        self.0
+    }
+}
+
+impl fmt::Display for ExchangeTz {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.id())
+    }
+}
+
+impl From<Tz> for ExchangeTz {
+    fn from(zone: Tz) -> Self {
+        ExchangeTz(zone)
+    }
+}
+
+impl TimeZone for ExchangeTz {
+    type Offset = TzOffset;
+
+    fn from_offset(offset: &TzOffset) -> Self {
+        ExchangeTz(Tz::from_offset(offset))
+    }
+
+    fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<TzOffset> {
+        #[allow(deprecated)]
+        self.0.offset_from_local_date(local)
+    }
+
+    fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<TzOffset> {
+        self.0.offset_from_local_datetime(local)
+    }
+
+    fn offset_from_utc_date(&self, utc: &NaiveDate) -> TzOffset {
+        #[allow(deprecated)]
+        self.0.offset_from_utc_date(utc)
+    }
+
+    fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> TzOffset {
+        self.0.offset_from_utc_datetime(utc)
+    }
+}
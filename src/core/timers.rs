@@ -0,0 +1,112 @@
+//! One-shot and recurring timers fired from an event loop
+//!
+//! Timers holds the deadlines only; the loop owning it asks for the timers due at its current
+//! time.  A live loop passes Instant::now(), a backtest the time of its simulation clock, so
+//! the same timers fire at the same simulated moments on every run.
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// Identifies a timer, unique within its Timers
+pub type TimerId = u64;
+
+//==================================================================================================
+#[derive(Clone, Copy, Debug)]
+struct Timer {
+    deadline: Instant,
+    every: Option<Duration>,
+}
+
+//==================================================================================================
+/// Registry of pending timers
+#[derive(Clone, Debug, Default)]
+pub struct Timers {
+    next_id: TimerId,
+    timers: BTreeMap<TimerId, Timer>,
+}
+
+impl Timers {
+    pub fn new() -> Self {
+        Timers::default()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Adds a timer firing once at deadline
+    pub fn once_at(&mut self, deadline: Instant) -> TimerId {
+        self.add(Timer {
+            deadline,
+            every: None,
+        })
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Adds a timer firing once, delay after now
+    pub fn once_after(&mut self, now: Instant, delay: Duration) -> TimerId {
+        self.once_at(now + delay)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Adds a timer firing every interval, the first time interval after now
+    pub fn every(&mut self, now: Instant, interval: Duration) -> TimerId {
+        self.add(Timer {
+            deadline: now + interval,
+            every: Some(interval),
+        })
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Removes a timer.  Returns false if it already fired for the last time or never existed.
+    pub fn cancel(&mut self, timer_id: TimerId) -> bool {
+        self.timers.remove(&timer_id).is_some()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// When the next timer is due
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.timers.values().map(|timer| timer.deadline).min()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Takes the timers due at now, earliest deadline first, with the deadline each was due at.
+    /// One-shot timers are removed; recurring timers are rescheduled, and fire once even if
+    /// several of their intervals passed since the last call.
+    pub fn due(&mut self, now: Instant) -> Vec<(TimerId, Instant)> {
+        let mut due: Vec<(TimerId, Instant)> = self
+            .timers
+            .iter()
+            .filter(|(_, timer)| timer.deadline <= now)
+            .map(|(timer_id, timer)| (*timer_id, timer.deadline))
+            .collect();
+        due.sort_by_key(|(timer_id, deadline)| (*deadline, *timer_id));
+
+        for (timer_id, _) in due.iter() {
+            let timer = self.timers[timer_id];
+            match timer.every {
+                Some(interval) if interval > Duration::from_secs(0) => {
+                    // the first deadline after now, however far the clock jumped
+                    let periods =
+                        now.duration_since(timer.deadline).as_nanos() / interval.as_nanos() + 1;
+                    let deadline = timer.deadline
+                        + Duration::from_nanos((interval.as_nanos() * periods) as u64);
+                    self.timers.insert(*timer_id, Timer { deadline, ..timer });
+                }
+                _ => {
+                    self.timers.remove(timer_id);
+                }
+            }
+        }
+        due
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn add(&mut self, timer: Timer) -> TimerId {
+        let timer_id = self.next_id;
+        self.next_id += 1;
+        self.timers.insert(timer_id, timer);
+        timer_id
+    }
+}
@@ -0,0 +1,267 @@
+//! The timestamps of bars, executions, historical ticks, orders and news
+//!
+//! TWS sends times as text in several formats: epoch seconds or milliseconds, "yyyymmdd" for
+//! daily bars, "yyyymmdd hh:mm:ss" optionally followed by a time zone, "yyyymmdd-hh:mm:ss" in
+//! UTC, and "yyyy-mm-dd hh:mm:ss.f" for news.  The domain structs hold them as a Timestamp,
+//! which is the parsed `Option<DateTime<Utc>>`, None when TWS left the time empty or sent one
+//! parse_timestamp does not read.  Build with the raw-timestamps feature to keep the text TWS
+//! sent instead; the functions of this module work on either, so code written against them
+//! builds both ways.
+//!
+//! The epoch formats and "yyyymmdd-hh:mm:ss" are UTC, and a "yyyymmdd" date is read at UTC
+//! midnight so its day is the same wherever it is read.  "yyyymmdd hh:mm:ss" without a time zone
+//! is in the time zone TWS runs in, e.g. the times of executions, so parse_timestamp reads it
+//! in the local time zone of this machine.  The bars of requests with format_date 1 are in the
+//! time zone of the exchange instead: register it with EClient::set_bar_time_zone, and read
+//! the time in it with timestamp_time_in.
+use chrono::{DateTime, Local, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use log::*;
+
+use crate::core::log_targets::DECODER;
+use crate::core::time_zones::ExchangeTz;
+
+/// The time of a domain struct: parsed into a UTC DateTime, None if empty or unreadable
+#[cfg(not(feature = "raw-timestamps"))]
+pub type Timestamp = Option<DateTime<Utc>>;
+
+/// The time of a domain struct: the text TWS sent, empty if unset
+#[cfg(feature = "raw-timestamps")]
+pub type Timestamp = String;
+
+/// The format of timestamp_to_wire, which TWS reads as UTC
+pub const WIRE_FORMAT: &str = "%Y%m%d-%H:%M:%S";
+
+/// Epoch numbers with more digits than this are milliseconds
+const MAX_SECONDS_DIGITS: usize = 11;
+
+//==================================================================================================
+/// Parses a time in any of the formats TWS sends, None if empty or unreadable.  Times without a
+/// time zone, or with one the time zone database does not know, are read in local time.  Dates
+/// are read at UTC midnight.
+pub fn parse_timestamp(text: &str) -> Option<DateTime<Utc>> {
+    parse_timestamp_with(text, &Local, None)
+}
+
+//==================================================================================================
+/// Like parse_timestamp, but reads times without a known time zone in zone, e.g. the exchange
+/// time of bars.  A time of the hour repeated when daylight saving ends is read as the first of the
+/// two unless that is before after, e.g. the time of the previous bar.  None for a time of the
+/// hour skipped when daylight saving starts.
+pub fn parse_timestamp_in(
+    text: &str,
+    zone: ExchangeTz,
+    after: Option<DateTime<Utc>>,
+) -> Option<DateTime<Utc>> {
+    parse_timestamp_with(text, &zone, after)
+}
+
+//==================================================================================================
+fn parse_timestamp_with<Z: TimeZone>(
+    text: &str,
+    zone: &Z,
+    after: Option<DateTime<Utc>>,
+) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    if text.chars().all(|c| c.is_ascii_digit()) {
+        return match text.len() {
+            // a date is a calendar day rather than a time, kept stable at UTC midnight
+            8 => NaiveDate::parse_from_str(text, "%Y%m%d")
+                .ok()
+                .and_then(|day| day.and_hms_opt(0, 0, 0))
+                .map(|midnight| Utc.from_utc_datetime(&midnight)),
+            len if len > MAX_SECONDS_DIGITS => text
+                .parse()
+                .ok()
+                .and_then(|millis| Utc.timestamp_millis_opt(millis).single()),
+            _ => text
+                .parse()
+                .ok()
+                .and_then(|seconds| Utc.timestamp_opt(seconds, 0).single()),
+        };
+    }
+    let mut parts = text.split_whitespace();
+    let (date, time, id) = (parts.next()?, parts.next(), parts.next());
+    if parts.next().is_some() {
+        return None;
+    }
+    let parse = |date_time: &str| {
+        ["%Y%m%d %H:%M:%S", "%Y-%m-%d %H:%M:%S%.f"]
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(date_time, format).ok())
+    };
+    match (time, id) {
+        (Some(time), Some(id)) => {
+            let local = parse(&format!("{} {}", date, time))?;
+            match ExchangeTz::from_id(id) {
+                Some(suffix_zone) => from_local(&local, &suffix_zone, after),
+                None => {
+                    warn!(target: DECODER, "Unknown time zone {}, reading {} in the default zone", id, text);
+                    from_local(&local, zone, after)
+                }
+            }
+        }
+        (Some(time), None) => from_local(&parse(&format!("{} {}", date, time))?, zone, after),
+        // "yyyymmdd-hh:mm:ss" is in UTC
+        _ => from_local(&parse(&date.replacen('-', " ", 1))?, &Utc, after),
+    }
+}
+
+//==================================================================================================
+fn from_local<Z: TimeZone>(
+    local: &NaiveDateTime,
+    zone: &Z,
+    after: Option<DateTime<Utc>>,
+) -> Option<DateTime<Utc>> {
+    let time = match zone.from_local_datetime(local) {
+        LocalResult::Single(time) => time,
+        LocalResult::Ambiguous(earlier, later) => match after {
+            Some(after) if earlier < after => later,
+            _ => earlier,
+        },
+        LocalResult::None => return None,
+    };
+    Some(time.with_timezone(&Utc))
+}
+
+//==================================================================================================
+/// The Timestamp of a time field TWS sent
+pub fn timestamp_from_wire(text: &str) -> Timestamp {
+    #[cfg(not(feature = "raw-timestamps"))]
+    {
+        parse_timestamp(text)
+    }
+    #[cfg(feature = "raw-timestamps")]
+    {
+        text.to_string()
+    }
+}
+
+//==================================================================================================
+/// The Timestamp of a time field TWS sent, reading times without a time zone in zone.  See
+/// parse_timestamp_in.
+pub fn timestamp_from_wire_in(
+    text: &str,
+    zone: ExchangeTz,
+    after: Option<DateTime<Utc>>,
+) -> Timestamp {
+    #[cfg(not(feature = "raw-timestamps"))]
+    {
+        parse_timestamp_in(text, zone, after)
+    }
+    #[cfg(feature = "raw-timestamps")]
+    {
+        let _ = (zone, after);
+        text.to_string()
+    }
+}
+
+//==================================================================================================
+/// The Timestamp of a time in epoch seconds
+pub fn timestamp_from_seconds(seconds: i64) -> Timestamp {
+    #[cfg(not(feature = "raw-timestamps"))]
+    {
+        Utc.timestamp_opt(seconds, 0).single()
+    }
+    #[cfg(feature = "raw-timestamps")]
+    {
+        seconds.to_string()
+    }
+}
+
+//==================================================================================================
+/// The Timestamp of a DateTime
+pub fn timestamp_from_time(time: DateTime<Utc>) -> Timestamp {
+    #[cfg(not(feature = "raw-timestamps"))]
+    {
+        Some(time)
+    }
+    #[cfg(feature = "raw-timestamps")]
+    {
+        time.format(WIRE_FORMAT).to_string()
+    }
+}
+
+//==================================================================================================
+/// The time of a Timestamp, None if unset or unreadable
+pub fn timestamp_time(timestamp: &Timestamp) -> Option<DateTime<Utc>> {
+    #[cfg(not(feature = "raw-timestamps"))]
+    {
+        *timestamp
+    }
+    #[cfg(feature = "raw-timestamps")]
+    {
+        parse_timestamp(timestamp)
+    }
+}
+
+//==================================================================================================
+/// The time of a Timestamp in a time zone, e.g. that of the exchange.  With raw-timestamps,
+/// times without a time zone are read in zone.
+pub fn timestamp_time_in(timestamp: &Timestamp, zone: ExchangeTz) -> Option<DateTime<ExchangeTz>> {
+    #[cfg(not(feature = "raw-timestamps"))]
+    let time = *timestamp;
+    #[cfg(feature = "raw-timestamps")]
+    let time = parse_timestamp_in(timestamp, zone, None);
+    time.map(|time| time.with_timezone(&zone))
+}
+
+//==================================================================================================
+/// The time of a Timestamp in epoch seconds, None if unset or unreadable
+pub fn timestamp_seconds(timestamp: &Timestamp) -> Option<i64> {
+    timestamp_time(timestamp).map(|time| time.timestamp())
+}
+
+//==================================================================================================
+/// Whether a Timestamp is set
+pub fn is_set(timestamp: &Timestamp) -> bool {
+    #[cfg(not(feature = "raw-timestamps"))]
+    {
+        timestamp.is_some()
+    }
+    #[cfg(feature = "raw-timestamps")]
+    {
+        !timestamp.is_empty()
+    }
+}
+
+//==================================================================================================
+/// The text to send a Timestamp to TWS as, empty if unset.  Without raw-timestamps it is in
+/// WIRE_FORMAT.
+pub fn timestamp_to_wire(timestamp: &Timestamp) -> String {
+    #[cfg(not(feature = "raw-timestamps"))]
+    {
+        timestamp
+            .map(|time| time.format(WIRE_FORMAT).to_string())
+            .unwrap_or_default()
+    }
+    #[cfg(feature = "raw-timestamps")]
+    {
+        timestamp.clone()
+    }
+}
+
+//==================================================================================================
+/// Serializes a Timestamp as its timestamp_to_wire text, e.g.
+/// `#[serde(with = "crate::core::timestamps::serde_timestamp")]`
+pub mod serde_timestamp {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::{timestamp_from_wire, timestamp_to_wire, Timestamp};
+
+    pub fn serialize<S>(timestamp: &Timestamp, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&timestamp_to_wire(timestamp))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Timestamp, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(timestamp_from_wire(&String::deserialize(deserializer)?))
+    }
+}
@@ -0,0 +1,351 @@
+//! Typed results for what-if (margin preview) orders
+//!
+//! WhatIfScenario aggregates the previews of several orders, e.g. the orders of a rebalance,
+//! to check before placing them that the account can carry their combined margin.  TWS
+//! previews each order against the current portfolio, not against the portfolio after the
+//! orders previewed before it, so the combined impact is the sum of the changes: exact for
+//! orders in unrelated contracts, conservative for orders offsetting each other.
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::common::UNSET_DOUBLE;
+use crate::core::contract::Contract;
+use crate::core::order::{Action, Order, OrderState};
+
+/// The wait between the what-if orders of a scenario unless configured otherwise
+pub const WHAT_IF_PACING: Duration = Duration::from_millis(200);
+
+//==================================================================================================
+/// Margin and commission impact of an order, as reported by TWS for a what-if order.
+/// Values TWS does not report are set to UNSET_DOUBLE.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MarginPreview {
+    pub order_id: i32,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
+    pub init_margin_before: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
+    pub init_margin_change: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
+    pub init_margin_after: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
+    pub maint_margin_before: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
+    pub maint_margin_change: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
+    pub maint_margin_after: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
+    pub equity_with_loan_before: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
+    pub equity_with_loan_change: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
+    pub equity_with_loan_after: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
+    pub commission: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
+    pub min_commission: f64,
+    #[cfg_attr(
+        feature = "serde-unset-none",
+        serde(
+            default = "crate::core::serde_unset::unset_double::unset",
+            with = "crate::core::serde_unset::unset_double"
+        )
+    )]
+    pub max_commission: f64,
+    pub commission_currency: String,
+    pub warning_text: String,
+}
+
+impl MarginPreview {
+    /// Builds a preview from the OrderState returned with a what-if open order
+    pub fn from_order_state(order_id: i32, order_state: &OrderState) -> Self {
+        MarginPreview {
+            order_id,
+            init_margin_before: parse_margin(&order_state.init_margin_before),
+            init_margin_change: parse_margin(&order_state.init_margin_change),
+            init_margin_after: parse_margin(&order_state.init_margin_after),
+            maint_margin_before: parse_margin(&order_state.maint_margin_before),
+            maint_margin_change: parse_margin(&order_state.maint_margin_change),
+            maint_margin_after: parse_margin(&order_state.maint_margin_after),
+            equity_with_loan_before: parse_margin(&order_state.equity_with_loan_before),
+            equity_with_loan_change: parse_margin(&order_state.equity_with_loan_change),
+            equity_with_loan_after: parse_margin(&order_state.equity_with_loan_after),
+            commission: order_state.commission,
+            min_commission: order_state.min_commission,
+            max_commission: order_state.max_commission,
+            commission_currency: order_state.commission_currency.clone(),
+            warning_text: order_state.warning_text.clone(),
+        }
+    }
+}
+
+impl fmt::Display for MarginPreview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "order_id: {}, init_margin_before: {}, init_margin_change: {}, init_margin_after: {}, \
+             maint_margin_before: {}, maint_margin_change: {}, maint_margin_after: {}, \
+             equity_with_loan_before: {}, equity_with_loan_change: {}, equity_with_loan_after: {}, \
+             commission: {}, min_commission: {}, max_commission: {}, commission_currency: {}, \
+             warning_text: {}",
+            self.order_id,
+            self.init_margin_before,
+            self.init_margin_change,
+            self.init_margin_after,
+            self.maint_margin_before,
+            self.maint_margin_change,
+            self.maint_margin_after,
+            self.equity_with_loan_before,
+            self.equity_with_loan_change,
+            self.equity_with_loan_after,
+            self.commission,
+            self.min_commission,
+            self.max_commission,
+            self.commission_currency,
+            self.warning_text
+        )
+    }
+}
+
+//==================================================================================================
+/// One order of a WhatIfScenario
+/// preview - the answer of TWS, None if it rejected the order or did not answer in time
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct WhatIfContribution {
+    pub order_id: i32,
+    pub symbol: String,
+    pub action: Action,
+    pub total_quantity: f64,
+    pub preview: Option<MarginPreview>,
+}
+
+impl WhatIfContribution {
+    pub fn new(
+        order_id: i32,
+        contract: &Contract,
+        order: &Order,
+        preview: Option<MarginPreview>,
+    ) -> Self {
+        WhatIfContribution {
+            order_id,
+            symbol: contract.symbol.clone(),
+            action: order.action.clone(),
+            total_quantity: order.total_quantity,
+            preview,
+        }
+    }
+}
+
+impl fmt::Display for WhatIfContribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "order_id: {}, {} {} {}",
+            self.order_id, self.action, self.total_quantity, self.symbol
+        )?;
+        match self.preview.as_ref() {
+            Some(preview) => write!(
+                f,
+                ", init_margin_change: {}, maint_margin_change: {}, commission: {}",
+                preview.init_margin_change, preview.maint_margin_change, preview.commission
+            ),
+            None => write!(f, ", not previewed"),
+        }
+    }
+}
+
+//==================================================================================================
+/// The previews of a list of hypothetical orders, as EClient::what_if_scenario returns them
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct WhatIfScenario {
+    pub contributions: Vec<WhatIfContribution>,
+}
+
+impl WhatIfScenario {
+    pub fn new(contributions: Vec<WhatIfContribution>) -> Self {
+        WhatIfScenario { contributions }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn previews(&self) -> impl Iterator<Item = &MarginPreview> {
+        self.contributions
+            .iter()
+            .filter_map(|contribution| contribution.preview.as_ref())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The orders TWS did not preview.  Their impact is missing from the totals.
+    pub fn unpreviewed(&self) -> Vec<i32> {
+        self.contributions
+            .iter()
+            .filter(|contribution| contribution.preview.is_none())
+            .map(|contribution| contribution.order_id)
+            .collect()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether TWS previewed every order
+    pub fn is_complete(&self) -> bool {
+        self.contributions
+            .iter()
+            .all(|contribution| contribution.preview.is_some())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The combined change of the initial margin
+    pub fn init_margin_change(&self) -> f64 {
+        sum_set(self.previews().map(|preview| preview.init_margin_change))
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The combined change of the maintenance margin
+    pub fn maint_margin_change(&self) -> f64 {
+        sum_set(self.previews().map(|preview| preview.maint_margin_change))
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The combined change of the equity with loan value, i.e. the commissions paid
+    pub fn equity_with_loan_change(&self) -> f64 {
+        sum_set(
+            self.previews()
+                .map(|preview| preview.equity_with_loan_change),
+        )
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The combined commission
+    pub fn commission(&self) -> f64 {
+        sum_set(self.previews().map(|preview| preview.commission))
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The initial margin after all the orders, None without a preview reporting the margin
+    /// before them
+    pub fn init_margin_after(&self) -> Option<f64> {
+        self.first_set(|preview| preview.init_margin_before)
+            .map(|before| before + self.init_margin_change())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The equity with loan value after all the orders
+    pub fn equity_with_loan_after(&self) -> Option<f64> {
+        self.first_set(|preview| preview.equity_with_loan_before)
+            .map(|before| before + self.equity_with_loan_change())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Whether the account could carry all the orders: every order was previewed and the
+    /// equity with loan value after them covers their initial margin.  False if TWS did not
+    /// report the values needed.
+    pub fn is_feasible(&self) -> bool {
+        match (self.init_margin_after(), self.equity_with_loan_after()) {
+            (Some(margin), Some(equity)) => self.is_complete() && margin <= equity,
+            _ => false,
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn first_set(&self, value: impl Fn(&MarginPreview) -> f64) -> Option<f64> {
+        self.previews()
+            .map(value)
+            .find(|value| *value != UNSET_DOUBLE)
+    }
+}
+
+impl fmt::Display for WhatIfScenario {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "orders: {}, init_margin_change: {}, maint_margin_change: {}, commission: {}, \
+             init_margin_after: {:?}, equity_with_loan_after: {:?}, feasible: {}",
+            self.contributions.len(),
+            self.init_margin_change(),
+            self.maint_margin_change(),
+            self.commission(),
+            self.init_margin_after(),
+            self.equity_with_loan_after(),
+            self.is_feasible()
+        )?;
+        for contribution in self.contributions.iter() {
+            write!(f, "\n  {}", contribution)?;
+        }
+        Ok(())
+    }
+}
+
+//==================================================================================================
+fn sum_set(values: impl Iterator<Item = f64>) -> f64 {
+    values.filter(|value| *value != UNSET_DOUBLE).sum()
+}
+
+//==================================================================================================
+fn parse_margin(value: &str) -> f64 {
+    value.trim().parse().unwrap_or(UNSET_DOUBLE)
+}
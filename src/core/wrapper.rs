@@ -4,15 +4,19 @@ use std::marker::{Send, Sync};
 
 use bigdecimal::BigDecimal;
 
+use crate::core::account_summary_tags::AccountSummaryTag;
 use crate::core::common::RealTimeBar;
 use crate::core::common::{
-    BarData, CommissionReport, DepthMktDataDescription, FaDataType, FamilyCode, HistogramData,
-    HistoricalTick, HistoricalTickBidAsk, HistoricalTickLast, NewsProvider, PriceIncrement,
-    SmartComponent, TickAttrib, TickAttribBidAsk, TickAttribLast, TickByTickType, TickType,
+    BarData, CommissionReport, DepthMktDataDescription, FaDataType, FamilyCode, FeeRateBar,
+    HistogramData, HistoricalTick, HistoricalTickBidAsk, HistoricalTickLast, NewsArticleContent,
+    NewsProvider, PriceIncrement, SmartComponent, TickAttrib, TickAttribBidAsk, TickAttribLast,
+    TickByTickType, TickType, YieldBar,
 };
 use crate::core::contract::{Contract, ContractDescription, ContractDetails, DeltaNeutralContract};
 use crate::core::execution::Execution;
+use crate::core::news_bulletins::Bulletin;
 use crate::core::order::{Order, OrderState, SoftDollarTier};
+use crate::core::timestamps::Timestamp;
 
 /// A trait that clients will implement that declares callback functions that get called when the application receives messages from the Trader WorkStation or IB Gateway
 pub trait Wrapper: Send + Sync {
@@ -21,6 +25,11 @@ pub trait Wrapper: Send + Sync {
     /// communication or when TWS wants to send a message to the core.
     fn error(&mut self, req_id: i32, error_code: i32, error_string: &str);
 
+    //----------------------------------------------------------------------------------------------
+    /// This event is called for informational messages TWS sends with an error code, such as
+    /// the 2100 range of market data farm connection notices.  See errors::is_warning_code.
+    fn warning(&mut self, req_id: i32, warning_code: i32, warning_string: &str);
+
     //----------------------------------------------------------------------------------------------
     fn win_error(&mut self, text: &str, last_error: i32);
 
@@ -42,8 +51,9 @@ pub trait Wrapper: Send + Sync {
     fn tick_price(&mut self, req_id: i32, tick_type: TickType, price: f64, attrib: TickAttrib);
 
     //----------------------------------------------------------------------------------------------
-    ///Market data tick size callback. Handles all size-related ticks.
-    fn tick_size(&mut self, req_id: i32, tick_type: TickType, size: i32);
+    ///Market data tick size callback. Handles all size-related ticks. Sizes are fractional
+    /// for crypto, e.g. 0.0125 BTC.
+    fn tick_size(&mut self, req_id: i32, tick_type: TickType, size: BigDecimal);
 
     //----------------------------------------------------------------------------------------------
     /// When requesting market data snapshots, this market will indicate the
@@ -232,7 +242,7 @@ pub trait Wrapper: Send + Sync {
         operation: i32,
         side: i32,
         price: f64,
-        size: i32,
+        size: BigDecimal,
     );
 
     //----------------------------------------------------------------------------------------------
@@ -260,7 +270,7 @@ pub trait Wrapper: Send + Sync {
         operation: i32,
         side: i32,
         price: f64,
-        size: i32,
+        size: BigDecimal,
         is_smart_depth: bool,
     );
 
@@ -268,20 +278,8 @@ pub trait Wrapper: Send + Sync {
     /// provides IB's bulletins
     ///
     /// # Arguments
-    /// * msg_id - the bulletin's identifier
-    /// * msg_type - one of:
-    ///     * 1 - Regular news bulletin
-    ///     * 2 - Exchange no longer available for trading
-    ///     * 3 - Exchange is available for trading
-    /// * news_message - the message
-    /// * origin_exch -    the exchange where the message comes from.
-    fn update_news_bulletin(
-        &mut self,
-        msg_id: i32,
-        msg_type: i32,
-        news_message: &str,
-        origin_exch: &str,
-    );
+    /// * bulletin - the bulletin, with its id, type, message and originating exchange
+    fn update_news_bulletin(&mut self, bulletin: &Bulletin);
 
     //----------------------------------------------------------------------------------------------
     /// Receives a comma-separated string with the managed account ids.
@@ -313,6 +311,26 @@ pub trait Wrapper: Send + Sync {
     /// Marks the ending of the historical bars reception.
     fn historical_data_end(&mut self, req_id: i32, start: &str, end: &str);
 
+    //----------------------------------------------------------------------------------------------
+    ///  returns the requested historical yield bars.  Called instead of historical_data when
+    ///  the request's what_to_show was YIELD_BID, YIELD_ASK, YIELD_BID_ASK or YIELD_LAST.
+    ///  Bars sent later because of keep_up_to_date still arrive through historical_data_update.
+    ///
+    /// # Arguments
+    /// * req_id - the request's identifier
+    /// * bar - YieldBar struct containing the bar's yields
+    fn historical_yield_data(&mut self, req_id: i32, bar: YieldBar);
+
+    //----------------------------------------------------------------------------------------------
+    ///  returns the requested historical fee rate bars.  Called instead of historical_data when
+    ///  the request's what_to_show was FEE_RATE.
+    ///  Bars sent later because of keep_up_to_date still arrive through historical_data_update.
+    ///
+    /// # Arguments
+    /// * req_id - the request's identifier
+    /// * bar - FeeRateBar struct containing the bar's fee rates
+    fn historical_fee_rate_data(&mut self, req_id: i32, bar: FeeRateBar);
+
     //----------------------------------------------------------------------------------------------
     /// Provides the xml-formatted parameters available to create a market scanner.
     ///
@@ -401,7 +419,7 @@ pub trait Wrapper: Send + Sync {
         &mut self,
         req_id: i32,
         account: &str,
-        tag: &str,
+        tag: &AccountSummaryTag,
         value: &str,
         currency: &str,
     );
@@ -566,7 +584,7 @@ pub trait Wrapper: Send + Sync {
     fn tick_news(
         &mut self,
         ticker_id: i32,
-        time_stamp: i32,
+        time_stamp: &Timestamp,
         provider_code: &str,
         article_id: &str,
         headline: &str,
@@ -595,12 +613,17 @@ pub trait Wrapper: Send + Sync {
     /// returns body of news article
     fn news_article(&mut self, request_id: i32, article_type: i32, article_text: &str);
 
+    //----------------------------------------------------------------------------------------------
+    /// returns body of news article decoded, following news_article: the text, or the bytes of
+    /// the PDF of a binary article
+    fn news_article_content(&mut self, request_id: i32, content: &NewsArticleContent);
+
     //----------------------------------------------------------------------------------------------
     /// returns historical news headlines
     fn historical_news(
         &mut self,
         request_id: i32,
-        time: &str,
+        time: &Timestamp,
         provider_code: &str,
         article_id: &str,
         headline: &str,
@@ -675,7 +698,7 @@ pub trait Wrapper: Send + Sync {
         tick_type: TickByTickType,
         time: i64,
         price: f64,
-        size: i32,
+        size: BigDecimal,
         tick_attrib_last: TickAttribLast,
         exchange: &str,
         special_conditions: &str,
@@ -689,8 +712,8 @@ pub trait Wrapper: Send + Sync {
         time: i64,
         bid_price: f64,
         ask_price: f64,
-        bid_size: i32,
-        ask_size: i32,
+        bid_size: BigDecimal,
+        ask_size: BigDecimal,
         tick_attrib_bid_ask: TickAttribBidAsk,
     );
 
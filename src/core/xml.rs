@@ -0,0 +1,288 @@
+//! Minimal XML reading and writing for the documents TWS exchanges: FA configuration and
+//! scanner parameters.  Handles elements, attributes, text and the predefined entities; skips
+//! the declaration, comments, processing instructions and DOCTYPE.
+use std::fmt::Write;
+
+use crate::core::errors::{IBKRApiLibError, TwsApiReportableError, TwsError};
+
+//==================================================================================================
+/// An element with its attributes, child elements and text
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    pub text: String,
+}
+
+impl Element {
+    pub fn new(name: &str) -> Self {
+        Element {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// An element holding only text
+    pub fn with_text(name: &str, text: &str) -> Self {
+        Element {
+            name: name.to_string(),
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn attribute(mut self, name: &str, value: &str) -> Self {
+        self.attributes.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    pub fn child(mut self, child: Element) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The first child with this name
+    pub fn find(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Every child with this name
+    pub fn find_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> + 'a {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The trimmed text of the first child with this name, "" without one
+    pub fn child_text(&self, name: &str) -> String {
+        self.find(name)
+            .map(|child| child.text.trim().to_string())
+            .unwrap_or_default()
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// The document with this element as root, with an XML declaration
+    pub fn to_document(&self) -> String {
+        let mut xml = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_string();
+        self.write(&mut xml, 0);
+        xml
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn write(&self, xml: &mut String, depth: usize) {
+        let indent = "  ".repeat(depth);
+        let _ = write!(xml, "{}<{}", indent, self.name);
+        for (name, value) in self.attributes.iter() {
+            let _ = write!(xml, " {}=\"{}\"", name, escape(value));
+        }
+        if self.children.is_empty() {
+            let _ = writeln!(xml, ">{}</{}>", escape(&self.text), self.name);
+            return;
+        }
+        xml.push_str(">\n");
+        for child in self.children.iter() {
+            child.write(xml, depth + 1);
+        }
+        let _ = writeln!(xml, "{}</{}>", indent, self.name);
+    }
+}
+
+//==================================================================================================
+/// Parses a document and returns its root element
+pub(crate) fn parse(xml: &str) -> Result<Element, IBKRApiLibError> {
+    let mut parser = Parser { xml, pos: 0 };
+    parser.skip_prolog()?;
+    let root = parser.element()?;
+    parser.skip_misc()?;
+    if parser.pos < xml.len() {
+        return Err(parser.error("content after the root element"));
+    }
+    Ok(root)
+}
+
+//==================================================================================================
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+//==================================================================================================
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+//==================================================================================================
+struct Parser<'a> {
+    xml: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.xml[self.pos..]
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn error(&self, detail: &str) -> IBKRApiLibError {
+        IBKRApiLibError::ApiError(TwsApiReportableError::new(
+            -1,
+            TwsError::BadMessage.code().to_string(),
+            format!(
+                "{} Invalid XML at offset {}: {}.",
+                TwsError::BadMessage.message(),
+                self.pos,
+                detail
+            ),
+        ))
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.pos = self.xml.len() - trimmed.len();
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Skips past the next occurrence of end
+    fn skip_past(&mut self, end: &str) -> Result<(), IBKRApiLibError> {
+        match self.rest().find(end) {
+            Some(index) => {
+                self.pos += index + end.len();
+                Ok(())
+            }
+            None => Err(self.error(&format!("missing {}", end))),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    /// Skips whitespace, comments and processing instructions
+    fn skip_misc(&mut self) -> Result<(), IBKRApiLibError> {
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.rest().starts_with("<?") {
+                self.skip_past("?>")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn skip_prolog(&mut self) -> Result<(), IBKRApiLibError> {
+        // a byte order mark is 3 bytes in UTF-8
+        if self.rest().starts_with('\u{feff}') {
+            self.pos += '\u{feff}'.len_utf8();
+        }
+        loop {
+            self.skip_misc()?;
+            if self.rest().starts_with("<!DOCTYPE") {
+                self.skip_past(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn name(&mut self) -> Result<String, IBKRApiLibError> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| c.is_whitespace() || c == '>' || c == '/' || c == '=')
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a name"));
+        }
+        self.pos += len;
+        Ok(rest[..len].to_string())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn expect(&mut self, token: &str) -> Result<(), IBKRApiLibError> {
+        if !self.rest().starts_with(token) {
+            return Err(self.error(&format!("expected {}", token)));
+        }
+        self.pos += token.len();
+        Ok(())
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn element(&mut self) -> Result<Element, IBKRApiLibError> {
+        self.expect("<")?;
+        let mut element = Element::new(&self.name()?);
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.pos += 2;
+                return Ok(element);
+            }
+            if self.rest().starts_with('>') {
+                self.pos += 1;
+                break;
+            }
+            let name = self.name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let quote = match self.rest().chars().next() {
+                Some(quote) if quote == '"' || quote == '\'' => quote,
+                _ => return Err(self.error("expected a quoted attribute value")),
+            };
+            self.pos += 1;
+            let len = self
+                .rest()
+                .find(quote)
+                .ok_or_else(|| self.error("unterminated attribute value"))?;
+            let value = unescape(&self.rest()[..len]);
+            self.pos += len + 1;
+            element.attributes.push((name, value));
+        }
+
+        loop {
+            let rest = self.rest();
+            let len = rest
+                .find('<')
+                .ok_or_else(|| self.error(&format!("unclosed element {}", element.name)))?;
+            element.text.push_str(&unescape(&rest[..len]));
+            self.pos += len;
+            let rest = self.rest();
+            if rest.starts_with("</") {
+                self.pos += 2;
+                let name = self.name()?;
+                if name != element.name {
+                    return Err(self.error(&format!("{} closed by {}", element.name, name)));
+                }
+                self.skip_whitespace();
+                self.expect(">")?;
+                return Ok(element);
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<![CDATA[") {
+                self.pos += "<![CDATA[".len();
+                let len = self
+                    .rest()
+                    .find("]]>")
+                    .ok_or_else(|| self.error("unterminated CDATA"))?;
+                element.text.push_str(&self.rest()[..len]);
+                self.pos += len + 3;
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else {
+                element.children.push(self.element()?);
+            }
+        }
+    }
+}
@@ -1,14 +1,18 @@
 //! Examples of populating fields that define various types of contacts
 
+use crate::core::common::SecType;
 use crate::core::contract::{ComboLeg, Contract, PositionType};
+use crate::core::currency::Currency;
+use crate::core::exchange::Exchange;
+use crate::core::order::Action;
 
 //==================================================================================================
 pub fn eur_gbp_fx() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "EUR".to_string();
-    contract.sec_type = "CASH".to_string();
-    contract.currency = "GBP".to_string();
-    contract.exchange = "IDEALPRO".to_string();
+    contract.sec_type = SecType::CASH;
+    contract.currency = "GBP".into();
+    contract.exchange = Exchange::IdealPro;
 
     contract
 }
@@ -17,9 +21,9 @@ pub fn eur_gbp_fx() -> Contract {
 pub fn index() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "DAX".to_string();
-    contract.sec_type = "IND".to_string();
-    contract.currency = "EUR".to_string();
-    contract.exchange = "DTB".to_string();
+    contract.sec_type = SecType::IND;
+    contract.currency = Currency::eur();
+    contract.exchange = "DTB".into();
 
     contract
 }
@@ -28,9 +32,9 @@ pub fn index() -> Contract {
 pub fn cfd() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "IBDE30".to_string();
-    contract.sec_type = "cfd".to_string();
-    contract.currency = "EUR".to_string();
-    contract.exchange = "SMART".to_string();
+    contract.sec_type = SecType::CFD;
+    contract.currency = Currency::eur();
+    contract.exchange = Exchange::Smart;
 
     contract
 }
@@ -39,10 +43,10 @@ pub fn cfd() -> Contract {
 pub fn european_stock() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "BMW".to_string();
-    contract.sec_type = "STK".to_string();
-    contract.currency = "EUR".to_string();
-    contract.exchange = "SMART".to_string();
-    contract.primary_exchange = "IBIS".to_string();
+    contract.sec_type = SecType::STK;
+    contract.currency = Currency::eur();
+    contract.exchange = Exchange::Smart;
+    contract.primary_exchange = Exchange::Ibis;
     contract
 }
 
@@ -50,10 +54,10 @@ pub fn european_stock() -> Contract {
 pub fn european_stock2() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "NOKIA".to_string();
-    contract.sec_type = "STK".to_string();
-    contract.currency = "EUR".to_string();
-    contract.exchange = "SMART".to_string();
-    contract.primary_exchange = "HEX".to_string();
+    contract.sec_type = SecType::STK;
+    contract.currency = Currency::eur();
+    contract.exchange = Exchange::Smart;
+    contract.primary_exchange = "HEX".into();
     contract
 }
 
@@ -61,9 +65,9 @@ pub fn european_stock2() -> Contract {
 pub fn option_at_ise() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "COF".to_string();
-    contract.sec_type = "OPT".to_string();
-    contract.currency = "USD".to_string();
-    contract.exchange = "ISE".to_string();
+    contract.sec_type = SecType::OPT;
+    contract.currency = Currency::usd();
+    contract.exchange = Exchange::Ise;
     contract.last_trade_date_or_contract_month = "20190315".to_string();
     contract.right = "P".to_string();
     contract.strike = 105.0;
@@ -76,9 +80,9 @@ pub fn bond_with_cusip() -> Contract {
     let mut contract = Contract::default();
     // enter CUSIP as symbol
     contract.symbol = "912828C57".to_string();
-    contract.sec_type = "BOND".to_string();
-    contract.exchange = "SMART".to_string();
-    contract.currency = "USD".to_string();
+    contract.sec_type = SecType::BOND;
+    contract.exchange = Exchange::Smart;
+    contract.currency = Currency::usd();
 
     contract
 }
@@ -87,7 +91,7 @@ pub fn bond_with_cusip() -> Contract {
 pub fn bond() -> Contract {
     let mut contract = Contract::default();
     contract.con_id = 15960357;
-    contract.exchange = "SMART".to_string();
+    contract.exchange = Exchange::Smart;
 
     contract
 }
@@ -96,9 +100,9 @@ pub fn bond() -> Contract {
 pub fn mutual_fund() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "VINIX".to_string();
-    contract.sec_type = "FUND".to_string();
-    contract.exchange = "FUNDSERV".to_string();
-    contract.currency = "USD".to_string();
+    contract.sec_type = SecType::FUND;
+    contract.exchange = "FUNDSERV".into();
+    contract.currency = Currency::usd();
 
     contract
 }
@@ -107,9 +111,9 @@ pub fn mutual_fund() -> Contract {
 pub fn commodity() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "XAUUSD".to_string();
-    contract.sec_type = "CMDTY".to_string();
-    contract.exchange = "SMART".to_string();
-    contract.currency = "USD".to_string();
+    contract.sec_type = SecType::CMDTY;
+    contract.exchange = Exchange::Smart;
+    contract.currency = Currency::usd();
 
     contract
 }
@@ -118,10 +122,10 @@ pub fn commodity() -> Contract {
 pub fn usstock() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "AMZN".to_string();
-    contract.sec_type = "STK".to_string();
-    contract.currency = "USD".to_string();
+    contract.sec_type = SecType::STK;
+    contract.currency = Currency::usd();
     //In the API side, NASDAQ is always defined as ISLAND in the exchange field
-    contract.exchange = "ISLAND".to_string();
+    contract.exchange = Exchange::Island;
     //stkcontract]
     contract
 }
@@ -130,12 +134,12 @@ pub fn usstock() -> Contract {
 pub fn usstock_with_primary_exch() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "MSFT".to_string();
-    contract.sec_type = "STK".to_string();
-    contract.currency = "USD".to_string();
-    contract.exchange = "SMART".to_string();
+    contract.sec_type = SecType::STK;
+    contract.currency = Currency::usd();
+    contract.exchange = Exchange::Smart;
     //Specify the Primary Exchange attribute to avoid contract ambiguity
     //(there is an ambiguity because there is also a MSFT contract with primary exchange = "AEB")
-    contract.primary_exchange = "ISLAND".to_string();
+    contract.primary_exchange = Exchange::Island;
     //stkcontractwithprimary]
     contract
 }
@@ -144,9 +148,9 @@ pub fn usstock_with_primary_exch() -> Contract {
 pub fn us_stock_at_smart() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "MSFT".to_string();
-    contract.sec_type = "STK".to_string();
-    contract.currency = "USD".to_string();
-    contract.exchange = "SMART".to_string();
+    contract.sec_type = SecType::STK;
+    contract.currency = Currency::usd();
+    contract.exchange = Exchange::Smart;
     contract
 }
 
@@ -154,9 +158,9 @@ pub fn us_stock_at_smart() -> Contract {
 pub fn us_option_contract() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "GOOG".to_string();
-    contract.sec_type = "OPT".to_string();
-    contract.exchange = "SMART".to_string();
-    contract.currency = "USD".to_string();
+    contract.sec_type = SecType::OPT;
+    contract.exchange = Exchange::Smart;
+    contract.currency = Currency::usd();
     contract.last_trade_date_or_contract_month = "20201218".to_string();
     contract.strike = 1180.0;
     contract.right = "C".to_string();
@@ -169,9 +173,9 @@ pub fn us_option_contract() -> Contract {
 pub fn option_at_box() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "GOOG".to_string();
-    contract.sec_type = "OPT".to_string();
-    contract.exchange = "BOX".to_string();
-    contract.currency = "USD".to_string();
+    contract.sec_type = SecType::OPT;
+    contract.exchange = Exchange::Box;
+    contract.currency = Currency::usd();
     contract.last_trade_date_or_contract_month = "20201218".to_string();
     contract.strike = 1180.0;
     contract.right = "C".to_string();
@@ -188,9 +192,9 @@ pub fn option_at_box() -> Contract {
 pub fn option_with_trading_class() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "SANT".to_string();
-    contract.sec_type = "OPT".to_string();
-    contract.exchange = "MEFFRV".to_string();
-    contract.currency = "EUR".to_string();
+    contract.sec_type = SecType::OPT;
+    contract.exchange = "MEFFRV".into();
+    contract.currency = Currency::eur();
     contract.last_trade_date_or_contract_month = "20190621".to_string();
     contract.strike = 7.5;
     contract.right = "C".to_string();
@@ -207,9 +211,9 @@ pub fn option_with_local_symbol() -> Contract {
     let mut contract = Contract::default();
     //Watch out for the spaces within the local symbol!
     contract.local_symbol = "C DBK  DEC 20  1600".to_string();
-    contract.sec_type = "OPT".to_string();
-    contract.exchange = "DTB".to_string();
-    contract.currency = "EUR".to_string();
+    contract.sec_type = SecType::OPT;
+    contract.exchange = "DTB".into();
+    contract.currency = Currency::eur();
 
     contract
 }
@@ -219,9 +223,9 @@ pub fn option_with_local_symbol() -> Contract {
 pub fn dutch_warrant() -> Contract {
     let mut contract = Contract::default();
     contract.local_symbol = "B881G".to_string();
-    contract.sec_type = "IOPT".to_string();
-    contract.exchange = "SBF".to_string();
-    contract.currency = "EUR".to_string();
+    contract.sec_type = SecType::IOPT;
+    contract.exchange = Exchange::Sbf;
+    contract.currency = Currency::eur();
 
     contract
 }
@@ -232,9 +236,9 @@ pub fn dutch_warrant() -> Contract {
 pub fn simple_future() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "ES".to_string();
-    contract.sec_type = "FUT".to_string();
-    contract.exchange = "GLOBEX".to_string();
-    contract.currency = "USD".to_string();
+    contract.sec_type = SecType::FUT;
+    contract.exchange = Exchange::Globex;
+    contract.currency = Currency::usd();
     contract.last_trade_date_or_contract_month = "202009".to_string();
 
     contract
@@ -245,9 +249,9 @@ pub fn simple_future() -> Contract {
 /// attributes such as symbol, currency, strike, etc.
 pub fn future_with_local_symbol() -> Contract {
     let mut contract = Contract::default();
-    contract.sec_type = "FUT".to_string();
-    contract.exchange = "GLOBEX".to_string();
-    contract.currency = "USD".to_string();
+    contract.sec_type = SecType::FUT;
+    contract.exchange = Exchange::Globex;
+    contract.currency = Currency::usd();
     contract.local_symbol = "ESU0".to_string();
 
     contract
@@ -257,9 +261,9 @@ pub fn future_with_local_symbol() -> Contract {
 pub fn future_with_multiplier() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "DAX".to_string();
-    contract.sec_type = "FUT".to_string();
-    contract.exchange = "DTB".to_string();
-    contract.currency = "EUR".to_string();
+    contract.sec_type = SecType::FUT;
+    contract.exchange = "DTB".into();
+    contract.currency = Currency::eur();
     contract.last_trade_date_or_contract_month = "201903".to_string();
     contract.multiplier = "5".to_string();
 
@@ -272,9 +276,9 @@ pub fn wrong_contract() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = " IJR ".to_string();
     contract.con_id = 9579976;
-    contract.sec_type = "STK".to_string();
-    contract.exchange = "SMART".to_string();
-    contract.currency = "USD".to_string();
+    contract.sec_type = SecType::STK;
+    contract.exchange = Exchange::Smart;
+    contract.currency = Currency::usd();
     contract
 }
 
@@ -282,9 +286,9 @@ pub fn wrong_contract() -> Contract {
 pub fn futures_on_options() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "ES".to_string();
-    contract.sec_type = "FOP".to_string();
-    contract.exchange = "GLOBEX".to_string();
-    contract.currency = "USD".to_string();
+    contract.sec_type = SecType::FOP;
+    contract.exchange = Exchange::Globex;
+    contract.currency = Currency::usd();
     contract.last_trade_date_or_contract_month = "20190315".to_string();
     contract.strike = 2900.0;
     contract.right = "C".to_string();
@@ -300,9 +304,9 @@ pub fn by_isin() -> Contract {
     let mut contract = Contract::default();
     contract.sec_id_type = "ISIN".to_string();
     contract.sec_id = "US45841N1072".to_string();
-    contract.exchange = "SMART".to_string();
-    contract.currency = "USD".to_string();
-    contract.sec_type = "STK".to_string();
+    contract.exchange = Exchange::Smart;
+    contract.currency = Currency::usd();
+    contract.sec_type = SecType::STK;
     contract
 }
 
@@ -316,9 +320,9 @@ pub fn by_isin() -> Contract {
 /// in the example below.
 pub fn by_con_id() -> Contract {
     let mut contract = Contract::default();
-    contract.sec_type = "CASH".to_string();
+    contract.sec_type = SecType::CASH;
     contract.con_id = 12087792;
-    contract.exchange = "IDEALPRO".to_string();
+    contract.exchange = Exchange::IdealPro;
     contract
 }
 
@@ -331,9 +335,9 @@ pub fn by_con_id() -> Contract {
 pub fn option_for_query() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "FISV".to_string();
-    contract.sec_type = "OPT".to_string();
-    contract.exchange = "SMART".to_string();
-    contract.currency = "USD".to_string();
+    contract.sec_type = SecType::OPT;
+    contract.exchange = Exchange::Smart;
+    contract.currency = Currency::usd();
 
     contract
 }
@@ -342,20 +346,20 @@ pub fn option_for_query() -> Contract {
 pub fn option_combo_contract() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "DBK".to_string();
-    contract.sec_type = "BAG".to_string();
-    contract.currency = "EUR".to_string();
-    contract.exchange = "DTB".to_string();
+    contract.sec_type = SecType::BAG;
+    contract.currency = Currency::eur();
+    contract.exchange = "DTB".into();
 
     let mut leg1 = ComboLeg::default();
     leg1.con_id = 317960956; //DBK JUN 21 2019 C
     leg1.ratio = 1.0;
-    leg1.action = "BUY".to_string();
+    leg1.action = Action::Buy;
     leg1.exchange = "DTB".to_string();
 
     let mut leg2 = ComboLeg::default();
     leg2.con_id = 334216780; //DBK MAR 15 2019 C
     leg2.ratio = 1.0;
-    leg2.action = "SELL".to_string();
+    leg2.action = Action::Sell;
     leg2.exchange = "DTB".to_string();
 
     contract.combo_legs = vec![];
@@ -372,20 +376,20 @@ pub fn option_combo_contract() -> Contract {
 pub fn stock_combo_contract() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "IBKR,MCD".to_string();
-    contract.sec_type = "BAG".to_string();
-    contract.currency = "USD".to_string();
-    contract.exchange = "SMART".to_string();
+    contract.sec_type = SecType::BAG;
+    contract.currency = Currency::usd();
+    contract.exchange = Exchange::Smart;
 
     let mut leg1 = ComboLeg::default();
     leg1.con_id = 43645865; //IBKR STK
     leg1.ratio = 1.0;
-    leg1.action = "BUY".to_string();
+    leg1.action = Action::Buy;
     leg1.exchange = "SMART".to_string();
 
     let mut leg2 = ComboLeg::default();
     leg2.con_id = 9408; //MCD STK
     leg2.ratio = 1.0;
-    leg2.action = "SELL".to_string();
+    leg2.action = Action::Sell;
     leg2.exchange = "SMART".to_string();
 
     contract.combo_legs = vec![];
@@ -400,14 +404,14 @@ pub fn stock_combo_contract() -> Contract {
 pub fn future_combo_contract() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "VIX".to_string();
-    contract.sec_type = "BAG".to_string();
-    contract.currency = "USD".to_string();
-    contract.exchange = "CFE".to_string();
+    contract.sec_type = SecType::BAG;
+    contract.currency = Currency::usd();
+    contract.exchange = Exchange::Cfe;
 
     let mut leg1 = ComboLeg::default();
     leg1.con_id = 438391466; // VIX FUT 201903
     leg1.ratio = 1.0;
-    leg1.action = "BUY".to_string();
+    leg1.action = Action::Buy;
     leg1.exchange = "CFE".to_string();
     leg1.exempt_code = -1;
     leg1.open_close = PositionType::SamePos;
@@ -415,7 +419,7 @@ pub fn future_combo_contract() -> Contract {
     let mut leg2 = ComboLeg::default();
     leg2.con_id = 394987014; // VIX FUT 201904
     leg2.ratio = 1.0;
-    leg2.action = "SELL".to_string();
+    leg2.action = Action::Sell;
     leg2.exchange = "CFE".to_string();
     leg2.exempt_code = -1;
     leg2.open_close = PositionType::SamePos;
@@ -431,20 +435,20 @@ pub fn future_combo_contract() -> Contract {
 pub fn smart_future_combo_contract() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "WTI".to_string(); // WTI,COIL spread. Symbol can be defined as first leg symbol ("WTI") or currency ("USD")
-    contract.sec_type = "BAG".to_string();
-    contract.currency = "USD".to_string();
-    contract.exchange = "SMART".to_string();
+    contract.sec_type = SecType::BAG;
+    contract.currency = Currency::usd();
+    contract.exchange = Exchange::Smart;
 
     let mut leg1 = ComboLeg::default();
     leg1.con_id = 55928698; // WTI future June 2017
     leg1.ratio = 1.0;
-    leg1.action = "BUY".to_string();
+    leg1.action = Action::Buy;
     leg1.exchange = "IPE".to_string();
 
     let mut leg2 = ComboLeg::default();
     leg2.con_id = 55850663; // COIL future June 2017
     leg2.ratio = 1.0;
-    leg2.action = "SELL".to_string();
+    leg2.action = Action::Sell;
     leg2.exchange = "IPE".to_string();
 
     contract.combo_legs = vec![];
@@ -458,20 +462,20 @@ pub fn smart_future_combo_contract() -> Contract {
 pub fn inter_cmdty_futures_contract() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "CL.BZ".to_string(); //symbol is 'local symbol' of intercommodity spread.
-    contract.sec_type = "BAG".to_string();
-    contract.currency = "USD".to_string();
-    contract.exchange = "NYMEX".to_string();
+    contract.sec_type = SecType::BAG;
+    contract.currency = Currency::usd();
+    contract.exchange = Exchange::Nymex;
 
     let mut leg1 = ComboLeg::default();
     leg1.con_id = 47207310; //CL Dec'16 @NYMEX
     leg1.ratio = 1.0;
-    leg1.action = "BUY".to_string();
+    leg1.action = Action::Buy;
     leg1.exchange = "NYMEX".to_string();
 
     let mut leg2 = ComboLeg::default();
     leg2.con_id = 47195961; //BZ Dec'16 @NYMEX
     leg2.ratio = 1.0;
-    leg2.action = "SELL".to_string();
+    leg2.action = Action::Sell;
     leg2.exchange = "NYMEX".to_string();
 
     contract.combo_legs = vec![];
@@ -484,8 +488,8 @@ pub fn inter_cmdty_futures_contract() -> Contract {
 //==================================================================================================
 pub fn news_feed_for_query() -> Contract {
     let mut contract = Contract::default();
-    contract.sec_type = "NEWS".to_string();
-    contract.exchange = "BRFG".to_string(); //Briefing Trader
+    contract.sec_type = SecType::NEWS;
+    contract.exchange = "BRFG".into(); //Briefing Trader
 
     contract
 }
@@ -494,8 +498,8 @@ pub fn news_feed_for_query() -> Contract {
 pub fn brfgbroadtape_news_feed() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "BRFG:BRFG_ALL".to_string();
-    contract.sec_type = "NEWS".to_string();
-    contract.exchange = "BRFG".to_string();
+    contract.sec_type = SecType::NEWS;
+    contract.exchange = "BRFG".into();
 
     contract
 }
@@ -504,8 +508,8 @@ pub fn brfgbroadtape_news_feed() -> Contract {
 pub fn djnlbroadtape_news_feed() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "DJNL:DJNL_ALL".to_string();
-    contract.sec_type = "NEWS".to_string();
-    contract.exchange = "DJNL".to_string();
+    contract.sec_type = SecType::NEWS;
+    contract.exchange = "DJNL".into();
 
     contract
 }
@@ -514,8 +518,8 @@ pub fn djnlbroadtape_news_feed() -> Contract {
 pub fn djtopbroadtape_news_feed() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "DJTOP:ASIAPAC".to_string();
-    contract.sec_type = "NEWS".to_string();
-    contract.exchange = "DJTOP".to_string();
+    contract.sec_type = SecType::NEWS;
+    contract.exchange = "DJTOP".into();
 
     contract
 }
@@ -524,8 +528,8 @@ pub fn djtopbroadtape_news_feed() -> Contract {
 pub fn brfupdnbroadtape_news_feed() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "BRFUPDN:BRF_ALL".to_string();
-    contract.sec_type = "NEWS".to_string();
-    contract.exchange = "BRFUPDN".to_string();
+    contract.sec_type = SecType::NEWS;
+    contract.exchange = "BRFUPDN".into();
 
     contract
 }
@@ -534,8 +538,8 @@ pub fn brfupdnbroadtape_news_feed() -> Contract {
 pub fn cont_fut() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "ES".to_string();
-    contract.sec_type = "CONTFUT".to_string();
-    contract.exchange = "GLOBEX".to_string();
+    contract.sec_type = SecType::CONTFUT;
+    contract.exchange = Exchange::Globex;
 
     contract
 }
@@ -544,8 +548,8 @@ pub fn cont_fut() -> Contract {
 pub fn cont_and_expiring_fut() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "ES".to_string();
-    contract.sec_type = "FUT+CONTFUT".to_string();
-    contract.exchange = "GLOBEX".to_string();
+    contract.sec_type = SecType::FUTCONTFUT;
+    contract.exchange = Exchange::Globex;
 
     contract
 }
@@ -554,9 +558,9 @@ pub fn cont_and_expiring_fut() -> Contract {
 pub fn jefferies_contract() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "AAPL".to_string();
-    contract.sec_type = "STK".to_string();
-    contract.exchange = "JEFFALGO".to_string();
-    contract.currency = "USD".to_string();
+    contract.sec_type = SecType::STK;
+    contract.exchange = "JEFFALGO".into();
+    contract.currency = Currency::usd();
 
     contract
 }
@@ -565,9 +569,9 @@ pub fn jefferies_contract() -> Contract {
 pub fn csfbcontract() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "IBKR".to_string();
-    contract.sec_type = "STK".to_string();
-    contract.exchange = "CSFBALGO".to_string();
-    contract.currency = "USD".to_string();
+    contract.sec_type = SecType::STK;
+    contract.exchange = "CSFBALGO".into();
+    contract.currency = Currency::usd();
 
     contract
 }
@@ -576,9 +580,9 @@ pub fn csfbcontract() -> Contract {
 pub fn usstock_cfd() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "IBM".to_string();
-    contract.sec_type = "cfd".to_string();
-    contract.currency = "USD".to_string();
-    contract.exchange = "SMART".to_string();
+    contract.sec_type = SecType::CFD;
+    contract.currency = Currency::usd();
+    contract.exchange = Exchange::Smart;
 
     contract
 }
@@ -587,9 +591,9 @@ pub fn usstock_cfd() -> Contract {
 pub fn european_stock_cfd() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "BMW".to_string();
-    contract.sec_type = "cfd".to_string();
-    contract.currency = "EUR".to_string();
-    contract.exchange = "SMART".to_string();
+    contract.sec_type = SecType::CFD;
+    contract.currency = Currency::eur();
+    contract.exchange = Exchange::Smart;
 
     contract
 }
@@ -598,9 +602,9 @@ pub fn european_stock_cfd() -> Contract {
 pub fn cash_cfd() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "EUR".to_string();
-    contract.sec_type = "cfd".to_string();
-    contract.currency = "USD".to_string();
-    contract.exchange = "SMART".to_string();
+    contract.sec_type = SecType::CFD;
+    contract.currency = Currency::usd();
+    contract.exchange = Exchange::Smart;
 
     contract
 }
@@ -609,9 +613,9 @@ pub fn cash_cfd() -> Contract {
 pub fn qbalgo_contract() -> Contract {
     let mut contract = Contract::default();
     contract.symbol = "ES".to_string();
-    contract.sec_type = "FUT".to_string();
-    contract.exchange = "QBALGO".to_string();
-    contract.currency = "USD".to_string();
+    contract.sec_type = SecType::FUT;
+    contract.exchange = "QBALGO".into();
+    contract.currency = Currency::usd();
     contract.last_trade_date_or_contract_month = "202009".to_string();
 
     contract
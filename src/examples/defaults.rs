@@ -8,15 +8,18 @@ use chrono::prelude::DateTime;
 use chrono::Utc;
 use log::*;
 
+use crate::core::account_summary_tags::AccountSummaryTag;
 use crate::core::common::{
-    BarData, CommissionReport, DepthMktDataDescription, FaDataType, FamilyCode, HistogramData,
-    HistoricalTick, HistoricalTickBidAsk, HistoricalTickLast, NewsProvider, PriceIncrement,
-    RealTimeBar, SmartComponent, TickAttrib, TickAttribBidAsk, TickAttribLast, TickByTickType,
-    TickType,
+    BarData, CommissionReport, DepthMktDataDescription, FaDataType, FamilyCode, FeeRateBar,
+    HistogramData, HistoricalTick, HistoricalTickBidAsk, HistoricalTickLast, NewsArticleContent,
+    NewsProvider, PriceIncrement, RealTimeBar, SmartComponent, TickAttrib, TickAttribBidAsk,
+    TickAttribLast, TickByTickType, TickType, YieldBar,
 };
 use crate::core::contract::{Contract, ContractDescription, ContractDetails, DeltaNeutralContract};
 use crate::core::execution::Execution;
+use crate::core::news_bulletins::Bulletin;
 use crate::core::order::{Order, OrderState, SoftDollarTier};
+use crate::core::timestamps::{timestamp_to_wire, Timestamp};
 use crate::core::wrapper::Wrapper;
 
 //==================================================================================================
@@ -36,6 +39,14 @@ impl Wrapper for DefaultWrapper {
         );
     }
 
+    //----------------------------------------------------------------------------------------------
+    fn warning(&mut self, req_id: i32, warning_code: i32, warning_string: &str) {
+        warn!(
+            "req_id: {} ,warning_code: {} , warning_string:{}",
+            req_id, warning_code, warning_string
+        );
+    }
+
     //----------------------------------------------------------------------------------------------
     fn win_error(&mut self, text: &str, last_error: i32) {
         error!("text: {} , last_error:{}", text, last_error);
@@ -63,7 +74,7 @@ impl Wrapper for DefaultWrapper {
     }
 
     //----------------------------------------------------------------------------------------------
-    fn tick_size(&mut self, req_id: i32, tick_type: TickType, size: i32) {
+    fn tick_size(&mut self, req_id: i32, tick_type: TickType, size: BigDecimal) {
         info!(
             "tick_size -- req_id: {}, tick_type: {}, size: {}",
             req_id, tick_type, size
@@ -264,7 +275,7 @@ impl Wrapper for DefaultWrapper {
         operation: i32,
         side: i32,
         price: f64,
-        size: i32,
+        size: BigDecimal,
     ) {
         info!(
             "update_mkt_depth -- req_id: {}, position: {}, operation: {}, side: {}, price: {}, size: {}",
@@ -281,7 +292,7 @@ impl Wrapper for DefaultWrapper {
         operation: i32,
         side: i32,
         price: f64,
-        size: i32,
+        size: BigDecimal,
         is_smart_depth: bool,
     ) {
         info!(
@@ -291,17 +302,8 @@ impl Wrapper for DefaultWrapper {
     }
 
     //----------------------------------------------------------------------------------------------
-    fn update_news_bulletin(
-        &mut self,
-        msg_id: i32,
-        msg_type: i32,
-        news_message: &str,
-        origin_exch: &str,
-    ) {
-        info!(
-            "update_news_bulletin -- msg_id: {}, msg_type: {}, news_message: {}, origin_exch: {}",
-            msg_id, msg_type, news_message, origin_exch
-        );
+    fn update_news_bulletin(&mut self, bulletin: &Bulletin) {
+        info!("update_news_bulletin -- {}", bulletin);
     }
 
     //----------------------------------------------------------------------------------------------
@@ -327,6 +329,19 @@ impl Wrapper for DefaultWrapper {
         );
     }
 
+    //----------------------------------------------------------------------------------------------
+    fn historical_yield_data(&mut self, req_id: i32, bar: YieldBar) {
+        info!("historical_yield_data -- req_id: {}, bar: {}", req_id, bar);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn historical_fee_rate_data(&mut self, req_id: i32, bar: FeeRateBar) {
+        info!(
+            "historical_fee_rate_data -- req_id: {}, bar: {}",
+            req_id, bar
+        );
+    }
+
     //----------------------------------------------------------------------------------------------
     fn scanner_parameters(&mut self, xml: &str) {
         info!("scanner_parameters -- xml: {}", xml);
@@ -432,7 +447,7 @@ impl Wrapper for DefaultWrapper {
         &mut self,
         req_id: i32,
         account: &str,
-        tag: &str,
+        tag: &AccountSummaryTag,
         value: &str,
         currency: &str,
     ) {
@@ -638,7 +653,7 @@ impl Wrapper for DefaultWrapper {
     fn tick_news(
         &mut self,
         ticker_id: i32,
-        time_stamp: i32,
+        time_stamp: &Timestamp,
         provider_code: &str,
         article_id: &str,
         headline: &str,
@@ -647,7 +662,12 @@ impl Wrapper for DefaultWrapper {
         info!(
             "tick_news -- ticker_id: {}, time_stamp: {}, provider_code: {}, article_id: {}, \
              headline: {}, extra_data: {},",
-            ticker_id, time_stamp, provider_code, article_id, headline, extra_data
+            ticker_id,
+            timestamp_to_wire(time_stamp),
+            provider_code,
+            article_id,
+            headline,
+            extra_data
         );
     }
 
@@ -686,18 +706,37 @@ impl Wrapper for DefaultWrapper {
         );
     }
 
+    //----------------------------------------------------------------------------------------------
+    fn news_article_content(&mut self, request_id: i32, content: &NewsArticleContent) {
+        match content {
+            NewsArticleContent::Text(text) => info!(
+                "news_article_content -- request_id: {}, text: {}",
+                request_id, text
+            ),
+            NewsArticleContent::Pdf(bytes) => info!(
+                "news_article_content -- request_id: {}, pdf: {} bytes",
+                request_id,
+                bytes.len()
+            ),
+        }
+    }
+
     //----------------------------------------------------------------------------------------------
     fn historical_news(
         &mut self,
         request_id: i32,
-        time: &str,
+        time: &Timestamp,
         provider_code: &str,
         article_id: &str,
         headline: &str,
     ) {
         info!(
             "historical_news -- request_id: {}, time: {}, provider_code: {}, article_id: {}, headline: {}",
-            request_id, time, provider_code, article_id, headline
+            request_id,
+            timestamp_to_wire(time),
+            provider_code,
+            article_id,
+            headline
         );
     }
 
@@ -811,7 +850,7 @@ impl Wrapper for DefaultWrapper {
         tick_type: TickByTickType,
         time: i64,
         price: f64,
-        size: i32,
+        size: BigDecimal,
         tick_attrib_last: TickAttribLast,
         exchange: &str,
         special_conditions: &str,
@@ -830,8 +869,8 @@ impl Wrapper for DefaultWrapper {
         time: i64,
         bid_price: f64,
         ask_price: f64,
-        bid_size: i32,
-        ask_size: i32,
+        bid_size: BigDecimal,
+        ask_size: BigDecimal,
         tick_attrib_bid_ask: TickAttribBidAsk,
     ) {
         info!(
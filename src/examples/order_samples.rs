@@ -2,8 +2,8 @@
 
 use num_traits::FromPrimitive;
 
-use crate::core::common::TagValue;
-use crate::core::order::{AuctionStrategy, Order, OrderComboLeg};
+use crate::core::common::{HedgeType, TagValue};
+use crate::core::order::{AuctionStrategy, Order, OrderComboLeg, OrderType, Tif};
 use crate::core::order_condition::{
     create_condition, ConditionType, ExecutionCondition, MarginCondition, PercentChangeCondition,
     PriceCondition, TimeCondition, VolumeCondition,
@@ -16,9 +16,9 @@ use crate::core::order_condition::{
 //==================================================================================================
 pub fn at_auction(action: &str, quantity: f64, price: f64) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.tif = "AUC".to_string();
-    order.order_type = "MTL".to_string();
+    order.action = action.into();
+    order.tif = Tif::Auc;
+    order.order_type = OrderType::Mtl;
     order.total_quantity = quantity;
     order.lmt_price = price;
     order
@@ -30,8 +30,8 @@ pub fn at_auction(action: &str, quantity: f64, price: f64) -> Order {
 /// Products: STK
 pub fn discretionary(action: &str, quantity: f64, price: f64, discretionary_amount: f64) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "LMT".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Lmt;
     order.total_quantity = quantity;
     order.lmt_price = price;
     order.discretionary_amt = discretionary_amount;
@@ -45,8 +45,8 @@ pub fn discretionary(action: &str, quantity: f64, price: f64, discretionary_amou
 /// Products: BOND, CFD, EFP, CASH, FUND, FUT, FOP, OPT, STK, WAR
 pub fn market_order(action: &str, quantity: f64) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "MKT".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Mkt;
     order.total_quantity = quantity;
     order
 }
@@ -61,8 +61,8 @@ pub fn market_order(action: &str, quantity: f64) -> Order {
 /// Products: BOND, CFD, CASH, FUT, FOP, OPT, STK, WAR
 pub fn market_if_touched(action: &str, quantity: f64, price: f64) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "MIT".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Mit;
     order.total_quantity = quantity;
     order.aux_price = price;
     order
@@ -73,8 +73,8 @@ pub fn market_if_touched(action: &str, quantity: f64, price: f64) -> Order {
 /// Products: CFD, FUT, STK, WAR
 pub fn market_on_close(action: &str, quantity: f64) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "MOC".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Moc;
     order.total_quantity = quantity;
     order
 }
@@ -84,10 +84,10 @@ pub fn market_on_close(action: &str, quantity: f64) -> Order {
 /// Products: CFD, STK, OPT, WAR
 pub fn market_on_open(action: &str, quantity: f64) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "MKT".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Mkt;
     order.total_quantity = quantity;
-    order.tif = "OPG".to_string();
+    order.tif = Tif::Opg;
     order
 }
 
@@ -98,8 +98,8 @@ pub fn market_on_open(action: &str, quantity: f64) -> Order {
 /// Products: STK
 pub fn midpoint_match(action: &str, quantity: f64) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "MKT".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Mkt;
     order.total_quantity = quantity;
     order
 }
@@ -110,8 +110,8 @@ pub fn midpoint_match(action: &str, quantity: f64) -> Order {
 /// order) you are willing to accept. Requires TWS 975+. Smart-routing to US stocks only.
 pub fn midprice(action: &str, quantity: f64, price_cap: f64) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "MIDPRICE".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::MidPrice;
     order.total_quantity = quantity;
     order.lmt_price = price_cap; // optional
                                  //midprice]
@@ -129,8 +129,8 @@ pub fn midprice(action: &str, quantity: f64, price_cap: f64) -> Order {
 pub fn pegged_to_market(action: &str, quantity: f64, market_offset: f64) -> Order {
     //pegged_market]
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "PEG MKT".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::PegMkt;
     order.total_quantity = quantity;
     order.aux_price = market_offset; //Offset price
                                      //pegged_market]
@@ -155,8 +155,8 @@ pub fn pegged_to_stock(
 ) -> Order {
     //pegged_stock]
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "PEG STK".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::PegStk;
     order.total_quantity = quantity;
     order.delta = delta;
     order.stock_ref_price = stock_reference_price;
@@ -184,8 +184,8 @@ pub fn relative_pegged_to_primary(
 ) -> Order {
     //relative_pegged_primary]
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "REL".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Rel;
     order.total_quantity = quantity;
     order.lmt_price = price_cap;
     order.aux_price = offset_amount;
@@ -202,8 +202,8 @@ pub fn relative_pegged_to_primary(
 pub fn sweep_to_fill(action: &str, quantity: f64, price: f64) -> Order {
     //sweep_to_fill]
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "LMT".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Lmt;
     order.total_quantity = quantity;
     order.lmt_price = price;
     order.sweep_to_fill = true;
@@ -229,8 +229,8 @@ pub fn auction_limit(
 ) -> Order {
     //auction_limit]
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "LMT".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Lmt;
     order.total_quantity = quantity;
     order.lmt_price = price;
     order.auction_strategy = auction_strategy;
@@ -259,8 +259,8 @@ pub fn auction_pegged_to_stock(
 ) -> Order {
     //auction_pegged_stock]
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "PEG STK".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::PegStk;
     order.total_quantity = quantity;
     order.delta = delta;
     order.starting_price = starting_price;
@@ -284,8 +284,8 @@ pub fn auction_pegged_to_stock(
 pub fn auction_relative(action: &str, quantity: f64, offset: f64) -> Order {
     //auction_relative]
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "REL".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Rel;
     order.total_quantity = quantity;
     order.aux_price = offset;
     //auction_relative]
@@ -299,8 +299,8 @@ pub fn auction_relative(action: &str, quantity: f64, offset: f64) -> Order {
 pub fn block(action: &str, quantity: f64, price: f64) -> Order {
     //block]
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "LMT".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Lmt;
     order.total_quantity = quantity; //Large volumes!
     order.lmt_price = price;
     order.block_order = true;
@@ -316,8 +316,8 @@ pub fn block(action: &str, quantity: f64, price: f64) -> Order {
 pub fn box_top(action: &str, quantity: f64) -> Order {
     //boxtop]
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "BOX TOP".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::BoxTop;
     order.total_quantity = quantity;
     //boxtop]
     order
@@ -330,8 +330,8 @@ pub fn box_top(action: &str, quantity: f64) -> Order {
 pub fn limit_order(action: &str, quantity: f64, limit_price: f64) -> Order {
     //limitorder]
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "LMT".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Lmt;
     order.total_quantity = quantity;
     order.lmt_price = limit_price;
     order.transmit = true;
@@ -350,8 +350,8 @@ pub fn limit_order_with_cash_qty(
     cash_qty: f64,
 ) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "LMT".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Lmt;
     order.total_quantity = quantity;
     order.lmt_price = limit_price;
     order.cash_qty = cash_qty;
@@ -372,8 +372,8 @@ pub fn limit_if_touched(
 ) -> Order {
     //limitiftouched]
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "LIT".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Lit;
     order.total_quantity = quantity;
     order.lmt_price = limit_price;
     order.aux_price = trigger_price;
@@ -387,8 +387,8 @@ pub fn limit_if_touched(
 //==================================================================================================
 pub fn limit_on_close(action: &str, quantity: f64, limit_price: f64) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "LOC".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Loc;
     order.total_quantity = quantity;
     order.lmt_price = limit_price;
 
@@ -401,9 +401,9 @@ pub fn limit_on_close(action: &str, quantity: f64, limit_price: f64) -> Order {
 //==================================================================================================
 pub fn limit_on_open(action: &str, quantity: f64, limit_price: f64) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.tif = "OPG".to_string();
-    order.order_type = "LMT".to_string();
+    order.action = action.into();
+    order.tif = Tif::Opg;
+    order.order_type = OrderType::Lmt;
     order.total_quantity = quantity;
     order.lmt_price = limit_price;
 
@@ -423,8 +423,8 @@ pub fn limit_on_open(action: &str, quantity: f64, limit_price: f64) -> Order {
 //==================================================================================================
 pub fn passive_relative(action: &str, quantity: f64, offset: f64) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "PASSV REL".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::PassvRel;
     order.total_quantity = quantity;
     order.aux_price = offset;
 
@@ -440,8 +440,8 @@ pub fn passive_relative(action: &str, quantity: f64, offset: f64) -> Order {
 pub fn pegged_to_midpoint(action: &str, quantity: f64, offset: f64, limit_price: f64) -> Order {
     //pegged_midpoint]
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "PEG MID".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::PegMid;
     order.total_quantity = quantity;
     order.aux_price = offset;
     order.lmt_price = limit_price;
@@ -465,8 +465,8 @@ pub fn bracket_order(
     // This will be our main or "parent" order
     let mut parent = Order::default();
     parent.order_id = parent_order_id;
-    parent.action = action.to_string();
-    parent.order_type = "LMT".to_string();
+    parent.action = action.into();
+    parent.order_type = OrderType::Lmt;
     parent.total_quantity = quantity;
     parent.lmt_price = limit_price;
     // The parent and children orders will need this attribute set to False to prevent accidental executions.
@@ -475,8 +475,8 @@ pub fn bracket_order(
 
     let mut take_profit = Order::default();
     take_profit.order_id = parent.order_id + 1;
-    take_profit.action = (if action == "BUY" { "SELL" } else { "BUY" }).to_string();
-    take_profit.order_type = "LMT".to_string();
+    take_profit.action = parent.action.opposite();
+    take_profit.order_type = OrderType::Lmt;
     take_profit.total_quantity = quantity;
     take_profit.lmt_price = take_profit_limit_price;
     take_profit.parent_id = parent_order_id;
@@ -484,8 +484,8 @@ pub fn bracket_order(
 
     let mut stop_loss = Order::default();
     stop_loss.order_id = parent.order_id + 2;
-    stop_loss.action = (if action == "BUY" { "SELL" } else { "BUY" }).to_string();
-    stop_loss.order_type = "STP".to_string();
+    stop_loss.action = parent.action.opposite();
+    stop_loss.order_type = OrderType::Stp;
     // stop trigger price
     stop_loss.aux_price = stop_loss_price;
     stop_loss.total_quantity = quantity;
@@ -504,8 +504,8 @@ pub fn bracket_order(
 //==================================================================================================
 pub fn market_to_limit(action: &str, quantity: f64) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "MTL".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Mtl;
     order.total_quantity = quantity;
 
     order
@@ -518,8 +518,8 @@ pub fn market_to_limit(action: &str, quantity: f64) -> Order {
 //==================================================================================================
 pub fn market_with_protection(action: &str, quantity: f64) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "MKT PRT".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::MktPrt;
     order.total_quantity = quantity;
 
     order
@@ -534,8 +534,8 @@ pub fn market_with_protection(action: &str, quantity: f64) -> Order {
 //==================================================================================================
 pub fn stop(action: &str, quantity: f64, stop_price: f64) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "STP".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Stp;
     order.aux_price = stop_price;
     order.total_quantity = quantity;
 
@@ -549,8 +549,8 @@ pub fn stop(action: &str, quantity: f64, stop_price: f64) -> Order {
 //==================================================================================================
 pub fn stop_limit(action: &str, quantity: f64, limit_price: f64, stop_price: f64) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "STP LMT".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::StpLmt;
     order.total_quantity = quantity;
     order.lmt_price = limit_price;
     order.aux_price = stop_price;
@@ -568,8 +568,8 @@ pub fn stop_limit(action: &str, quantity: f64, limit_price: f64, stop_price: f64
 pub fn stop_with_protection(action: &str, quantity: f64, stop_price: f64) -> Order {
     let mut order = Order::default();
     order.total_quantity = quantity;
-    order.action = action.to_string();
-    order.order_type = "STP PRT".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::StpPrt;
     order.aux_price = stop_price;
 
     order
@@ -589,8 +589,8 @@ pub fn trailing_stop(
     trail_stop_price: f64,
 ) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "TRAIL".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Trail;
     order.total_quantity = quantity;
     order.trailing_percent = trailing_percent;
     order.trail_stop_price = trail_stop_price;
@@ -615,8 +615,8 @@ pub fn trailing_stop_limit(
     trail_stop_price: f64,
 ) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "TRAIL LIMIT".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::TrailLimit;
     order.total_quantity = quantity;
     order.trail_stop_price = trail_stop_price;
     order.lmt_price_offset = lmt_price_offset;
@@ -638,9 +638,9 @@ pub fn combo_limit_order(
     non_guaranteed: bool,
 ) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "LMT".to_string();
-    order.tif = "GTC".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Lmt;
+    order.tif = Tif::Gtc;
     order.total_quantity = quantity;
     order.lmt_price = limit_price;
     if non_guaranteed {
@@ -660,8 +660,8 @@ pub fn combo_limit_order(
 //==================================================================================================
 pub fn combo_market_order(action: &str, quantity: f64, non_guaranteed: bool) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "MKT".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Mkt;
     order.total_quantity = quantity;
     if non_guaranteed {
         order
@@ -685,8 +685,8 @@ pub fn limit_order_for_combo_with_leg_prices(
     non_guaranteed: bool,
 ) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "LMT".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Lmt;
     order.total_quantity = quantity;
 
     for price in leg_prices {
@@ -717,9 +717,9 @@ pub fn relative_limit_combo(
     non_guaranteed: bool,
 ) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
+    order.action = action.into();
     order.total_quantity = quantity;
-    order.order_type = "REL + LMT".to_string();
+    order.order_type = OrderType::RelLmt;
     order.lmt_price = limit_price;
     if non_guaranteed {
         order
@@ -738,9 +738,9 @@ pub fn relative_limit_combo(
 //==================================================================================================
 pub fn relative_market_combo(action: &str, quantity: f64, non_guaranteed: bool) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
+    order.action = action.into();
     order.total_quantity = quantity;
-    order.order_type = "REL + MKT".to_string();
+    order.order_type = OrderType::RelMkt;
     if non_guaranteed {
         order
             .smart_combo_routing_params
@@ -785,8 +785,8 @@ pub fn volatility(
     volatility_type: i32,
 ) -> Order {
     let mut order = Order::default();
-    order.action = action.to_string();
-    order.order_type = "VOL".to_string();
+    order.action = action.into();
+    order.order_type = OrderType::Vol;
     order.total_quantity = quantity;
     order.volatility = volatility_percent; //Expressed in percentage (40%)
     order.volatility_type = volatility_type; // 1=daily, 2=annual
@@ -799,7 +799,7 @@ pub fn market_fhedge(parent_order_id: i32, action: &str) -> Order {
     // FX Hedge orders can only have a quantity of 0
     let mut order = market_order(action, 0.0);
     order.parent_id = parent_order_id;
-    order.hedge_type = "F".to_string();
+    order.hedge_type = HedgeType::Fx;
     order
 }
 
@@ -818,9 +818,9 @@ pub fn pegged_to_benchmark(
     reference_contract_upper_range: f64,
 ) -> Order {
     let mut order = Order::default();
-    order.order_type = "PEG BENCH".to_string();
+    order.order_type = OrderType::PegBench;
     // BUY or SELL
-    order.action = action.to_string();
+    order.action = action.into();
     order.total_quantity = quantity;
     // Beginning with price...
     order.starting_price = starting_price;
@@ -833,7 +833,7 @@ pub fn pegged_to_benchmark(
     // in the reference contract...
     order.reference_contract_id = reference_con_id;
     // being traded at...
-    order.reference_exchange_id = reference_exchange.parse().unwrap();
+    order.reference_exchange_id = reference_exchange.to_string();
     //starting reference price is...
     order.stock_ref_price = stock_reference_price;
     // Keep order active as long as reference contract trades between...
@@ -853,12 +853,7 @@ pub fn attach_adjustable_to_stop(
 ) -> Order {
     // Attached order is a conventional STP order in opposite direction
     let mut order = stop(
-        (if parent.action == "BUY" {
-            "SELL"
-        } else {
-            "BUY"
-        })
-        .as_ref(),
+        parent.action.opposite().code(),
         parent.total_quantity,
         attached_order_stop_price,
     );
@@ -866,7 +861,7 @@ pub fn attach_adjustable_to_stop(
     // When trigger price is penetrated
     order.trigger_price = trigger_price;
     // The parent order will be turned into a STP order
-    order.adjusted_order_type = "STP".to_string();
+    order.adjusted_order_type = OrderType::Stp;
     // With the given STP price
     order.adjusted_stop_price = adjust_stop_price;
     //adjustable_stop]
@@ -883,12 +878,7 @@ pub fn attach_adjustable_to_stop_limit(
 ) -> Order {
     // Attached order is a conventional STP order
     let mut order = stop(
-        (if parent.action == "BUY" {
-            "SELL"
-        } else {
-            "BUY"
-        })
-        .as_ref(),
+        parent.action.opposite().code(),
         parent.total_quantity,
         attached_order_stop_price,
     );
@@ -896,7 +886,7 @@ pub fn attach_adjustable_to_stop_limit(
     // When trigger price is penetrated
     order.trigger_price = trigger_price;
     // The parent order will be turned into a STP LMT order
-    order.adjusted_order_type = "STP LMT".to_string();
+    order.adjusted_order_type = OrderType::StpLmt;
     // With the given stop price
     order.adjusted_stop_price = adjusted_stop_price;
     // And the given limit price
@@ -916,12 +906,7 @@ pub fn attach_adjustable_to_trail(
 ) -> Order {
     // Attached order is a conventional STP order
     let mut order = stop(
-        (if parent.action == "BUY" {
-            "SELL"
-        } else {
-            "BUY"
-        })
-        .as_ref(),
+        parent.action.opposite().code(),
         parent.total_quantity,
         attached_order_stop_price,
     );
@@ -929,10 +914,10 @@ pub fn attach_adjustable_to_trail(
     // When trigger price is penetrated
     order.trigger_price = trigger_price;
     // The parent order will be turned into a TRAIL order
-    order.adjusted_order_type = "TRAIL".to_string();
+    order.adjusted_order_type = OrderType::Trail;
     // With a stop price of...
     order.adjusted_stop_price = adjusted_stop_price;
-    // traling by and amount (0) or a percent (1)...
+    // traling by and amount (TRAILING_UNIT_AMOUNT) or a percent (TRAILING_UNIT_PERCENT)...
     order.adjustable_trailing_unit = trail_unit;
     // of...
     order.adjusted_trailing_amount = adjusted_trail_amount;
@@ -1023,7 +1008,7 @@ pub fn percentage_change_condition(
     is_conjunction: bool,
 ) -> PercentChangeCondition {
     let mut pct_change_condition: PercentChangeCondition =
-        create_condition(ConditionType::Execution).into();
+        create_condition(ConditionType::PercentChange).into();
     // If there is a price percent change measured against last close price above or below...
     pct_change_condition
         .contract_condition
@@ -1,17 +1,20 @@
 #![allow(unused_imports)]
 use crate::{
     core::client::{EClient, LogLevel},
+    core::common,
     core::common::{
-        BarData, CommissionReport, DepthMktDataDescription, FaDataType, FamilyCode, HistogramData,
-        HistoricalTick, HistoricalTickBidAsk, HistoricalTickLast, MarketDataTypeEnum, NewsProvider,
-        PriceIncrement, RealTimeBar, SmartComponent, TagValue, TickAttrib, TickAttribBidAsk,
-        TickAttribLast, TickByTickType, TickType,
+        BarData, BarSize, CommissionReport, DepthMktDataDescription, FaDataType, FamilyCode,
+        FeeRateBar, HistogramData, HistoricalTick, HistoricalTickBidAsk, HistoricalTickLast,
+        MarketDataTypeEnum, NewsArticleContent, NewsProvider, PriceIncrement, RealTimeBar,
+        SmartComponent, TagValue, TickAttrib, TickAttribBidAsk, TickAttribLast, TickByTickType,
+        TickType, WhatToShow, YieldBar,
     },
     core::contract::{Contract, ContractDescription, ContractDetails, DeltaNeutralContract},
     core::errors::IBKRApiLibError,
     core::execution::{Execution, ExecutionFilter},
     core::{
-        account_summary_tags::AccountSummaryTags,
+        account_summary_tags::{AccountSummaryTag, AccountSummaryTagSet},
+        news_bulletins::Bulletin,
         order::{Order, OrderState, SoftDollarTier},
         order_condition::TriggerMethod,
         wrapper::Wrapper,
@@ -26,6 +29,7 @@ use crate::{
             fill_twap_params, fill_vwap_params,
         },
         streamer::Streamer,
+        timestamps::{timestamp_to_wire, Timestamp},
     },
     examples::{
         contract_samples, fa_allocation_samples, order_samples, scanner_subscription_samples,
@@ -109,14 +113,25 @@ impl<T: Streamer> TestWrapper<T> {
         locked_client.req_family_codes()?;
 
         // Requesting accounts' summary
-        let all_tags = AccountSummaryTags::AllTags.to_string();
-        locked_client.req_account_summary(9001, "All", all_tags.as_str())?;
-
-        locked_client.req_account_summary(9002, "All", "$LEDGER")?;
-
-        locked_client.req_account_summary(9003, "All", "$LEDGER:EUR")?;
-
-        locked_client.req_account_summary(9004, "All", "$LEDGER:ALL")?;
+        locked_client.req_account_summary(9001, "All", &AccountSummaryTagSet::all())?;
+
+        locked_client.req_account_summary(
+            9002,
+            "All",
+            &AccountSummaryTagSet::new().with(AccountSummaryTag::Ledger),
+        )?;
+
+        locked_client.req_account_summary(
+            9003,
+            "All",
+            &AccountSummaryTagSet::new().with(AccountSummaryTag::LedgerCurrency("EUR".to_string())),
+        )?;
+
+        locked_client.req_account_summary(
+            9004,
+            "All",
+            &AccountSummaryTagSet::new().with(AccountSummaryTag::LedgerAll),
+        )?;
 
         locked_client.req_account_updates(true, self.account.as_str())?;
 
@@ -142,8 +157,8 @@ impl<T: Streamer> TestWrapper<T> {
             .req_real_time_bars(
                 3001,
                 contract_samples::us_stock_at_smart().borrow(),
-                1,
-                "TRADES",
+                &BarSize::_5Secs,
+                &WhatToShow::Trades,
                 true,
                 vec![],
             )?;
@@ -1409,9 +1424,9 @@ impl<T: Streamer> TestWrapper<T> {
                 4102,
                 contract_samples::simple_future().borrow(),
                 query_time.as_str(),
-                "1 M",
-                "1 day",
-                "MIDPOINT",
+                &common::Duration::Months(1),
+                &BarSize::_1Day,
+                &WhatToShow::Midpoint,
                 1,
                 1,
                 false,
@@ -1426,9 +1441,9 @@ impl<T: Streamer> TestWrapper<T> {
                 4103,
                 contract_samples::simple_future().borrow(),
                 query_time.as_str(),
-                "10 D",
-                "1 min",
-                "TRADES",
+                &common::Duration::Days(10),
+                &BarSize::_1Min,
+                &WhatToShow::Trades,
                 1,
                 1,
                 false,
@@ -1443,9 +1458,9 @@ impl<T: Streamer> TestWrapper<T> {
                 4104,
                 contract_samples::eur_gbp_fx().borrow(),
                 "",
-                "1 M",
-                "1 day",
-                "MIDPOINT",
+                &common::Duration::Months(1),
+                &BarSize::_1Day,
+                &WhatToShow::Midpoint,
                 1,
                 1,
                 true,
@@ -2242,9 +2257,9 @@ impl<T: Streamer> TestWrapper<T> {
                 18002,
                 contract_samples::cont_fut().borrow(),
                 time_str.to_string().as_str(),
-                "1 Y",
-                "1 month",
-                "TRADES",
+                &common::Duration::Years(1),
+                &BarSize::_1Month,
+                &WhatToShow::Trades,
                 0,
                 1,
                 false,
@@ -3038,6 +3053,14 @@ where
         );
     }
 
+    //----------------------------------------------------------------------------------------------
+    fn warning(&mut self, req_id: i32, warning_code: i32, warning_string: &str) {
+        warn!(
+            "req_id: {} ,warning_code: {} , warning_string:{}",
+            req_id, warning_code, warning_string
+        );
+    }
+
     //----------------------------------------------------------------------------------------------
     fn win_error(&mut self, text: &str, last_error: i32) {
         error!("text: {} , last_error:{}", text, last_error);
@@ -3065,7 +3088,7 @@ where
     }
 
     //----------------------------------------------------------------------------------------------
-    fn tick_size(&mut self, req_id: i32, tick_type: TickType, size: i32) {
+    fn tick_size(&mut self, req_id: i32, tick_type: TickType, size: BigDecimal) {
         info!(
             "tick_size -- req_id: {}, tick_type: {}, size: {}",
             req_id, tick_type, size
@@ -3271,7 +3294,7 @@ where
         operation: i32,
         side: i32,
         price: f64,
-        size: i32,
+        size: BigDecimal,
     ) {
         info!(
             "update_mkt_depth -- req_id: {}, position: {}, operation: {}, side: {}, price: {}, size: {}",
@@ -3288,7 +3311,7 @@ where
         operation: i32,
         side: i32,
         price: f64,
-        size: i32,
+        size: BigDecimal,
         is_smart_depth: bool,
     ) {
         info!(
@@ -3298,17 +3321,8 @@ where
     }
 
     //----------------------------------------------------------------------------------------------
-    fn update_news_bulletin(
-        &mut self,
-        msg_id: i32,
-        msg_type: i32,
-        news_message: &str,
-        origin_exch: &str,
-    ) {
-        info!(
-            "update_news_bulletin -- msg_id: {}, msg_type: {}, news_message: {}, origin_exch: {}",
-            msg_id, msg_type, news_message, origin_exch
-        );
+    fn update_news_bulletin(&mut self, bulletin: &Bulletin) {
+        info!("update_news_bulletin -- {}", bulletin);
     }
 
     //----------------------------------------------------------------------------------------------
@@ -3336,6 +3350,19 @@ where
         );
     }
 
+    //----------------------------------------------------------------------------------------------
+    fn historical_yield_data(&mut self, req_id: i32, bar: YieldBar) {
+        info!("historical_yield_data -- req_id: {}, bar: {}", req_id, bar);
+    }
+
+    //----------------------------------------------------------------------------------------------
+    fn historical_fee_rate_data(&mut self, req_id: i32, bar: FeeRateBar) {
+        info!(
+            "historical_fee_rate_data -- req_id: {}, bar: {}",
+            req_id, bar
+        );
+    }
+
     //----------------------------------------------------------------------------------------------
     fn scanner_parameters(&mut self, xml: &str) {
         info!("scanner_parameters -- xml: {}", xml);
@@ -3441,7 +3468,7 @@ where
         &mut self,
         req_id: i32,
         account: &str,
-        tag: &str,
+        tag: &AccountSummaryTag,
         value: &str,
         currency: &str,
     ) {
@@ -3647,7 +3674,7 @@ where
     fn tick_news(
         &mut self,
         ticker_id: i32,
-        time_stamp: i32,
+        time_stamp: &Timestamp,
         provider_code: &str,
         article_id: &str,
         headline: &str,
@@ -3656,7 +3683,12 @@ where
         info!(
             "tick_news -- ticker_id: {}, time_stamp: {}, provider_code: {}, article_id: {}, \
              headline: {}, extra_data: {},",
-            ticker_id, time_stamp, provider_code, article_id, headline, extra_data
+            ticker_id,
+            timestamp_to_wire(time_stamp),
+            provider_code,
+            article_id,
+            headline,
+            extra_data
         );
     }
 
@@ -3695,18 +3727,37 @@ where
         );
     }
 
+    //----------------------------------------------------------------------------------------------
+    fn news_article_content(&mut self, request_id: i32, content: &NewsArticleContent) {
+        match content {
+            NewsArticleContent::Text(text) => info!(
+                "news_article_content -- request_id: {}, text: {}",
+                request_id, text
+            ),
+            NewsArticleContent::Pdf(bytes) => info!(
+                "news_article_content -- request_id: {}, pdf: {} bytes",
+                request_id,
+                bytes.len()
+            ),
+        }
+    }
+
     //----------------------------------------------------------------------------------------------
     fn historical_news(
         &mut self,
         request_id: i32,
-        time: &str,
+        time: &Timestamp,
         provider_code: &str,
         article_id: &str,
         headline: &str,
     ) {
         info!(
             "historical_news -- request_id: {}, time: {}, provider_code: {}, article_id: {}, headline: {}",
-            request_id, time, provider_code, article_id, headline
+            request_id,
+            timestamp_to_wire(time),
+            provider_code,
+            article_id,
+            headline
         );
     }
 
@@ -3819,7 +3870,7 @@ where
         tick_type: TickByTickType,
         time: i64,
         price: f64,
-        size: i32,
+        size: BigDecimal,
         tick_attrib_last: TickAttribLast,
         exchange: &str,
         special_conditions: &str,
@@ -3838,8 +3889,8 @@ where
         time: i64,
         bid_price: f64,
         ask_price: f64,
-        bid_size: i32,
-        ask_size: i32,
+        bid_size: BigDecimal,
+        ask_size: BigDecimal,
         tick_attrib_bid_ask: TickAttribBidAsk,
     ) {
         info!(
@@ -1,2 +1,57 @@
+pub(crate) mod test_account_values;
+pub(crate) mod test_aggregation;
+pub(crate) mod test_capture;
+pub(crate) mod test_combo_fills;
+pub(crate) mod test_common;
+pub(crate) mod test_confirmation;
+pub(crate) mod test_contract;
+pub(crate) mod test_contract_cache;
+pub(crate) mod test_contract_details;
+pub(crate) mod test_coverage;
+pub(crate) mod test_currency;
+pub(crate) mod test_display_groups;
 pub(crate) mod test_eclient;
+pub(crate) mod test_errors;
+pub(crate) mod test_exchange;
+pub(crate) mod test_exits;
+pub(crate) mod test_export;
+pub(crate) mod test_fa;
+pub(crate) mod test_fills;
+pub(crate) mod test_heartbeat;
+pub(crate) mod test_history;
+pub(crate) mod test_history_cache;
+pub(crate) mod test_instrument;
+pub(crate) mod test_intent_log;
+pub(crate) mod test_latency;
+pub(crate) mod test_live_gateway;
+pub(crate) mod test_log_targets;
+pub(crate) mod test_market_rules;
+pub(crate) mod test_message_queue;
 pub(crate) mod test_messages;
+pub(crate) mod test_mock_tws;
+pub(crate) mod test_news_bulletins;
+pub(crate) mod test_option_chains;
+pub(crate) mod test_order_book;
+pub(crate) mod test_order_condition;
+pub(crate) mod test_order_flow;
+pub(crate) mod test_order_templates;
+pub(crate) mod test_order_tracker;
+pub(crate) mod test_pnl;
+pub(crate) mod test_pool;
+pub(crate) mod test_portfolio;
+pub(crate) mod test_precautions;
+pub(crate) mod test_quote;
+pub(crate) mod test_reader;
+pub(crate) mod test_request_context;
+pub(crate) mod test_risk;
+pub(crate) mod test_roll_calendar;
+pub(crate) mod test_scanner;
+pub(crate) mod test_serde_unset;
+pub(crate) mod test_shutdown;
+pub(crate) mod test_smart_components;
+pub(crate) mod test_strategy;
+pub(crate) mod test_tick_timeline;
+pub(crate) mod test_time_zones;
+pub(crate) mod test_timers;
+pub(crate) mod test_timestamps;
+pub(crate) mod test_what_if;
@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::core::account_values::{AccountValueDeltas, ChangeThreshold};
+
+    #[test]
+    fn test_account_value_deltas() {
+        let mut deltas = AccountValueDeltas::new(ChangeThreshold::new(10.0, 0.01));
+        deltas.set_threshold("UnrealizedPnL", ChangeThreshold::new(100.0, 1.0));
+
+        let first = deltas
+            .update("NetLiquidation", "1000", "USD", "DU123")
+            .unwrap();
+        assert_eq!(None, first.previous);
+
+        assert!(deltas
+            .update("NetLiquidation", "1005", "USD", "DU123")
+            .is_none());
+        // compared with the last reported value, so the drift adds up
+        let change = deltas
+            .update("NetLiquidation", "1011", "USD", "DU123")
+            .unwrap();
+        assert_eq!(Some("1000".to_string()), change.previous);
+        assert_eq!("1011", change.value);
+
+        deltas
+            .update("UnrealizedPnL", "50", "USD", "DU123")
+            .unwrap();
+        assert!(deltas
+            .update("UnrealizedPnL", "90", "USD", "DU123")
+            .is_none());
+
+        // only a change exceeding both limits is reported
+        deltas.set_threshold("CashBalance", ChangeThreshold::new(10.0, 0.5));
+        deltas
+            .update("CashBalance", "1000", "USD", "DU123")
+            .unwrap();
+        assert!(deltas
+            .update("CashBalance", "1011", "USD", "DU123")
+            .is_none());
+        // from 0 the absolute limit decides
+        deltas.update("CashBalance", "0", "EUR", "DU123").unwrap();
+        assert!(deltas.update("CashBalance", "5", "EUR", "DU123").is_none());
+        assert!(deltas.update("CashBalance", "11", "EUR", "DU123").is_some());
+
+        deltas
+            .update("AccountType", "INDIVIDUAL", "", "DU123")
+            .unwrap();
+        assert!(deltas
+            .update("AccountType", "INDIVIDUAL", "", "DU123")
+            .is_none());
+        assert!(deltas
+            .update("AccountType", "CORPORATION", "", "DU123")
+            .is_some());
+    }
+}
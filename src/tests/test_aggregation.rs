@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod tests {
+
+    use bigdecimal::BigDecimal;
+
+    use crate::core::aggregation::{RealTimeBarResampler, TickBarAggregator};
+    use crate::core::common::RealTimeBar;
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::timestamps::{timestamp_from_seconds, timestamp_seconds};
+
+    #[test]
+    fn test_tick_bar_aggregator() {
+        let mut aggregator = TickBarAggregator::new(5, false);
+        assert!(aggregator
+            .on_trade(100, 10.0, BigDecimal::from(100))
+            .is_empty());
+        assert!(aggregator
+            .on_trade(103, 11.0, BigDecimal::from(300))
+            .is_empty());
+        // out of order, same bar: keeps the close of the trade at 103
+        assert!(aggregator
+            .on_trade(101, 9.0, BigDecimal::from(100))
+            .is_empty());
+
+        let bars = aggregator.on_trade(106, 12.0, BigDecimal::from(50));
+        assert_eq!(1, bars.len());
+        assert_eq!(timestamp_from_seconds(100), bars[0].date);
+        assert_eq!(10.0, bars[0].open);
+        assert_eq!(11.0, bars[0].high);
+        assert_eq!(9.0, bars[0].low);
+        assert_eq!(11.0, bars[0].close);
+        assert_eq!(BigDecimal::from(500), bars[0].volume);
+        assert_eq!(3, bars[0].bar_count);
+        assert_eq!((1000.0 + 3300.0 + 900.0) / 500.0, bars[0].average);
+
+        assert!(aggregator
+            .on_trade(99, 8.0, BigDecimal::from(10))
+            .is_empty()); // bar already emitted
+        assert_eq!(1, aggregator.late_ticks());
+        assert_eq!(12.0, aggregator.flush().unwrap().close);
+        assert!(aggregator.current().is_none());
+
+        // an earlier trade of the bar arriving later becomes the open
+        aggregator.on_trade(147, 20.0, BigDecimal::from(1));
+        aggregator.on_trade(146, 19.0, BigDecimal::from(1));
+        let bar = aggregator.current().unwrap();
+        assert_eq!((19.0, 20.0), (bar.open, bar.close));
+    }
+
+    #[test]
+    fn test_tick_bar_aggregator_fill_gaps() {
+        let mut aggregator = TickBarAggregator::new(60, true);
+        aggregator.on_trade(60, 10.0, BigDecimal::from(1));
+        let bars = aggregator.on_trade(245, 11.0, BigDecimal::from(1));
+        let dates: Vec<Option<i64>> = bars
+            .iter()
+            .map(|bar| timestamp_seconds(&bar.date))
+            .collect();
+        assert_eq!(vec![Some(60), Some(120), Some(180)], dates);
+        assert_eq!(10.0, bars[2].open);
+        assert_eq!(BigDecimal::from(0), bars[2].volume);
+        assert_eq!(
+            timestamp_from_seconds(240),
+            aggregator.current().unwrap().date
+        );
+    }
+
+    #[test]
+    fn test_real_time_bar_resampler() -> Result<(), IBKRApiLibError> {
+        let bar = |time: i64, price: f64, volume: i64| {
+            RealTimeBar::new(
+                time.to_string(),
+                price,
+                price + 1.0,
+                price - 1.0,
+                price,
+                BigDecimal::from(volume),
+                price,
+                2,
+            )
+        };
+        let mut resampler = RealTimeBarResampler::new(15);
+        // starts mid-interval: the first bar covers 10..15 only
+        let bars = resampler.on_bar(&bar(10, 10.0, 100))?;
+        assert_eq!(1, bars.len());
+        assert_eq!("0", bars[0].date_time);
+
+        assert!(resampler.on_bar(&bar(15, 11.0, 100))?.is_empty());
+        assert!(resampler.on_bar(&bar(20, 13.0, 300))?.is_empty());
+        let bars = resampler.on_bar(&bar(25, 12.0, 100))?;
+        assert_eq!(1, bars.len());
+        assert_eq!("15", bars[0].date_time);
+        assert_eq!(11.0, bars[0].open);
+        assert_eq!(14.0, bars[0].high);
+        assert_eq!(10.0, bars[0].low);
+        assert_eq!(12.0, bars[0].close);
+        assert_eq!(BigDecimal::from(500), bars[0].volume);
+        assert_eq!(6, bars[0].count);
+        assert_eq!((1100.0 + 3900.0 + 1200.0) / 500.0, bars[0].wap);
+        // the 15 interval is already emitted, its bars are not emitted again
+        assert!(resampler.on_bar(&bar(20, 13.0, 1))?.is_empty());
+        assert!(resampler.current().is_none());
+        assert_eq!(1, resampler.late_bars());
+
+        // gap: 35 never arrives, the 45 bar closes the 30 interval
+        assert!(resampler.on_bar(&bar(30, 12.0, 1))?.is_empty());
+        let bars = resampler.on_bar(&bar(45, 12.0, 1))?;
+        assert_eq!(
+            vec!["30"],
+            bars.iter()
+                .map(|b| b.date_time.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!("45", resampler.current().unwrap().date_time);
+
+        assert!(resampler.on_bar(&bar(40, 12.0, 1))?.is_empty());
+        assert_eq!(2, resampler.late_bars());
+        assert!(resampler.on_bar(&RealTimeBar::default()).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use crate::core::capture::{
+        decode_capture, read_capture, read_raw_capture, CapturedFrame, Direction, ReplaySpeed,
+        WireCapture,
+    };
+    use crate::core::client::EClient;
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::messages::OutgoingMessageIds;
+    use crate::core::server_versions::MAX_CLIENT_VER;
+    use crate::core::testing::MockTws;
+    use crate::examples::defaults::DefaultWrapper;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn test_replay_speed_delay() {
+        assert_eq!(
+            Duration::from_millis(10),
+            ReplaySpeed::Original.delay(10_000)
+        );
+        assert_eq!(
+            Duration::from_millis(1),
+            ReplaySpeed::Accelerated(10.0).delay(10_000)
+        );
+        assert_eq!(Duration::from_secs(0), ReplaySpeed::Unpaced.delay(10_000));
+        assert_eq!(Duration::from_secs(0), ReplaySpeed::Original.delay(-5));
+    }
+
+    #[test]
+    fn test_capture_and_replay() -> Result<(), IBKRApiLibError> {
+        let path = std::env::temp_dir().join(format!("twsapi_capture_{}.cap", std::process::id()));
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_to(
+            OutgoingMessageIds::StartApi,
+            vec![vec![
+                "4",
+                "2",
+                "-1",
+                "2104",
+                "Market data farm connection is OK",
+            ]],
+        );
+
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.set_capture(Some(WireCapture::create(&path)?));
+        let messages = client.subscribe_messages();
+        client.connect("127.0.0.1", mock.port(), 3)?;
+        assert_eq!(2104, messages.recv_timeout(TIMEOUT)?.code);
+        client.disconnect()?;
+
+        let frames = read_capture(&path)?;
+        fs::remove_file(&path)?;
+        let outbound: Vec<String> = frames
+            .iter()
+            .filter(|frame| frame.direction == Direction::Outbound)
+            .map(|frame| frame.text())
+            .collect();
+        assert!(outbound[0].starts_with("v100.."));
+        assert!(outbound[1].starts_with("71\u{0}2\u{0}3\u{0}"));
+        let inbound = frames
+            .iter()
+            .filter(|frame| frame.direction == Direction::Inbound)
+            .count();
+        assert_eq!(2, inbound);
+
+        let mut replayed = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        let messages = replayed.subscribe_messages();
+        replayed.connect_replay(frames, ReplaySpeed::Unpaced)?;
+        assert_eq!(MAX_CLIENT_VER, replayed.server_version());
+        let message = messages.recv_timeout(TIMEOUT)?;
+        assert_eq!((-1, 2104), (message.req_id, message.code));
+        Ok(())
+    }
+
+    #[test]
+    fn test_shutdown_joins_replay() -> Result<(), IBKRApiLibError> {
+        let frame = |time_micros: i64, fields: &[&str]| {
+            let text: String = fields.iter().map(|field| format!("{}\0", field)).collect();
+            CapturedFrame::new(Direction::Inbound, time_micros, text.into_bytes())
+        };
+        let frames = vec![
+            frame(0, &["176", "20240102 10:00:00 UTC"]),
+            frame(
+                200_000,
+                &["4", "2", "-1", "2104", "Market data farm connection is OK"],
+            ),
+        ];
+        let wrapper = Arc::new(Mutex::new(DefaultWrapper::new()));
+        let mut replayed = EClient::new(wrapper.clone());
+        replayed.connect_replay(frames, ReplaySpeed::Original)?;
+        replayed.shutdown()?;
+        // the feeder and decoder threads finished and dropped their wrapper
+        assert_eq!(2, Arc::strong_count(&wrapper));
+        Ok(())
+    }
+
+    #[test]
+    fn test_raw_capture() -> Result<(), IBKRApiLibError> {
+        let path =
+            std::env::temp_dir().join(format!("twsapi_raw_capture_{}.cap", std::process::id()));
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_to(
+            OutgoingMessageIds::StartApi,
+            vec![vec![
+                "4",
+                "2",
+                "-1",
+                "2104",
+                "Market data farm connection is OK",
+            ]],
+        );
+
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.set_capture(Some(WireCapture::create_raw(&path)?));
+        let messages = client.subscribe_messages();
+        client.connect("127.0.0.1", mock.port(), 3)?;
+        assert_eq!(2104, messages.recv_timeout(TIMEOUT)?.code);
+        client.disconnect()?;
+
+        let chunks = read_raw_capture(&path)?;
+        assert!(chunks[0].bytes.starts_with(b"API\0"));
+        assert!(chunks
+            .windows(2)
+            .all(|pair| pair[0].time_nanos <= pair[1].time_nanos));
+
+        let frames = read_capture(&path)?;
+        assert!(frames[0].text().starts_with("v100.."));
+        assert_eq!(
+            2,
+            frames
+                .iter()
+                .filter(|frame| frame.direction == Direction::Inbound)
+                .count()
+        );
+        let decoded = decode_capture(&path, Arc::new(Mutex::new(DefaultWrapper::new())))?;
+        fs::remove_file(&path)?;
+        assert_eq!(1, decoded);
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests {
+
+    use bigdecimal::BigDecimal;
+    use num_traits::FromPrimitive;
+
+    use crate::core::combo_fills::ComboFillAggregator;
+    use crate::core::common::SecType;
+    use crate::core::contract::{ComboLeg, Contract};
+    use crate::core::execution::Execution;
+    use crate::core::order::Action;
+    use crate::examples::order_samples::limit_order;
+
+    fn contract(con_id: i32, sec_type: &str) -> Contract {
+        Contract {
+            con_id,
+            sec_type: SecType::from(sec_type),
+            ..Default::default()
+        }
+    }
+
+    fn execution(exec_id: &str, order_id: i32, perm_id: i32, shares: f64, price: f64) -> Execution {
+        Execution {
+            exec_id: exec_id.to_string(),
+            order_id,
+            perm_id,
+            shares: BigDecimal::from_f64(shares).unwrap(),
+            price,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_combo_fill_attribution() {
+        let mut spread = contract(0, "BAG");
+        spread.combo_legs = vec![
+            ComboLeg {
+                con_id: 1,
+                ratio: 1.0,
+                action: Action::Buy,
+                ..Default::default()
+            },
+            ComboLeg {
+                con_id: 2,
+                ratio: 2.0,
+                action: Action::Sell,
+                ..Default::default()
+            },
+        ];
+        let order = limit_order("BUY", 2.0, 1.5);
+
+        let mut fills = ComboFillAggregator::new();
+        fills.register(10, &spread, &order);
+
+        fills.on_execution(&contract(1, "OPT"), &execution("e1", 10, 77, 2.0, 5.0));
+        assert_eq!(None, fills.get(10).unwrap().net_price());
+        // matched by perm_id from now on
+        fills.on_execution(&contract(2, "OPT"), &execution("e2", 0, 77, 2.0, 1.8));
+        let combo = fills.get(10).unwrap();
+        assert_eq!(77, combo.perm_id);
+        assert_eq!(1.0, combo.filled_quantity());
+        assert!(!combo.is_complete());
+
+        fills.on_execution(&contract(2, "OPT"), &execution("e3", 10, 77, 2.0, 1.6));
+        // a repeated execution is counted once
+        fills.on_execution(&contract(2, "OPT"), &execution("e3", 10, 77, 2.0, 1.6));
+        fills.on_execution(&contract(0, "BAG"), &execution("e4", 10, 77, 2.0, 1.6));
+        assert!(fills
+            .on_execution(&contract(1, "OPT"), &execution("e5", 11, 78, 1.0, 5.0))
+            .is_none());
+
+        let combo = fills.get(10).unwrap();
+        assert!(combo.is_complete());
+        assert_eq!(2.0, combo.legs[0].shares);
+        assert_eq!(4.0, combo.legs[1].shares);
+        assert!((combo.legs[1].avg_price - 1.7).abs() < 1e-9);
+        assert!((combo.net_price().unwrap() - 1.6).abs() < 1e-9);
+        assert!((combo.price_improvement().unwrap() + 0.1).abs() < 1e-9);
+        assert_eq!(Some(1.6), combo.reported_price);
+    }
+}
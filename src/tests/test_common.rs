@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::core::account_summary_tags::{AccountSummaryTag, AccountSummaryTagSet};
+    use crate::core::common::{
+        BarSize, Duration, NewsArticleContent, SecType, TickType, WhatToShow, NEWS_ARTICLE_BINARY,
+        NEWS_ARTICLE_TEXT,
+    };
+    use crate::core::errors::{is_warning_code, IBKRApiLibError};
+
+    #[test]
+    fn test_bar_size_and_duration_display() {
+        assert_eq!("1 secs", BarSize::_1Secs.to_string());
+        assert_eq!("1 min", BarSize::_1Min.to_string());
+        assert_eq!("2 mins", BarSize::_2Mins.to_string());
+        assert_eq!("8 hours", BarSize::_8Hours.to_string());
+        assert_eq!("1 month", BarSize::_1Month.to_string());
+
+        assert_eq!("1800 S", Duration::Seconds(1800).to_string());
+        assert_eq!("10 D", Duration::Days(10).to_string());
+        assert_eq!("2 W", Duration::Weeks(2).to_string());
+        assert_eq!("6 M", Duration::Months(6).to_string());
+        assert_eq!("1 Y", Duration::Years(1).to_string());
+    }
+
+    #[test]
+    fn test_what_to_show_display() {
+        assert_eq!("AGGTRADES", WhatToShow::AggTrades.to_string());
+        assert_eq!("FEE_RATE", WhatToShow::FeeRate.to_string());
+        assert_eq!("SCHEDULE", WhatToShow::Schedule.to_string());
+        assert_eq!("YIELD_BID", WhatToShow::YieldBid.to_string());
+    }
+
+    #[test]
+    fn test_what_to_show_is_valid_for() {
+        assert!(WhatToShow::AggTrades.is_valid_for(&SecType::CRYPTO));
+        assert!(!WhatToShow::Trades.is_valid_for(&SecType::CRYPTO));
+        assert!(WhatToShow::YieldLast.is_valid_for(&SecType::BOND));
+        assert!(!WhatToShow::YieldLast.is_valid_for(&SecType::STK));
+        assert!(WhatToShow::FeeRate.is_valid_for(&SecType::STK));
+        assert!(!WhatToShow::Midpoint.is_valid_for(&SecType::IND));
+    }
+
+    #[test]
+    fn test_sec_type_codes() {
+        assert_eq!(SecType::FOP, SecType::from("FOP"));
+        assert_eq!(SecType::None, SecType::from(""));
+        assert_eq!(SecType::FUTCONTFUT, SecType::from("FUT+CONTFUT"));
+        assert_eq!(
+            SecType::Other("FUT+IND".to_string()),
+            SecType::from("FUT+IND")
+        );
+        assert_eq!(None, SecType::from_code("FUT+IND"));
+        assert_eq!("CRYPTO", SecType::CRYPTO.to_string());
+        assert_eq!("FUT+CONTFUT", SecType::FUTCONTFUT.code());
+        assert!(SecType::CONTFUT.is_continuous() && SecType::FUTCONTFUT.is_continuous());
+        assert!(!SecType::FUT.is_continuous());
+        assert_eq!(String::from("BAG"), String::from(SecType::BAG));
+    }
+
+    #[test]
+    fn test_tick_type_from_code() {
+        assert_eq!(TickType::Bid, TickType::from_code(1));
+        assert_eq!(TickType::DelayedYieldAsk, TickType::from_code(104));
+        assert_eq!(TickType::NotSet, TickType::from_code(9999));
+        assert_eq!(66, TickType::DelayedBid.code());
+        assert_eq!("bidPrice", TickType::Bid.to_string());
+    }
+
+    #[test]
+    fn test_is_warning_code() {
+        assert!(is_warning_code(2104));
+        assert!(is_warning_code(2158));
+        assert!(!is_warning_code(200));
+        assert!(!is_warning_code(1100));
+    }
+
+    #[test]
+    fn test_news_article_content() -> Result<(), IBKRApiLibError> {
+        assert_eq!(
+            NewsArticleContent::Text("Shares rise".to_string()),
+            NewsArticleContent::from_article(NEWS_ARTICLE_TEXT, "Shares rise")?
+        );
+        let pdf = NewsArticleContent::from_article(NEWS_ARTICLE_BINARY, "JVBERi0x\nLjQK")?;
+        assert_eq!(NewsArticleContent::Pdf(b"%PDF-1.4\n".to_vec()), pdf);
+        assert_eq!(
+            NewsArticleContent::Pdf(b"ab".to_vec()),
+            NewsArticleContent::from_article(NEWS_ARTICLE_BINARY, "YWI=")?
+        );
+        assert!(NewsArticleContent::from_article(NEWS_ARTICLE_BINARY, "YW*=").is_err());
+
+        let path = std::env::temp_dir().join(format!("twsapi_news_{}.pdf", std::process::id()));
+        pdf.save_to(&path)?;
+        assert_eq!(b"%PDF-1.4\n".to_vec(), std::fs::read(&path)?);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_account_summary_tags() {
+        for tag in AccountSummaryTag::ALL.iter() {
+            assert_eq!(*tag, AccountSummaryTag::from_tag(&tag.as_str()));
+        }
+        assert_eq!(
+            AccountSummaryTag::RegTEquity,
+            AccountSummaryTag::from_tag("RegTEquity")
+        );
+        assert_eq!(
+            AccountSummaryTag::FuturesPnl,
+            AccountSummaryTag::from_tag("FuturesPNL")
+        );
+        assert_eq!(
+            AccountSummaryTag::LedgerCurrency("EUR".to_string()),
+            AccountSummaryTag::from_tag("$LEDGER:EUR")
+        );
+        assert_eq!(
+            "$LEDGER:EUR",
+            AccountSummaryTag::LedgerCurrency("EUR".to_string()).as_str()
+        );
+        assert_eq!(
+            AccountSummaryTag::Other("NetLiqudation".to_string()),
+            AccountSummaryTag::from_tag("NetLiqudation")
+        );
+
+        let all = AccountSummaryTagSet::all().to_string();
+        assert!(all.starts_with("AccountType,NetLiquidation,"));
+        assert_eq!(AccountSummaryTag::ALL.len(), all.split(',').count());
+    }
+}
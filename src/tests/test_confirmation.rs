@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+
+    use std::time::{Duration, Instant};
+
+    use crate::core::confirmation::{ConfirmationEvent, ConfirmationMonitor};
+
+    #[test]
+    fn test_confirmation_monitor_timeout_and_cancel() {
+        let start = Instant::now();
+        let mut monitor =
+            ConfirmationMonitor::new(Duration::from_secs(5), Some(Duration::from_secs(30)));
+        monitor.on_submitted(1, start);
+        monitor.on_submitted(2, start);
+        monitor.on_order_status(2);
+
+        assert!(monitor.poll(start + Duration::from_secs(1)).is_empty());
+        assert_eq!(
+            vec![ConfirmationEvent::PendingManualConfirmation {
+                order_id: 1,
+                waited: Duration::from_secs(6)
+            }],
+            monitor.poll(start + Duration::from_secs(6))
+        );
+        assert!(monitor.poll(start + Duration::from_secs(7)).is_empty());
+        assert_eq!(
+            vec![ConfirmationEvent::AutoCancel {
+                order_id: 1,
+                waited: Duration::from_secs(30)
+            }],
+            monitor.poll(start + Duration::from_secs(30))
+        );
+        assert!(monitor.watched_orders().is_empty());
+    }
+
+    #[test]
+    fn test_confirmation_monitor_warning_code() {
+        let start = Instant::now();
+        let mut monitor = ConfirmationMonitor::new(Duration::from_secs(5), None);
+        monitor.on_submitted(3, start);
+
+        assert!(monitor.on_message(3, 2104, start).is_none());
+        assert!(monitor.on_message(4, 399, start).is_none());
+        assert!(monitor.on_message(3, 399, start).is_some());
+        assert!(monitor.on_message(3, 399, start).is_none());
+        // already reported and never cancelled
+        assert!(monitor.poll(start + Duration::from_secs(60)).is_empty());
+        assert_eq!(vec![3], monitor.watched_orders());
+    }
+}
@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+
+    use chrono::NaiveDate;
+
+    use crate::core::common::{Right, SecType};
+    use crate::core::contract::{front_month, Contract, ContractDetails};
+    use crate::core::currency::Currency;
+    use crate::core::exchange::Exchange;
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_contract_constructors() {
+        let stock = Contract::stock("AAPL");
+        assert_eq!(
+            ("AAPL", SecType::STK, Exchange::Smart, Currency::usd()),
+            (
+                stock.symbol.as_str(),
+                stock.sec_type,
+                stock.exchange,
+                stock.currency
+            )
+        );
+
+        let future = Contract::future("ES", "202506", "cme");
+        assert_eq!(SecType::FUT, future.sec_type);
+        assert_eq!("202506", future.last_trade_date_or_contract_month);
+        assert_eq!(Exchange::Cme, future.exchange);
+
+        let option = Contract::option("SPY", "20250620", 500.0, Right::Call);
+        assert_eq!(SecType::OPT, option.sec_type);
+        assert_eq!(
+            ("C", "100"),
+            (option.right.as_str(), option.multiplier.as_str())
+        );
+        assert_eq!(500.0, option.strike);
+
+        let crypto = Contract::crypto("BTC");
+        assert_eq!(
+            (SecType::CRYPTO, Exchange::Paxos),
+            (crypto.sec_type, crypto.exchange)
+        );
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_forex_pairs() {
+        for pair in &["EURUSD", "EUR.USD", "eur/usd"] {
+            let contract = Contract::forex(pair);
+            assert_eq!("EUR", contract.symbol);
+            assert_eq!(Currency::usd(), contract.currency);
+            assert_eq!(SecType::CASH, contract.sec_type);
+            assert_eq!(Exchange::IdealPro, contract.exchange);
+        }
+        let odd = Contract::forex("EURO");
+        assert_eq!("EURO", odd.symbol);
+        assert!(!odd.currency.is_set());
+    }
+
+    //------------------------------------------------------------------------------------------------
+    fn future_details(sec_type: SecType, con_id: i32, last_trade_date: &str) -> ContractDetails {
+        ContractDetails {
+            contract: Contract {
+                con_id,
+                sec_type,
+                last_trade_date_or_contract_month: last_trade_date.to_string(),
+                multiplier: "50".to_string(),
+                ..Contract::future("ES", "", "CME")
+            },
+            ..Default::default()
+        }
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_front_month() {
+        let today = NaiveDate::from_ymd_opt(2025, 6, 16).unwrap();
+        let futures = vec![
+            future_details(SecType::FUT, 3, "20251219"),
+            future_details(SecType::FUT, 1, "20250321"),
+            future_details(SecType::FUT, 2, "20250620 08:30 US/Central"),
+        ];
+        let front = front_month(&futures, today).unwrap();
+        assert_eq!((2, SecType::FUT), (front.con_id, front.sec_type));
+        assert_eq!("50", front.multiplier);
+
+        // IB rolls before expiry, its continuous future names the month after
+        let mut with_continuous = futures.clone();
+        with_continuous.push(future_details(SecType::CONTFUT, 3, "20251219"));
+        let front = front_month(&with_continuous, today).unwrap();
+        assert_eq!((3, SecType::FUT), (front.con_id, front.sec_type));
+
+        let later = NaiveDate::from_ymd_opt(2026, 1, 2).unwrap();
+        assert!(front_month(&with_continuous, later).is_none());
+        assert!(front_month(&[], today).is_none());
+
+        let continuous = Contract::continuous_future("ES", "CME");
+        assert!(continuous.sec_type.is_continuous());
+        assert_eq!(Exchange::Cme, continuous.exchange);
+    }
+}
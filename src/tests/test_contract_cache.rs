@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+
+    use std::time::{Duration, Instant};
+
+    use crate::core::contract::ContractDetails;
+    use crate::core::contract_cache::{ContractCache, ContractField};
+
+    fn details(min_tick: f64, trading_hours: &str) -> ContractDetails {
+        let mut details = ContractDetails {
+            min_tick,
+            trading_hours: trading_hours.to_string(),
+            ..Default::default()
+        };
+        details.contract.con_id = 265598;
+        details.contract.symbol = "AAPL".to_string();
+        details.contract.multiplier = "1".to_string();
+        details
+    }
+
+    #[test]
+    fn test_contract_cache_refresh() {
+        let start = Instant::now();
+        let mut cache = ContractCache::new(Duration::from_secs(60));
+        let changes = cache.subscribe();
+
+        assert_eq!(
+            None,
+            cache.update(details(0.01, "20200101:0930-1600"), start)
+        );
+        assert!(cache.is_fresh(265598, start));
+        assert!(cache.stale(start).is_empty());
+        assert_eq!(vec![265598], cache.stale(start + Duration::from_secs(60)));
+
+        let later = start + Duration::from_secs(90);
+        assert_eq!(
+            None,
+            cache.update(details(0.01, "20200101:0930-1600"), later)
+        );
+        let changed = cache
+            .update(details(0.005, "20200101:0930-1300"), later)
+            .unwrap();
+        assert_eq!(2, changed.changes.len());
+        let min_tick = changed.change(ContractField::MinTick).unwrap();
+        assert_eq!(
+            ("0.01", "0.005"),
+            (min_tick.old.as_str(), min_tick.new.as_str())
+        );
+        assert!(changed.change(ContractField::TradingHours).is_some());
+        assert!(changed.change(ContractField::Multiplier).is_none());
+
+        assert_eq!(changed, changes.try_recv().unwrap());
+        assert!(changes.try_recv().is_err());
+        assert_eq!(0.005, cache.get(265598).unwrap().min_tick);
+
+        // the hours moving on by a day in the other format are the same sessions
+        assert_eq!(
+            None,
+            cache.update(
+                details(
+                    0.005,
+                    "20200101:0930-20200101:1300;20200102:0930-20200102:1600"
+                ),
+                later
+            )
+        );
+        let changed = cache
+            .update(details(0.005, "20200102:CLOSED;20200103:0930-1600"), later)
+            .unwrap();
+        assert!(changed.change(ContractField::TradingHours).is_some());
+    }
+}
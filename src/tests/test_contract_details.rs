@@ -0,0 +1,332 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::mpsc::RecvTimeoutError;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crate::core::client::EClient;
+    use crate::core::common::{Right, SecType, UNSET_DOUBLE};
+    use crate::core::contract::Contract;
+    use crate::core::currency::Currency;
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::exchange::Exchange;
+    use crate::core::exercise::{ExerciseAction, OptionExercise};
+    use crate::core::messages::OutgoingMessageIds;
+    use crate::core::order::{Action, OrderType};
+    use crate::core::order_templates::PeggedToBenchmark;
+    use crate::core::order_tracker::OrderPhase;
+    use crate::core::server_versions::MAX_CLIENT_VER;
+    use crate::core::testing::MockTws;
+    use crate::examples::defaults::DefaultWrapper;
+    use crate::examples::order_samples::{limit_order, market_order};
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn contract_data(req_id: &str, symbol: &str, con_id: &str) -> Vec<String> {
+        let mut fields = vec!["10", req_id, symbol, "STK", "", "0", "", "SMART", "USD"];
+        fields.extend_from_slice(&[symbol, "NMS", symbol, con_id, "0.01", "", "LMT", "SMART"]);
+        fields.extend_from_slice(&["1", "0", "", "NASDAQ", "", "", "", "", "US/Eastern"]);
+        fields.extend_from_slice(&["", "", "", "0", "0", "1", "", "", "26", "", "COMMON"]);
+        fields.extend_from_slice(&["1", "1", "1"]);
+        fields.iter().map(|field| field.to_string()).collect()
+    }
+
+    fn stock(symbol: &str) -> Contract {
+        Contract {
+            symbol: symbol.to_string(),
+            sec_type: SecType::STK,
+            exchange: Exchange::Smart,
+            currency: Currency::usd(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_contract_details_bulk() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_with(
+            OutgoingMessageIds::ReqContractData,
+            Box::new(|request| {
+                let req_id = request[2].as_str();
+                let end = vec!["52".to_string(), "1".to_string(), req_id.to_string()];
+                match request[4].as_str() {
+                    "NOPE" => vec![vec![
+                        "4".to_string(),
+                        "2".to_string(),
+                        req_id.to_string(),
+                        "200".to_string(),
+                        "No security definition has been found for the request".to_string(),
+                    ]],
+                    "TWIN" => vec![
+                        contract_data(req_id, "TWIN", "1"),
+                        contract_data(req_id, "TWIN", "2"),
+                        end,
+                    ],
+                    symbol => vec![contract_data(req_id, symbol, "265598"), end],
+                }
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let contracts = vec![stock("AAPL"), stock("NOPE"), stock("TWIN"), stock("MSFT")];
+        let results = client.contract_details_bulk(100, &contracts, 2, TIMEOUT)?;
+        assert_eq!(4, results.len());
+        match &results[0] {
+            Ok(details) => assert_eq!(
+                ("AAPL", 265598),
+                (
+                    details[0].contract.symbol.as_str(),
+                    details[0].contract.con_id
+                )
+            ),
+            Err(err) => panic!("AAPL failed: {}", err),
+        }
+        match &results[1] {
+            Err(IBKRApiLibError::ApiError(err)) => assert_eq!("200", err.code),
+            other => panic!("expected error 200, got {:?}", other.is_ok()),
+        }
+        assert_eq!(
+            2,
+            results[2]
+                .as_ref()
+                .map(|details| details.len())
+                .unwrap_or(0)
+        );
+        assert!(results[3].is_ok());
+
+        let req_ids: Vec<String> = mock
+            .received()
+            .iter()
+            .filter(|fields| fields[0] == "9")
+            .map(|fields| fields[2].clone())
+            .collect();
+        assert_eq!(vec!["100", "101", "102", "103"], req_ids);
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_contract_details_bulk_times_out_each_request() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        // the others keep answering while the silent request runs out of time
+        mock.respond_with(
+            OutgoingMessageIds::ReqContractData,
+            Box::new(|request| {
+                let req_id = request[2].as_str();
+                match request[4].as_str() {
+                    "SILENT" => vec![],
+                    symbol => {
+                        thread::sleep(Duration::from_millis(100));
+                        let end = vec!["52".to_string(), "1".to_string(), req_id.to_string()];
+                        vec![contract_data(req_id, symbol, "265598"), end]
+                    }
+                }
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let mut contracts = vec![stock("SILENT")];
+        contracts.extend((0..10).map(|n| stock(&format!("S{}", n))));
+        let started = Instant::now();
+        let results =
+            client.contract_details_bulk(400, &contracts, 2, Duration::from_millis(600))?;
+        // the silent request failed while the others went on, not after they were done
+        assert!(started.elapsed() < Duration::from_millis(1600));
+        match &results[0] {
+            Err(IBKRApiLibError::RecvTimeoutError(RecvTimeoutError::Timeout)) => {}
+            other => panic!("expected a timeout, got {:?}", other.is_ok()),
+        }
+        assert!(results[1..].iter().all(|result| result.is_ok()));
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_order_type_checked_against_details() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_with(
+            OutgoingMessageIds::ReqContractData,
+            Box::new(|request| {
+                let req_id = request[2].as_str();
+                let end = vec!["52".to_string(), "1".to_string(), req_id.to_string()];
+                vec![contract_data(req_id, "AAPL", "265598"), end]
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let results = client.contract_details_bulk(200, &[stock("AAPL")], 1, TIMEOUT)?;
+        let details = results[0].as_ref().map_err(|err| err.to_string()).unwrap();
+        assert!(details[0].supports_order_type(&OrderType::Lmt));
+        assert!(!details[0].supports_order_type(&OrderType::StpLmt));
+
+        let contract = details[0].contract.clone();
+        match client.place_order(1, &contract, &market_order("BUY", 1.0)) {
+            Err(IBKRApiLibError::ApiError(err)) => assert!(err.description.contains("MKT")),
+            other => panic!("expected an api error, got {:?}", other.is_ok()),
+        }
+        client.place_order(2, &contract, &limit_order("BUY", 1.0, 100.0))?;
+        let request = mock.expect_message(OutgoingMessageIds::PlaceOrder, TIMEOUT)?;
+        assert!(request.contains(&"LMT".to_string()));
+
+        // the details of one exchange say nothing about another
+        let on_island = Contract {
+            exchange: Exchange::Island,
+            ..contract.clone()
+        };
+        client.place_order(3, &on_island, &market_order("BUY", 1.0))?;
+        mock.expect_message(OutgoingMessageIds::PlaceOrder, TIMEOUT)?;
+
+        // nor are they kept across connections
+        client.disconnect()?;
+        client.connect("127.0.0.1", mock.port(), 0)?;
+        client.place_order(4, &contract, &market_order("BUY", 1.0))?;
+        mock.expect_message(OutgoingMessageIds::PlaceOrder, TIMEOUT)?;
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_pegged_to_benchmark_resolves_reference() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_with(
+            OutgoingMessageIds::ReqContractData,
+            Box::new(|request| {
+                let req_id = request[2].as_str();
+                let end = vec!["52".to_string(), "1".to_string(), req_id.to_string()];
+                match request[4].as_str() {
+                    "TWIN" => vec![
+                        contract_data(req_id, "TWIN", "1"),
+                        contract_data(req_id, "TWIN", "2"),
+                        end,
+                    ],
+                    symbol => vec![contract_data(req_id, symbol, "265598"), end],
+                }
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let builder = PeggedToBenchmark::new(Action::Buy, 100.0, 33.0, stock("AAPL"))
+            .peg(0.2, 1.0, false)
+            .reference_price(150.0)
+            .reference_range(140.0, 160.0);
+        let order = builder.resolve(&mut client, 300, TIMEOUT)?;
+        assert_eq!(OrderType::PegBench, order.order_type);
+        assert_eq!(
+            (265598, "NASDAQ"),
+            (
+                order.reference_contract_id,
+                order.reference_exchange_id.as_str()
+            )
+        );
+        assert_eq!(
+            (140.0, 160.0),
+            (order.stock_range_lower, order.stock_range_upper)
+        );
+
+        let ambiguous = PeggedToBenchmark::new(Action::Buy, 100.0, 33.0, stock("TWIN"));
+        assert!(ambiguous
+            .peg(0.2, 1.0, false)
+            .resolve(&mut client, 301, TIMEOUT)
+            .is_err());
+
+        let mut without_peg = order.clone();
+        without_peg.pegged_change_amount = UNSET_DOUBLE;
+        assert!(client.place_order(1, &stock("IBM"), &without_peg).is_err());
+        client.place_order(2, &stock("IBM"), &order)?;
+        let request = mock.expect_message(OutgoingMessageIds::PlaceOrder, TIMEOUT)?;
+        let peg = ["265598", "0", "0.2", "1", "NASDAQ"].map(|field| field.to_string());
+        assert!(request.windows(5).any(|fields| fields == peg));
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_order_type_codes() {
+        assert_eq!(OrderType::StpLmt, OrderType::from("STP LMT"));
+        assert_eq!(OrderType::TrailLimit, OrderType::from("trail limit"));
+        assert_eq!(Some("TRAILLMT"), OrderType::TrailLimit.capability());
+        assert_eq!("PEG MID", OrderType::PegMid.to_string());
+        assert_eq!(
+            OrderType::Other("ALGO X".to_string()),
+            OrderType::from("ALGO X")
+        );
+        // types whose listing code is not known, and details without order types, pass
+        assert!(OrderType::BoxTop.is_supported_by("LMT,MKT"));
+        assert!(OrderType::Moc.is_supported_by(""));
+        assert!(!OrderType::Moc.is_supported_by("LMT,MKT"));
+    }
+
+    #[test]
+    fn test_exercise() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_with(
+            OutgoingMessageIds::ReqContractData,
+            Box::new(|request| {
+                let req_id = request[2].as_str();
+                let end = vec!["52".to_string(), "1".to_string(), req_id.to_string()];
+                let mut option = contract_data(req_id, "SPY", "712345");
+                option[3..7].clone_from_slice(&[
+                    "OPT".to_string(),
+                    "20250620".to_string(),
+                    "450".to_string(),
+                    "C".to_string(),
+                ]);
+                match request[7].as_str() {
+                    "0" => vec![option.clone(), option, end],
+                    _ => vec![option, end],
+                }
+            }),
+        );
+        mock.respond_with(
+            OutgoingMessageIds::ExerciseOptions,
+            Box::new(|request| {
+                let status = |status: &str, filled: &str, remaining: &str| {
+                    let fields = ["3", &request[2], status, filled, remaining, "0", "0", "0"];
+                    fields
+                        .iter()
+                        .chain(["0", "0", "", "0"].iter())
+                        .map(|field| field.to_string())
+                        .collect::<Vec<String>>()
+                };
+                vec![status("PreSubmitted", "0", "3"), status("Filled", "3", "0")]
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let call = Contract::option("SPY", "20250620", 450.0, Right::Call);
+        let stock = OptionExercise::new(stock("SPY"), ExerciseAction::Exercise, 3, "DU1");
+        assert!(client.exercise(20, &stock, TIMEOUT).is_err());
+        let none = OptionExercise::new(call.clone(), ExerciseAction::Lapse, 0, "DU1");
+        assert!(client.exercise(20, &none, TIMEOUT).is_err());
+        // every strike of the expiry matches without one
+        let ambiguous = Contract {
+            strike: 0.0,
+            ..call.clone()
+        };
+        let ambiguous = OptionExercise::new(ambiguous, ExerciseAction::Exercise, 3, "DU1");
+        assert!(client.exercise(21, &ambiguous, TIMEOUT).is_err());
+
+        let exercise =
+            OptionExercise::new(call, ExerciseAction::Exercise, 3, "DU1").override_natural(true);
+        let statuses = client.exercise(22, &exercise, TIMEOUT)?;
+        let submitted = statuses.recv_timeout(TIMEOUT)?;
+        assert_eq!(
+            (22, Some(OrderPhase::PreSubmitted)),
+            (submitted.req_id, submitted.phase)
+        );
+        let filled = statuses.recv_timeout(TIMEOUT)?;
+        assert!(filled.is_terminal());
+        assert_eq!(3.0, filled.filled);
+
+        let request = mock.expect_message(OutgoingMessageIds::ExerciseOptions, TIMEOUT)?;
+        assert_eq!("712345", request[3]);
+        assert_eq!(
+            vec!["1", "3", "DU1", "1"],
+            request[request.len() - 4..].to_vec()
+        );
+        client.disconnect()
+    }
+}
@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::atomic::Ordering;
+    use std::sync::{Arc, Mutex};
+
+    use crate::core::client::ConnStatus;
+    use crate::core::coverage::FieldCoverage;
+    use crate::core::decoder::Decoder;
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::message_queue::{message_queue, QueueConfig};
+    use crate::examples::defaults::DefaultWrapper;
+
+    fn fields(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_field_coverage_record() {
+        let mut coverage = FieldCoverage::new();
+        coverage.record(&fields(&["49", "1", "1600000000"]), 0);
+        coverage.record(&fields(&["49", "1", "1600000000", "a", "b"]), 2);
+        coverage.record(&fields(&["4", "2", "-1", "2104", "ok"]), 0);
+
+        let report = coverage.report();
+        assert_eq!(1, report.len());
+        assert_eq!(49, report[0].message_id);
+        assert_eq!(2, report[0].messages);
+        assert_eq!(1, report[0].with_unparsed);
+        assert_eq!(fields(&["a", "b"]), report[0].last_unparsed);
+        assert_eq!(2, coverage.all().len());
+        assert!(coverage
+            .to_string()
+            .starts_with("message: CurrentTime (49)"));
+    }
+
+    #[test]
+    fn test_decoder_records_unparsed_fields() -> Result<(), IBKRApiLibError> {
+        let (_sender, receiver) = message_queue(QueueConfig::default());
+        let mut decoder = Decoder::new(
+            Arc::new(Mutex::new(DefaultWrapper::new())),
+            receiver,
+            151,
+            Arc::new(Mutex::new(ConnStatus::CONNECTED)),
+        );
+
+        decoder.interpret(&fields(&["49", "1", "1600000000", "new_field"]))?;
+        assert!(decoder
+            .pending
+            .field_coverage
+            .lock()
+            .unwrap()
+            .all()
+            .is_empty());
+
+        decoder
+            .pending
+            .lenient_decoding
+            .store(true, Ordering::Relaxed);
+        decoder.interpret(&fields(&["49", "1", "1600000000", "new_field"]))?;
+        let report = decoder.pending.field_coverage.lock().unwrap().report();
+        assert_eq!(fields(&["new_field"]), report[0].last_unparsed);
+        Ok(())
+    }
+}
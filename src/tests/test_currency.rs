@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::core::account_values::{AccountValueDeltas, ChangeThreshold};
+    use crate::core::currency::Currency;
+
+    #[test]
+    fn test_new_upper_cases() {
+        assert_eq!("USD", Currency::new(" usd ").code());
+        assert_eq!(Currency::eur(), Currency::from("Eur"));
+        assert_eq!("EUR.USD", Currency::eur().pair(&Currency::usd()));
+        assert_eq!("EUR.USD", Currency::usd().market_pair(&Currency::eur()));
+        assert_eq!("GBP.USD", Currency::usd().market_pair(&"GBP".into()));
+        assert_eq!(
+            "EUR.GBP",
+            Currency::from("GBP").market_pair(&Currency::eur())
+        );
+        assert_eq!(
+            "USD.JPY",
+            Currency::from("JPY").market_pair(&Currency::usd())
+        );
+        assert_eq!(
+            "USD.MXN",
+            Currency::from("MXN").market_pair(&Currency::usd())
+        );
+        assert_eq!("NOK.SEK", Currency::from("SEK").market_pair(&"NOK".into()));
+        assert_eq!("HKD.JPY", Currency::from("JPY").market_pair(&"HKD".into()));
+        assert!(!Currency::default().is_set());
+        assert!(Currency::from("base").is_base());
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Currency::usd(), Currency::parse("usd").unwrap());
+        assert_eq!("CNH", Currency::parse("CNH").unwrap().code());
+        assert!(Currency::parse("USDX").is_err());
+        assert!(Currency::parse("XYZ").is_err());
+        assert!(Currency::parse("BASE").is_err());
+        assert!("GBP".parse::<Currency>().is_ok());
+    }
+
+    #[test]
+    fn test_account_value_currency() {
+        let mut deltas = AccountValueDeltas::new(ChangeThreshold::default());
+        let change = deltas.update("CashBalance", "100", "BASE", "DU1").unwrap();
+        assert!(change.currency.is_base());
+        let change = deltas.update("CashBalance", "50", "usd", "DU1").unwrap();
+        assert_eq!(Currency::usd(), change.currency);
+    }
+}
@@ -0,0 +1,118 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use crate::core::client::EClient;
+    use crate::core::contract::Contract;
+    use crate::core::display_groups::{DisplayGroupId, DisplayGroups, GroupSelection};
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::messages::OutgoingMessageIds;
+    use crate::core::server_versions::MAX_CLIENT_VER;
+    use crate::core::testing::MockTws;
+    use crate::examples::defaults::DefaultWrapper;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn test_group_selection_codes() {
+        assert_eq!(
+            vec![DisplayGroupId(4), DisplayGroupId(1), DisplayGroupId(2)],
+            DisplayGroupId::parse_list("4|1|2")
+        );
+        assert!(DisplayGroupId::parse_list("").is_empty());
+
+        let ibm = GroupSelection::from_code("8314@SMART");
+        assert_eq!(
+            GroupSelection::Contract {
+                con_id: 8314,
+                exchange: "SMART".to_string()
+            },
+            ibm
+        );
+        assert_eq!("8314@SMART", ibm.code());
+        assert_eq!(
+            ibm,
+            GroupSelection::contract(&Contract {
+                con_id: 8314,
+                ..Contract::stock("IBM")
+            })
+        );
+        assert_eq!(GroupSelection::None, GroupSelection::from_code("none"));
+        assert_eq!(GroupSelection::Combo, GroupSelection::from_code("combo"));
+        assert_eq!(
+            GroupSelection::Other("IBM@SMART".to_string()),
+            GroupSelection::from_code("IBM@SMART")
+        );
+    }
+
+    #[test]
+    fn test_display_groups() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_with(
+            OutgoingMessageIds::QueryDisplayGroups,
+            Box::new(|request| {
+                vec![vec![
+                    "67".to_string(),
+                    "1".to_string(),
+                    request[2].clone(),
+                    "4|1|2".to_string(),
+                ]]
+            }),
+        );
+        mock.respond_with(
+            OutgoingMessageIds::SubscribeToGroupEvents,
+            Box::new(|request| {
+                vec![vec![
+                    "68".to_string(),
+                    "1".to_string(),
+                    request[2].clone(),
+                    "none".to_string(),
+                ]]
+            }),
+        );
+        mock.respond_with(
+            OutgoingMessageIds::UpdateDisplayGroup,
+            Box::new(|request| {
+                vec![vec![
+                    "68".to_string(),
+                    "1".to_string(),
+                    request[2].clone(),
+                    request[3].clone(),
+                ]]
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let groups = DisplayGroups::query(&mut client, 1, TIMEOUT)?;
+        assert_eq!(DisplayGroupId(4), groups[0]);
+
+        let mut display_groups = DisplayGroups::new();
+        let events = display_groups.subscribe(&mut client, 2, groups[0])?;
+        assert!(display_groups.subscribe(&mut client, 3, groups[0]).is_err());
+        let first = events.recv_timeout(TIMEOUT)?;
+        assert_eq!(
+            (2, DisplayGroupId(4), GroupSelection::None),
+            (first.req_id, first.group_id, first.selection)
+        );
+
+        let ibm = GroupSelection::Contract {
+            con_id: 8314,
+            exchange: "SMART".to_string(),
+        };
+        display_groups.select(&mut client, groups[0], &ibm)?;
+        assert_eq!(ibm, events.recv_timeout(TIMEOUT)?.selection);
+        assert!(display_groups
+            .select(&mut client, DisplayGroupId(1), &ibm)
+            .is_err());
+
+        display_groups.unsubscribe(&mut client, groups[0])?;
+        assert!(display_groups.subscribed().is_empty());
+        mock.expect_message(OutgoingMessageIds::UnsubscribeFromGroupEvents, TIMEOUT)?;
+        // the receiver ends with the subscription
+        assert!(events.recv_timeout(TIMEOUT).is_err());
+        client.disconnect()
+    }
+}
@@ -3,63 +3,100 @@ mod tests {
     use crate::core::client::{ConnStatus, EClient, POISONED_MUTEX};
 
     use crate::core::{
+        account_summary_tags::{AccountSummaryTag, AccountSummaryTagSet},
         common::{
-            BarData, CommissionReport, DepthMktDataDescription, FaDataType, FamilyCode,
-            HistogramData, HistoricalTick, HistoricalTickBidAsk, HistoricalTickLast, NewsProvider,
-            PriceIncrement, RealTimeBar, SmartComponent, TickAttrib, TickAttribBidAsk,
-            TickAttribLast, TickByTickType, TickType,
+            BarData, BarSize, CommissionReport, DepthMktDataDescription,
+            Duration as HistoricalDuration, FaDataType, FamilyCode, FeeRateBar, HistogramData,
+            HistoricalTick, HistoricalTickBidAsk, HistoricalTickLast, NewsArticleContent,
+            NewsProvider, PriceIncrement, RealTimeBar, SecType, SmartComponent, TickAttrib,
+            TickAttribBidAsk, TickAttribLast, TickByTickType, TickType, WhatToShow, YieldBar,
         },
         contract::{Contract, ContractDescription, ContractDetails, DeltaNeutralContract},
         execution::{Execution, ExecutionFilter},
-        order::{Order, SoftDollarTier},
+        news_bulletins::Bulletin,
+        order::{Action, Order, OrderType, SoftDollarTier, Tif},
+        order_templates,
+        reader::DispatchMode,
+        request_context,
+        server_versions::MAX_CLIENT_VER,
         streamer::{Streamer, TestStreamer},
+        subscriptions::RequestMetadata,
+        testing::MockTws,
+        timestamps::{timestamp_from_seconds, Timestamp},
         wrapper::Wrapper,
     };
     use crate::{
+        core::precautions::PrecautionarySettings,
         core::{
-            errors::IBKRApiLibError,
+            decoder::{decode_decimal, Decoder},
+            errors::{IBKRApiLibError, TwsError},
+            exchange::Exchange,
+            message_queue::{message_queue, QueueConfig},
             messages::{read_fields, read_msg, OutgoingMessageIds},
             order::OrderState,
+            what_if::MarginPreview,
+        },
+        examples::{
+            contract_samples::{bond, simple_future, usstock},
+            defaults::DefaultWrapper,
+            order_samples::limit_order,
         },
-        examples::contract_samples::simple_future,
     };
+    use std::sync::mpsc::{channel, TryRecvError};
     use std::sync::{Arc, Mutex};
-
-    pub struct DummyTestWrapper {}
+    use std::time::{Duration, Instant};
+
+    use bigdecimal::BigDecimal;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Records the request each market data callback answers, as seen through the request
+    /// context and the metadata of the thread
+    #[derive(Default)]
+    pub struct DummyTestWrapper {
+        metadata: RequestMetadata<String>,
+        seen: Vec<(i32, Option<String>, Option<String>)>,
+        order_statuses: Vec<i32>,
+        executions: Vec<Execution>,
+        /// the kind of each historical bar received, by req_id
+        bar_kinds: Vec<(i32, &'static str)>,
+    }
 
     impl DummyTestWrapper {
         fn new() -> Self {
-            DummyTestWrapper {}
+            DummyTestWrapper::default()
+        }
+
+        fn see(&mut self, req_id: i32) {
+            let context = request_context::current().map(|context| context.to_string());
+            self.seen.push((req_id, context, self.metadata.current()));
         }
     }
 
     impl Wrapper for DummyTestWrapper {
-        fn error(&mut self, _req_id: i32, _error_code: i32, _error_string: &str) {
+        fn error(&mut self, _req_id: i32, _error_code: i32, _error_string: &str) {}
+        fn warning(&mut self, _req_id: i32, _warning_code: i32, _warning_string: &str) {
             todo!()
         }
         fn win_error(&mut self, _text: &str, _last_error: i32) {
             todo!()
         }
-        fn connect_ack(&mut self) {
-            todo!()
-        }
+        fn connect_ack(&mut self) {}
         fn market_data_type(&mut self, _req_id: i32, _market_data_type: i32) {
             todo!()
         }
         fn tick_price(
             &mut self,
-            _req_id: i32,
+            req_id: i32,
             _tick_type: TickType,
             _price: f64,
             _attrib: TickAttrib,
         ) {
-            todo!()
-        }
-        fn tick_size(&mut self, _req_id: i32, _tick_type: TickType, _size: i32) {
-            todo!()
+            self.see(req_id);
         }
-        fn tick_snapshot_end(&mut self, _req_id: i32) {
-            todo!()
+        fn tick_size(&mut self, _req_id: i32, _tick_type: TickType, _size: BigDecimal) {}
+        fn tick_snapshot_end(&mut self, req_id: i32) {
+            self.see(req_id);
         }
         fn tick_generic(&mut self, _req_id: i32, _tick_type: TickType, _value: f64) {
             todo!()
@@ -83,7 +120,7 @@ mod tests {
         }
         fn order_status(
             &mut self,
-            _order_id: i32,
+            order_id: i32,
             _status: &str,
             _filled: f64,
             _remaining: f64,
@@ -95,7 +132,7 @@ mod tests {
             _why_held: &str,
             _mkt_cap_price: f64,
         ) {
-            todo!()
+            self.order_statuses.push(order_id);
         }
         fn open_order(
             &mut self,
@@ -109,9 +146,7 @@ mod tests {
         fn open_order_end(&mut self) {
             todo!()
         }
-        fn connection_closed(&mut self) {
-            todo!()
-        }
+        fn connection_closed(&mut self) {}
         fn update_account_value(
             &mut self,
             _key: &str,
@@ -152,8 +187,8 @@ mod tests {
         fn contract_details_end(&mut self, _req_id: i32) {
             todo!()
         }
-        fn exec_details(&mut self, _req_id: i32, _contract: Contract, _execution: Execution) {
-            todo!()
+        fn exec_details(&mut self, _req_id: i32, _contract: Contract, execution: Execution) {
+            self.executions.push(execution);
         }
         fn exec_details_end(&mut self, _req_id: i32) {
             todo!()
@@ -165,7 +200,7 @@ mod tests {
             _operation: i32,
             _side: i32,
             _price: f64,
-            _size: i32,
+            _size: BigDecimal,
         ) {
             todo!()
         }
@@ -177,18 +212,12 @@ mod tests {
             _operation: i32,
             _side: i32,
             _price: f64,
-            _size: i32,
+            _size: BigDecimal,
             _is_smart_depth: bool,
         ) {
             todo!()
         }
-        fn update_news_bulletin(
-            &mut self,
-            _msg_id: i32,
-            _msg_type: i32,
-            _news_message: &str,
-            _origin_exch: &str,
-        ) {
+        fn update_news_bulletin(&mut self, _bulletin: &Bulletin) {
             todo!()
         }
         fn managed_accounts(&mut self, _accounts_list: &str) {
@@ -197,11 +226,15 @@ mod tests {
         fn receive_fa(&mut self, _fa_data: FaDataType, _cxml: &str) {
             todo!()
         }
-        fn historical_data(&mut self, _req_id: i32, _bar: BarData) {
-            todo!()
+        fn historical_data(&mut self, req_id: i32, _bar: BarData) {
+            self.bar_kinds.push((req_id, "price"));
         }
-        fn historical_data_end(&mut self, _req_id: i32, _start: &str, _end: &str) {
-            todo!()
+        fn historical_data_end(&mut self, _req_id: i32, _start: &str, _end: &str) {}
+        fn historical_yield_data(&mut self, req_id: i32, _bar: YieldBar) {
+            self.bar_kinds.push((req_id, "yield"));
+        }
+        fn historical_fee_rate_data(&mut self, req_id: i32, _bar: FeeRateBar) {
+            self.bar_kinds.push((req_id, "fee_rate"));
         }
         fn scanner_parameters(&mut self, _xml: &str) {
             todo!()
@@ -256,7 +289,7 @@ mod tests {
             &mut self,
             _req_id: i32,
             _account: &str,
-            _tag: &str,
+            _tag: &AccountSummaryTag,
             _value: &str,
             _currency: &str,
         ) {
@@ -364,7 +397,7 @@ mod tests {
         fn tick_news(
             &mut self,
             _ticker_id: i32,
-            _time_stamp: i32,
+            _time_stamp: &Timestamp,
             _provider_code: &str,
             _article_id: &str,
             _headline: &str,
@@ -390,10 +423,13 @@ mod tests {
         fn news_article(&mut self, _request_id: i32, _article_type: i32, _article_text: &str) {
             todo!()
         }
+        fn news_article_content(&mut self, _request_id: i32, _content: &NewsArticleContent) {
+            todo!()
+        }
         fn historical_news(
             &mut self,
             _request_id: i32,
-            _time: &str,
+            _time: &Timestamp,
             _provider_code: &str,
             _article_id: &str,
             _headline: &str,
@@ -466,7 +502,7 @@ mod tests {
             _tick_type: TickByTickType,
             _time: i64,
             _price: f64,
-            _size: i32,
+            _size: BigDecimal,
             _tick_attrib_last: TickAttribLast,
             _exchange: &str,
             _special_conditions: &str,
@@ -479,8 +515,8 @@ mod tests {
             _time: i64,
             _bid_price: f64,
             _ask_price: f64,
-            _bid_size: i32,
-            _ask_size: i32,
+            _bid_size: BigDecimal,
+            _ask_size: BigDecimal,
             _tick_attrib_bid_ask: TickAttribBidAsk,
         ) {
             todo!()
@@ -538,19 +574,22 @@ mod tests {
         let version = 2;
         let req_id = 100;
         let group_name = "MyGroup";
-        let tags = "tag1:tag_value1, tag2:tag_value2";
+        let tags = AccountSummaryTagSet::new()
+            .with(AccountSummaryTag::NetLiquidation)
+            .with(AccountSummaryTag::LedgerCurrency("EUR".to_string()))
+            .with(AccountSummaryTag::NetLiquidation);
         let mut buf = Vec::<u8>::new();
 
         let mut locked_app = app.lock().expect("EClient mutex was poisoned");
 
         locked_app.connect_test();
-        locked_app.req_account_summary(req_id, group_name, tags)?;
+        locked_app.req_account_summary(req_id, group_name, &tags)?;
         locked_app.stream.as_mut().unwrap().read_to_end(&mut buf)?;
 
-        let expected: [u8; 54] = [
-            0, 0, 0, 50, 54, 50, 0, 50, 0, 49, 48, 48, 0, 77, 121, 71, 114, 111, 117, 112, 0, 116,
-            97, 103, 49, 58, 116, 97, 103, 95, 118, 97, 108, 117, 101, 49, 44, 32, 116, 97, 103,
-            50, 58, 116, 97, 103, 95, 118, 97, 108, 117, 101, 50, 0,
+        let expected: [u8; 48] = [
+            0, 0, 0, 44, 54, 50, 0, 50, 0, 49, 48, 48, 0, 77, 121, 71, 114, 111, 117, 112, 0, 78,
+            101, 116, 76, 105, 113, 117, 105, 100, 97, 116, 105, 111, 110, 44, 36, 76, 69, 68, 71,
+            69, 82, 58, 69, 85, 82, 0,
         ];
 
         let msg_data = read_msg(buf.as_slice())?;
@@ -564,7 +603,7 @@ mod tests {
         assert_eq!(version, fields[1].parse::<i32>().unwrap());
         assert_eq!(req_id, fields[2].parse::<i32>().unwrap());
         assert_eq!(group_name, fields[3]);
-        assert_eq!(tags, fields[4]);
+        assert_eq!("NetLiquidation,$LEDGER:EUR", fields[4]);
 
         Ok(())
     }
@@ -907,4 +946,570 @@ mod tests {
 
         Ok(())
     }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_place_order_precaution_violation() -> Result<(), IBKRApiLibError> {
+        let wrapper = Arc::new(Mutex::new(DummyTestWrapper::new()));
+        let app = Arc::new(Mutex::new(EClient::<DummyTestWrapper>::new(
+            wrapper.clone(),
+        )));
+        let mut buf = Vec::<u8>::new();
+
+        let mut locked_app = app.lock().expect("EClient mutex was poisoned");
+
+        locked_app.connect_test();
+        locked_app.set_precautionary_settings(Some(PrecautionarySettings::new(
+            Some(10.0),
+            None,
+            None,
+        )));
+
+        let contract = simple_future();
+        assert!(locked_app
+            .place_order(1, &contract, &limit_order("BUY", 100.0, 3000.0))
+            .is_err());
+        locked_app.stream.as_mut().unwrap().read_to_end(&mut buf)?;
+        assert!(buf.is_empty());
+
+        Ok(())
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_place_order_checks_attributes() -> Result<(), IBKRApiLibError> {
+        let wrapper = Arc::new(Mutex::new(DummyTestWrapper::new()));
+        let mut app = EClient::<DummyTestWrapper>::new(wrapper);
+        let mut buf = Vec::<u8>::new();
+        app.connect_test();
+
+        let mut not_held = limit_order("BUY", 1.0, 3000.0);
+        not_held.not_held = true;
+        assert!(app.place_order(1, &simple_future(), &not_held).is_err());
+        let mut block = limit_order("BUY", 50.0, 3000.0);
+        block.block_order = true;
+        assert!(app.place_order(2, &simple_future(), &block).is_err());
+        let mut all_or_none = limit_order("BUY", 1.0, 3000.0);
+        all_or_none.all_or_none = true;
+        assert!(app.place_order(3, &simple_future(), &all_or_none).is_err());
+        app.stream.as_mut().unwrap().read_to_end(&mut buf)?;
+        assert!(buf.is_empty());
+
+        let mut ats = simple_future();
+        ats.exchange = Exchange::Ibkrats;
+        app.place_order(4, &ats, &not_held)?;
+        app.stream.as_mut().unwrap().read_to_end(&mut buf)?;
+        assert!(!buf.is_empty());
+        Ok(())
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_decode_unknown_origin() {
+        let (_sender, receiver) = message_queue(QueueConfig::default());
+        let mut decoder = Decoder::new(
+            Arc::new(Mutex::new(DefaultWrapper::new())),
+            receiver,
+            MAX_CLIENT_VER,
+            Arc::new(Mutex::new(ConnStatus::CONNECTED)),
+        );
+        let completed_order = |origin: &str| -> Vec<String> {
+            let mut fields: Vec<String> = [
+                "101",
+                "495512563",
+                "ES",
+                "FUT",
+                "202009",
+                "0",
+                "",
+                "50",
+                "GLOBEX",
+                "USD",
+                "ESU0",
+                "ES",
+                "BUY",
+                "1",
+                "LMT",
+                "3000",
+                "0",
+                "DAY",
+                "",
+                "DU1",
+                "O",
+            ]
+            .iter()
+            .map(|field| field.to_string())
+            .collect();
+            fields.push(origin.to_string());
+            fields
+        };
+        // decoding stops at the origin, before the fields this message leaves out
+        match decoder.interpret(&completed_order("7")) {
+            Err(IBKRApiLibError::ApiError(error)) => {
+                assert_eq!(TwsError::BadMessage.code().to_string(), error.code);
+                assert!(error.description.contains("Unknown origin 7"));
+            }
+            other => panic!("expected an unknown origin error, got {:?}", other),
+        }
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_decode_unset_decimal() -> Result<(), IBKRApiLibError> {
+        let fields: Vec<String> = ["", "9223372036854775807", "0.0125"]
+            .iter()
+            .map(|field| field.to_string())
+            .collect();
+        let mut fields_itr = fields.iter();
+        assert_eq!(BigDecimal::from(0), decode_decimal(&mut fields_itr)?);
+        assert_eq!(BigDecimal::from(0), decode_decimal(&mut fields_itr)?);
+        assert_eq!(
+            "0.0125".parse::<BigDecimal>().unwrap(),
+            decode_decimal(&mut fields_itr)?
+        );
+        assert!(decode_decimal(&mut ["1.2.3".to_string()].iter()).is_err());
+
+        let (_sender, receiver) = message_queue(QueueConfig::default());
+        let mut decoder = Decoder::new(
+            Arc::new(Mutex::new(DefaultWrapper::new())),
+            receiver,
+            MAX_CLIENT_VER,
+            Arc::new(Mutex::new(ConnStatus::CONNECTED)),
+        );
+        // a bid with an empty size
+        let tick_price: Vec<String> = ["1", "6", "1", "1", "101.25", "", "0"]
+            .iter()
+            .map(|field| field.to_string())
+            .collect();
+        decoder.interpret(&tick_price)
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_order_warning_keeps_what_if() -> Result<(), IBKRApiLibError> {
+        let (_sender, receiver) = message_queue(QueueConfig::default());
+        let mut decoder = Decoder::new(
+            Arc::new(Mutex::new(DefaultWrapper::new())),
+            receiver,
+            MAX_CLIENT_VER,
+            Arc::new(Mutex::new(ConnStatus::CONNECTED)),
+        );
+        let (preview_sender, preview) = channel::<MarginPreview>();
+        decoder
+            .pending
+            .what_if
+            .lock()
+            .expect(POISONED_MUTEX)
+            .insert(7, preview_sender);
+        let error = |code: &str, message: &str| -> Vec<String> {
+            ["4", "2", "7", code, message]
+                .iter()
+                .map(|field| field.to_string())
+                .collect()
+        };
+
+        // the warning comes before the OpenOrder with the preview
+        decoder.interpret(&error("399", "Order Message: Warning: large order"))?;
+        assert!(matches!(preview.try_recv(), Err(TryRecvError::Empty)));
+        decoder.interpret(&error("201", "Order rejected"))?;
+        assert!(matches!(
+            preview.try_recv(),
+            Err(TryRecvError::Disconnected)
+        ));
+        Ok(())
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_place_order_checks_crypto_orders() -> Result<(), IBKRApiLibError> {
+        let wrapper = Arc::new(Mutex::new(DummyTestWrapper::new()));
+        let mut app = EClient::<DummyTestWrapper>::new(wrapper);
+        let mut buf = Vec::<u8>::new();
+        app.connect_test();
+
+        let btc = Contract::crypto("BTC");
+        let mut buy = order_templates::cash_market(Action::Buy, 500.0)?;
+        assert!(app.place_order(1, &btc, &buy).is_err());
+        buy.tif = Tif::Ioc;
+        let mut fractional = order_templates::market(Action::Buy, 0.0125)?;
+        fractional.tif = Tif::Ioc;
+        assert!(app.place_order(2, &btc, &fractional).is_err());
+        let mut trail = order_templates::trailing_stop(Action::Sell, 0.5, 100.0)?;
+        assert!(app.place_order(3, &btc, &trail).is_err());
+        trail.order_type = OrderType::Lmt;
+        trail.lmt_price = 64000.0;
+        trail.tif = Tif::Gtd;
+        assert!(app.place_order(4, &btc, &trail).is_err());
+        app.stream.as_mut().unwrap().read_to_end(&mut buf)?;
+        assert!(buf.is_empty());
+
+        app.place_order(5, &btc, &buy)?;
+        trail.tif = Tif::Gtc;
+        app.place_order(6, &btc, &trail)?;
+        app.stream.as_mut().unwrap().read_to_end(&mut buf)?;
+        let fields = read_fields(&read_msg(&buf)?.1);
+        assert!(fields.contains(&"PAXOS".to_string()));
+        assert!(fields.contains(&"500".to_string()));
+        Ok(())
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_decode_crypto_execution() -> Result<(), IBKRApiLibError> {
+        let (sender, receiver) = message_queue(QueueConfig::default());
+        let wrapper = Arc::new(Mutex::new(DummyTestWrapper::new()));
+        let mut decoder = Decoder::new(
+            wrapper.clone(),
+            receiver,
+            MAX_CLIENT_VER,
+            Arc::new(Mutex::new(ConnStatus::CONNECTED)),
+        );
+        let fields = [
+            "11",
+            "4",
+            "9",
+            "479624278",
+            "BTC",
+            "CRYPTO",
+            "",
+            "0",
+            "",
+            "",
+            "PAXOS",
+            "USD",
+            "BTC.USD",
+            "BTC",
+            "0001f4e8.57427bb8.01.01",
+            "20240102 10:00:00",
+            "DU123",
+            "PAXOS",
+            "BOT",
+            "0.0125",
+            "64250.5",
+            "77",
+            "0",
+            "0",
+            "0.0125",
+            "64250.5",
+            "",
+            "",
+            "",
+            "",
+            "2",
+        ];
+        sender
+            .send(Ok(fields
+                .iter()
+                .map(|field| format!("{}\0", field))
+                .collect()))
+            .unwrap();
+        drop(sender);
+        decoder.run()?;
+
+        let executions = &wrapper.lock().expect(POISONED_MUTEX).executions;
+        assert_eq!(1, executions.len());
+        let fraction: BigDecimal = "0.0125".parse().unwrap();
+        assert_eq!(
+            (&fraction, &fraction),
+            (&executions[0].shares, &executions[0].cum_qty)
+        );
+        assert_eq!(64250.5, executions[0].price);
+        Ok(())
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_continuous_futures_checks() -> Result<(), IBKRApiLibError> {
+        let wrapper = Arc::new(Mutex::new(DummyTestWrapper::new()));
+        let mut app = EClient::<DummyTestWrapper>::new(wrapper);
+        let mut buf = Vec::<u8>::new();
+        app.connect_test();
+
+        let mut es = Contract::continuous_future("ES", "CME");
+        assert!(app
+            .place_order(1, &es, &limit_order("BUY", 1.0, 5000.0))
+            .is_err());
+        let request = |app: &mut EClient<DummyTestWrapper>, contract: &Contract, end: &str| {
+            app.req_historical_data(
+                2,
+                contract,
+                end,
+                &HistoricalDuration::Years(1),
+                &BarSize::_1Day,
+                &WhatToShow::Trades,
+                1,
+                1,
+                false,
+                vec![],
+            )
+        };
+        assert!(request(&mut app, &es, "20250101 00:00:00 UTC").is_err());
+        es.sec_type = SecType::FUTCONTFUT;
+        assert!(request(&mut app, &es, "").is_err());
+        app.stream.as_mut().unwrap().read_to_end(&mut buf)?;
+        assert!(buf.is_empty());
+
+        es.sec_type = SecType::CONTFUT;
+        request(&mut app, &es, "")?;
+        app.stream.as_mut().unwrap().read_to_end(&mut buf)?;
+        let fields = read_fields(&read_msg(&buf)?.1);
+        assert!(fields.contains(&"CONTFUT".to_string()));
+        Ok(())
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_place_order_checks_good_till_date() -> Result<(), IBKRApiLibError> {
+        let wrapper = Arc::new(Mutex::new(DummyTestWrapper::new()));
+        let mut app = EClient::<DummyTestWrapper>::new(wrapper);
+        let mut buf = Vec::<u8>::new();
+        app.connect_test();
+
+        let mut gtd = limit_order("BUY", 1.0, 3000.0);
+        gtd.tif = Tif::from("gtd");
+        assert_eq!(Tif::Gtd, gtd.tif);
+        assert!(app.place_order(1, &simple_future(), &gtd).is_err());
+        app.stream.as_mut().unwrap().read_to_end(&mut buf)?;
+        assert!(buf.is_empty());
+
+        gtd.good_till_date = timestamp_from_seconds(1_600_000_000);
+        app.place_order(2, &simple_future(), &gtd)?;
+        app.stream.as_mut().unwrap().read_to_end(&mut buf)?;
+        let fields = read_fields(&read_msg(&buf)?.1);
+        assert!(fields.contains(&"GTD".to_string()));
+        Ok(())
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_place_order_sends_action_codes() -> Result<(), IBKRApiLibError> {
+        let wrapper = Arc::new(Mutex::new(DummyTestWrapper::new()));
+        let mut app = EClient::<DummyTestWrapper>::new(wrapper);
+        let mut buf = Vec::<u8>::new();
+        app.connect_test();
+
+        assert_eq!(Action::SShort, Action::from("sshort"));
+        assert_eq!(Action::Buy, Action::SLong.opposite());
+        assert_eq!(Action::Other("HOLD".to_string()), Action::from("HOLD"));
+
+        let order = limit_order("sell", 1.0, 3000.0);
+        assert_eq!(Action::Sell, order.action);
+        app.place_order(1, &simple_future(), &order)?;
+        app.stream.as_mut().unwrap().read_to_end(&mut buf)?;
+        let fields = read_fields(&read_msg(&buf)?.1);
+        assert!(fields.contains(&"SELL".to_string()));
+        assert!(!fields.contains(&"sell".to_string()));
+        Ok(())
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_place_order_sends_mifid2_fields() -> Result<(), IBKRApiLibError> {
+        let wrapper = Arc::new(Mutex::new(DummyTestWrapper::new()));
+        let mut app = EClient::<DummyTestWrapper>::new(wrapper);
+        let mut buf = Vec::<u8>::new();
+        app.connect_test();
+
+        let mut order = limit_order("BUY", 1.0, 3000.0);
+        order.mifid2decision_maker = "JSMITH".to_string();
+        order.mifid2execution_algo = "ALGO7".to_string();
+        app.place_order(1, &simple_future(), &order)?;
+        app.stream.as_mut().unwrap().read_to_end(&mut buf)?;
+        let fields = read_fields(&read_msg(&buf)?.1);
+        let maker = fields.iter().position(|field| field == "JSMITH").unwrap();
+        assert_eq!(["JSMITH", "", "", "ALGO7"], fields[maker..maker + 4]);
+
+        // a person and an algo for the same decision is not sent
+        order.mifid2decision_algo = "ALGO3".to_string();
+        assert!(app.place_order(2, &simple_future(), &order).is_err());
+        Ok(())
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_requests_record_context() -> Result<(), IBKRApiLibError> {
+        let wrapper = Arc::new(Mutex::new(DummyTestWrapper::new()));
+        let mut app = EClient::<DummyTestWrapper>::new(wrapper);
+        app.connect_test();
+
+        app.req_mkt_data(42, &simple_future(), "", false, false, vec![])?;
+        app.req_pnl(43, "DU1", "")?;
+        let context = app.request_context(42).unwrap();
+        assert_eq!("req_mkt_data", context.request);
+        assert_eq!("ES", context.contract.as_ref().unwrap().symbol);
+        assert!(app.request_context(43).unwrap().contract.is_none());
+
+        app.cancel_mkt_data(42)?;
+        assert!(app.request_context(42).is_none());
+        Ok(())
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_priority_orders_skip_the_market_data_lock() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let wrapper = Arc::new(Mutex::new(DummyTestWrapper::new()));
+        let order_wrapper = Arc::new(Mutex::new(DummyTestWrapper::new()));
+        let mut app = EClient::new(wrapper.clone());
+        app.set_dispatch_mode(DispatchMode::PriorityOrders);
+        app.set_order_wrapper(Some(order_wrapper.clone()));
+        app.connect("127.0.0.1", mock.port(), 0)?;
+
+        {
+            // a slow market data callback holds the main wrapper
+            let main = wrapper.lock().expect(POISONED_MUTEX);
+            mock.send(&["1", "6", "42", "4", "3000.25", "2", "0"])?;
+            mock.send(&[
+                "3", "7", "Filled", "1", "0", "3000.25", "1", "0", "3000.25", "0", "", "0",
+            ])?;
+            let deadline = Instant::now() + TIMEOUT;
+            while order_wrapper
+                .lock()
+                .expect(POISONED_MUTEX)
+                .order_statuses
+                .is_empty()
+            {
+                assert!(
+                    Instant::now() < deadline,
+                    "the order status waited for the tick"
+                );
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            assert!(main.seen.is_empty());
+        }
+
+        // the tick sent first reaches the main wrapper once it is released
+        let deadline = Instant::now() + TIMEOUT;
+        while wrapper.lock().expect(POISONED_MUTEX).seen.is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(1, wrapper.lock().expect(POISONED_MUTEX).seen.len());
+        assert!(wrapper
+            .lock()
+            .expect(POISONED_MUTEX)
+            .order_statuses
+            .is_empty());
+        assert_eq!(
+            vec![7],
+            order_wrapper.lock().expect(POISONED_MUTEX).order_statuses
+        );
+        assert!(order_wrapper.lock().expect(POISONED_MUTEX).seen.is_empty());
+        app.disconnect()
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_callbacks_see_request_context() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_to(
+            OutgoingMessageIds::ReqMktData,
+            vec![
+                vec!["1", "6", "42", "4", "3000.25", "2", "0"],
+                vec!["57", "1", "42"],
+            ],
+        );
+        let wrapper = Arc::new(Mutex::new(DummyTestWrapper::new()));
+        let metadata = wrapper.lock().expect(POISONED_MUTEX).metadata.clone();
+        let mut app = EClient::new(wrapper.clone());
+        app.connect("127.0.0.1", mock.port(), 0)?;
+        app.track_metadata(&metadata);
+
+        metadata.attach(42, "momentum".to_string());
+        app.req_mkt_data(42, &simple_future(), "", true, false, vec![])?;
+        let deadline = Instant::now() + TIMEOUT;
+        while wrapper.lock().expect(POISONED_MUTEX).seen.len() < 2 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        let context = "req_id: 42, request: req_mkt_data, contract: ES FUT GLOBEX USD";
+        let seen = (42, Some(context.to_string()), Some("momentum".to_string()));
+        assert_eq!(
+            vec![seen.clone(), seen],
+            wrapper.lock().expect(POISONED_MUTEX).seen
+        );
+        // the snapshot ended the request
+        assert!(app.request_context(42).is_none());
+        assert!(metadata.is_empty());
+        app.disconnect()
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_historical_bar_kinds() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_with(
+            OutgoingMessageIds::ReqHistoricalData,
+            Box::new(|request| {
+                let req_id = request[1].as_str();
+                let fields = if request.iter().any(|field| field == "YIELD_ASK") {
+                    vec!["4", "2", req_id, "162", "HMDS query returned no data"]
+                } else {
+                    vec![
+                        "17",
+                        req_id,
+                        "",
+                        "",
+                        "1",
+                        "1600000000",
+                        "1.5",
+                        "1.6",
+                        "1.4",
+                        "1.55",
+                        "0",
+                        "1.5",
+                        "0",
+                    ]
+                };
+                vec![fields.iter().map(|field| field.to_string()).collect()]
+            }),
+        );
+        let wrapper = Arc::new(Mutex::new(DummyTestWrapper::new()));
+        let mut app = EClient::new(wrapper.clone());
+        app.connect("127.0.0.1", mock.port(), 0)?;
+
+        let mut request = |req_id: i32, contract: &Contract, what_to_show: &WhatToShow| {
+            app.req_historical_data(
+                req_id,
+                contract,
+                "",
+                &HistoricalDuration::Days(1),
+                &BarSize::_1Day,
+                what_to_show,
+                1,
+                2,
+                false,
+                vec![],
+            )
+        };
+        request(1, &bond(), &WhatToShow::YieldBid)?;
+        request(2, &usstock(), &WhatToShow::FeeRate)?;
+        // the rejected yield request does not leave its kind to the next request of its req_id
+        request(3, &bond(), &WhatToShow::YieldAsk)?;
+        request(3, &usstock(), &WhatToShow::Trades)?;
+        let deadline = Instant::now() + TIMEOUT;
+        while wrapper.lock().expect(POISONED_MUTEX).bar_kinds.len() < 3 && Instant::now() < deadline
+        {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(
+            vec![(1, "yield"), (2, "fee_rate"), (3, "price")],
+            wrapper.lock().expect(POISONED_MUTEX).bar_kinds
+        );
+        app.disconnect()
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_place_order_records_latency() -> Result<(), IBKRApiLibError> {
+        let wrapper = Arc::new(Mutex::new(DummyTestWrapper::new()));
+        let mut app = EClient::<DummyTestWrapper>::new(wrapper);
+        app.connect_test();
+
+        app.place_order(7, &simple_future(), &limit_order("BUY", 1.0, 3000.0))?;
+        let latency = app.order_latency(7).unwrap();
+        assert!(latency.encode <= latency.write);
+        assert!(latency.first_status.is_none());
+        assert_eq!(1, app.order_latency_stats().write.count);
+        Ok(())
+    }
 }
@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::core::errors::{
+        ErrorCategory, ErrorSeverity, IbError, TwsApiReportableError, TwsError, TwsMessage,
+    };
+
+    #[test]
+    fn test_ib_error_categories() {
+        let farm_ok = TwsMessage::new(-1, 2104, "Market data farm connection is OK".to_string());
+        assert_eq!(IbError::MarketDataFarmOk, farm_ok.kind());
+        assert_eq!(ErrorCategory::Connectivity, farm_ok.kind().category());
+        assert!(farm_ok.kind().is_informational());
+
+        let rejected = IbError::from_code(201);
+        assert_eq!(ErrorCategory::OrderReject, rejected.category());
+        assert_eq!(ErrorSeverity::Error, rejected.severity());
+        assert_eq!(ErrorCategory::Request, IbError::from_code(162).category());
+        let pacing = TwsMessage::new(
+            4,
+            162,
+            "Historical Market Data Service error message:Historical data request pacing \
+             violation"
+                .to_string(),
+        );
+        assert_eq!(ErrorCategory::Pacing, pacing.category());
+        let no_data = TwsMessage::new(
+            5,
+            162,
+            "Historical Market Data Service error message:HMDS query returned no data".to_string(),
+        );
+        assert_eq!(ErrorCategory::Request, no_data.category());
+        let lost = IbError::from_code(1100);
+        assert_eq!(ErrorCategory::Connectivity, lost.category());
+        assert_eq!(ErrorSeverity::Warning, lost.severity());
+        assert_eq!(ErrorSeverity::Warning, IbError::from_code(2119).severity());
+        assert_eq!(IbError::Other(10090), IbError::from_code(10090));
+        assert_eq!(10090, IbError::Other(10090).code());
+
+        let client = TwsApiReportableError::new(
+            -1,
+            TwsError::NotConnected.code().to_string(),
+            TwsError::NotConnected.message().to_string(),
+        );
+        let kind = client.kind().unwrap();
+        assert_eq!(ErrorCategory::Client, kind.category());
+        assert_eq!(ErrorSeverity::Fatal, kind.severity());
+    }
+}
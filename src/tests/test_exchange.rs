@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::core::exchange::Exchange;
+    use crate::examples::contract_samples;
+
+    #[test]
+    fn test_from_code() {
+        assert_eq!(Exchange::Smart, Exchange::from_code("SMART"));
+        assert_eq!(Exchange::Globex, Exchange::from_code("globex"));
+        assert_eq!(Exchange::None, Exchange::from_code(""));
+        assert_eq!(
+            Exchange::Other("MEFFRV".to_string()),
+            Exchange::from_code("MEFFRV")
+        );
+        assert_eq!("IDEALPRO", Exchange::IdealPro.code());
+        assert_eq!("", Exchange::None.to_string());
+        assert!(!Exchange::None.is_set());
+    }
+
+    #[test]
+    fn test_likely_misspelling_of() {
+        let misspelling = |code: &str| Exchange::from(code).likely_misspelling_of();
+        assert_eq!(Some(Exchange::Globex), misspelling("GLOBAX"));
+        assert_eq!(Some(Exchange::Globex), misspelling("GLOBX"));
+        assert_eq!(Some(Exchange::Smart), misspelling("SMRAT"));
+        assert_eq!(Some(Exchange::IdealPro), misspelling("idealpor"));
+        assert_eq!(None, misspelling("SMART"));
+        // real venues a letter or digit away from a known one
+        assert_eq!(None, misspelling("BEX"));
+        assert_eq!(None, misspelling("IBIS2"));
+        assert_eq!(None, misspelling("CBOE2"));
+        assert_eq!(None, misspelling("DTB"));
+    }
+
+    #[test]
+    fn test_sample_exchanges_accepted() {
+        for contract in &[
+            contract_samples::index(),
+            contract_samples::european_stock2(),
+            contract_samples::mutual_fund(),
+            contract_samples::option_with_trading_class(),
+            contract_samples::simple_future(),
+            contract_samples::brfgbroadtape_news_feed(),
+            contract_samples::djnlbroadtape_news_feed(),
+            contract_samples::djtopbroadtape_news_feed(),
+            contract_samples::brfupdnbroadtape_news_feed(),
+            contract_samples::jefferies_contract(),
+            contract_samples::csfbcontract(),
+            contract_samples::qbalgo_contract(),
+        ] {
+            assert_eq!(None, contract.exchange.likely_misspelling_of());
+            assert_eq!(None, contract.primary_exchange.likely_misspelling_of());
+        }
+    }
+}
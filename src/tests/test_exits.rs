@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::{Arc, Mutex};
+
+    use crate::core::client::EClient;
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::exits::{ExitManager, ExitReason};
+    use crate::core::order::Action;
+    use crate::core::order_tracker::{OrderPhase, OrderTracker};
+    use crate::core::server_versions::MAX_CLIENT_VER;
+    use crate::core::testing::MockTws;
+    use crate::examples::contract_samples::simple_future;
+    use crate::examples::defaults::DefaultWrapper;
+    use crate::examples::order_samples::bracket_order;
+
+    #[test]
+    fn test_exit_manager_breakeven_and_trail() {
+        let (parent, take_profit, stop_loss) = bracket_order(10, "BUY", 3.0, 100.0, 110.0, 95.0);
+        let mut tracker = OrderTracker::new();
+        tracker.track(10, &simple_future(), &parent);
+        let mut exits = ExitManager::new(&simple_future(), &parent, &take_profit, &stop_loss)
+            .with_breakeven(2.0, 0.25)
+            .with_atr_trail(2.0);
+        let mut next_order_id = 20;
+
+        // nothing before the entry filled
+        assert!(exits
+            .on_price(103.0, None, &tracker, &mut next_order_id)
+            .is_empty());
+        tracker.on_order_status(10, "Filled", 3.0, 0.0, 100.0, 0);
+
+        assert!(exits
+            .on_price(101.0, None, &tracker, &mut next_order_id)
+            .is_empty());
+        let orders = exits.on_price(102.5, None, &tracker, &mut next_order_id);
+        assert_eq!(1, orders.len());
+        assert_eq!(
+            (12, ExitReason::Breakeven),
+            (orders[0].order_id, orders[0].reason)
+        );
+        assert_eq!(100.25, orders[0].order.aux_price);
+
+        let orders = exits.on_price(106.0, Some(1.5), &tracker, &mut next_order_id);
+        assert_eq!(ExitReason::Trail, orders[0].reason);
+        assert_eq!(103.0, exits.stop_price());
+        // the stop never moves back
+        assert!(exits
+            .on_price(104.0, Some(1.5), &tracker, &mut next_order_id)
+            .is_empty());
+        assert_eq!(103.0, exits.stop_price());
+    }
+
+    #[test]
+    fn test_exit_manager_scale_out_in_thirds() {
+        let (parent, take_profit, stop_loss) = bracket_order(10, "SELL", 9.0, 100.0, 90.0, 105.0);
+        let mut tracker = OrderTracker::new();
+        tracker.on_order_status(10, "Filled", 9.0, 0.0, 100.0, 0);
+        let mut exits = ExitManager::new(&simple_future(), &parent, &take_profit, &stop_loss)
+            .with_scale_out(&[4.0, 2.0]);
+        let mut next_order_id = 20;
+
+        let orders = exits.on_price(97.5, None, &tracker, &mut next_order_id);
+        let reasons: Vec<(i32, ExitReason)> = orders
+            .iter()
+            .map(|order| (order.order_id, order.reason))
+            .collect();
+        assert_eq!(
+            vec![
+                (20, ExitReason::ScaleOut),
+                (11, ExitReason::Resize),
+                (12, ExitReason::Resize)
+            ],
+            reasons
+        );
+        assert_eq!(Action::Buy, orders[0].order.action);
+        assert_eq!(3.0, orders[0].order.total_quantity);
+        assert_eq!(6.0, exits.remaining_quantity());
+
+        let orders = exits.on_price(95.0, None, &tracker, &mut next_order_id);
+        assert_eq!(3, orders.len());
+        assert_eq!(3.0, exits.remaining_quantity());
+        assert_eq!(22, next_order_id);
+
+        tracker.on_order_status(12, "Filled", 3.0, 0.0, 105.0, 0);
+        assert!(exits
+            .on_price(90.0, None, &tracker, &mut next_order_id)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_exit_manager_scales_out_of_the_whole_entry() -> Result<(), IBKRApiLibError> {
+        let (parent, take_profit, stop_loss) = bracket_order(10, "BUY", 9.0, 100.0, 110.0, 95.0);
+        let mut tracker = OrderTracker::new();
+        tracker.on_order_status(10, "Submitted", 3.0, 6.0, 100.0, 0);
+        let mut exits = ExitManager::new(&simple_future(), &parent, &take_profit, &stop_loss)
+            .with_scale_out(&[4.0, 2.0]);
+        let mut next_order_id = 20;
+
+        // the children cover the whole entry while it fills
+        assert!(exits
+            .on_price(102.5, None, &tracker, &mut next_order_id)
+            .is_empty());
+        assert_eq!(9.0, exits.remaining_quantity());
+        tracker.on_order_status(10, "Filled", 9.0, 0.0, 100.0, 0);
+        let orders = exits.on_price(102.5, None, &tracker, &mut next_order_id);
+        assert_eq!(3, orders.len());
+        assert_eq!(6.0, exits.remaining_quantity());
+
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+        exits.apply(&mut client, &mut tracker, &orders)?;
+        let scale_out = tracker.get(20).unwrap();
+        assert_eq!(
+            (OrderPhase::PendingSubmit, 3.0),
+            (scale_out.phase, scale_out.total_quantity)
+        );
+        assert_eq!(6.0, tracker.get(12).unwrap().total_quantity);
+        client.disconnect()
+    }
+}
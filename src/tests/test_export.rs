@@ -0,0 +1,129 @@
+#[cfg(all(test, feature = "export"))]
+mod tests {
+
+    use std::fs::{self, File};
+
+    use arrow_array::{Array, Float64Array, Int32Array, TimestampMicrosecondArray};
+    use arrow_schema::{DataType, TimeUnit};
+    use bigdecimal::BigDecimal;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use crate::core::common::{BarData, HistoricalTickLast, TickAttribLast};
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::execution::Execution;
+    use crate::core::export::{write_csv, write_parquet_file};
+    use crate::core::timestamps::{timestamp_from_seconds, timestamp_to_wire};
+
+    #[test]
+    fn test_write_bars_csv() -> Result<(), IBKRApiLibError> {
+        let date = timestamp_from_seconds(1577836800);
+        let bars = vec![BarData::new(
+            date.clone(),
+            0.1 + 0.2,
+            1.5,
+            0.25,
+            1.0,
+            BigDecimal::from(1200),
+            3,
+            0.75,
+        )];
+        let mut buf = Vec::<u8>::new();
+        write_csv(&mut buf, &bars)?;
+        assert_eq!(
+            format!(
+                "timestamp,date,open,high,low,close,volume,bar_count,average\n\
+                 2020-01-01T00:00:00+00:00,{},0.30000000000000004,1.5,0.25,1,1200,3,0.75\n",
+                timestamp_to_wire(&date)
+            ),
+            String::from_utf8(buf).unwrap()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_ticks_csv_escapes() -> Result<(), IBKRApiLibError> {
+        let time = timestamp_from_seconds(0);
+        let ticks = vec![HistoricalTickLast::new(
+            time.clone(),
+            TickAttribLast::new(false, true),
+            10.0,
+            BigDecimal::from(5),
+            "ARCA".to_string(),
+            "a,\"b\"".to_string(),
+        )];
+        let mut buf = Vec::<u8>::new();
+        write_csv(&mut buf, &ticks)?;
+        let csv = String::from_utf8(buf).unwrap();
+        assert_eq!(
+            format!(
+                "1970-01-01T00:00:00+00:00,{},10,5,ARCA,\"a,\"\"b\"\"\",false,true",
+                timestamp_to_wire(&time)
+            ),
+            csv.lines().nth(1).unwrap()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_bars_parquet() -> Result<(), IBKRApiLibError> {
+        let path = std::env::temp_dir().join(format!("twsapi_bars_{}.parquet", std::process::id()));
+        let bars = vec![
+            BarData::new(
+                timestamp_from_seconds(1577836800),
+                0.1 + 0.2,
+                1.5,
+                0.25,
+                1.0,
+                BigDecimal::from(1200),
+                3,
+                0.75,
+            ),
+            BarData::default(),
+        ];
+        write_parquet_file(&path, &bars)?;
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path)?)
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches: Vec<_> = reader.collect::<Result<_, _>>().unwrap();
+        let batch = &batches[0];
+        assert_eq!(2, batch.num_rows());
+        assert_eq!(
+            &DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            batch.schema().field(0).data_type()
+        );
+        let timestamps = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<TimestampMicrosecondArray>()
+            .unwrap();
+        assert_eq!(1_577_836_800_000_000, timestamps.value(0));
+        // an unset date is null
+        assert!(timestamps.is_null(1));
+        let open = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert_eq!(0.1 + 0.2, open.value(0));
+        let bar_count = batch
+            .column(7)
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap();
+        assert_eq!(3, bar_count.value(0));
+        fs::remove_file(&path)?;
+
+        let executions = vec![Execution {
+            exec_id: "0001f4e8.57427bb8.01.01".to_string(),
+            shares: BigDecimal::from(100),
+            ..Default::default()
+        }];
+        write_parquet_file(&path, &executions)?;
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path)?).unwrap();
+        assert_eq!(1, reader.metadata().file_metadata().num_rows());
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use crate::core::account_values::AccountValueChange;
+    use crate::core::accounts::AccountId;
+    use crate::core::client::EClient;
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::fa::{FaAlias, FaAliases, FaConfig, FaGroups, FaProfiles};
+    use crate::core::messages::OutgoingMessageIds;
+    use crate::core::portfolio::Position;
+    use crate::core::server_versions::MAX_CLIENT_VER;
+    use crate::core::testing::MockTws;
+    use crate::examples::contract_samples::simple_future;
+    use crate::examples::defaults::DefaultWrapper;
+    use crate::examples::fa_allocation_samples::{FA_TWO_GROUPS, FA_TWO_PROFILES};
+
+    #[test]
+    fn test_fa_groups() -> Result<(), IBKRApiLibError> {
+        let mut groups = FaGroups::from_xml(FA_TWO_GROUPS)?;
+        assert_eq!(2, groups.groups.len());
+        let pct_change = groups.group("Pct_Change").unwrap();
+        assert_eq!(vec!["DU119915", "DU119916"], pct_change.accounts);
+        assert_eq!("PctChange", pct_change.default_method);
+
+        assert!(groups.add_account("Pct_Change", "DU119917"));
+        assert!(!groups.add_account("Pct_Change", "DU119917"));
+        assert!(groups.remove_account("Equal_Quantity", "DU119915"));
+        assert!(!groups.remove_account("Missing", "DU119915"));
+
+        let round_trip = FaGroups::from_xml(&groups.to_xml())?;
+        assert_eq!(groups, round_trip);
+        assert_eq!(
+            vec!["DU119916"],
+            round_trip.group("Equal_Quantity").unwrap().accounts
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fa_profiles_and_aliases() -> Result<(), IBKRApiLibError> {
+        let profiles = FaProfiles::from_xml(FA_TWO_PROFILES)?;
+        let ratios = profiles.profile("Ratios_2_1").unwrap();
+        assert_eq!(1, ratios.profile_type);
+        assert_eq!(
+            ("DU119916", 1.0),
+            (
+                ratios.allocations[1].account.as_str(),
+                ratios.allocations[1].amount
+            )
+        );
+        assert_eq!(profiles, FaProfiles::from_xml(&profiles.to_xml())?);
+
+        let mut aliases = FaAliases::from_xml(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<ListOfAccountAliases>\
+             <AccountAlias><account>DU119915</account><alias>Growth &amp; Income</alias>\
+             </AccountAlias></ListOfAccountAliases>",
+        )?;
+        assert_eq!(Some("Growth & Income"), aliases.alias("DU119915"));
+        aliases.set_alias("DU119916", "<Conservative>");
+        assert_eq!(
+            vec![
+                FaAlias::new("DU119915", "Growth & Income"),
+                FaAlias::new("DU119916", "<Conservative>")
+            ],
+            FaAliases::from_xml(&aliases.to_xml())?.aliases
+        );
+
+        assert!(FaGroups::from_xml(FA_TWO_PROFILES).is_err());
+        assert!(FaGroups::from_xml("<ListOfGroups><Group></ListOfGroups>").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_replace_fa_config() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let groups = FaGroups::from_xml(FA_TWO_GROUPS)?;
+        client.replace_fa_config(4, &groups)?;
+        let replace = mock.expect_message(OutgoingMessageIds::ReplaceFa, Duration::from_secs(5))?;
+        assert_eq!("1", replace[2]);
+        assert_eq!(groups, FaGroups::from_xml(&replace[3])?);
+        assert_eq!("4", replace[4]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_account_display_names() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        let messages = client.subscribe_messages();
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let aliases = FaAliases::new(vec![FaAlias::new("DU119915", "Growth")]);
+        mock.send(&["15", "1", "DU119915,DU119916"])?;
+        mock.send(&["16", "1", "3", &aliases.to_xml()])?;
+        // decoded after the messages above
+        mock.send(&["4", "2", "-1", "2104", "Market data farm connection is OK"])?;
+        messages.recv_timeout(Duration::from_secs(5))?;
+
+        let accounts = client.accounts();
+        assert_eq!(
+            vec![AccountId::new("DU119915"), AccountId::new("DU119916")],
+            accounts.accounts()
+        );
+        assert_eq!("Growth", client.display_name("DU119915"));
+        assert_eq!("DU119916", client.display_name("DU119916"));
+        assert_eq!(Some("DU119915"), accounts.resolve("Growth"));
+        assert_eq!(
+            "accounts: Growth (DU119915), DU119916",
+            accounts.to_string()
+        );
+        let position = Position::new("DU119915", simple_future(), 2.0, 3000.0);
+        assert!(position
+            .display_with(&accounts)
+            .starts_with("account: Growth, con_id: 0, symbol: ES"));
+        let change = AccountValueChange {
+            account_name: "DU119916".to_string(),
+            key: "NetLiquidation".to_string(),
+            value: "100000".to_string(),
+            ..Default::default()
+        };
+        assert!(change
+            .display_with(&accounts)
+            .starts_with("account_name: DU119916, key: NetLiquidation"));
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_wait_until_ready() -> Result<(), IBKRApiLibError> {
+        assert_eq!(
+            vec![AccountId::new("DU119915"), AccountId::new("DU119916")],
+            AccountId::parse_list(" DU119915,DU119916,")
+        );
+
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+        let short = Duration::from_millis(50);
+        assert!(client.wait_until_ready(short).is_err());
+
+        // not ready with only one of the two
+        mock.send(&["9", "1", "7"])?;
+        assert!(client.wait_until_ready(short).is_err());
+        assert!(client.managed_accounts().is_none());
+
+        mock.send(&["15", "1", "DU119915,DU119916"])?;
+        let ready = client.wait_until_ready(Duration::from_secs(5))?;
+        assert_eq!(7, ready.next_valid_id);
+        assert_eq!(ready.accounts, vec!["DU119915", "DU119916"]);
+        assert_eq!(Some(ready.accounts), client.managed_accounts());
+        client.disconnect()?;
+        assert!(client.wait_until_ready(short).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod tests {
+
+    use std::time::{Duration, Instant};
+
+    use bigdecimal::BigDecimal;
+
+    use crate::core::common::CommissionReport;
+    use crate::core::contract::Contract;
+    use crate::core::currency::Currency;
+    use crate::core::execution::Execution;
+    use crate::core::fills::{CommissionUpdate, FillCorrelator, DELIVERED_CAPACITY};
+
+    fn execution(exec_id: &str) -> Execution {
+        Execution {
+            exec_id: exec_id.to_string(),
+            side: "BOT".to_string(),
+            shares: BigDecimal::from(100),
+            price: 10.5,
+            ..Default::default()
+        }
+    }
+
+    fn report(exec_id: &str, commission: f64) -> CommissionReport {
+        CommissionReport {
+            exec_id: exec_id.to_string(),
+            commission,
+            currency: Currency::usd(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fill_correlation() {
+        let start = Instant::now();
+        let mut fills = FillCorrelator::new(Duration::from_secs(5));
+        let delivered = fills.subscribe();
+        let updates = fills.subscribe_commission_updates();
+
+        assert!(fills
+            .on_execution(Contract::default(), execution("e1"), start)
+            .is_none());
+        let fill = fills
+            .on_commission_report(report("e1", 1.25), start)
+            .unwrap();
+        assert_eq!("e1", fill.execution.exec_id);
+        assert_eq!(Some(1.25), fill.commission.map(|report| report.commission));
+
+        // the report may arrive first
+        assert!(fills
+            .on_commission_report(report("e2", 0.5), start)
+            .is_none());
+        assert!(fills
+            .on_execution(Contract::default(), execution("e2"), start)
+            .is_some());
+
+        // executions sent again are delivered once
+        assert!(fills
+            .on_execution(Contract::default(), execution("e1"), start)
+            .is_none());
+        assert!(fills
+            .on_commission_report(report("e1", 1.25), start)
+            .is_none());
+        assert_eq!(2, delivered.try_iter().count());
+
+        fills.on_execution(Contract::default(), execution("e3"), start);
+        assert!(fills.expire(start + Duration::from_secs(4)).is_empty());
+        let expired = fills.expire(start + Duration::from_secs(5));
+        assert_eq!(1, expired.len());
+        assert!(expired[0].commission.is_none());
+        assert_eq!(0, fills.waiting());
+        // a late report is delivered as an update, once, and the fill is not delivered again
+        assert!(fills
+            .on_commission_report(report("e3", 1.0), start)
+            .is_none());
+        assert!(fills
+            .on_commission_report(report("e3", 1.0), start)
+            .is_none());
+        assert_eq!(1, delivered.try_iter().count());
+        let late: Vec<CommissionUpdate> = updates.try_iter().collect();
+        assert_eq!(1, late.len());
+        assert_eq!("e3", late[0].exec_id);
+        assert_eq!(1.0, late[0].commission.commission);
+    }
+
+    #[test]
+    fn test_delivered_executions_are_bounded() {
+        let start = Instant::now();
+        let mut fills = FillCorrelator::new(Duration::from_secs(5));
+        for id in 0..=DELIVERED_CAPACITY {
+            let exec_id = format!("e{}", id);
+            fills.on_execution(Contract::default(), execution(&exec_id), start);
+            fills.on_commission_report(report(&exec_id, 1.0), start);
+        }
+        // the oldest execution is forgotten, the newest still skipped when sent again
+        assert!(fills
+            .on_execution(Contract::default(), execution("e0"), start)
+            .is_none());
+        assert_eq!(1, fills.waiting());
+        let newest = format!("e{}", DELIVERED_CAPACITY);
+        assert!(fills
+            .on_execution(Contract::default(), execution(&newest), start)
+            .is_none());
+        assert_eq!(1, fills.waiting());
+    }
+}
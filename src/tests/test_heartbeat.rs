@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crate::core::client::EClient;
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::heartbeat::{HeartbeatConfig, HeartbeatWatchdog};
+    use crate::core::messages::OutgoingMessageIds;
+    use crate::core::server_versions::MAX_CLIENT_VER;
+    use crate::core::testing::MockTws;
+    use crate::examples::defaults::DefaultWrapper;
+
+    #[test]
+    fn test_heartbeat_answered() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_to(
+            OutgoingMessageIds::ReqCurrentTime,
+            vec![vec!["49", "1", "1600000000"]],
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let config = HeartbeatConfig::new(Duration::from_millis(20), Duration::from_millis(200));
+        let mut watchdog = HeartbeatWatchdog::new(config);
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_millis(400) {
+            assert!(watchdog.poll(&mut client, Instant::now())?.is_none());
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert!(client.is_connected());
+        assert!(client.last_inbound().is_some());
+        mock.expect_message(OutgoingMessageIds::ReqCurrentTime, Duration::from_secs(1))?;
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_heartbeat_connection_lost() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let client = Arc::new(Mutex::new(EClient::new(Arc::new(Mutex::new(
+            DefaultWrapper::new(),
+        )))));
+        client
+            .lock()
+            .unwrap()
+            .connect("127.0.0.1", mock.port(), 0)?;
+
+        let lost = HeartbeatWatchdog::spawn(
+            client.clone(),
+            HeartbeatConfig::new(Duration::from_millis(20), Duration::from_millis(100)),
+        )
+        .recv_timeout(Duration::from_secs(5))?;
+        assert!(lost.silent_for >= Duration::from_millis(100));
+        assert!(lost.probes > 0);
+        assert!(!client.lock().unwrap().is_connected());
+        mock.expect_message(OutgoingMessageIds::ReqCurrentTime, Duration::from_secs(1))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+#[cfg(test)]
+mod tests {
+
+    use bigdecimal::BigDecimal;
+    use chrono::{Local, NaiveDate, TimeZone, Utc};
+
+    use crate::core::common::{BarData, BarSize, Duration};
+    use crate::core::history::{
+        bar_timestamp, check_bars, parse_head_timestamp, plan_chunks, stitch, BarIssueKind,
+        BarUpdateTracker, ConsistencyConfig,
+    };
+    use crate::core::timestamps::timestamp_from_wire;
+
+    #[test]
+    fn test_plan_chunks() {
+        let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2020, 1, 3, 12, 0, 0).unwrap();
+        let chunks = plan_chunks(start, end, &BarSize::_1Min);
+
+        assert_eq!(3, chunks.len());
+        assert_eq!("20200102 00:00:00 GMT", chunks[0].end_date_time);
+        assert_eq!(Duration::Days(1), chunks[0].duration);
+        assert_eq!("20200103 12:00:00 GMT", chunks[2].end_date_time);
+        assert!(plan_chunks(end, start, &BarSize::_1Day).is_empty());
+    }
+
+    #[test]
+    fn test_stitch() {
+        let bar = |date: &str, close: f64| BarData {
+            date: timestamp_from_wire(date),
+            close,
+            ..Default::default()
+        };
+        let start = Utc.timestamp_opt(100, 0).unwrap();
+        let end = Utc.timestamp_opt(400, 0).unwrap();
+        let bars = stitch(
+            vec![
+                vec![bar("40", 0.0), bar("100", 1.0), bar("200", 2.0)],
+                vec![bar("200", 2.5), bar("300", 3.0), bar("400", 4.0)],
+            ],
+            start,
+            end,
+        );
+        let closes: Vec<f64> = bars.iter().map(|bar| bar.close).collect();
+        assert_eq!(vec![1.0, 2.5, 3.0], closes);
+
+        // dates are read at UTC midnight wherever the tests run
+        assert_eq!(Some(86400), bar_timestamp(&bar("19700102", 0.0)));
+        assert_eq!(
+            Some(86460),
+            bar_timestamp(&bar("19700102  00:01:00 UTC", 0.0))
+        );
+        assert_eq!(None, bar_timestamp(&bar("yesterday", 0.0)));
+    }
+
+    #[test]
+    fn test_check_bars() {
+        let bar = |date: &str, open: f64, close: f64, volume: i64| BarData {
+            date: timestamp_from_wire(date),
+            open,
+            close,
+            volume: BigDecimal::from(volume),
+            ..Default::default()
+        };
+        let bars = vec![
+            bar("20200102 14:30:00 UTC", 100.0, 100.0, 10),
+            bar("20200102 14:31:00 UTC", 100.0, 101.0, 10),
+            bar("20200102 14:31:00 UTC", 101.0, 101.0, 10),
+            bar("20200102 14:34:00 UTC", 101.0, 102.0, 0),
+            bar("20200106 14:30:00 UTC", 51.0, 51.0, 10),
+            bar("never", 51.0, 51.0, 10),
+        ];
+        let issues = check_bars(&bars, &BarSize::_1Min, &ConsistencyConfig::default());
+        let kinds: Vec<(usize, BarIssueKind)> = issues
+            .into_iter()
+            .map(|issue| (issue.index, issue.kind))
+            .collect();
+        assert_eq!(
+            vec![
+                (2, BarIssueKind::Duplicate),
+                (3, BarIssueKind::ZeroVolume),
+                (3, BarIssueKind::Gap { missing_bars: 2 }),
+                (
+                    4,
+                    BarIssueKind::MissingSession {
+                        date: NaiveDate::from_ymd_opt(2020, 1, 3).unwrap()
+                    }
+                ),
+                (
+                    4,
+                    BarIssueKind::PriceJump {
+                        ratio: 0.5,
+                        split: Some(0.5)
+                    }
+                ),
+                (5, BarIssueKind::UnreadableDate),
+            ],
+            kinds
+        );
+
+        let config = ConsistencyConfig {
+            check_volume: false,
+            max_missing_bars: 2,
+            holidays: vec![NaiveDate::from_ymd_opt(2020, 1, 3).unwrap()],
+            ..Default::default()
+        };
+        let issues = check_bars(&bars[..5], &BarSize::_1Min, &config);
+        assert_eq!(2, issues.len());
+        let daily = vec![
+            bar("20200102", 100.0, 100.0, 10),
+            bar("20200106", 110.0, 110.0, 10),
+            bar("20200103", 110.0, 110.0, 10),
+        ];
+        let issues = check_bars(&daily, &BarSize::_1Day, &ConsistencyConfig::default());
+        assert_eq!(
+            vec![
+                BarIssueKind::MissingSession {
+                    date: NaiveDate::from_ymd_opt(2020, 1, 3).unwrap()
+                },
+                BarIssueKind::OutOfOrder
+            ],
+            issues
+                .into_iter()
+                .map(|issue| issue.kind)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_bar_update_tracker() {
+        let bar = |date: &str, close| BarData {
+            date: timestamp_from_wire(date),
+            close,
+            ..Default::default()
+        };
+        let mut tracker = BarUpdateTracker::new();
+
+        let updates = tracker.on_update(bar("20200901 10:00:00", 1.0));
+        assert_eq!(1, updates.len());
+        assert!(!updates[0].is_final());
+        let updates = tracker.on_update(bar("20200901 10:00:00", 1.5));
+        assert_eq!(1, updates.len());
+        assert_eq!(1.5, updates[0].bar().close);
+
+        let updates = tracker.on_update(bar("20200901 10:05:00", 2.0));
+        assert_eq!(2, updates.len());
+        assert!(updates[0].is_final());
+        assert_eq!(1.5, updates[0].bar().close);
+        assert_eq!(
+            timestamp_from_wire("20200901 10:05:00"),
+            updates[1].bar().date
+        );
+
+        let last = tracker.finish().unwrap();
+        assert_eq!(2.0, last.close);
+        assert!(tracker.current().is_none());
+    }
+
+    #[test]
+    fn test_parse_head_timestamp() {
+        let expected = Utc.with_ymd_and_hms(2004, 1, 23, 14, 30, 0).unwrap();
+        assert_eq!(Some(expected), parse_head_timestamp("1074868200"));
+        assert_eq!(Some(expected), parse_head_timestamp("20040123-14:30:00"));
+        // 14:30 in New York is 19:30 UTC in January
+        assert_eq!(
+            Utc.with_ymd_and_hms(2004, 1, 23, 19, 30, 0).single(),
+            parse_head_timestamp("20040123 14:30:00 US/Eastern")
+        );
+        assert_eq!(
+            Local
+                .with_ymd_and_hms(2004, 1, 23, 14, 30, 0)
+                .single()
+                .map(|time| time.with_timezone(&Utc)),
+            parse_head_timestamp("20040123  14:30:00")
+        );
+        assert_eq!(None, parse_head_timestamp("yesterday"));
+    }
+}
@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+
+    use bigdecimal::BigDecimal;
+    use chrono::{TimeZone, Utc};
+
+    use crate::core::common::{BarData, BarSize, WhatToShow};
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::history_cache::{completed_end, CachedSeries, HistoryCache, HistoryCacheKey};
+    use crate::core::timestamps::timestamp_from_seconds;
+
+    fn bar(time: i64, close: f64) -> BarData {
+        BarData::new(
+            timestamp_from_seconds(time),
+            1.0,
+            2.0,
+            0.5,
+            close,
+            BigDecimal::from(10),
+            1,
+            1.25,
+        )
+    }
+
+    #[test]
+    fn test_cached_series_missing_ranges() {
+        let mut series = CachedSeries::new();
+        series.insert(100, 200, vec![bar(100, 1.0), bar(160, 2.0)]);
+        series.insert(300, 400, vec![bar(300, 3.0)]);
+        series.insert(200, 250, vec![bar(200, 2.5), bar(160, 2.1)]);
+
+        assert_eq!(&[(100, 250), (300, 400)], series.ranges());
+        let start = Utc.timestamp_opt(50, 0).unwrap();
+        let end = Utc.timestamp_opt(500, 0).unwrap();
+        assert_eq!(
+            vec![(50, 100), (250, 300), (400, 500)],
+            series.missing(start, end)
+        );
+        assert!(series
+            .missing(
+                Utc.timestamp_opt(120, 0).unwrap(),
+                Utc.timestamp_opt(240, 0).unwrap()
+            )
+            .is_empty());
+        let closes: Vec<f64> = series.bars(start, end).iter().map(|b| b.close).collect();
+        assert_eq!(vec![1.0, 2.1, 2.5, 3.0], closes);
+    }
+
+    #[test]
+    fn test_completed_end() {
+        let bars = vec![bar(0, 1.0), bar(3540, 2.0)];
+        // a range in the past is complete
+        assert_eq!(
+            3600,
+            completed_end(0, 3600, &bars, &BarSize::_1Min, 100_000)
+        );
+        // nothing after now, nor after the start of the minute forming at now
+        assert_eq!(
+            960,
+            completed_end(0, 3600, &bars[..1], &BarSize::_1Min, 1000)
+        );
+        // the day bar of today is still forming
+        let today = vec![bar(0, 1.0), bar(86_400, 2.0)];
+        assert_eq!(
+            86_400,
+            completed_end(0, 200_000, &today, &BarSize::_1Day, 90_000)
+        );
+        // a month bar may still be forming for a month
+        assert_eq!(
+            0,
+            completed_end(0, 10, &bars[..1], &BarSize::_1Month, 864_000)
+        );
+
+        let mut series = CachedSeries::new();
+        series.insert(0, 0, vec![bar(0, 1.0)]);
+        assert!(series.ranges().is_empty());
+        assert_eq!(
+            1,
+            series
+                .bars(
+                    Utc.timestamp_opt(0, 0).unwrap(),
+                    Utc.timestamp_opt(60, 0).unwrap()
+                )
+                .len()
+        );
+    }
+
+    #[test]
+    fn test_history_cache_round_trip() -> Result<(), IBKRApiLibError> {
+        let dir = std::env::temp_dir().join(format!("twsapi_history_cache_{}", std::process::id()));
+        let cache = HistoryCache::new(&dir);
+        let key = HistoryCacheKey::new(265598, &BarSize::_1Min, &WhatToShow::Trades, 1);
+        assert!(cache.load(&key)?.ranges().is_empty());
+
+        let mut series = CachedSeries::new();
+        series.insert(0, 120, vec![bar(0, 0.1 + 0.2), bar(60, 3.0)]);
+        cache.save(&key, &series)?;
+
+        let loaded = cache.load(&key)?;
+        assert_eq!(&[(0, 120)], loaded.ranges());
+        let bars = loaded.bars(
+            Utc.timestamp_opt(0, 0).unwrap(),
+            Utc.timestamp_opt(120, 0).unwrap(),
+        );
+        assert_eq!(2, bars.len());
+        assert_eq!(0.1 + 0.2, bars[0].close);
+        assert_eq!(1.25, bars[1].average);
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
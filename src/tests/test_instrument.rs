@@ -0,0 +1,111 @@
+#[cfg(test)]
+mod tests {
+
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    use crate::core::common::PriceIncrement;
+    use crate::core::contract::ContractDetails;
+    use crate::core::instrument::{parse_trading_hours, Instrument};
+    use crate::core::order::Action;
+    use crate::core::order_templates;
+    use crate::core::portfolio::{ClosingOrderConfig, Portfolio};
+    use crate::core::precautions::PrecautionarySettings;
+    use crate::core::risk::RiskAggregator;
+    use crate::examples::contract_samples::simple_future;
+
+    fn at(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y%m%d %H%M").unwrap()
+    }
+
+    #[test]
+    fn test_parse_trading_hours() {
+        let old = parse_trading_hours("20090507:0700-1830,1830-0230;20090508:CLOSED");
+        assert_eq!(2, old.len());
+        assert_eq!(at("20090507", "1830"), old[1].start);
+        assert_eq!(at("20090508", "0230"), old[1].end);
+
+        let new = parse_trading_hours(
+            "20180323:1700-20180324:1600;20180324:CLOSED;20180325:1700-20180326:1600",
+        );
+        assert_eq!(2, new.len());
+        assert_eq!(at("20180324", "1600"), new[0].end);
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2018, 3, 25).unwrap(),
+            new[1].start.date()
+        );
+        assert!(parse_trading_hours("").is_empty());
+    }
+
+    #[test]
+    fn test_instrument() {
+        let mut contract = simple_future();
+        contract.con_id = 495512563;
+        contract.multiplier = "50".to_string();
+        let details = ContractDetails {
+            contract,
+            min_tick: 0.25,
+            valid_exchanges: "GLOBEX,QBALGO".to_string(),
+            market_rule_ids: "67,67".to_string(),
+            time_zone_id: "US/Central".to_string(),
+            trading_hours: "20180323:1700-20180324:1600;20180325:1700-20180326:1600".to_string(),
+            liquid_hours: "20180324:0830-20180324:1500".to_string(),
+            ..Default::default()
+        };
+        let instrument = Instrument::new(details).with_market_rule(
+            67,
+            vec![
+                PriceIncrement::new(100.0, 0.5),
+                PriceIncrement::new(0.0, 0.05),
+            ],
+        );
+
+        assert_eq!(vec![67, 67], instrument.market_rule_ids());
+        assert_eq!(Some(67), instrument.market_rule_id("QBALGO"));
+        assert_eq!(None, instrument.market_rule_id("NYMEX"));
+        assert_eq!(0.05, instrument.tick_size("GLOBEX", 12.0));
+        assert_eq!(0.5, instrument.tick_size("GLOBEX", 4000.0));
+        assert_eq!(0.25, instrument.tick_size("NYMEX", 4000.0));
+        assert_eq!(0.35, instrument.round_to_tick("GLOBEX", 0.33));
+        assert_eq!(4000.5, instrument.round_to_tick("GLOBEX", 4000.4));
+        assert_eq!(400_000.0, instrument.notional(2.0, 4000.0));
+
+        let buy = order_templates::instrument_limit(&instrument, Action::Buy, 1.0, 4000.7).unwrap();
+        assert_eq!(4000.5, buy.lmt_price);
+        let sell =
+            order_templates::instrument_limit(&instrument, Action::Sell, 1.0, 4000.2).unwrap();
+        assert_eq!(4000.5, sell.lmt_price);
+        assert!(
+            order_templates::instrument_limit(&instrument, Action::Buy, 1.0, f64::NAN).is_err()
+        );
+
+        let settings = PrecautionarySettings::new(None, Some(250_000.0), None);
+        assert!(settings
+            .check_instrument(1, &instrument, &buy, None)
+            .is_ok());
+        // 4000.2 is off the 0.5 tick, 2 contracts of 50 are over the value limit
+        let off_tick = order_templates::limit(Action::Buy, 1.0, 4000.2).unwrap();
+        assert!(settings
+            .check_instrument(2, &instrument, &off_tick, None)
+            .is_err());
+        let too_big = order_templates::limit(Action::Buy, 2.0, 4000.5).unwrap();
+        assert!(settings
+            .check_instrument(3, &instrument, &too_big, None)
+            .is_err());
+
+        // the multiplier of the instrument scales a position whose contract lacks one
+        let mut position_contract = instrument.contract().clone();
+        position_contract.multiplier = String::new();
+        let mut portfolio = Portfolio::new(ClosingOrderConfig::default());
+        portfolio.on_position("DU1", position_contract, 2.0, 200_000.0);
+        let mut risk = RiskAggregator::new();
+        risk.track_instrument(10, &instrument);
+        assert!((risk.report(&portfolio).total.delta - 100.0).abs() < 1e-9);
+
+        assert!(instrument.is_trading(at("20180324", "1000")));
+        assert!(!instrument.is_trading(at("20180324", "1630")));
+        assert!(instrument.is_liquid(at("20180324", "1000")));
+        assert!(!instrument.is_liquid(at("20180323", "2000")));
+        let next = instrument.next_session(at("20180324", "1630")).unwrap();
+        assert_eq!(at("20180325", "1700"), next.start);
+    }
+}
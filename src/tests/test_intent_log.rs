@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use bigdecimal::BigDecimal;
+
+    use crate::core::client::EClient;
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::execution::Execution;
+    use crate::core::intent_log::{
+        read_intents, reconcile, IntentKind, IntentStatus, OrderIntentLog, OrderSnapshot,
+    };
+    use crate::core::messages::OutgoingMessageIds;
+    use crate::core::order::Order;
+    use crate::core::server_versions::MAX_CLIENT_VER;
+    use crate::core::testing::MockTws;
+    use crate::examples::contract_samples::simple_future;
+    use crate::examples::defaults::DefaultWrapper;
+    use crate::examples::order_samples::limit_order;
+
+    #[test]
+    fn test_intent_log_reconciliation() -> Result<(), IBKRApiLibError> {
+        let path = std::env::temp_dir().join(format!("twsapi_intents_{}.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.set_intent_log(Some(OrderIntentLog::open(&path)?));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let mut order = limit_order("BUY", 2.0, 101.5);
+        order.order_ref = "tab\there".to_string();
+        for order_id in 1..=4 {
+            client.place_order(order_id, &simple_future(), &order)?;
+        }
+        // a what-if order is not logged
+        let what_if = Order {
+            what_if: true,
+            ..order.clone()
+        };
+        client.place_order(6, &simple_future(), &what_if)?;
+        client.cancel_order(2)?;
+        client.cancel_order(3)?;
+        mock.expect_message(OutgoingMessageIds::CancelOrder, Duration::from_secs(5))?;
+        client.disconnect()?;
+
+        // a crash while appending
+        OpenOptions::new()
+            .append(true)
+            .open(&path)?
+            .write_all(b"1600000000\tPLACE\t5")?;
+        let intents = read_intents(&path)?;
+        assert_eq!(6, intents.len());
+        assert!(intents.iter().all(|intent| intent.order_id != 6));
+        assert_eq!(IntentKind::Cancel, intents[5].kind);
+        assert_eq!("tab\there", intents[0].order_ref);
+        assert_eq!(101.5, intents[0].lmt_price);
+
+        let mut snapshot = OrderSnapshot::new();
+        snapshot.on_order_status(1, "Submitted");
+        snapshot.on_order_status(2, "Cancelled");
+        snapshot.on_order_status(3, "Submitted");
+        snapshot.on_execution(&Execution {
+            order_id: 1,
+            shares: BigDecimal::from(1),
+            ..Default::default()
+        });
+
+        let report = reconcile(&intents, &snapshot);
+        let statuses: Vec<(i32, IntentKind, IntentStatus)> = report
+            .intents
+            .iter()
+            .map(|reconciled| {
+                (
+                    reconciled.intent.order_id,
+                    reconciled.intent.kind,
+                    reconciled.status,
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (1, IntentKind::Place, IntentStatus::Confirmed),
+                (4, IntentKind::Place, IntentStatus::Unknown),
+                (2, IntentKind::Cancel, IntentStatus::Confirmed),
+                (3, IntentKind::Cancel, IntentStatus::Failed),
+            ],
+            statuses
+        );
+        assert!(!report.is_clean());
+
+        // filled orders took effect and are no longer working
+        let mut snapshot = OrderSnapshot::new();
+        for order_id in 1..=4 {
+            snapshot.on_order_status(order_id, "Filled");
+        }
+        assert!(reconcile(&intents, &snapshot).is_clean());
+
+        OrderIntentLog::open(&path)?.clear()?;
+        assert!(read_intents(&path)?.is_empty());
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+
+    use std::time::{Duration, Instant};
+
+    use crate::core::latency::{LatencySummary, OrderLatencyRecorder};
+
+    #[test]
+    fn test_latency_summary() {
+        let samples = (1..=100).map(Duration::from_millis).collect();
+        let summary = LatencySummary::from_samples(samples);
+        assert_eq!(100, summary.count);
+        assert_eq!(Duration::from_millis(50), summary.p50);
+        assert_eq!(Duration::from_millis(90), summary.p90);
+        assert_eq!(Duration::from_millis(99), summary.p99);
+        assert_eq!(Duration::from_millis(100), summary.max);
+        assert_eq!(
+            LatencySummary::default(),
+            LatencySummary::from_samples(vec![])
+        );
+    }
+
+    #[test]
+    fn test_order_latency_recorder() {
+        let start = Instant::now();
+        let mut recorder = OrderLatencyRecorder::new(2);
+        recorder.record_submission(
+            1,
+            start,
+            start + Duration::from_micros(10),
+            start + Duration::from_micros(30),
+        );
+        recorder.record_status(1, start + Duration::from_millis(5));
+        recorder.record_status(1, start + Duration::from_millis(9));
+
+        let latency = recorder.get(1).unwrap();
+        assert_eq!(Duration::from_micros(10), latency.encode);
+        assert_eq!(Duration::from_micros(30), latency.write);
+        assert_eq!(Some(Duration::from_millis(5)), latency.first_status);
+
+        recorder.record_submission(2, start, start, start);
+        recorder.record_submission(3, start, start, start);
+        assert!(recorder.get(1).is_none());
+        let stats = recorder.stats();
+        assert_eq!(2, stats.write.count);
+        assert_eq!(0, stats.first_status.count);
+    }
+}
@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use crate::core::client::EClient;
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::live_gateway::{
+        is_paper_account, live_gateway_config, live_gateway_contract, smoke_test, TEST_HOST_VAR,
+    };
+    use crate::examples::defaults::DefaultWrapper;
+
+    #[test]
+    fn test_is_paper_account() {
+        assert!(is_paper_account("DU1234567"));
+        assert!(is_paper_account("DF1234567"));
+        assert!(!is_paper_account("U1234567"));
+    }
+
+    /// Runs against the gateway of IBKR_TEST_HOST, and passes without it
+    #[test]
+    fn test_live_gateway() -> Result<(), IBKRApiLibError> {
+        let config = match live_gateway_config() {
+            Some(config) => config,
+            None => {
+                eprintln!(
+                    "{} is not set, skipping the live gateway test",
+                    TEST_HOST_VAR
+                );
+                return Ok(());
+            }
+        };
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        let contract = live_gateway_contract();
+        let report = smoke_test(&mut client, &config, &contract, Duration::from_secs(20))?;
+        eprintln!("{}", report);
+
+        assert!(report.server_version > 0);
+        assert!(!report.accounts.is_empty());
+        assert_eq!(contract.symbol, report.contract.symbol);
+        assert!(report.contract.con_id > 0);
+        assert!(!client.is_connected());
+        Ok(())
+    }
+}
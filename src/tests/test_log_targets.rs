@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+
+    use std::thread;
+    use std::time::Duration;
+
+    use log::LevelFilter;
+
+    use crate::core::log_targets::{
+        self, clear_target_level, set_target_level, set_target_level_for, target_level,
+    };
+
+    #[test]
+    fn test_target_levels() {
+        set_target_level("twsapi", LevelFilter::Warn);
+        set_target_level(log_targets::PACING, LevelFilter::Trace);
+
+        assert_eq!(Some(LevelFilter::Trace), target_level(log_targets::PACING));
+        assert_eq!(Some(LevelFilter::Warn), target_level(log_targets::READER));
+        assert_eq!(None, target_level("twsapi_other"));
+        assert_eq!(LevelFilter::Trace, log::max_level());
+
+        clear_target_level(log_targets::PACING);
+        assert_eq!(Some(LevelFilter::Warn), target_level(log_targets::PACING));
+        clear_target_level("twsapi");
+        assert_eq!(None, target_level(log_targets::PACING));
+        let max_level = log::max_level();
+
+        // the longer call replaces the shorter, whose expiry then leaves it alone
+        let millis = Duration::from_millis;
+        set_target_level_for(log_targets::DECODER, LevelFilter::Debug, millis(50));
+        set_target_level_for(log_targets::DECODER, LevelFilter::Trace, millis(300));
+        thread::sleep(millis(150));
+        assert_eq!(Some(LevelFilter::Trace), target_level(log_targets::DECODER));
+        thread::sleep(millis(300));
+        assert_eq!(None, target_level(log_targets::DECODER));
+        assert_eq!(max_level, log::max_level());
+
+        // an expired level gives way to the one set before
+        set_target_level(log_targets::ORDERS, LevelFilter::Info);
+        set_target_level_for(log_targets::ORDERS, LevelFilter::Trace, millis(50));
+        assert_eq!(LevelFilter::Trace, log::max_level());
+        thread::sleep(millis(150));
+        assert_eq!(Some(LevelFilter::Info), target_level(log_targets::ORDERS));
+        assert_eq!(max_level.max(LevelFilter::Info), log::max_level());
+        clear_target_level(log_targets::ORDERS);
+        assert_eq!(max_level, log::max_level());
+    }
+}
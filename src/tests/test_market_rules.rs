@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::core::common::PriceIncrement;
+    use crate::core::contract::ContractDetails;
+    use crate::core::exchange::Exchange;
+    use crate::core::market_rules::{round_price, MarketRules, RoundDirection};
+    use crate::core::order::Action;
+    use crate::examples::contract_samples::simple_future;
+
+    fn details() -> ContractDetails {
+        ContractDetails {
+            contract: simple_future(),
+            min_tick: 0.25,
+            valid_exchanges: "GLOBEX,QBALGO".to_string(),
+            market_rule_ids: "67,68".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_round_price() {
+        assert_eq!(0.3, round_price(0.1 + 0.2, 0.05, RoundDirection::Up));
+        assert_eq!(0.3, round_price(0.1 + 0.2, 0.05, RoundDirection::Down));
+        assert_eq!(10.25, round_price(10.3, 0.25, RoundDirection::Nearest));
+        assert_eq!(10.5, round_price(10.3, 0.25, RoundDirection::Up));
+        assert_eq!(10.25, round_price(10.49, 0.25, RoundDirection::Down));
+        assert_eq!(-10.25, round_price(-10.3, 0.25, RoundDirection::Up));
+        assert_eq!(10.3, round_price(10.3, 0.0, RoundDirection::Up));
+        assert_eq!(
+            RoundDirection::Down,
+            RoundDirection::for_limit(&Action::Buy)
+        );
+        assert_eq!(RoundDirection::Up, RoundDirection::for_limit(&Action::Sell));
+    }
+
+    #[test]
+    fn test_market_rules() {
+        let details = details();
+        let mut rules = MarketRules::new();
+        assert_eq!(vec![67, 68], rules.missing(&details));
+        // without the rule the min_tick applies
+        assert_eq!(
+            4000.25,
+            rules.round_to_tick(&details, 4000.3, RoundDirection::Nearest)
+        );
+
+        rules.insert(
+            67,
+            vec![
+                PriceIncrement::new(1000.0, 0.5),
+                PriceIncrement::new(0.0, 0.05),
+            ],
+        );
+        assert_eq!(vec![68], rules.missing(&details));
+        assert_eq!(0.05, rules.tick_size(&details, 12.0));
+        assert_eq!(
+            12.35,
+            rules.round_to_tick(&details, 12.33, RoundDirection::Up)
+        );
+        assert_eq!(
+            12.3,
+            rules.round_to_tick(&details, 12.33, RoundDirection::Down)
+        );
+        assert_eq!(
+            4000.5,
+            rules.round_to_tick(&details, 4000.3, RoundDirection::Up)
+        );
+        assert_eq!(
+            4000.0,
+            rules.round_to_tick(&details, 4000.3, RoundDirection::Down)
+        );
+        assert_eq!(
+            4000.5,
+            rules.round_to_tick(&details, 4000.5, RoundDirection::Up)
+        );
+
+        // a contract on an exchange it does not list falls back to the min_tick
+        let mut elsewhere = details.clone();
+        elsewhere.contract.exchange = Exchange::Nymex;
+        assert_eq!(
+            4000.25,
+            rules.round_to_tick(&elsewhere, 4000.3, RoundDirection::Down)
+        );
+    }
+}
@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod tests {
+
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::core::errors::MessageError;
+    use crate::core::message_queue::{message_queue, QueueConfig, QueuePolicy};
+
+    fn tick(price: &str) -> Result<String, MessageError> {
+        Ok(format!("1\u{0}6\u{0}42\u{0}1\u{0}{}\u{0}", price))
+    }
+
+    fn market_depth() -> Result<String, MessageError> {
+        Ok("12\u{0}1\u{0}42\u{0}0\u{0}0\u{0}1\u{0}1.0\u{0}100\u{0}".to_string())
+    }
+
+    fn order_status() -> Result<String, MessageError> {
+        Ok("3\u{0}7\u{0}Filled\u{0}".to_string())
+    }
+
+    #[test]
+    fn test_queue_drop_oldest_market_data() {
+        let (sender, receiver) =
+            message_queue(QueueConfig::new(2, QueuePolicy::DropOldestMarketData));
+        sender.send(order_status()).unwrap();
+        sender.send(tick("1.0")).unwrap();
+        sender.send(tick("2.0")).unwrap();
+
+        let stats = receiver.monitor().stats();
+        assert_eq!(2, stats.depth);
+        assert_eq!((1, 1), (stats.full, stats.dropped));
+        assert_eq!(order_status(), receiver.recv().unwrap());
+        assert_eq!(tick("2.0"), receiver.recv().unwrap());
+        drop(sender);
+        assert!(receiver.recv().is_err());
+    }
+
+    #[test]
+    fn test_queue_drops_tick_replaced_by_queued_tick() {
+        let (sender, receiver) =
+            message_queue(QueueConfig::new(3, QueuePolicy::DropOldestMarketData));
+        sender.send(tick("1.0")).unwrap();
+        sender.send(order_status()).unwrap();
+        sender.send(tick("2.0")).unwrap();
+        // the first tick is replaced by the second, so an order status makes room by dropping it
+        sender.send(order_status()).unwrap();
+        assert_eq!(1, receiver.monitor().stats().dropped);
+        assert_eq!(order_status(), receiver.recv().unwrap());
+        assert_eq!(tick("2.0"), receiver.recv().unwrap());
+        assert_eq!(order_status(), receiver.recv().unwrap());
+
+        // with the replaced tick gone, a full queue of distinct ticks blocks
+        sender.send(tick("3.0")).unwrap();
+        sender.send(order_status()).unwrap();
+        sender.send(order_status()).unwrap();
+        let blocked = thread::spawn(move || sender.send(order_status()).is_ok());
+        thread::sleep(Duration::from_millis(50));
+        assert!(!blocked.is_finished());
+        assert_eq!(tick("3.0"), receiver.recv().unwrap());
+        assert!(blocked.join().unwrap());
+    }
+
+    #[test]
+    fn test_queue_keeps_market_data_nothing_replaces() {
+        let (sender, receiver) =
+            message_queue(QueueConfig::new(2, QueuePolicy::DropOldestMarketData));
+        sender.send(market_depth()).unwrap();
+        // a tick of another tick type does not replace the bid
+        sender.send(tick("1.0")).unwrap();
+        let ask = Ok("1\u{0}6\u{0}42\u{0}2\u{0}1.5\u{0}".to_string());
+        let blocked = thread::spawn(move || sender.send(ask).is_ok());
+        thread::sleep(Duration::from_millis(50));
+        assert!(!blocked.is_finished());
+        assert_eq!(0, receiver.monitor().stats().dropped);
+
+        assert_eq!(market_depth(), receiver.recv().unwrap());
+        assert!(blocked.join().unwrap());
+        assert_eq!(tick("1.0"), receiver.recv().unwrap());
+    }
+
+    #[test]
+    fn test_queue_block_and_error() {
+        let (sender, receiver) = message_queue(QueueConfig::new(1, QueuePolicy::Block));
+        sender.send(order_status()).unwrap();
+        let blocked = thread::spawn(move || sender.send(order_status()).is_ok());
+        thread::sleep(Duration::from_millis(50));
+        assert!(!blocked.is_finished());
+        assert!(receiver.recv().is_ok());
+        assert!(blocked.join().unwrap());
+        assert_eq!(1, receiver.monitor().stats().max_depth);
+
+        let (sender, receiver) = message_queue(QueueConfig::new(1, QueuePolicy::Error));
+        sender.send(tick("1.0")).unwrap();
+        sender.send(tick("2.0")).unwrap();
+        assert!(sender.has_overflowed());
+        sender.send(tick("3.0")).unwrap();
+        assert_eq!(tick("1.0"), receiver.recv().unwrap());
+        assert_eq!(
+            Err(MessageError::QueueOverflow { capacity: 1 }),
+            receiver.recv().unwrap()
+        );
+        drop(receiver);
+        assert!(sender.send(tick("4.0")).is_err());
+    }
+}
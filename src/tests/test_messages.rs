@@ -2,7 +2,7 @@
 mod tests {
 
     use crate::core::common::{TickByTickType, UNSET_DOUBLE, UNSET_INTEGER};
-    use crate::core::errors::IBKRApiLibError;
+    use crate::core::errors::{IBKRApiLibError, MessageError};
     use crate::core::messages::{
         make_field, make_field_handle_empty, make_message, read_fields, read_msg,
         OutgoingMessageIds,
@@ -92,4 +92,40 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_make_message_utf8() -> Result<(), IBKRApiLibError> {
+        let text = "Société Générale\u{0}Zürich\u{0}";
+        let msg = make_message(text)?;
+        assert_eq!(&i32::to_be_bytes(text.len() as i32), &msg[0..4]);
+        assert_eq!(29, text.len());
+        assert_eq!((29, text.to_string(), Vec::<u8>::new()), read_msg(&msg)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_msg_bad_frames() -> Result<(), IBKRApiLibError> {
+        let msg_bytes: Vec<u8> = vec![0, 0, 0, 3, 52, 0xFF, 0, 0, 0, 0, 2, 49, 0];
+        assert_eq!(
+            Err(MessageError::InvalidUtf8 {
+                size: 3,
+                valid_up_to: 1
+            }),
+            read_msg(&msg_bytes)
+        );
+        assert_eq!(
+            (2, "1\u{0}".to_owned(), Vec::<u8>::new()),
+            read_msg(&msg_bytes[7..])?
+        );
+
+        assert_eq!(
+            Err(MessageError::InvalidLength(-1)),
+            read_msg(&[0xFF, 0xFF, 0xFF, 0xFF, 49])
+        );
+        assert_eq!(
+            Err(MessageError::InvalidLength(0x1000000)),
+            read_msg(&[1, 0, 0, 0, 49])
+        );
+        Ok(())
+    }
 }
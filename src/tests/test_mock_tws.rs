@@ -0,0 +1,938 @@
+#[cfg(test)]
+mod tests {
+
+    use std::io::Write;
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::mpsc::RecvTimeoutError;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use bigdecimal::BigDecimal;
+    use chrono::{TimeZone, Utc};
+
+    use crate::core::client::EClient;
+    use crate::core::common::{
+        BarSize, Duration as HistoricalDuration, FamilyCode, HistogramEntry, Right, SecType,
+        WhatToShow,
+    };
+    use crate::core::connection::{ConnectionConfig, ReconnectPolicy};
+    use crate::core::contract::{Contract, ContractDetails, DeltaNeutralContract};
+    use crate::core::currency::Currency;
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::exchange::Exchange;
+    use crate::core::history_cache::{HistoryCache, HistoryCacheKey};
+    use crate::core::market_rules::RoundDirection;
+    use crate::core::messages::OutgoingMessageIds;
+    use crate::core::order::SoftDollarTier;
+    use crate::core::reader::{is_priority_message, DispatchMode};
+    use crate::core::server_versions::MAX_CLIENT_VER;
+    use crate::core::testing::MockTws;
+    use crate::core::tick_by_tick::{LastTick, MidPointTick, TickByTickEvent};
+    use crate::core::timestamps::timestamp_from_wire;
+    use crate::examples::contract_samples::simple_future;
+    use crate::examples::defaults::DefaultWrapper;
+    use crate::examples::order_samples::limit_order;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn test_mock_tws_scripted_session() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_to(OutgoingMessageIds::StartApi, vec![vec!["9", "1", "100"]]);
+        mock.respond_with(
+            OutgoingMessageIds::ReqMktData,
+            Box::new(|request| {
+                vec![vec![
+                    "4".to_string(),
+                    "2".to_string(),
+                    request[2].clone(),
+                    "200".to_string(),
+                    "No security definition has been found for the request".to_string(),
+                ]]
+            }),
+        );
+
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        let messages = client.subscribe_messages();
+        client.connect("127.0.0.1", mock.port(), 7)?;
+        assert_eq!(MAX_CLIENT_VER, client.server_version());
+
+        let start_api = mock.expect_message(OutgoingMessageIds::StartApi, TIMEOUT)?;
+        assert_eq!("7", start_api[2]);
+
+        client.req_mkt_data(42, &simple_future(), "", false, false, vec![])?;
+        let request = mock.expect_message(OutgoingMessageIds::ReqMktData, TIMEOUT)?;
+        assert_eq!("42", request[2]);
+
+        let message = messages.recv_timeout(TIMEOUT)?;
+        assert_eq!(42, message.req_id);
+        assert_eq!(200, message.code);
+        assert_eq!(2, mock.received().len());
+
+        assert!(mock
+            .expect_message(OutgoingMessageIds::ReqMktData, Duration::from_millis(10))
+            .is_err());
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_priority_orders_dispatch() -> Result<(), IBKRApiLibError> {
+        assert!(is_priority_message("3\u{0}1\u{0}Filled"));
+        assert!(is_priority_message("11\u{0}-1"));
+        assert!(!is_priority_message("1\u{0}6\u{0}42"));
+        assert!(!is_priority_message(""));
+
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.set_dispatch_mode(DispatchMode::PriorityOrders);
+        let messages = client.subscribe_messages();
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        mock.send(&[
+            "3", "7", "Filled", "100", "0", "10.5", "1", "0", "10.5", "0", "", "0",
+        ])?;
+        mock.send(&["4", "2", "42", "200", "No security definition"])?;
+        let message = messages.recv_timeout(TIMEOUT)?;
+        assert_eq!(42, message.req_id);
+        assert!(client.is_connected());
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_connect_with_config() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let config = ConnectionConfig::new("127.0.0.1", mock.port(), 3)
+            .with_connect_timeout(TIMEOUT)
+            .with_read_timeout(TIMEOUT)
+            .with_connect_options("+PACEAPI")
+            .with_reconnect(ReconnectPolicy::new(
+                2,
+                Duration::from_millis(10),
+                Duration::from_millis(10),
+            ))
+            .with_max_message_rate(4);
+
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect_with(&config)?;
+        assert_eq!(
+            Some(format!("v100..{} +PACEAPI", MAX_CLIENT_VER)),
+            mock.handshake()
+        );
+        assert_eq!(Some(&config), client.connection_config());
+        mock.expect_message(OutgoingMessageIds::StartApi, TIMEOUT)?;
+
+        // with start_api there are 4 messages in the first second, so the last request waits
+        let started = Instant::now();
+        for _ in 0..4 {
+            client.req_current_time()?;
+        }
+        assert!(started.elapsed() >= Duration::from_millis(500));
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_place_order_paced() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let config = ConnectionConfig::new("127.0.0.1", mock.port(), 0).with_max_message_rate(2);
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect_with(&config)?;
+
+        // with start_api there are 2 messages in the first second, so the second order waits
+        let started = Instant::now();
+        client.place_order(1, &simple_future(), &limit_order("BUY", 1.0, 3000.0))?;
+        client.place_order(2, &simple_future(), &limit_order("BUY", 1.0, 3000.0))?;
+        assert!(started.elapsed() >= Duration::from_millis(500));
+        mock.expect_message(OutgoingMessageIds::PlaceOrder, TIMEOUT)?;
+        mock.expect_message(OutgoingMessageIds::PlaceOrder, TIMEOUT)?;
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_mock_tws_serves_after_failed_connection() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        // not the API prefix, so the mock drops the connection
+        TcpStream::connect(("127.0.0.1", mock.port() as u16))?.write_all(b"GET ")?;
+
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+        mock.expect_message(OutgoingMessageIds::StartApi, TIMEOUT)?;
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_connect_with_retries() -> Result<(), IBKRApiLibError> {
+        let port = TcpListener::bind("127.0.0.1:0")?.local_addr()?.port() as u32;
+        let config = ConnectionConfig::new("127.0.0.1", port, 0).with_reconnect(
+            ReconnectPolicy::new(3, Duration::from_millis(10), Duration::from_millis(100)),
+        );
+        assert_eq!(Duration::from_millis(20), config.reconnect.delay_before(2));
+        assert_eq!(Duration::from_millis(100), config.reconnect.delay_before(5));
+
+        // nothing listens on the port any more, so every attempt is refused
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        let started = Instant::now();
+        assert!(client.connect_with(&config).is_err());
+        assert!(started.elapsed() >= Duration::from_millis(30));
+        assert!(!client.is_connected());
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_id_in_use_retry() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_to(OutgoingMessageIds::StartApi, vec![vec!["9", "1", "100"]]);
+        mock.reject_client_id(7);
+        mock.reject_client_id(8);
+
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        let config = ConnectionConfig::new("127.0.0.1", mock.port(), 7)
+            .with_read_timeout(TIMEOUT)
+            .with_client_id_retries(1);
+        assert!(client.connect_with(&config).is_err());
+        assert!(!client.is_connected());
+
+        client.connect_with(&config.with_client_id_retries(3))?;
+        assert!(client.is_connected());
+        assert_eq!(9, client.client_id());
+        let client_ids: Vec<String> = mock
+            .received()
+            .iter()
+            .filter(|fields| fields[0] == (OutgoingMessageIds::StartApi as i32).to_string())
+            .map(|fields| fields[2].clone())
+            .collect();
+        assert_eq!(vec!["7", "8", "7", "8", "9"], client_ids);
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_bar_updates() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+        let updates = client.subscribe_bar_updates(5);
+
+        for (date, close) in [("20200901 10:00:00", "1.5"), ("20200901 10:00:00", "1.75")] {
+            mock.send(&["90", "5", "3", date, "1", close, "2", "1", "1.5", "10"])?;
+        }
+        mock.send(&[
+            "90",
+            "5",
+            "1",
+            "20200901 10:05:00",
+            "2",
+            "2",
+            "2",
+            "2",
+            "2",
+            "1",
+        ])?;
+        let received: Vec<_> = (0..4)
+            .map(|_| updates.recv_timeout(TIMEOUT))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(
+            vec![false, false, true, false],
+            received
+                .iter()
+                .map(|update| update.is_final())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(1.75, received[2].bar().close);
+
+        assert_eq!(
+            timestamp_from_wire("20200901 10:05:00"),
+            received[3].bar().date
+        );
+
+        // the bar in progress is not sent again as Final
+        client.cancel_historical_data(5)?;
+        assert_eq!(
+            Err(RecvTimeoutError::Disconnected),
+            updates
+                .recv_timeout(TIMEOUT)
+                .map(|update| update.is_final())
+        );
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_tick_by_tick_events() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+        let ticks = client.subscribe_tick_by_tick(3);
+
+        mock.send(&[
+            "99",
+            "3",
+            "2",
+            "1600000000",
+            "4000.25",
+            "2",
+            "3",
+            "GLOBEX",
+            "I",
+        ])?;
+        mock.send(&[
+            "99",
+            "3",
+            "3",
+            "1600000001",
+            "4000",
+            "4000.5",
+            "10",
+            "7",
+            "2",
+        ])?;
+        mock.send(&["99", "3", "4", "1600000002", "4000.25"])?;
+
+        let last = ticks.recv_timeout(TIMEOUT)?;
+        assert_eq!(
+            TickByTickEvent::AllLast(LastTick {
+                time: 1600000000,
+                price: 4000.25,
+                size: BigDecimal::from(2),
+                exchange: "GLOBEX".to_string(),
+                conditions: "I".to_string(),
+                past_limit: true,
+                unreported: true,
+            }),
+            last
+        );
+        match ticks.recv_timeout(TIMEOUT)? {
+            TickByTickEvent::BidAsk(tick) => {
+                assert_eq!(BigDecimal::from(7), tick.ask_size);
+                assert!(!tick.bid_past_low);
+                assert!(tick.ask_past_high);
+            }
+            other => panic!("expected a BidAsk tick, got {}", other),
+        }
+        assert_eq!(
+            TickByTickEvent::MidPoint(MidPointTick {
+                time: 1600000002,
+                mid_point: 4000.25
+            }),
+            ticks.recv_timeout(TIMEOUT)?
+        );
+
+        client.cancel_tick_by_tick_data(3)?;
+        assert!(ticks.recv_timeout(Duration::from_millis(10)).is_err());
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_crypto_snapshot_decimal_sizes() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_with(
+            OutgoingMessageIds::ReqMktData,
+            Box::new(|request| {
+                let req_id = request[2].as_str();
+                vec![
+                    vec!["1", "6", req_id, "1", "64250.5", "0.0125", "0"],
+                    vec!["1", "6", req_id, "2", "64251", "1.5", "0"],
+                    vec!["2", "6", req_id, "8", "812.3456"],
+                    vec!["57", "1", req_id],
+                ]
+                .into_iter()
+                .map(|fields| fields.iter().map(|field| field.to_string()).collect())
+                .collect()
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let quote = client
+            .snapshot(8, &Contract::crypto("BTC"))?
+            .recv_timeout(TIMEOUT)?;
+        let decimal = |size: &str| Some(size.parse::<BigDecimal>().unwrap());
+        assert_eq!((64250.5, decimal("0.0125")), (quote.bid, quote.bid_size));
+        assert_eq!((64251.0, decimal("1.5")), (quote.ask, quote.ask_size));
+        assert_eq!(decimal("812.3456"), quote.volume);
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_rejected_requests_end_receivers() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let reject = |id: &str, code: &str, message: &str| {
+            vec![["4", "2", id, code, message]
+                .iter()
+                .map(|field| field.to_string())
+                .collect()]
+        };
+        mock.respond_with(
+            OutgoingMessageIds::ReqMktData,
+            Box::new(move |request| {
+                reject(&request[2], "200", "No security definition has been found")
+            }),
+        );
+        mock.respond_with(
+            OutgoingMessageIds::PlaceOrder,
+            Box::new(move |request| reject(&request[1], "201", "Order rejected")),
+        );
+        mock.respond_with(
+            OutgoingMessageIds::ReqSecDefOptParams,
+            Box::new(move |request| reject(&request[1], "321", "Error validating request")),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let start = Instant::now();
+        let quote = client.snapshot(30, &simple_future())?;
+        assert!(matches!(
+            quote.recv_timeout(TIMEOUT),
+            Err(RecvTimeoutError::Disconnected)
+        ));
+        let preview = client.what_if(31, &simple_future(), &limit_order("BUY", 1.0, 3000.0))?;
+        assert!(matches!(
+            preview.recv_timeout(TIMEOUT),
+            Err(RecvTimeoutError::Disconnected)
+        ));
+        let chains = client.option_chains(32, &simple_future(), "")?;
+        assert!(matches!(
+            chains.recv_timeout(TIMEOUT),
+            Err(RecvTimeoutError::Disconnected)
+        ));
+
+        // a rejected order of a scenario does not wait for the timeout
+        let orders = vec![(simple_future(), limit_order("BUY", 1.0, 3000.0))];
+        let scenario =
+            client.what_if_scenario(&orders, &mut 33, TIMEOUT, Duration::from_millis(0))?;
+        assert!(scenario.contributions[0].preview.is_none());
+        assert!(start.elapsed() < TIMEOUT);
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_place_delta_neutral() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_with(
+            OutgoingMessageIds::PlaceOrder,
+            Box::new(|request| {
+                vec![["56", "1", &request[1], "756733", "0.52", "451.25"]
+                    .iter()
+                    .map(|field| field.to_string())
+                    .collect()]
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let mut call = Contract::option("SPY", "20250620", 450.0, Right::Call);
+        let order = limit_order("BUY", 1.0, 12.5);
+        assert!(client.place_delta_neutral(7, &call, &order).is_err());
+
+        call.delta_neutral_contract = Some(DeltaNeutralContract::new(756733, 0.0, 0.0));
+        let validation = client
+            .place_delta_neutral(7, &call, &order)?
+            .recv_timeout(TIMEOUT)?;
+        assert_eq!(
+            (756733, 0.52, 451.25),
+            (validation.con_id, validation.delta, validation.price)
+        );
+        let request = mock.expect_message(OutgoingMessageIds::PlaceOrder, TIMEOUT)?;
+        let hedge = request
+            .windows(4)
+            .position(|fields| fields == ["1", "756733", "0", "0"]);
+        assert!(hedge.is_some());
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_req_histogram() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_with(
+            OutgoingMessageIds::ReqHistogramData,
+            Box::new(|request| {
+                vec![["89", &request[1], "2", "4000.25", "12", "4000.5", "7.5"]
+                    .iter()
+                    .map(|field| field.to_string())
+                    .collect()]
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let histogram = client
+            .req_histogram(6, &simple_future(), true, "3 days")?
+            .recv_timeout(TIMEOUT)?;
+        let request = mock.expect_message(OutgoingMessageIds::ReqHistogramData, TIMEOUT)?;
+        assert_eq!("3 days", request[request.len() - 1]);
+        assert_eq!(
+            vec![
+                HistogramEntry::new(4000.25, BigDecimal::from(12)),
+                HistogramEntry::new(4000.5, "7.5".parse().unwrap()),
+            ],
+            histogram
+        );
+        let json = serde_json::to_string(&histogram[1]).unwrap();
+        assert_eq!(
+            histogram[1],
+            serde_json::from_str::<HistogramEntry>(&json).unwrap()
+        );
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_req_histogram_ends_on_error() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_with(
+            OutgoingMessageIds::ReqHistogramData,
+            Box::new(|request| match request[1].as_str() {
+                "6" => vec![
+                    ["4", "2", "6", "162", "Historical Market Data Service error"]
+                        .iter()
+                        .map(|field| field.to_string())
+                        .collect(),
+                ],
+                _ => vec![["89", &request[1], "1", "4000.25", "many"]
+                    .iter()
+                    .map(|field| field.to_string())
+                    .collect()],
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        // an error for the request and a count that is not a decimal both end the receiver
+        let failed = client.req_histogram(6, &simple_future(), true, "3 days")?;
+        assert_eq!(
+            Err(RecvTimeoutError::Disconnected),
+            failed.recv_timeout(TIMEOUT)
+        );
+        let garbled = client.req_histogram(7, &simple_future(), true, "3 days")?;
+        assert_eq!(
+            Err(RecvTimeoutError::Disconnected),
+            garbled.recv_timeout(TIMEOUT)
+        );
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_market_rules_cached() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_with(
+            OutgoingMessageIds::ReqMarketRule,
+            Box::new(|request| {
+                vec![["93", &request[1], "2", "0", "0.05", "1000", "0.5"]
+                    .iter()
+                    .map(|field| field.to_string())
+                    .collect()]
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let details = ContractDetails {
+            contract: simple_future(),
+            min_tick: 0.25,
+            valid_exchanges: "GLOBEX".to_string(),
+            market_rule_ids: "67".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(vec![67], client.req_missing_market_rules(&details)?);
+        let deadline = Instant::now() + TIMEOUT;
+        while client.market_rules().is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(client.market_rules().get(67).is_some());
+        assert!(client.req_missing_market_rules(&details)?.is_empty());
+        assert_eq!(
+            4000.0,
+            client.round_to_tick(&details, 4000.3, RoundDirection::Down)
+        );
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_smart_components_exchange_names() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_with(
+            OutgoingMessageIds::ReqSmartComponents,
+            Box::new(|request| {
+                vec![["82", &request[1], "2", "0", "NYSE", "N", "1", "ARCA", "P"]
+                    .iter()
+                    .map(|field| field.to_string())
+                    .collect()]
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        mock.send(&["81", "3", "0.01", "a6", "3"])?;
+        let deadline = Instant::now() + TIMEOUT;
+        while client.smart_components().bbo_exchange(3).is_none() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(vec!["a6".to_string()], client.smart_components().missing());
+
+        client.req_smart_components(8, "a6")?;
+        while client.smart_component_map("a6").is_none() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(
+            Some(vec!["NYSE".to_string(), "ARCA".to_string()]),
+            client.exchange_names(3, "NP")
+        );
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_req_soft_dollar_tiers() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_with(
+            OutgoingMessageIds::ReqSoftDollarTiers,
+            Box::new(|request| {
+                let fields = match request[1].as_str() {
+                    "13" => vec!["4", "2", "13", "321", "No soft dollar tiers"],
+                    req_id => vec!["77", req_id, "1", "Research", "0.001", "Research 0.001"],
+                };
+                vec![fields.iter().map(|field| field.to_string()).collect()]
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let tiers = client.req_soft_dollar_tiers(11)?.recv_timeout(TIMEOUT)?;
+        let tier = SoftDollarTier::new(
+            "Research".to_string(),
+            "0.001".to_string(),
+            "Research 0.001".to_string(),
+        );
+        assert_eq!(vec![tier.clone()], tiers);
+
+        let mut order = limit_order("BUY", 1.0, 3000.0);
+        order.soft_dollar_tier = tier;
+        client.place_order(12, &simple_future(), &order)?;
+        let request = mock.expect_message(OutgoingMessageIds::PlaceOrder, TIMEOUT)?;
+        let name = request.iter().position(|field| field == "Research");
+        assert!(name.is_some_and(|name| request[name + 1] == "0.001"));
+
+        // the error ends the receiver instead of leaving it waiting
+        let rejected = client.req_soft_dollar_tiers(13)?;
+        assert_eq!(
+            Err(RecvTimeoutError::Disconnected),
+            rejected.recv_timeout(TIMEOUT)
+        );
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_family_codes() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_with(
+            OutgoingMessageIds::ReqFamilyCodes,
+            Box::new(|_| {
+                vec![["78", "2", "DU123", "F1", "DU456", "F1"]
+                    .iter()
+                    .map(|field| field.to_string())
+                    .collect()]
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let family_codes = client.family_codes()?.recv_timeout(TIMEOUT)?;
+        assert_eq!(
+            vec![
+                FamilyCode::new("DU123".to_string(), "F1".to_string()),
+                FamilyCode::new("DU456".to_string(), "F1".to_string()),
+            ],
+            family_codes
+        );
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_search_symbols() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_with(
+            OutgoingMessageIds::ReqMatchingSymbols,
+            Box::new(|request| {
+                let fields = match request[1].as_str() {
+                    "3" => vec!["4", "2", "3", "321", "Error validating request"],
+                    req_id => vec![
+                        "79", req_id, "1", "265598", "AAPL", "STK", "NASDAQ", "USD", "3", "OPT",
+                        "WAR", "XYZ",
+                    ],
+                };
+                vec![fields.iter().map(|field| field.to_string()).collect()]
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let start = Instant::now();
+        let descriptions = client.search_symbols(1, "AAP")?.recv_timeout(TIMEOUT)?;
+        assert_eq!(1, descriptions.len());
+        assert_eq!("AAPL", descriptions[0].contract.symbol);
+        assert_eq!(3, descriptions[0].derivative_sec_types.len());
+        let derivative_types = descriptions[0].derivative_types();
+        assert!(matches!(derivative_types[..], [SecType::OPT, SecType::WAR]));
+        client.search_symbols(2, "Apple")?.recv_timeout(TIMEOUT)?;
+        // the second search waited for the pacing of the first
+        assert!(start.elapsed() >= Duration::from_millis(900));
+
+        // a rejected search ends its receiver
+        let rejected = client.search_symbols(3, "")?;
+        assert!(matches!(
+            rejected.recv_timeout(TIMEOUT),
+            Err(RecvTimeoutError::Disconnected)
+        ));
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_earliest_data_point() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_with(
+            OutgoingMessageIds::ReqHeadTimestamp,
+            Box::new(|request| {
+                let fields = match request[1].as_str() {
+                    "5" => vec!["4", "2", "5", "162", "HMDS query returned no data"],
+                    req_id => vec!["88", req_id, "1074868200"],
+                };
+                vec![fields.iter().map(|field| field.to_string()).collect()]
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let earliest =
+            client.earliest_data_point(4, &simple_future(), &WhatToShow::Trades, true, TIMEOUT)?;
+        assert_eq!(1074868200, earliest.timestamp());
+        let request = mock.expect_message(OutgoingMessageIds::ReqHeadTimestamp, TIMEOUT)?;
+        assert_eq!("2", request[request.len() - 1]);
+
+        // the error ends the wait instead of the timeout
+        let start = Instant::now();
+        match client.earliest_data_point(5, &simple_future(), &WhatToShow::Trades, true, TIMEOUT) {
+            Err(IBKRApiLibError::ApiError(err)) => assert_eq!("162", err.code),
+            other => panic!("expected error 162, got {:?}", other.is_ok()),
+        }
+        assert!(start.elapsed() < TIMEOUT);
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_download_history_pacing_violation_not_cached() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_with(
+            OutgoingMessageIds::ReqHistoricalData,
+            Box::new(|request| {
+                vec![[
+                    "4",
+                    "2",
+                    &request[1],
+                    "162",
+                    "Historical Market Data Service error message:Historical data request \
+                     pacing violation",
+                ]
+                .iter()
+                .map(|field| field.to_string())
+                .collect()]
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let dir = std::env::temp_dir().join(format!("twsapi_pacing_cache_{}", std::process::id()));
+        let cache = HistoryCache::new(&dir);
+        let mut contract = simple_future();
+        contract.con_id = 495512552;
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 14, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 3, 1, 15, 0, 0).unwrap();
+        match client.download_history_cached(
+            &cache,
+            15,
+            &contract,
+            start,
+            end,
+            &BarSize::_1Min,
+            &WhatToShow::Trades,
+            1,
+            TIMEOUT,
+        ) {
+            Err(IBKRApiLibError::ApiError(err)) => assert_eq!("162", err.code),
+            other => panic!("expected error 162, got {:?}", other.is_ok()),
+        }
+        let key = HistoryCacheKey::new(contract.con_id, &BarSize::_1Min, &WhatToShow::Trades, 1);
+        assert!(cache.load(&key)?.ranges().is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+        client.disconnect()
+    }
+
+    #[cfg(not(feature = "raw-timestamps"))]
+    #[test]
+    fn test_bar_time_zone() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+        let unknown = ContractDetails {
+            time_zone_id: "Mars/Olympus_Mons".to_string(),
+            ..Default::default()
+        };
+        assert!(client.set_bar_time_zone(5, &unknown).is_err());
+        let details = ContractDetails {
+            time_zone_id: "US/Eastern".to_string(),
+            ..Default::default()
+        };
+        assert_eq!("US/Eastern", client.set_bar_time_zone(5, &details)?.id());
+        let updates = client.subscribe_bar_updates(5);
+
+        // 30 minute bars through the hour repeated when daylight saving ended
+        for date in [
+            "20201101 01:00:00",
+            "20201101 01:30:00",
+            "20201101 01:00:00",
+            "20201101 01:30:00",
+        ] {
+            mock.send(&["90", "5", "1", date, "1", "1", "1", "1", "1", "1"])?;
+        }
+        let finals: Vec<i64> = (0..7)
+            .map(|_| updates.recv_timeout(TIMEOUT))
+            .collect::<Result<Vec<_>, _>>()?
+            .iter()
+            .filter(|update| update.is_final())
+            .filter_map(|update| update.bar().date)
+            .map(|date| date.timestamp())
+            .collect();
+        // 05:00, 05:30 and 06:00 UTC
+        assert_eq!(vec![1604206800, 1604208600, 1604210400], finals);
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_typed_bar_requests() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        client.req_historical_data(
+            6,
+            &simple_future(),
+            "",
+            &HistoricalDuration::Days(10),
+            &BarSize::_1Min,
+            &WhatToShow::Trades,
+            1,
+            1,
+            false,
+            vec![],
+        )?;
+        let request = mock.expect_message(OutgoingMessageIds::ReqHistoricalData, TIMEOUT)?;
+        let position = |field: &str| request.iter().position(|f| f == field);
+        assert_eq!(position("1 min").map(|i| i + 1), position("10 D"));
+
+        assert!(client
+            .req_real_time_bars(
+                7,
+                &simple_future(),
+                &BarSize::_1Min,
+                &WhatToShow::Trades,
+                false,
+                vec![]
+            )
+            .is_err());
+        client.req_real_time_bars(
+            7,
+            &simple_future(),
+            &BarSize::_5Secs,
+            &WhatToShow::Trades,
+            false,
+            vec![],
+        )?;
+        let request = mock.expect_message(OutgoingMessageIds::ReqRealTimeBars, TIMEOUT)?;
+        assert_eq!(
+            Some(&"TRADES".to_string()),
+            request.iter().skip_while(|f| *f != "5").nth(1)
+        );
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_what_to_show_checks() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        assert!(client
+            .req_real_time_bars(
+                8,
+                &simple_future(),
+                &BarSize::_5Secs,
+                &WhatToShow::BidAsk,
+                false,
+                vec![]
+            )
+            .is_err());
+        // SCHEDULE needs a newer server version than this client negotiates
+        assert!(client
+            .req_historical_data(
+                9,
+                &simple_future(),
+                "",
+                &HistoricalDuration::Days(1),
+                &BarSize::_1Day,
+                &WhatToShow::Schedule,
+                1,
+                1,
+                false,
+                vec![],
+            )
+            .is_err());
+        client.req_historical_data(
+            10,
+            &simple_future(),
+            "",
+            &HistoricalDuration::Months(1),
+            &BarSize::_1Day,
+            &WhatToShow::AdjustedLast,
+            1,
+            1,
+            false,
+            vec![],
+        )?;
+        let request = mock.expect_message(OutgoingMessageIds::ReqHistoricalData, TIMEOUT)?;
+        assert_eq!("10", request[1]);
+        assert!(request.contains(&"ADJUSTED_LAST".to_string()));
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_misspelled_exchange_rejected() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let mut contract = simple_future();
+        contract.exchange = Exchange::from("GLOBX");
+        match client.req_contract_details(11, &contract) {
+            Err(IBKRApiLibError::ApiError(err)) => assert!(err.description.contains("GLOBEX")),
+            other => panic!("expected an api error, got {:?}", other),
+        }
+
+        contract.exchange = Exchange::from("globex");
+        client.req_contract_details(12, &contract)?;
+        let request = mock.expect_message(OutgoingMessageIds::ReqContractData, TIMEOUT)?;
+        assert!(request.contains(&"GLOBEX".to_string()));
+        client.disconnect()
+    }
+
+    #[test]
+    fn test_unknown_currency_rejected() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let mut contract = simple_future();
+        contract.currency = Currency::from("USX");
+        assert!(client.req_contract_details(13, &contract).is_err());
+
+        contract.currency = Currency::from("usd");
+        client.req_contract_details(14, &contract)?;
+        let request = mock.expect_message(OutgoingMessageIds::ReqContractData, TIMEOUT)?;
+        assert!(request.contains(&"USD".to_string()));
+        client.disconnect()
+    }
+}
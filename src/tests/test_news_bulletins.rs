@@ -0,0 +1,71 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use crate::core::client::EClient;
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::exchange::Exchange;
+    use crate::core::messages::OutgoingMessageIds;
+    use crate::core::news_bulletins::{Bulletin, BulletinType};
+    use crate::core::server_versions::MAX_CLIENT_VER;
+    use crate::core::testing::MockTws;
+    use crate::examples::defaults::DefaultWrapper;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn test_bulletin_type_codes() {
+        for code in 1..=4 {
+            assert_eq!(code, BulletinType::from_code(code).code());
+        }
+        assert_eq!(
+            BulletinType::ExchangeUnavailable,
+            BulletinType::from_code(2)
+        );
+        assert_eq!(BulletinType::Other(9), BulletinType::from_code(9));
+        assert_eq!(
+            Exchange::None,
+            Bulletin::new(1, BulletinType::Regular, "", "").exchange
+        );
+    }
+
+    #[test]
+    fn test_subscribe_news_bulletins() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        // only the request for the bulletins of the day gets one
+        mock.respond_with(
+            OutgoingMessageIds::ReqNewsBulletins,
+            Box::new(|request| match request[2].as_str() {
+                "1" => vec![["14", "1", "7", "2", "Trading on NYSE is halted", "NYSE"]
+                    .iter()
+                    .map(|field| field.to_string())
+                    .collect()],
+                _ => vec![],
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let bulletins = client.subscribe_news_bulletins(true)?;
+        let request = mock.expect_message(OutgoingMessageIds::ReqNewsBulletins, TIMEOUT)?;
+        assert_eq!("1", request[2]);
+        assert_eq!(
+            Bulletin {
+                id: 7,
+                kind: BulletinType::ExchangeUnavailable,
+                message: "Trading on NYSE is halted".to_string(),
+                exchange: Exchange::Nyse,
+            },
+            bulletins.recv_timeout(TIMEOUT)?
+        );
+
+        let other = client.subscribe_news_bulletins(false)?;
+        other.cancel(&mut client)?;
+        mock.expect_message(OutgoingMessageIds::CancelNewsBulletins, TIMEOUT)?;
+        // cancelling ends every handle of the client
+        assert!(bulletins.recv_timeout(TIMEOUT).is_err());
+        client.disconnect()
+    }
+}
@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use crate::core::client::EClient;
+    use crate::core::contract::Contract;
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::messages::OutgoingMessageIds;
+    use crate::core::option_chains::{OptionChain, OptionChainCache};
+    use crate::core::server_versions::MAX_CLIENT_VER;
+    use crate::core::testing::MockTws;
+    use crate::examples::defaults::DefaultWrapper;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn fields(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_option_chain_cache_ttl() {
+        let start = Instant::now();
+        let mut cache = OptionChainCache::new(Duration::from_secs(3600));
+        assert!(cache.get(756733, start).is_none());
+
+        let chain = OptionChain {
+            exchange: "CBOE".to_string(),
+            underlying_con_id: 756733,
+            ..Default::default()
+        };
+        cache.insert(756733, vec![chain.clone()], start);
+        assert_eq!(Some(&[chain][..]), cache.get(756733, start));
+        assert!(cache
+            .get(756733, start + Duration::from_secs(3599))
+            .is_some());
+        assert!(cache
+            .get(756733, start + Duration::from_secs(3600))
+            .is_none());
+        assert_eq!(1, cache.len());
+        assert!(cache.remove(756733).is_some());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_option_chains_cached() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_with(
+            OutgoingMessageIds::ReqSecDefOptParams,
+            Box::new(|request| {
+                let req_id = request[1].as_str();
+                vec![
+                    fields(&[
+                        "75", req_id, "CBOE", "756733", "SPY", "100", "2", "20250620", "20250321",
+                        "3", "455", "450", "455.5",
+                    ]),
+                    fields(&[
+                        "75", req_id, "SMART", "756733", "SPY", "100", "1", "20250321", "1", "450",
+                    ]),
+                    fields(&["76", req_id]),
+                ]
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let mut cache = OptionChainCache::new(Duration::from_secs(3600));
+        assert!(client
+            .option_chains_cached(&mut cache, 30, &Contract::stock("SPY"), TIMEOUT)
+            .is_err());
+
+        let spy = Contract {
+            con_id: 756733,
+            ..Contract::stock("SPY")
+        };
+        let chains = client.option_chains_cached(&mut cache, 30, &spy, TIMEOUT)?;
+        assert_eq!(2, chains.len());
+        assert_eq!(
+            (
+                "CBOE",
+                vec!["20250321".to_string(), "20250620".to_string()],
+                vec![450.0, 455.0, 455.5]
+            ),
+            (
+                chains[0].exchange.as_str(),
+                chains[0].expirations.clone(),
+                chains[0].strikes.clone()
+            )
+        );
+        let request = mock.expect_message(OutgoingMessageIds::ReqSecDefOptParams, TIMEOUT)?;
+        assert_eq!(fields(&["78", "30", "SPY", "", "STK", "756733"]), request);
+
+        // served from the cache, without another request
+        assert_eq!(
+            chains,
+            client.option_chains_cached(&mut cache, 31, &spy, TIMEOUT)?
+        );
+        assert!(mock
+            .expect_message(
+                OutgoingMessageIds::ReqSecDefOptParams,
+                Duration::from_millis(100)
+            )
+            .is_err());
+        client.disconnect()
+    }
+}
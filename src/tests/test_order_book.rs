@@ -0,0 +1,32 @@
+#[cfg(test)]
+mod tests {
+
+    use bigdecimal::BigDecimal;
+
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::order_book::OrderBook;
+
+    #[test]
+    fn test_order_book_operations() -> Result<(), IBKRApiLibError> {
+        let mut book = OrderBook::new();
+        book.apply(0, "ARCA", 0, 1, 10.0, BigDecimal::from(100))?;
+        book.apply(0, "NSDQ", 0, 1, 10.1, BigDecimal::from(200))?; // insert shifts the ARCA row down
+        book.apply(0, "ARCA", 0, 0, 10.2, BigDecimal::from(300))?;
+
+        assert_eq!(10.1, book.best_bid().unwrap().price);
+        assert_eq!("NSDQ", book.best_bid().unwrap().market_maker);
+        assert_eq!(10.2, book.best_ask().unwrap().price);
+        assert_eq!(2, book.depth(5).0.len());
+        assert_eq!(0.0, book.imbalance(5).unwrap());
+
+        book.apply(1, "ARCA", 1, 1, 10.0, BigDecimal::from(50))?;
+        assert_eq!(BigDecimal::from(50), book.depth(5).0[1].size);
+
+        book.apply(0, "", 2, 1, 0.0, BigDecimal::from(0))?;
+        assert_eq!(10.0, book.best_bid().unwrap().price);
+
+        assert!(book.apply(0, "", 7, 1, 0.0, BigDecimal::from(0)).is_err());
+        assert!(book.apply(0, "", 0, 3, 0.0, BigDecimal::from(0)).is_err());
+        Ok(())
+    }
+}
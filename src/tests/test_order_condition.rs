@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::messages::read_fields;
+    use crate::core::order_condition::{
+        decode_condition, Condition, ConditionType, ExecutionCondition, MarginCondition,
+        OrderConditionEnum, PercentChangeCondition, PriceCondition, TimeCondition, TriggerMethod,
+        VolumeCondition,
+    };
+
+    //------------------------------------------------------------------------------------------------
+    fn wire_fields(condition: &OrderConditionEnum) -> Result<Vec<String>, IBKRApiLibError> {
+        let mut fields = vec![(condition.get_type() as i32).to_string()];
+        fields.extend(read_fields(&condition.make_fields()?.concat()));
+        Ok(fields)
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_conditions_encode_in_tws_order() -> Result<(), IBKRApiLibError> {
+        let price: OrderConditionEnum =
+            PriceCondition::new(TriggerMethod::Last, 208813720, "SMART", true, 600.5).into();
+        assert_eq!(
+            vec!["1", "o", "1", "600.5", "208813720", "SMART", "2"],
+            wire_fields(&price)?
+        );
+
+        let volume: OrderConditionEnum =
+            VolumeCondition::new(208813720, "SMART", false, 1000).into();
+        assert_eq!(
+            vec!["6", "o", "0", "1000", "208813720", "SMART"],
+            wire_fields(&volume)?
+        );
+
+        let change: OrderConditionEnum =
+            PercentChangeCondition::new(208813720, "SMART".to_string(), true, 15.0).into();
+        assert_eq!(
+            vec!["7", "a", "1", "15", "208813720", "SMART"],
+            wire_fields(&change.and())?
+        );
+        Ok(())
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_conditions_decode_what_they_encode() -> Result<(), IBKRApiLibError> {
+        let conditions: Vec<OrderConditionEnum> = vec![
+            PriceCondition::new(TriggerMethod::DoubleLast, 1, "SMART", false, 99.25).into(),
+            OrderConditionEnum::from(TimeCondition::new(true, "20250118 23:59:59".to_string()))
+                .and(),
+            MarginCondition::new(true, 30.0).into(),
+            ExecutionCondition::new(
+                "CASH".to_string(),
+                "IDEALPRO".to_string(),
+                "EUR".to_string(),
+            )
+            .into(),
+            OrderConditionEnum::from(VolumeCondition::new(2, "ARCA", true, 100000)).and(),
+            PercentChangeCondition::new(3, "SMART".to_string(), false, 2.5).into(),
+        ];
+        for condition in &conditions {
+            let fields = wire_fields(condition)?;
+            let decoded = decode_condition(&mut fields.iter())?;
+            assert_eq!(*condition, decoded);
+            assert_eq!(condition.is_conjunction(), decoded.is_conjunction());
+        }
+        assert!(conditions[1].is_conjunction() && !conditions[2].is_conjunction());
+        assert!(ConditionType::Execution == conditions[3].get_type());
+
+        let unknown = ["2".to_string(), "o".to_string()];
+        assert!(decode_condition(&mut unknown.iter()).is_err());
+        Ok(())
+    }
+}
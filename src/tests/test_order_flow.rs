@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod tests {
+
+    use bigdecimal::BigDecimal;
+
+    use crate::core::common::{
+        HistoricalTickBidAsk, HistoricalTickLast, TickAttribBidAsk, TickAttribLast,
+    };
+    use crate::core::order_flow::{
+        classify_timeline, signed_volume, signed_volume_by_interval, Aggressor, TradeClassifier,
+    };
+    use crate::core::tick_timeline::merge_ticks;
+    use crate::core::timestamps::timestamp_from_seconds;
+
+    #[test]
+    fn test_trade_classifier() {
+        let mut classifier = TradeClassifier::new();
+        // no quote yet and no previous trade
+        assert_eq!(
+            Aggressor::Unknown,
+            classifier.on_trade(1, 10.0, BigDecimal::from(1)).aggressor
+        );
+        // tick test before any quote
+        assert_eq!(
+            Aggressor::Buy,
+            classifier.on_trade(2, 10.1, BigDecimal::from(1)).aggressor
+        );
+
+        classifier.on_quote(10.0, 10.2);
+        assert_eq!(
+            Aggressor::Buy,
+            classifier.on_trade(3, 10.2, BigDecimal::from(1)).aggressor
+        );
+        assert_eq!(
+            Aggressor::Sell,
+            classifier.on_trade(4, 10.0, BigDecimal::from(1)).aggressor
+        );
+        // at the midpoint, 10.1 is an uptick from 10.0
+        assert_eq!(
+            Aggressor::Buy,
+            classifier.on_trade(5, 10.1, BigDecimal::from(1)).aggressor
+        );
+        // unchanged price at the midpoint keeps the last price change's side
+        assert_eq!(
+            Aggressor::Buy,
+            classifier.on_trade(6, 10.1, BigDecimal::from(1)).aggressor
+        );
+
+        // crossed quote falls back to the tick test
+        classifier.on_quote(10.3, 10.2);
+        assert_eq!(
+            Aggressor::Sell,
+            classifier.on_trade(7, 10.05, BigDecimal::from(1)).aggressor
+        );
+    }
+
+    #[test]
+    fn test_signed_volume() {
+        let quote = |time: i64, bid: f64, ask: f64| {
+            HistoricalTickBidAsk::new(
+                timestamp_from_seconds(time),
+                TickAttribBidAsk::new(false, false),
+                bid,
+                ask,
+                BigDecimal::from(1),
+                BigDecimal::from(1),
+            )
+        };
+        let trade = |time: i64, price: f64, size: i64| {
+            HistoricalTickLast::new(
+                timestamp_from_seconds(time),
+                TickAttribLast::new(false, false),
+                price,
+                BigDecimal::from(size),
+                "ARCA".to_string(),
+                "".to_string(),
+            )
+        };
+        let timeline = merge_ticks(
+            vec![quote(0, 10.0, 10.2), quote(65, 10.4, 10.6)],
+            vec![
+                trade(10, 10.2, 300),
+                trade(20, 10.0, 100),
+                trade(70, 10.6, 200),
+            ],
+        );
+        let trades = classify_timeline(&timeline);
+        assert_eq!(3, trades.len());
+
+        let total = signed_volume(&trades);
+        assert_eq!(BigDecimal::from(500), total.buy_volume);
+        assert_eq!(BigDecimal::from(100), total.sell_volume);
+        assert_eq!(BigDecimal::from(400), total.net());
+        assert_eq!(Some(400.0 / 600.0), total.imbalance());
+
+        let by_minute = signed_volume_by_interval(&trades, 60);
+        assert_eq!(2, by_minute.len());
+        assert_eq!(0, by_minute[0].0);
+        assert_eq!(BigDecimal::from(200), by_minute[0].1.net());
+        assert_eq!(60, by_minute[1].0);
+        assert_eq!(BigDecimal::from(200), by_minute[1].1.buy_volume);
+    }
+}
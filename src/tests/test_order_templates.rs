@@ -0,0 +1,404 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::core::common::{HedgeType, Right, SecType, UNSET_DOUBLE, UNSET_INTEGER};
+    use crate::core::contract::{Contract, DeltaNeutralContract};
+    use crate::core::currency::Currency;
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::exchange::Exchange;
+    use crate::core::order::{
+        check_adjusted_order, check_cash_quantity, check_delta_neutral, check_hedge_order, Action,
+        Order, OrderState, OrderType, TRAILING_UNIT_PERCENT,
+    };
+    use crate::core::order_decoder::OrderDecoder;
+    use crate::core::order_templates::{self, ScaleOrderBuilder, StopAdjustment};
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_templates_set_only_their_fields() -> Result<(), IBKRApiLibError> {
+        let market = order_templates::market(Action::Buy, 2.0)?;
+        assert_eq!(
+            (Action::Buy, OrderType::Mkt, 2.0),
+            (market.action, market.order_type, market.total_quantity)
+        );
+        assert_eq!(
+            (UNSET_DOUBLE, UNSET_DOUBLE),
+            (market.lmt_price, market.aux_price)
+        );
+
+        let stop_limit = order_templates::stop_limit(Action::Sell, 1.0, 99.5, 100.0)?;
+        assert_eq!(OrderType::StpLmt, stop_limit.order_type);
+        assert_eq!((99.5, 100.0), (stop_limit.lmt_price, stop_limit.aux_price));
+
+        let trailing = order_templates::trailing_stop_percent(Action::Sell, 1.0, 1.5)?;
+        assert_eq!(OrderType::Trail, trailing.order_type);
+        assert_eq!(
+            (1.5, UNSET_DOUBLE),
+            (trailing.trailing_percent, trailing.aux_price)
+        );
+
+        let relative = order_templates::relative(Action::Buy, 1.0, 0.01, None)?;
+        assert_eq!(
+            (0.01, UNSET_DOUBLE),
+            (relative.aux_price, relative.lmt_price)
+        );
+
+        let midprice = order_templates::midprice(Action::Buy, 1.0, Some(101.0))?;
+        assert_eq!(
+            (OrderType::MidPrice, 101.0),
+            (midprice.order_type, midprice.lmt_price)
+        );
+
+        // spreads can trade at negative prices
+        assert!(order_templates::limit(Action::Buy, 1.0, -0.25).is_ok());
+        Ok(())
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_templates_reject_invalid_values() {
+        assert!(order_templates::market(Action::None, 1.0).is_err());
+        assert!(order_templates::market(Action::Buy, 0.0).is_err());
+        assert!(order_templates::market_on_close(Action::Sell, f64::NAN).is_err());
+        assert!(order_templates::limit(Action::Buy, 1.0, f64::NAN).is_err());
+        assert!(order_templates::stop(Action::Sell, 1.0, UNSET_DOUBLE).is_err());
+        assert!(order_templates::trailing_stop(Action::Sell, 1.0, -1.0).is_err());
+        assert!(order_templates::trailing_stop_percent(Action::Sell, 1.0, 150.0).is_err());
+        assert!(order_templates::midprice(Action::Buy, 1.0, Some(f64::INFINITY)).is_err());
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_attach_adjusted_stop() -> Result<(), IBKRApiLibError> {
+        let mut parent = order_templates::limit(Action::Buy, 3.0, 100.0)?;
+        parent.order_id = 7;
+        let adjustment = StopAdjustment::Trail {
+            stop_price: 99.0,
+            trailing_amount: 0.5,
+            trailing_unit: TRAILING_UNIT_PERCENT,
+        };
+        let stop = order_templates::attach_adjusted_stop(&parent, 95.0, 102.0, adjustment)?;
+        assert_eq!(
+            (Action::Sell, 3.0, 7, OrderType::Stp),
+            (
+                stop.action.clone(),
+                stop.total_quantity,
+                stop.parent_id,
+                stop.order_type.clone()
+            )
+        );
+        assert_eq!((95.0, 102.0), (stop.aux_price, stop.trigger_price));
+        assert_eq!(OrderType::Trail, stop.adjusted_order_type);
+        assert_eq!(
+            (99.0, 0.5),
+            (stop.adjusted_stop_price, stop.adjusted_trailing_amount)
+        );
+        assert!(check_adjusted_order(7, &stop).is_ok());
+
+        // without its order_id the stop would not be attached to anything
+        parent.order_id = 0;
+        let adjustment = StopAdjustment::Stop { stop_price: 99.0 };
+        assert!(order_templates::attach_adjusted_stop(&parent, 95.0, 102.0, adjustment).is_err());
+
+        let mut unadjustable = stop;
+        unadjustable.adjusted_order_type = OrderType::Lmt;
+        assert!(check_adjusted_order(7, &unadjustable).is_err());
+        unadjustable.adjusted_order_type = OrderType::StpLmt;
+        assert!(check_adjusted_order(7, &unadjustable).is_err());
+        Ok(())
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_attach_fx_hedge() -> Result<(), IBKRApiLibError> {
+        let contract = Contract {
+            currency: Currency::eur(),
+            ..Contract::stock("SAP")
+        };
+        let mut parent = order_templates::limit(Action::Buy, 10.0, 180.0)?;
+        parent.order_id = 11;
+        let (pair, hedge) =
+            order_templates::attach_fx_hedge(&mut parent, &contract, &Currency::usd())?;
+        assert!(!parent.transmit);
+        assert_eq!(
+            ("EUR", SecType::CASH, Exchange::IdealPro, Currency::usd()),
+            (
+                pair.symbol.as_str(),
+                pair.sec_type,
+                pair.exchange,
+                pair.currency
+            )
+        );
+        assert_eq!(
+            (Action::Buy, OrderType::Mkt, 0.0, 11, HedgeType::Fx, true),
+            (
+                hedge.action.clone(),
+                hedge.order_type.clone(),
+                hedge.total_quantity,
+                hedge.parent_id,
+                hedge.hedge_type.clone(),
+                hedge.transmit
+            )
+        );
+
+        parent.action = Action::SShort;
+        let (_, hedge) =
+            order_templates::attach_fx_hedge(&mut parent, &contract, &Currency::usd())?;
+        assert_eq!(Action::Sell, hedge.action);
+
+        // a contract in USD for an account in GBP trades GBP.USD, selling it to buy USD
+        parent.action = Action::Buy;
+        let (pair, hedge) = order_templates::attach_fx_hedge(
+            &mut parent,
+            &Contract::stock("AAPL"),
+            &Currency::from("GBP"),
+        )?;
+        assert_eq!(
+            ("GBP", Currency::usd(), Action::Sell),
+            (pair.symbol.as_str(), pair.currency, hedge.action)
+        );
+
+        // nothing to hedge in the base currency, nor without the order_id of the parent
+        assert!(
+            order_templates::attach_fx_hedge(&mut parent, &contract, &Currency::eur()).is_err()
+        );
+        parent.order_id = 0;
+        assert!(
+            order_templates::attach_fx_hedge(&mut parent, &contract, &Currency::usd()).is_err()
+        );
+        Ok(())
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_check_hedge_order() -> Result<(), IBKRApiLibError> {
+        let mut hedge = order_templates::market(Action::Sell, 100.0)?;
+        hedge.parent_id = 3;
+        hedge.hedge_type = HedgeType::from("B");
+        assert!(check_hedge_order(4, &hedge).is_err());
+        hedge.hedge_param = "beta=1.2".to_string();
+        assert!(check_hedge_order(4, &hedge).is_ok());
+
+        hedge.hedge_type = HedgeType::Pair;
+        assert!(check_hedge_order(4, &hedge).is_err());
+        hedge.hedge_param = "ratio=0.5".to_string();
+        assert!(check_hedge_order(4, &hedge).is_ok());
+
+        hedge.hedge_type = HedgeType::Fx;
+        assert!(check_hedge_order(4, &hedge).is_err());
+        hedge.parent_id = 0;
+        hedge.hedge_type = HedgeType::Delta;
+        assert!(check_hedge_order(4, &hedge).is_err());
+        Ok(())
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_scale_order_builder() -> Result<(), IBKRApiLibError> {
+        let order = ScaleOrderBuilder::new(Action::Buy, 1000.0, 50.0, 200, 0.1)
+            .subsequent_level_size(100)
+            .price_adjust(0.05, 60)
+            .profit_taker(0.5, true)
+            .build()?;
+        assert_eq!(OrderType::Lmt, order.order_type);
+        assert_eq!(
+            (200, 100, 0.1),
+            (
+                order.scale_init_level_size,
+                order.scale_subs_level_size,
+                order.scale_price_increment
+            )
+        );
+        assert_eq!(
+            (0.05, 60, 0.5, true),
+            (
+                order.scale_price_adjust_value,
+                order.scale_price_adjust_interval,
+                order.scale_profit_offset,
+                order.scale_auto_reset
+            )
+        );
+        assert_eq!(
+            (UNSET_INTEGER, UNSET_INTEGER),
+            (order.scale_init_position, order.scale_init_fill_qty)
+        );
+
+        let builder = ScaleOrderBuilder::new(Action::Sell, 1000.0, 50.0, 200, 0.1);
+        assert!(builder.clone().initial_position(400, 200).build().is_ok());
+        // components larger than the order, no increment, auto reset without a profit taker
+        assert!(ScaleOrderBuilder::new(Action::Sell, 100.0, 50.0, 200, 0.1)
+            .build()
+            .is_err());
+        assert!(
+            ScaleOrderBuilder::new(Action::Sell, 1000.0, 50.0, 200, UNSET_DOUBLE)
+                .build()
+                .is_err()
+        );
+        assert!(builder
+            .clone()
+            .profit_taker(UNSET_DOUBLE, true)
+            .build()
+            .is_err());
+        assert!(builder
+            .clone()
+            .price_adjust(0.05, UNSET_INTEGER)
+            .build()
+            .is_err());
+        assert!(builder
+            .initial_position(400, UNSET_INTEGER)
+            .build()
+            .is_err());
+        Ok(())
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_decode_scale_order_params() -> Result<(), IBKRApiLibError> {
+        let (mut contract, mut order, mut order_state) =
+            (Contract::default(), Order::default(), OrderState::default());
+        let fields: Vec<String> = [
+            "200", "100", "0.1", "0.05", "60", "0.5", "1", "400", "200", "1",
+        ]
+        .iter()
+        .map(|field| field.to_string())
+        .collect();
+        let mut decoder = OrderDecoder::new(&mut contract, &mut order, &mut order_state, 28, 0);
+        decoder.decode_scale_order_params(&mut fields.iter())?;
+        assert_eq!(
+            (200, 100, 0.1, 0.05, 60, 0.5),
+            (
+                order.scale_init_level_size,
+                order.scale_subs_level_size,
+                order.scale_price_increment,
+                order.scale_price_adjust_value,
+                order.scale_price_adjust_interval,
+                order.scale_profit_offset
+            )
+        );
+        assert_eq!(
+            (true, 400, 200, true),
+            (
+                order.scale_auto_reset,
+                order.scale_init_position,
+                order.scale_init_fill_qty,
+                order.scale_random_percent
+            )
+        );
+
+        // without an increment TWS sends none of the fields after it
+        let mut order = Order::default();
+        let fields: Vec<String> = vec!["200".to_string(), String::new(), String::new()];
+        let mut decoder = OrderDecoder::new(&mut contract, &mut order, &mut order_state, 28, 0);
+        let mut fields_iter = fields.iter();
+        decoder.decode_scale_order_params(&mut fields_iter)?;
+        assert_eq!(None, fields_iter.next());
+        assert_eq!(
+            (200, UNSET_INTEGER, UNSET_DOUBLE),
+            (
+                order.scale_init_level_size,
+                order.scale_subs_level_size,
+                order.scale_price_increment
+            )
+        );
+        Ok(())
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_cash_quantity_templates() -> Result<(), IBKRApiLibError> {
+        let market = order_templates::cash_market(Action::Buy, 500.0)?;
+        assert_eq!(
+            (OrderType::Mkt, 0.0, 500.0),
+            (
+                market.order_type.clone(),
+                market.total_quantity,
+                market.cash_qty
+            )
+        );
+        let limit = order_templates::cash_limit(Action::Sell, 10000.0, 1.085)?;
+        assert_eq!(
+            (0.0, 10000.0, 1.085),
+            (limit.total_quantity, limit.cash_qty, limit.lmt_price)
+        );
+
+        assert!(order_templates::cash_market(Action::Buy, 0.0).is_err());
+        assert!(order_templates::cash_market(Action::Buy, UNSET_DOUBLE).is_err());
+        let mut stop = order_templates::stop(Action::Sell, 1.0, 99.0)?;
+        assert!(check_cash_quantity(1, &stop).is_ok());
+        stop.cash_qty = 500.0;
+        assert!(check_cash_quantity(1, &stop).is_err());
+        Ok(())
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_delta_neutral_volatility() -> Result<(), IBKRApiLibError> {
+        let order = order_templates::delta_neutral_volatility(
+            Action::Buy,
+            10.0,
+            40.0,
+            2,
+            OrderType::Rel,
+            Some(0.02),
+        )?;
+        assert_eq!(
+            (OrderType::Vol, 40.0, 2),
+            (
+                order.order_type.clone(),
+                order.volatility,
+                order.volatility_type
+            )
+        );
+        assert_eq!(
+            (OrderType::Rel, 0.02),
+            (
+                order.delta_neutral_order_type.clone(),
+                order.delta_neutral_aux_price
+            )
+        );
+        let volatility = |volatility_type, hedge_order_type| {
+            order_templates::delta_neutral_volatility(
+                Action::Buy,
+                10.0,
+                40.0,
+                volatility_type,
+                hedge_order_type,
+                None,
+            )
+        };
+        assert!(volatility(3, OrderType::Mkt).is_err());
+        assert!(volatility(1, OrderType::None).is_err());
+        assert_eq!(
+            UNSET_DOUBLE,
+            volatility(1, OrderType::Mkt)?.delta_neutral_aux_price
+        );
+        Ok(())
+    }
+
+    //------------------------------------------------------------------------------------------------
+    #[test]
+    fn test_check_delta_neutral() -> Result<(), IBKRApiLibError> {
+        let mut call = Contract::option("SPY", "20250620", 500.0, Right::Call);
+        let mut order = order_templates::limit(Action::Buy, 1.0, 12.5)?;
+        assert!(check_delta_neutral(1, &call, &order).is_ok());
+
+        call.delta_neutral_contract = Some(DeltaNeutralContract::new(0, 0.5, 510.0));
+        assert!(check_delta_neutral(1, &call, &order).is_err());
+        call.delta_neutral_contract = Some(DeltaNeutralContract::new(756733, 0.5, 510.0));
+        assert!(check_delta_neutral(1, &call, &order).is_ok());
+        let stock = Contract {
+            delta_neutral_contract: call.delta_neutral_contract.clone(),
+            ..Contract::stock("SPY")
+        };
+        assert!(check_delta_neutral(1, &stock, &order).is_err());
+
+        // attributes of the hedge are not sent without its order type
+        order.delta_neutral_con_id = 756733;
+        assert!(check_delta_neutral(1, &call, &order).is_err());
+        order.delta_neutral_order_type = OrderType::Mkt;
+        assert!(check_delta_neutral(1, &call, &order).is_err());
+        order.order_type = OrderType::Vol;
+        assert!(check_delta_neutral(1, &call, &order).is_ok());
+        Ok(())
+    }
+}
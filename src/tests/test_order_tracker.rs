@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+
+    use bigdecimal::BigDecimal;
+    use num_traits::FromPrimitive;
+
+    use crate::core::contract::Contract;
+    use crate::core::errors::{IbError, TwsMessage};
+    use crate::core::execution::Execution;
+    use crate::core::order::OrderState;
+    use crate::core::order_tracker::{OrderPhase, OrderTracker, EXEC_ID_CAPACITY};
+    use crate::examples::order_samples::limit_order;
+
+    fn execution(exec_id: &str, order_id: i32, cum_qty: f64, avg_price: f64) -> Execution {
+        Execution {
+            exec_id: exec_id.to_string(),
+            order_id,
+            perm_id: 77,
+            cum_qty: BigDecimal::from_f64(cum_qty).unwrap(),
+            avg_price,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_order_lifecycle() {
+        let mut tracker = OrderTracker::new();
+        let transitions = tracker.subscribe();
+        let order = limit_order("BUY", 100.0, 10.0);
+        tracker.track(1, &Contract::default(), &order);
+        assert_eq!(OrderPhase::PendingSubmit, tracker.get(1).unwrap().phase);
+
+        let state = OrderState {
+            status: "Submitted".to_string(),
+            ..Default::default()
+        };
+        tracker.on_open_order(1, &Contract::default(), &order, &state);
+        tracker.on_order_status(1, "Submitted", 0.0, 100.0, 0.0, 77);
+        tracker.on_execution(&execution("e1", 1, 40.0, 10.0));
+        assert!(tracker
+            .on_execution(&execution("e1", 1, 40.0, 10.0))
+            .is_none());
+        tracker.on_order_status(1, "Submitted", 40.0, 60.0, 10.0, 77);
+        let filled = tracker
+            .on_execution(&execution("e2", 1, 100.0, 9.94))
+            .unwrap();
+        assert_eq!(OrderPhase::Filled, filled.to);
+        assert!(tracker
+            .on_order_status(1, "Submitted", 40.0, 60.0, 10.0, 77)
+            .is_none());
+
+        let tracked = tracker.get(1).unwrap();
+        assert_eq!(
+            (100.0, 0.0, 9.94),
+            (tracked.filled, tracked.remaining, tracked.avg_fill_price)
+        );
+        let phases: Vec<OrderPhase> = transitions.try_iter().map(|t| t.to).collect();
+        assert_eq!(
+            vec![
+                OrderPhase::Submitted,
+                OrderPhase::PartiallyFilled,
+                OrderPhase::Filled
+            ],
+            phases
+        );
+
+        tracker.track(2, &Contract::default(), &order);
+        let rejected = TwsMessage::new(
+            2,
+            IbError::OrderRejected.code(),
+            "Order rejected".to_string(),
+        );
+        assert_eq!(
+            OrderPhase::Rejected,
+            tracker.on_error(&rejected).unwrap().to
+        );
+        assert_eq!(
+            IbError::OrderRejected.code(),
+            tracker.get(2).unwrap().last_error.as_ref().unwrap().code
+        );
+        assert!(tracker.working().is_empty());
+    }
+
+    #[test]
+    fn test_phase_classification() {
+        assert_eq!(
+            Some(OrderPhase::Cancelled),
+            OrderPhase::from_status("ApiCancelled", 0.0)
+        );
+        assert_eq!(
+            Some(OrderPhase::PartiallyFilled),
+            OrderPhase::from_status("Submitted", 5.0)
+        );
+        assert_eq!(None, OrderPhase::from_status("Unknown", 0.0));
+        assert!(OrderPhase::PreSubmitted.is_working());
+        assert!(!OrderPhase::Inactive.is_working());
+        assert!(!OrderPhase::Inactive.is_terminal());
+    }
+
+    #[test]
+    fn test_exec_ids_are_bounded() {
+        let mut tracker = OrderTracker::new();
+        tracker.track(1, &Contract::default(), &limit_order("BUY", 1e9, 10.0));
+        for qty in 1..=EXEC_ID_CAPACITY + 1 {
+            tracker.on_execution(&execution(&format!("e{}", qty), 1, qty as f64, 10.0));
+        }
+        // e1 was forgotten, but the quantity filled keeps it from counting twice
+        assert!(tracker
+            .on_execution(&execution("e1", 1, 1.0, 10.0))
+            .is_none());
+        assert_eq!(
+            (EXEC_ID_CAPACITY + 1) as f64,
+            tracker.get(1).unwrap().filled
+        );
+    }
+}
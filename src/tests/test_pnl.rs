@@ -0,0 +1,94 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use crate::core::client::EClient;
+    use crate::core::common::UNSET_DOUBLE;
+    use crate::core::contract::Contract;
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::messages::OutgoingMessageIds;
+    use crate::core::pnl::{PnlScope, PnlTracker, PnlValues};
+    use crate::core::portfolio::{ClosingOrderConfig, Portfolio};
+    use crate::core::server_versions::MAX_CLIENT_VER;
+    use crate::core::testing::MockTws;
+    use crate::examples::defaults::DefaultWrapper;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn contract(con_id: i32) -> Contract {
+        Contract {
+            con_id,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pnl_tracker() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let mut portfolio = Portfolio::new(ClosingOrderConfig::default());
+        portfolio.on_position("DU1", contract(1), 100.0, 10.0);
+        portfolio.on_position("DU1", contract(2), -5.0, 20.0);
+        portfolio.on_position("DU2", contract(3), 1.0, 30.0);
+
+        let mut tracker = PnlTracker::new("DU1", "", 50);
+        let updates = tracker.subscribe();
+        tracker.start(&mut client)?;
+        tracker.sync_positions(&mut client, &portfolio)?;
+        assert_eq!(vec![1, 2], tracker.tracked());
+        let request = mock.expect_message(OutgoingMessageIds::ReqPnl, TIMEOUT)?;
+        assert_eq!(("50", "DU1"), (request[1].as_str(), request[2].as_str()));
+        mock.expect_message(OutgoingMessageIds::ReqPnlSingle, TIMEOUT)?;
+        mock.expect_message(OutgoingMessageIds::ReqPnlSingle, TIMEOUT)?;
+
+        let req_id_1 = tracker.track_position(&mut client, 1)?;
+        assert!(tracker.on_pnl(50, 12.0, 30.0, UNSET_DOUBLE));
+        assert!(tracker.on_pnl_single(req_id_1, 100, 10.0, 25.0, 0.0, 1100.0));
+        assert!(tracker.on_pnl_single(req_id_1, 100, 10.0, 25.0, 0.0, 1101.0));
+        assert!(tracker.on_pnl_single(req_id_1, 100, 10.0, 25.0, 0.0, 1101.0));
+        assert!(!tracker.on_pnl(49, 0.0, 0.0, 0.0));
+
+        let account = updates.try_recv().unwrap();
+        assert_eq!(PnlScope::Account, account.scope);
+        assert_eq!((None, None), (account.pnl.realized, account.position));
+        assert_eq!(PnlScope::Position(1), updates.try_recv().unwrap().scope);
+        // the value alone changed
+        let revalued = updates.try_recv().unwrap();
+        assert_eq!(
+            (Some(100), Some(1101.0)),
+            (revalued.position, revalued.value)
+        );
+        // nothing changed
+        assert!(updates.try_recv().is_err());
+
+        let snapshot = tracker.snapshot();
+        assert_eq!(Some(1101.0), snapshot.positions[&1].value);
+        assert_eq!(
+            PnlValues {
+                daily: Some(10.0),
+                unrealized: Some(25.0),
+                realized: Some(0.0)
+            },
+            snapshot.positions_total()
+        );
+
+        assert!(tracker.on_pnl_single(req_id_1, 60, 10.0, 25.0, 0.0, 1101.0));
+        assert_eq!(Some(60), updates.try_recv().unwrap().position);
+
+        portfolio.on_position("DU1", contract(1), 0.0, 0.0);
+        tracker.sync_positions(&mut client, &portfolio)?;
+        assert_eq!(vec![2], tracker.tracked());
+        assert!(tracker.snapshot().positions.is_empty());
+        let cancel = mock.expect_message(OutgoingMessageIds::CancelPnlSingle, TIMEOUT)?;
+        assert_eq!(req_id_1.to_string(), cancel[1]);
+
+        tracker.stop(&mut client)?;
+        assert!(tracker.tracked().is_empty());
+        mock.expect_message(OutgoingMessageIds::CancelPnl, TIMEOUT)?;
+        client.disconnect()
+    }
+}
@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crate::core::client::EClient;
+    use crate::core::common::{BarSize, WhatToShow};
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::messages::OutgoingMessageIds;
+    use crate::core::pool::ClientPool;
+    use crate::core::server_versions::MAX_CLIENT_VER;
+    use crate::core::testing::MockTws;
+    use crate::examples::contract_samples::simple_future;
+    use crate::examples::defaults::DefaultWrapper;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn test_pool_shards_market_data() -> Result<(), IBKRApiLibError> {
+        let mocks = [
+            MockTws::start(MAX_CLIENT_VER)?,
+            MockTws::start(MAX_CLIENT_VER)?,
+        ];
+        let wrapper = Arc::new(Mutex::new(DefaultWrapper::new()));
+        let mut clients = Vec::new();
+        for (client_id, mock) in mocks.iter().enumerate() {
+            let mut client = EClient::new(wrapper.clone());
+            client.connect("127.0.0.1", mock.port(), client_id as i32)?;
+            clients.push(client);
+        }
+        let mut pool = ClientPool::from_clients(clients, 2)?;
+
+        pool.req_mkt_data(1, &simple_future(), "", false, false, vec![])?;
+        pool.req_mkt_data(2, &simple_future(), "", false, false, vec![])?;
+        pool.req_mkt_depth(3, &simple_future(), 5, false, vec![])?;
+        // snapshots hold no line
+        pool.req_mkt_data(4, &simple_future(), "", true, false, vec![])?;
+        pool.req_real_time_bars(
+            5,
+            &simple_future(),
+            &BarSize::_5Secs,
+            &WhatToShow::Trades,
+            false,
+            vec![],
+        )?;
+        assert_eq!(vec![2, 2], pool.lines_in_use());
+        assert_eq!(
+            (Some(0), Some(1), Some(0), Some(1)),
+            (
+                pool.connection_of(1),
+                pool.connection_of(2),
+                pool.connection_of(3),
+                pool.connection_of(5)
+            )
+        );
+        assert_eq!(
+            "2",
+            mocks[1].expect_message(OutgoingMessageIds::ReqMktData, TIMEOUT)?[2]
+        );
+        mocks[1].expect_message(OutgoingMessageIds::ReqRealTimeBars, TIMEOUT)?;
+
+        match pool.req_mkt_data(6, &simple_future(), "", false, false, vec![]) {
+            Err(IBKRApiLibError::ApiError(err)) => assert_eq!("541", err.code),
+            other => panic!("expected no lines left, got {:?}", other),
+        }
+        // a snapshot is sent on the primary with every line in use
+        pool.req_mkt_data(7, &simple_future(), "", true, false, vec![])?;
+        let snapshots: Vec<String> = (0..3)
+            .map(|_| mocks[0].expect_message(OutgoingMessageIds::ReqMktData, TIMEOUT))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .map(|request| request[2].clone())
+            .collect();
+        assert_eq!(vec!["1", "4", "7"], snapshots);
+        assert_eq!(vec![2, 2], pool.lines_in_use());
+
+        pool.cancel_mkt_data(2)?;
+        assert_eq!(
+            "2",
+            mocks[1].expect_message(OutgoingMessageIds::CancelMktData, TIMEOUT)?[2]
+        );
+        pool.req_mkt_data(6, &simple_future(), "", false, false, vec![])?;
+        assert_eq!(Some(1), pool.connection_of(6));
+
+        pool.shutdown()?;
+        assert!(pool.clients().iter().all(|client| !client.is_connected()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_pool_frees_lines_of_rejected_requests() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_with(
+            OutgoingMessageIds::ReqMktData,
+            Box::new(|request| {
+                let fields = match request[2].as_str() {
+                    "2" => vec!["4", "2", "2", "354", "Market data is not subscribed"],
+                    req_id => vec!["4", "2", req_id, "10167", "Displaying delayed market data"],
+                };
+                vec![fields.iter().map(|field| field.to_string()).collect()]
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+        let mut pool = ClientPool::from_clients(vec![client], 2)?;
+
+        pool.req_mkt_data(1, &simple_future(), "", false, false, vec![])?;
+        pool.req_mkt_data(2, &simple_future(), "", false, false, vec![])?;
+        let start = Instant::now();
+        while pool.connection_of(2).is_some() && start.elapsed() < TIMEOUT {
+            thread::sleep(Duration::from_millis(10));
+        }
+        // the rejected request freed its line, the delayed one kept it
+        assert_eq!(vec![1], pool.lines_in_use());
+        pool.req_mkt_data(3, &simple_future(), "", false, false, vec![])?;
+        assert_eq!(vec![2], pool.lines_in_use());
+        pool.shutdown()
+    }
+
+    #[test]
+    fn test_pool_needs_a_connection() {
+        let clients: Vec<EClient<DefaultWrapper>> = vec![];
+        assert!(ClientPool::from_clients(clients, 2).is_err());
+        let wrapper = Arc::new(Mutex::new(DefaultWrapper::new()));
+        assert!(ClientPool::connect(wrapper, "127.0.0.1", 7497, &[], 2).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use bigdecimal::BigDecimal;
+    use num_traits::FromPrimitive;
+
+    use crate::core::client::EClient;
+    use crate::core::contract::Contract;
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::execution::Execution;
+    use crate::core::messages::OutgoingMessageIds;
+    use crate::core::order::Action;
+    use crate::core::portfolio::{ClosingOrderConfig, Portfolio, CLOSE_ORDER_REF, OPEN_ORDER_REF};
+    use crate::core::server_versions::MAX_CLIENT_VER;
+    use crate::core::testing::MockTws;
+    use crate::examples::defaults::DefaultWrapper;
+
+    fn contract(con_id: i32) -> Contract {
+        Contract {
+            con_id,
+            symbol: "SPY".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_closing_order() -> Result<(), IBKRApiLibError> {
+        let mut portfolio = Portfolio::new(ClosingOrderConfig::new(false, true));
+        portfolio.on_position("DU1", contract(1), 100.0, 400.0);
+        portfolio.on_position("DU1", contract(2), -50.0, 20.0);
+
+        let orders = portfolio.closing_order(1, 40.0)?;
+        assert_eq!(1, orders.len());
+        assert_eq!(Action::Sell, orders[0].action);
+        assert_eq!(40.0, orders[0].total_quantity);
+        assert_eq!("DU1", orders[0].account);
+        assert_eq!("C", orders[0].open_close);
+        assert_eq!(CLOSE_ORDER_REF, orders[0].order_ref);
+
+        let orders = portfolio.closing_order(2, 80.0)?;
+        assert_eq!(1, orders.len());
+        assert_eq!(Action::Buy, orders[0].action);
+        assert_eq!(80.0, orders[0].total_quantity);
+        assert_eq!("", orders[0].open_close);
+
+        assert!(portfolio.closing_order(1, 0.0).is_err());
+        assert!(portfolio.closing_order(3, 10.0).is_err());
+
+        portfolio.on_position("DU2", contract(1), 10.0, 400.0);
+        assert!(portfolio.closing_order(1, 10.0).is_err());
+        assert_eq!(
+            10.0,
+            portfolio.closing_order_for_account("DU2", 1, 10.0)?[0].total_quantity
+        );
+
+        portfolio.on_position("DU2", contract(1), 0.0, 0.0);
+        assert_eq!(1, portfolio.closing_order(1, 10.0)?.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_closing_order_split_flip() -> Result<(), IBKRApiLibError> {
+        let mut portfolio = Portfolio::new(ClosingOrderConfig::new(true, true));
+        portfolio.on_position("DU1", contract(1), -30.0, 400.0);
+
+        let orders = portfolio.closing_order(1, 100.0)?;
+        assert_eq!(2, orders.len());
+        assert_eq!(
+            ("BUY", 30.0),
+            (orders[0].action.code(), orders[0].total_quantity)
+        );
+        assert_eq!(
+            ("C", CLOSE_ORDER_REF),
+            (orders[0].open_close.as_str(), orders[0].order_ref.as_str())
+        );
+        assert_eq!(
+            ("BUY", 70.0),
+            (orders[1].action.code(), orders[1].total_quantity)
+        );
+        assert_eq!(
+            ("O", OPEN_ORDER_REF),
+            (orders[1].open_close.as_str(), orders[1].order_ref.as_str())
+        );
+        Ok(())
+    }
+
+    fn execution(exec_id: &str, side: &str, shares: f64, price: f64) -> Execution {
+        Execution {
+            exec_id: exec_id.to_string(),
+            acct_number: "DU1".to_string(),
+            side: side.to_string(),
+            shares: BigDecimal::from_f64(shares).unwrap(),
+            price,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_positions_live() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+        let mut portfolio = Portfolio::new(ClosingOrderConfig::default());
+        portfolio.on_position("DU1", contract(1), 100.0, 400.0);
+        portfolio.on_position("DU1", contract(2), 10.0, 20.0);
+        portfolio.on_position("DU2", contract(1), 5.0, 410.0);
+
+        portfolio.on_execution(&contract(1), &execution("e1", "BOT", 100.0, 410.0));
+        portfolio.on_execution(&contract(1), &execution("e1", "BOT", 100.0, 410.0));
+        let held = portfolio.position("DU1", 1).unwrap();
+        assert_eq!((200.0, 405.0), (held.position, held.avg_cost));
+        assert_eq!(205.0, portfolio.net_position(1));
+
+        portfolio.on_execution(&contract(2), &execution("e2", "SLD", 15.0, 25.0));
+        let held = portfolio.position("DU1", 2).unwrap();
+        assert_eq!((-5.0, 25.0), (held.position, held.avg_cost));
+        portfolio.on_execution(&contract(2), &execution("e3", "BOT", 5.0, 24.0));
+        assert!(portfolio.position("DU1", 2).is_none());
+
+        // a refresh of DU1 no longer reporting contract 1 leaves DU2 alone
+        portfolio.refresh_multi(&mut client, 1, "DU1", "")?;
+        mock.expect_message(
+            OutgoingMessageIds::ReqPositionsMulti,
+            Duration::from_secs(5),
+        )?;
+        portfolio.on_position("DU1", contract(3), 1.0, 50.0);
+        portfolio.on_position_end();
+        assert!(portfolio.position("DU1", 1).is_none());
+        assert!(portfolio.position("DU1", 3).is_some());
+        assert!(portfolio.position("DU2", 1).is_some());
+
+        // an execution applied before the refresh is not applied again
+        portfolio.on_execution(&contract(3), &execution("e1", "BOT", 100.0, 410.0));
+        assert_eq!(1.0, portfolio.position("DU1", 3).unwrap().position);
+
+        // a refresh of every account reporting nothing for DU2 closes its positions
+        portfolio.refresh(&mut client)?;
+        mock.expect_message(OutgoingMessageIds::ReqPositions, Duration::from_secs(5))?;
+        portfolio.on_position("DU1", contract(3), 1.0, 50.0);
+        portfolio.on_position_end();
+        assert!(portfolio.position("DU1", 3).is_some());
+        assert!(portfolio.position("DU2", 1).is_none());
+        client.disconnect()
+    }
+}
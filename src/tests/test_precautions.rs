@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::core::order::Action;
+    use crate::core::order_templates;
+    use crate::core::precautions::{
+        Marketability, MarketabilityAction, MarketabilityCheck, PrecautionarySettings,
+    };
+    use crate::core::quote::Quote;
+    use crate::examples::contract_samples::simple_future;
+    use crate::examples::order_samples::{limit_order, market_order, stop, trailing_stop};
+
+    #[test]
+    fn test_precautionary_settings_check() {
+        let mut contract = simple_future();
+        contract.multiplier = "50".to_string();
+        let settings = PrecautionarySettings::new(Some(10.0), Some(500000.0), Some(5.0));
+
+        assert!(settings
+            .check(1, &contract, &limit_order("BUY", 2.0, 3000.0), None)
+            .is_ok());
+        // size
+        assert!(settings
+            .check(1, &contract, &limit_order("BUY", 11.0, 3000.0), None)
+            .is_err());
+        // value: 4 * 3000 * 50
+        assert!(settings
+            .check(1, &contract, &limit_order("BUY", 4.0, 3000.0), None)
+            .is_err());
+        // price deviation
+        assert!(settings
+            .check(1, &contract, &limit_order("BUY", 1.0, 3200.0), Some(3000.0))
+            .is_err());
+        // market orders are valued at the market price when it is known
+        assert!(settings
+            .check(1, &contract, &market_order("BUY", 4.0), None)
+            .is_ok());
+        assert!(settings
+            .check(1, &contract, &market_order("BUY", 4.0), Some(3000.0))
+            .is_err());
+        // the stop price of a stop order is its price
+        assert!(settings
+            .check(1, &contract, &stop("SELL", 1.0, 3200.0), Some(3000.0))
+            .is_err());
+        // the trailing amount of a trailing stop is not: it is valued at the market price
+        let mut trail = trailing_stop("SELL", 4.0, 0.0, 0.0);
+        trail.aux_price = 0.5;
+        assert!(settings.check(1, &contract, &trail, Some(3000.0)).is_err());
+        assert!(settings.check(1, &contract, &trail, None).is_ok());
+        // a cash quantity order is valued at its cash quantity
+        let cash = order_templates::cash_market(Action::Buy, 600000.0).unwrap();
+        assert!(settings.check(1, &contract, &cash, None).is_err());
+        let cash = order_templates::cash_limit(Action::Buy, 400000.0, 3000.0).unwrap();
+        assert!(settings.check(1, &contract, &cash, Some(3000.0)).is_ok());
+    }
+
+    #[test]
+    fn test_marketability_check() {
+        let mut quote = Quote::new();
+        quote.bid = 99.0;
+        quote.ask = 100.0;
+        let check =
+            MarketabilityCheck::new(5.0, MarketabilityAction::Warn, MarketabilityAction::Block);
+
+        let classify =
+            |action: &str, price: f64| check.classify(&limit_order(action, 1.0, price), &quote);
+        assert_eq!(Some(Marketability::Passive), classify("BUY", 99.5));
+        assert_eq!(Some(Marketability::Marketable), classify("BUY", 104.0));
+        assert_eq!(Some(Marketability::ThroughMarket), classify("BUY", 110.0));
+        assert_eq!(Some(Marketability::Passive), classify("SELL", 99.5));
+        assert_eq!(Some(Marketability::ThroughMarket), classify("SELL", 90.0));
+        assert_eq!(None, check.classify(&market_order("BUY", 1.0), &quote));
+
+        assert!(check
+            .check(1, &limit_order("BUY", 1.0, 104.0), &quote)
+            .is_ok());
+        // a sell limit typed as 9.90 instead of 99.0
+        assert!(check
+            .check(1, &limit_order("SELL", 1.0, 9.9), &quote)
+            .is_err());
+        assert!(check
+            .check(1, &limit_order("SELL", 1.0, 9.9), &Quote::new())
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests {
+
+    use std::time::{Duration, Instant};
+
+    use bigdecimal::BigDecimal;
+
+    use crate::core::common::{TickType, UNSET_DOUBLE};
+    use crate::core::freshness::Freshness;
+    use crate::core::order_book::OrderBook;
+    use crate::core::quote::Quote;
+
+    #[test]
+    fn test_quote_apply_ticks() {
+        let mut quote = Quote::new();
+        assert_eq!(None, quote.midpoint());
+
+        assert!(quote.apply_price(TickType::Bid, 100.0));
+        assert!(quote.apply_price(TickType::DelayedAsk, 100.5));
+        assert!(quote.apply_size(TickType::BidSize, BigDecimal::from(300)));
+        assert!(!quote.apply_price(TickType::Halted, 1.0));
+        assert!(!quote.apply_size(TickType::NotSet, BigDecimal::from(1)));
+
+        assert_eq!(100.0, quote.bid);
+        assert_eq!(100.5, quote.ask);
+        assert_eq!(Some(BigDecimal::from(300)), quote.bid_size);
+        assert_eq!(None, quote.ask_size);
+        assert_eq!(UNSET_DOUBLE, quote.last);
+        assert_eq!(Some(100.25), quote.midpoint());
+    }
+
+    #[test]
+    fn test_quote_staleness() {
+        let max_age = Duration::from_secs(5);
+        let mut quote = Quote::new();
+        let now = Instant::now();
+        assert!(quote.is_stale(max_age, now));
+        assert_eq!(None, quote.age(now));
+
+        quote.apply_price(TickType::Bid, 100.0);
+        assert!(!quote.is_stale(max_age, now + Duration::from_secs(1)));
+        assert!(quote.is_stale(max_age, now + Duration::from_secs(10)));
+        // a tick outside the quote is no update
+        let updated = quote.updated;
+        quote.apply_price(TickType::Halted, 1.0);
+        assert_eq!(updated, quote.updated);
+
+        let mut book = OrderBook::new();
+        assert!(book.is_stale(max_age, now));
+        book.apply(0, "", 0, 1, 100.0, BigDecimal::from(10))
+            .unwrap();
+        assert!(!book.is_stale(max_age, now + Duration::from_secs(1)));
+    }
+}
@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::core::errors::{IBKRApiLibError, MessageError};
+    use crate::core::messages::make_message;
+    use crate::core::reader::FrameAssembler;
+
+    #[test]
+    fn test_frame_assembler() -> Result<(), IBKRApiLibError> {
+        let mut bytes = make_message("49\u{0}1\u{0}1600000000\u{0}")?;
+        bytes.extend(make_message("")?);
+        bytes.extend(make_message("9\u{0}1\u{0}100\u{0}")?);
+
+        // the first frame split across reads, the others in one read
+        let mut frames = FrameAssembler::new();
+        frames.push(&bytes[..2]);
+        assert!(frames.next_frame().is_none());
+        frames.push(&bytes[2..10]);
+        assert!(frames.next_frame().is_none());
+        assert_eq!(10, frames.pending());
+        frames.push(&bytes[10..]);
+        assert_eq!(
+            Some(Ok("49\u{0}1\u{0}1600000000\u{0}".to_string())),
+            frames.next_frame()
+        );
+        assert_eq!(Some(Ok(String::new())), frames.next_frame());
+        assert_eq!(
+            Some(Ok("9\u{0}1\u{0}100\u{0}".to_string())),
+            frames.next_frame()
+        );
+        assert!(frames.next_frame().is_none());
+        assert_eq!(0, frames.pending());
+
+        frames.push(&[0, 0, 0, 1, 0xFF]);
+        frames.push(&make_message("1")?);
+        assert_eq!(
+            Some(Err(MessageError::InvalidUtf8 {
+                size: 1,
+                valid_up_to: 0
+            })),
+            frames.next_frame()
+        );
+        assert_eq!(Some(Ok("1".to_string())), frames.next_frame());
+
+        frames.push(&[0x80, 0, 0, 0, 1, 2, 3]);
+        assert_eq!(
+            Some(Err(MessageError::InvalidLength(i32::MIN))),
+            frames.next_frame()
+        );
+        assert_eq!(0, frames.pending());
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::{Arc, Mutex};
+
+    use crate::core::client::{ConnStatus, EClient};
+    use crate::core::decoder::Decoder;
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::message_queue::{message_queue, QueueConfig};
+    use crate::core::request_context::{self, RequestContext, RequestContexts};
+    use crate::core::server_versions::MAX_CLIENT_VER;
+    use crate::core::subscriptions::RequestMetadata;
+    use crate::core::testing::MockTws;
+    use crate::examples::contract_samples::simple_future;
+    use crate::examples::defaults::DefaultWrapper;
+
+    #[test]
+    fn test_request_context_guards() {
+        assert!(request_context::current().is_none());
+        let outer = request_context::enter(RequestContext::new(1, "req_pnl", None));
+        {
+            let _inner = request_context::enter(RequestContext::new(
+                2,
+                "req_mkt_data",
+                Some(&simple_future()),
+            ));
+            let current = request_context::current().unwrap();
+            assert_eq!(2, current.req_id);
+            assert_eq!(
+                "req_id: 2, request: req_mkt_data, contract: ES FUT GLOBEX USD",
+                current.to_string()
+            );
+        }
+        assert_eq!(Some(1), request_context::current_req_id());
+        drop(outer);
+        assert!(request_context::current().is_none());
+        assert!(request_context::current_req_id().is_none());
+    }
+
+    #[test]
+    fn test_request_contexts_end() {
+        let mut contexts = RequestContexts::default();
+        contexts.record(RequestContext::new(1, "req_head_time_stamp", None));
+        contexts.record(RequestContext::new(2, "req_historical_data", None));
+        contexts.keep(2);
+
+        assert!(contexts.end(1));
+        assert!(contexts.get(1).is_none());
+        // kept up to date until cancelled
+        assert!(!contexts.end(2));
+        assert_eq!("req_historical_data", contexts.get(2).unwrap().request);
+        contexts.forget(2);
+        assert!(contexts.get(2).is_none());
+    }
+
+    #[test]
+    fn test_request_metadata() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let metadata: RequestMetadata<String> = RequestMetadata::new();
+        client.track_metadata(&metadata);
+        metadata.attach(1, "momentum".to_string());
+        metadata.attach(2, "hedger".to_string());
+        client.req_mkt_data(1, &simple_future(), "", false, false, vec![])?;
+        client.req_mkt_data(2, &simple_future(), "", false, false, vec![])?;
+
+        let tagged = metadata.clone().tag(2, 101.5);
+        assert_eq!(Some("hedger".to_string()), tagged.metadata);
+        assert_eq!(101.5, tagged.event);
+        assert_eq!(None, metadata.tag(3, ()).metadata);
+        {
+            let _context = request_context::enter(RequestContext::new(2, "req_mkt_data", None));
+            assert_eq!(Some("hedger".to_string()), metadata.current());
+        }
+        assert!(metadata.current().is_none());
+
+        client.cancel_mkt_data(1)?;
+        assert!(!metadata.contains(1));
+        assert_eq!(Some("hedger".to_string()), metadata.get(2));
+        client.shutdown()?;
+        assert!(metadata.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors_end_requests() -> Result<(), IBKRApiLibError> {
+        let (_sender, receiver) = message_queue(QueueConfig::default());
+        let mut decoder = Decoder::new(
+            Arc::new(Mutex::new(DefaultWrapper::new())),
+            receiver,
+            MAX_CLIENT_VER,
+            Arc::new(Mutex::new(ConnStatus::CONNECTED)),
+        );
+        let metadata: RequestMetadata<String> = RequestMetadata::new();
+        decoder
+            .pending
+            .metadata
+            .lock()
+            .unwrap()
+            .push(Box::new(metadata.clone()));
+        for req_id in 1..=2 {
+            let context = RequestContext::new(req_id, "req_mkt_data", Some(&simple_future()));
+            decoder
+                .pending
+                .request_contexts
+                .lock()
+                .unwrap()
+                .record(context);
+            metadata.attach(req_id, "momentum".to_string());
+        }
+
+        let fields = |req_id: &str, code: &str| -> Vec<String> {
+            ["4", "2", req_id, code, "message"]
+                .iter()
+                .map(|field| field.to_string())
+                .collect()
+        };
+        // delayed market data is displayed instead, the request goes on
+        decoder.interpret(&fields("1", "10167"))?;
+        decoder.interpret(&fields("2", "200"))?;
+
+        let contexts = decoder.pending.request_contexts.lock().unwrap();
+        assert!(contexts.get(1).is_some());
+        assert!(contexts.get(2).is_none());
+        assert_eq!(Some("momentum".to_string()), metadata.get(1));
+        assert!(!metadata.contains(2));
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::core::common::{SecType, TickType, UNSET_DOUBLE};
+    use crate::core::contract::Contract;
+    use crate::core::portfolio::{ClosingOrderConfig, Portfolio};
+    use crate::core::risk::{Greek, GreekLimits, Greeks, RiskAggregator, RiskScope};
+
+    fn contract(con_id: i32, symbol: &str, sec_type: &str, multiplier: &str) -> Contract {
+        Contract {
+            con_id,
+            symbol: symbol.to_string(),
+            sec_type: SecType::from(sec_type),
+            multiplier: multiplier.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_risk_aggregation() {
+        let mut portfolio = Portfolio::new(ClosingOrderConfig::default());
+        portfolio.on_position("DU1", contract(1, "SPY", "STK", ""), -50.0, 400.0);
+        portfolio.on_position("DU1", contract(2, "SPY", "OPT", "100"), 2.0, 5.0);
+        portfolio.on_position("DU1", contract(3, "QQQ", "OPT", "100"), -1.0, 3.0);
+        portfolio.on_position("DU1", contract(4, "QQQ", "OPT", "100"), 1.0, 3.0);
+
+        let mut risk = RiskAggregator::new();
+        risk.track(10, 2);
+        risk.track(11, 3);
+        risk.on_option_computation(10, TickType::ModelOption, 0.4, 0.02, 0.3, -0.05);
+        // live delta wins, the greeks it lacks come from the model
+        risk.on_option_computation(
+            10,
+            TickType::LastOptionComputation,
+            0.5,
+            UNSET_DOUBLE,
+            UNSET_DOUBLE,
+            UNSET_DOUBLE,
+        );
+        risk.on_option_computation(11, TickType::DelayedModelOption, -0.3, 0.01, 0.2, -0.1);
+        assert_eq!(Some(Greeks::new(0.5, 0.02, 0.3, -0.05)), risk.greeks(2));
+
+        let report = risk.report(&portfolio);
+        assert_eq!(vec![4], report.without_greeks);
+        let spy = report.by_underlying["SPY"];
+        assert!((spy.delta - 50.0).abs() < 1e-9);
+        assert!((spy.gamma - 4.0).abs() < 1e-9);
+        let qqq = report.by_underlying["QQQ"];
+        assert!((qqq.delta - 30.0).abs() < 1e-9);
+        assert!((report.total.delta - 80.0).abs() < 1e-9);
+        assert!((report.total.theta + 0.0).abs() < 1e-9);
+
+        risk.set_limits(
+            GreekLimits::new(Some(75.0), None, None, None),
+            GreekLimits::new(None, Some(3.0), None, None),
+        );
+        let breaches = risk.check(&portfolio);
+        assert_eq!(2, breaches.len());
+        assert_eq!(
+            (RiskScope::Total, Greek::Delta),
+            (breaches[0].scope.clone(), breaches[0].greek)
+        );
+        assert_eq!(
+            (RiskScope::Underlying("SPY".to_string()), Greek::Gamma),
+            (breaches[1].scope.clone(), breaches[1].greek)
+        );
+        // reported once while it lasts
+        assert!(risk.check(&portfolio).is_empty());
+
+        portfolio.on_position("DU1", contract(1, "SPY", "STK", ""), -60.0, 400.0);
+        assert!(risk.check(&portfolio).is_empty());
+        portfolio.on_position("DU1", contract(1, "SPY", "STK", ""), -40.0, 400.0);
+        assert_eq!(1, risk.check(&portfolio).len());
+    }
+}
@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use chrono::NaiveDate;
+
+    use crate::core::client::EClient;
+    use crate::core::common::SecType;
+    use crate::core::contract::{Contract, ContractDetails};
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::messages::OutgoingMessageIds;
+    use crate::core::roll_calendar::{RollCalendar, RollRule};
+    use crate::core::server_versions::MAX_CLIENT_VER;
+    use crate::core::testing::MockTws;
+    use crate::examples::defaults::DefaultWrapper;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn month(con_id: i32, local_symbol: &str, last_trade_date: &str) -> ContractDetails {
+        ContractDetails {
+            contract: Contract {
+                con_id,
+                local_symbol: local_symbol.to_string(),
+                last_trade_date_or_contract_month: last_trade_date.to_string(),
+                ..Contract::future("ES", "", "CME")
+            },
+            ..Default::default()
+        }
+    }
+
+    fn chain() -> Vec<ContractDetails> {
+        vec![
+            month(3, "ESU5", "20250919"),
+            month(1, "ESH5", "20250321"),
+            month(2, "ESM5", "20250620"),
+        ]
+    }
+
+    #[test]
+    fn test_roll_by_days_to_expiry() {
+        let mut calendar = RollCalendar::new(RollRule::DaysToExpiry(7));
+        let notices = calendar.subscribe();
+        calendar.set_chain(chain());
+        assert_eq!(
+            vec![1, 2, 3],
+            calendar
+                .chain()
+                .iter()
+                .map(|detail| detail.contract.con_id)
+                .collect::<Vec<i32>>()
+        );
+
+        let first = calendar.update(date(2025, 3, 1)).unwrap();
+        assert!(first.from.is_none());
+        assert_eq!("ESH5", first.to.local_symbol);
+        assert!(calendar.update(date(2025, 3, 10)).is_none());
+
+        let roll = calendar.update(date(2025, 3, 14)).unwrap();
+        assert_eq!(
+            (Some("ESH5"), "ESM5"),
+            (
+                roll.from.as_ref().map(|from| from.local_symbol.as_str()),
+                roll.to.local_symbol.as_str()
+            )
+        );
+        assert_eq!(2, calendar.active().unwrap().con_id);
+        assert_eq!(2, notices.try_iter().count());
+
+        // the last month is kept until it expires
+        assert_eq!(
+            3,
+            calendar.select(date(2025, 9, 18)).unwrap().contract.con_id
+        );
+        assert!(calendar.select(date(2025, 9, 20)).is_none());
+    }
+
+    #[test]
+    fn test_roll_by_volume() {
+        let mut calendar = RollCalendar::new(RollRule::Volume);
+        calendar.set_chain(chain());
+        calendar.set_volume(1, 900_000.0);
+        calendar.set_volume(2, 100_000.0);
+        assert_eq!(1, calendar.update(date(2025, 3, 10)).unwrap().to.con_id);
+
+        calendar.set_volume(2, 1_200_000.0);
+        assert_eq!(2, calendar.update(date(2025, 3, 12)).unwrap().to.con_id);
+
+        // never rolls back to an earlier month
+        calendar.set_volume(1, 5_000_000.0);
+        assert!(calendar.update(date(2025, 3, 13)).is_none());
+
+        // rolls off the active month once it expires, without volume for the next one
+        calendar.set_volume(3, 0.0);
+        assert_eq!(3, calendar.update(date(2025, 6, 21)).unwrap().to.con_id);
+    }
+
+    #[test]
+    fn test_roll_calendar_fetch() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        mock.respond_with(
+            OutgoingMessageIds::ReqContractData,
+            Box::new(|request| {
+                let req_id = request[2].as_str();
+                let end = vec!["52".to_string(), "1".to_string(), req_id.to_string()];
+                match request[5].as_str() {
+                    "FUT" => vec![end],
+                    sec_type => vec![vec![
+                        "4".to_string(),
+                        "2".to_string(),
+                        req_id.to_string(),
+                        "321".to_string(),
+                        format!("unexpected sec type {}", sec_type),
+                    ]],
+                }
+            }),
+        );
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let root = Contract {
+            sec_type: SecType::CONTFUT,
+            ..Contract::future("ES", "202506", "CME")
+        };
+        // TWS knows no month of the root
+        assert!(RollCalendar::fetch(&mut client, 10, &root, RollRule::Volume, TIMEOUT).is_err());
+        let request = mock.expect_message(OutgoingMessageIds::ReqContractData, TIMEOUT)?;
+        assert_eq!(
+            ("ES", "FUT", ""),
+            (
+                request[4].as_str(),
+                request[5].as_str(),
+                request[6].as_str()
+            )
+        );
+        client.disconnect()
+    }
+}
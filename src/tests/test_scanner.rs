@@ -0,0 +1,198 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use crate::core::client::EClient;
+    use crate::core::common::UNSET_DOUBLE;
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::messages::OutgoingMessageIds;
+    use crate::core::scanner::{
+        ScanCode, ScanInstrument, ScanLocation, ScannerFilter, ScannerParameterList,
+        ScannerParameters, ScannerSubscriptionBuilder,
+    };
+    use crate::core::server_versions::MAX_CLIENT_VER;
+    use crate::core::testing::MockTws;
+    use crate::examples::defaults::DefaultWrapper;
+
+    fn builder() -> ScannerSubscriptionBuilder {
+        ScannerSubscriptionBuilder::new(
+            ScanInstrument::UsStocks,
+            ScanLocation::UsMajor,
+            ScanCode::TopPercGain,
+        )
+        .rows(20)
+        .filter(ScannerFilter::PriceAbove(5.0))
+        .filter(ScannerFilter::MarketCapAbove(1000.0))
+    }
+
+    fn invalid_code(result: Result<impl std::fmt::Debug, IBKRApiLibError>) -> String {
+        match result {
+            Err(IBKRApiLibError::ApiError(err)) => err.code,
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_scanner_builder() -> Result<(), IBKRApiLibError> {
+        let request = builder().build()?;
+        assert_eq!(
+            ("STK", "STK.US.MAJOR", "TOP_PERC_GAIN", 20),
+            (
+                request.subscription.instrument.as_str(),
+                request.subscription.location_code.as_str(),
+                request.subscription.scan_code.as_str(),
+                request.subscription.number_of_rows
+            )
+        );
+        assert_eq!(UNSET_DOUBLE, request.subscription.above_price);
+        let filters: Vec<String> = request
+            .filter_options
+            .iter()
+            .map(|tag_value| tag_value.to_string())
+            .collect();
+        assert_eq!(vec!["priceAbove=5;", "marketCapAbove1e6=1000;"], filters);
+
+        assert_eq!("542", invalid_code(builder().rows(51).build()));
+        assert_eq!(
+            "542",
+            invalid_code(builder().filter(ScannerFilter::PriceAbove(6.0)).build())
+        );
+        assert_eq!(
+            "542",
+            invalid_code(
+                builder()
+                    .filter(ScannerFilter::ChangePercBelow(f64::NAN))
+                    .build()
+            )
+        );
+
+        let mut parameters = ScannerParameterList::default();
+        parameters.instruments.insert("STK".to_string());
+        parameters.location_codes.insert("STK.US.MAJOR".to_string());
+        parameters.scan_codes.insert("TOP_PERC_GAIN".to_string());
+        parameters.filters.insert("priceAbove".to_string());
+        assert_eq!("542", invalid_code(builder().build_validated(&parameters)));
+        parameters.filters.insert("marketCapAbove1e6".to_string());
+        builder().build_validated(&parameters)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_scanner_request_sent() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        let request = builder().build()?;
+        client.req_scanner_subscription(7, request.subscription, vec![], request.filter_options)?;
+        let sent = mock.expect_message(
+            OutgoingMessageIds::ReqScannerSubscription,
+            Duration::from_secs(5),
+        )?;
+        assert_eq!(
+            ("7", "20", "STK"),
+            (sent[1].as_str(), sent[2].as_str(), sent[3].as_str())
+        );
+        // unset filters are sent empty
+        assert_eq!("", sent[6]);
+        assert_eq!("priceAbove=5;marketCapAbove1e6=1000;", sent[sent.len() - 2]);
+        Ok(())
+    }
+
+    const SCANNER_PARAMETERS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ScanParameterResponse>
+  <InstrumentList varName="instrumentList">
+    <Instrument>
+      <name>US Stocks</name>
+      <type>STK</type>
+      <filters>PRICE,MKTCAP</filters>
+      <group>STK.GLOBAL</group>
+      <shortName>US</shortName>
+    </Instrument>
+    <Instrument>
+      <name>US Futures</name>
+      <type>FUT.US</type>
+      <filters>PRICE</filters>
+    </Instrument>
+  </InstrumentList>
+  <LocationTree varName="locationTree">
+    <Location>
+      <displayName>US Stocks</displayName>
+      <locationCode>STK.US</locationCode>
+      <instruments>STK</instruments>
+      <LocationTree varName="locationTree">
+        <Location>
+          <displayName>Listed/NASDAQ</displayName>
+          <locationCode>STK.US.MAJOR</locationCode>
+          <instruments>STK</instruments>
+        </Location>
+      </LocationTree>
+    </Location>
+  </LocationTree>
+  <ScanTypeList varName="scanTypeList">
+    <ScanType>
+      <displayName>Top % Gainers</displayName>
+      <scanCode>TOP_PERC_GAIN</scanCode>
+      <instruments>STK,FUT.US</instruments>
+    </ScanType>
+    <ScanType>
+      <displayName>Hot by Volume</displayName>
+      <scanCode>HOT_BY_VOLUME</scanCode>
+      <instruments>STK</instruments>
+    </ScanType>
+  </ScanTypeList>
+  <FilterList varName="filterList">
+    <RangeFilter>
+      <id>PRICE</id>
+      <category>Price</category>
+      <AbstractField type="scanner.filter.DoubleField">
+        <code>priceAbove</code>
+        <displayName>Price Above</displayName>
+      </AbstractField>
+      <AbstractField type="scanner.filter.DoubleField">
+        <code>priceBelow</code>
+        <displayName>Price Below</displayName>
+      </AbstractField>
+    </RangeFilter>
+    <SimpleFilter>
+      <id>MKTCAP</id>
+      <AbstractField type="scanner.filter.DoubleField">
+        <code>marketCapAbove1e6</code>
+      </AbstractField>
+    </SimpleFilter>
+  </FilterList>
+</ScanParameterResponse>
+"#;
+
+    #[test]
+    fn test_scanner_parameters_from_xml() -> Result<(), IBKRApiLibError> {
+        let parameters = ScannerParameters::from_xml(SCANNER_PARAMETERS)?;
+        assert_eq!(
+            vec!["PRICE", "MKTCAP"],
+            parameters.instrument("STK").unwrap().filters
+        );
+        assert_eq!(2, parameters.locations.len());
+        assert_eq!("STK.US", parameters.locations[1].parent);
+        assert_eq!(2, parameters.scan_types_for("STK").len());
+        assert_eq!(1, parameters.scan_types_for("FUT.US").len());
+        assert!(parameters.locations_for("FUT.US").is_empty());
+
+        let filters = parameters.filters_for("FUT.US");
+        assert_eq!(1, filters.len());
+        assert_eq!("RangeFilter", filters[0].kind);
+        assert_eq!("priceBelow", filters[0].fields[1].code);
+        assert_eq!(
+            "scanner.filter.DoubleField",
+            filters[0].fields[1].field_type
+        );
+
+        builder().build_validated(&parameters.parameter_list())?;
+        assert_eq!(
+            "508",
+            invalid_code(ScannerParameters::from_xml("<ListOfGroups/>"))
+        );
+        Ok(())
+    }
+}
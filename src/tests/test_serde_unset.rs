@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod tests {
+
+    use serde::de::value::{Error, UnitDeserializer};
+
+    use crate::core::common::{UNSET_DOUBLE, UNSET_INTEGER};
+    use crate::core::serde_unset::{unset_double, unset_integer};
+
+    #[test]
+    fn test_missing_values_deserialize_to_unset() -> Result<(), Error> {
+        assert_eq!(
+            UNSET_DOUBLE,
+            unset_double::deserialize(UnitDeserializer::<Error>::new())?
+        );
+        assert_eq!(
+            UNSET_INTEGER,
+            unset_integer::deserialize(UnitDeserializer::<Error>::new())?
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "serde-unset-none")]
+    mod domain {
+
+        use serde::de::DeserializeOwned;
+        use serde::Serialize;
+        use serde_json::Value;
+
+        use crate::core::common::{CommissionReport, WshEventData, UNSET_DOUBLE, UNSET_INTEGER};
+        use crate::core::contract::ContractDetails;
+        use crate::core::order::{Order, OrderState};
+        use crate::core::quote::Quote;
+        use crate::core::scanner::{
+            ScanCode, ScanInstrument, ScanLocation, ScannerSubscriptionBuilder,
+        };
+        use crate::core::what_if::MarginPreview;
+
+        /// The fields holding a sentinel, which are missing a serde_unset adapter
+        fn sentinels(value: &Value, path: &str, found: &mut Vec<String>) {
+            match value {
+                Value::Number(number)
+                    if number.as_f64() == Some(UNSET_DOUBLE)
+                        || number.as_i64() == Some(UNSET_INTEGER as i64) =>
+                {
+                    found.push(path.to_string());
+                }
+                Value::Array(values) => {
+                    for (i, value) in values.iter().enumerate() {
+                        sentinels(value, &format!("{}[{}]", path, i), found);
+                    }
+                }
+                Value::Object(fields) => {
+                    for (name, value) in fields.iter() {
+                        sentinels(value, &format!("{}.{}", path, name), found);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        /// Serializes value, checks no sentinel is written, and reads it back, also without
+        /// the fields written as null
+        fn assert_round_trip<T: Serialize + DeserializeOwned>(value: &T) {
+            let written = serde_json::to_value(value).unwrap();
+            let mut found = vec![];
+            sentinels(&written, "", &mut found);
+            assert!(found.is_empty(), "sentinels written for {:?}", found);
+
+            let read: T = serde_json::from_value(written.clone()).unwrap();
+            assert_eq!(written, serde_json::to_value(&read).unwrap());
+
+            let mut missing = written.clone();
+            if let Value::Object(fields) = &mut missing {
+                fields.retain(|_, value| !value.is_null());
+            }
+            let read: T = serde_json::from_value(missing).unwrap();
+            assert_eq!(written, serde_json::to_value(&read).unwrap());
+        }
+
+        #[test]
+        fn test_domain_structs_round_trip() {
+            let order = Order {
+                lmt_price: 101.5,
+                ..Default::default()
+            };
+            assert_round_trip(&order);
+            assert_eq!(
+                Value::Null,
+                serde_json::to_value(&order).unwrap()["aux_price"]
+            );
+            assert_round_trip(&OrderState::default());
+            assert_round_trip(&ContractDetails::default());
+            assert_round_trip(&CommissionReport::default());
+            assert_round_trip(&WshEventData::default());
+            assert_round_trip(&Quote::default());
+            assert_round_trip(&MarginPreview::default());
+            let scanner = ScannerSubscriptionBuilder::new(
+                ScanInstrument::UsStocks,
+                ScanLocation::UsMajor,
+                ScanCode::TopPercGain,
+            )
+            .build()
+            .unwrap();
+            assert_round_trip(&scanner.subscription);
+        }
+
+        #[test]
+        fn test_missing_fields_read_as_unset() {
+            let mut written = serde_json::to_value(Order::default()).unwrap();
+            if let Value::Object(fields) = &mut written {
+                fields.remove("lmt_price");
+                fields.remove("min_qty");
+            }
+            let order: Order = serde_json::from_value(written).unwrap();
+            assert_eq!(
+                (UNSET_DOUBLE, UNSET_INTEGER),
+                (order.lmt_price, order.min_qty)
+            );
+        }
+    }
+}
@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use crate::core::client::EClient;
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::messages::OutgoingMessageIds;
+    use crate::core::server_versions::MAX_CLIENT_VER;
+    use crate::core::subscriptions::Subscription;
+    use crate::core::testing::MockTws;
+    use crate::examples::contract_samples::simple_future;
+    use crate::examples::defaults::DefaultWrapper;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn test_shutdown_cancels_subscriptions() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+
+        client.req_mkt_data(1, &simple_future(), "", false, false, vec![])?;
+        client.req_mkt_data(2, &simple_future(), "", true, false, vec![])?;
+        client.req_positions()?;
+        client.req_account_updates(true, "DU1")?;
+        client.req_pnl(3, "DU1", "")?;
+        client.cancel_pnl(3)?;
+        assert_eq!(
+            vec![
+                Subscription::MktData(1),
+                Subscription::Positions,
+                Subscription::AccountUpdates("DU1".to_string())
+            ],
+            client.active_subscriptions()
+        );
+
+        client.shutdown()?;
+        assert!(!client.is_connected());
+        assert!(client.active_subscriptions().is_empty());
+        assert_eq!(
+            "1",
+            mock.expect_message(OutgoingMessageIds::CancelMktData, TIMEOUT)?[2]
+        );
+        mock.expect_message(OutgoingMessageIds::CancelPositions, TIMEOUT)?;
+        mock.expect_message(OutgoingMessageIds::ReqAcctData, TIMEOUT)?;
+        let unsubscribe = mock.expect_message(OutgoingMessageIds::ReqAcctData, TIMEOUT)?;
+        assert_eq!(
+            ("0", "DU1"),
+            (unsubscribe[2].as_str(), unsubscribe[3].as_str())
+        );
+
+        // nothing left to do
+        client.shutdown()
+    }
+}
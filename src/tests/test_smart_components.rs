@@ -0,0 +1,32 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::core::common::SmartComponent;
+    use crate::core::smart_components::{exchange_names, smart_component_map, SmartComponents};
+
+    #[test]
+    fn test_smart_components() {
+        let map = smart_component_map(&[
+            SmartComponent::new(0, "NYSE".to_string(), "N".to_string()),
+            SmartComponent::new(1, "ARCA".to_string(), "P".to_string()),
+            SmartComponent::new(2, "IEX".to_string(), "".to_string()),
+        ]);
+        assert_eq!(2, map.len());
+        assert_eq!(Some(&(1, "ARCA".to_string())), map.get(&'P'));
+        assert_eq!(vec!["NYSE", "ARCA", "Z"], exchange_names(&map, "NP Z"));
+
+        let mut components = SmartComponents::new();
+        components.set_bbo_exchange(3, "a6");
+        components.set_bbo_exchange(4, "");
+        assert_eq!(None, components.bbo_exchange(4));
+        assert_eq!(vec!["a6".to_string()], components.missing());
+        assert_eq!(None, components.exchange_names(3, "N"));
+
+        components.insert("a6", map);
+        assert!(components.missing().is_empty());
+        assert_eq!(
+            Some(vec!["ARCA".to_string()]),
+            components.exchange_names(3, "P")
+        );
+    }
+}
@@ -0,0 +1,247 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::mpsc::channel;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use bigdecimal::BigDecimal;
+
+    use crate::core::client::EClient;
+    use crate::core::common::{BarData, RealTimeBar};
+    use crate::core::contract::Contract;
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::execution::Execution;
+    use crate::core::messages::OutgoingMessageIds;
+    use crate::core::order::{Action, Order};
+    use crate::core::server_versions::MAX_CLIENT_VER;
+    use crate::core::strategy::{LiveContext, Runner, Strategy, StrategyContext, StrategyEvent};
+    use crate::core::testing::MockTws;
+    use crate::core::timers::{TimerId, Timers};
+    use crate::examples::contract_samples::simple_future;
+    use crate::examples::defaults::DefaultWrapper;
+    use crate::examples::order_samples::market_order;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    /// Buys once the close reaches 100, counts fills and timer events
+    #[derive(Default)]
+    struct Breakout {
+        feed_id: i32,
+        order_id: Option<i32>,
+        fills: usize,
+        timer_id: Option<TimerId>,
+        timers: Vec<Instant>,
+    }
+
+    impl Strategy for Breakout {
+        fn on_start(&mut self, context: &mut dyn StrategyContext) -> Result<(), IBKRApiLibError> {
+            self.feed_id = context.subscribe_bars(&simple_future())?;
+            let now = context.now();
+            self.timer_id = Some(context.timers().every(now, MINUTE));
+            Ok(())
+        }
+
+        fn on_bar(
+            &mut self,
+            context: &mut dyn StrategyContext,
+            feed_id: i32,
+            bar: &BarData,
+        ) -> Result<(), IBKRApiLibError> {
+            if feed_id == self.feed_id && bar.close >= 100.0 && self.order_id.is_none() {
+                self.order_id =
+                    Some(context.place_order(&simple_future(), &market_order("BUY", 1.0))?);
+            }
+            Ok(())
+        }
+
+        fn on_fill(
+            &mut self,
+            _context: &mut dyn StrategyContext,
+            _contract: &Contract,
+            _execution: &Execution,
+        ) -> Result<(), IBKRApiLibError> {
+            self.fills += 1;
+            Ok(())
+        }
+
+        fn on_timer(
+            &mut self,
+            _context: &mut dyn StrategyContext,
+            timer_id: TimerId,
+            time: Instant,
+        ) -> Result<(), IBKRApiLibError> {
+            assert_eq!(self.timer_id, Some(timer_id));
+            self.timers.push(time);
+            Ok(())
+        }
+    }
+
+    /// Context of a backtest, recording the orders
+    struct RecordingContext {
+        clock: Instant,
+        feeds: Vec<String>,
+        orders: Vec<(String, Order)>,
+        timers: Timers,
+    }
+
+    impl RecordingContext {
+        fn new(clock: Instant) -> Self {
+            RecordingContext {
+                clock,
+                feeds: vec![],
+                orders: vec![],
+                timers: Timers::new(),
+            }
+        }
+    }
+
+    impl StrategyContext for RecordingContext {
+        fn subscribe_bars(&mut self, contract: &Contract) -> Result<i32, IBKRApiLibError> {
+            self.feeds.push(contract.symbol.clone());
+            Ok(self.feeds.len() as i32)
+        }
+
+        fn subscribe_ticks(&mut self, contract: &Contract) -> Result<i32, IBKRApiLibError> {
+            self.subscribe_bars(contract)
+        }
+
+        fn place_order(
+            &mut self,
+            contract: &Contract,
+            order: &Order,
+        ) -> Result<i32, IBKRApiLibError> {
+            self.orders.push((contract.symbol.clone(), order.clone()));
+            Ok(self.orders.len() as i32)
+        }
+
+        fn cancel_order(&mut self, _order_id: i32) -> Result<(), IBKRApiLibError> {
+            Ok(())
+        }
+
+        fn timers(&mut self) -> &mut Timers {
+            &mut self.timers
+        }
+
+        fn now(&self) -> Instant {
+            self.clock
+        }
+    }
+
+    fn bar(feed_id: i32, close: f64) -> StrategyEvent {
+        StrategyEvent::from_realtime_bar(
+            feed_id,
+            &RealTimeBar::new(
+                "1600000000".to_string(),
+                close,
+                close,
+                close,
+                close,
+                BigDecimal::from(10),
+                close,
+                1,
+            ),
+        )
+    }
+
+    #[test]
+    fn test_runner_delivers_events() -> Result<(), IBKRApiLibError> {
+        let (tx, rx) = channel();
+        let feeder = thread::spawn(move || {
+            for close in [98.0, 101.0, 102.0].iter() {
+                tx.send(bar(1, *close)).unwrap();
+            }
+            tx.send(StrategyEvent::Fill {
+                contract: Box::new(simple_future()),
+                execution: Execution::default(),
+            })
+            .unwrap();
+        });
+
+        let start = Instant::now();
+        let mut runner = Runner::new(Breakout::default(), RecordingContext::new(start));
+        runner.run(&rx)?;
+        feeder.join().unwrap();
+        // the simulation clock advances two and a half minutes
+        runner.fire_timers(start + MINUTE * 5 / 2)?;
+
+        assert_eq!(vec![simple_future().symbol], runner.context().feeds);
+        assert_eq!(1, runner.context().orders.len());
+        assert_eq!(Action::Buy, runner.context().orders[0].1.action);
+        assert_eq!(
+            (Some(1), 1),
+            (runner.strategy().order_id, runner.strategy().fills)
+        );
+        assert_eq!(vec![start + MINUTE], runner.strategy().timers);
+        Ok(())
+    }
+
+    /// Records the order in which bars and timers reach it
+    #[derive(Default)]
+    struct Sequence {
+        events: Vec<&'static str>,
+    }
+
+    impl Strategy for Sequence {
+        fn on_start(&mut self, context: &mut dyn StrategyContext) -> Result<(), IBKRApiLibError> {
+            let now = context.now();
+            context.timers().once_at(now);
+            Ok(())
+        }
+
+        fn on_bar(
+            &mut self,
+            _context: &mut dyn StrategyContext,
+            _feed_id: i32,
+            _bar: &BarData,
+        ) -> Result<(), IBKRApiLibError> {
+            self.events.push("bar");
+            Ok(())
+        }
+
+        fn on_timer(
+            &mut self,
+            _context: &mut dyn StrategyContext,
+            _timer_id: TimerId,
+            _time: Instant,
+        ) -> Result<(), IBKRApiLibError> {
+            self.events.push("timer");
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_runner_fires_due_timers_before_events() -> Result<(), IBKRApiLibError> {
+        let (tx, rx) = channel();
+        tx.send(bar(1, 100.0)).unwrap();
+        tx.send(bar(1, 101.0)).unwrap();
+        drop(tx);
+
+        let mut runner = Runner::new(Sequence::default(), RecordingContext::new(Instant::now()));
+        runner.run(&rx)?;
+        assert_eq!(vec!["timer", "bar", "bar"], runner.strategy().events);
+        Ok(())
+    }
+
+    #[test]
+    fn test_live_context() -> Result<(), IBKRApiLibError> {
+        let mock = MockTws::start(MAX_CLIENT_VER)?;
+        let mut client = EClient::new(Arc::new(Mutex::new(DefaultWrapper::new())));
+        client.connect("127.0.0.1", mock.port(), 0)?;
+        let context = LiveContext::new(Arc::new(Mutex::new(client)), 7, 100);
+
+        let mut runner = Runner::new(Breakout::default(), context);
+        runner.start()?;
+        runner.handle(&bar(100, 100.0))?;
+        assert_eq!(Some(7), runner.strategy().order_id);
+
+        let bars = mock.expect_message(OutgoingMessageIds::ReqRealTimeBars, TIMEOUT)?;
+        assert_eq!("100", bars[2]);
+        let order = mock.expect_message(OutgoingMessageIds::PlaceOrder, TIMEOUT)?;
+        assert_eq!("7", order[1]);
+        Ok(())
+    }
+}
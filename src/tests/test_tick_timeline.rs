@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+
+    use bigdecimal::BigDecimal;
+
+    use crate::core::common::{
+        HistoricalTickBidAsk, HistoricalTickLast, TickAttribBidAsk, TickAttribLast,
+    };
+    use crate::core::tick_timeline::{merge_ticks, TimelineEvent};
+    use crate::core::timestamps::timestamp_from_seconds;
+
+    #[test]
+    fn test_merge_ticks() {
+        let quote = |time: i64, bid: f64| {
+            HistoricalTickBidAsk::new(
+                timestamp_from_seconds(time),
+                TickAttribBidAsk::new(false, false),
+                bid,
+                bid + 0.01,
+                BigDecimal::from(1),
+                BigDecimal::from(1),
+            )
+        };
+        let trade = |time: i64, price: f64| {
+            HistoricalTickLast::new(
+                timestamp_from_seconds(time),
+                TickAttribLast::new(false, false),
+                price,
+                BigDecimal::from(100),
+                "ARCA".to_string(),
+                "".to_string(),
+            )
+        };
+        let timeline = merge_ticks(
+            vec![quote(1, 10.0), quote(2, 10.1), quote(2, 10.2)],
+            vec![trade(2, 10.15), trade(2, 10.2), trade(3, 10.3)],
+        );
+
+        let described: Vec<String> = timeline
+            .iter()
+            .map(|event| match event {
+                TimelineEvent::Quote(quote) => format!("Q{}@{}", event.time(), quote.price_bid),
+                TimelineEvent::Trade(trade) => format!("T{}@{}", event.time(), trade.price),
+            })
+            .collect();
+        assert_eq!(
+            vec!["Q1@10", "Q2@10.1", "Q2@10.2", "T2@10.15", "T2@10.2", "T3@10.3"],
+            described
+        );
+    }
+}
@@ -0,0 +1,201 @@
+#[cfg(test)]
+mod tests {
+
+    use std::sync::{Arc, Mutex};
+
+    use chrono::{LocalResult, NaiveDate, TimeZone, Utc};
+
+    use crate::core::client::ConnStatus;
+    use crate::core::common::RealTimeBar;
+    use crate::core::decoder::Decoder;
+    use crate::core::errors::IBKRApiLibError;
+    use crate::core::message_queue::{message_queue, QueueConfig};
+    use crate::core::pending::PendingBarTimeZone;
+    use crate::core::time_zones::ExchangeTz;
+    use crate::core::timestamps::parse_timestamp_in;
+    use crate::examples::defaults::DefaultWrapper;
+
+    #[test]
+    fn test_from_id() {
+        let eastern = ExchangeTz::from_id("America/New_York").unwrap();
+        assert_eq!(
+            Some(eastern),
+            ExchangeTz::from_id("EST (Eastern Standard Time)")
+        );
+        assert_eq!(
+            "US/Eastern",
+            ExchangeTz::from_id("US/Eastern").unwrap().id()
+        );
+        assert_eq!(
+            "Europe/Berlin",
+            ExchangeTz::from_id("Europe/Berlin").unwrap().id()
+        );
+        assert_eq!(None, ExchangeTz::from_id("Mars/Olympus_Mons"));
+    }
+
+    #[test]
+    fn test_daylight_saving() {
+        let eastern = ExchangeTz::from_id("US/Eastern").unwrap();
+        let local = |day, hour, minute| {
+            NaiveDate::from_ymd_opt(2021, 3, day)
+                .and_then(|date| date.and_hms_opt(hour, minute, 0))
+                .unwrap()
+        };
+        let utc_hour = |time: chrono::DateTime<ExchangeTz>| {
+            time.with_timezone(&Utc).format("%H:%M").to_string()
+        };
+
+        // spring forward on March 14th: 02:30 does not exist
+        assert_eq!(
+            "06:30",
+            utc_hour(eastern.from_local_datetime(&local(13, 1, 30)).unwrap())
+        );
+        assert_eq!(
+            LocalResult::None,
+            eastern.from_local_datetime(&local(14, 2, 30))
+        );
+        assert_eq!(
+            "07:30",
+            utc_hour(eastern.from_local_datetime(&local(14, 3, 30)).unwrap())
+        );
+
+        // fall back on November 7th: 01:30 happens twice
+        let repeated = NaiveDate::from_ymd_opt(2021, 11, 7)
+            .and_then(|date| date.and_hms_opt(1, 30, 0))
+            .unwrap();
+        match eastern.from_local_datetime(&repeated) {
+            LocalResult::Ambiguous(earlier, later) => {
+                assert_eq!("05:30", utc_hour(earlier));
+                assert_eq!("06:30", utc_hour(later));
+            }
+            other => panic!("expected an ambiguous time, got {:?}", other),
+        }
+
+        // before 2007 daylight saving in the US started on the first Sunday of April
+        let before_2007 = |month, day| {
+            NaiveDate::from_ymd_opt(2006, month, day)
+                .and_then(|date| date.and_hms_opt(12, 0, 0))
+                .unwrap()
+        };
+        assert!(!eastern.is_dst(&before_2007(3, 20)));
+        assert!(eastern.is_dst(&before_2007(4, 3)));
+
+        let london = ExchangeTz::from_id("Europe/London").unwrap();
+        let northern_summer = Utc.with_ymd_and_hms(2021, 7, 1, 12, 0, 0).unwrap();
+        assert_eq!(
+            "13:00",
+            northern_summer
+                .with_timezone(&london)
+                .format("%H:%M")
+                .to_string()
+        );
+        let sydney = ExchangeTz::from_id("Australia/Sydney").unwrap();
+        assert_eq!(
+            "22:00",
+            northern_summer
+                .with_timezone(&sydney)
+                .format("%H:%M")
+                .to_string()
+        );
+        let southern_summer = Utc.with_ymd_and_hms(2021, 1, 1, 12, 0, 0).unwrap();
+        assert_eq!(
+            "23:00",
+            southern_summer
+                .with_timezone(&sydney)
+                .format("%H:%M")
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_timestamp_in() {
+        let chicago = ExchangeTz::from_id("US/Central").unwrap();
+        // 09:30 Chicago is 14:30 UTC in summer and 15:30 UTC in winter
+        assert_eq!(
+            Utc.with_ymd_and_hms(2020, 7, 1, 14, 30, 0).single(),
+            parse_timestamp_in("20200701  09:30:00", chicago, None)
+        );
+        assert_eq!(
+            Utc.with_ymd_and_hms(2020, 12, 1, 15, 30, 0).single(),
+            parse_timestamp_in("20201201  09:30:00", chicago, None)
+        );
+        // an explicit zone wins over the default one
+        assert_eq!(
+            Utc.with_ymd_and_hms(2020, 7, 1, 13, 30, 0).single(),
+            parse_timestamp_in("20200701 09:30:00 US/Eastern", chicago, None)
+        );
+        // a zone the time zone database does not know keeps the date, read in the default zone
+        assert_eq!(
+            Utc.with_ymd_and_hms(2020, 7, 1, 14, 30, 0).single(),
+            parse_timestamp_in("20200701 09:30:00 Mars/Olympus_Mons", chicago, None)
+        );
+        // epoch seconds are not affected
+        assert_eq!(
+            Utc.timestamp_opt(1593613800, 0).single(),
+            parse_timestamp_in("1593613800", chicago, None)
+        );
+
+        let eastern = ExchangeTz::from_id("US/Eastern").unwrap();
+        let second_pass = Utc.with_ymd_and_hms(2020, 11, 1, 5, 30, 0).single();
+        assert_eq!(
+            Utc.with_ymd_and_hms(2020, 11, 1, 6, 0, 0).single(),
+            parse_timestamp_in("20201101 01:00:00", eastern, second_pass)
+        );
+
+        let bar = RealTimeBar {
+            date_time: "1593613800".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            "2020-07-01 10:30:00 EDT",
+            bar.time_in(eastern).unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn test_bar_time_zone_ends_with_historical_data() -> Result<(), IBKRApiLibError> {
+        let (_sender, receiver) = message_queue(QueueConfig::default());
+        let mut decoder = Decoder::new(
+            Arc::new(Mutex::new(DefaultWrapper::new())),
+            receiver,
+            151,
+            Arc::new(Mutex::new(ConnStatus::CONNECTED)),
+        );
+        let eastern = ExchangeTz::from_id("US/Eastern").unwrap();
+        for (req_id, keep_up_to_date) in [(1, false), (2, true)] {
+            decoder.pending.bar_time_zones.lock().unwrap().insert(
+                req_id,
+                PendingBarTimeZone {
+                    zone: eastern,
+                    last: None,
+                    keep_up_to_date,
+                },
+            );
+            let bars = [
+                "17",
+                "",
+                "",
+                "",
+                "1",
+                "20200701 09:30:00",
+                "1",
+                "1",
+                "1",
+                "1",
+                "1",
+                "1",
+                "1",
+            ];
+            let req_id = req_id.to_string();
+            let mut fields: Vec<String> = bars.iter().map(|field| field.to_string()).collect();
+            fields[1] = req_id;
+            decoder.interpret(&fields)?;
+        }
+
+        // the updates of keep_up_to_date are still read in the zone
+        let zones = decoder.pending.bar_time_zones.lock().unwrap();
+        assert!(!zones.contains_key(&1));
+        assert!(zones.contains_key(&2));
+        Ok(())
+    }
+}
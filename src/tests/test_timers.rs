@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+
+    use std::time::{Duration, Instant};
+
+    use crate::core::timers::Timers;
+
+    #[test]
+    fn test_timers() {
+        let start = Instant::now();
+        let second = Duration::from_secs(1);
+        let mut timers = Timers::new();
+        let every = timers.every(start, second * 10);
+        let once = timers.once_after(start, second * 15);
+        let cancelled = timers.once_at(start + second * 5);
+        assert!(timers.cancel(cancelled));
+        assert_eq!(Some(start + second * 10), timers.next_deadline());
+
+        assert!(timers.due(start + second * 9).is_empty());
+        assert_eq!(
+            vec![(every, start + second * 10)],
+            timers.due(start + second * 10)
+        );
+        // the recurring timer fires once for the two intervals passed
+        assert_eq!(
+            vec![(once, start + second * 15), (every, start + second * 20)],
+            timers.due(start + second * 31)
+        );
+        assert_eq!(Some(start + second * 40), timers.next_deadline());
+        assert!(!timers.cancel(once));
+        assert!(timers.cancel(every));
+        assert!(timers.is_empty());
+    }
+
+    #[test]
+    fn test_timers_after_clock_jump() {
+        let start = Instant::now();
+        let mut timers = Timers::new();
+        let every = timers.every(start, Duration::from_millis(1));
+        // a backtest clock jumping a year fires the timer once and schedules it after the jump
+        let year = Duration::from_secs(365 * 24 * 60 * 60);
+        assert_eq!(
+            vec![(every, start + Duration::from_millis(1))],
+            timers.due(start + year)
+        );
+        assert_eq!(
+            Some(start + year + Duration::from_millis(1)),
+            timers.next_deadline()
+        );
+    }
+}
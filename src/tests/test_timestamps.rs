@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+
+    use chrono::{Local, TimeZone, Utc};
+
+    use crate::core::timestamps::{
+        is_set, parse_timestamp, timestamp_from_seconds, timestamp_from_wire, timestamp_seconds,
+        timestamp_to_wire,
+    };
+
+    #[test]
+    fn test_parse_timestamp() {
+        let time = Utc.with_ymd_and_hms(2020, 9, 1, 10, 5, 0).unwrap();
+        assert_eq!(Some(time), parse_timestamp("1598954700"));
+        assert_eq!(Some(time), parse_timestamp("1598954700000"));
+        assert_eq!(Some(time), parse_timestamp("20200901  10:05:00 UTC"));
+        assert_eq!(Some(time), parse_timestamp("20200901-10:05:00"));
+
+        // times without a time zone are in the time zone TWS runs in
+        let local = Local
+            .with_ymd_and_hms(2020, 9, 1, 10, 5, 0)
+            .single()
+            .map(|time| time.with_timezone(&Utc));
+        assert_eq!(local, parse_timestamp("20200901 10:05:00"));
+        assert_eq!(local, parse_timestamp("2020-09-01 10:05:00.0"));
+        // dates are read at UTC midnight, so their day does not depend on the local time zone
+        assert_eq!(
+            Utc.with_ymd_and_hms(2020, 9, 1, 0, 0, 0).single(),
+            parse_timestamp("20200901")
+        );
+
+        assert_eq!(Some(time), parse_timestamp("20200901 06:05:00 US/Eastern"));
+
+        assert_eq!(None, parse_timestamp(""));
+        // an unknown zone keeps the time, read in local time
+        assert_eq!(
+            local,
+            parse_timestamp("20200901 10:05:00 Mars/Olympus_Mons")
+        );
+        assert_eq!(None, parse_timestamp("yesterday"));
+    }
+
+    #[test]
+    fn test_timestamp_wire() {
+        let timestamp = timestamp_from_wire("20200901-10:05:00");
+        assert!(is_set(&timestamp));
+        assert_eq!(Some(1598954700), timestamp_seconds(&timestamp));
+        assert_eq!("20200901-10:05:00", timestamp_to_wire(&timestamp));
+        assert_eq!(
+            timestamp_seconds(&timestamp_from_seconds(1598954700)),
+            Some(1598954700)
+        );
+
+        let unset = timestamp_from_wire("");
+        assert!(!is_set(&unset));
+        assert_eq!("", timestamp_to_wire(&unset));
+    }
+}
@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::core::common::UNSET_DOUBLE;
+    use crate::core::order::OrderState;
+    use crate::core::what_if::{MarginPreview, WhatIfContribution, WhatIfScenario};
+    use crate::examples::contract_samples::simple_future;
+    use crate::examples::order_samples::limit_order;
+
+    #[test]
+    fn test_margin_preview_from_order_state() {
+        let order_state = OrderState {
+            init_margin_before: "1000.5".to_string(),
+            init_margin_change: "250".to_string(),
+            init_margin_after: "1.7976931348623157E308".to_string(),
+            maint_margin_after: "".to_string(),
+            commission: 1.25,
+            commission_currency: "USD".to_string(),
+            ..Default::default()
+        };
+
+        let preview = MarginPreview::from_order_state(7, &order_state);
+
+        assert_eq!(7, preview.order_id);
+        assert_eq!(1000.5, preview.init_margin_before);
+        assert_eq!(250.0, preview.init_margin_change);
+        assert_eq!(UNSET_DOUBLE, preview.init_margin_after);
+        assert_eq!(UNSET_DOUBLE, preview.maint_margin_after);
+        assert_eq!(1.25, preview.commission);
+        assert_eq!("USD", preview.commission_currency);
+    }
+
+    #[test]
+    fn test_what_if_scenario_totals() {
+        let preview = |order_id, init_change, equity_before| MarginPreview {
+            order_id,
+            init_margin_before: 40_000.0,
+            init_margin_change: init_change,
+            equity_with_loan_before: equity_before,
+            equity_with_loan_change: -2.5,
+            maint_margin_change: UNSET_DOUBLE,
+            commission: 2.5,
+            ..Default::default()
+        };
+        let contract = simple_future();
+        let order = limit_order("BUY", 2.0, 4000.0);
+        let mut scenario = WhatIfScenario::new(vec![
+            WhatIfContribution::new(1, &contract, &order, Some(preview(1, 6_000.0, 50_000.0))),
+            WhatIfContribution::new(2, &contract, &order, Some(preview(2, 3_000.0, 50_000.0))),
+        ]);
+
+        assert_eq!(9_000.0, scenario.init_margin_change());
+        assert_eq!(0.0, scenario.maint_margin_change());
+        assert_eq!(5.0, scenario.commission());
+        assert_eq!(Some(49_000.0), scenario.init_margin_after());
+        assert_eq!(Some(49_995.0), scenario.equity_with_loan_after());
+        assert!(scenario.is_feasible());
+
+        scenario
+            .contributions
+            .push(WhatIfContribution::new(3, &contract, &order, None));
+        assert_eq!(vec![3], scenario.unpreviewed());
+        assert!(!scenario.is_feasible());
+
+        scenario.contributions[2].preview = Some(preview(3, 1_000.0, 50_000.0));
+        assert_eq!(Some(50_000.0), scenario.init_margin_after());
+        assert!(!scenario.is_feasible());
+    }
+}